@@ -0,0 +1,478 @@
+//! 命令列參數
+//!
+//! 未指定子命令時進入互動選單；指定子命令時以參數與設定檔直接執行對應功能，
+//! 不詢問路徑與選項，方便以排程或腳本執行
+
+use crate::component::video_renamer::SortKey;
+use crate::component::{
+    AutoMoveByType, ContactSheetGenerator, DuplicationChecker, Interaction, OrphanFileMover,
+    OutputFormat, PreflightFailed, RunStatus, VideoEncoder, VideoRenamer,
+};
+use crate::config::{Config, MAX_AV1_CRF, MatchMode, UserSettings};
+use crate::jobs;
+use crate::tools::{DependencyReport, TransferMode, check_dependencies, expand_home};
+use anyhow::{Result, anyhow};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rust_i18n::t;
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 影片整理工具
+#[derive(Debug, Parser)]
+#[command(
+    version,
+    after_help = "結束代碼：0 完成、1 執行錯誤、2 部分檔案處理失敗、3 中斷、4 執行前檢查失敗"
+)]
+pub struct Cli {
+    /// 要執行的功能（省略時進入互動選單）
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// 將執行結果以 JSON 輸出到 stdout（取代彩色摘要）
+    #[arg(long, global = true, env = "AUTO_VIDEO_ORGANIZE_JSON")]
+    pub json: bool,
+    /// 將執行結果以 JSON 寫入檔案（取代彩色摘要）
+    #[arg(long, global = true, value_name = "PATH")]
+    pub json_file: Option<PathBuf>,
+}
+
+impl Cli {
+    /// 結果的輸出方式，命令列參數優先於設定檔的 `json_output`
+    #[must_use]
+    pub fn output_format(&self, settings: &UserSettings) -> OutputFormat {
+        if let Some(path) = &self.json_file {
+            return OutputFormat::JsonFile(path.clone());
+        }
+        if self.json {
+            return OutputFormat::Json;
+        }
+        match settings.json_output.as_deref() {
+            None => OutputFormat::Human,
+            Some("-") => OutputFormat::Json,
+            Some(path) => OutputFormat::JsonFile(expand_home(path)),
+        }
+    }
+}
+
+/// 各子命令共用的參數
+#[derive(Debug, Args)]
+pub struct CommonArgs {
+    /// 要處理的資料夾
+    #[arg(short, long)]
+    pub input: PathBuf,
+    /// 不詢問確認，直接執行（確認提示一律視為同意並記錄於日誌）
+    #[arg(short, long, alias = "no-confirm")]
+    pub yes: bool,
+}
+
+impl CommonArgs {
+    const fn interaction(&self) -> Interaction {
+        if self.yes {
+            Interaction::Unattended
+        } else {
+            Interaction::ConfirmOnly
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// 影片重新編碼
+    Encode {
+        #[command(flatten)]
+        common: CommonArgs,
+        /// 本次使用的 CRF（不寫回設定；範圍依設定的編碼器而定，x264/x265 為 0-51，SVT-AV1 為 0-63）
+        #[arg(long, value_parser = clap::value_parser!(u8).range(0..=i64::from(MAX_AV1_CRF)))]
+        crf: Option<u8>,
+    },
+    /// 檢查重複檔案（使用預設紀錄與設定的處理方式）
+    Dedup {
+        #[command(flatten)]
+        common: CommonArgs,
+        /// 只產生報告，不處理檔案也不更新紀錄
+        #[arg(long)]
+        dry_run: bool,
+        /// 本次另比對影片畫面，找出重新編碼過的相同影片（不寫回設定）
+        #[arg(long)]
+        perceptual: bool,
+    },
+    /// 生成影片預覽圖（使用設定的模式）
+    ContactSheet {
+        #[command(flatten)]
+        common: CommonArgs,
+        /// 本次使用的網格欄數（不寫回設定）
+        #[arg(long)]
+        cols: Option<usize>,
+        /// 本次使用的網格列數（不寫回設定）
+        #[arg(long)]
+        rows: Option<usize>,
+    },
+    /// 依檔案類型整理（使用設定的整理依據，分類資料夾建立於輸入資料夾）
+    AutoMove {
+        #[command(flatten)]
+        common: CommonArgs,
+        /// 複製而非移動
+        #[arg(long)]
+        copy: bool,
+        /// 只列出目標路徑，不移動檔案
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// 移動孤立檔案（使用設定的分組規則）
+    Orphan {
+        #[command(flatten)]
+        common: CommonArgs,
+        /// 只匯出孤立檔案清單，不移動檔案
+        #[arg(long)]
+        report: bool,
+    },
+    /// 影片排序重新命名（使用設定的檔名樣板）
+    Rename {
+        #[command(flatten)]
+        common: CommonArgs,
+        /// 排序方式
+        #[arg(long, value_enum, default_value_t = RenameSort::Duration)]
+        sort: RenameSort,
+        /// 起始編號
+        #[arg(long, default_value_t = 1)]
+        start_index: usize,
+        /// 只顯示預覽，不重新命名
+        #[arg(long)]
+        preview: bool,
+        /// 子資料夾中的影片依資料夾各自編號
+        #[arg(long)]
+        per_directory: bool,
+        /// 略過檔名已含 UUID（先前已重新命名）的影片
+        #[arg(long)]
+        skip_renamed: bool,
+    },
+    /// 依 TOML 工作檔依序執行多個功能，並彙整各步驟的結果
+    RunJobs {
+        /// 工作檔路徑
+        file: PathBuf,
+    },
+}
+
+/// 重新命名的排序方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RenameSort {
+    #[default]
+    Duration,
+    Size,
+    Resolution,
+    Created,
+    Modified,
+    Name,
+}
+
+impl From<RenameSort> for SortKey {
+    fn from(sort: RenameSort) -> Self {
+        match sort {
+            RenameSort::Duration => Self::Duration,
+            RenameSort::Size => Self::Size,
+            RenameSort::Resolution => Self::Resolution,
+            RenameSort::Created => Self::CreatedDate,
+            RenameSort::Modified => Self::ModifiedDate,
+            RenameSort::Name => Self::NaturalName,
+        }
+    }
+}
+
+/// 結束代碼：全部完成
+pub const EXIT_OK: i32 = 0;
+/// 結束代碼：執行途中發生錯誤而停止
+pub const EXIT_ERROR: i32 = 1;
+/// 結束代碼：執行完畢，但有檔案處理失敗
+pub const EXIT_PARTIAL_FAILURE: i32 = 2;
+/// 結束代碼：收到中斷訊號而提前停止
+pub const EXIT_ABORTED: i32 = 3;
+/// 結束代碼：執行前檢查失敗（缺少相依程式、輸入路徑不存在等）
+pub const EXIT_PREFLIGHT_FAILED: i32 = 4;
+
+/// 子命令執行結果對應的結束代碼
+#[must_use]
+pub fn exit_code(result: &Result<RunStatus>) -> i32 {
+    match result {
+        Ok(RunStatus::Completed) => EXIT_OK,
+        Ok(RunStatus::CompletedWithFailures) => EXIT_PARTIAL_FAILURE,
+        Ok(RunStatus::Aborted) => EXIT_ABORTED,
+        Err(e) if e.is::<PreflightFailed>() => EXIT_PREFLIGHT_FAILED,
+        Err(_) => EXIT_ERROR,
+    }
+}
+
+/// 執行子命令，參數只套用於本次執行，不寫回設定檔
+///
+/// 收到中斷訊號時，即使功能本身正常結束也回報為中斷
+pub fn run(
+    command: Command,
+    output: OutputFormat,
+    config: &Config,
+    shutdown_signal: &Arc<AtomicBool>,
+) -> Result<RunStatus> {
+    let status = run_command(command, output, config, shutdown_signal)?;
+    if shutdown_signal.load(Ordering::SeqCst) {
+        return Ok(RunStatus::Aborted);
+    }
+    Ok(status)
+}
+
+fn run_command(
+    command: Command,
+    output: OutputFormat,
+    config: &Config,
+    shutdown_signal: &Arc<AtomicBool>,
+) -> Result<RunStatus> {
+    let mut config = config.clone();
+    let deps = check_dependencies();
+
+    match command {
+        Command::Encode { common, crf } => {
+            ensure_available(deps.video_tools_ready(), deps)?;
+            if let Some(crf) = crf {
+                config
+                    .settings
+                    .video_encoder
+                    .validate_crf(crf)
+                    .map_err(|e| anyhow!(e).context(PreflightFailed))?;
+                config.settings.video_encoder.crf = Some(crf);
+            }
+            VideoEncoder::new(config, Arc::clone(shutdown_signal))
+                .with_interaction(common.interaction())
+                .with_output(output)
+                .run_in(&common.input)
+        }
+        Command::Dedup {
+            common,
+            dry_run,
+            perceptual,
+        } => {
+            let duplication = &mut config.settings.duplication;
+            if perceptual {
+                duplication.match_mode = MatchMode::Perceptual;
+            }
+            if duplication.match_mode == MatchMode::Perceptual {
+                ensure_available(deps.video_tools_ready(), deps)?;
+            }
+            DuplicationChecker::new(config, Arc::clone(shutdown_signal))
+                .with_interaction(common.interaction())
+                .with_output(output)
+                .with_dry_run(dry_run)
+                .run_in(&common.input)
+        }
+        Command::ContactSheet { common, cols, rows } => {
+            ensure_available(deps.contact_sheet_ready(), deps)?;
+            let contact_sheet = &mut config.settings.contact_sheet;
+            contact_sheet.grid_cols = cols.unwrap_or(contact_sheet.grid_cols);
+            contact_sheet.grid_rows = rows.unwrap_or(contact_sheet.grid_rows);
+            ContactSheetGenerator::new(config, Arc::clone(shutdown_signal))
+                .with_interaction(common.interaction())
+                .with_output(output)
+                .run_in(&common.input)
+        }
+        Command::AutoMove {
+            common,
+            copy,
+            dry_run,
+        } => {
+            let mode = if copy {
+                TransferMode::Copy
+            } else {
+                TransferMode::Move
+            };
+            AutoMoveByType::new(config, Arc::clone(shutdown_signal))
+                .with_interaction(common.interaction())
+                .with_output(output)
+                .with_transfer_mode(mode)
+                .with_dry_run(dry_run)
+                .run_in(&common.input)
+        }
+        Command::Orphan { common, report } => {
+            OrphanFileMover::new(config, Arc::clone(shutdown_signal))
+                .with_interaction(common.interaction())
+                .with_output(output)
+                .with_report_only(report)
+                .run_in(&common.input)
+        }
+        Command::Rename {
+            common,
+            sort,
+            start_index,
+            preview,
+            per_directory,
+            skip_renamed,
+        } => {
+            let renamer = &mut config.settings.renamer;
+            renamer.number_per_directory |= per_directory;
+            renamer.skip_renamed |= skip_renamed;
+            VideoRenamer::new(config, Arc::clone(shutdown_signal))
+                .with_interaction(common.interaction())
+                .with_output(output)
+                .with_sort_key(sort.into())
+                .with_start_index(start_index)
+                .with_preview_only(preview)
+                .run_in(&common.input)
+        }
+        Command::RunJobs { file } => jobs::run_jobs(&file, &output, &config, shutdown_signal),
+    }
+}
+
+/// 功能缺少相依時回傳錯誤與原因
+fn ensure_available(available: bool, deps: &DependencyReport) -> Result<()> {
+    if !available {
+        return Err(anyhow!(
+            "{}",
+            t!(
+                "dependency.command_unavailable",
+                details = deps.errors().join(&t!("dependency.separator"))
+            )
+        )
+        .context(PreflightFailed));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+    use std::path::Path;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_encode() {
+        let cli = Cli::try_parse_from([
+            "auto_video_organize",
+            "encode",
+            "--input",
+            "/videos",
+            "--crf",
+            "18",
+            "--yes",
+        ])
+        .unwrap();
+        let Some(Command::Encode { common, crf }) = cli.command else {
+            panic!("expected encode");
+        };
+        assert_eq!(common.input, PathBuf::from("/videos"));
+        assert_eq!(common.interaction(), Interaction::Unattended);
+        assert_eq!(crf, Some(18));
+
+        assert!(
+            Cli::try_parse_from(["auto_video_organize", "encode", "-i", "/v", "--crf", "64"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_yes_flag() {
+        for flag in ["-y", "--yes", "--no-confirm"] {
+            let cli = Cli::try_parse_from(["auto_video_organize", "orphan", "-i", "/videos", flag])
+                .unwrap();
+            let Some(Command::Orphan { common, .. }) = cli.command else {
+                panic!("expected orphan");
+            };
+            assert_eq!(common.interaction(), Interaction::Unattended);
+        }
+    }
+
+    #[test]
+    fn test_parse_without_subcommand() {
+        let cli = Cli::try_parse_from(["auto_video_organize"]).unwrap();
+        assert!(cli.command.is_none());
+    }
+
+    #[test]
+    fn test_parse_json_flag() {
+        let settings = UserSettings::default();
+        let cli = Cli::try_parse_from(["auto_video_organize", "dedup", "-i", "/videos", "--json"])
+            .unwrap();
+        assert!(matches!(cli.output_format(&settings), OutputFormat::Json));
+
+        let cli = Cli::try_parse_from(["auto_video_organize", "dedup", "-i", "/videos"]).unwrap();
+        assert!(matches!(cli.output_format(&settings), OutputFormat::Human));
+
+        let cli = Cli::try_parse_from([
+            "auto_video_organize",
+            "dedup",
+            "-i",
+            "/videos",
+            "--json-file",
+            "/tmp/result.json",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.output_format(&settings),
+            OutputFormat::JsonFile(path) if path == Path::new("/tmp/result.json")
+        ));
+    }
+
+    #[test]
+    fn test_output_format_from_settings() {
+        let cli = Cli::try_parse_from(["auto_video_organize", "dedup", "-i", "/videos"]).unwrap();
+        let mut settings = UserSettings {
+            json_output: Some("-".to_string()),
+            ..UserSettings::default()
+        };
+        assert!(matches!(cli.output_format(&settings), OutputFormat::Json));
+
+        settings.json_output = Some("/tmp/result.json".to_string());
+        assert!(matches!(
+            cli.output_format(&settings),
+            OutputFormat::JsonFile(_)
+        ));
+
+        let cli =
+            Cli::try_parse_from(["auto_video_organize", "dedup", "-i", "/v", "--json"]).unwrap();
+        assert!(matches!(cli.output_format(&settings), OutputFormat::Json));
+    }
+
+    #[test]
+    fn test_parse_run_jobs() {
+        let cli = Cli::try_parse_from(["auto_video_organize", "run-jobs", "jobs.toml"]).unwrap();
+        let Some(Command::RunJobs { file }) = cli.command else {
+            panic!("expected run-jobs");
+        };
+        assert_eq!(file, PathBuf::from("jobs.toml"));
+    }
+
+    #[test]
+    fn test_parse_rename_defaults() {
+        let cli = Cli::try_parse_from(["auto_video_organize", "rename", "-i", "/videos"]).unwrap();
+        let Some(Command::Rename {
+            common,
+            sort,
+            start_index,
+            preview,
+            ..
+        }) = cli.command
+        else {
+            panic!("expected rename");
+        };
+        assert_eq!(common.interaction(), Interaction::ConfirmOnly);
+        assert_eq!(SortKey::from(sort), SortKey::Duration);
+        assert_eq!(start_index, 1);
+        assert!(!preview);
+    }
+
+    #[test]
+    fn test_exit_code() {
+        assert_eq!(exit_code(&Ok(RunStatus::Completed)), EXIT_OK);
+        assert_eq!(
+            exit_code(&Ok(RunStatus::CompletedWithFailures)),
+            EXIT_PARTIAL_FAILURE
+        );
+        assert_eq!(exit_code(&Ok(RunStatus::Aborted)), EXIT_ABORTED);
+        assert_eq!(exit_code(&Err(anyhow!("失敗"))), EXIT_ERROR);
+        assert_eq!(
+            exit_code(&Err(anyhow!("路徑不存在").context(PreflightFailed))),
+            EXIT_PREFLIGHT_FAILED
+        );
+    }
+}
@@ -0,0 +1,396 @@
+//! 依日期整理檔案
+//!
+//! 依檔案日期將檔案移動或複製到 `2023/07/` 這類資料夾。
+//! 影片可優先使用 ffprobe 讀到的建立時間，其餘使用修改時間（以 UTC 計算日期）。
+
+use super::file_categorizer::claim_target;
+use super::undo_log::{MoveRecord, UNDO_LOG_FILE, write_undo_log};
+use crate::config::FileTypeTable;
+use crate::tools::{
+    ExcludeFilter, SizeFilter, TransferMode, ensure_directory_exists, get_creation_time,
+    scan_all_files, transfer_file,
+};
+use anyhow::Result;
+use log::{debug, info, warn};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 每日秒數
+const SECS_PER_DAY: u64 = 86_400;
+
+/// 早於此年份的建立時間視為未設定（部分封裝以 1970 或 1904 年表示空值）
+const MIN_MEDIA_YEAR: i64 = 1971;
+
+/// 檔案日期
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FileDate {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl FileDate {
+    /// 由系統時間換算 UTC 日期（早於 1970 年時回傳 `None`）
+    #[must_use]
+    pub fn from_system_time(time: SystemTime) -> Option<Self> {
+        let secs = time.duration_since(UNIX_EPOCH).ok()?.as_secs();
+        let (year, month, day) = civil_from_days((secs / SECS_PER_DAY) as i64);
+        Some(Self { year, month, day })
+    }
+
+    /// 解析 ISO 8601 日期開頭（例如 `2023-07-15T10:20:30.000000Z`）
+    #[must_use]
+    pub fn parse_iso(value: &str) -> Option<Self> {
+        let date = value.trim().get(..10)?;
+        let mut parts = date.split('-');
+        let year: i64 = parts.next()?.parse().ok()?;
+        let month: u32 = parts.next()?.parse().ok()?;
+        let day: u32 = parts.next()?.parse().ok()?;
+        ((1..=12).contains(&month) && (1..=31).contains(&day)).then_some(Self { year, month, day })
+    }
+
+    /// 依格式產生相對資料夾路徑（`{year}`、`{month}`、`{day}`，月與日補零到兩位）
+    #[must_use]
+    pub fn folder(&self, pattern: &str) -> PathBuf {
+        PathBuf::from(
+            pattern
+                .replace("{year}", &format!("{:04}", self.year))
+                .replace("{month}", &format!("{:02}", self.month))
+                .replace("{day}", &format!("{:02}", self.day)),
+        )
+    }
+}
+
+/// 由 1970-01-01 起算的天數換算年月日（Howard Hinnant 的 civil_from_days）
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// 檢查日期資料夾格式
+pub fn validate_pattern(pattern: &str) -> std::result::Result<(), String> {
+    if !pattern.contains("{year}") {
+        return Err("格式必須包含 {year}".to_string());
+    }
+    let path = Path::new(pattern);
+    if path
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return Err("格式只能是相對資料夾路徑（不可包含 .. 或以 / 開頭）".to_string());
+    }
+    Ok(())
+}
+
+/// 已決定日期資料夾的檔案
+#[derive(Debug, Clone)]
+pub struct DatedFile {
+    pub path: PathBuf,
+    pub size: u64,
+    pub date: FileDate,
+    /// 相對於目標目錄的日期資料夾
+    pub folder: PathBuf,
+}
+
+/// 依日期整理結果
+#[derive(Debug, Default)]
+pub struct DateOrganizeResult {
+    /// 各日期資料夾的檔案數量
+    pub folder_counts: BTreeMap<PathBuf, usize>,
+    /// 成功移動的檔案數
+    pub files_moved: usize,
+    /// 成功複製的檔案數
+    pub files_copied: usize,
+    /// 移動或複製失敗的檔案數
+    pub errors: usize,
+    /// 跳過的檔案數（目標目錄已有內容相同的同名檔案）
+    pub skipped: usize,
+    /// 是否已寫入可復原這次移動的紀錄
+    pub undo_log_written: bool,
+}
+
+impl DateOrganizeResult {
+    /// 取得總檔案數
+    #[must_use]
+    pub const fn total_files(&self) -> usize {
+        self.files_moved + self.files_copied + self.errors + self.skipped
+    }
+}
+
+/// 依日期整理檔案
+pub struct DateOrganizer {
+    file_type_table: FileTypeTable,
+    shutdown_signal: Arc<AtomicBool>,
+    pattern: String,
+    use_media_time: bool,
+    exclude_filter: ExcludeFilter,
+    /// 掃描時的檔案大小篩選
+    size_filter: Option<SizeFilter>,
+}
+
+impl DateOrganizer {
+    pub fn new(file_type_table: FileTypeTable, shutdown_signal: Arc<AtomicBool>) -> Self {
+        Self {
+            file_type_table,
+            shutdown_signal,
+            pattern: "{year}/{month}".to_string(),
+            use_media_time: false,
+            exclude_filter: ExcludeFilter::default(),
+            size_filter: None,
+        }
+    }
+
+    /// 套用掃描排除規則
+    #[must_use]
+    pub fn with_exclude_filter(mut self, exclude_filter: ExcludeFilter) -> Self {
+        self.exclude_filter = exclude_filter;
+        self
+    }
+
+    /// 套用掃描檔案大小篩選
+    #[must_use]
+    pub fn with_size_filter(mut self, size_filter: SizeFilter) -> Self {
+        self.size_filter = Some(size_filter);
+        self
+    }
+
+    /// 掃描檔案大小篩選（掃描後可取得篩除數量）
+    #[must_use]
+    pub const fn size_filter(&self) -> Option<&SizeFilter> {
+        self.size_filter.as_ref()
+    }
+
+    /// 設定日期資料夾格式
+    #[must_use]
+    pub fn with_pattern(mut self, pattern: &str) -> Self {
+        self.pattern = pattern.to_string();
+        self
+    }
+
+    /// 影片優先使用 ffprobe 讀到的建立時間
+    #[must_use]
+    pub const fn with_media_time(mut self, use_media_time: bool) -> Self {
+        self.use_media_time = use_media_time;
+        self
+    }
+
+    /// 掃描檔案並決定日期資料夾
+    ///
+    /// 已位於對應日期資料夾中的檔案與復原紀錄不列入
+    pub fn scan_and_date(&self, directory: &Path) -> Result<Vec<DatedFile>> {
+        info!("開始掃描目錄: {}", directory.display());
+        let files = scan_all_files(directory, &self.exclude_filter, self.size_filter.as_ref())?;
+        let undo_log = directory.join(UNDO_LOG_FILE);
+
+        let dated: Vec<DatedFile> = files
+            .par_iter()
+            .filter_map(|file| {
+                if self.shutdown_signal.load(Ordering::SeqCst) || file.path == undo_log {
+                    return None;
+                }
+
+                let Some(date) = self.file_date(&file.path) else {
+                    warn!("無法取得檔案日期，略過: {}", file.path.display());
+                    return None;
+                };
+                let folder = date.folder(&self.pattern);
+                if file.path.parent() == Some(directory.join(&folder).as_path()) {
+                    return None;
+                }
+                Some(DatedFile {
+                    path: file.path.clone(),
+                    size: file.size,
+                    date,
+                    folder,
+                })
+            })
+            .collect();
+
+        info!("掃描到 {} 個待整理檔案", dated.len());
+        Ok(dated)
+    }
+
+    fn file_date(&self, path: &Path) -> Option<FileDate> {
+        if self.use_media_time && self.file_type_table.is_video_file(path) {
+            match get_creation_time(path) {
+                Ok(Some(value)) => {
+                    if let Some(date) =
+                        FileDate::parse_iso(&value).filter(|d| d.year >= MIN_MEDIA_YEAR)
+                    {
+                        return Some(date);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => debug!("無法讀取建立時間 {}: {e:#}", path.display()),
+            }
+        }
+
+        fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(FileDate::from_system_time)
+    }
+
+    /// 移動或複製檔案到日期資料夾
+    ///
+    /// 目標已有同名檔案時：內容相同視為已整理而跳過，否則加上編號另存。
+    /// 移動模式下成功移動的檔案寫入 `base_dir` 中的復原紀錄
+    pub fn move_files_to_date_folders(
+        &self,
+        files: &[DatedFile],
+        base_dir: &Path,
+        mode: TransferMode,
+    ) -> Result<DateOrganizeResult> {
+        let mut result = DateOrganizeResult::default();
+
+        let folders: HashSet<&PathBuf> = files.iter().map(|f| &f.folder).collect();
+        for folder in folders {
+            ensure_directory_exists(&base_dir.join(folder))?;
+        }
+
+        let transferred_count = AtomicUsize::new(0);
+        let error_count = AtomicUsize::new(0);
+        let skipped_count = AtomicUsize::new(0);
+        let claimed_targets = Mutex::new(HashSet::new());
+        let moved = Mutex::new(Vec::new());
+
+        files.par_iter().for_each(|file| {
+            if self.shutdown_signal.load(Ordering::SeqCst) {
+                return;
+            }
+
+            let target_dir = base_dir.join(&file.folder);
+            let target_path =
+                match claim_target(&file.path, file.size, &target_dir, &claimed_targets) {
+                    Ok(Some(path)) => path,
+                    Ok(None) => {
+                        debug!("跳過已存在的檔案: {}", file.path.display());
+                        skipped_count.fetch_add(1, Ordering::SeqCst);
+                        return;
+                    }
+                    Err(e) => {
+                        warn!("{mode}檔案失敗 {}: {e:#}", file.path.display());
+                        error_count.fetch_add(1, Ordering::SeqCst);
+                        return;
+                    }
+                };
+
+            match transfer_file(&file.path, &target_path, mode) {
+                Ok(()) => {
+                    debug!(
+                        "{mode}檔案: {} -> {}",
+                        file.path.display(),
+                        target_path.display()
+                    );
+                    transferred_count.fetch_add(1, Ordering::SeqCst);
+                    if mode == TransferMode::Move
+                        && let Ok(mut moved) = moved.lock()
+                    {
+                        moved.push(MoveRecord {
+                            from: file.path.clone(),
+                            to: target_path,
+                            size: file.size,
+                        });
+                    }
+                }
+                Err(e) => {
+                    warn!("{mode}檔案失敗 {}: {e:#}", file.path.display());
+                    error_count.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        });
+
+        let transferred = transferred_count.load(Ordering::SeqCst);
+        match mode {
+            TransferMode::Move => result.files_moved = transferred,
+            TransferMode::Copy => result.files_copied = transferred,
+        }
+        result.errors = error_count.load(Ordering::SeqCst);
+        result.skipped = skipped_count.load(Ordering::SeqCst);
+
+        // 沒有移動任何檔案時保留上一次的復原紀錄
+        let moved = moved.into_inner().unwrap_or_default();
+        if !moved.is_empty() {
+            match write_undo_log(base_dir, moved) {
+                Ok(()) => result.undo_log_written = true,
+                Err(e) => warn!("無法寫入復原紀錄: {e:#}"),
+            }
+        }
+
+        for file in files {
+            *result.folder_counts.entry(file.folder.clone()).or_insert(0) += 1;
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_from_system_time() {
+        let date = |secs| FileDate::from_system_time(UNIX_EPOCH + Duration::from_secs(secs));
+        assert_eq!(
+            date(0),
+            Some(FileDate {
+                year: 1970,
+                month: 1,
+                day: 1
+            })
+        );
+        // 2024-02-29T23:59:59Z（閏日）
+        assert_eq!(
+            date(1_709_251_199),
+            Some(FileDate {
+                year: 2024,
+                month: 2,
+                day: 29
+            })
+        );
+        // 2023-12-31T12:00:00Z
+        assert_eq!(
+            date(1_704_024_000),
+            Some(FileDate {
+                year: 2023,
+                month: 12,
+                day: 31
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_iso_and_folder() {
+        let date = FileDate::parse_iso("2023-07-05T10:20:30.000000Z").unwrap();
+        assert_eq!(date.folder("{year}/{month}"), PathBuf::from("2023/07"));
+        assert_eq!(
+            date.folder("{year}/{year}-{month}-{day}"),
+            PathBuf::from("2023/2023-07-05")
+        );
+        assert_eq!(FileDate::parse_iso("2023-13-01"), None);
+        assert_eq!(FileDate::parse_iso("n/a"), None);
+    }
+
+    #[test]
+    fn test_validate_pattern() {
+        assert!(validate_pattern("{year}/{month}").is_ok());
+        assert!(validate_pattern("{month}").is_err());
+        assert!(validate_pattern("../{year}").is_err());
+        assert!(validate_pattern("/{year}").is_err());
+    }
+}
@@ -1,32 +1,47 @@
-use crate::config::{FileCategory, FileTypeTable};
-use crate::tools::{FileInfo, ensure_directory_exists, scan_all_files};
-use anyhow::{Context, Result};
+use super::date_organizer::FileDate;
+use super::undo_log::{MoveRecord, UNDO_LOG_FILE, write_undo_log};
+use crate::config::{FileCategory, FileTypeTable, OrganizeBy};
+use crate::tools::{
+    ExcludeFilter, FileInfo, SizeFilter, TransferMode, calculate_file_hash,
+    ensure_directory_exists, scan_all_files_with, scan_top_level_files, transfer_file,
+    unique_destination_with,
+};
+use anyhow::{Result, anyhow};
 use log::{debug, info, warn};
 use rayon::prelude::*;
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// 任何層級都略過的資料夾（版本控制、套件與 NAS 縮圖資料夾）
+pub const DEFAULT_EXCLUDED_FOLDERS: [&str; 3] = [".git", "node_modules", "@eaDir"];
 
 /// 分類結果
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize)]
 pub struct CategorizationResult {
     /// 各分類的檔案數量
     pub category_counts: HashMap<FileCategory, usize>,
     /// 成功移動的檔案數
     pub files_moved: usize,
-    /// 移動失敗的檔案數
+    /// 成功複製的檔案數
+    pub files_copied: usize,
+    /// 移動或複製失敗的檔案數
     pub errors: usize,
-    /// 跳過的檔案數（已在目標目錄中）
+    /// 跳過的檔案數（目標目錄已有內容相同的同名檔案）
     pub skipped: usize,
+    /// 是否已寫入可復原這次移動的紀錄
+    pub undo_log_written: bool,
 }
 
 impl CategorizationResult {
     /// 取得總檔案數
     #[must_use]
     pub const fn total_files(&self) -> usize {
-        self.files_moved + self.errors + self.skipped
+        self.files_moved + self.files_copied + self.errors + self.skipped
     }
 }
 
@@ -36,14 +51,36 @@ pub struct CategorizedFile {
     pub path: PathBuf,
     pub category: FileCategory,
     pub size: u64,
+    /// 修改時間，依日期分子資料夾時使用
+    pub modified: Option<SystemTime>,
+}
+
+/// 移動規劃：要處理的（來源, 目標），以及跳過與無法決定目標的檔案數
+#[derive(Debug, Default)]
+struct MovePlan {
+    moves: Vec<(PathBuf, PathBuf)>,
+    skipped: usize,
+    errors: usize,
 }
 
 /// 檔案分類器
 pub struct FileCategorizer {
     file_type_table: FileTypeTable,
     shutdown_signal: Arc<AtomicBool>,
-    /// 要排除的資料夾名稱
+    /// 要排除的第一層資料夾名稱（分類目標資料夾）
     exclude_folders: Vec<String>,
+    /// 任何層級都要排除的資料夾名稱
+    excluded_folder_names: Vec<String>,
+    /// 使用者自訂的分類資料夾名稱
+    folder_overrides: HashMap<FileCategory, String>,
+    /// 掃描時略過的檔案與資料夾
+    exclude_filter: ExcludeFilter,
+    /// 掃描時的檔案大小篩選
+    size_filter: Option<SizeFilter>,
+    /// 分類資料夾下的結構
+    organize_by: OrganizeBy,
+    /// 是否包含子資料夾中的檔案
+    recursive: bool,
 }
 
 impl FileCategorizer {
@@ -59,21 +96,108 @@ impl FileCategorizer {
             file_type_table,
             shutdown_signal,
             exclude_folders,
+            excluded_folder_names: DEFAULT_EXCLUDED_FOLDERS
+                .iter()
+                .map(|name| name.to_lowercase())
+                .collect(),
+            folder_overrides: HashMap::new(),
+            exclude_filter: ExcludeFilter::default(),
+            size_filter: None,
+            organize_by: OrganizeBy::default(),
+            recursive: true,
         }
     }
 
+    /// 設定是否包含子資料夾中的檔案（預設包含）
+    #[must_use]
+    pub const fn with_recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// 設定分類資料夾下是否再依日期分子資料夾
+    #[must_use]
+    pub const fn with_organize_by(mut self, organize_by: OrganizeBy) -> Self {
+        self.organize_by = organize_by;
+        self
+    }
+
+    /// 套用掃描排除規則
+    #[must_use]
+    pub fn with_exclude_filter(mut self, exclude_filter: ExcludeFilter) -> Self {
+        self.exclude_filter = exclude_filter;
+        self
+    }
+
+    /// 套用掃描檔案大小篩選
+    #[must_use]
+    pub fn with_size_filter(mut self, size_filter: SizeFilter) -> Self {
+        self.size_filter = Some(size_filter);
+        self
+    }
+
+    /// 掃描檔案大小篩選（掃描後可取得篩除數量）
+    #[must_use]
+    pub const fn size_filter(&self) -> Option<&SizeFilter> {
+        self.size_filter.as_ref()
+    }
+
+    /// 額外在任何層級都略過的資料夾名稱（不分大小寫）
+    #[must_use]
+    pub fn with_excluded_folders(mut self, names: &[String]) -> Self {
+        for name in names {
+            let name = name.trim().to_lowercase();
+            if !name.is_empty() && !self.excluded_folder_names.contains(&name) {
+                self.excluded_folder_names.push(name);
+            }
+        }
+        self
+    }
+
+    /// 套用自訂分類資料夾名稱
+    ///
+    /// 自訂的資料夾同樣排除在掃描之外，避免再次執行時把已整理的檔案移出
+    #[must_use]
+    pub fn with_folder_overrides(mut self, overrides: HashMap<FileCategory, String>) -> Self {
+        for category in overrides.keys() {
+            let folder = category.folder_name_with(&overrides);
+            if let Some(first) = Path::new(folder).components().next() {
+                let name = first.as_os_str().to_string_lossy().to_lowercase();
+                if !self.exclude_folders.contains(&name) {
+                    self.exclude_folders.push(name);
+                }
+            }
+        }
+        self.folder_overrides = overrides;
+        self
+    }
+
+    /// 分類的目標資料夾名稱
+    #[must_use]
+    pub fn folder_name(&self, category: FileCategory) -> &str {
+        category.folder_name_with(&self.folder_overrides)
+    }
+
     /// 掃描並分類所有檔案
     pub fn scan_and_categorize(&self, directory: &Path) -> Result<Vec<CategorizedFile>> {
         info!("開始掃描目錄: {}", directory.display());
 
-        // 掃描所有檔案
-        let files = scan_all_files(directory)?;
-
-        // 過濾掉已在分類資料夾中的檔案
-        let filtered_files: Vec<FileInfo> = files
-            .into_iter()
-            .filter(|f| !self.is_in_excluded_folder(&f.path, directory))
-            .collect();
+        // 掃描所有檔案，分類資料夾與排除的資料夾不會進入；不包含子資料夾時只列出目錄本身的檔案
+        let files = if self.recursive {
+            scan_all_files_with(
+                directory,
+                &self.exclude_filter,
+                self.size_filter.as_ref(),
+                |dir| self.is_excluded_dir(dir, directory),
+            )?
+        } else {
+            scan_top_level_files(directory, &self.exclude_filter, self.size_filter.as_ref())?
+        };
+
+        // 過濾掉復原紀錄
+        let undo_log = directory.join(UNDO_LOG_FILE);
+        let filtered_files: Vec<FileInfo> =
+            files.into_iter().filter(|f| f.path != undo_log).collect();
 
         info!("掃描到 {} 個待分類檔案", filtered_files.len());
 
@@ -90,6 +214,7 @@ impl FileCategorizer {
                     path: file.path.clone(),
                     category,
                     size: file.size,
+                    modified: file.modified,
                 })
             })
             .collect();
@@ -97,86 +222,149 @@ impl FileCategorizer {
         Ok(categorized)
     }
 
-    /// 檢查檔案是否在排除的資料夾中
-    fn is_in_excluded_folder(&self, file_path: &Path, base_dir: &Path) -> bool {
+    /// 檢查資料夾是否為排除的資料夾或位於其中，掃描時不進入這些資料夾
+    ///
+    /// 分類目標資料夾只比對第一層，其他排除的資料夾比對每一層
+    fn is_excluded_dir(&self, dir: &Path, base_dir: &Path) -> bool {
         // 取得相對於 base_dir 的路徑
-        if let Ok(relative) = file_path.strip_prefix(base_dir) {
-            // 檢查第一層資料夾是否在排除列表中
-            if let Some(first_component) = relative.components().next() {
-                let folder_name = first_component.as_os_str().to_string_lossy().to_lowercase();
-                return self.exclude_folders.iter().any(|f| f == &folder_name);
+        let Ok(folders) = dir.strip_prefix(base_dir) else {
+            return false;
+        };
+        folders.components().enumerate().any(|(depth, component)| {
+            let folder_name = component.as_os_str().to_string_lossy().to_lowercase();
+            (depth == 0 && self.exclude_folders.contains(&folder_name))
+                || self.excluded_folder_names.contains(&folder_name)
+        })
+    }
+
+    /// 檔案的目標資料夾（分類資料夾，依設定再加上日期子資料夾）
+    ///
+    /// 無法取得修改時間的檔案直接放在分類資料夾
+    fn target_dir(&self, file: &CategorizedFile, base_dir: &Path) -> PathBuf {
+        let category_dir = base_dir.join(self.folder_name(file.category));
+        match self.organize_by {
+            OrganizeBy::Category => category_dir,
+            OrganizeBy::CategoryThenDate { granularity } => {
+                match file.modified.and_then(FileDate::from_system_time) {
+                    Some(date) => category_dir.join(date.folder(granularity.pattern())),
+                    None => category_dir,
+                }
+            }
+        }
+    }
+
+    /// 規劃每個檔案的目標路徑（來源, 目標），不實際移動檔案
+    ///
+    /// 目標已有內容相同的同名檔案時視為已整理而不列入；同名但內容不同時加上編號
+    #[must_use]
+    pub fn plan_moves(
+        &self,
+        files: &[CategorizedFile],
+        base_dir: &Path,
+    ) -> Vec<(PathBuf, PathBuf)> {
+        self.plan(files, base_dir).moves
+    }
+
+    fn plan(&self, files: &[CategorizedFile], base_dir: &Path) -> MovePlan {
+        let claimed_targets = Mutex::new(HashSet::new());
+        let mut plan = MovePlan::default();
+        for file in files {
+            let target_dir = self.target_dir(file, base_dir);
+            match claim_target(&file.path, file.size, &target_dir, &claimed_targets) {
+                Ok(Some(target)) => plan.moves.push((file.path.clone(), target)),
+                Ok(None) => {
+                    debug!("跳過已存在的檔案: {}", file.path.display());
+                    plan.skipped += 1;
+                }
+                Err(e) => {
+                    warn!("無法決定目標路徑 {}: {e:#}", file.path.display());
+                    plan.errors += 1;
+                }
             }
         }
-        false
+        plan
     }
 
-    /// 移動檔案到對應的分類資料夾
+    /// 移動或複製檔案到對應的分類資料夾（巢狀的日期子資料夾一併建立）
+    ///
+    /// 目標路徑由 [`Self::plan_moves`] 決定，目標已存在而未列入規劃的檔案計為跳過。
+    /// 移動模式下成功移動的檔案寫入 `base_dir` 中的復原紀錄
     pub fn move_files_to_categories(
         &self,
         files: &[CategorizedFile],
         base_dir: &Path,
+        mode: TransferMode,
     ) -> Result<CategorizationResult> {
         let mut result = CategorizationResult::default();
 
+        let MovePlan {
+            moves: planned,
+            skipped,
+            errors: plan_errors,
+        } = self.plan(files, base_dir);
+        result.skipped = skipped;
+
         // 建立所需的分類資料夾
-        let used_categories: Vec<FileCategory> = files.iter().map(|f| f.category).collect();
-        for category in &used_categories {
-            let category_dir = base_dir.join(category.folder_name());
-            ensure_directory_exists(&category_dir)?;
+        let target_dirs: HashSet<&Path> = planned
+            .iter()
+            .filter_map(|(_, target)| target.parent())
+            .collect();
+        for target_dir in target_dirs {
+            ensure_directory_exists(target_dir)?;
         }
 
         // 使用原子計數器
-        let moved_count = AtomicUsize::new(0);
+        let transferred_count = AtomicUsize::new(0);
         let error_count = AtomicUsize::new(0);
-        let skipped_count = AtomicUsize::new(0);
+        let moved = Mutex::new(Vec::new());
 
-        // 平行移動檔案
-        files.par_iter().for_each(|file| {
+        // 平行處理檔案
+        planned.par_iter().for_each(|(source, target_path)| {
             if self.shutdown_signal.load(Ordering::SeqCst) {
                 return;
             }
 
-            let target_dir = base_dir.join(file.category.folder_name());
-            let file_name = file.path.file_name().unwrap_or_default();
-            let target_path = target_dir.join(file_name);
-
-            // 檢查目標檔案是否已存在
-            if target_path.exists() {
-                debug!("跳過已存在的檔案: {}", target_path.display());
-                skipped_count.fetch_add(1, Ordering::SeqCst);
-                return;
-            }
-
-            // 移動檔案
-            match fs::rename(&file.path, &target_path) {
+            match transfer_file(source, target_path, mode) {
                 Ok(()) => {
                     debug!(
-                        "移動檔案: {} -> {}",
-                        file.path.display(),
+                        "{mode}檔案: {} -> {}",
+                        source.display(),
                         target_path.display()
                     );
-                    moved_count.fetch_add(1, Ordering::SeqCst);
+                    transferred_count.fetch_add(1, Ordering::SeqCst);
+                    if mode == TransferMode::Move
+                        && let Ok(mut moved) = moved.lock()
+                    {
+                        let size = fs::metadata(target_path).map_or(0, |m| m.len());
+                        moved.push(MoveRecord {
+                            from: source.clone(),
+                            to: target_path.clone(),
+                            size,
+                        });
+                    }
                 }
                 Err(e) => {
-                    // 如果 rename 失敗（可能是跨檔案系統），嘗試複製後刪除
-                    if let Err(copy_err) = self.copy_and_delete(&file.path, &target_path) {
-                        warn!(
-                            "移動檔案失敗 {}: {} (原始錯誤: {})",
-                            file.path.display(),
-                            copy_err,
-                            e
-                        );
-                        error_count.fetch_add(1, Ordering::SeqCst);
-                    } else {
-                        moved_count.fetch_add(1, Ordering::SeqCst);
-                    }
+                    warn!("{mode}檔案失敗 {}: {e:#}", source.display());
+                    error_count.fetch_add(1, Ordering::SeqCst);
                 }
             }
         });
 
-        result.files_moved = moved_count.load(Ordering::SeqCst);
-        result.errors = error_count.load(Ordering::SeqCst);
-        result.skipped = skipped_count.load(Ordering::SeqCst);
+        let transferred = transferred_count.load(Ordering::SeqCst);
+        match mode {
+            TransferMode::Move => result.files_moved = transferred,
+            TransferMode::Copy => result.files_copied = transferred,
+        }
+        result.errors = plan_errors + error_count.load(Ordering::SeqCst);
+
+        // 沒有移動任何檔案時保留上一次的復原紀錄
+        let moved = moved.into_inner().unwrap_or_default();
+        if !moved.is_empty() {
+            match write_undo_log(base_dir, moved) {
+                Ok(()) => result.undo_log_written = true,
+                Err(e) => warn!("無法寫入復原紀錄: {e:#}"),
+            }
+        }
 
         // 統計各分類數量
         for file in files {
@@ -185,23 +373,51 @@ impl FileCategorizer {
 
         Ok(result)
     }
+}
 
-    /// 複製檔案後刪除原檔案
-    fn copy_and_delete(&self, source: &Path, target: &Path) -> Result<()> {
-        fs::copy(source, target).with_context(|| {
-            format!("複製檔案失敗: {} -> {}", source.display(), target.display())
-        })?;
+/// 決定檔案在目標資料夾中的路徑，目標已有內容相同的同名檔案時回傳 `None`
+///
+/// 大小相同時再比對 hash，同名但內容不同時加上編號；
+/// 平行處理時已分配的路徑記錄在 `claimed_targets`
+pub(super) fn claim_target(
+    source: &Path,
+    size: u64,
+    target_dir: &Path,
+    claimed_targets: &Mutex<HashSet<PathBuf>>,
+) -> Result<Option<PathBuf>> {
+    let file_name = source
+        .file_name()
+        .ok_or_else(|| anyhow!("無法取得檔案名稱"))?;
+    let target_path = target_dir.join(file_name);
+    let same_content = fs::metadata(&target_path).is_ok_and(|m| m.len() == size)
+        && same_file_content(source, &target_path);
+
+    let mut claimed = claimed_targets
+        .lock()
+        .map_err(|e| anyhow!("Lock failed: {e}"))?;
+    if same_content && !claimed.contains(&target_path) {
+        return Ok(None);
+    }
 
-        fs::remove_file(source).with_context(|| format!("刪除原檔案失敗: {}", source.display()))?;
+    let candidate = unique_destination_with(target_dir, file_name, |p| claimed.contains(p));
+    claimed.insert(candidate.clone());
+    Ok(Some(candidate))
+}
 
-        Ok(())
+/// 兩個檔案的 hash 是否相同，任一方無法讀取時視為不同
+fn same_file_content(a: &Path, b: &Path) -> bool {
+    match (calculate_file_hash(a), calculate_file_hash(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::component::auto_move_by_type::undo_log::undo_last_move;
     use crate::config::Config;
+    use serde_json::json;
     use tempfile::TempDir;
 
     fn create_test_categorizer() -> FileCategorizer {
@@ -211,23 +427,56 @@ mod tests {
     }
 
     #[test]
-    fn test_is_in_excluded_folder() {
+    fn test_is_excluded_dir() {
         let categorizer = create_test_categorizer();
         let base_dir = Path::new("/test");
 
-        // 在 video 資料夾中的檔案應該被排除
-        assert!(categorizer.is_in_excluded_folder(Path::new("/test/video/movie.mp4"), base_dir));
+        // video 與 image 分類資料夾應該被排除
+        assert!(categorizer.is_excluded_dir(Path::new("/test/video"), base_dir));
+        assert!(categorizer.is_excluded_dir(Path::new("/test/image/2024"), base_dir));
 
-        // 在 image 資料夾中的檔案應該被排除
-        assert!(categorizer.is_in_excluded_folder(Path::new("/test/image/photo.jpg"), base_dir));
+        // 不是分類資料夾的不應該被排除
+        assert!(!categorizer.is_excluded_dir(Path::new("/test/downloads"), base_dir));
 
-        // 不在排除資料夾中的檔案不應該被排除
-        assert!(
-            !categorizer.is_in_excluded_folder(Path::new("/test/downloads/file.txt"), base_dir)
-        );
+        // 根目錄本身不應該被排除
+        assert!(!categorizer.is_excluded_dir(base_dir, base_dir));
+
+        // 分類資料夾只比對第一層
+        assert!(!categorizer.is_excluded_dir(Path::new("/test/trip/video"), base_dir));
+    }
 
-        // 根目錄的檔案不應該被排除
-        assert!(!categorizer.is_in_excluded_folder(Path::new("/test/file.txt"), base_dir));
+    #[test]
+    fn test_excluded_folders_at_any_depth() {
+        let categorizer = create_test_categorizer().with_excluded_folders(&["Backup".to_string()]);
+        let base_dir = Path::new("/test");
+
+        for path in [
+            "/test/.git/objects",
+            "/test/project/node_modules/a",
+            "/test/photos/2024/@eaDir",
+            "/test/a/b/backup",
+        ] {
+            assert!(
+                categorizer.is_excluded_dir(Path::new(path), base_dir),
+                "{path}"
+            );
+        }
+        assert!(!categorizer.is_excluded_dir(Path::new("/test/a/backup.d"), base_dir));
+    }
+
+    #[test]
+    fn test_scan_skips_excluded_folders() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path();
+        fs::create_dir_all(base.join("project/node_modules/pkg")).unwrap();
+        fs::write(base.join("project/node_modules/pkg/index.js"), "x").unwrap();
+        fs::create_dir_all(base.join("video")).unwrap();
+        fs::write(base.join("video/organized.mp4"), "x").unwrap();
+        fs::write(base.join("project/main.js"), "x").unwrap();
+
+        let files = create_test_categorizer().scan_and_categorize(base).unwrap();
+        let paths: Vec<_> = files.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(paths, [base.join("project/main.js")]);
     }
 
     #[test]
@@ -273,7 +522,7 @@ mod tests {
         let files = categorizer.scan_and_categorize(base_path).unwrap();
 
         let result = categorizer
-            .move_files_to_categories(&files, base_path)
+            .move_files_to_categories(&files, base_path, TransferMode::Move)
             .unwrap();
 
         assert_eq!(result.files_moved, 2);
@@ -286,5 +535,235 @@ mod tests {
         // 確認原檔案已不存在
         assert!(!base_path.join("movie.mp4").exists());
         assert!(!base_path.join("photo.jpg").exists());
+
+        // 復原紀錄不會被當成待分類檔案，復原後回到原狀
+        assert!(result.undo_log_written);
+        assert!(
+            categorizer
+                .scan_and_categorize(base_path)
+                .unwrap()
+                .is_empty()
+        );
+        let undo = undo_last_move(base_path).unwrap();
+        assert_eq!(undo.restored, 2);
+        assert!(base_path.join("movie.mp4").exists());
+        assert!(!base_path.join("video").exists());
+    }
+
+    #[test]
+    fn test_copy_files_to_other_directory() {
+        let source_dir = TempDir::new().unwrap();
+        let target_dir = TempDir::new().unwrap();
+        let source = source_dir.path();
+        let target = target_dir.path();
+
+        fs::write(source.join("movie.mp4"), "video content").unwrap();
+        fs::write(source.join("photo.jpg"), "image content").unwrap();
+        fs::write(source.join("clip.mp4"), "new clip").unwrap();
+        fs::write(source.join("trailer.mp4"), "new trailer").unwrap();
+
+        // 內容相同的同名檔案視為已整理；大小或內容不同則加上編號
+        fs::create_dir(target.join("video")).unwrap();
+        fs::write(target.join("video/movie.mp4"), "video content").unwrap();
+        fs::write(target.join("video/clip.mp4"), "older clip content").unwrap();
+        fs::write(target.join("video/trailer.mp4"), "old trailer").unwrap();
+
+        let categorizer = create_test_categorizer();
+        let files = categorizer.scan_and_categorize(source).unwrap();
+        let result = categorizer
+            .move_files_to_categories(&files, target, TransferMode::Copy)
+            .unwrap();
+
+        assert_eq!(result.files_copied, 3);
+        assert_eq!(result.files_moved, 0);
+        assert_eq!(result.skipped, 1);
+        assert_eq!(result.total_files(), 4);
+
+        assert!(source.join("movie.mp4").exists());
+        assert!(source.join("photo.jpg").exists());
+        assert!(source.join("clip.mp4").exists());
+        assert!(target.join("image/photo.jpg").exists());
+        assert_eq!(
+            fs::read_to_string(target.join("video/clip_1.mp4")).unwrap(),
+            "new clip"
+        );
+        assert_eq!(
+            fs::read_to_string(target.join("video/clip.mp4")).unwrap(),
+            "older clip content"
+        );
+        assert_eq!(
+            fs::read_to_string(target.join("video/trailer_1.mp4")).unwrap(),
+            "new trailer"
+        );
+    }
+
+    #[test]
+    fn test_unresolvable_target_counts_as_error() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+        fs::write(base_path.join("movie.mp4"), "video content").unwrap();
+
+        let categorizer = create_test_categorizer();
+        let mut files = categorizer.scan_and_categorize(base_path).unwrap();
+        // 沒有檔名的路徑無法決定目標
+        files.push(CategorizedFile {
+            path: PathBuf::from("/"),
+            category: FileCategory::Other,
+            size: 0,
+            modified: None,
+        });
+        let result = categorizer
+            .move_files_to_categories(&files, base_path, TransferMode::Move)
+            .unwrap();
+
+        assert_eq!(result.files_moved, 1);
+        assert_eq!(result.errors, 1);
+        assert_eq!(result.skipped, 0);
+    }
+
+    #[test]
+    fn test_non_recursive_scan() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+        fs::create_dir_all(base_path.join("downloads")).unwrap();
+        fs::write(base_path.join("movie.mp4"), "video content").unwrap();
+        fs::write(base_path.join("downloads/photo.jpg"), "image content").unwrap();
+
+        let categorizer = create_test_categorizer().with_recursive(false);
+        let files = categorizer.scan_and_categorize(base_path).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, base_path.join("movie.mp4"));
+
+        // 已整理的檔案仍不會被重新分類
+        categorizer
+            .move_files_to_categories(&files, base_path, TransferMode::Move)
+            .unwrap();
+        assert!(
+            categorizer
+                .scan_and_categorize(base_path)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_plan_moves_does_not_touch_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+        fs::write(base_path.join("movie.mp4"), "video content").unwrap();
+        fs::write(base_path.join("photo.jpg"), "image content").unwrap();
+        fs::create_dir(base_path.join("sub")).unwrap();
+        fs::write(base_path.join("sub/movie.mp4"), "another video").unwrap();
+
+        let categorizer = create_test_categorizer();
+        let files = categorizer.scan_and_categorize(base_path).unwrap();
+        let mut planned = categorizer.plan_moves(&files, base_path);
+        planned.sort();
+
+        let targets: Vec<PathBuf> = planned.iter().map(|(_, t)| t.clone()).collect();
+        assert_eq!(targets.len(), 3);
+        assert!(targets.contains(&base_path.join("image/photo.jpg")));
+        assert!(targets.contains(&base_path.join("video/movie.mp4")));
+        assert!(targets.contains(&base_path.join("video/movie_1.mp4")));
+
+        // 規劃不會建立資料夾或移動檔案
+        assert!(!base_path.join("video").exists());
+        assert!(base_path.join("movie.mp4").exists());
+    }
+
+    #[test]
+    fn test_category_then_date_folders() {
+        use crate::config::DateGranularity;
+        use std::time::Duration;
+
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+        let photo = base_path.join("photo.jpg");
+        let movie = base_path.join("movie.mp4");
+        fs::write(&photo, "image content").unwrap();
+        fs::write(&movie, "video content").unwrap();
+        // 2023-06-15
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_686_787_200);
+        for path in [&photo, &movie] {
+            fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        }
+
+        let categorizer =
+            create_test_categorizer().with_organize_by(OrganizeBy::CategoryThenDate {
+                granularity: DateGranularity::Month,
+            });
+        let files = categorizer.scan_and_categorize(base_path).unwrap();
+        let result = categorizer
+            .move_files_to_categories(&files, base_path, TransferMode::Move)
+            .unwrap();
+        assert_eq!(result.files_moved, 2);
+        assert!(base_path.join("image/2023/06/photo.jpg").exists());
+        assert!(base_path.join("video/2023/06/movie.mp4").exists());
+
+        let by_year = create_test_categorizer().with_organize_by(OrganizeBy::CategoryThenDate {
+            granularity: DateGranularity::Year,
+        });
+        assert_eq!(
+            by_year.target_dir(&files[0], base_path),
+            base_path
+                .join(by_year.folder_name(files[0].category))
+                .join("2023")
+        );
+    }
+
+    #[test]
+    fn test_folder_overrides() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+        fs::write(base_path.join("movie.mp4"), "video content").unwrap();
+        fs::write(base_path.join("photo.jpg"), "image content").unwrap();
+
+        let overrides = HashMap::from([(FileCategory::Video, "Movies".to_string())]);
+        let categorizer = create_test_categorizer().with_folder_overrides(overrides);
+        assert_eq!(categorizer.folder_name(FileCategory::Video), "Movies");
+        assert_eq!(categorizer.folder_name(FileCategory::Image), "image");
+
+        let files = categorizer.scan_and_categorize(base_path).unwrap();
+        categorizer
+            .move_files_to_categories(&files, base_path, TransferMode::Move)
+            .unwrap();
+        assert!(base_path.join("Movies/movie.mp4").exists());
+        assert!(base_path.join("image/photo.jpg").exists());
+
+        // 再次掃描時自訂資料夾中的檔案不會被重新分類
+        assert!(
+            categorizer
+                .scan_and_categorize(base_path)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_result_json_schema() {
+        let result = CategorizationResult {
+            category_counts: HashMap::from([(FileCategory::Video, 2)]),
+            files_moved: 2,
+            files_copied: 0,
+            errors: 1,
+            skipped: 0,
+            undo_log_written: true,
+        };
+        assert_eq!(
+            serde_json::to_value(&result).unwrap(),
+            json!({
+                "category_counts": { "video": 2 },
+                "files_moved": 2,
+                "files_copied": 0,
+                "errors": 1,
+                "skipped": 0,
+                "undo_log_written": true
+            })
+        );
     }
 }
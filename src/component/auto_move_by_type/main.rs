@@ -1,21 +1,74 @@
+use super::date_organizer::{DateOrganizeResult, DateOrganizer, DatedFile, validate_pattern};
 use super::file_categorizer::{CategorizationResult, CategorizedFile, FileCategorizer};
+use super::undo_log::{UndoResult, has_undo_log, undo_last_move};
+use crate::component::path_prompt::{prompt_directory, prompt_output_directory};
+use crate::component::scan_options::{prompt_size_filter, report_size_filtered};
+use crate::component::{Interaction, OutputFormat, PreflightFailed, RunStatus, report};
 use crate::config::save::{add_recent_path, save_settings};
-use crate::config::{Config, FileCategory};
-use crate::tools::validate_directory_exists;
-use anyhow::Result;
+use crate::config::{Config, DateGranularity, FileCategory, OrganizeBy, PathFeature, UserSettings};
+use crate::tools::{ExcludeFilter, TransferMode, validate_directory_exists};
+use anyhow::{Context, Result};
 use console::style;
 use dialoguer::theme::ColorfulTheme;
 use dialoguer::{Confirm, Input, Select};
 use log::{info, warn};
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 
+/// 整理依據
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OrganizeMode {
+    /// 依檔案類型（可再依日期分子資料夾）
+    Category(OrganizeBy),
+    /// 只依日期
+    Date,
+    /// 復原上一次移動的檔案
+    Undo,
+}
+
+/// 預覽的移動計畫（JSON 輸出用）
+#[derive(Debug, Serialize)]
+struct PlannedMoves<'a> {
+    /// 是否為複製（否則為移動）
+    copy: bool,
+    /// 預計移動或複製的檔案與目標路徑
+    moves: Vec<PlannedMove<'a>>,
+    /// 目標已有相同檔案而將跳過的檔案數
+    skipped: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct PlannedMove<'a> {
+    source: &'a Path,
+    target: &'a Path,
+}
+
+impl<'a> PlannedMoves<'a> {
+    fn new(planned: &'a [(PathBuf, PathBuf)], total: usize, mode: TransferMode) -> Self {
+        Self {
+            copy: mode == TransferMode::Copy,
+            moves: planned
+                .iter()
+                .map(|(source, target)| PlannedMove { source, target })
+                .collect(),
+            skipped: total - planned.len(),
+        }
+    }
+}
+
 /// 自動依類型移動檔案元件
 pub struct AutoMoveByType {
     config: Config,
     shutdown_signal: Arc<AtomicBool>,
+    interaction: Interaction,
+    output: OutputFormat,
+    /// 不逐項詢問時的整理方式
+    transfer_mode: TransferMode,
+    /// 不逐項詢問時是否只預覽目標路徑
+    dry_run: bool,
 }
 
 impl AutoMoveByType {
@@ -23,117 +76,369 @@ impl AutoMoveByType {
         Self {
             config,
             shutdown_signal,
+            interaction: Interaction::Prompt,
+            output: OutputFormat::Human,
+            transfer_mode: TransferMode::Move,
+            dry_run: false,
         }
     }
 
-    pub fn run(&self) -> Result<()> {
-        println!("{}", style("=== 自動依類型整理檔案 ===").cyan().bold());
+    #[must_use]
+    pub const fn with_interaction(mut self, interaction: Interaction) -> Self {
+        self.interaction = interaction;
+        self
+    }
+
+    #[must_use]
+    pub fn with_output(mut self, output: OutputFormat) -> Self {
+        self.output = output;
+        self
+    }
+
+    /// 不逐項詢問時移動或複製檔案（預設移動）
+    #[must_use]
+    pub const fn with_transfer_mode(mut self, mode: TransferMode) -> Self {
+        self.transfer_mode = mode;
+        self
+    }
+
+    /// 不逐項詢問時只列出目標路徑，不移動檔案
+    #[must_use]
+    pub const fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn run(&self) -> Result<RunStatus> {
+        report!(
+            self.output,
+            "{}",
+            style("=== 自動整理檔案 ===").cyan().bold()
+        );
 
         // 取得輸入路徑
-        let Some(input_path) = self.prompt_input_path()? else {
-            return Ok(()); // ESC pressed
+        let mut settings = self.config.settings.clone();
+        let Some(input_path) = self.prompt_input_path(&mut settings)? else {
+            return Ok(RunStatus::Completed); // ESC pressed
         };
         let directory = PathBuf::from(&input_path);
 
         validate_directory_exists(&directory)?;
 
-        // 更新路徑歷史並儲存
-        {
-            let mut settings = self.config.settings.clone();
-            add_recent_path(&mut settings, &input_path);
-            if let Err(e) = save_settings(&settings) {
-                warn!("無法儲存路徑歷史: {e}");
-            }
+        // 路徑歷史與本次變更的選項一併儲存
+        add_recent_path(&mut settings, PathFeature::AutoMove, &input_path);
+        let status = self.organize_in(&directory, &mut settings);
+        if let Err(e) = save_settings(&settings) {
+            warn!("無法儲存設定: {e}");
+        }
+        status
+    }
+
+    /// 選擇整理依據並整理，選項的變更寫入 `settings`，由呼叫端儲存
+    fn organize_in(&self, directory: &Path, settings: &mut UserSettings) -> Result<RunStatus> {
+        let Some(mode) = self.prompt_organize_mode(directory, settings)? else {
+            return Ok(RunStatus::Completed); // ESC pressed
+        };
+        match mode {
+            OrganizeMode::Category(organize_by) => self.run_by_category(directory, organize_by),
+            OrganizeMode::Date => self.run_by_date(directory, settings),
+            OrganizeMode::Undo => self.run_undo(directory),
         }
+    }
+
+    /// 不詢問路徑與選項，依設定的整理依據將指定資料夾依類型整理（命令列執行）
+    pub fn run_in(&self, directory: &Path) -> Result<RunStatus> {
+        report!(
+            self.output,
+            "{}",
+            style("=== 自動整理檔案 ===").cyan().bold()
+        );
+        validate_directory_exists(directory).context(PreflightFailed)?;
+        self.run_by_category(directory, self.config.settings.organize_by)
+    }
+
+    fn run_by_category(&self, directory: &Path, organize_by: OrganizeBy) -> Result<RunStatus> {
+        let recursive = !self.interaction.prompts_options()
+            || Confirm::new()
+                .with_prompt("是否包含子資料夾中的檔案？")
+                .default(true)
+                .interact()?;
 
         // 建立分類器
         let categorizer = FileCategorizer::new(
             self.config.file_type_table.clone(),
             Arc::clone(&self.shutdown_signal),
-        );
+        )
+        .with_folder_overrides(self.config.settings.category_folder_overrides.clone())
+        .with_excluded_folders(&self.config.settings.excluded_folders)
+        .with_organize_by(organize_by)
+        .with_recursive(recursive)
+        .with_exclude_filter(ExcludeFilter::new(&self.config.settings.exclude_globs))
+        .with_size_filter(prompt_size_filter(
+            &self.config.settings,
+            self.interaction,
+            &self.output,
+        )?);
 
         // 掃描並分類
-        println!("{}", style("掃描檔案中...").dim());
-        let files = categorizer.scan_and_categorize(&directory)?;
+        report!(self.output, "{}", style("掃描檔案中...").dim());
+        let files = categorizer.scan_and_categorize(directory)?;
+        if let Some(size_filter) = categorizer.size_filter() {
+            report_size_filtered(size_filter, &self.output);
+        }
 
         if files.is_empty() {
-            println!("{}", style("找不到任何待分類的檔案").yellow());
-            return Ok(());
+            report!(self.output, "{}", style("找不到任何待分類的檔案").yellow());
+            return Ok(RunStatus::Completed);
         }
 
         // 顯示分類摘要
         self.print_category_summary(&files);
 
+        // 選擇移動或複製，以及分類資料夾的建立位置
+        let Some(mode) = self.prompt_transfer_mode()? else {
+            return Ok(RunStatus::Completed); // ESC pressed
+        };
+        let target_dir = self.prompt_target_directory(directory, mode)?;
+
+        // 僅預覽時列出目標路徑後結束
+        let Some(dry_run) = self.prompt_dry_run()? else {
+            return Ok(RunStatus::Completed); // ESC pressed
+        };
+        if dry_run {
+            let planned = categorizer.plan_moves(&files, &target_dir);
+            self.print_planned_moves(&planned, files.len(), mode);
+            return Ok(RunStatus::Completed);
+        }
+
         // 確認是否執行
-        if !self.confirm_move()? {
-            println!("{}", style("操作已取消").yellow());
-            return Ok(());
+        if !self.confirm_move(mode, &target_dir)? {
+            report!(self.output, "{}", style("操作已取消").yellow());
+            return Ok(RunStatus::Completed);
         }
 
         // 檢查中斷訊號
         if self.shutdown_signal.load(Ordering::SeqCst) {
             warn!("收到中斷訊號，停止處理");
-            return Ok(());
+            return Ok(RunStatus::Aborted);
         }
 
-        // 移動檔案
-        println!("{}", style("移動檔案中...").cyan());
-        let result = categorizer.move_files_to_categories(&files, &directory)?;
+        // 移動或複製檔案
+        report!(self.output, "{}", style(format!("{mode}檔案中...")).cyan());
+        let result = categorizer.move_files_to_categories(&files, &target_dir, mode)?;
 
         self.print_result(&result);
 
-        Ok(())
+        Ok(RunStatus::from_failures(result.errors))
+    }
+
+    fn run_undo(&self, directory: &Path) -> Result<RunStatus> {
+        if !self.interaction.confirm(
+            &format!(
+                "確定要將上一次整理的檔案移回原位置嗎？（{}）",
+                directory.display()
+            ),
+            true,
+        )? {
+            report!(self.output, "{}", style("操作已取消").yellow());
+            return Ok(RunStatus::Completed);
+        }
+
+        let result = undo_last_move(directory)?;
+        self.print_undo_result(&result);
+        Ok(RunStatus::from_failures(result.errors))
     }
 
-    fn prompt_input_path(&self) -> Result<Option<String>> {
-        let recent_paths = &self.config.settings.recent_paths;
+    fn run_by_date(&self, directory: &Path, settings: &mut UserSettings) -> Result<RunStatus> {
+        let pattern = self.prompt_date_pattern(settings)?;
+        let use_media_time = self.config.settings.date_organize.use_media_time;
 
-        // 如果沒有歷史路徑，直接輸入
-        if recent_paths.is_empty() {
-            let path: String = Input::new()
-                .with_prompt("請輸入要整理的資料夾路徑")
-                .interact_text()?;
-            return Ok(Some(path.trim().to_string()));
+        let organizer = DateOrganizer::new(
+            self.config.file_type_table.clone(),
+            Arc::clone(&self.shutdown_signal),
+        )
+        .with_pattern(&pattern)
+        .with_media_time(use_media_time)
+        .with_exclude_filter(ExcludeFilter::new(&self.config.settings.exclude_globs))
+        .with_size_filter(prompt_size_filter(
+            &self.config.settings,
+            self.interaction,
+            &self.output,
+        )?);
+
+        // 掃描並決定日期資料夾
+        report!(self.output, "{}", style("掃描檔案中...").dim());
+        if use_media_time {
+            report!(
+                self.output,
+                "{}",
+                style("影片優先使用建立時間，其餘檔案使用修改時間").dim()
+            );
+        }
+        let files = organizer.scan_and_date(directory)?;
+        if let Some(size_filter) = organizer.size_filter() {
+            report_size_filtered(size_filter, &self.output);
         }
 
-        // 建立選項清單：歷史路徑 + 輸入新路徑
-        let mut options: Vec<String> = recent_paths
-            .iter()
-            .enumerate()
-            .map(|(i, p)| {
-                let exists = Path::new(p).exists();
-                let indicator = if exists { "✓" } else { "✗" };
-                format!("{} [{}] {}", i + 1, indicator, p)
-            })
-            .collect();
-        options.push("輸入新路徑...".to_string());
+        if files.is_empty() {
+            report!(self.output, "{}", style("找不到任何待整理的檔案").yellow());
+            return Ok(RunStatus::Completed);
+        }
+
+        // 顯示日期資料夾摘要
+        self.print_date_summary(&files);
+
+        let Some(mode) = self.prompt_transfer_mode()? else {
+            return Ok(RunStatus::Completed); // ESC pressed
+        };
+        let target_dir = self.prompt_target_directory(directory, mode)?;
+
+        if !self.confirm_move(mode, &target_dir)? {
+            report!(self.output, "{}", style("操作已取消").yellow());
+            return Ok(RunStatus::Completed);
+        }
+
+        if self.shutdown_signal.load(Ordering::SeqCst) {
+            warn!("收到中斷訊號，停止處理");
+            return Ok(RunStatus::Aborted);
+        }
+
+        report!(self.output, "{}", style(format!("{mode}檔案中...")).cyan());
+        let result = organizer.move_files_to_date_folders(&files, &target_dir, mode)?;
+
+        self.print_date_result(&result);
 
-        println!("{}", style("(按 ESC 返回主選單)").dim());
+        Ok(RunStatus::from_failures(result.errors))
+    }
+
+    fn prompt_input_path(&self, settings: &mut UserSettings) -> Result<Option<String>> {
+        prompt_directory(
+            settings,
+            PathFeature::AutoMove,
+            "請輸入要整理的資料夾路徑",
+            &self.output,
+        )
+    }
+
+    /// 選擇整理依據，依類型整理時的資料夾結構變更時寫回設定
+    ///
+    /// 資料夾中有復原紀錄時，另外提供復原上一次整理的選項
+    fn prompt_organize_mode(
+        &self,
+        directory: &Path,
+        settings: &mut UserSettings,
+    ) -> Result<Option<OrganizeMode>> {
+        let mut modes = vec![
+            OrganizeMode::Category(OrganizeBy::Category),
+            OrganizeMode::Category(OrganizeBy::CategoryThenDate {
+                granularity: DateGranularity::Month,
+            }),
+            OrganizeMode::Category(OrganizeBy::CategoryThenDate {
+                granularity: DateGranularity::Year,
+            }),
+            OrganizeMode::Date,
+        ];
+        let mut options = vec![
+            "依檔案類型",
+            "依檔案類型，再依年/月分資料夾",
+            "依檔案類型，再依年份分資料夾",
+            "依日期（年/月資料夾）",
+        ];
+        if has_undo_log(directory) {
+            modes.push(OrganizeMode::Undo);
+            options.push("復原上一次移動的檔案");
+        }
+        let current = settings.organize_by;
+        let default = modes
+            .iter()
+            .position(|mode| *mode == OrganizeMode::Category(current))
+            .unwrap_or(0);
 
         let selection = Select::with_theme(&ColorfulTheme::default())
-            .with_prompt("請選擇路徑")
+            .with_prompt("請選擇整理依據")
             .items(&options)
-            .default(0)
+            .default(default)
             .interact_opt()?;
+        let Some(mode) = selection.map(|idx| modes[idx]) else {
+            return Ok(None);
+        };
+
+        if let OrganizeMode::Category(organize_by) = mode {
+            settings.organize_by = organize_by;
+        }
+        Ok(Some(mode))
+    }
 
-        match selection {
-            None => Ok(None),
-            Some(idx) if idx < recent_paths.len() => Ok(Some(recent_paths[idx].clone())),
-            Some(_) => {
-                let path: String = Input::new()
-                    .with_prompt("請輸入要整理的資料夾路徑")
-                    .interact_text()?;
-                Ok(Some(path.trim().to_string()))
+    /// 輸入日期資料夾格式，變更時寫回設定
+    fn prompt_date_pattern(&self, settings: &mut UserSettings) -> Result<String> {
+        let current = &settings.date_organize.pattern;
+        report!(
+            self.output,
+            "{}",
+            style("可使用 {year}、{month}、{day}，以 / 分隔子資料夾").dim()
+        );
+        let pattern: String = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("日期資料夾格式")
+            .with_initial_text(current.clone())
+            .validate_with(|input: &String| validate_pattern(input.trim()))
+            .interact_text()?;
+        let pattern = pattern.trim().to_string();
+
+        settings.date_organize.pattern.clone_from(&pattern);
+        Ok(pattern)
+    }
+
+    fn prompt_transfer_mode(&self) -> Result<Option<TransferMode>> {
+        if !self.interaction.prompts_options() {
+            return Ok(Some(self.transfer_mode));
+        }
+        let options = ["移動（整理後原位置不再保留）", "複製（保留原始檔案）"];
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("請選擇整理方式")
+            .items(options)
+            .default(0)
+            .interact_opt()?;
+        Ok(selection.map(|idx| {
+            if idx == 0 {
+                TransferMode::Move
+            } else {
+                TransferMode::Copy
             }
+        }))
+    }
+
+    /// 選擇實際執行或僅預覽，回傳是否僅預覽
+    fn prompt_dry_run(&self) -> Result<Option<bool>> {
+        if !self.interaction.prompts_options() {
+            return Ok(Some(self.dry_run));
         }
+        let options = ["實際執行", "僅預覽目標路徑（不移動檔案）"];
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("請選擇執行方式")
+            .items(options)
+            .default(0)
+            .interact_opt()?;
+        Ok(selection.map(|idx| idx == 1))
+    }
+
+    /// 輸入分類資料夾的建立位置（預設為掃描的資料夾）
+    fn prompt_target_directory(&self, scan_dir: &Path, mode: TransferMode) -> Result<PathBuf> {
+        if !self.interaction.prompts_options() {
+            return Ok(scan_dir.to_path_buf());
+        }
+        let target = prompt_output_directory(
+            &format!("{mode}到哪個資料夾（分類資料夾建立於此，留空 = 掃描的資料夾）"),
+            &scan_dir.display().to_string(),
+            &self.output,
+        )?;
+        Ok(target.unwrap_or_else(|| scan_dir.to_path_buf()))
     }
 
-    fn confirm_move(&self) -> Result<bool> {
-        let confirm = Confirm::new()
-            .with_prompt("確定要移動這些檔案嗎？")
-            .default(true)
-            .interact()?;
-        Ok(confirm)
+    fn confirm_move(&self, mode: TransferMode, target_dir: &Path) -> Result<bool> {
+        self.interaction.confirm(
+            &format!("確定要將這些檔案{mode}到 {} 嗎？", target_dir.display()),
+            true,
+        )
     }
 
     fn print_category_summary(&self, files: &[CategorizedFile]) {
@@ -145,12 +450,13 @@ impl AutoMoveByType {
             entry.1 += file.size;
         }
 
-        println!();
-        println!(
+        report!(self.output);
+        report!(
+            self.output,
             "{}",
             style(format!("找到 {} 個檔案，分類如下：", files.len())).green()
         );
-        println!();
+        report!(self.output);
 
         // 按檔案數量排序
         let mut sorted_counts: Vec<_> = counts.into_iter().collect();
@@ -158,10 +464,12 @@ impl AutoMoveByType {
 
         for (category, (count, size)) in sorted_counts {
             let size_mb = size as f64 / 1024.0 / 1024.0;
-            let folder_name = category.folder_name();
+            let folder_name =
+                category.folder_name_with(&self.config.settings.category_folder_overrides);
             let display_name = category.display_name();
 
-            println!(
+            report!(
+                self.output,
                 "  {} {} ({}) - {} 個檔案，{:.2} MB",
                 style("→").dim(),
                 style(folder_name).cyan(),
@@ -171,32 +479,105 @@ impl AutoMoveByType {
             );
         }
 
-        println!();
+        report!(self.output);
+    }
+
+    fn print_planned_moves(
+        &self,
+        planned: &[(PathBuf, PathBuf)],
+        total: usize,
+        mode: TransferMode,
+    ) {
+        if self
+            .output
+            .emit_json(&PlannedMoves::new(planned, total, mode))
+        {
+            return;
+        }
+        report!(self.output);
+        report!(
+            self.output,
+            "{}",
+            style("=== 預覽結果（未移動任何檔案） ===").cyan().bold()
+        );
+        for (source, target) in planned {
+            report!(
+                self.output,
+                "  {} {} {}",
+                source.display(),
+                style("→").dim(),
+                style(target.display()).cyan()
+            );
+        }
+
+        report!(self.output);
+        report!(
+            self.output,
+            "  預計{mode}: {} 個檔案",
+            style(planned.len()).green()
+        );
+        let skipped = total - planned.len();
+        if skipped > 0 {
+            report!(
+                self.output,
+                "  將跳過: {} 個檔案（目標已有相同檔案）",
+                style(skipped).yellow()
+            );
+        }
     }
 
     fn print_result(&self, result: &CategorizationResult) {
-        println!();
-        println!("{}", style("=== 整理結果 ===").cyan().bold());
-        println!("  成功移動: {} 個檔案", style(result.files_moved).green());
+        if self.output.emit_json(result) {
+            return;
+        }
+        report!(self.output);
+        report!(self.output, "{}", style("=== 整理結果 ===").cyan().bold());
+        if result.files_moved > 0 {
+            report!(
+                self.output,
+                "  成功移動: {} 個檔案",
+                style(result.files_moved).green()
+            );
+        }
+        if result.files_copied > 0 {
+            report!(
+                self.output,
+                "  成功複製: {} 個檔案",
+                style(result.files_copied).green()
+            );
+        }
 
         if result.skipped > 0 {
-            println!("  已跳過: {} 個檔案", style(result.skipped).yellow());
+            report!(
+                self.output,
+                "  已跳過: {} 個檔案",
+                style(result.skipped).yellow()
+            );
         }
 
         if result.errors > 0 {
-            println!("  失敗: {} 個檔案", style(result.errors).red());
+            report!(self.output, "  失敗: {} 個檔案", style(result.errors).red());
+        }
+
+        if result.undo_log_written {
+            report!(
+                self.output,
+                "{}",
+                style("可再次選擇目標資料夾並選擇「復原」將檔案移回原位置").dim()
+            );
         }
 
         // 顯示各分類的統計
         if !result.category_counts.is_empty() {
-            println!();
-            println!("{}", style("分類統計:").dim());
+            report!(self.output);
+            report!(self.output, "{}", style("分類統計:").dim());
 
             let mut sorted_counts: Vec<_> = result.category_counts.iter().collect();
             sorted_counts.sort_by(|a, b| b.1.cmp(a.1));
 
             for (category, count) in sorted_counts {
-                println!(
+                report!(
+                    self.output,
                     "  {} {}: {} 個",
                     style("•").dim(),
                     category.display_name(),
@@ -206,8 +587,145 @@ impl AutoMoveByType {
         }
 
         info!(
-            "檔案整理完成 - 移動: {}, 跳過: {}, 失敗: {}",
-            result.files_moved, result.skipped, result.errors
+            "檔案整理完成 - 移動: {}, 複製: {}, 跳過: {}, 失敗: {}",
+            result.files_moved, result.files_copied, result.skipped, result.errors
+        );
+        self.output.report_log_file();
+    }
+
+    fn print_undo_result(&self, result: &UndoResult) {
+        report!(self.output);
+        report!(self.output, "{}", style("=== 復原結果 ===").cyan().bold());
+        report!(
+            self.output,
+            "  已移回: {} 個檔案",
+            style(result.restored).green()
+        );
+        if result.skipped > 0 {
+            report!(
+                self.output,
+                "  已略過: {} 個檔案（之後已被移動或變更）",
+                style(result.skipped).yellow()
+            );
+        }
+        if result.errors > 0 {
+            report!(
+                self.output,
+                "  失敗: {} 個檔案（保留在復原紀錄中）",
+                style(result.errors).red()
+            );
+        }
+        if result.folders_removed > 0 {
+            report!(self.output, "  移除空資料夾: {} 個", result.folders_removed);
+        }
+        self.output.report_log_file();
+    }
+
+    fn print_date_summary(&self, files: &[DatedFile]) {
+        let mut counts: BTreeMap<&Path, (usize, u64)> = BTreeMap::new();
+        for file in files {
+            let entry = counts.entry(file.folder.as_path()).or_insert((0, 0));
+            entry.0 += 1;
+            entry.1 += file.size;
+        }
+
+        report!(self.output);
+        report!(
+            self.output,
+            "{}",
+            style(format!("找到 {} 個檔案，依日期分組如下：", files.len())).green()
+        );
+        report!(self.output);
+
+        for (folder, (count, size)) in counts {
+            let size_mb = size as f64 / 1024.0 / 1024.0;
+            report!(
+                self.output,
+                "  {} {} - {} 個檔案，{:.2} MB",
+                style("→").dim(),
+                style(folder.display()).cyan(),
+                count,
+                size_mb
+            );
+        }
+
+        report!(self.output);
+    }
+
+    fn print_date_result(&self, result: &DateOrganizeResult) {
+        report!(self.output);
+        report!(self.output, "{}", style("=== 整理結果 ===").cyan().bold());
+        if result.files_moved > 0 {
+            report!(
+                self.output,
+                "  成功移動: {} 個檔案",
+                style(result.files_moved).green()
+            );
+        }
+        if result.files_copied > 0 {
+            report!(
+                self.output,
+                "  成功複製: {} 個檔案",
+                style(result.files_copied).green()
+            );
+        }
+        if result.skipped > 0 {
+            report!(
+                self.output,
+                "  已跳過: {} 個檔案",
+                style(result.skipped).yellow()
+            );
+        }
+        if result.errors > 0 {
+            report!(self.output, "  失敗: {} 個檔案", style(result.errors).red());
+        }
+        if result.undo_log_written {
+            report!(
+                self.output,
+                "{}",
+                style("可再次選擇目標資料夾並選擇「復原」將檔案移回原位置").dim()
+            );
+        }
+
+        if !result.folder_counts.is_empty() {
+            report!(self.output);
+            report!(self.output, "{}", style("日期資料夾統計:").dim());
+            for (folder, count) in &result.folder_counts {
+                report!(
+                    self.output,
+                    "  {} {}: {} 個",
+                    style("•").dim(),
+                    folder.display(),
+                    count
+                );
+            }
+        }
+
+        info!(
+            "依日期整理完成 - 移動: {}, 複製: {}, 跳過: {}, 失敗: {}",
+            result.files_moved, result.files_copied, result.skipped, result.errors
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_planned_moves_json_schema() {
+        let planned = vec![(
+            PathBuf::from("/a/clip.mp4"),
+            PathBuf::from("/a/video/clip.mp4"),
+        )];
+        assert_eq!(
+            serde_json::to_value(PlannedMoves::new(&planned, 3, TransferMode::Move)).unwrap(),
+            json!({
+                "copy": false,
+                "moves": [{"source": "/a/clip.mp4", "target": "/a/video/clip.mp4"}],
+                "skipped": 2
+            })
         );
     }
 }
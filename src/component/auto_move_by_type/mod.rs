@@ -1,9 +1,19 @@
 //! 自動依類型移動檔案元件
 //!
-//! 掃描資料夾中的檔案，根據副檔名自動分類並移動到對應的資料夾
+//! 掃描資料夾中的檔案，根據副檔名自動分類並移動到對應的資料夾，
+//! 或依檔案日期整理到年/月資料夾；移動後可復原上一次的整理
 
+mod date_organizer;
 mod file_categorizer;
 mod main;
+mod undo_log;
 
+pub use date_organizer::{
+    DateOrganizeResult, DateOrganizer, DatedFile, FileDate, validate_pattern,
+};
 pub use file_categorizer::{CategorizationResult, CategorizedFile, FileCategorizer};
 pub use main::AutoMoveByType;
+pub use undo_log::{
+    MoveRecord, UNDO_LOG_FILE, UndoResult, has_undo_log, undo_last_move, undo_moves_at,
+    write_undo_log, write_undo_log_at,
+};
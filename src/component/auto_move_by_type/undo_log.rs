@@ -0,0 +1,246 @@
+//! 整理復原紀錄
+//!
+//! 依類型或日期移動檔案後，將每個成功的移動寫入目標資料夾中的 `.auto_move_undo.json`，
+//! 復原時只還原目前仍位於紀錄位置的檔案，之後又被移動過的檔案保持原狀。
+//! 影片重新命名也以相同格式寫入自己的復原紀錄。
+
+use crate::tools::{ensure_directory_exists, move_file};
+use anyhow::{Context, Result};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 復原紀錄檔名稱
+pub const UNDO_LOG_FILE: &str = ".auto_move_undo.json";
+
+/// 單一檔案的移動紀錄
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MoveRecord {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    /// 移動時的檔案大小，復原前用來確認仍是同一個檔案
+    pub size: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct UndoLog {
+    /// 紀錄建立時間（Unix 秒數）
+    #[serde(default)]
+    created_at: u64,
+    moves: Vec<MoveRecord>,
+}
+
+/// 復原結果
+#[derive(Debug, Default)]
+pub struct UndoResult {
+    /// 已移回原位置的檔案數
+    pub restored: usize,
+    /// 已不在紀錄位置（之後又被移動或刪除）而略過的檔案數
+    pub skipped: usize,
+    /// 原位置已有檔案或移動失敗的檔案數（保留在復原紀錄中）
+    pub errors: usize,
+    /// 移除的空資料夾數
+    pub folders_removed: usize,
+}
+
+/// 復原紀錄檔的路徑
+#[must_use]
+pub fn undo_log_path(base_dir: &Path) -> PathBuf {
+    base_dir.join(UNDO_LOG_FILE)
+}
+
+/// 資料夾中是否有可復原的整理紀錄
+#[must_use]
+pub fn has_undo_log(base_dir: &Path) -> bool {
+    undo_log_path(base_dir).is_file()
+}
+
+/// 寫入復原紀錄（覆蓋上一次的紀錄）
+pub fn write_undo_log(base_dir: &Path, moves: Vec<MoveRecord>) -> Result<()> {
+    write_undo_log_at(&undo_log_path(base_dir), moves)
+}
+
+/// 寫入指定路徑的復原紀錄（覆蓋上一次的紀錄）
+pub fn write_undo_log_at(log_path: &Path, moves: Vec<MoveRecord>) -> Result<()> {
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    save(log_path, &UndoLog { created_at, moves })
+}
+
+fn save(path: &Path, log: &UndoLog) -> Result<()> {
+    let content = serde_json::to_string_pretty(log).with_context(|| "無法序列化復原紀錄")?;
+    fs::write(path, content).with_context(|| format!("無法寫入復原紀錄: {}", path.display()))
+}
+
+/// 復原上一次的整理，將檔案移回原位置並移除變空的資料夾
+///
+/// 只還原目前位於紀錄位置且大小相同的檔案；失敗的項目留在復原紀錄中，
+/// 全部處理完畢時刪除復原紀錄
+pub fn undo_last_move(base_dir: &Path) -> Result<UndoResult> {
+    undo_moves_at(&undo_log_path(base_dir), base_dir)
+}
+
+/// 依指定路徑的復原紀錄將檔案移回原位置，只移除 `base_dir` 之內變空的資料夾
+pub fn undo_moves_at(path: &Path, base_dir: &Path) -> Result<UndoResult> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("無法讀取復原紀錄: {}", path.display()))?;
+    let log: UndoLog = serde_json::from_str(&content)
+        .with_context(|| format!("無法解析復原紀錄: {}", path.display()))?;
+
+    let mut result = UndoResult::default();
+    let mut failed = Vec::new();
+    let mut touched_dirs = Vec::new();
+
+    for record in log.moves.into_iter().rev() {
+        if !fs::metadata(&record.to).is_ok_and(|m| m.is_file() && m.len() == record.size) {
+            debug!("檔案已不在紀錄位置，略過: {}", record.to.display());
+            result.skipped += 1;
+            continue;
+        }
+        if record.from.exists() {
+            warn!("原位置已有檔案，無法復原: {}", record.from.display());
+            result.errors += 1;
+            failed.push(record);
+            continue;
+        }
+
+        let restored = record
+            .from
+            .parent()
+            .map_or(Ok(()), ensure_directory_exists)
+            .and_then(|()| move_file(&record.to, &record.from));
+        match restored {
+            Ok(()) => {
+                debug!(
+                    "復原檔案: {} -> {}",
+                    record.to.display(),
+                    record.from.display()
+                );
+                if let Some(parent) = record.to.parent() {
+                    touched_dirs.push(parent.to_path_buf());
+                }
+                result.restored += 1;
+            }
+            Err(e) => {
+                warn!("復原檔案失敗 {}: {e:#}", record.to.display());
+                result.errors += 1;
+                failed.push(record);
+            }
+        }
+    }
+
+    result.folders_removed = remove_empty_dirs(touched_dirs, base_dir);
+
+    if failed.is_empty() {
+        fs::remove_file(path).with_context(|| format!("無法刪除復原紀錄: {}", path.display()))?;
+    } else {
+        failed.reverse();
+        save(
+            path,
+            &UndoLog {
+                created_at: log.created_at,
+                moves: failed,
+            },
+        )?;
+    }
+
+    info!(
+        "復原完成 - 還原: {}, 略過: {}, 失敗: {}",
+        result.restored, result.skipped, result.errors
+    );
+    Ok(result)
+}
+
+/// 由下往上移除變空的資料夾，不會移除 `base_dir` 本身或其外的資料夾
+fn remove_empty_dirs(mut dirs: Vec<PathBuf>, base_dir: &Path) -> usize {
+    // 較深的資料夾先處理，讓上層資料夾有機會變空
+    dirs.sort_by(|a, b| {
+        b.components()
+            .count()
+            .cmp(&a.components().count())
+            .then_with(|| a.cmp(b))
+    });
+    dirs.dedup();
+
+    let mut removed = 0;
+    for dir in dirs {
+        let mut current = dir.as_path();
+        while current != base_dir && current.starts_with(base_dir) {
+            if fs::remove_dir(current).is_err() {
+                break;
+            }
+            removed += 1;
+            let Some(parent) = current.parent() else {
+                break;
+            };
+            current = parent;
+        }
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn record(base: &Path, from: &str, to: &str) -> MoveRecord {
+        let to = base.join(to);
+        fs::create_dir_all(to.parent().unwrap()).unwrap();
+        fs::write(&to, from).unwrap();
+        MoveRecord {
+            from: base.join(from),
+            to,
+            size: from.len() as u64,
+        }
+    }
+
+    #[test]
+    fn test_undo_restores_files_and_removes_empty_folders() {
+        let dir = TempDir::new().unwrap();
+        let base = dir.path();
+        let moves = vec![
+            record(base, "a.jpg", "image/a.jpg"),
+            record(base, "sub/b.mp4", "video/2023/06/b.mp4"),
+        ];
+        write_undo_log(base, moves).unwrap();
+        assert!(has_undo_log(base));
+
+        let result = undo_last_move(base).unwrap();
+        assert_eq!(result.restored, 2);
+        assert_eq!(result.folders_removed, 4);
+        assert_eq!(fs::read_to_string(base.join("a.jpg")).unwrap(), "a.jpg");
+        assert!(base.join("sub/b.mp4").exists());
+        assert!(!base.join("image").exists());
+        assert!(!base.join("video").exists());
+        assert!(!has_undo_log(base));
+    }
+
+    #[test]
+    fn test_undo_skips_files_moved_since() {
+        let dir = TempDir::new().unwrap();
+        let base = dir.path();
+        let moved_again = record(base, "a.jpg", "image/a.jpg");
+        let replaced = record(base, "b.jpg", "image/b.jpg");
+        let conflict = record(base, "c.jpg", "image/c.jpg");
+        fs::rename(&moved_again.to, base.join("elsewhere.jpg")).unwrap();
+        fs::write(&replaced.to, "different size").unwrap();
+        fs::write(&conflict.from, "new file").unwrap();
+        write_undo_log(base, vec![moved_again, replaced, conflict.clone()]).unwrap();
+
+        let result = undo_last_move(base).unwrap();
+        assert_eq!(result.restored, 0);
+        assert_eq!(result.skipped, 2);
+        assert_eq!(result.errors, 1);
+        assert!(!base.join("a.jpg").exists());
+        assert_eq!(fs::read_to_string(&conflict.from).unwrap(), "new file");
+
+        // 無法復原的項目保留在紀錄中
+        let log: UndoLog =
+            serde_json::from_str(&fs::read_to_string(undo_log_path(base)).unwrap()).unwrap();
+        assert_eq!(log.moves, vec![conflict]);
+    }
+}
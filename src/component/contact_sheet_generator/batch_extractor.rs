@@ -1,3 +1,4 @@
+use crate::tools::resolve_ffmpeg;
 use anyhow::{Context, Result};
 use log::{debug, info, warn};
 use std::path::{Path, PathBuf};
@@ -6,6 +7,10 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 
 use super::thumbnail_extractor::{THUMBNAIL_HEIGHT, THUMBNAIL_WIDTH};
+use super::timestamp_overlay::TimestampOverlay;
+
+/// select 濾鏡擷取時間點的容差（前後各半）
+const SELECT_TOLERANCE: f64 = 0.05;
 
 /// 批次擷取結果
 #[derive(Debug)]
@@ -23,6 +28,8 @@ pub struct BatchExtractorConfig {
     pub height: u32,
     /// JPEG 品質 (1-31，數字越小品質越高)
     pub quality: u8,
+    /// 在縮圖上疊加時間戳記（`None` 表示不疊加）
+    pub timestamp_overlay: Option<TimestampOverlay>,
 }
 
 impl Default for BatchExtractorConfig {
@@ -31,6 +38,7 @@ impl Default for BatchExtractorConfig {
             width: THUMBNAIL_WIDTH,
             height: THUMBNAIL_HEIGHT,
             quality: 2,
+            timestamp_overlay: None,
         }
     }
 }
@@ -116,14 +124,12 @@ fn extract_batch(
     // 使用 between(t, start, end) 確保能捕捉到目標時間
     let select_expr = build_select_expression(timestamps);
 
-    // 建立縮放濾鏡
-    let scale_filter = format!(
-        "scale={}:{}:force_original_aspect_ratio=decrease,pad={}:{}:(ow-iw)/2:(oh-ih)/2:black",
-        config.width, config.height, config.width, config.height
-    );
-
     // 完整的濾鏡鏈
-    let filter_complex = format!("{select_expr},{scale_filter}");
+    let mut filter_complex = format!("{select_expr},{}", build_scale_filter(config));
+    if let Some(overlay) = &config.timestamp_overlay {
+        filter_complex.push(',');
+        filter_complex.push_str(&build_batch_overlay_filter(overlay, timestamps));
+    }
 
     // 輸出路徑模板
     let output_pattern = output_dir.join(format!("thumb_{:03}_%03d.jpg", start_index / 18));
@@ -146,7 +152,7 @@ fn extract_batch(
 
     debug!("執行批次擷取: ffmpeg {}", args.join(" "));
 
-    let output = Command::new("ffmpeg")
+    let output = Command::new(resolve_ffmpeg()?)
         .args(&args)
         .output()
         .with_context(|| "無法執行 ffmpeg 批次擷取")?;
@@ -205,6 +211,14 @@ fn extract_batch(
     })
 }
 
+/// 時間點的選取範圍
+fn select_window(timestamp: f64) -> (f64, f64) {
+    (
+        (timestamp - SELECT_TOLERANCE).max(0.0),
+        timestamp + SELECT_TOLERANCE,
+    )
+}
+
 /// 建立 select 濾鏡表達式
 fn build_select_expression(timestamps: &[f64]) -> String {
     // 使用 between 確保能捕捉到目標時間附近的幀
@@ -212,8 +226,7 @@ fn build_select_expression(timestamps: &[f64]) -> String {
     let conditions: Vec<String> = timestamps
         .iter()
         .map(|&t| {
-            let start = (t - 0.05).max(0.0);
-            let end = t + 0.05;
+            let (start, end) = select_window(t);
             format!("between(t\\,{start:.3}\\,{end:.3})")
         })
         .collect();
@@ -221,6 +234,28 @@ fn build_select_expression(timestamps: &[f64]) -> String {
     format!("select='{}'", conditions.join("+"))
 }
 
+/// 建立縮放和填充濾鏡
+fn build_scale_filter(config: &BatchExtractorConfig) -> String {
+    format!(
+        "scale={}:{}:force_original_aspect_ratio=decrease,pad={}:{}:(ow-iw)/2:(oh-ih)/2:black",
+        config.width, config.height, config.width, config.height
+    )
+}
+
+/// 建立批次擷取的時間戳記濾鏡
+///
+/// 每個時間點各一段 drawtext，只在與 select 相同的範圍內顯示
+fn build_batch_overlay_filter(overlay: &TimestampOverlay, timestamps: &[f64]) -> String {
+    timestamps
+        .iter()
+        .map(|&t| {
+            let (start, end) = select_window(t);
+            overlay.drawtext_filter_between(t, start, end)
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
 /// 逐一擷取（降級方案）
 fn extract_individually(
     video_path: &Path,
@@ -274,10 +309,11 @@ fn extract_single_thumbnail(
     let t0 = (timestamp - seek_margin).max(0.0);
     let delta = timestamp - t0;
 
-    let scale_filter = format!(
-        "scale={}:{}:force_original_aspect_ratio=decrease,pad={}:{}:(ow-iw)/2:(oh-ih)/2:black",
-        config.width, config.height, config.width, config.height
-    );
+    let mut filter = build_scale_filter(config);
+    if let Some(overlay) = &config.timestamp_overlay {
+        filter.push(',');
+        filter.push_str(&overlay.drawtext_filter(timestamp));
+    }
 
     let mut args = vec![
         "-hide_banner".to_string(),
@@ -304,14 +340,14 @@ fn extract_single_thumbnail(
         "-frames:v".to_string(),
         "1".to_string(),
         "-vf".to_string(),
-        scale_filter,
+        filter,
         "-q:v".to_string(),
         config.quality.to_string(),
         "-y".to_string(),
         output_path.to_string_lossy().to_string(),
     ]);
 
-    let output = Command::new("ffmpeg")
+    let output = Command::new(resolve_ffmpeg()?)
         .args(&args)
         .output()
         .with_context(|| "無法執行 ffmpeg 擷取縮圖")?;
@@ -330,7 +366,7 @@ fn extract_single_thumbnail(
 
 /// 產生黑色替代圖片
 fn generate_black_placeholder(output_path: &Path, config: &BatchExtractorConfig) -> Result<()> {
-    let output = Command::new("ffmpeg")
+    let output = Command::new(resolve_ffmpeg()?)
         .args([
             "-hide_banner",
             "-loglevel",
@@ -375,5 +411,21 @@ mod tests {
         assert_eq!(config.width, THUMBNAIL_WIDTH);
         assert_eq!(config.height, THUMBNAIL_HEIGHT);
         assert_eq!(config.quality, 2);
+        assert!(config.timestamp_overlay.is_none());
+    }
+
+    #[test]
+    fn test_build_batch_overlay_filter() {
+        let overlay = TimestampOverlay {
+            font_file: PathBuf::from("/f.ttf"),
+        };
+        let filter = build_batch_overlay_filter(&overlay, &[1.0, 65.0]);
+
+        let parts: Vec<&str> = filter.split(",drawtext=").collect();
+        assert_eq!(parts.len(), 2);
+        assert!(parts[0].contains("text='00\\:00\\:01'"));
+        assert!(parts[0].contains("enable='between(t\\,0.950\\,1.050)'"));
+        assert!(parts[1].contains("text='00\\:01\\:05'"));
+        assert!(parts[1].contains("enable='between(t\\,64.950\\,65.050)'"));
     }
 }
@@ -1,7 +1,12 @@
 use super::thumbnail_extractor::{THUMBNAIL_HEIGHT, THUMBNAIL_WIDTH};
+use super::timestamp_overlay::{
+    escape_drawtext_text, escape_filter_value, format_timestamp, is_cjk_font,
+};
+use crate::config::ContactSheetFormat;
+use crate::tools::{VideoInfo, resolve_ffmpeg};
 use anyhow::{Context, Result};
 use log::debug;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 /// 預設網格配置：9 欄 x 6 列 = 54 張縮圖
@@ -9,6 +14,107 @@ pub const DEFAULT_GRID_COLS: usize = 9;
 pub const DEFAULT_GRID_ROWS: usize = 6;
 pub const DEFAULT_THUMBNAIL_COUNT: usize = DEFAULT_GRID_COLS * DEFAULT_GRID_ROWS;
 
+/// 標頭高度（兩行文字）
+pub const HEADER_HEIGHT: u32 = 64;
+
+/// 標頭文字大小與邊距（像素）
+const HEADER_FONT_SIZE: u32 = 20;
+const HEADER_PADDING: u32 = 10;
+
+/// PNG 壓縮等級（0-9，無損，只影響檔案大小與速度）
+const PNG_COMPRESSION_LEVEL: u8 = 9;
+
+/// 預覽圖輸出編碼設定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SheetEncoding {
+    pub format: ContactSheetFormat,
+    /// 品質（1-100，數字越大品質越高）
+    pub quality: u8,
+}
+
+impl Default for SheetEncoding {
+    fn default() -> Self {
+        Self {
+            format: ContactSheetFormat::Jpeg,
+            quality: 90,
+        }
+    }
+}
+
+impl SheetEncoding {
+    /// 依格式產生 ffmpeg 編碼參數
+    ///
+    /// JPEG 使用 `-q:v`（2-31，數字越小品質越高），
+    /// PNG 使用 `-compression_level`，WebP 使用 libwebp 的 `-quality`
+    fn codec_args(&self) -> Vec<String> {
+        let quality = u32::from(self.quality.clamp(1, 100));
+        match self.format {
+            ContactSheetFormat::Jpeg => {
+                let qscale = 2 + (100 - quality) * 29 / 100;
+                vec!["-q:v".to_string(), qscale.to_string()]
+            }
+            ContactSheetFormat::Png => vec![
+                "-compression_level".to_string(),
+                PNG_COMPRESSION_LEVEL.to_string(),
+            ],
+            ContactSheetFormat::Webp => vec![
+                "-c:v".to_string(),
+                "libwebp".to_string(),
+                "-quality".to_string(),
+                quality.to_string(),
+            ],
+        }
+    }
+}
+
+/// 預覽圖標頭資訊
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SheetHeader {
+    /// 第一行：檔名
+    pub title: String,
+    /// 第二行：時長、解析度、編碼、檔案大小
+    pub details: String,
+    /// drawtext 使用的字型檔
+    pub font_file: PathBuf,
+}
+
+impl SheetHeader {
+    /// 由影片資訊建立標頭，過長的檔名會依預覽圖寬度截斷
+    ///
+    /// 字型沒有 CJK 字形時標籤改用英文、檔名中無法顯示的字元改為 `?`，避免顯示為方框
+    #[must_use]
+    pub fn new(
+        file_name: &str,
+        video_info: &VideoInfo,
+        file_size: u64,
+        font_file: &Path,
+        grid_cols: usize,
+    ) -> Self {
+        let sheet_width = grid_cols as u32 * THUMBNAIL_WIDTH;
+        let cjk_font = is_cjk_font(font_file);
+        let duration_label = if cjk_font { "時長" } else { "Duration" };
+        let title = if cjk_font {
+            file_name.to_string()
+        } else {
+            replace_non_latin(file_name)
+        };
+        let details = format!(
+            "{duration_label} {}  |  {}x{}  |  {}  |  {}",
+            format_timestamp(video_info.duration_seconds),
+            video_info.width,
+            video_info.height,
+            video_info.codec_name.as_deref().unwrap_or("unknown"),
+            format_file_size(file_size)
+        );
+
+        Self {
+            title: truncate_to_width(&title, sheet_width),
+            details: truncate_to_width(&details, sheet_width),
+            font_file: font_file.to_path_buf(),
+        }
+    }
+}
+
 /// 使用 ffmpeg xstack 濾鏡合併縮圖為預覽圖
 ///
 /// xstack 濾鏡比 tile 濾鏡更靈活，可以精確控制每張圖的位置
@@ -17,6 +123,51 @@ pub fn create_contact_sheet(
     output_path: &Path,
     grid_cols: usize,
     grid_rows: usize,
+    encoding: SheetEncoding,
+) -> Result<()> {
+    let expected_count = grid_cols * grid_rows;
+    let layout = build_xstack_layout(grid_cols, grid_rows);
+    let filter = format!("xstack=inputs={expected_count}:layout={layout}");
+
+    merge_thumbnails(
+        thumbnails,
+        output_path,
+        grid_cols,
+        grid_rows,
+        filter,
+        encoding,
+    )
+}
+
+/// 合併縮圖並在上方加入檔案資訊標頭
+///
+/// 標頭為與網格同寬的純色背景，以 drawtext 繪製兩行文字，再以 vstack 疊在網格上方
+pub fn create_contact_sheet_with_header(
+    thumbnails: &[impl AsRef<Path>],
+    output_path: &Path,
+    grid_cols: usize,
+    grid_rows: usize,
+    header: &SheetHeader,
+    encoding: SheetEncoding,
+) -> Result<()> {
+    let filter = build_header_filter_graph(grid_cols, grid_rows, header);
+    merge_thumbnails(
+        thumbnails,
+        output_path,
+        grid_cols,
+        grid_rows,
+        filter,
+        encoding,
+    )
+}
+
+fn merge_thumbnails(
+    thumbnails: &[impl AsRef<Path>],
+    output_path: &Path,
+    grid_cols: usize,
+    grid_rows: usize,
+    filter: String,
+    encoding: SheetEncoding,
 ) -> Result<()> {
     let expected_count = grid_cols * grid_rows;
     if thumbnails.len() < expected_count {
@@ -34,10 +185,6 @@ pub fn create_contact_sheet(
         grid_rows
     );
 
-    // 建立 xstack 佈局字串
-    // 格式: 0_0|w0_0|w0+w1_0|...|0_h0|w0_h0|...
-    let layout = build_xstack_layout(grid_cols, grid_rows);
-
     // 建立 ffmpeg 命令參數
     let mut args: Vec<String> = vec![
         "-hide_banner".to_string(),
@@ -52,19 +199,16 @@ pub fn create_contact_sheet(
         debug!("輸入 [{}]: {}", i, thumb.as_ref().display());
     }
 
-    // 建立 filter_complex
-    let filter = format!("xstack=inputs={expected_count}:layout={layout}");
-
     args.extend([
         "-filter_complex".to_string(),
         filter,
         "-frames:v".to_string(),
         "1".to_string(),
-        "-y".to_string(),
-        output_path.to_string_lossy().to_string(),
     ]);
+    args.extend(encoding.codec_args());
+    args.extend(["-y".to_string(), output_path.to_string_lossy().to_string()]);
 
-    let output = Command::new("ffmpeg")
+    let output = Command::new(resolve_ffmpeg()?)
         .args(&args)
         .output()
         .with_context(|| "無法執行 ffmpeg 合併預覽圖")?;
@@ -82,6 +226,84 @@ pub fn create_contact_sheet(
     Ok(())
 }
 
+/// 建立含標頭的濾鏡圖
+///
+/// 格式：`[0:v]...[N:v]xstack[grid];color,drawtext,drawtext[header];[header][grid]vstack`
+fn build_header_filter_graph(grid_cols: usize, grid_rows: usize, header: &SheetHeader) -> String {
+    let count = grid_cols * grid_rows;
+    let inputs: String = (0..count).map(|i| format!("[{i}:v]")).collect();
+    let layout = build_xstack_layout(grid_cols, grid_rows);
+    let (width, _) = calculate_contact_sheet_size(grid_cols, grid_rows);
+    let font_file = escape_filter_value(&header.font_file.to_string_lossy());
+
+    let draw_line = |text: &str, y: u32| {
+        format!(
+            "drawtext=fontfile='{font_file}':expansion=none:text='{}':fontsize={HEADER_FONT_SIZE}:fontcolor=white:x={HEADER_PADDING}:y={y}",
+            escape_drawtext_text(text)
+        )
+    };
+
+    format!(
+        "{inputs}xstack=inputs={count}:layout={layout}[grid];color=c=0x202020:s={width}x{HEADER_HEIGHT}:d=1,{},{}[header];[header][grid]vstack=inputs=2",
+        draw_line(&header.title, HEADER_PADDING),
+        draw_line(&header.details, HEADER_PADDING + HEADER_FONT_SIZE + 8)
+    )
+}
+
+/// 依可用寬度截斷文字，超出時以 `…` 結尾
+///
+/// 以字型大小估算字寬：ASCII 約 0.6 倍，其他（例如中日韓文字）視為 1 倍
+fn truncate_to_width(text: &str, width: u32) -> String {
+    let char_width = |c: char| {
+        if c.is_ascii() {
+            HEADER_FONT_SIZE * 3 / 5
+        } else {
+            HEADER_FONT_SIZE
+        }
+    };
+    let available = width.saturating_sub(HEADER_PADDING * 2);
+
+    let total: u32 = text.chars().map(char_width).sum();
+    if total <= available {
+        return text.to_string();
+    }
+
+    let budget = available.saturating_sub(char_width('…'));
+    let mut used = 0;
+    let mut truncated: String = text
+        .chars()
+        .take_while(|&c| {
+            used += char_width(c);
+            used <= budget
+        })
+        .collect();
+    truncated.push('…');
+    truncated
+}
+
+/// 將拉丁字型沒有的字元（拉丁擴充區之後，例如中日韓文字）替換為 `?`
+fn replace_non_latin(text: &str) -> String {
+    text.chars()
+        .map(|c| if c > '\u{024F}' { '?' } else { c })
+        .collect()
+}
+
+/// 將位元組數格式化為易讀的大小
+fn format_file_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.2} {}", UNITS[unit])
+    }
+}
+
 /// 建立 xstack 佈局字串
 ///
 /// 每個位置格式為 `x_y，使用` | 分隔
@@ -101,13 +323,22 @@ fn build_xstack_layout(cols: usize, rows: usize) -> String {
 }
 
 /// 計算預覽圖的最終尺寸
-#[cfg(test)]
 const fn calculate_contact_sheet_size(grid_cols: usize, grid_rows: usize) -> (u32, u32) {
     let width = grid_cols as u32 * THUMBNAIL_WIDTH;
     let height = grid_rows as u32 * THUMBNAIL_HEIGHT;
     (width, height)
 }
 
+/// 計算含標頭預覽圖的最終尺寸
+#[cfg(test)]
+const fn calculate_contact_sheet_size_with_header(
+    grid_cols: usize,
+    grid_rows: usize,
+) -> (u32, u32) {
+    let (width, height) = calculate_contact_sheet_size(grid_cols, grid_rows);
+    (width, height + HEADER_HEIGHT)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -132,6 +363,148 @@ mod tests {
         assert_eq!(height, 6 * 180);
     }
 
+    fn header(title: &str) -> SheetHeader {
+        SheetHeader {
+            title: title.to_string(),
+            details: "時長 00:01:05".to_string(),
+            font_file: PathBuf::from("/f.ttf"),
+        }
+    }
+
+    #[test]
+    fn test_build_header_filter_graph() {
+        let graph = build_header_filter_graph(2, 2, &header("clip.mp4"));
+
+        assert!(graph.starts_with(
+            "[0:v][1:v][2:v][3:v]xstack=inputs=4:layout=0_0|320_0|0_180|320_180[grid];"
+        ));
+        assert!(graph.contains("color=c=0x202020:s=640x64:d=1,drawtext="));
+        assert!(graph.contains("text='clip.mp4'"));
+        assert!(graph.contains("text='時長 00\\:01\\:05'"));
+        assert!(graph.ends_with("[header];[header][grid]vstack=inputs=2"));
+    }
+
+    #[test]
+    fn test_calculate_contact_sheet_size_with_header() {
+        let (width, height) = calculate_contact_sheet_size_with_header(9, 6);
+        assert_eq!(width, 9 * 320);
+        assert_eq!(height, 6 * 180 + HEADER_HEIGHT);
+    }
+
+    #[test]
+    fn test_sheet_header_truncates_long_file_name() {
+        let info = VideoInfo {
+            duration_seconds: 3725.0,
+            width: 1920,
+            height: 1080,
+            frame_rate: 30.0,
+            codec_name: Some("hevc".to_string()),
+            profile: None,
+            pixel_format: None,
+            bit_rate: None,
+            audio_codec: None,
+            audio_channels: None,
+            subtitle_codecs: Vec::new(),
+        };
+        let long_name = "a".repeat(500);
+        let header = SheetHeader::new(
+            &long_name,
+            &info,
+            1536 * 1024 * 1024,
+            Path::new("/f.ttf"),
+            2,
+        );
+
+        assert!(header.title.ends_with('…'));
+        assert!(header.title.chars().count() < 500);
+        assert!(header.details.starts_with("Duration 01:02:05"));
+        assert!(header.details.contains("1920x1080"));
+
+        let cjk_header = SheetHeader::new(
+            "clip.mp4",
+            &info,
+            1024,
+            Path::new("/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc"),
+            2,
+        );
+        assert!(cjk_header.details.starts_with("時長 01:02:05"));
+    }
+
+    #[test]
+    fn test_sheet_header_title_falls_back_without_cjk_font() {
+        let info = VideoInfo {
+            duration_seconds: 60.0,
+            width: 1280,
+            height: 720,
+            frame_rate: 30.0,
+            codec_name: None,
+            profile: None,
+            pixel_format: None,
+            bit_rate: None,
+            audio_codec: None,
+            audio_channels: None,
+            subtitle_codecs: Vec::new(),
+        };
+        let latin = SheetHeader::new(
+            "影片 Café.mp4",
+            &info,
+            1024,
+            Path::new("/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf"),
+            4,
+        );
+        assert_eq!(latin.title, "?? Café.mp4");
+
+        // 不在候選清單、但檔名顯示為 CJK 字型
+        let custom = SheetHeader::new(
+            "影片 Café.mp4",
+            &info,
+            1024,
+            Path::new("/home/user/.fonts/NotoSansCJKtc-Regular.otf"),
+            4,
+        );
+        assert_eq!(custom.title, "影片 Café.mp4");
+        assert!(custom.details.starts_with("時長"));
+    }
+
+    #[test]
+    fn test_truncate_to_width_keeps_short_text() {
+        assert_eq!(truncate_to_width("short.mp4", 640), "short.mp4");
+        let cjk = truncate_to_width(&"影".repeat(100), 640);
+        assert!(cjk.ends_with('…'));
+        assert!(cjk.chars().count() <= 31);
+    }
+
+    #[test]
+    fn test_format_file_size() {
+        assert_eq!(format_file_size(512), "512 B");
+        assert_eq!(format_file_size(1536), "1.50 KB");
+        assert_eq!(format_file_size(1536 * 1024 * 1024), "1.50 GB");
+    }
+
+    #[test]
+    fn test_codec_args_per_format() {
+        let jpeg = SheetEncoding::default().codec_args();
+        assert_eq!(jpeg, ["-q:v", "4"]);
+
+        let best_jpeg = SheetEncoding {
+            format: ContactSheetFormat::Jpeg,
+            quality: 100,
+        };
+        assert_eq!(best_jpeg.codec_args(), ["-q:v", "2"]);
+
+        let png = SheetEncoding {
+            format: ContactSheetFormat::Png,
+            quality: 90,
+        };
+        assert_eq!(png.codec_args(), ["-compression_level", "9"]);
+
+        let webp = SheetEncoding {
+            format: ContactSheetFormat::Webp,
+            quality: 0,
+        };
+        assert_eq!(webp.codec_args(), ["-c:v", "libwebp", "-quality", "1"]);
+    }
+
     #[test]
     fn test_default_grid_count() {
         assert_eq!(DEFAULT_THUMBNAIL_COUNT, 54);
@@ -0,0 +1,107 @@
+//! 預覽圖網格配置
+//!
+//! 負責網格欄列數的驗證，以及依影片長度縮小網格
+
+use super::contact_sheet_merger::{DEFAULT_GRID_COLS, DEFAULT_GRID_ROWS};
+use anyhow::{Result, bail};
+
+/// 網格欄列數上限
+pub const MAX_GRID_SIZE: usize = 12;
+
+/// 每張縮圖至少需要的影片秒數，避免短片擷取到幾乎相同的畫面
+const MIN_SECONDS_PER_THUMBNAIL: f64 = 1.0;
+
+/// 預覽圖網格配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridLayout {
+    pub cols: usize,
+    pub rows: usize,
+}
+
+impl Default for GridLayout {
+    fn default() -> Self {
+        Self {
+            cols: DEFAULT_GRID_COLS,
+            rows: DEFAULT_GRID_ROWS,
+        }
+    }
+}
+
+impl GridLayout {
+    /// 建立並驗證網格配置
+    pub fn new(cols: usize, rows: usize) -> Result<Self> {
+        if cols == 0 || rows == 0 {
+            bail!("網格欄列數必須大於 0: {cols}x{rows}");
+        }
+        if cols > MAX_GRID_SIZE || rows > MAX_GRID_SIZE {
+            bail!("網格過大: {cols}x{rows}（上限 {MAX_GRID_SIZE}x{MAX_GRID_SIZE}）");
+        }
+        Ok(Self { cols, rows })
+    }
+
+    /// 縮圖總數
+    #[must_use]
+    pub const fn thumbnail_count(&self) -> usize {
+        self.cols * self.rows
+    }
+
+    /// 依影片長度縮小網格
+    ///
+    /// 影片太短無法取得足夠的縮圖時，先減少列數，仍不足再減少欄數
+    #[must_use]
+    pub fn shrink_to_fit(&self, duration_seconds: f64) -> Self {
+        let max_count = ((duration_seconds / MIN_SECONDS_PER_THUMBNAIL).floor() as usize).max(1);
+        if self.thumbnail_count() <= max_count {
+            return *self;
+        }
+
+        let cols = self.cols.min(max_count);
+        let rows = (max_count / cols).clamp(1, self.rows);
+        Self { cols, rows }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_grid() {
+        let grid = GridLayout::default();
+        assert_eq!(grid.cols, 9);
+        assert_eq!(grid.rows, 6);
+        assert_eq!(grid.thumbnail_count(), 54);
+    }
+
+    #[test]
+    fn test_new_rejects_invalid_sizes() {
+        assert!(GridLayout::new(0, 6).is_err());
+        assert!(GridLayout::new(9, 0).is_err());
+        assert!(GridLayout::new(13, 6).is_err());
+        assert!(GridLayout::new(9, 13).is_err());
+        assert!(GridLayout::new(12, 12).is_ok());
+        assert!(GridLayout::new(1, 1).is_ok());
+    }
+
+    #[test]
+    fn test_shrink_to_fit_long_video_unchanged() {
+        let grid = GridLayout::new(9, 6).unwrap();
+        assert_eq!(grid.shrink_to_fit(3600.0), grid);
+        assert_eq!(grid.shrink_to_fit(54.0), grid);
+    }
+
+    #[test]
+    fn test_shrink_to_fit_reduces_rows_first() {
+        let grid = GridLayout::new(9, 6).unwrap();
+        let shrunk = grid.shrink_to_fit(30.0);
+        assert_eq!(shrunk, GridLayout { cols: 9, rows: 3 });
+        assert!(shrunk.thumbnail_count() <= 30);
+    }
+
+    #[test]
+    fn test_shrink_to_fit_reduces_cols_for_very_short_video() {
+        let grid = GridLayout::new(9, 6).unwrap();
+        assert_eq!(grid.shrink_to_fit(4.5), GridLayout { cols: 4, rows: 1 });
+        assert_eq!(grid.shrink_to_fit(0.2), GridLayout { cols: 1, rows: 1 });
+    }
+}
@@ -1,16 +1,25 @@
 use super::batch_extractor::{BatchExtractorConfig, extract_thumbnails_batch};
 use super::contact_sheet_merger::{
-    DEFAULT_GRID_COLS, DEFAULT_GRID_ROWS, DEFAULT_THUMBNAIL_COUNT, create_contact_sheet,
+    SheetEncoding, SheetHeader, create_contact_sheet, create_contact_sheet_with_header,
 };
-use super::scene_detector::detect_scenes;
-use super::thumbnail_extractor::{create_thumbnail_tasks, extract_thumbnails_parallel};
+use super::grid_layout::{GridLayout, MAX_GRID_SIZE};
+use super::scene_detector::{SCENE_CACHE_DIR, detect_scenes_cached};
+use super::thumbnail_extractor::{
+    ThumbnailTask, create_thumbnail_tasks, extract_thumbnails_parallel,
+};
+use super::timestamp_overlay::{TimestampOverlay, detect_font};
 use super::timestamp_selector::select_timestamps;
 use super::uniform_selector::select_uniform_timestamps;
+use crate::component::path_prompt::prompt_directory;
+use crate::component::scan_options::{prompt_size_filter, report_size_filtered};
+use crate::component::{Interaction, OutputFormat, PreflightFailed, RunStatus, report};
 use crate::config::save::{add_recent_path, save_settings};
-use crate::config::{Config, ContactSheetOutputMode};
+use crate::config::{
+    Config, ContactSheetFormat, ContactSheetOutputMode, PathFeature, UserSettings,
+};
 use crate::tools::{
-    VideoFileInfo, ensure_directory_exists, get_video_info, scan_video_files,
-    validate_directory_exists,
+    ExcludeFilter, VideoFileInfo, VideoInfo, check_space, ensure_directory_exists, get_video_info,
+    probe_capabilities, scan_video_files, validate_directory_exists,
 };
 use anyhow::{Context, Result};
 use console::style;
@@ -19,6 +28,8 @@ use dialoguer::{Input, Select};
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use log::{debug, error, info, warn};
 use rayon::prelude::*;
+use serde::Serialize;
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -28,14 +39,17 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 /// 預覽圖預設輸出子目錄名稱
 const CONTACT_SHEET_OUTPUT_DIR: &str = "_contact_sheets";
 
-/// 快速模式處理階段數量（A-C 共 3 階段）
-const FAST_STAGE_COUNT: u64 = 3;
+/// 每部影片預估需要的空間（預覽圖與暫存縮圖），用於開始前的可用空間檢查
+const SPACE_ESTIMATE_PER_VIDEO: u64 = 20 * 1024 * 1024;
+
+/// 快速模式處理階段數量（A-D 共 4 階段）
+const FAST_STAGE_COUNT: u64 = 4;
 
 /// 精準模式處理階段數量（A-E 共 5 階段）
 const PRECISE_STAGE_COUNT: u64 = 5;
 
 /// 生成模式
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
 pub enum GenerationMode {
     /// 快速模式：跳過場景偵測，使用均勻時間點分布
     #[default]
@@ -44,6 +58,15 @@ pub enum GenerationMode {
     Precise,
 }
 
+impl fmt::Display for GenerationMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fast => write!(f, "快速模式"),
+            Self::Precise => write!(f, "精準模式"),
+        }
+    }
+}
+
 /// 產生唯一 ID（結合時間戳與執行緒 ID）
 fn generate_unique_id() -> String {
     let timestamp = SystemTime::now()
@@ -72,7 +95,15 @@ fn create_video_progress_style() -> ProgressStyle {
         .progress_chars("▓▒░")
 }
 
-/// 截斷名稱以適應顯示寬度
+/// 檢查是否已有任一支援格式的預覽圖
+fn contact_sheet_exists(output_dir: &Path, video_name: &str) -> bool {
+    ContactSheetFormat::ALL.iter().any(|format| {
+        output_dir
+            .join(format!("{video_name}.{}", format.extension()))
+            .exists()
+    })
+}
+
 fn truncate_name(name: &str, max_len: usize) -> String {
     if name.chars().count() <= max_len {
         format!("{name:<width$}", width = max_len)
@@ -83,8 +114,9 @@ fn truncate_name(name: &str, max_len: usize) -> String {
 }
 
 /// 預覽圖生成結果
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct GenerationResult {
+    pub mode: GenerationMode,
     pub total_videos: usize,
     pub successful: usize,
     pub failed: usize,
@@ -111,6 +143,8 @@ pub struct GenerationResult {
 pub struct ContactSheetGenerator {
     config: Config,
     shutdown_signal: Arc<AtomicBool>,
+    interaction: Interaction,
+    output: OutputFormat,
 }
 
 impl ContactSheetGenerator {
@@ -118,63 +152,137 @@ impl ContactSheetGenerator {
         Self {
             config,
             shutdown_signal,
+            interaction: Interaction::Prompt,
+            output: OutputFormat::Human,
         }
     }
 
-    pub fn run(&self) -> Result<()> {
-        println!("{}", style("=== 影片預覽圖生成 ===").cyan().bold());
+    #[must_use]
+    pub const fn with_interaction(mut self, interaction: Interaction) -> Self {
+        self.interaction = interaction;
+        self
+    }
+
+    #[must_use]
+    pub fn with_output(mut self, output: OutputFormat) -> Self {
+        self.output = output;
+        self
+    }
+
+    pub fn run(&self) -> Result<RunStatus> {
+        report!(
+            self.output,
+            "{}",
+            style("=== 影片預覽圖生成 ===").cyan().bold()
+        );
 
         // 選擇模式
         let Some(mode) = self.prompt_mode()? else {
-            return Ok(()); // ESC pressed, return to main menu
+            return Ok(RunStatus::Completed); // ESC pressed, return to main menu
         };
 
+        // 確認 ffmpeg 具備所需濾鏡
+        self.check_ffmpeg_capabilities(mode)?;
+
+        // 選擇網格大小
+        let grid = self.prompt_grid_layout()?;
+
         // 取得輸入路徑
-        let Some(input_path) = self.prompt_input_path()? else {
-            return Ok(()); // ESC pressed, return to main menu
+        let mut settings = self.config.settings.clone();
+        let Some(input_path) = self.prompt_input_path(&mut settings)? else {
+            return Ok(RunStatus::Completed); // ESC pressed, return to main menu
         };
         let input_dir = PathBuf::from(&input_path);
         validate_directory_exists(&input_dir)?;
 
-        // 更新路徑歷史並儲存（使用局部變數避免修改 self）
+        // 更新路徑歷史與網格設定並儲存（使用局部變數避免修改 self）
         {
-            let mut settings = self.config.settings.clone();
-            add_recent_path(&mut settings, &input_path);
+            add_recent_path(&mut settings, PathFeature::ContactSheet, &input_path);
+            settings.contact_sheet.grid_cols = grid.cols;
+            settings.contact_sheet.grid_rows = grid.rows;
+            settings.contact_sheet.fast_mode = mode == GenerationMode::Fast;
             if let Err(e) = save_settings(&settings) {
                 warn!("無法儲存路徑歷史: {e}");
             }
         }
 
+        self.generate_in(&input_dir, mode, grid)
+    }
+
+    /// 不詢問路徑與選項，依設定檔的模式與網格為指定資料夾生成預覽圖（命令列執行）
+    pub fn run_in(&self, input_dir: &Path) -> Result<RunStatus> {
+        report!(
+            self.output,
+            "{}",
+            style("=== 影片預覽圖生成 ===").cyan().bold()
+        );
+        let settings = &self.config.settings.contact_sheet;
+        let mode = if settings.fast_mode {
+            GenerationMode::Fast
+        } else {
+            GenerationMode::Precise
+        };
+        self.check_ffmpeg_capabilities(mode)
+            .context(PreflightFailed)?;
+        let grid =
+            GridLayout::new(settings.grid_cols, settings.grid_rows).context(PreflightFailed)?;
+        validate_directory_exists(input_dir).context(PreflightFailed)?;
+        self.generate_in(input_dir, mode, grid)
+    }
+
+    fn generate_in(
+        &self,
+        input_dir: &Path,
+        mode: GenerationMode,
+        grid: GridLayout,
+    ) -> Result<RunStatus> {
         // 根據設定決定輸出目錄
         let output_mode = self.config.settings.contact_sheet.output_mode;
         let output_dir = match output_mode {
             ContactSheetOutputMode::SubDirectory => input_dir.join(CONTACT_SHEET_OUTPUT_DIR),
-            ContactSheetOutputMode::SameDirectory => input_dir.clone(),
+            ContactSheetOutputMode::SameDirectory => input_dir.to_path_buf(),
         };
         ensure_directory_exists(&output_dir)?;
 
         match output_mode {
             ContactSheetOutputMode::SubDirectory => {
-                println!("預覽圖將輸出至: {}", style(output_dir.display()).cyan());
+                report!(
+                    self.output,
+                    "預覽圖將輸出至: {}",
+                    style(output_dir.display()).cyan()
+                );
             }
             ContactSheetOutputMode::SameDirectory => {
-                println!(
+                report!(
+                    self.output,
                     "預覽圖將輸出至: {} (與影片同目錄)",
                     style(output_dir.display()).cyan()
                 );
             }
         }
 
+        let size_filter =
+            prompt_size_filter(&self.config.settings, self.interaction, &self.output)?;
+
         // 掃描影片檔案
-        println!("{}", style("掃描影片檔案中...").dim());
-        let video_files = scan_video_files(&input_dir, &self.config.file_type_table)?;
+        report!(self.output, "{}", style("掃描影片檔案中...").dim());
+        let video_files = scan_video_files(
+            input_dir,
+            &self.config.file_type_table,
+            &ExcludeFilter::new(&self.config.settings.exclude_globs),
+            Some(&size_filter),
+            false,
+            &self.shutdown_signal,
+        )?;
+        report_size_filtered(&size_filter, &self.output);
 
         if video_files.is_empty() {
-            println!("{}", style("找不到任何影片檔案").yellow());
-            return Ok(());
+            report!(self.output, "{}", style("找不到任何影片檔案").yellow());
+            return Ok(RunStatus::Completed);
         }
 
-        println!(
+        report!(
+            self.output,
             "{}",
             style(format!(
                 "找到 {} 個影片檔案，依檔案大小排序（由小到大）",
@@ -186,7 +294,8 @@ impl ContactSheetGenerator {
         // 顯示檔案列表
         for (index, file) in video_files.iter().enumerate() {
             let size_mb = file.size as f64 / 1024.0 / 1024.0;
-            println!(
+            report!(
+                self.output,
                 "  {}. {} ({:.2} MB)",
                 index + 1,
                 file.path.file_name().unwrap_or_default().to_string_lossy(),
@@ -194,32 +303,74 @@ impl ContactSheetGenerator {
             );
         }
 
-        println!();
+        report!(self.output);
+
+        if !self.confirm_disk_space(&output_dir, &video_files)? {
+            return Ok(RunStatus::Completed);
+        }
 
         let mode_desc = match mode {
             GenerationMode::Fast => "快速模式",
             GenerationMode::Precise => "精準模式",
         };
-        println!(
+        report!(
+            self.output,
             "{}",
             style(format!(
-                "開始生成預覽圖（{}，使用 {} 個執行緒）...",
+                "開始生成預覽圖（{}，{}x{} 網格，使用 {} 個執行緒）...",
                 mode_desc,
+                grid.cols,
+                grid.rows,
                 rayon::current_num_threads()
             ))
             .cyan()
         );
 
         // 平行處理所有影片
-        let result = self.process_videos_parallel(&video_files, &output_dir, mode);
+        let result = self.process_videos_parallel(&video_files, &output_dir, mode, grid);
 
         self.print_summary(&result);
 
-        Ok(())
+        Ok(RunStatus::from_failures(result.failed))
+    }
+
+    /// 依尚未生成預覽圖的影片數預估所需空間，不足時詢問是否仍要開始
+    fn confirm_disk_space(&self, output_dir: &Path, videos: &[VideoFileInfo]) -> Result<bool> {
+        let pending = videos
+            .iter()
+            .filter(|video| {
+                video
+                    .path
+                    .file_stem()
+                    .is_none_or(|stem| !contact_sheet_exists(output_dir, &stem.to_string_lossy()))
+            })
+            .count() as u64;
+        let check = match check_space(output_dir, pending * SPACE_ESTIMATE_PER_VIDEO) {
+            Ok(check) => check,
+            Err(e) => {
+                warn!("無法檢查可用空間，略過檢查: {e:#}");
+                return Ok(true);
+            }
+        };
+        if check.is_sufficient() {
+            return Ok(true);
+        }
+
+        report!(
+            self.output,
+            "{}",
+            style(format!(
+                "輸出位置可用空間可能不足：{pending} 部影片預估需要 {:.2} MB，剩餘 {:.2} MB",
+                check.required as f64 / 1024.0 / 1024.0,
+                check.available as f64 / 1024.0 / 1024.0
+            ))
+            .yellow()
+        );
+        self.interaction.confirm("仍要開始生成預覽圖？", false)
     }
 
     fn prompt_mode(&self) -> Result<Option<GenerationMode>> {
-        println!("{}", style("(按 ESC 返回主選單)").dim());
+        report!(self.output, "{}", style("(按 ESC 返回主選單)").dim());
 
         let options = vec![
             "快速模式（推薦）- 跳過場景偵測，速度快 3-5 倍",
@@ -229,7 +380,7 @@ impl ContactSheetGenerator {
         let selection = Select::with_theme(&ColorfulTheme::default())
             .with_prompt("請選擇生成模式")
             .items(&options)
-            .default(0)
+            .default(usize::from(!self.config.settings.contact_sheet.fast_mode))
             .interact_opt()?;
 
         Ok(match selection {
@@ -240,60 +391,56 @@ impl ContactSheetGenerator {
         })
     }
 
-    fn prompt_input_path(&self) -> Result<Option<String>> {
-        let recent_paths = &self.config.settings.recent_paths;
-
-        // 如果沒有歷史路徑，直接輸入
-        if recent_paths.is_empty() {
-            let path: String = Input::new()
-                .with_prompt("請輸入影片資料夾路徑")
-                .interact_text()?;
-            return Ok(Some(path.trim().to_string()));
+    /// 確認 ffmpeg 具備所選模式與輸出格式需要的濾鏡與編碼器
+    fn check_ffmpeg_capabilities(&self, mode: GenerationMode) -> Result<()> {
+        let mut filters = vec!["scale", "pad", "select", "xstack"];
+        if mode == GenerationMode::Precise {
+            filters.push("scdet");
         }
+        let encoders: &[&str] = match self.config.settings.contact_sheet.output_format {
+            ContactSheetFormat::Jpeg => &["mjpeg"],
+            ContactSheetFormat::Png => &["png"],
+            ContactSheetFormat::Webp => &["libwebp"],
+        };
+        probe_capabilities()?.ensure(&filters, encoders)
+    }
 
-        // 建立選項清單：歷史路徑 + 輸入新路徑
-        let mut options: Vec<String> = recent_paths
-            .iter()
-            .enumerate()
-            .map(|(i, p)| {
-                // 檢查路徑是否存在
-                let exists = Path::new(p).exists();
-                let indicator = if exists { "✓" } else { "✗" };
-                format!("{} [{}] {}", i + 1, indicator, p)
-            })
-            .collect();
-        options.push("輸入新路徑...".to_string());
-
-        println!("{}", style("(按 ESC 返回主選單)").dim());
-
-        let selection = Select::with_theme(&ColorfulTheme::default())
-            .with_prompt("請選擇路徑")
-            .items(&options)
-            .default(0)
-            .interact_opt()?;
+    /// 詢問網格欄列數，預設值取自設定，超出範圍時重新詢問
+    fn prompt_grid_layout(&self) -> Result<GridLayout> {
+        let settings = &self.config.settings.contact_sheet;
+        loop {
+            let cols: usize = Input::new()
+                .with_prompt(format!("網格欄數（1-{MAX_GRID_SIZE}）"))
+                .default(settings.grid_cols)
+                .interact_text()?;
+            let rows: usize = Input::new()
+                .with_prompt(format!("網格列數（1-{MAX_GRID_SIZE}）"))
+                .default(settings.grid_rows)
+                .interact_text()?;
 
-        match selection {
-            None => Ok(None), // ESC pressed
-            Some(idx) if idx < recent_paths.len() => {
-                // 選擇歷史路徑
-                Ok(Some(recent_paths[idx].clone()))
-            }
-            Some(_) => {
-                // 輸入新路徑
-                let path: String = Input::new()
-                    .with_prompt("請輸入影片資料夾路徑")
-                    .interact_text()?;
-                Ok(Some(path.trim().to_string()))
+            match GridLayout::new(cols, rows) {
+                Ok(grid) => return Ok(grid),
+                Err(e) => report!(self.output, "{}", style(e).red()),
             }
         }
     }
 
+    fn prompt_input_path(&self, settings: &mut UserSettings) -> Result<Option<String>> {
+        prompt_directory(
+            settings,
+            PathFeature::ContactSheet,
+            "請輸入影片資料夾路徑",
+            &self.output,
+        )
+    }
+
     /// 平行處理所有影片，吃滿 CPU
     fn process_videos_parallel(
         &self,
         videos: &[VideoFileInfo],
         output_dir: &Path,
         mode: GenerationMode,
+        grid: GridLayout,
     ) -> GenerationResult {
         let successful = AtomicUsize::new(0);
         let failed = AtomicUsize::new(0);
@@ -318,6 +465,7 @@ impl ContactSheetGenerator {
         );
         separator.tick();
 
+        let encoding = self.sheet_encoding();
         let stage_count = match mode {
             GenerationMode::Fast => FAST_STAGE_COUNT,
             GenerationMode::Precise => PRECISE_STAGE_COUNT,
@@ -333,10 +481,11 @@ impl ContactSheetGenerator {
                 |s| s.to_string_lossy().to_string(),
             );
 
-            // 檢查輸出檔案是否已存在
+            // 檢查輸出檔案是否已存在（任一支援格式皆視為已存在）
             // 使用與影片相同的檔名（只改副檔名），以便孤立檔案比對能正確配對
-            let output_path = output_dir.join(format!("{video_name}.jpg"));
-            if output_path.exists() {
+            let output_path =
+                output_dir.join(format!("{video_name}.{}", encoding.format.extension()));
+            if contact_sheet_exists(output_dir, &video_name) {
                 info!("{video_name}: 預覽圖已存在，跳過");
                 skipped.fetch_add(1, Ordering::SeqCst);
                 main_pb.inc(1);
@@ -351,10 +500,11 @@ impl ContactSheetGenerator {
             video_pb.enable_steady_tick(Duration::from_millis(80));
 
             match self.process_single_video_with_progress(
-                &video.path,
+                video,
                 &output_path,
                 &video_pb,
                 mode,
+                grid,
             ) {
                 Ok(()) => {
                     video_pb.set_message("✓ 完成");
@@ -385,6 +535,7 @@ impl ContactSheetGenerator {
         main_pb.finish_with_message("處理完成");
 
         GenerationResult {
+            mode,
             total_videos: total,
             successful: successful.load(Ordering::SeqCst),
             failed: failed.load(Ordering::SeqCst),
@@ -394,13 +545,15 @@ impl ContactSheetGenerator {
 
     fn process_single_video_with_progress(
         &self,
-        video_path: &Path,
+        video: &VideoFileInfo,
         output_path: &Path,
         progress: &ProgressBar,
         mode: GenerationMode,
+        grid: GridLayout,
     ) -> Result<()> {
         // 建立暫存目錄（使用唯一 ID 避免平行處理時衝突）
-        let video_stem = video_path
+        let video_stem = video
+            .path
             .file_stem()
             .map_or_else(|| "video".to_string(), |s| s.to_string_lossy().to_string());
 
@@ -414,10 +567,10 @@ impl ContactSheetGenerator {
 
         let result = match mode {
             GenerationMode::Fast => {
-                self.process_video_fast_mode(video_path, output_path, &temp_dir, progress)
+                self.process_video_fast_mode(video, output_path, &temp_dir, progress, grid)
             }
             GenerationMode::Precise => {
-                self.process_video_precise_mode(video_path, output_path, &temp_dir, progress)
+                self.process_video_precise_mode(video, output_path, &temp_dir, progress, grid)
             }
         };
 
@@ -432,11 +585,13 @@ impl ContactSheetGenerator {
     /// 快速模式處理：跳過場景偵測
     fn process_video_fast_mode(
         &self,
-        video_path: &Path,
+        video: &VideoFileInfo,
         output_path: &Path,
         temp_dir: &Path,
         progress: &ProgressBar,
+        grid: GridLayout,
     ) -> Result<()> {
+        let video_path = video.path.as_path();
         let video_name = video_path.file_name().map_or_else(
             || "unknown".to_string(),
             |s| s.to_string_lossy().to_string(),
@@ -458,27 +613,31 @@ impl ContactSheetGenerator {
             anyhow::bail!("影片太短（< 1 秒）");
         }
 
+        // 影片太短時縮小網格，而非直接失敗
+        let grid = grid.shrink_to_fit(video_info.duration_seconds);
+        let thumbnail_count = grid.thumbnail_count();
+        debug!("{video_name}: 使用 {}x{} 網格", grid.cols, grid.rows);
+
         // Stage B: 均勻選取時間點（快速）
         progress.set_message("B: 選取時間點");
         debug!("{video_name}: 均勻選取截圖時間點...");
-        let timestamps =
-            select_uniform_timestamps(video_info.duration_seconds, DEFAULT_THUMBNAIL_COUNT);
+        let timestamps = select_uniform_timestamps(video_info.duration_seconds, thumbnail_count);
         debug!("{video_name}: 選取 {} 個時間點", timestamps.len());
         progress.inc(1);
 
-        if timestamps.len() < DEFAULT_THUMBNAIL_COUNT {
+        if timestamps.len() < thumbnail_count {
             anyhow::bail!(
                 "無法選取足夠的時間點: 需要 {}，只有 {}",
-                DEFAULT_THUMBNAIL_COUNT,
+                thumbnail_count,
                 timestamps.len()
             );
         }
 
-        // Stage C: 批次擷取縮圖並合併
-        progress.set_message("C: 擷取並合併");
+        // Stage C: 批次擷取縮圖
+        progress.set_message("C: 擷取縮圖");
         debug!("{video_name}: 批次擷取縮圖...");
 
-        let config = BatchExtractorConfig::default();
+        let config = self.batch_extractor_config();
         let batch_result = extract_thumbnails_batch(
             video_path,
             &timestamps,
@@ -492,22 +651,25 @@ impl ContactSheetGenerator {
             batch_result.success_count, batch_result.failed_count
         );
 
-        if batch_result.success_count < DEFAULT_THUMBNAIL_COUNT {
+        progress.inc(1);
+
+        if batch_result.success_count < thumbnail_count {
             anyhow::bail!(
-                "縮圖擷取失敗: 需要 {DEFAULT_THUMBNAIL_COUNT} 張，只有 {} 張成功",
+                "縮圖擷取失敗: 需要 {thumbnail_count} 張，只有 {} 張成功",
                 batch_result.success_count
             );
         }
 
-        // 合併預覽圖
+        // Stage D: 合併預覽圖
+        progress.set_message("D: 合併圖片");
         debug!("{video_name}: 合併預覽圖...");
-        create_contact_sheet(
+        self.merge_contact_sheet(
+            video,
+            &video_info,
             &batch_result.thumbnail_paths,
             output_path,
-            DEFAULT_GRID_COLS,
-            DEFAULT_GRID_ROWS,
-        )
-        .with_context(|| "合併預覽圖失敗")?;
+            grid,
+        )?;
         progress.inc(1);
 
         debug!("{video_name}: 預覽圖生成完成");
@@ -518,21 +680,24 @@ impl ContactSheetGenerator {
     /// 精準模式處理：使用場景偵測
     fn process_video_precise_mode(
         &self,
-        video_path: &Path,
+        video: &VideoFileInfo,
         output_path: &Path,
         temp_dir: &Path,
         progress: &ProgressBar,
+        grid: GridLayout,
     ) -> Result<()> {
-        self.process_video_stages_with_progress(video_path, output_path, temp_dir, progress)
+        self.process_video_stages_with_progress(video, output_path, temp_dir, progress, grid)
     }
 
     fn process_video_stages_with_progress(
         &self,
-        video_path: &Path,
+        video: &VideoFileInfo,
         output_path: &Path,
         temp_dir: &Path,
         progress: &ProgressBar,
+        grid: GridLayout,
     ) -> Result<()> {
+        let video_path = video.path.as_path();
         let video_name = video_path.file_name().map_or_else(
             || "unknown".to_string(),
             |s| s.to_string_lossy().to_string(),
@@ -554,29 +719,41 @@ impl ContactSheetGenerator {
             anyhow::bail!("影片太短（< 1 秒）");
         }
 
+        // 影片太短時縮小網格，而非直接失敗
+        let grid = grid.shrink_to_fit(video_info.duration_seconds);
+        let thumbnail_count = grid.thumbnail_count();
+        debug!("{video_name}: 使用 {}x{} 網格", grid.cols, grid.rows);
+
         // Stage B: 場景變換偵測
         progress.set_message("B: 偵測場景");
         debug!("{video_name}: 偵測場景變換...");
-        let scenes =
-            detect_scenes(video_path, &video_info, None).with_context(|| "場景偵測失敗")?;
+        // 快取放在輸出資料夾，中斷後重新執行可跳過已完成的偵測
+        let cache_dir = output_path
+            .parent()
+            .unwrap_or(Path::new("."))
+            .join(SCENE_CACHE_DIR);
+        let scenes = detect_scenes_cached(
+            video_path,
+            &video_info,
+            None,
+            &cache_dir,
+            self.config.settings.contact_sheet.use_scene_cache,
+        )
+        .with_context(|| "場景偵測失敗")?;
         debug!("{video_name}: 找到 {} 個場景變換點", scenes.len());
         progress.inc(1);
 
         // Stage C: 選取時間點
         progress.set_message("C: 選取時間點");
         debug!("{video_name}: 選取截圖時間點...");
-        let timestamps = select_timestamps(
-            video_info.duration_seconds,
-            &scenes,
-            DEFAULT_THUMBNAIL_COUNT,
-        );
+        let timestamps = select_timestamps(video_info.duration_seconds, &scenes, thumbnail_count);
         debug!("{video_name}: 選取 {} 個時間點", timestamps.len());
         progress.inc(1);
 
-        if timestamps.len() < DEFAULT_THUMBNAIL_COUNT {
+        if timestamps.len() < thumbnail_count {
             anyhow::bail!(
                 "無法選取足夠的時間點: 需要 {}，只有 {}",
-                DEFAULT_THUMBNAIL_COUNT,
+                thumbnail_count,
                 timestamps.len()
             );
         }
@@ -584,64 +761,212 @@ impl ContactSheetGenerator {
         // Stage D: 擷取縮圖
         progress.set_message("D: 擷取縮圖");
         debug!("{video_name}: 擷取縮圖...");
-        let tasks = create_thumbnail_tasks(video_path, &timestamps, temp_dir);
-        let results = extract_thumbnails_parallel(tasks, &self.shutdown_signal);
-
-        let success_count = results.iter().filter(|r| r.success).count();
-        let failed_count = results.len() - success_count;
-        debug!("{video_name}: 縮圖擷取完成 - 成功 {success_count}, 失敗 {failed_count}");
+        let thumbnail_paths = if self.config.settings.contact_sheet.use_batch_extraction {
+            self.extract_thumbnails_in_batches(video_path, &timestamps, temp_dir)?
+        } else {
+            self.extract_thumbnails_per_frame(video_path, &timestamps, temp_dir)
+        };
+        let success_count = thumbnail_paths.len();
+        debug!("{video_name}: 縮圖擷取完成 - 成功 {success_count}");
         progress.inc(1);
 
-        if success_count < DEFAULT_THUMBNAIL_COUNT {
-            anyhow::bail!(
-                "縮圖擷取失敗: 需要 {DEFAULT_THUMBNAIL_COUNT} 張，只有 {success_count} 張成功"
-            );
+        if success_count < thumbnail_count {
+            anyhow::bail!("縮圖擷取失敗: 需要 {thumbnail_count} 張，只有 {success_count} 張成功");
         }
 
         // Stage E: 合併預覽圖
         progress.set_message("E: 合併圖片");
         debug!("{video_name}: 合併預覽圖...");
 
-        // 收集成功的縮圖路徑（按索引排序）
+        self.merge_contact_sheet(video, &video_info, &thumbnail_paths, output_path, grid)?;
+        progress.inc(1);
+
+        debug!("{video_name}: 預覽圖生成完成");
+
+        Ok(())
+    }
+
+    /// 逐張平行擷取，回傳依索引排序的成功縮圖路徑
+    fn extract_thumbnails_per_frame(
+        &self,
+        video_path: &Path,
+        timestamps: &[f64],
+        temp_dir: &Path,
+    ) -> Vec<PathBuf> {
+        let timestamp_overlay = self.timestamp_overlay();
+        let tasks = create_thumbnail_tasks(video_path, timestamps, temp_dir)
+            .into_iter()
+            .map(|task| ThumbnailTask {
+                timestamp_overlay: timestamp_overlay.clone(),
+                ..task
+            })
+            .collect();
+        let results = extract_thumbnails_parallel(tasks, &self.shutdown_signal);
+
         let mut thumbnail_paths: Vec<_> = results
-            .iter()
+            .into_iter()
             .filter(|r| r.success)
-            .map(|r| (r.index, r.output_path.clone()))
+            .map(|r| (r.index, r.output_path))
             .collect();
         thumbnail_paths.sort_by_key(|(idx, _)| *idx);
-        let thumbnail_paths: Vec<_> = thumbnail_paths.into_iter().map(|(_, p)| p).collect();
+        thumbnail_paths.into_iter().map(|(_, p)| p).collect()
+    }
 
-        create_contact_sheet(
-            &thumbnail_paths,
-            output_path,
-            DEFAULT_GRID_COLS,
-            DEFAULT_GRID_ROWS,
-        )
-        .with_context(|| "合併預覽圖失敗")?;
-        progress.inc(1);
+    /// 批次擷取，回傳依索引排序的成功縮圖路徑
+    ///
+    /// 批次結果以 `thumb_NNN.jpg`（補零索引）命名，
+    /// 依檔名排序即可還原時間順序，確保合併時位置正確。
+    fn extract_thumbnails_in_batches(
+        &self,
+        video_path: &Path,
+        timestamps: &[f64],
+        temp_dir: &Path,
+    ) -> Result<Vec<PathBuf>> {
+        let config = self.batch_extractor_config();
+        let batch_result = extract_thumbnails_batch(
+            video_path,
+            timestamps,
+            temp_dir,
+            &config,
+            &self.shutdown_signal,
+        )?;
 
-        debug!("{video_name}: 預覽圖生成完成");
+        let mut thumbnail_paths = batch_result.thumbnail_paths;
+        thumbnail_paths.sort();
+        Ok(thumbnail_paths)
+    }
 
-        Ok(())
+    /// 合併縮圖為預覽圖，設定啟用標頭且有可用字型時加入檔案資訊標頭
+    fn merge_contact_sheet(
+        &self,
+        video: &VideoFileInfo,
+        video_info: &VideoInfo,
+        thumbnail_paths: &[PathBuf],
+        output_path: &Path,
+        grid: GridLayout,
+    ) -> Result<()> {
+        let encoding = self.sheet_encoding();
+        let font_file = if self.config.settings.contact_sheet.show_header {
+            Self::drawtext_font()
+        } else {
+            None
+        };
+
+        match font_file {
+            Some(font_file) => {
+                let file_name = video.path.file_name().map_or_else(
+                    || "unknown".to_string(),
+                    |s| s.to_string_lossy().to_string(),
+                );
+                let header =
+                    SheetHeader::new(&file_name, video_info, video.size, font_file, grid.cols);
+                create_contact_sheet_with_header(
+                    thumbnail_paths,
+                    output_path,
+                    grid.cols,
+                    grid.rows,
+                    &header,
+                    encoding,
+                )
+            }
+            None => {
+                create_contact_sheet(thumbnail_paths, output_path, grid.cols, grid.rows, encoding)
+            }
+        }
+        .with_context(|| "合併預覽圖失敗")
+    }
+
+    fn sheet_encoding(&self) -> SheetEncoding {
+        let settings = &self.config.settings.contact_sheet;
+        SheetEncoding {
+            format: settings.output_format,
+            quality: settings.quality,
+        }
+    }
+
+    /// 依設定取得時間戳記疊加（找不到字型時為 `None`）
+    fn timestamp_overlay(&self) -> Option<TimestampOverlay> {
+        if !self.config.settings.contact_sheet.burn_timestamps {
+            return None;
+        }
+        Self::drawtext_font().map(|font_file| TimestampOverlay {
+            font_file: font_file.to_path_buf(),
+        })
+    }
+
+    /// 取得 drawtext 可用的字型（ffmpeg 缺少 drawtext 或找不到字型時為 `None`）
+    fn drawtext_font() -> Option<&'static Path> {
+        if !probe_capabilities().is_ok_and(|caps| caps.has_filter("drawtext")) {
+            warn!("ffmpeg 缺少 drawtext 濾鏡，預覽圖將不顯示文字");
+            return None;
+        }
+        detect_font()
+    }
+
+    fn batch_extractor_config(&self) -> BatchExtractorConfig {
+        BatchExtractorConfig {
+            timestamp_overlay: self.timestamp_overlay(),
+            ..BatchExtractorConfig::default()
+        }
     }
 
     fn print_summary(&self, result: &GenerationResult) {
-        println!();
-        println!("{}", style("=== 預覽圖生成摘要 ===").cyan().bold());
-        println!("  總計: {} 個影片", result.total_videos);
-        println!("  成功: {} 個", style(result.successful).green());
+        if self.output.emit_json(result) {
+            return;
+        }
+        report!(self.output);
+        report!(
+            self.output,
+            "{}",
+            style("=== 預覽圖生成摘要 ===").cyan().bold()
+        );
+        report!(self.output, "  模式: {}", result.mode);
+        report!(self.output, "  總計: {} 個影片", result.total_videos);
+        report!(
+            self.output,
+            "  成功: {} 個",
+            style(result.successful).green()
+        );
 
         if result.skipped > 0 {
-            println!("  跳過: {} 個", style(result.skipped).yellow());
+            report!(self.output, "  跳過: {} 個", style(result.skipped).yellow());
         }
 
         if result.failed > 0 {
-            println!("  失敗: {} 個", style(result.failed).red());
+            report!(self.output, "  失敗: {} 個", style(result.failed).red());
         }
 
         info!(
-            "預覽圖生成完成 - 成功: {}, 跳過: {}, 失敗: {}",
-            result.successful, result.skipped, result.failed
+            "預覽圖生成完成（{}）- 成功: {}, 跳過: {}, 失敗: {}",
+            result.mode, result.successful, result.skipped, result.failed
+        );
+        self.output.report_log_file();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_result_json_schema() {
+        let result = GenerationResult {
+            mode: GenerationMode::Precise,
+            total_videos: 4,
+            successful: 2,
+            failed: 1,
+            skipped: 1,
+        };
+        assert_eq!(
+            serde_json::to_value(&result).unwrap(),
+            json!({
+                "mode": "Precise",
+                "total_videos": 4,
+                "successful": 2,
+                "failed": 1,
+                "skipped": 1
+            })
         );
     }
 }
@@ -1,12 +1,18 @@
-use crate::tools::VideoInfo;
+use crate::tools::{VideoInfo, resolve_ffmpeg};
 use anyhow::{Context, Result};
-use log::debug;
+use log::{debug, warn};
 use regex::Regex;
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::UNIX_EPOCH;
+
+/// 場景偵測快取資料夾名稱（位於預覽圖輸出資料夾內）
+pub const SCENE_CACHE_DIR: &str = ".scene_cache";
 
 /// 場景變換點資訊
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SceneChange {
     pub timestamp: f64,
     #[allow(dead_code)]
@@ -14,6 +20,7 @@ pub struct SceneChange {
 }
 
 /// 場景偵測設定
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct SceneDetectorConfig {
     /// 場景變換閾值 (0-100)，越低越敏感
     pub threshold: f64,
@@ -78,7 +85,7 @@ pub fn detect_scenes(
         config.scale_width, config.analyze_fps, config.threshold
     );
 
-    let output = Command::new("ffmpeg")
+    let output = Command::new(resolve_ffmpeg()?)
         .args(["-hide_banner", "-i"])
         .arg(path)
         .args([
@@ -95,6 +102,132 @@ pub fn detect_scenes(
     parse_scdet_output(&stderr, video_info.duration_seconds)
 }
 
+/// 使用快取的場景偵測
+///
+/// 以影片路徑、大小與修改時間作為指紋，結果存放在 `cache_dir` 中。
+/// 指紋與偵測設定皆相同時直接讀取快取；`use_cache` 為 `false` 時
+/// 不讀取快取，但仍會以新的結果覆寫
+pub fn detect_scenes_cached(
+    path: &Path,
+    video_info: &VideoInfo,
+    config: Option<SceneDetectorConfig>,
+    cache_dir: &Path,
+    use_cache: bool,
+) -> Result<Vec<SceneChange>> {
+    let config = config.unwrap_or_else(|| SceneDetectorConfig::auto_adjust(video_info));
+    with_scene_cache(path, config, cache_dir, use_cache, || {
+        detect_scenes(path, video_info, Some(config))
+    })
+}
+
+/// 快取檔內容
+#[derive(Debug, Serialize, Deserialize)]
+struct SceneCacheEntry {
+    fingerprint: SceneCacheFingerprint,
+    threshold: f64,
+    analyze_fps: f64,
+    scale_width: u32,
+    scenes: Vec<SceneChange>,
+}
+
+/// 影片檔指紋（路徑 + 大小 + 修改時間）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct SceneCacheFingerprint {
+    path: PathBuf,
+    size: u64,
+    modified_secs: u64,
+    modified_nanos: u32,
+}
+
+impl SceneCacheFingerprint {
+    fn of(path: &Path) -> Result<Self> {
+        let metadata =
+            fs::metadata(path).with_context(|| format!("無法讀取檔案資訊: {}", path.display()))?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .unwrap_or_default();
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            size: metadata.len(),
+            modified_secs: modified.as_secs(),
+            modified_nanos: modified.subsec_nanos(),
+        })
+    }
+
+    /// 快取檔名：指紋的 blake3 雜湊
+    fn cache_file_name(&self) -> String {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(self.path.to_string_lossy().as_bytes());
+        hasher.update(&self.size.to_le_bytes());
+        hasher.update(&self.modified_secs.to_le_bytes());
+        hasher.update(&self.modified_nanos.to_le_bytes());
+        format!("{}.json", &hasher.finalize().to_hex()[..32])
+    }
+}
+
+impl SceneCacheEntry {
+    fn matches(&self, fingerprint: &SceneCacheFingerprint, config: &SceneDetectorConfig) -> bool {
+        self.fingerprint == *fingerprint
+            && self.threshold == config.threshold
+            && self.analyze_fps == config.analyze_fps
+            && self.scale_width == config.scale_width
+    }
+}
+
+/// 快取邏輯本體，`detect` 只在快取未命中時呼叫
+fn with_scene_cache(
+    path: &Path,
+    config: SceneDetectorConfig,
+    cache_dir: &Path,
+    use_cache: bool,
+    detect: impl FnOnce() -> Result<Vec<SceneChange>>,
+) -> Result<Vec<SceneChange>> {
+    let fingerprint = SceneCacheFingerprint::of(path)?;
+    let cache_path = cache_dir.join(fingerprint.cache_file_name());
+
+    if use_cache && let Some(scenes) = load_cached_scenes(&cache_path, &fingerprint, &config) {
+        debug!("使用場景偵測快取: {}", cache_path.display());
+        return Ok(scenes);
+    }
+
+    let scenes = detect()?;
+
+    let entry = SceneCacheEntry {
+        fingerprint,
+        threshold: config.threshold,
+        analyze_fps: config.analyze_fps,
+        scale_width: config.scale_width,
+        scenes,
+    };
+    // 快取寫入失敗不影響偵測結果
+    if let Err(e) = write_cache_entry(cache_dir, &cache_path, &entry) {
+        warn!("無法寫入場景偵測快取 {}: {e}", cache_path.display());
+    }
+
+    Ok(entry.scenes)
+}
+
+fn load_cached_scenes(
+    cache_path: &Path,
+    fingerprint: &SceneCacheFingerprint,
+    config: &SceneDetectorConfig,
+) -> Option<Vec<SceneChange>> {
+    let content = fs::read_to_string(cache_path).ok()?;
+    let entry: SceneCacheEntry = serde_json::from_str(&content)
+        .inspect_err(|e| debug!("場景偵測快取格式錯誤 {}: {e}", cache_path.display()))
+        .ok()?;
+    entry.matches(fingerprint, config).then_some(entry.scenes)
+}
+
+fn write_cache_entry(cache_dir: &Path, cache_path: &Path, entry: &SceneCacheEntry) -> Result<()> {
+    fs::create_dir_all(cache_dir)?;
+    fs::write(cache_path, serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
 /// 解析 ffmpeg scdet 輸出
 fn parse_scdet_output(output: &str, duration: f64) -> Result<Vec<SceneChange>> {
     let mut scenes = Vec::new();
@@ -134,6 +267,10 @@ fn parse_scdet_output(output: &str, duration: f64) -> Result<Vec<SceneChange>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::Cell;
+    use std::fs::File;
+    use std::time::{Duration, SystemTime};
+    use tempfile::TempDir;
 
     #[test]
     fn test_parse_scdet_output_t_format() {
@@ -178,6 +315,13 @@ lavfi.scd.time=25.678
             width: 1920,
             height: 1080,
             frame_rate: 30.0,
+            codec_name: None,
+            profile: None,
+            pixel_format: None,
+            bit_rate: None,
+            audio_codec: None,
+            audio_channels: None,
+            subtitle_codecs: Vec::new(),
         };
         let config = SceneDetectorConfig::auto_adjust(&short_video);
         assert!((config.analyze_fps - 2.0).abs() < 0.01);
@@ -187,8 +331,113 @@ lavfi.scd.time=25.678
             width: 1920,
             height: 1080,
             frame_rate: 30.0,
+            codec_name: None,
+            profile: None,
+            pixel_format: None,
+            bit_rate: None,
+            audio_codec: None,
+            audio_channels: None,
+            subtitle_codecs: Vec::new(),
         };
         let config = SceneDetectorConfig::auto_adjust(&long_video);
         assert!((config.analyze_fps - 0.5).abs() < 0.01);
     }
+
+    fn scene(timestamp: f64) -> SceneChange {
+        SceneChange {
+            timestamp,
+            score: 1.0,
+        }
+    }
+
+    fn run(
+        video: &Path,
+        cache_dir: &Path,
+        use_cache: bool,
+        result: f64,
+        calls: &Cell<usize>,
+    ) -> Vec<SceneChange> {
+        with_scene_cache(
+            video,
+            SceneDetectorConfig::default(),
+            cache_dir,
+            use_cache,
+            || {
+                calls.set(calls.get() + 1);
+                Ok(vec![scene(result)])
+            },
+        )
+        .unwrap()
+    }
+
+    fn setup() -> (TempDir, PathBuf, PathBuf) {
+        let dir = TempDir::new().unwrap();
+        let video = dir.path().join("movie.mp4");
+        fs::write(&video, b"video").unwrap();
+        let cache_dir = dir.path().join(SCENE_CACHE_DIR);
+        (dir, video, cache_dir)
+    }
+
+    #[test]
+    fn test_cache_miss_then_hit() {
+        let (_dir, video, cache_dir) = setup();
+        let calls = Cell::new(0);
+
+        assert_eq!(run(&video, &cache_dir, true, 5.0, &calls), vec![scene(5.0)]);
+        assert_eq!(calls.get(), 1);
+        assert_eq!(fs::read_dir(&cache_dir).unwrap().count(), 1);
+
+        // 第二次直接讀取快取，不再偵測
+        assert_eq!(run(&video, &cache_dir, true, 9.0, &calls), vec![scene(5.0)]);
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn test_cache_stale_when_mtime_changes() {
+        let (_dir, video, cache_dir) = setup();
+        let calls = Cell::new(0);
+        run(&video, &cache_dir, true, 5.0, &calls);
+
+        let later = SystemTime::now() + Duration::from_secs(60);
+        File::options()
+            .write(true)
+            .open(&video)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+
+        assert_eq!(run(&video, &cache_dir, true, 9.0, &calls), vec![scene(9.0)]);
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn test_cache_ignored_when_disabled() {
+        let (_dir, video, cache_dir) = setup();
+        let calls = Cell::new(0);
+        run(&video, &cache_dir, true, 5.0, &calls);
+
+        assert_eq!(
+            run(&video, &cache_dir, false, 9.0, &calls),
+            vec![scene(9.0)]
+        );
+        assert_eq!(calls.get(), 2);
+
+        // 忽略快取時仍會以新結果覆寫
+        assert_eq!(run(&video, &cache_dir, true, 1.0, &calls), vec![scene(9.0)]);
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn test_cache_miss_when_config_differs() {
+        let (_dir, video, cache_dir) = setup();
+        run(&video, &cache_dir, true, 5.0, &Cell::new(0));
+
+        let config = SceneDetectorConfig {
+            threshold: 30.0,
+            ..SceneDetectorConfig::default()
+        };
+        let scenes =
+            with_scene_cache(&video, config, &cache_dir, true, || Ok(vec![scene(7.0)])).unwrap();
+        assert_eq!(scenes, vec![scene(7.0)]);
+    }
 }
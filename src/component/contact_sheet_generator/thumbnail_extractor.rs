@@ -1,3 +1,5 @@
+use super::timestamp_overlay::TimestampOverlay;
+use crate::tools::resolve_ffmpeg;
 use anyhow::{Context, Result};
 use log::{debug, error, warn};
 use rayon::prelude::*;
@@ -28,6 +30,8 @@ pub struct ThumbnailTask {
     pub timestamp: f64,
     pub output_path: PathBuf,
     pub index: usize,
+    /// 在縮圖上疊加時間戳記（`None` 表示不疊加）
+    pub timestamp_overlay: Option<TimestampOverlay>,
 }
 
 /// 縮圖擷取結果
@@ -104,7 +108,7 @@ pub fn extract_thumbnail(task: &ThumbnailTask) -> ThumbnailResult {
 
 /// 產生全黑替代圖片
 fn generate_black_placeholder(output_path: &Path) -> Result<()> {
-    let output = Command::new("ffmpeg")
+    let output = Command::new(resolve_ffmpeg()?)
         .args([
             "-hide_banner",
             "-loglevel",
@@ -146,10 +150,7 @@ fn extract_thumbnail_inner(task: &ThumbnailTask) -> Result<()> {
         task.index, task.timestamp, t0, delta
     );
 
-    // 建立縮放和填充濾鏡（保持 16:9 比例，不足部分填黑）
-    let filter = format!(
-        "scale={THUMBNAIL_WIDTH}:{THUMBNAIL_HEIGHT}:force_original_aspect_ratio=decrease,pad={THUMBNAIL_WIDTH}:{THUMBNAIL_HEIGHT}:(ow-iw)/2:(oh-ih)/2:black"
-    );
+    let filter = build_thumbnail_filter(task);
 
     let mut args = vec![
         "-hide_banner".to_string(),
@@ -188,7 +189,7 @@ fn extract_thumbnail_inner(task: &ThumbnailTask) -> Result<()> {
         task.output_path.to_string_lossy().to_string(),
     ]);
 
-    let output = Command::new("ffmpeg")
+    let output = Command::new(resolve_ffmpeg()?)
         .args(&args)
         .output()
         .with_context(|| format!("無法執行 ffmpeg 擷取縮圖: {}", task.video_path.display()))?;
@@ -206,6 +207,21 @@ fn extract_thumbnail_inner(task: &ThumbnailTask) -> Result<()> {
     Ok(())
 }
 
+/// 建立縮圖濾鏡鏈
+///
+/// 縮放並填充至固定尺寸（保持 16:9 比例，不足部分填黑），
+/// 時間戳記在填充後才疊加，因此輸出尺寸不受影響
+fn build_thumbnail_filter(task: &ThumbnailTask) -> String {
+    let scale_filter = format!(
+        "scale={THUMBNAIL_WIDTH}:{THUMBNAIL_HEIGHT}:force_original_aspect_ratio=decrease,pad={THUMBNAIL_WIDTH}:{THUMBNAIL_HEIGHT}:(ow-iw)/2:(oh-ih)/2:black"
+    );
+
+    match &task.timestamp_overlay {
+        Some(overlay) => format!("{scale_filter},{}", overlay.drawtext_filter(task.timestamp)),
+        None => scale_filter,
+    }
+}
+
 /// 平行擷取多個縮圖
 ///
 /// 使用 rayon 進行平行處理，每個 ffmpeg 程序使用單執行緒
@@ -252,6 +268,7 @@ pub fn create_thumbnail_tasks(
             timestamp,
             output_path: output_dir.join(format!("thumb_{i:03}.jpg")),
             index: i,
+            timestamp_overlay: None,
         })
         .collect()
 }
@@ -289,10 +306,26 @@ mod tests {
             timestamp: 10.5,
             output_path: PathBuf::from("/test/thumb.jpg"),
             index: 0,
+            timestamp_overlay: None,
         };
 
         let cloned = task.clone();
         assert_eq!(cloned.video_path, task.video_path);
         assert!((cloned.timestamp - task.timestamp).abs() < 0.01);
     }
+
+    #[test]
+    fn test_build_thumbnail_filter_with_overlay() {
+        let mut task =
+            create_thumbnail_tasks(Path::new("/v.mp4"), &[65.0], Path::new("/out")).remove(0);
+        let plain = build_thumbnail_filter(&task);
+        assert!(!plain.contains("drawtext"));
+
+        task.timestamp_overlay = Some(TimestampOverlay {
+            font_file: PathBuf::from("/f.ttf"),
+        });
+        let filter = build_thumbnail_filter(&task);
+        assert!(filter.starts_with(&format!("{plain},drawtext=")));
+        assert!(filter.contains("text='00\\:01\\:05'"));
+    }
 }
@@ -0,0 +1,227 @@
+//! 縮圖時間戳記疊加
+//!
+//! 使用 ffmpeg 的 drawtext 濾鏡在縮圖右下角標示時間（HH:MM:SS）。
+//! drawtext 需要字型檔，找不到可用字型時不疊加文字並記錄警告。
+//! 預覽圖標頭會顯示中文標籤與檔名，因此優先使用含 CJK 字形的字型。
+
+use log::{debug, warn};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// 含 CJK 字形的常見字型檔位置（優先嘗試，避免中日韓文字顯示為方框）
+const CJK_FONT_CANDIDATES: &[&str] = &[
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-sans-cjk-fonts/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/adobe-source-han-sans/SourceHanSans-Regular.ttc",
+    "/usr/share/fonts/opentype/source-han-sans/SourceHanSans-Regular.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-zenhei.ttc",
+    "/System/Library/Fonts/PingFang.ttc",
+    "/System/Library/Fonts/Hiragino Sans GB.ttc",
+    "/System/Library/Fonts/Supplemental/Arial Unicode.ttf",
+    "/Library/Fonts/Arial Unicode.ttf",
+    "C:/Windows/Fonts/msjh.ttc",
+    "C:/Windows/Fonts/msyh.ttc",
+    "C:/Windows/Fonts/meiryo.ttc",
+];
+
+/// 只含拉丁字形的常見字型檔位置（找不到 CJK 字型時依序嘗試）
+const FONT_CANDIDATES: &[&str] = &[
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu-sans-fonts/DejaVuSans.ttf",
+    "/usr/share/fonts/truetype/liberation/LiberationSans-Regular.ttf",
+    "/System/Library/Fonts/Supplemental/Arial.ttf",
+    "/System/Library/Fonts/Helvetica.ttc",
+    "/Library/Fonts/Arial.ttf",
+    "C:/Windows/Fonts/arial.ttf",
+    "C:/Windows/Fonts/segoeui.ttf",
+];
+
+/// 常見 CJK 字型檔名中的關鍵字（使用者自行安裝、不在候選清單的字型）
+const CJK_FONT_NAME_HINTS: &[&str] = &[
+    "cjk",
+    "sourcehan",
+    "source han",
+    "wqy",
+    "pingfang",
+    "hiragino",
+    "msjh",
+    "msyh",
+    "meiryo",
+    "yahei",
+    "jhenghei",
+    "simsun",
+    "simhei",
+    "mingliu",
+    "arial unicode",
+    "droidsansfallback",
+    "sarasa",
+];
+
+/// 時間戳記文字大小（像素）
+const FONT_SIZE: u32 = 16;
+
+/// 時間戳記疊加設定
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimestampOverlay {
+    /// drawtext 使用的字型檔
+    pub font_file: PathBuf,
+}
+
+impl TimestampOverlay {
+    /// 尋找可用字型並建立疊加設定
+    ///
+    /// 找不到字型時回傳 `None`
+    #[must_use]
+    pub fn detect() -> Option<Self> {
+        detect_font().map(|font_file| Self {
+            font_file: font_file.to_path_buf(),
+        })
+    }
+
+    /// 建立顯示固定時間的 drawtext 濾鏡
+    #[must_use]
+    pub fn drawtext_filter(&self, timestamp: f64) -> String {
+        format!(
+            "drawtext=fontfile='{}':text='{}':fontsize={FONT_SIZE}:fontcolor=white:box=1:boxcolor=black@0.6:boxborderw=3:x=w-tw-6:y=h-th-6",
+            escape_filter_value(&self.font_file.to_string_lossy()),
+            escape_filter_value(&format_timestamp(timestamp))
+        )
+    }
+
+    /// 建立只在指定時間範圍內顯示的 drawtext 濾鏡
+    ///
+    /// 批次擷取時多個時間點共用同一條濾鏡鏈，
+    /// 以 `enable` 讓每段文字只出現在對應的幀上
+    #[must_use]
+    pub fn drawtext_filter_between(&self, timestamp: f64, start: f64, end: f64) -> String {
+        format!(
+            "{}:enable='between(t\\,{start:.3}\\,{end:.3})'",
+            self.drawtext_filter(timestamp)
+        )
+    }
+}
+
+/// 尋找 drawtext 可用的字型檔（結果會快取）
+///
+/// 找不到字型時只在第一次記錄警告
+#[must_use]
+pub fn detect_font() -> Option<&'static Path> {
+    static FONT: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+    FONT.get_or_init(|| {
+        let found = CJK_FONT_CANDIDATES
+            .iter()
+            .chain(FONT_CANDIDATES)
+            .map(Path::new)
+            .find(|path| path.is_file())
+            .map(Path::to_path_buf);
+
+        match &found {
+            Some(path) => debug!("drawtext 字型: {}", path.display()),
+            None => warn!("找不到可用字型，預覽圖將不顯示文字"),
+        }
+        found
+    })
+    .as_deref()
+}
+
+/// 字型是否含 CJK 字形（依內建的候選清單與常見 CJK 字型的檔名判斷）
+#[must_use]
+pub fn is_cjk_font(font_file: &Path) -> bool {
+    if CJK_FONT_CANDIDATES
+        .iter()
+        .any(|candidate| Path::new(candidate) == font_file)
+    {
+        return true;
+    }
+    let name = font_file
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    CJK_FONT_NAME_HINTS.iter().any(|hint| name.contains(hint))
+}
+
+/// 將秒數格式化為 HH:MM:SS
+#[must_use]
+pub fn format_timestamp(seconds: f64) -> String {
+    let total = seconds.max(0.0).floor() as u64;
+    format!(
+        "{:02}:{:02}:{:02}",
+        total / 3600,
+        (total % 3600) / 60,
+        total % 60
+    )
+}
+
+/// 跳脫濾鏡參數中的特殊字元
+///
+/// Windows 路徑的反斜線改為斜線，冒號與單引號需以反斜線跳脫
+pub(super) fn escape_filter_value(value: &str) -> String {
+    value
+        .replace('\\', "/")
+        .replace(':', "\\:")
+        .replace('\'', "\\'")
+}
+
+/// 跳脫 drawtext 顯示的任意文字
+///
+/// 單引號在濾鏡圖的引號內無法跳脫，改以全形引號取代；
+/// 反斜線與冒號以反斜線跳脫
+pub(super) fn escape_drawtext_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(':', "\\:")
+        .replace('\'', "\u{2019}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overlay(font: &str) -> TimestampOverlay {
+        TimestampOverlay {
+            font_file: PathBuf::from(font),
+        }
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0.0), "00:00:00");
+        assert_eq!(format_timestamp(65.9), "00:01:05");
+        assert_eq!(format_timestamp(3725.0), "01:02:05");
+        assert_eq!(format_timestamp(-1.0), "00:00:00");
+    }
+
+    #[test]
+    fn test_drawtext_filter() {
+        let filter = overlay("/fonts/DejaVuSans.ttf").drawtext_filter(3725.0);
+        assert_eq!(
+            filter,
+            "drawtext=fontfile='/fonts/DejaVuSans.ttf':text='01\\:02\\:05':fontsize=16:fontcolor=white:box=1:boxcolor=black@0.6:boxborderw=3:x=w-tw-6:y=h-th-6"
+        );
+    }
+
+    #[test]
+    fn test_drawtext_filter_escapes_windows_path() {
+        let filter = overlay("C:\\Windows\\Fonts\\arial.ttf").drawtext_filter(0.0);
+        assert!(filter.starts_with("drawtext=fontfile='C\\:/Windows/Fonts/arial.ttf'"));
+    }
+
+    #[test]
+    fn test_escape_drawtext_text() {
+        assert_eq!(escape_drawtext_text("a:b"), "a\\:b");
+        assert_eq!(escape_drawtext_text("it's"), "it\u{2019}s");
+        assert_eq!(escape_drawtext_text("a\\b"), "a\\\\b");
+    }
+
+    #[test]
+    fn test_drawtext_filter_between() {
+        let filter = overlay("/f.ttf").drawtext_filter_between(10.0, 9.95, 10.05);
+        assert!(filter.contains("text='00\\:00\\:10'"));
+        assert!(filter.ends_with(":enable='between(t\\,9.950\\,10.050)'"));
+    }
+}
@@ -0,0 +1,171 @@
+//! 重複檔案報告
+//!
+//! 將本次掃描找到的重複檔案依 hash 分組，寫成 `duplicates_report.json` 與
+//! `duplicates_report.csv`，方便在移動或刪除前先行稽核。
+
+use crate::tools::csv_field;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// JSON 報告檔名稱
+pub const DUPLICATE_REPORT_JSON: &str = "duplicates_report.json";
+/// CSV 報告檔名稱
+pub const DUPLICATE_REPORT_CSV: &str = "duplicates_report.csv";
+
+/// 同一內容的重複檔案
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DuplicateGroup {
+    pub hash: String,
+    /// 單一檔案大小
+    pub size: u64,
+    /// 紀錄中的原始檔案（舊版紀錄為 `None`）
+    pub original: Option<PathBuf>,
+    /// 本次掃描找到的重複檔案
+    pub duplicates: Vec<PathBuf>,
+}
+
+impl DuplicateGroup {
+    /// 移除重複檔案後可釋放的空間
+    #[must_use]
+    pub const fn wasted_bytes(&self) -> u64 {
+        self.size * self.duplicates.len() as u64
+    }
+}
+
+/// 收集重複檔案並依 hash 分組
+#[derive(Debug, Default)]
+pub struct DuplicateGroups {
+    groups: HashMap<(u64, String), DuplicateGroup>,
+}
+
+impl DuplicateGroups {
+    /// 加入重複檔案；重複檔案是先前記下的原始檔案時（原始檔案被取代），改記新的原始檔案
+    pub fn add(&mut self, size: u64, hash: String, original: Option<PathBuf>, duplicate: PathBuf) {
+        let group = self
+            .groups
+            .entry((size, hash.clone()))
+            .or_insert_with(|| DuplicateGroup {
+                hash,
+                size,
+                original: original.clone(),
+                duplicates: Vec::new(),
+            });
+        if group.original.as_ref() == Some(&duplicate) {
+            group.original = original;
+        }
+        group.duplicates.push(duplicate);
+    }
+
+    /// 依可釋放空間由大到小排序的分組
+    #[must_use]
+    pub fn into_sorted(self) -> Vec<DuplicateGroup> {
+        let mut groups: Vec<DuplicateGroup> = self.groups.into_values().collect();
+        for group in &mut groups {
+            group.duplicates.sort();
+        }
+        groups.sort_by(|a, b| {
+            b.wasted_bytes()
+                .cmp(&a.wasted_bytes())
+                .then_with(|| a.hash.cmp(&b.hash))
+        });
+        groups
+    }
+}
+
+#[derive(Serialize)]
+struct DuplicateReport<'a> {
+    bytes_reclaimable: u64,
+    groups: &'a [DuplicateGroup],
+}
+
+/// 寫入 JSON 報告
+pub fn write_json_report(path: &Path, groups: &[DuplicateGroup]) -> Result<()> {
+    let report = DuplicateReport {
+        bytes_reclaimable: groups.iter().map(DuplicateGroup::wasted_bytes).sum(),
+        groups,
+    };
+    let content = serde_json::to_string_pretty(&report)?;
+    fs::write(path, content).with_context(|| format!("無法寫入重複檔案報告: {}", path.display()))
+}
+
+/// 寫入 CSV 報告（每個檔案一列，原始檔案標記為 `original`）
+pub fn write_csv_report(path: &Path, groups: &[DuplicateGroup]) -> Result<()> {
+    let mut content = String::from("hash,size,role,path\n");
+    for group in groups {
+        let rows = group
+            .original
+            .iter()
+            .map(|original| ("original", original))
+            .chain(group.duplicates.iter().map(|dup| ("duplicate", dup)));
+        for (role, file) in rows {
+            let _ = writeln!(
+                content,
+                "{},{},{role},{}",
+                group.hash,
+                group.size,
+                csv_field(&file.to_string_lossy())
+            );
+        }
+    }
+    fs::write(path, content).with_context(|| format!("無法寫入重複檔案報告: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn groups() -> Vec<DuplicateGroup> {
+        let mut groups = DuplicateGroups::default();
+        groups.add(
+            10,
+            "small".to_string(),
+            Some(PathBuf::from("/a/one.txt")),
+            PathBuf::from("/b/one.txt"),
+        );
+        groups.add(
+            100,
+            "large".to_string(),
+            None,
+            PathBuf::from("/b/two, copy.mkv"),
+        );
+        groups.add(100, "large".to_string(), None, PathBuf::from("/b/two.mkv"));
+        groups.into_sorted()
+    }
+
+    #[test]
+    fn test_groups_sorted_by_wasted_bytes() {
+        let groups = groups();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].hash, "large");
+        assert_eq!(groups[0].wasted_bytes(), 200);
+        assert_eq!(groups[1].wasted_bytes(), 10);
+    }
+
+    #[test]
+    fn test_write_reports() {
+        let dir = TempDir::new().unwrap();
+        let groups = groups();
+
+        let json_path = dir.path().join(DUPLICATE_REPORT_JSON);
+        write_json_report(&json_path, &groups).unwrap();
+        let value: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(value["bytes_reclaimable"], 210);
+        assert_eq!(value["groups"][0]["duplicates"][1], "/b/two.mkv");
+        assert_eq!(value["groups"][1]["original"], "/a/one.txt");
+
+        let csv_path = dir.path().join(DUPLICATE_REPORT_CSV);
+        write_csv_report(&csv_path, &groups).unwrap();
+        let csv = fs::read_to_string(&csv_path).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "hash,size,role,path");
+        assert_eq!(lines[1], "large,100,duplicate,\"/b/two, copy.mkv\"");
+        assert_eq!(lines[3], "small,10,original,/a/one.txt");
+        assert_eq!(lines.len(), 5);
+    }
+}
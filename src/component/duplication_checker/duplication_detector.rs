@@ -1,26 +1,102 @@
-use super::hash_table::HashTable;
-use crate::tools::{FileInfo, calculate_file_hash, ensure_directory_exists, scan_all_files};
-use anyhow::{Context, Result};
-use log::{error, info};
+use super::duplicate_report::{DuplicateGroup, DuplicateGroups};
+use super::hash_table::{HashTable, HashTableLock, KeepDecision, PartialMatch, file_mtime};
+use super::perceptual_match::{PerceptualMatcher, SimilarVideo};
+use crate::component::RunStatus;
+use crate::config::{DeletionMode, DuplicateAction, FileTypeTable, KeepPolicy, PerceptualSettings};
+use crate::tools::{
+    Disposal, ExcludeFilter, FileInfo, HashAborted, SizeFilter, calculate_file_hash_cancellable,
+    calculate_partial_hash, dispose_file, ensure_directory_exists, format_size, scan_all_files,
+};
+use anyhow::{Context, Result, bail};
+use indicatif::{ProgressBar, ProgressStyle};
+use log::{error, info, warn};
 use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
-#[derive(Debug)]
+/// 部分 hash 讀取檔案開頭與結尾的位元組數
+pub(super) const PARTIAL_HASH_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug, Serialize)]
 pub struct DuplicationResult {
     pub total_files: usize,
+    /// 實際處理完的檔案數（中斷時少於 `total_files`）
+    pub files_processed: usize,
+    /// 計算 hash 時實際讀取的位元組數（只計算部分 hash 的檔案只計入讀取的部分）
+    pub bytes_processed: u64,
+    /// 是否因中斷訊號提前停止
+    pub aborted: bool,
     pub duplicates_found: usize,
     pub duplicates_moved: usize,
+    /// 送到資源回收筒的重複檔案數
+    pub duplicates_trashed: usize,
+    pub duplicates_deleted: usize,
+    pub duplicates_hardlinked: usize,
     pub new_files_registered: usize,
+    /// 刪除或硬連結模式下，紀錄中的原始檔案本身（重新掃描同一資料夾時）
+    pub already_recorded: usize,
+    /// 登記的參考資料夾檔案數
+    pub reference_files_indexed: usize,
+    /// 因檔案大小被略過的檔案數
+    pub filtered_by_size: usize,
+    /// 參考資料夾中因檔案大小被略過的檔案數
+    pub reference_filtered_by_size: usize,
+    /// 重複檔案的總大小（移除後可釋放的空間）
+    pub bytes_reclaimable: u64,
+    /// 本次實際移走、刪除或以硬連結取代的檔案總大小
+    pub bytes_reclaimed: u64,
+    /// 本次找到的重複檔案，依可釋放空間由大到小排序
+    pub duplicate_groups: Vec<DuplicateGroup>,
+    /// 與紀錄中的影片畫面相似的影片（畫面相似比對時），依路徑排序
+    pub similar_videos: Vec<SimilarVideo>,
     pub errors: usize,
 }
 
+impl DuplicationResult {
+    /// 整體結果：中斷優先於處理失敗
+    #[must_use]
+    pub const fn status(&self) -> RunStatus {
+        if self.aborted {
+            RunStatus::Aborted
+        } else {
+            RunStatus::from_failures(self.errors)
+        }
+    }
+}
+
 pub struct DuplicationDetector {
     hash_table: HashTable,
+    /// 使用期間鎖定 hash table，避免其他程序同時寫入
+    hash_table_lock: HashTableLock,
     hash_table_path: PathBuf,
     duplication_directory: PathBuf,
+    action: DuplicateAction,
+    /// 只回報重複檔案，不處理檔案也不更新 hash table
+    dry_run: bool,
+    /// 掃描時略過的檔案與資料夾
+    exclude_filter: ExcludeFilter,
+    /// 掃描時的檔案大小篩選
+    size_filter: Option<SizeFilter>,
+    /// 索引參考資料夾時的檔案大小篩選（範圍相同，篩除數量分開統計）
+    reference_size_filter: Option<SizeFilter>,
+    /// 參考資料夾：只登記到 hash table，其中的檔案不會被移動或刪除
+    reference_directory: Option<PathBuf>,
+    /// 移動模式下重複檔案的去向
+    deletion_mode: DeletionMode,
+    /// 同時計算 hash 的執行緒數，0 代表不限制
+    hash_threads: usize,
+    /// 與紀錄中的原始檔案重複時保留的檔案，`None` 時保留原始檔案
+    keep_policy: Option<KeepPolicy>,
+    /// 畫面相似比對，`None` 時只比對內容完全相同的檔案
+    perceptual: Option<PerceptualMatcher>,
+    /// 計算 hash 時實際讀取的位元組數
+    bytes_read: AtomicU64,
+    /// 依保留方式被取代而已處理的原始檔案，仍在佇列中時略過
+    replaced_originals: Mutex<HashSet<PathBuf>>,
     shutdown_signal: Arc<AtomicBool>,
 }
 
@@ -28,58 +104,254 @@ impl DuplicationDetector {
     pub fn new(
         hash_table_path: &Path,
         base_directory: &Path,
+        action: DuplicateAction,
+        dry_run: bool,
         shutdown_signal: Arc<AtomicBool>,
     ) -> Result<Self> {
         let duplication_directory = base_directory.join("duplication_file");
-        ensure_directory_exists(&duplication_directory)?;
+        // 只有在實際移動重複檔案時才建立 duplication_file 目錄
+        if action == DuplicateAction::Move && !dry_run {
+            ensure_directory_exists(&duplication_directory)?;
+        }
 
+        let hash_table_lock = HashTableLock::acquire(hash_table_path)?;
         let hash_table = HashTable::load_from_file(hash_table_path)?;
 
         Ok(Self {
             hash_table,
+            hash_table_lock,
             hash_table_path: hash_table_path.to_path_buf(),
             duplication_directory,
+            action,
+            dry_run,
+            exclude_filter: ExcludeFilter::default(),
+            size_filter: None,
+            reference_size_filter: None,
+            reference_directory: None,
+            deletion_mode: DeletionMode::default(),
+            hash_threads: 0,
+            keep_policy: None,
+            perceptual: None,
+            bytes_read: AtomicU64::new(0),
+            replaced_originals: Mutex::new(HashSet::new()),
             shutdown_signal,
         })
     }
 
+    /// 限制同時計算 hash 的執行緒數（0 代表依 CPU 核心數）
+    #[must_use]
+    pub const fn with_hash_threads(mut self, hash_threads: usize) -> Self {
+        self.hash_threads = hash_threads;
+        self
+    }
+
+    /// 設定與紀錄中的原始檔案重複時保留的檔案
+    ///
+    /// 掃描資料夾中的原始檔案較不應保留時，改以重複檔案為原始檔案並處理原本的原始檔案
+    #[must_use]
+    pub const fn with_keep_policy(mut self, keep_policy: Option<KeepPolicy>) -> Self {
+        self.keep_policy = keep_policy;
+        self
+    }
+
+    /// 另以影格的感知 hash 比對新影片，找出重新編碼或縮放過的相同影片
+    ///
+    /// 畫面相似的影片內容並不相同，只在移動模式下移走；刪除與硬連結模式下只回報
+    #[must_use]
+    pub fn with_perceptual_match(
+        mut self,
+        settings: PerceptualSettings,
+        file_type_table: FileTypeTable,
+    ) -> Self {
+        self.perceptual = Some(PerceptualMatcher::new(settings, file_type_table));
+        self
+    }
+
+    /// 設定移動模式下重複檔案移到資料夾或送到資源回收筒
+    #[must_use]
+    pub const fn with_deletion_mode(mut self, deletion_mode: DeletionMode) -> Self {
+        self.deletion_mode = deletion_mode;
+        self
+    }
+
+    /// 套用掃描排除規則
+    #[must_use]
+    pub fn with_exclude_filter(mut self, exclude_filter: ExcludeFilter) -> Self {
+        self.exclude_filter = exclude_filter;
+        self
+    }
+
+    /// 套用掃描檔案大小篩選
+    #[must_use]
+    pub fn with_size_filter(mut self, size_filter: SizeFilter) -> Self {
+        self.reference_size_filter = Some(size_filter.same_range());
+        self.size_filter = Some(size_filter);
+        self
+    }
+
+    /// 掃描前先將參考資料夾登記到 hash table
+    ///
+    /// 參考資料夾只建立索引，之後只處理掃描資料夾中與其重複的檔案
+    #[must_use]
+    pub fn with_reference_directory(mut self, reference_directory: PathBuf) -> Self {
+        self.reference_directory = Some(reference_directory);
+        self
+    }
+
+    /// 掃描資料夾並處理重複檔案
+    ///
+    /// 設定參考資料夾時，先索引參考資料夾（不移動也不刪除），再掃描 `directory`
     pub fn detect_and_move_duplicates(&mut self, directory: &Path) -> Result<DuplicationResult> {
+        let reference_files = match &self.reference_directory {
+            Some(reference) => {
+                ensure_separate_directories(reference, directory)?;
+                info!("開始索引參考資料夾: {}", reference.display());
+                scan_all_files(
+                    reference,
+                    &self.exclude_filter,
+                    self.reference_size_filter.as_ref(),
+                )?
+            }
+            None => Vec::new(),
+        };
+
         info!("開始掃描目錄: {}", directory.display());
 
-        let files = scan_all_files(directory)?;
+        let files: Vec<FileInfo> =
+            scan_all_files(directory, &self.exclude_filter, self.size_filter.as_ref())?
+                .into_iter()
+                .filter(|file| file.path != self.hash_table_lock.path())
+                .collect();
         let total_files = files.len();
 
         info!("找到 {total_files} 個檔案，開始去重檢查...");
+        if let Some(matcher) = &self.perceptual {
+            matcher.load(&self.hash_table_path)?;
+        }
 
+        let files_processed = AtomicUsize::new(0);
         let duplicates_found = AtomicUsize::new(0);
         let duplicates_moved = AtomicUsize::new(0);
+        let duplicates_trashed = AtomicUsize::new(0);
+        let duplicates_deleted = AtomicUsize::new(0);
+        let duplicates_hardlinked = AtomicUsize::new(0);
         let new_files_registered = AtomicUsize::new(0);
+        let already_recorded = AtomicUsize::new(0);
+        let bytes_reclaimed = AtomicU64::new(0);
         let errors = AtomicUsize::new(0);
 
         let hash_table = Arc::new(Mutex::new(std::mem::take(&mut self.hash_table)));
         let duplication_directory = self.duplication_directory.clone();
         let shutdown_signal = Arc::clone(&self.shutdown_signal);
 
-        // 使用 rayon 平行處理
-        files.par_iter().for_each(|file| {
-            if shutdown_signal.load(Ordering::SeqCst) {
-                return;
+        let reference_files_indexed = match &self.reference_directory {
+            Some(reference) if !reference_files.is_empty() => {
+                run_with_threads(self.hash_threads, || {
+                    self.index_reference_files(reference, &reference_files, &hash_table)
+                })?
             }
+            _ => 0,
+        };
+        // 讀取量只計算掃描資料夾
+        self.bytes_read.store(0, Ordering::SeqCst);
 
-            match self.process_file(file, &hash_table, &duplication_directory) {
-                Ok(ProcessResult::Duplicate) => {
-                    duplicates_found.fetch_add(1, Ordering::SeqCst);
-                    duplicates_moved.fetch_add(1, Ordering::SeqCst);
-                }
-                Ok(ProcessResult::New) => {
-                    new_files_registered.fetch_add(1, Ordering::SeqCst);
+        let groups = Mutex::new(DuplicateGroups::default());
+        let similar = Mutex::new(Vec::new());
+        let progress_bar = Arc::new(new_progress_bar(total_files));
+        progress_bar.set_message("重複: 0");
+
+        // 使用 rayon 平行處理
+        run_with_threads(self.hash_threads, || {
+            files.par_iter().for_each(|file| {
+                if shutdown_signal.load(Ordering::SeqCst) {
+                    return;
                 }
-                Err(e) => {
-                    error!("處理檔案失敗 {}: {}", file.path.display(), e);
-                    errors.fetch_add(1, Ordering::SeqCst);
+
+                let found_duplicate = || {
+                    duplicates_found.fetch_add(1, Ordering::SeqCst);
+                };
+                let reclaimed = || {
+                    bytes_reclaimed.fetch_add(file.size, Ordering::SeqCst);
+                };
+
+                match self.process_file(
+                    file,
+                    directory,
+                    &hash_table,
+                    &duplication_directory,
+                    &groups,
+                    &similar,
+                ) {
+                    Ok(ProcessResult::Duplicate(action)) => {
+                        found_duplicate();
+                        let counter = match action {
+                            DuplicateAction::Move => &duplicates_moved,
+                            DuplicateAction::Delete => &duplicates_deleted,
+                            DuplicateAction::Hardlink => &duplicates_hardlinked,
+                        };
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        reclaimed();
+                    }
+                    Ok(ProcessResult::Trashed) => {
+                        found_duplicate();
+                        duplicates_trashed.fetch_add(1, Ordering::SeqCst);
+                        reclaimed();
+                    }
+                    Ok(ProcessResult::Reported) => {
+                        found_duplicate();
+                    }
+                    Ok(ProcessResult::Similar(disposal)) => match disposal {
+                        Some(Disposal::Trashed) => {
+                            duplicates_trashed.fetch_add(1, Ordering::SeqCst);
+                            reclaimed();
+                        }
+                        Some(Disposal::MovedTo(_)) => {
+                            duplicates_moved.fetch_add(1, Ordering::SeqCst);
+                            reclaimed();
+                        }
+                        None => {}
+                    },
+                    Ok(ProcessResult::New) => {
+                        new_files_registered.fetch_add(1, Ordering::SeqCst);
+                    }
+                    Ok(ProcessResult::AlreadyRecorded) => {
+                        already_recorded.fetch_add(1, Ordering::SeqCst);
+                    }
+                    // 已在取代原始檔案時處理並計入
+                    Ok(ProcessResult::Replaced) => {}
+                    Err(_) if self.was_replaced(&file.path) => {}
+                    // 計算 hash 途中中斷的檔案視為未處理，不計入錯誤
+                    Err(e) if e.is::<HashAborted>() => return,
+                    Err(e) => {
+                        error!("處理檔案失敗 {}: {}", file.path.display(), e);
+                        errors.fetch_add(1, Ordering::SeqCst);
+                    }
                 }
-            }
-        });
+
+                files_processed.fetch_add(1, Ordering::SeqCst);
+                progress_bar.set_message(format!(
+                    "重複: {}，已讀取 {}",
+                    duplicates_found.load(Ordering::SeqCst),
+                    format_size(self.bytes_read.load(Ordering::SeqCst))
+                ));
+                progress_bar.inc(1);
+            })
+        })?;
+
+        let aborted = shutdown_signal.load(Ordering::SeqCst);
+        let summary = format!(
+            "重複: {}，已讀取 {}",
+            duplicates_found.load(Ordering::SeqCst),
+            format_size(self.bytes_read.load(Ordering::SeqCst))
+        );
+        if aborted {
+            progress_bar.abandon_with_message(format!(
+                "已中斷（處理 {} 個檔案後停止），{summary}",
+                files_processed.load(Ordering::SeqCst)
+            ));
+        } else {
+            progress_bar.finish_with_message(format!("完成，{summary}"));
+        }
 
         // 取回 hash_table
         self.hash_table = Arc::try_unwrap(hash_table)
@@ -87,16 +359,51 @@ impl DuplicationDetector {
             .into_inner()
             .map_err(|e| anyhow::anyhow!("Mutex poisoned: {e}"))?;
 
-        // 儲存更新後的 hash table
-        self.hash_table
-            .save_to_file(&self.hash_table_path)
-            .with_context(|| "無法儲存 hash table")?;
+        // 儲存更新後的 hash table（只回報時保留原本的紀錄）
+        if !self.dry_run {
+            self.hash_table
+                .save_to_file(&self.hash_table_path)
+                .with_context(|| "無法儲存 hash table")?;
+            if let Some(matcher) = &self.perceptual {
+                matcher.save(&self.hash_table_path)?;
+            }
+        }
+
+        let duplicate_groups = groups
+            .into_inner()
+            .map_err(|e| anyhow::anyhow!("Mutex poisoned: {e}"))?
+            .into_sorted();
+        let bytes_reclaimable = duplicate_groups
+            .iter()
+            .map(DuplicateGroup::wasted_bytes)
+            .sum();
+        let mut similar_videos = similar
+            .into_inner()
+            .map_err(|e| anyhow::anyhow!("Mutex poisoned: {e}"))?;
+        similar_videos.sort_by(|a: &SimilarVideo, b| a.path.cmp(&b.path));
 
         let result = DuplicationResult {
             total_files,
+            files_processed: files_processed.load(Ordering::SeqCst),
+            bytes_processed: self.bytes_read.load(Ordering::SeqCst),
+            aborted,
             duplicates_found: duplicates_found.load(Ordering::SeqCst),
             duplicates_moved: duplicates_moved.load(Ordering::SeqCst),
+            duplicates_trashed: duplicates_trashed.load(Ordering::SeqCst),
+            duplicates_deleted: duplicates_deleted.load(Ordering::SeqCst),
+            duplicates_hardlinked: duplicates_hardlinked.load(Ordering::SeqCst),
             new_files_registered: new_files_registered.load(Ordering::SeqCst),
+            already_recorded: already_recorded.load(Ordering::SeqCst),
+            reference_files_indexed,
+            bytes_reclaimable,
+            bytes_reclaimed: bytes_reclaimed.load(Ordering::SeqCst),
+            duplicate_groups,
+            similar_videos,
+            filtered_by_size: self.size_filter.as_ref().map_or(0, SizeFilter::rejected),
+            reference_filtered_by_size: self
+                .reference_size_filter
+                .as_ref()
+                .map_or(0, SizeFilter::rejected),
             errors: errors.load(Ordering::SeqCst),
         };
 
@@ -108,111 +415,1031 @@ impl DuplicationDetector {
         Ok(result)
     }
 
-    fn process_file(
+    /// 索引參考資料夾的檔案，回傳登記的檔案數
+    fn index_reference_files(
         &self,
-        file: &FileInfo,
+        reference_directory: &Path,
+        files: &[FileInfo],
         hash_table: &Arc<Mutex<HashTable>>,
-        duplication_directory: &Path,
-    ) -> Result<ProcessResult> {
-        let size = file.size;
+    ) -> usize {
+        let indexed = AtomicUsize::new(0);
+        let progress_bar = Arc::new(new_progress_bar(files.len()));
+        progress_bar.set_message("索引參考資料夾");
 
-        // 先檢查是否有相同大小的檔案
-        let has_same_size = {
-            let table = hash_table
-                .lock()
-                .map_err(|e| anyhow::anyhow!("Lock failed: {e}"))?;
-            table.has_size(size)
-        };
+        files.par_iter().for_each(|file| {
+            if self.shutdown_signal.load(Ordering::SeqCst) {
+                return;
+            }
 
-        if !has_same_size {
-            // 沒有相同大小的檔案，這是新檔案，計算 hash 並加入
-            let hash = calculate_file_hash(&file.path)?;
+            match self.register_reference_file(file, hash_table, reference_directory) {
+                Ok(()) => {
+                    indexed.fetch_add(1, Ordering::SeqCst);
+                }
+                Err(e) if e.is::<HashAborted>() => return,
+                Err(e) => error!("索引檔案失敗 {}: {}", file.path.display(), e),
+            }
+            progress_bar.inc(1);
+        });
+
+        progress_bar.finish_and_clear();
+        let indexed = indexed.load(Ordering::SeqCst);
+        info!("參考資料夾索引完成: {indexed} 個檔案");
+        indexed
+    }
+
+    /// 登記參考檔案
+    ///
+    /// 已有紀錄但原始檔案不在參考資料夾（或已不存在）時，改以參考檔案為原始檔案，
+    /// 讓掃描資料夾中先前登記過的相同檔案也會被視為重複
+    fn register_reference_file(
+        &self,
+        file: &FileInfo,
+        hash_table: &Arc<Mutex<HashTable>>,
+        reference_directory: &Path,
+    ) -> Result<()> {
+        if let Some(matcher) = &self.perceptual {
+            matcher.register(file, &self.shutdown_signal)?;
+        }
+        let Some((hash, original)) = self.lookup_or_register(file, hash_table)? else {
+            return Ok(());
+        };
+        if original.is_none_or(|path| !path.starts_with(reference_directory) || !path.is_file()) {
             let mut table = hash_table
                 .lock()
                 .map_err(|e| anyhow::anyhow!("Lock failed: {e}"))?;
-            table.insert(size, hash);
-            return Ok(ProcessResult::New);
+            table.insert(file.size, hash, &file.path);
         }
+        Ok(())
+    }
 
-        // 有相同大小的檔案，計算 hash 來確認是否重複
-        let hash = calculate_file_hash(&file.path)?;
+    /// 查詢檔案是否已有紀錄，沒有時登記為新檔案
+    ///
+    /// 已有紀錄時回傳完整 hash 與紀錄中的原始檔案路徑，新檔案回傳 `None`
+    fn lookup_or_register(
+        &self,
+        file: &FileInfo,
+        hash_table: &Arc<Mutex<HashTable>>,
+    ) -> Result<Option<(String, Option<PathBuf>)>> {
+        let size = file.size;
+        self.register_imported(file, hash_table)?;
+        let partial = calculate_partial_hash(&file.path, PARTIAL_HASH_BYTES)?;
+        self.bytes_read
+            .fetch_add(partial_hash_read_bytes(size), Ordering::SeqCst);
 
-        let is_duplicate = {
-            let table = hash_table
+        // 查詢與登記在同一次鎖定內完成，避免兩個相同檔案同時被視為新檔案
+        let pending = {
+            let mut table = hash_table
                 .lock()
                 .map_err(|e| anyhow::anyhow!("Lock failed: {e}"))?;
-            table.contains_hash(size, &hash)
+            match table.register_partial(size, &partial, &file.path) {
+                PartialMatch::Registered => return Ok(None),
+                PartialMatch::Collision(pending) => pending,
+            }
         };
 
-        if is_duplicate {
-            // 是重複檔案，移動到 duplication_file 資料夾
-            self.move_to_duplication_folder(file, duplication_directory)?;
-            Ok(ProcessResult::Duplicate)
-        } else {
-            // 相同大小但不同 hash，加入到 hash table
+        // 部分 hash 相同才計算完整 hash，先前只登記部分 hash 的檔案也一併補上
+        let hash = full_hash(&file.path, size, &partial, &self.shutdown_signal)?;
+        self.bytes_read
+            .fetch_add(full_hash_read_bytes(size), Ordering::SeqCst);
+        let pending = match pending {
+            Some(path) if path == file.path => Some((hash.clone(), path)),
+            Some(path) => match pending_full_hash(&path, size, &partial, &self.shutdown_signal) {
+                Ok(Some(pending_hash)) => Some((pending_hash, path)),
+                Ok(None) => {
+                    info!("已記錄的檔案內容已變更，捨棄該紀錄: {}", path.display());
+                    None
+                }
+                // 中斷時保留先前檔案的部分 hash 紀錄，下次掃描再補上
+                Err(e) if e.is::<HashAborted>() => return Err(e),
+                Err(e) => {
+                    warn!("無法計算已記錄檔案的 hash，略過該紀錄: {e:#}");
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let known_original = {
             let mut table = hash_table
                 .lock()
                 .map_err(|e| anyhow::anyhow!("Lock failed: {e}"))?;
-            table.insert(size, hash);
-            Ok(ProcessResult::New)
+            table.resolve_partial(size, &partial, pending);
+            if table.contains_hash(size, &hash) {
+                Some(table.original_path(size, &hash).map(Path::to_path_buf))
+            } else {
+                table.insert(size, hash.clone(), &file.path);
+                None
+            }
+        };
+
+        Ok(known_original.map(|original| (hash, original)))
+    }
+
+    /// 先登記相同大小、從其他工具匯入但尚未計算 hash 的檔案
+    ///
+    /// 已不存在或大小已改變的匯入檔案直接捨棄；與目前檔案相同路徑時交由呼叫端登記
+    fn register_imported(&self, file: &FileInfo, hash_table: &Arc<Mutex<HashTable>>) -> Result<()> {
+        let imported = hash_table
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock failed: {e}"))?
+            .take_unhashed(file.size);
+        for (index, path) in imported.iter().enumerate() {
+            if *path == file.path
+                || !fs::metadata(path).is_ok_and(|m| m.is_file() && m.len() == file.size)
+            {
+                continue;
+            }
+            let imported_file = FileInfo {
+                path: path.clone(),
+                size: file.size,
+                modified: None,
+            };
+            match self.lookup_or_register(&imported_file, hash_table) {
+                Ok(_) => {}
+                Err(e) if e.is::<HashAborted>() => {
+                    // 中斷時放回尚未登記的匯入檔案，下次掃描再計算
+                    let mut table = hash_table
+                        .lock()
+                        .map_err(|e| anyhow::anyhow!("Lock failed: {e}"))?;
+                    for path in &imported[index..] {
+                        table.import_file(file.size, None, path);
+                    }
+                    return Err(e);
+                }
+                Err(e) => warn!(
+                    "無法計算匯入檔案的 hash，略過該紀錄 {}: {e:#}",
+                    imported_file.path.display()
+                ),
+            }
         }
+        Ok(())
     }
 
-    fn move_to_duplication_folder(
+    fn process_file(
         &self,
         file: &FileInfo,
+        directory: &Path,
+        hash_table: &Arc<Mutex<HashTable>>,
         duplication_directory: &Path,
-    ) -> Result<()> {
-        let file_name = file
-            .path
-            .file_name()
-            .ok_or_else(|| anyhow::anyhow!("無法取得檔案名稱"))?;
-
-        let mut dest_path = duplication_directory.join(file_name);
-
-        // 如果目標已存在，加上編號
-        if dest_path.exists() {
-            let stem = file
-                .path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("file");
-            let ext = file.path.extension().and_then(|s| s.to_str()).unwrap_or("");
-
-            let mut counter = 1;
-            loop {
-                let new_name = if ext.is_empty() {
-                    format!("{stem}_{counter}")
-                } else {
-                    format!("{stem}_{counter}.{ext}")
-                };
-                dest_path = duplication_directory.join(&new_name);
-                if !dest_path.exists() {
-                    break;
+        groups: &Mutex<DuplicateGroups>,
+        similar: &Mutex<Vec<SimilarVideo>>,
+    ) -> Result<ProcessResult> {
+        if self.was_replaced(&file.path) {
+            return Ok(ProcessResult::Replaced);
+        }
+        let Some((hash, original)) = self.lookup_or_register(file, hash_table)? else {
+            return match &self.perceptual {
+                Some(matcher) => {
+                    self.match_perceptual(matcher, file, duplication_directory, similar)
+                }
+                None => Ok(ProcessResult::New),
+            };
+        };
+        // 依保留方式改以此檔案為原始檔案時，改為處理被取代的原始檔案
+        let (duplicate, original) = match self.keep_policy {
+            Some(policy) => {
+                let decision = hash_table
+                    .lock()
+                    .map_err(|e| anyhow::anyhow!("Lock failed: {e}"))?
+                    .choose_original(file.size, &hash, &file.path, policy, directory);
+                match decision {
+                    KeepDecision::Original(original) => (file.path.clone(), original),
+                    KeepDecision::Replaced(replaced) => {
+                        info!(
+                            "依保留方式改以 {} 為原始檔案，改為處理 {}",
+                            file.path.display(),
+                            replaced.display()
+                        );
+                        self.replaced_originals
+                            .lock()
+                            .map_err(|e| anyhow::anyhow!("Lock failed: {e}"))?
+                            .insert(replaced.clone());
+                        (replaced, Some(file.path.clone()))
+                    }
                 }
-                counter += 1;
             }
+            None => (file.path.clone(), original),
+        };
+        let record = |original: Option<PathBuf>| -> Result<()> {
+            groups
+                .lock()
+                .map_err(|e| anyhow::anyhow!("Lock failed: {e}"))?
+                .add(file.size, hash.clone(), original, duplicate.clone());
+            Ok(())
+        };
+
+        if self.action == DuplicateAction::Move {
+            if self.dry_run {
+                record(original.clone())?;
+                return Ok(report_duplicate(&duplicate, original.as_deref()));
+            }
+            let disposal = move_to_duplication_folder(
+                &duplicate,
+                duplication_directory,
+                original.as_deref(),
+                self.deletion_mode,
+            )?;
+            record(original)?;
+            return Ok(match disposal {
+                Disposal::Trashed => ProcessResult::Trashed,
+                Disposal::MovedTo(_) => ProcessResult::Duplicate(DuplicateAction::Move),
+            });
         }
 
-        fs::rename(&file.path, &dest_path).with_context(|| {
-            format!(
-                "無法移動重複檔案: {} -> {}",
-                file.path.display(),
-                dest_path.display()
-            )
-        })?;
+        // 紀錄中的原始檔案就是此檔案本身（重新掃描同一資料夾），刪除會失去唯一的副本
+        if original
+            .as_deref()
+            .is_some_and(|original| is_same_path(original, &duplicate))
+        {
+            return Ok(ProcessResult::AlreadyRecorded);
+        }
+
+        // 刪除或硬連結前必須確認原始檔案仍在，否則可能刪掉唯一的副本
+        let Some(original) = original.filter(|path| path.is_file()) else {
+            warn!(
+                "找不到重複檔案的原始檔案，改將此檔案記錄為原始檔案: {}",
+                file.path.display()
+            );
+            let mut table = hash_table
+                .lock()
+                .map_err(|e| anyhow::anyhow!("Lock failed: {e}"))?;
+            table.insert(file.size, hash.clone(), &file.path);
+            return Ok(ProcessResult::New);
+        };
+
+        if self.dry_run {
+            record(Some(original.clone()))?;
+            return Ok(report_duplicate(&duplicate, Some(&original)));
+        }
+
+        let recorded_mtime = hash_table
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock failed: {e}"))?
+            .original_mtime(file.size, &hash);
+        self.verify_original(&original, file.size, &hash, recorded_mtime)?;
+
+        if self.action == DuplicateAction::Delete {
+            fs::remove_file(&duplicate)
+                .with_context(|| format!("無法刪除重複檔案: {}", duplicate.display()))?;
+            info!(
+                "刪除重複檔案: {}（原始檔案: {}）",
+                duplicate.display(),
+                original.display()
+            );
+        } else {
+            replace_with_hardlink(&original, &duplicate)?;
+            info!(
+                "以硬連結取代重複檔案: {} -> {}",
+                duplicate.display(),
+                original.display()
+            );
+        }
+        record(Some(original))?;
+        Ok(ProcessResult::Duplicate(self.action))
+    }
+
+    /// 檔案是否為依保留方式被取代的原始檔案（已當作重複檔案處理）
+    fn was_replaced(&self, path: &Path) -> bool {
+        self.replaced_originals
+            .lock()
+            .is_ok_and(|replaced| replaced.contains(path))
+    }
+
+    /// 刪除或硬連結前確認原始檔案在登記後沒有被修改
+    ///
+    /// 大小與修改時間都和紀錄相同時直接通過，否則重新計算完整 hash；
+    /// 內容已不同時回傳錯誤，呼叫端略過此重複檔案
+    fn verify_original(
+        &self,
+        original: &Path,
+        size: u64,
+        hash: &str,
+        recorded_mtime: Option<u64>,
+    ) -> Result<()> {
+        let current_size = fs::metadata(original)
+            .with_context(|| format!("無法讀取原始檔案: {}", original.display()))?
+            .len();
+        if current_size == size {
+            if recorded_mtime.is_some() && file_mtime(original) == recorded_mtime {
+                return Ok(());
+            }
+            let partial = calculate_partial_hash(original, PARTIAL_HASH_BYTES)?;
+            if full_hash(original, size, &partial, &self.shutdown_signal)? == hash {
+                return Ok(());
+            }
+        }
+        bail!(
+            "原始檔案在登記後已變更，保留重複檔案: {}",
+            original.display()
+        )
+    }
 
+    /// 以畫面比對內容沒有紀錄的檔案，與紀錄中的影片相似時在移動模式下移走
+    fn match_perceptual(
+        &self,
+        matcher: &PerceptualMatcher,
+        file: &FileInfo,
+        duplication_directory: &Path,
+        similar: &Mutex<Vec<SimilarVideo>>,
+    ) -> Result<ProcessResult> {
+        let Some(found) = matcher.match_or_register(file, &self.shutdown_signal)? else {
+            return Ok(ProcessResult::New);
+        };
         info!(
-            "移動重複檔案: {} -> {}",
-            file.path.display(),
-            dest_path.display()
+            "發現畫面相似的影片: {}（紀錄中的影片: {}，距離 {}）",
+            found.path.display(),
+            found.original.display(),
+            found.distance
         );
+        let disposal = if self.action == DuplicateAction::Move && !self.dry_run {
+            Some(move_to_duplication_folder(
+                &file.path,
+                duplication_directory,
+                Some(&found.original),
+                self.deletion_mode,
+            )?)
+        } else {
+            None
+        };
+        similar
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock failed: {e}"))?
+            .push(found);
+        Ok(ProcessResult::Similar(disposal))
+    }
+}
 
-        Ok(())
+/// 將重複檔案移動到 duplication_file 資料夾（同名時加上編號），或依設定送到資源回收筒
+pub(super) fn move_to_duplication_folder(
+    path: &Path,
+    duplication_directory: &Path,
+    original: Option<&Path>,
+    deletion_mode: DeletionMode,
+) -> Result<Disposal> {
+    let disposal = dispose_file(path, duplication_directory, deletion_mode)
+        .with_context(|| format!("無法處理重複檔案: {}", path.display()))?;
+
+    let destination = match &disposal {
+        Disposal::Trashed => "資源回收筒".to_string(),
+        Disposal::MovedTo(dest_path) => dest_path.display().to_string(),
+    };
+    match original {
+        Some(original) => info!(
+            "移動重複檔案: {} -> {destination}（原始檔案: {}）",
+            path.display(),
+            original.display()
+        ),
+        None => info!("移動重複檔案: {} -> {destination}", path.display()),
     }
+
+    Ok(disposal)
 }
 
 enum ProcessResult {
-    Duplicate,
+    Duplicate(DuplicateAction),
+    /// 已送到資源回收筒
+    Trashed,
+    /// 只回報，未處理
+    Reported,
     New,
+    AlreadyRecorded,
+    /// 依保留方式被取代的原始檔案，已當作重複檔案處理
+    Replaced,
+    /// 與紀錄中的影片畫面相似；移走時附帶去向
+    Similar(Option<Disposal>),
+}
+
+fn report_duplicate(path: &Path, original: Option<&Path>) -> ProcessResult {
+    match original {
+        Some(original) => info!(
+            "發現重複檔案: {}（原始檔案: {}）",
+            path.display(),
+            original.display()
+        ),
+        None => info!("發現重複檔案: {}", path.display()),
+    }
+    ProcessResult::Reported
+}
+
+pub(super) fn new_progress_bar(len: usize) -> ProgressBar {
+    let progress_bar = ProgressBar::new(len as u64);
+    progress_bar.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}")
+            .expect("Invalid progress bar template")
+            .progress_chars("#>-"),
+    );
+    progress_bar
+}
+
+/// 在最多 `threads` 個執行緒的 rayon 執行緒池中執行，0 代表使用全域執行緒池
+pub(super) fn run_with_threads<R: Send>(
+    threads: usize,
+    op: impl FnOnce() -> R + Send,
+) -> Result<R> {
+    if threads == 0 {
+        return Ok(op());
+    }
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .with_context(|| "無法建立 hash 執行緒池")?;
+    Ok(pool.install(op))
+}
+
+/// 參考資料夾與掃描資料夾不可互相包含，否則參考檔案也會被當成重複處理
+fn ensure_separate_directories(reference: &Path, directory: &Path) -> Result<()> {
+    let reference = reference
+        .canonicalize()
+        .with_context(|| format!("無法解析參考資料夾: {}", reference.display()))?;
+    let directory = directory
+        .canonicalize()
+        .with_context(|| format!("無法解析掃描資料夾: {}", directory.display()))?;
+    if reference.starts_with(&directory) || directory.starts_with(&reference) {
+        anyhow::bail!(
+            "參考資料夾與掃描資料夾不可互相包含: {} / {}",
+            reference.display(),
+            directory.display()
+        );
+    }
+    Ok(())
+}
+
+/// 計算部分 hash 時讀取的位元組數
+pub(super) const fn partial_hash_read_bytes(size: u64) -> u64 {
+    if size < PARTIAL_HASH_BYTES * 2 {
+        size
+    } else {
+        PARTIAL_HASH_BYTES * 2
+    }
+}
+
+/// [`full_hash`] 另外讀取的位元組數（部分 hash 已涵蓋整個檔案時為 0）
+pub(super) const fn full_hash_read_bytes(size: u64) -> u64 {
+    if size <= PARTIAL_HASH_BYTES * 2 {
+        0
+    } else {
+        size
+    }
+}
+
+/// 計算完整 hash；檔案小到部分 hash 已涵蓋整個檔案時直接沿用
+///
+/// 收到中斷訊號時回傳 [`HashAborted`]
+pub(super) fn full_hash(
+    path: &Path,
+    size: u64,
+    partial: &str,
+    shutdown_signal: &AtomicBool,
+) -> Result<String> {
+    if size <= PARTIAL_HASH_BYTES * 2 {
+        return Ok(partial.to_string());
+    }
+    calculate_file_hash_cancellable(path, shutdown_signal)
+}
+
+/// 計算先前只登記部分 hash 的檔案的完整 hash
+///
+/// 紀錄可能來自先前的掃描，檔案已不存在或內容已改變（部分 hash 不同）時視為過期並回傳 `None`，
+/// 不沿用舊的部分 hash
+fn pending_full_hash(
+    path: &Path,
+    size: u64,
+    partial: &str,
+    shutdown_signal: &AtomicBool,
+) -> Result<Option<String>> {
+    if !fs::metadata(path).is_ok_and(|m| m.is_file() && m.len() == size) {
+        return Ok(None);
+    }
+    let current = calculate_partial_hash(path, PARTIAL_HASH_BYTES)?;
+    if current != partial {
+        return Ok(None);
+    }
+    full_hash(path, size, &current, shutdown_signal).map(Some)
+}
+
+/// 兩個路徑是否指向同一個檔案（無法正規化時直接比較）
+fn is_same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// 以指向原始檔案的硬連結取代重複檔案
+///
+/// 先在同一資料夾建立暫存連結再改名覆蓋，失敗時重複檔案保持原狀
+pub(super) fn replace_with_hardlink(original: &Path, duplicate: &Path) -> Result<()> {
+    let file_name = duplicate
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("無法取得檔案名稱"))?;
+    let temp_path =
+        duplicate.with_file_name(format!(".{}.hardlink_tmp", file_name.to_string_lossy()));
+
+    fs::hard_link(original, &temp_path).with_context(|| {
+        format!(
+            "無法建立硬連結（原始檔案與重複檔案必須在同一個磁碟）: {} -> {}",
+            temp_path.display(),
+            original.display()
+        )
+    })?;
+
+    if let Err(e) = fs::rename(&temp_path, duplicate) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e)
+            .with_context(|| format!("無法以硬連結取代重複檔案: {}", duplicate.display()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn detect(dir: &Path, action: DuplicateAction) -> DuplicationResult {
+        detect_with(dir, action, false)
+    }
+
+    fn detect_with(dir: &Path, action: DuplicateAction, dry_run: bool) -> DuplicationResult {
+        let hash_table_path = dir.join("hash_table.json");
+        let mut detector = DuplicationDetector::new(
+            &hash_table_path,
+            dir,
+            action,
+            dry_run,
+            Arc::new(AtomicBool::new(false)),
+        )
+        .unwrap();
+        detector
+            .detect_and_move_duplicates(&dir.join("files"))
+            .unwrap()
+    }
+
+    fn write_files(dir: &Path) -> PathBuf {
+        let files = dir.join("files");
+        fs::create_dir(&files).unwrap();
+        fs::write(files.join("a.bin"), b"same content").unwrap();
+        fs::write(files.join("b.bin"), b"same content").unwrap();
+        fs::write(files.join("c.bin"), b"different").unwrap();
+        files
+    }
+
+    #[test]
+    fn test_bytes_processed_counts_only_partial_reads() {
+        let dir = TempDir::new().unwrap();
+        let files = dir.path().join("files");
+        fs::create_dir(&files).unwrap();
+        let size = usize::try_from(PARTIAL_HASH_BYTES * 3).unwrap();
+        fs::write(files.join("large.bin"), vec![7u8; size]).unwrap();
+
+        let mut detector = DuplicationDetector::new(
+            &dir.path().join("hash_table.json"),
+            dir.path(),
+            DuplicateAction::Delete,
+            true,
+            Arc::new(AtomicBool::new(false)),
+        )
+        .unwrap();
+        let result = detector.detect_and_move_duplicates(&files).unwrap();
+
+        // 沒有大小相同的檔案時只讀取開頭與結尾
+        assert_eq!(result.new_files_registered, 1);
+        assert_eq!(result.bytes_processed, PARTIAL_HASH_BYTES * 2);
+    }
+
+    #[test]
+    fn test_single_hash_thread_and_abort_summary() {
+        let dir = TempDir::new().unwrap();
+        write_files(dir.path());
+        let hash_table_path = dir.path().join("hash_table.json");
+        let shutdown_signal = Arc::new(AtomicBool::new(false));
+        let new_detector = || {
+            DuplicationDetector::new(
+                &hash_table_path,
+                dir.path(),
+                DuplicateAction::Delete,
+                true,
+                Arc::clone(&shutdown_signal),
+            )
+            .unwrap()
+            .with_hash_threads(1)
+        };
+
+        let result = new_detector()
+            .detect_and_move_duplicates(&dir.path().join("files"))
+            .unwrap();
+        assert_eq!(result.duplicates_found, 1);
+        assert_eq!(result.files_processed, 3);
+        assert_eq!(result.bytes_processed, 12 + 12 + 9);
+        assert!(!result.aborted);
+
+        shutdown_signal.store(true, Ordering::SeqCst);
+        let result = new_detector()
+            .detect_and_move_duplicates(&dir.path().join("files"))
+            .unwrap();
+        assert!(result.aborted);
+        assert_eq!(result.total_files, 3);
+        assert_eq!(result.files_processed, 0);
+    }
+
+    #[test]
+    fn test_delete_keeps_one_copy() {
+        let dir = TempDir::new().unwrap();
+        let files = write_files(dir.path());
+
+        let result = detect(dir.path(), DuplicateAction::Delete);
+        assert_eq!(result.duplicates_found, 1);
+        assert_eq!(result.duplicates_deleted, 1);
+        assert_eq!(result.new_files_registered, 2);
+        assert!(files.join("a.bin").exists() ^ files.join("b.bin").exists());
+        assert!(files.join("c.bin").exists());
+        assert!(!dir.path().join("duplication_file").exists());
+
+        // 重新掃描同一資料夾時，已記錄的原始檔案不會被當成重複
+        let rescan = detect(dir.path(), DuplicateAction::Delete);
+        assert_eq!(rescan.duplicates_found, 0);
+        assert_eq!(rescan.already_recorded, 2);
+    }
+
+    #[test]
+    fn test_changed_original_keeps_duplicate() {
+        let dir = TempDir::new().unwrap();
+        let files = write_files(dir.path());
+        detect(dir.path(), DuplicateAction::Delete);
+        let original = ["a.bin", "b.bin"]
+            .map(|name| files.join(name))
+            .into_iter()
+            .find(|path| path.exists())
+            .unwrap();
+
+        // 登記後原始檔案被改寫為相同大小的不同內容
+        fs::write(&original, b"edit content").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&original)
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(120))
+            .unwrap();
+        let duplicate = files.join("d.bin");
+        fs::write(&duplicate, b"same content").unwrap();
+
+        for action in [DuplicateAction::Delete, DuplicateAction::Hardlink] {
+            let result = detect(dir.path(), action);
+            assert_eq!(result.duplicates_found, 0);
+            assert_eq!(result.errors, 1);
+            assert_eq!(fs::read(&duplicate).unwrap(), b"same content");
+        }
+    }
+
+    #[test]
+    fn test_changed_partial_only_record_is_stale() {
+        let dir = TempDir::new().unwrap();
+        let files = dir.path().join("files");
+        fs::create_dir(&files).unwrap();
+        fs::write(files.join("a.bin"), b"same content").unwrap();
+        // 只有一個檔案時只登記部分 hash
+        detect(dir.path(), DuplicateAction::Delete);
+
+        fs::write(files.join("a.bin"), b"edit content").unwrap();
+        fs::write(files.join("b.bin"), b"same content").unwrap();
+        let result = detect(dir.path(), DuplicateAction::Delete);
+        assert_eq!(result.duplicates_found, 0);
+        assert_eq!(result.errors, 0);
+        assert_eq!(fs::read(files.join("b.bin")).unwrap(), b"same content");
+        assert_eq!(fs::read(files.join("a.bin")).unwrap(), b"edit content");
+    }
+
+    #[test]
+    fn test_keep_policy_keeps_newest_copy() {
+        let dir = TempDir::new().unwrap();
+        let files = write_files(dir.path());
+        fs::File::options()
+            .write(true)
+            .open(files.join("a.bin"))
+            .unwrap()
+            .set_modified(std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000))
+            .unwrap();
+
+        let result = DuplicationDetector::new(
+            &dir.path().join("hash_table.json"),
+            dir.path(),
+            DuplicateAction::Delete,
+            false,
+            Arc::new(AtomicBool::new(false)),
+        )
+        .unwrap()
+        .with_keep_policy(Some(KeepPolicy::Newest))
+        .detect_and_move_duplicates(&files)
+        .unwrap();
+        assert_eq!(result.duplicates_deleted, 1);
+        assert_eq!(
+            result.duplicate_groups[0].original.as_deref(),
+            Some(files.join("b.bin").as_path())
+        );
+        assert!(!files.join("a.bin").exists());
+        assert!(files.join("b.bin").exists());
+    }
+
+    #[test]
+    fn test_replaced_original_still_queued_is_skipped() {
+        // 掃描順序取決於檔案系統，兩種檔名順序都測試，確保較新的檔案會先被處理
+        for (older, newer) in [("a.bin", "b.bin"), ("b.bin", "a.bin")] {
+            let dir = TempDir::new().unwrap();
+            let files = dir.path().join("files");
+            fs::create_dir(&files).unwrap();
+            fs::write(files.join(older), b"same content").unwrap();
+            fs::File::options()
+                .write(true)
+                .open(files.join(older))
+                .unwrap()
+                .set_modified(std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000))
+                .unwrap();
+            let detector = || {
+                DuplicationDetector::new(
+                    &dir.path().join("hash_table.json"),
+                    dir.path(),
+                    DuplicateAction::Delete,
+                    false,
+                    Arc::new(AtomicBool::new(false)),
+                )
+                .unwrap()
+                .with_hash_threads(1)
+                .with_keep_policy(Some(KeepPolicy::Newest))
+            };
+            // 先登記較舊的檔案，再次掃描時兩個檔案都在佇列中
+            detector().detect_and_move_duplicates(&files).unwrap();
+            fs::write(files.join(newer), b"same content").unwrap();
+
+            let result = detector().detect_and_move_duplicates(&files).unwrap();
+            assert_eq!(result.errors, 0, "{older}");
+            assert_eq!(result.duplicates_deleted, 1, "{older}");
+            assert_eq!(result.files_processed, 2, "{older}");
+            assert!(!files.join(older).exists());
+            assert!(files.join(newer).exists());
+        }
+    }
+
+    #[test]
+    fn test_hardlink_replaces_duplicate() {
+        let dir = TempDir::new().unwrap();
+        let files = write_files(dir.path());
+
+        let result = detect(dir.path(), DuplicateAction::Hardlink);
+        assert_eq!(result.duplicates_hardlinked, 1);
+        assert_eq!(result.bytes_reclaimed, "same content".len() as u64);
+        assert_eq!(fs::read(files.join("a.bin")).unwrap(), b"same content");
+        assert_eq!(fs::read(files.join("b.bin")).unwrap(), b"same content");
+
+        // 修改其中一個即反映到另一個，代表兩者為同一檔案
+        fs::write(files.join("a.bin"), b"changed").unwrap();
+        assert_eq!(fs::read(files.join("b.bin")).unwrap(), b"changed");
+    }
+
+    #[test]
+    fn test_dry_run_only_reports() {
+        let dir = TempDir::new().unwrap();
+        let files = write_files(dir.path());
+
+        for action in DuplicateAction::ALL {
+            let result = detect_with(dir.path(), action, true);
+            assert_eq!(result.duplicates_found, 1);
+            assert_eq!(result.duplicates_moved, 0);
+            assert_eq!(result.duplicates_deleted, 0);
+            assert_eq!(result.duplicates_hardlinked, 0);
+            assert_eq!(result.bytes_reclaimable, "same content".len() as u64);
+            assert_eq!(result.bytes_reclaimed, 0);
+            assert_eq!(result.duplicate_groups.len(), 1);
+            assert_eq!(result.duplicate_groups[0].duplicates.len(), 1);
+        }
+        assert!(files.join("a.bin").exists());
+        assert!(files.join("b.bin").exists());
+        assert!(!dir.path().join("duplication_file").exists());
+        assert!(!dir.path().join("hash_table.json").exists());
+    }
+
+    #[test]
+    fn test_partial_hash_collision_compares_full_hash() {
+        let dir = TempDir::new().unwrap();
+        let files = dir.path().join("files");
+        fs::create_dir(&files).unwrap();
+
+        // 開頭與結尾相同、中間不同的大檔案不是重複檔案
+        let len = usize::try_from(PARTIAL_HASH_BYTES * 3).unwrap();
+        let content = vec![7u8; len];
+        let mut changed = content.clone();
+        changed[len / 2] = 8;
+        fs::write(files.join("a.bin"), &content).unwrap();
+        fs::write(files.join("b.bin"), &changed).unwrap();
+
+        let result = detect(dir.path(), DuplicateAction::Delete);
+        assert_eq!(result.duplicates_found, 0);
+        assert_eq!(result.new_files_registered, 2);
+
+        // 之後加入的相同檔案以完整 hash 找到原始檔案
+        fs::write(files.join("c.bin"), &content).unwrap();
+        let result = detect(dir.path(), DuplicateAction::Delete);
+        assert_eq!(result.duplicates_found, 1);
+        assert_eq!(result.already_recorded, 2);
+        assert!(files.join("a.bin").exists());
+        assert!(!files.join("c.bin").exists());
+        assert!(files.join("b.bin").exists());
+    }
+
+    #[test]
+    fn test_abort_during_full_hash_keeps_records() {
+        let dir = TempDir::new().unwrap();
+        let files = dir.path().join("files");
+        fs::create_dir(&files).unwrap();
+        let content = vec![7u8; usize::try_from(PARTIAL_HASH_BYTES * 3).unwrap()];
+        fs::write(files.join("a.bin"), &content).unwrap();
+        detect(dir.path(), DuplicateAction::Delete);
+
+        // 比對完整 hash 途中中斷：回傳中斷錯誤，先前檔案的紀錄保持不變
+        fs::write(files.join("c.bin"), &content).unwrap();
+        let mut detector = DuplicationDetector::new(
+            &dir.path().join("hash_table.json"),
+            dir.path(),
+            DuplicateAction::Delete,
+            false,
+            Arc::new(AtomicBool::new(true)),
+        )
+        .unwrap();
+        let hash_table = Arc::new(Mutex::new(std::mem::take(&mut detector.hash_table)));
+        let file = FileInfo {
+            path: files.join("c.bin"),
+            size: content.len() as u64,
+            modified: None,
+        };
+        let err = detector
+            .process_file(
+                &file,
+                &files,
+                &hash_table,
+                &dir.path().join("duplication_file"),
+                &Mutex::new(DuplicateGroups::default()),
+                &Mutex::new(Vec::new()),
+            )
+            .err()
+            .unwrap();
+        assert!(err.is::<HashAborted>());
+        assert_eq!(hash_table.lock().unwrap().len(), 1);
+        drop(detector);
+
+        let result = detect(dir.path(), DuplicateAction::Delete);
+        assert_eq!(result.duplicates_found, 1);
+        assert_eq!(result.errors, 0);
+        assert!(files.join("a.bin").exists());
+        assert!(!files.join("c.bin").exists());
+    }
+
+    #[test]
+    fn test_reference_directory_is_not_modified() {
+        let dir = TempDir::new().unwrap();
+        let files = write_files(dir.path());
+        let reference = dir.path().join("reference");
+        fs::create_dir(&reference).unwrap();
+        fs::write(reference.join("master.bin"), b"different").unwrap();
+        fs::write(reference.join("master_copy.bin"), b"different").unwrap();
+
+        let mut detector = DuplicationDetector::new(
+            &dir.path().join("hash_table.json"),
+            dir.path(),
+            DuplicateAction::Delete,
+            false,
+            Arc::new(AtomicBool::new(false)),
+        )
+        .unwrap()
+        .with_reference_directory(reference.clone())
+        .with_size_filter(SizeFilter::new(Some(2), None));
+        fs::write(reference.join("tiny.bin"), b"x").unwrap();
+        let result = detector.detect_and_move_duplicates(&files).unwrap();
+
+        assert_eq!(result.reference_files_indexed, 2);
+        assert_eq!(result.reference_filtered_by_size, 1);
+        assert_eq!(result.filtered_by_size, 0);
+        assert_eq!(result.duplicates_deleted, 2);
+        assert!(!files.join("c.bin").exists());
+        assert!(files.join("a.bin").exists() ^ files.join("b.bin").exists());
+        assert!(reference.join("master.bin").exists());
+        assert!(reference.join("master_copy.bin").exists());
+    }
+
+    #[test]
+    fn test_reference_directory_must_not_overlap() {
+        let dir = TempDir::new().unwrap();
+        let files = write_files(dir.path());
+
+        let mut detector = DuplicationDetector::new(
+            &dir.path().join("hash_table.json"),
+            dir.path(),
+            DuplicateAction::Delete,
+            false,
+            Arc::new(AtomicBool::new(false)),
+        )
+        .unwrap()
+        .with_reference_directory(dir.path().to_path_buf());
+        assert!(detector.detect_and_move_duplicates(&files).is_err());
+        assert!(files.join("a.bin").exists() && files.join("b.bin").exists());
+    }
+
+    #[test]
+    fn test_perceptual_mode_skips_unreadable_videos() {
+        let dir = TempDir::new().unwrap();
+        let files = write_files(dir.path());
+        fs::write(files.join("broken.mp4"), b"not a video").unwrap();
+        let hash_table_path = dir.path().join("hash_table.json");
+
+        // 無法取樣的影片只略過畫面比對，內容比對照常進行
+        let result = DuplicationDetector::new(
+            &hash_table_path,
+            dir.path(),
+            DuplicateAction::Move,
+            true,
+            Arc::new(AtomicBool::new(false)),
+        )
+        .unwrap()
+        .with_perceptual_match(
+            PerceptualSettings::default(),
+            Config::new().unwrap().file_type_table,
+        )
+        .detect_and_move_duplicates(&files)
+        .unwrap();
+        assert_eq!(result.duplicates_found, 1);
+        assert_eq!(result.new_files_registered, 3);
+        assert!(result.similar_videos.is_empty());
+        assert_eq!(result.errors, 0);
+        assert!(files.join("broken.mp4").exists());
+    }
+
+    #[test]
+    fn test_result_json_schema() {
+        let result = DuplicationResult {
+            total_files: 3,
+            files_processed: 3,
+            bytes_processed: 300,
+            aborted: false,
+            duplicates_found: 1,
+            duplicates_moved: 1,
+            duplicates_trashed: 0,
+            duplicates_deleted: 0,
+            duplicates_hardlinked: 0,
+            new_files_registered: 2,
+            already_recorded: 0,
+            reference_files_indexed: 0,
+            filtered_by_size: 0,
+            reference_filtered_by_size: 0,
+            bytes_reclaimable: 100,
+            bytes_reclaimed: 100,
+            duplicate_groups: vec![DuplicateGroup {
+                hash: "abc".to_string(),
+                size: 100,
+                original: Some(PathBuf::from("a.mp4")),
+                duplicates: vec![PathBuf::from("b.mp4")],
+            }],
+            similar_videos: vec![SimilarVideo {
+                path: PathBuf::from("c.mkv"),
+                size: 80,
+                original: PathBuf::from("a.mp4"),
+                distance: 3,
+            }],
+            errors: 0,
+        };
+        assert_eq!(
+            serde_json::to_value(&result).unwrap(),
+            json!({
+                "total_files": 3,
+                "files_processed": 3,
+                "bytes_processed": 300,
+                "aborted": false,
+                "duplicates_found": 1,
+                "duplicates_moved": 1,
+                "duplicates_trashed": 0,
+                "duplicates_deleted": 0,
+                "duplicates_hardlinked": 0,
+                "new_files_registered": 2,
+                "already_recorded": 0,
+                "reference_files_indexed": 0,
+                "filtered_by_size": 0,
+                "reference_filtered_by_size": 0,
+                "bytes_reclaimable": 100,
+                "bytes_reclaimed": 100,
+                "duplicate_groups": [{
+                    "hash": "abc",
+                    "size": 100,
+                    "original": "a.mp4",
+                    "duplicates": ["b.mp4"]
+                }],
+                "similar_videos": [{
+                    "path": "c.mkv",
+                    "size": 80,
+                    "original": "a.mp4",
+                    "distance": 3
+                }],
+                "errors": 0
+            })
+        );
+    }
 }
@@ -0,0 +1,465 @@
+//! 資料夾模式去重
+//!
+//! 只比對掃描資料夾內的檔案，不讀取也不寫入 hash table。
+//! 先依大小分組，只有大小相同的檔案才計算 hash；每組相同內容保留一個檔案，
+//! 其餘依設定移動、刪除或以硬連結取代。
+
+use super::duplicate_report::{DuplicateGroup, DuplicateGroups};
+use super::duplication_detector::{
+    DuplicationResult, PARTIAL_HASH_BYTES, full_hash, full_hash_read_bytes,
+    move_to_duplication_folder, new_progress_bar, partial_hash_read_bytes, replace_with_hardlink,
+    run_with_threads,
+};
+use crate::config::{DeletionMode, DuplicateAction, KeepPolicy};
+use crate::tools::{
+    Disposal, ExcludeFilter, FileInfo, HashAborted, SizeFilter, calculate_partial_hash,
+    ensure_directory_exists, scan_all_files,
+};
+use anyhow::{Context, Result};
+use indicatif::ProgressBar;
+use log::{error, info};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+
+/// 資料夾模式去重器
+pub struct FolderDeduplicator {
+    duplication_directory: PathBuf,
+    action: DuplicateAction,
+    /// 只回報重複檔案，不處理檔案
+    dry_run: bool,
+    keep_policy: KeepPolicy,
+    /// 移動模式下重複檔案的去向
+    deletion_mode: DeletionMode,
+    /// 同時計算 hash 的執行緒數，0 代表不限制
+    hash_threads: usize,
+    /// 掃描時略過的檔案與資料夾
+    exclude_filter: ExcludeFilter,
+    /// 掃描時的檔案大小篩選
+    size_filter: Option<SizeFilter>,
+    shutdown_signal: Arc<AtomicBool>,
+}
+
+impl FolderDeduplicator {
+    pub fn new(
+        base_directory: &Path,
+        action: DuplicateAction,
+        dry_run: bool,
+        shutdown_signal: Arc<AtomicBool>,
+    ) -> Self {
+        Self {
+            duplication_directory: base_directory.join("duplication_file"),
+            action,
+            dry_run,
+            keep_policy: KeepPolicy::default(),
+            deletion_mode: DeletionMode::default(),
+            hash_threads: 0,
+            exclude_filter: ExcludeFilter::default(),
+            size_filter: None,
+            shutdown_signal,
+        }
+    }
+
+    /// 設定每組重複檔案保留哪一個
+    #[must_use]
+    pub const fn with_keep_policy(mut self, keep_policy: KeepPolicy) -> Self {
+        self.keep_policy = keep_policy;
+        self
+    }
+
+    /// 設定移動模式下重複檔案移到資料夾或送到資源回收筒
+    #[must_use]
+    pub const fn with_deletion_mode(mut self, deletion_mode: DeletionMode) -> Self {
+        self.deletion_mode = deletion_mode;
+        self
+    }
+
+    /// 限制同時計算 hash 的執行緒數（0 代表依 CPU 核心數）
+    #[must_use]
+    pub const fn with_hash_threads(mut self, hash_threads: usize) -> Self {
+        self.hash_threads = hash_threads;
+        self
+    }
+
+    /// 套用掃描排除規則
+    #[must_use]
+    pub fn with_exclude_filter(mut self, exclude_filter: ExcludeFilter) -> Self {
+        self.exclude_filter = exclude_filter;
+        self
+    }
+
+    /// 套用掃描檔案大小篩選
+    #[must_use]
+    pub fn with_size_filter(mut self, size_filter: SizeFilter) -> Self {
+        self.size_filter = Some(size_filter);
+        self
+    }
+
+    /// 掃描資料夾，找出資料夾內的重複檔案並處理
+    pub fn detect_and_handle_duplicates(&self, directory: &Path) -> Result<DuplicationResult> {
+        info!("開始掃描目錄（資料夾模式）: {}", directory.display());
+
+        // 先前移到 duplication_file 的檔案不再比對
+        let files: Vec<FileInfo> =
+            scan_all_files(directory, &self.exclude_filter, self.size_filter.as_ref())?
+                .into_iter()
+                .filter(|file| !file.path.starts_with(&self.duplication_directory))
+                .collect();
+        let total_files = files.len();
+
+        let stats = HashStats::default();
+        let groups = run_with_threads(self.hash_threads, || {
+            self.find_duplicate_groups(&files, &stats)
+        })?;
+        info!("找到 {total_files} 個檔案，{} 組重複", groups.len());
+
+        if !self.dry_run && self.action == DuplicateAction::Move && !groups.is_empty() {
+            ensure_directory_exists(&self.duplication_directory)?;
+        }
+
+        let mut result = DuplicationResult {
+            total_files,
+            files_processed: stats.processed.load(Ordering::SeqCst),
+            bytes_processed: stats.bytes_read.load(Ordering::SeqCst),
+            aborted: false,
+            duplicates_found: 0,
+            duplicates_moved: 0,
+            duplicates_trashed: 0,
+            duplicates_deleted: 0,
+            duplicates_hardlinked: 0,
+            new_files_registered: 0,
+            already_recorded: 0,
+            reference_files_indexed: 0,
+            filtered_by_size: self.size_filter.as_ref().map_or(0, SizeFilter::rejected),
+            reference_filtered_by_size: 0,
+            bytes_reclaimable: 0,
+            bytes_reclaimed: 0,
+            duplicate_groups: Vec::new(),
+            similar_videos: Vec::new(),
+            errors: stats.errors.load(Ordering::SeqCst),
+        };
+
+        let mut report = DuplicateGroups::default();
+        for (hash, mut members) in groups {
+            if self.shutdown_signal.load(Ordering::SeqCst) {
+                break;
+            }
+
+            let keep = members.remove(keep_index(self.keep_policy, &members));
+            for duplicate in members {
+                result.duplicates_found += 1;
+                match self.handle_duplicate(&duplicate.path, &keep.path) {
+                    Ok(trashed) => {
+                        if !self.dry_run {
+                            result.bytes_reclaimed += duplicate.size;
+                        }
+                        match (self.dry_run, self.action) {
+                            (true, _) => {}
+                            (false, DuplicateAction::Move) if trashed => {
+                                result.duplicates_trashed += 1;
+                            }
+                            (false, DuplicateAction::Move) => result.duplicates_moved += 1,
+                            (false, DuplicateAction::Delete) => result.duplicates_deleted += 1,
+                            (false, DuplicateAction::Hardlink) => {
+                                result.duplicates_hardlinked += 1;
+                            }
+                        }
+                        report.add(
+                            duplicate.size,
+                            hash.clone(),
+                            Some(keep.path.clone()),
+                            duplicate.path,
+                        );
+                    }
+                    Err(e) => {
+                        error!("處理檔案失敗 {}: {e:#}", duplicate.path.display());
+                        result.errors += 1;
+                    }
+                }
+            }
+        }
+
+        result.aborted = self.shutdown_signal.load(Ordering::SeqCst);
+        result.duplicate_groups = report.into_sorted();
+        result.bytes_reclaimable = result
+            .duplicate_groups
+            .iter()
+            .map(DuplicateGroup::wasted_bytes)
+            .sum();
+
+        info!(
+            "資料夾去重完成 - 總計: {}, 重複: {}, 錯誤: {}",
+            result.total_files, result.duplicates_found, result.errors
+        );
+        Ok(result)
+    }
+
+    /// 依大小、部分 hash、完整 hash 逐步分組，回傳兩個以上檔案內容相同的組別
+    ///
+    /// 檔案在確定沒有相同內容的檔案或算完最後一個 hash 時才計為處理完
+    fn find_duplicate_groups(
+        &self,
+        files: &[FileInfo],
+        stats: &HashStats,
+    ) -> Vec<(String, Vec<FileInfo>)> {
+        let mut by_size: HashMap<u64, Vec<&FileInfo>> = HashMap::new();
+        for file in files {
+            by_size.entry(file.size).or_default().push(file);
+        }
+        let candidates: Vec<&FileInfo> = by_size
+            .into_values()
+            .filter(|group| group.len() > 1)
+            .flatten()
+            .collect();
+        stats
+            .processed
+            .fetch_add(files.len() - candidates.len(), Ordering::SeqCst);
+
+        let progress_bar = new_progress_bar(candidates.len());
+        progress_bar.set_message("計算 hash");
+
+        // 部分 hash 相同的檔案才計算完整 hash（小檔案的部分 hash 即完整 hash）
+        let partials = self.hash_all(&candidates, stats, &progress_bar, |file| {
+            let partial = calculate_partial_hash(&file.path, PARTIAL_HASH_BYTES)?;
+            Ok((partial, partial_hash_read_bytes(file.size)))
+        });
+        let mut by_partial: HashMap<(u64, String), Vec<&FileInfo>> = HashMap::new();
+        for (file, partial) in partials {
+            by_partial
+                .entry((file.size, partial))
+                .or_default()
+                .push(file);
+        }
+
+        let mut duplicate_groups = Vec::new();
+        for ((size, partial), group) in by_partial {
+            if group.len() < 2 {
+                stats.processed.fetch_add(group.len(), Ordering::SeqCst);
+                continue;
+            }
+            progress_bar.inc_length(group.len() as u64);
+            let hashes = self.hash_all(&group, stats, &progress_bar, |file| {
+                let hash = full_hash(&file.path, size, &partial, &self.shutdown_signal)?;
+                Ok((hash, full_hash_read_bytes(size)))
+            });
+            stats.processed.fetch_add(hashes.len(), Ordering::SeqCst);
+            let mut by_hash: HashMap<String, Vec<FileInfo>> = HashMap::new();
+            for (file, hash) in hashes {
+                by_hash.entry(hash).or_default().push(file.clone());
+            }
+            duplicate_groups.extend(by_hash.into_iter().filter(|(_, group)| group.len() > 1));
+        }
+
+        progress_bar.finish_and_clear();
+        duplicate_groups
+    }
+
+    /// 平行計算 hash（`hash` 同時回傳讀取的位元組數），失敗的檔案記錄錯誤後略過
+    fn hash_all<'a>(
+        &self,
+        files: &[&'a FileInfo],
+        stats: &HashStats,
+        progress_bar: &ProgressBar,
+        hash: impl Fn(&FileInfo) -> Result<(String, u64)> + Sync,
+    ) -> Vec<(&'a FileInfo, String)> {
+        files
+            .par_iter()
+            .filter_map(|&file| {
+                if self.shutdown_signal.load(Ordering::SeqCst) {
+                    return None;
+                }
+                progress_bar.inc(1);
+                match hash(file) {
+                    Ok((value, bytes_read)) => {
+                        stats.bytes_read.fetch_add(bytes_read, Ordering::SeqCst);
+                        Some((file, value))
+                    }
+                    Err(e) if e.is::<HashAborted>() => None,
+                    Err(e) => {
+                        error!("計算 hash 失敗 {}: {e:#}", file.path.display());
+                        stats.errors.fetch_add(1, Ordering::SeqCst);
+                        stats.processed.fetch_add(1, Ordering::SeqCst);
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// 處理重複檔案，回傳是否已送到資源回收筒
+    fn handle_duplicate(&self, duplicate: &Path, keep: &Path) -> Result<bool> {
+        if self.dry_run {
+            info!(
+                "發現重複檔案: {}（保留: {}）",
+                duplicate.display(),
+                keep.display()
+            );
+            return Ok(false);
+        }
+
+        match self.action {
+            DuplicateAction::Move => {
+                let disposal = move_to_duplication_folder(
+                    duplicate,
+                    &self.duplication_directory,
+                    Some(keep),
+                    self.deletion_mode,
+                )?;
+                Ok(disposal == Disposal::Trashed)
+            }
+            DuplicateAction::Delete => {
+                fs::remove_file(duplicate)
+                    .with_context(|| format!("無法刪除重複檔案: {}", duplicate.display()))?;
+                info!(
+                    "刪除重複檔案: {}（保留: {}）",
+                    duplicate.display(),
+                    keep.display()
+                );
+                Ok(false)
+            }
+            DuplicateAction::Hardlink => {
+                replace_with_hardlink(keep, duplicate)?;
+                info!(
+                    "以硬連結取代重複檔案: {} -> {}",
+                    duplicate.display(),
+                    keep.display()
+                );
+                Ok(false)
+            }
+        }
+    }
+}
+
+/// 計算 hash 的統計
+#[derive(Default)]
+struct HashStats {
+    /// 處理完的檔案數
+    processed: AtomicUsize,
+    /// 實際讀取的位元組數
+    bytes_read: AtomicU64,
+    /// 計算 hash 失敗的檔案數
+    errors: AtomicUsize,
+}
+
+/// 依保留方式選出要保留的檔案；條件相同時保留路徑排序較前者，結果不受掃描順序影響
+fn keep_index(policy: KeepPolicy, members: &[FileInfo]) -> usize {
+    let indices = 0..members.len();
+    let by_path = |a: &usize, b: &usize| members[*a].path.cmp(&members[*b].path);
+    let chosen = match policy {
+        KeepPolicy::Oldest => indices.min_by(|a, b| {
+            members[*a]
+                .modified
+                .cmp(&members[*b].modified)
+                .then_with(|| by_path(a, b))
+        }),
+        KeepPolicy::Newest => indices.min_by(|a, b| {
+            members[*b]
+                .modified
+                .cmp(&members[*a].modified)
+                .then_with(|| by_path(a, b))
+        }),
+        KeepPolicy::ShortestPath => indices.min_by(|a, b| {
+            let len = |i: &usize| members[*i].path.as_os_str().len();
+            len(a).cmp(&len(b)).then_with(|| by_path(a, b))
+        }),
+    };
+    chosen.unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+    use tempfile::TempDir;
+
+    fn info(path: &str, modified_secs: u64) -> FileInfo {
+        FileInfo {
+            path: PathBuf::from(path),
+            size: 1,
+            modified: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(modified_secs)),
+        }
+    }
+
+    #[test]
+    fn test_keep_policy() {
+        let members = [
+            info("/data/b/long/name.mp4", 200),
+            info("/data/a.mp4", 300),
+            info("/data/copy/a.mp4", 100),
+        ];
+        assert_eq!(keep_index(KeepPolicy::Oldest, &members), 2);
+        assert_eq!(keep_index(KeepPolicy::Newest, &members), 1);
+        assert_eq!(keep_index(KeepPolicy::ShortestPath, &members), 1);
+
+        // 修改時間相同時保留路徑排序較前者
+        let tied = [info("/data/z.mp4", 100), info("/data/y.mp4", 100)];
+        assert_eq!(keep_index(KeepPolicy::Oldest, &tied), 1);
+        assert_eq!(keep_index(KeepPolicy::Newest, &tied), 1);
+    }
+
+    #[test]
+    fn test_folder_mode_ignores_hash_table() {
+        let dir = TempDir::new().unwrap();
+        let files = dir.path().join("files");
+        fs::create_dir_all(files.join("copy")).unwrap();
+        fs::write(files.join("a.bin"), b"same content").unwrap();
+        fs::write(files.join("copy/a.bin"), b"same content").unwrap();
+        fs::write(files.join("b.bin"), b"diff content").unwrap();
+
+        let run = || {
+            FolderDeduplicator::new(
+                dir.path(),
+                DuplicateAction::Move,
+                false,
+                Arc::new(AtomicBool::new(false)),
+            )
+            .with_keep_policy(KeepPolicy::ShortestPath)
+            .detect_and_handle_duplicates(&files)
+            .unwrap()
+        };
+
+        let result = run();
+        assert_eq!(result.total_files, 3);
+        assert_eq!(result.files_processed, 3);
+        assert_eq!(result.bytes_processed, 36);
+        assert_eq!(result.duplicates_moved, 1);
+        assert_eq!(result.bytes_reclaimed, 12);
+        assert_eq!(result.bytes_reclaimable, 12);
+        assert!(files.join("a.bin").exists());
+        assert!(!files.join("copy/a.bin").exists());
+        assert!(dir.path().join("duplication_file/a.bin").exists());
+        assert!(!dir.path().join("hash_table.json").exists());
+
+        // 再次掃描同一資料夾不會把剩下的檔案當成重複
+        let result = run();
+        assert_eq!(result.duplicates_found, 0);
+        assert!(files.join("a.bin").exists());
+        assert!(files.join("b.bin").exists());
+    }
+
+    #[test]
+    fn test_aborted_run_counts_processed_files() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.bin"), b"same content").unwrap();
+        fs::write(dir.path().join("b.bin"), b"same content").unwrap();
+        fs::write(dir.path().join("c.bin"), b"unique").unwrap();
+
+        let result = FolderDeduplicator::new(
+            dir.path(),
+            DuplicateAction::Move,
+            true,
+            Arc::new(AtomicBool::new(true)),
+        )
+        .detect_and_handle_duplicates(dir.path())
+        .unwrap();
+
+        // 只有大小不同、不需計算 hash 的檔案算處理完
+        assert!(result.aborted);
+        assert_eq!(result.total_files, 3);
+        assert_eq!(result.files_processed, 1);
+        assert_eq!(result.bytes_processed, 0);
+    }
+}
@@ -0,0 +1,291 @@
+//! 匯入其他工具的重複檔案掃描結果
+//!
+//! 支援 czkawka 的 JSON 匯出、rmlint 的 JSON 輸出與 `hash,size,path` CSV。
+//! 只有標示為 BLAKE3 的 hash 會直接使用，其他演算法只匯入大小與路徑，
+//! 之後掃描到相同大小的檔案時才計算 BLAKE3
+
+use super::hash_table::{HashTable, ImportOutcome};
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 匯入檔案的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Czkawka,
+    Rmlint,
+    Csv,
+}
+
+impl fmt::Display for ImportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Czkawka => write!(f, "czkawka JSON"),
+            Self::Rmlint => write!(f, "rmlint JSON"),
+            Self::Csv => write!(f, "CSV"),
+        }
+    }
+}
+
+/// 匯入的單一檔案
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedEntry {
+    pub path: PathBuf,
+    pub size: u64,
+    /// 整個檔案的 BLAKE3 hash（小寫十六進位），其他演算法為 `None`
+    pub blake3: Option<String>,
+}
+
+/// 匯入結果
+#[derive(Debug)]
+pub struct ImportResult {
+    pub format: ImportFormat,
+    /// 新增的紀錄數
+    pub imported: usize,
+    /// 已有紀錄（相同路徑或相同 hash）的項目數
+    pub merged: usize,
+    /// 缺少路徑、大小或格式錯誤而略過的項目數
+    pub skipped: usize,
+    /// 使用匯入 hash 的項目數（其餘只匯入大小與路徑）
+    pub with_hash: usize,
+}
+
+/// 讀取匯入檔案，回傳格式、可匯入的項目與略過的項目數
+pub fn parse_import_file(path: &Path) -> Result<(ImportFormat, Vec<ImportedEntry>, usize)> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("無法讀取匯入檔案: {}", path.display()))?;
+
+    let trimmed = content.trim_start();
+    let (format, parsed) = if trimmed.starts_with('[') {
+        (ImportFormat::Rmlint, parse_rmlint(trimmed))
+    } else if trimmed.starts_with('{') {
+        (ImportFormat::Czkawka, parse_czkawka(trimmed))
+    } else {
+        (ImportFormat::Csv, Ok(parse_csv(&content)))
+    };
+    let (entries, skipped) =
+        parsed.with_context(|| format!("無法解析 {format} 檔案: {}", path.display()))?;
+    Ok((format, entries, skipped))
+}
+
+/// 將匯入檔案的項目加入 hash table
+pub fn import_into(table: &mut HashTable, path: &Path) -> Result<ImportResult> {
+    let (format, entries, skipped) = parse_import_file(path)?;
+    let mut result = ImportResult {
+        format,
+        imported: 0,
+        merged: 0,
+        skipped,
+        with_hash: 0,
+    };
+
+    for entry in entries {
+        if entry.blake3.is_some() {
+            result.with_hash += 1;
+        }
+        match table.import_file(entry.size, entry.blake3.as_deref(), &entry.path) {
+            ImportOutcome::Imported => result.imported += 1,
+            ImportOutcome::Merged => result.merged += 1,
+        }
+    }
+    Ok(result)
+}
+
+/// rmlint：第一個物件為標頭（含 `checksum_type`），之後為各檔案，最後為統計
+fn parse_rmlint(content: &str) -> Result<(Vec<ImportedEntry>, usize)> {
+    let items: Vec<Value> = serde_json::from_str(content)?;
+    let Some(header) = items
+        .first()
+        .filter(|item| item.get("description").is_some())
+    else {
+        bail!("找不到 rmlint 標頭");
+    };
+    let is_blake3 = header
+        .get("checksum_type")
+        .and_then(Value::as_str)
+        .is_some_and(is_blake3_name);
+
+    let mut entries = Vec::new();
+    let mut skipped = 0;
+    for item in &items[1..] {
+        let Some(kind) = item.get("type").and_then(Value::as_str) else {
+            continue; // 統計資訊
+        };
+        if !matches!(kind, "duplicate_file" | "unique_file") {
+            skipped += 1;
+            continue;
+        }
+        match entry_from_object(item, is_blake3.then_some("checksum")) {
+            Some(entry) => entries.push(entry),
+            None => skipped += 1,
+        }
+    }
+    Ok((entries, skipped))
+}
+
+/// czkawka：依大小（或名稱）分組的巢狀結構，收集所有含 `path` 的物件
+///
+/// czkawka 的 hash 演算法可設定且不會寫入匯出檔，因此只匯入大小與路徑
+fn parse_czkawka(content: &str) -> Result<(Vec<ImportedEntry>, usize)> {
+    fn collect(value: &Value, entries: &mut Vec<ImportedEntry>, skipped: &mut usize) {
+        match value {
+            Value::Object(map) if map.contains_key("path") => {
+                match entry_from_object(value, None) {
+                    Some(entry) => entries.push(entry),
+                    None => *skipped += 1,
+                }
+            }
+            Value::Object(map) => map.values().for_each(|v| collect(v, entries, skipped)),
+            Value::Array(items) => items.iter().for_each(|v| collect(v, entries, skipped)),
+            _ => {}
+        }
+    }
+
+    let value: Value = serde_json::from_str(content)?;
+    let mut entries = Vec::new();
+    let mut skipped = 0;
+    collect(&value, &mut entries, &mut skipped);
+    Ok((entries, skipped))
+}
+
+/// `hash,size,path` CSV，可有標頭列（第一欄名稱為 hash 演算法，例如 `blake3`、`sha256`）
+///
+/// 路徑可含逗號，也可以用引號包住
+fn parse_csv(content: &str) -> (Vec<ImportedEntry>, usize) {
+    let mut entries = Vec::new();
+    let mut skipped = 0;
+    let mut is_blake3 = false;
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut fields = line.splitn(3, ',');
+        let (Some(hash), Some(size), Some(path)) = (fields.next(), fields.next(), fields.next())
+        else {
+            skipped += 1;
+            continue;
+        };
+        let Ok(size) = size.trim().parse::<u64>() else {
+            if index == 0 {
+                is_blake3 = is_blake3_name(hash.trim());
+            } else {
+                skipped += 1;
+            }
+            continue;
+        };
+
+        let path = unquote(path.trim());
+        if path.is_empty() {
+            skipped += 1;
+            continue;
+        }
+        entries.push(ImportedEntry {
+            path: PathBuf::from(path),
+            size,
+            blake3: is_blake3.then(|| normalize_blake3(hash.trim())).flatten(),
+        });
+    }
+    (entries, skipped)
+}
+
+/// 由含 `path` 與 `size` 的物件建立匯入項目，`hash_key` 為 BLAKE3 hash 欄位
+fn entry_from_object(value: &Value, hash_key: Option<&str>) -> Option<ImportedEntry> {
+    let path = value.get("path")?.as_str().filter(|p| !p.is_empty())?;
+    let size = value.get("size")?.as_u64()?;
+    let blake3 = hash_key
+        .and_then(|key| value.get(key))
+        .and_then(Value::as_str)
+        .and_then(normalize_blake3);
+    Some(ImportedEntry {
+        path: PathBuf::from(path),
+        size,
+        blake3,
+    })
+}
+
+fn is_blake3_name(name: &str) -> bool {
+    matches!(name.to_ascii_lowercase().as_str(), "blake3" | "b3")
+}
+
+/// BLAKE3 hash 須為 64 個十六進位字元，統一為小寫
+fn normalize_blake3(hash: &str) -> Option<String> {
+    (hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| hash.to_ascii_lowercase())
+}
+
+fn unquote(value: &str) -> String {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .map_or_else(|| value.to_string(), |v| v.replace("\"\"", "\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/hash_import")
+            .join(name)
+    }
+
+    #[test]
+    fn test_parse_czkawka() {
+        let (format, entries, skipped) = parse_import_file(&fixture("czkawka.json")).unwrap();
+        assert_eq!(format, ImportFormat::Czkawka);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(skipped, 1); // 缺少大小
+        assert!(entries.iter().all(|e| e.blake3.is_none()));
+        assert!(
+            entries
+                .iter()
+                .any(|e| e.path == Path::new("/media/movies/a (copy).mkv") && e.size == 1_048_576)
+        );
+    }
+
+    #[test]
+    fn test_parse_rmlint() {
+        let (format, entries, skipped) = parse_import_file(&fixture("rmlint.json")).unwrap();
+        assert_eq!(format, ImportFormat::Rmlint);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(skipped, 1); // emptyfile
+        assert_eq!(entries[0].path, Path::new("/media/videos/c.mp4"));
+        assert_eq!(entries[0].size, 4096);
+        assert_eq!(
+            entries[0].blake3.as_deref(),
+            Some("af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262")
+        );
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let (format, entries, skipped) = parse_import_file(&fixture("hashes.csv")).unwrap();
+        assert_eq!(format, ImportFormat::Csv);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(skipped, 2);
+        assert_eq!(entries[1].path, Path::new("/media/docs/d, copy.pdf"));
+        // sha256 不是 BLAKE3，只匯入大小與路徑
+        assert!(entries.iter().all(|e| e.blake3.is_none()));
+    }
+
+    #[test]
+    fn test_import_into_reports_merged() {
+        let mut table = HashTable::new();
+        let result = import_into(&mut table, &fixture("rmlint.json")).unwrap();
+        assert_eq!(result.format, ImportFormat::Rmlint);
+        assert_eq!(result.imported, 1);
+        assert_eq!(result.merged, 1); // 第二個檔案的 hash 相同
+        assert_eq!(result.with_hash, 2);
+
+        let result = import_into(&mut table, &fixture("czkawka.json")).unwrap();
+        assert_eq!((result.imported, result.merged, result.skipped), (3, 0, 1));
+
+        let result = import_into(&mut table, &fixture("czkawka.json")).unwrap();
+        assert_eq!((result.imported, result.merged), (0, 3));
+    }
+}
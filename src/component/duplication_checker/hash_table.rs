@@ -1,45 +1,325 @@
-use anyhow::{Context, Result};
+use crate::config::KeepPolicy;
+use crate::config::load::config_directory;
+use crate::tools::expand_home;
+use anyhow::{Context, Result, bail};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
-use std::fs;
-use std::path::Path;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-/// `HashTable` 資料結構：Key 是檔案大小，Value 是該大小下所有已知檔案的 hash 集合
+/// 部分 hash 對應尚未計算完整 hash 的檔案路徑
+type PathMap = HashMap<String, Option<PathBuf>>;
+
+/// 完整 hash 對應第一次出現該 hash 的檔案紀錄
+type RecordMap = HashMap<String, HashRecord>;
+
+/// 第一次出現某個 hash 的檔案
+///
+/// 時間皆為 Unix 秒數；舊版紀錄沒有這些資訊，讀取後為 `None`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct HashRecord {
+    first_seen_path: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mtime: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    registered_at: Option<u64>,
+}
+
+impl HashRecord {
+    /// 記錄檔案路徑、修改時間與登記時間
+    fn new(path: &Path) -> Self {
+        Self {
+            first_seen_path: Some(path.to_path_buf()),
+            mtime: file_mtime(path),
+            registered_at: unix_seconds(SystemTime::now()),
+        }
+    }
+
+    /// 舊版紀錄只有路徑（或什麼都沒有）
+    const fn legacy(path: Option<PathBuf>) -> Self {
+        Self {
+            first_seen_path: path,
+            mtime: None,
+            registered_at: None,
+        }
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> Option<u64> {
+    time.duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs())
+}
+
+pub(super) fn file_mtime(path: &Path) -> Option<u64> {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(unix_seconds)
+}
+
+/// 依保留方式，`candidate` 是否比 `current` 更應保留
+///
+/// 參數為修改時間（Unix 秒數）與路徑；條件相同時保留路徑排序較前者，結果不受處理順序影響
+fn prefers(
+    policy: KeepPolicy,
+    (candidate_mtime, candidate): (Option<u64>, &Path),
+    (current_mtime, current): (Option<u64>, &Path),
+) -> bool {
+    let ordering = match policy {
+        KeepPolicy::Oldest => candidate_mtime.cmp(&current_mtime),
+        KeepPolicy::Newest => current_mtime.cmp(&candidate_mtime),
+        KeepPolicy::ShortestPath => candidate.as_os_str().len().cmp(&current.as_os_str().len()),
+    };
+    ordering.then_with(|| candidate.cmp(current)) == Ordering::Less
+}
+
+/// 完整 hash 紀錄（檔案格式），舊版的值只有路徑字串或 `null`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RecordEntry {
+    Record(HashRecord),
+    Path(Option<PathBuf>),
+}
+
+impl From<RecordEntry> for HashRecord {
+    fn from(entry: RecordEntry) -> Self {
+        match entry {
+            RecordEntry::Record(record) => record,
+            RecordEntry::Path(path) => Self::legacy(path),
+        }
+    }
+}
+
+fn into_records(entries: HashMap<String, RecordEntry>) -> RecordMap {
+    entries.into_iter().map(|(h, e)| (h, e.into())).collect()
+}
+
+/// `HashTable` 資料結構：Key 是檔案大小，Value 是該大小下所有已知檔案的 hash，
+/// 以及第一次出現該 hash 的檔案路徑與時間（硬連結與刪除時用來確認原始檔仍存在）
+///
+/// 每個大小另有部分 hash（開頭與結尾）索引，部分 hash 沒有重複時不必計算完整 hash
 #[derive(Debug, Clone, Default)]
 pub struct HashTable {
-    entries: HashMap<u64, HashSet<String>>,
+    entries: HashMap<u64, SizeRecord>,
+}
+
+/// 單一檔案大小下的紀錄
+#[derive(Debug, Clone, Default)]
+struct SizeRecord {
+    /// 完整 hash
+    full: RecordMap,
+    /// 部分 hash 索引，值為 `Some(路徑)` 代表該檔案尚未計算完整 hash
+    ///
+    /// 沒有索引（`None`）時，該大小的檔案一律比對完整 hash（舊版紀錄）
+    partial: Option<PathMap>,
+    /// 從其他工具匯入、只知道大小的檔案，第一次遇到相同大小的檔案時才計算 hash
+    unhashed: Vec<PathBuf>,
+}
+
+/// 依保留方式比對重複檔案與紀錄中原始檔案的結果
+#[derive(Debug, PartialEq, Eq)]
+pub enum KeepDecision {
+    /// 保留紀錄中的原始檔案（舊版紀錄為 `None`）
+    Original(Option<PathBuf>),
+    /// 改以重複檔案為原始檔案，值為被取代的原始檔案
+    Replaced(PathBuf),
+}
+
+/// 以部分 hash 登記檔案的結果
+#[derive(Debug, PartialEq, Eq)]
+pub enum PartialMatch {
+    /// 沒有相同大小與部分 hash 的紀錄，已登記為新檔案
+    Registered,
+    /// 需要比對完整 hash；附帶尚未計算完整 hash 的先前檔案
+    Collision(Option<PathBuf>),
+}
+
+/// 匯入單一檔案的結果
+#[derive(Debug, PartialEq, Eq)]
+pub enum ImportOutcome {
+    /// 新增的紀錄
+    Imported,
+    /// 已有相同路徑或相同 hash 的紀錄
+    Merged,
+}
+
+/// 單一檔案大小下的紀錄（檔案格式）
+///
+/// 舊版只記錄 hash 集合或 hash 與路徑，讀取時仍接受該格式（沒有部分 hash 索引）
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SizeEntries {
+    Indexed {
+        full: HashMap<String, RecordEntry>,
+        #[serde(default)]
+        partial: Option<PathMap>,
+        #[serde(default)]
+        unhashed: Vec<PathBuf>,
+    },
+    WithPaths(HashMap<String, RecordEntry>),
+    Legacy(HashSet<String>),
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum SizeEntriesRef<'a> {
+    Indexed {
+        full: &'a RecordMap,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        partial: Option<&'a PathMap>,
+        #[serde(skip_serializing_if = "<[PathBuf]>::is_empty")]
+        unhashed: &'a [PathBuf],
+    },
+    WithPaths(&'a RecordMap),
+}
+
+impl From<SizeEntries> for SizeRecord {
+    fn from(entries: SizeEntries) -> Self {
+        match entries {
+            SizeEntries::Indexed {
+                full,
+                partial,
+                unhashed,
+            } => Self {
+                full: into_records(full),
+                partial,
+                unhashed,
+            },
+            SizeEntries::WithPaths(full) => Self {
+                full: into_records(full),
+                partial: None,
+                unhashed: Vec::new(),
+            },
+            SizeEntries::Legacy(hashes) => Self {
+                full: hashes
+                    .into_iter()
+                    .map(|h| (h, HashRecord::legacy(None)))
+                    .collect(),
+                partial: None,
+                unhashed: Vec::new(),
+            },
+        }
+    }
+}
+
+impl<'a> From<&'a SizeRecord> for SizeEntriesRef<'a> {
+    fn from(record: &'a SizeRecord) -> Self {
+        if record.partial.is_none() && record.unhashed.is_empty() {
+            return Self::WithPaths(&record.full);
+        }
+        Self::Indexed {
+            full: &record.full,
+            partial: record.partial.as_ref(),
+            unhashed: &record.unhashed,
+        }
+    }
+}
+
+/// 未設定位置時的 hash table 檔案名稱（與設定檔同一個資料夾）
+pub const DEFAULT_HASH_TABLE_FILE: &str = "hash_table.json";
+
+/// 取得 hash table 檔案路徑
+///
+/// `configured` 為設定的位置（支援 `~`），具名紀錄存放在同一資料夾，
+/// 例如 `hash_table.json` 的 `movies` 紀錄為 `hash_table.movies.json`
+#[must_use]
+pub fn resolve_hash_table_path(configured: Option<&str>, name: Option<&str>) -> PathBuf {
+    let base = configured
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+        .map_or_else(
+            || config_directory().join(DEFAULT_HASH_TABLE_FILE),
+            expand_home,
+        );
+    let Some(name) = name else {
+        return base;
+    };
+
+    let stem = base
+        .file_stem()
+        .map_or_else(|| "hash_table".into(), |s| s.to_string_lossy());
+    let file_name = match base.extension() {
+        Some(ext) => format!("{stem}.{name}.{}", ext.to_string_lossy()),
+        None => format!("{stem}.{name}"),
+    };
+    base.with_file_name(file_name)
 }
 
-// 自訂序列化：將 u64 key 轉換成 string key
+/// 具名紀錄的名稱只能包含英數字、`-` 與 `_`
+#[must_use]
+pub fn is_valid_table_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// 目前的 hash table 檔案格式版本
+///
+/// 版本 2 起以 `{"version": 2, "sizes": {...}}` 包住各大小的紀錄；
+/// 沒有版本欄位的舊檔案直接是大小對應紀錄的物件
+pub const HASH_TABLE_VERSION: u32 = 2;
+
+#[derive(Serialize)]
+struct VersionedRef<'a> {
+    version: u32,
+    sizes: HashMap<String, SizeEntriesRef<'a>>,
+}
+
+/// hash table 檔案格式
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HashTableFile {
+    Versioned {
+        version: u32,
+        sizes: HashMap<String, SizeEntries>,
+    },
+    Unversioned(HashMap<String, SizeEntries>),
+}
+
+// 自訂序列化：將 u64 key 轉換成 string key，並加上格式版本
 impl Serialize for HashTable {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let string_map: HashMap<String, &HashSet<String>> = self
-            .entries
-            .iter()
-            .map(|(k, v)| (k.to_string(), v))
-            .collect();
-        string_map.serialize(serializer)
+        VersionedRef {
+            version: HASH_TABLE_VERSION,
+            sizes: self
+                .entries
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.into()))
+                .collect(),
+        }
+        .serialize(serializer)
     }
 }
 
-// 自訂反序列化：將 string key 解析回 u64
+// 自訂反序列化：將 string key 解析回 u64，拒絕較新版本的格式
 impl<'de> Deserialize<'de> for HashTable {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let string_map: HashMap<String, HashSet<String>> = HashMap::deserialize(deserializer)?;
+        let string_map = match HashTableFile::deserialize(deserializer)? {
+            HashTableFile::Versioned { version, .. } if version > HASH_TABLE_VERSION => {
+                return Err(serde::de::Error::custom(format!(
+                    "hash table 格式版本 {version} 比程式支援的版本 {HASH_TABLE_VERSION} 新"
+                )));
+            }
+            HashTableFile::Versioned { sizes, .. } | HashTableFile::Unversioned(sizes) => sizes,
+        };
         let entries = string_map
             .into_iter()
             .map(|(k, v)| {
                 k.parse::<u64>()
-                    .map(|size| (size, v))
+                    .map(|size| (size, v.into()))
                     .map_err(serde::de::Error::custom)
             })
-            .collect::<Result<HashMap<u64, HashSet<String>>, _>>()?;
+            .collect::<Result<HashMap<u64, SizeRecord>, _>>()?;
         Ok(Self { entries })
     }
 }
@@ -68,19 +348,11 @@ impl HashTable {
             .with_context(|| format!("無法解析 hash table 檔案: {}", path.display()))
     }
 
+    /// 先寫入暫存檔再改名取代，儲存中斷時原本的檔案保持完整
     pub fn save_to_file(&self, path: &Path) -> Result<()> {
         let content =
             serde_json::to_string_pretty(&self).with_context(|| "無法序列化 hash table")?;
-
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)
-                .with_context(|| format!("無法建立目錄: {}", parent.display()))?;
-        }
-
-        fs::write(path, content)
-            .with_context(|| format!("無法寫入 hash table 檔案: {}", path.display()))?;
-
-        Ok(())
+        replace_file(path, &content).with_context(|| "無法儲存 hash table")
     }
 
     /// 檢查是否存在相同大小的檔案
@@ -94,43 +366,309 @@ impl HashTable {
     pub fn contains_hash(&self, size: u64, hash: &str) -> bool {
         self.entries
             .get(&size)
-            .is_some_and(|hashes| hashes.contains(hash))
+            .is_some_and(|record| record.full.contains_key(hash))
     }
 
-    /// 新增一個 hash 到指定大小的列表中
-    pub fn insert(&mut self, size: u64, hash: String) {
-        self.entries.entry(size).or_default().insert(hash);
+    /// 第一次出現該 hash 的檔案路徑（舊版紀錄為 `None`）
+    #[must_use]
+    pub fn original_path(&self, size: u64, hash: &str) -> Option<&Path> {
+        self.entries
+            .get(&size)?
+            .full
+            .get(hash)?
+            .first_seen_path
+            .as_deref()
+    }
+
+    /// 登記原始檔案時的修改時間（Unix 秒數，舊版紀錄為 `None`）
+    #[must_use]
+    pub fn original_mtime(&self, size: u64, hash: &str) -> Option<u64> {
+        self.entries.get(&size)?.full.get(hash)?.mtime
+    }
+
+    /// 依保留方式決定保留紀錄中的原始檔案或 `path`，`path` 較應保留時改以它為原始檔案
+    ///
+    /// 只有位於 `replaceable_under` 中且仍存在的原始檔案會被取代。比對與更新在同一次呼叫內完成，
+    /// 平行處理時呼叫端持有鎖即可確保每個檔案最多被取代一次，且回傳的原始檔案為目前的紀錄
+    pub fn choose_original(
+        &mut self,
+        size: u64,
+        hash: &str,
+        path: &Path,
+        policy: KeepPolicy,
+        replaceable_under: &Path,
+    ) -> KeepDecision {
+        let Some(record) = self
+            .entries
+            .get_mut(&size)
+            .and_then(|record| record.full.get_mut(hash))
+        else {
+            return KeepDecision::Original(None);
+        };
+        let Some(original) = record.first_seen_path.clone() else {
+            return KeepDecision::Original(None);
+        };
+        if original == path || !original.starts_with(replaceable_under) || !original.is_file() {
+            return KeepDecision::Original(Some(original));
+        }
+
+        let candidate = HashRecord::new(path);
+        let original_mtime = record.mtime.or_else(|| file_mtime(&original));
+        if prefers(policy, (candidate.mtime, path), (original_mtime, &original)) {
+            *record = candidate;
+            KeepDecision::Replaced(original)
+        } else {
+            KeepDecision::Original(Some(original))
+        }
+    }
+
+    /// 新增一個 hash 到指定大小的列表中，並記錄其原始檔案路徑與修改時間
+    ///
+    /// hash 已存在時更新原始檔案紀錄。新的大小不會建立部分 hash 索引；
+    /// 已有索引的大小須先以 [`Self::resolve_partial`] 標記該檔案的部分 hash
+    pub fn insert(&mut self, size: u64, hash: String, path: &Path) {
+        self.entries
+            .entry(size)
+            .or_default()
+            .full
+            .insert(hash, HashRecord::new(path));
+    }
+
+    /// 以部分 hash 登記檔案，查詢與登記在同一次呼叫內完成
+    pub fn register_partial(&mut self, size: u64, partial: &str, path: &Path) -> PartialMatch {
+        let record = self.entries.entry(size).or_insert_with(SizeRecord::indexed);
+        let Some(partials) = &mut record.partial else {
+            return PartialMatch::Collision(None);
+        };
+        match partials.get(partial) {
+            Some(pending) => PartialMatch::Collision(pending.clone()),
+            None => {
+                partials.insert(partial.to_string(), Some(path.to_path_buf()));
+                PartialMatch::Registered
+            }
+        }
+    }
+
+    /// 標記部分 hash 已改以完整 hash 比對，並補上先前檔案的完整 hash
+    pub fn resolve_partial(
+        &mut self,
+        size: u64,
+        partial: &str,
+        pending: Option<(String, PathBuf)>,
+    ) {
+        let Some(record) = self.entries.get_mut(&size) else {
+            return;
+        };
+        let Some(partials) = &mut record.partial else {
+            return;
+        };
+        partials.insert(partial.to_string(), None);
+        if let Some((hash, path)) = pending {
+            record
+                .full
+                .entry(hash)
+                .or_insert_with(|| HashRecord::new(&path));
+        }
+    }
+
+    /// 匯入其他工具的掃描結果
+    ///
+    /// `blake3` 為整個檔案的 BLAKE3 hash（與本工具的完整 hash 相同），
+    /// 其他演算法的結果只匯入大小與路徑，之後遇到相同大小的檔案時才計算 hash
+    pub fn import_file(&mut self, size: u64, blake3: Option<&str>, path: &Path) -> ImportOutcome {
+        // 新的大小有完整 hash 時不建立部分 hash 索引，該大小的檔案一律比對完整 hash
+        let record = self.entries.entry(size).or_insert_with(|| {
+            if blake3.is_some() {
+                SizeRecord::default()
+            } else {
+                SizeRecord::indexed()
+            }
+        });
+        let known_path = record
+            .full
+            .values()
+            .any(|entry| entry.first_seen_path.as_deref() == Some(path))
+            || record.pending().any(|pending| pending == path)
+            || record.unhashed.iter().any(|unhashed| unhashed == path);
+        if known_path || blake3.is_some_and(|hash| record.full.contains_key(hash)) {
+            return ImportOutcome::Merged;
+        }
+
+        // 已有部分 hash 索引的大小不能直接加入完整 hash，否則部分 hash 比對會漏掉此檔案
+        match blake3 {
+            Some(hash) if record.partial.is_none() => {
+                record.full.insert(hash.to_string(), HashRecord::new(path));
+            }
+            _ => record.unhashed.push(path.to_path_buf()),
+        }
+        ImportOutcome::Imported
+    }
+
+    /// 取出指定大小下只知道大小的匯入檔案，由呼叫端計算 hash 後重新登記
+    pub fn take_unhashed(&mut self, size: u64) -> Vec<PathBuf> {
+        self.entries
+            .get_mut(&size)
+            .map(|record| std::mem::take(&mut record.unhashed))
+            .unwrap_or_default()
+    }
+
+    /// 紀錄的檔案總數（包含尚未計算完整 hash 的檔案）
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries
+            .values()
+            .map(|record| record.full.len() + record.pending().count() + record.unhashed.len())
+            .sum()
     }
 
-    #[cfg(test)]
     #[must_use]
     pub fn is_empty(&self) -> bool {
         self.entries.is_empty()
     }
+
+    /// 清空所有紀錄
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// 移除原始檔案已不存在的紀錄，回傳移除的數量
+    ///
+    /// 舊版紀錄沒有路徑，無法確認是否存在，一律保留
+    pub fn prune_missing(&mut self) -> usize {
+        let before = self.len();
+        for record in self.entries.values_mut() {
+            record
+                .full
+                .retain(|_, entry| entry.first_seen_path.as_deref().is_none_or(Path::exists));
+            if let Some(partials) = &mut record.partial {
+                partials.retain(|_, path| path.as_deref().is_none_or(Path::exists));
+            }
+            record.unhashed.retain(|path| path.exists());
+        }
+        self.entries.retain(|_, record| {
+            !record.full.is_empty()
+                || record.pending().next().is_some()
+                || !record.unhashed.is_empty()
+        });
+        before - self.len()
+    }
+}
+
+/// 先寫入同目錄的暫存檔再改名取代，寫入中斷時原本的檔案保持完整
+pub(super) fn replace_file(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("無法建立目錄: {}", parent.display()))?;
+    }
+
+    let temp_path = sibling_path(path, ".tmp");
+    let write_temp = || -> std::io::Result<()> {
+        let mut file = File::create(&temp_path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()
+    };
+    write_temp().with_context(|| format!("無法寫入暫存檔: {}", temp_path.display()))?;
+
+    fs::rename(&temp_path, path).with_context(|| format!("無法寫入檔案: {}", path.display()))
+}
+
+/// 在檔案名稱後加上後綴的同目錄路徑
+pub(super) fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().map_or_else(OsString::new, OsString::from);
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// hash table 的程序間鎖定（`<hash table 檔名>.lock`），釋放時自動解鎖
+///
+/// 避免兩個程序同時使用同一個 hash table，後儲存的一方覆蓋另一方新增的紀錄
+#[derive(Debug)]
+pub struct HashTableLock {
+    path: PathBuf,
+    _file: File,
+}
+
+impl HashTableLock {
+    /// 鎖定 hash table，已被其他程序鎖定時立即回傳錯誤
+    pub fn acquire(path: &Path) -> Result<Self> {
+        let lock_path = sibling_path(path, ".lock");
+        if let Some(parent) = lock_path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("無法建立目錄: {}", parent.display()))?;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(&lock_path)
+            .with_context(|| format!("無法開啟 hash table 鎖定檔: {}", lock_path.display()))?;
+        match file.try_lock() {
+            Ok(()) => Ok(Self {
+                path: lock_path,
+                _file: file,
+            }),
+            Err(TryLockError::WouldBlock) => {
+                bail!("hash table 正被其他程序使用: {}", path.display())
+            }
+            Err(TryLockError::Error(e)) => {
+                Err(e).with_context(|| format!("無法鎖定 hash table: {}", lock_path.display()))
+            }
+        }
+    }
+
+    /// 鎖定檔路徑（掃描時須略過）
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl SizeRecord {
+    /// 有部分 hash 索引的新紀錄
+    fn indexed() -> Self {
+        Self {
+            partial: Some(HashMap::new()),
+            ..Self::default()
+        }
+    }
+
+    /// 尚未計算完整 hash 的檔案
+    fn pending(&self) -> impl Iterator<Item = &PathBuf> {
+        self.partial
+            .iter()
+            .flat_map(HashMap::values)
+            .filter_map(Option::as_ref)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tempfile::NamedTempFile;
+    use tempfile::{NamedTempFile, TempDir};
 
     #[test]
     fn test_hash_table_insert_and_contains() {
         let mut table = HashTable::new();
-        table.insert(1000, "abc123".to_string());
+        table.insert(1000, "abc123".to_string(), Path::new("/videos/a.mp4"));
 
         assert!(table.has_size(1000));
         assert!(table.contains_hash(1000, "abc123"));
         assert!(!table.contains_hash(1000, "def456"));
         assert!(!table.has_size(2000));
+        assert_eq!(
+            table.original_path(1000, "abc123"),
+            Some(Path::new("/videos/a.mp4"))
+        );
+        assert_eq!(table.original_path(1000, "def456"), None);
     }
 
     #[test]
     fn test_hash_table_save_and_load() {
         let mut table = HashTable::new();
-        table.insert(1000, "hash1".to_string());
-        table.insert(1000, "hash2".to_string());
-        table.insert(2000, "hash3".to_string());
+        table.insert(1000, "hash1".to_string(), Path::new("/a"));
+        table.insert(1000, "hash2".to_string(), Path::new("/b"));
+        table.insert(2000, "hash3".to_string(), Path::new("/c"));
 
         let temp_file = NamedTempFile::new().unwrap();
         table.save_to_file(temp_file.path()).unwrap();
@@ -139,6 +677,291 @@ mod tests {
         assert!(loaded.contains_hash(1000, "hash1"));
         assert!(loaded.contains_hash(1000, "hash2"));
         assert!(loaded.contains_hash(2000, "hash3"));
+        assert_eq!(loaded.original_path(1000, "hash2"), Some(Path::new("/b")));
+    }
+
+    #[test]
+    fn test_load_legacy_format() {
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), r#"{"1000": ["hash1", "hash2"]}"#).unwrap();
+
+        let loaded = HashTable::load_from_file(temp_file.path()).unwrap();
+        assert!(loaded.contains_hash(1000, "hash1"));
+        assert!(loaded.contains_hash(1000, "hash2"));
+        assert_eq!(loaded.original_path(1000, "hash1"), None);
+    }
+
+    #[test]
+    fn test_legacy_format_migrates_on_save() {
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), r#"{"1000": ["hash1"]}"#).unwrap();
+
+        let mut table = HashTable::load_from_file(temp_file.path()).unwrap();
+        table.insert(2000, "hash2".to_string(), Path::new("/videos/b.mp4"));
+        table.save_to_file(temp_file.path()).unwrap();
+
+        let content: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(temp_file.path()).unwrap()).unwrap();
+        assert_eq!(content["version"], HASH_TABLE_VERSION);
+        let sizes = &content["sizes"];
+        assert!(sizes["1000"]["hash1"]["first_seen_path"].is_null());
+        assert_eq!(sizes["2000"]["hash2"]["first_seen_path"], "/videos/b.mp4");
+        assert!(sizes["2000"]["hash2"]["registered_at"].is_u64());
+
+        let reloaded = HashTable::load_from_file(temp_file.path()).unwrap();
+        assert!(reloaded.contains_hash(1000, "hash1"));
+        assert_eq!(
+            reloaded.original_path(2000, "hash2"),
+            Some(Path::new("/videos/b.mp4"))
+        );
+    }
+
+    #[test]
+    fn test_newer_version_is_rejected() {
+        let json = r#"{"version": 99, "sizes": {}}"#;
+        assert!(serde_json::from_str::<HashTable>(json).is_err());
+
+        let json = r#"{"version": 2, "sizes": {"1000": {"full": {}, "partial": {"p1": "/a"}}}}"#;
+        let table: HashTable = serde_json::from_str(json).unwrap();
+        assert!(table.has_size(1000));
+    }
+
+    #[test]
+    fn test_import_file() {
+        let mut table = HashTable::new();
+        let a = Path::new("/videos/a.mp4");
+        let b = Path::new("/videos/b.mp4");
+
+        assert_eq!(table.import_file(1000, None, a), ImportOutcome::Imported);
+        assert_eq!(table.import_file(1000, None, a), ImportOutcome::Merged);
+        assert_eq!(
+            table.import_file(2000, Some("hash_b"), b),
+            ImportOutcome::Imported
+        );
+        assert_eq!(
+            table.import_file(2000, Some("hash_b"), Path::new("/copy/b.mp4")),
+            ImportOutcome::Merged
+        );
+        assert!(table.contains_hash(2000, "hash_b"));
+
+        // 已有部分 hash 索引的大小只匯入大小與路徑
+        table.register_partial(3000, "p1", Path::new("/videos/c.mp4"));
+        assert_eq!(
+            table.import_file(3000, Some("hash_d"), Path::new("/videos/d.mp4")),
+            ImportOutcome::Imported
+        );
+        assert!(!table.contains_hash(3000, "hash_d"));
+        assert_eq!(table.len(), 4);
+
+        let json = serde_json::to_string(&table).unwrap();
+        let mut loaded: HashTable = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.len(), 4);
+        assert_eq!(loaded.take_unhashed(1000), vec![a.to_path_buf()]);
+        assert!(loaded.take_unhashed(1000).is_empty());
+    }
+
+    #[test]
+    fn test_resolve_hash_table_path() {
+        // 未設定位置時放在設定檔旁，不受工作目錄影響
+        assert_eq!(
+            resolve_hash_table_path(None, None),
+            config_directory().join("hash_table.json")
+        );
+        assert_eq!(
+            resolve_hash_table_path(Some(" "), Some("movies")),
+            config_directory().join("hash_table.movies.json")
+        );
+        assert_eq!(
+            resolve_hash_table_path(Some("/data/dedup.json"), Some("photos")),
+            PathBuf::from("/data/dedup.photos.json")
+        );
+        assert_eq!(
+            resolve_hash_table_path(Some("~/dedup.json"), None),
+            std::env::home_dir().unwrap().join("dedup.json")
+        );
+        assert!(is_valid_table_name("movies_2024"));
+        assert!(!is_valid_table_name("../movies"));
+        assert!(!is_valid_table_name(""));
+    }
+
+    #[test]
+    fn test_save_replaces_file_atomically() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("hash_table.json");
+
+        let mut table = HashTable::new();
+        table.insert(1000, "hash1".to_string(), Path::new("/a"));
+        table.save_to_file(&path).unwrap();
+
+        // 中斷的儲存只會留下暫存檔，原本的紀錄仍可讀取
+        fs::write(sibling_path(&path, ".tmp"), r#"{"version": 2, "si"#).unwrap();
+        let loaded = HashTable::load_from_file(&path).unwrap();
+        assert!(loaded.contains_hash(1000, "hash1"));
+
+        table.insert(2000, "hash2".to_string(), Path::new("/b"));
+        table.save_to_file(&path).unwrap();
+        assert!(!sibling_path(&path, ".tmp").exists());
+        let loaded = HashTable::load_from_file(&path).unwrap();
+        assert!(loaded.contains_hash(2000, "hash2"));
+    }
+
+    #[test]
+    fn test_lock_is_exclusive() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("hash_table.json");
+
+        let lock = HashTableLock::acquire(&path).unwrap();
+        let err = HashTableLock::acquire(&path).unwrap_err();
+        assert!(err.to_string().contains("正被其他程序使用"));
+
+        drop(lock);
+        assert!(HashTableLock::acquire(&path).is_ok());
+    }
+
+    #[test]
+    fn test_load_path_format() {
+        let json = r#"{"1000": {"hash1": "/videos/a.mp4", "hash2": null}}"#;
+        let table: HashTable = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            table.original_path(1000, "hash1"),
+            Some(Path::new("/videos/a.mp4"))
+        );
+        assert!(table.contains_hash(1000, "hash2"));
+        assert_eq!(table.original_path(1000, "hash2"), None);
+    }
+
+    #[test]
+    fn test_record_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("a.mp4");
+        fs::write(&file, b"a").unwrap();
+
+        let mut table = HashTable::new();
+        table.insert(1, "hash1".to_string(), &file);
+        let record = table.entries[&1].full["hash1"].clone();
+        assert_eq!(record.first_seen_path.as_deref(), Some(file.as_path()));
+        assert!(record.mtime.is_some());
+        assert!(record.registered_at.is_some());
+
+        let json = serde_json::to_string(&table).unwrap();
+        let loaded: HashTable = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.entries[&1].full["hash1"], record);
+    }
+
+    #[test]
+    fn test_prune_missing_and_clear() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let existing = dir.path().join("a.mp4");
+        fs::write(&existing, b"a").unwrap();
+
+        let mut table: HashTable = serde_json::from_str(r#"{"1": ["legacy"]}"#).unwrap();
+        table.insert(1000, "kept".to_string(), &existing);
+        table.insert(1000, "gone".to_string(), &dir.path().join("b.mp4"));
+        table.insert(2000, "gone2".to_string(), &dir.path().join("c.mp4"));
+        assert_eq!(table.len(), 4);
+
+        assert_eq!(table.prune_missing(), 2);
+        assert_eq!(table.len(), 2);
+        assert!(table.contains_hash(1000, "kept"));
+        assert!(table.contains_hash(1, "legacy"));
+        assert!(!table.has_size(2000));
+
+        table.clear();
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn test_partial_index() {
+        let mut table = HashTable::new();
+        let a = Path::new("/videos/a.mp4");
+        let b = Path::new("/videos/b.mp4");
+
+        assert_eq!(
+            table.register_partial(1000, "p1", a),
+            PartialMatch::Registered
+        );
+        assert_eq!(table.len(), 1);
+        assert_eq!(
+            table.register_partial(1000, "p2", b),
+            PartialMatch::Registered
+        );
+        assert_eq!(
+            table.register_partial(1000, "p1", b),
+            PartialMatch::Collision(Some(a.to_path_buf()))
+        );
+
+        // 補上完整 hash 後，同一部分 hash 不再附帶待計算的檔案
+        table.resolve_partial(1000, "p1", Some(("full_a".to_string(), a.to_path_buf())));
+        table.insert(1000, "full_b".to_string(), b);
+        assert_eq!(table.original_path(1000, "full_a"), Some(a));
+        assert_eq!(
+            table.register_partial(1000, "p1", b),
+            PartialMatch::Collision(None)
+        );
+        assert_eq!(table.len(), 3);
+
+        // 沒有部分 hash 索引的大小一律比對完整 hash
+        table.insert(2000, "legacy".to_string(), a);
+        assert_eq!(
+            table.register_partial(2000, "p3", b),
+            PartialMatch::Collision(None)
+        );
+
+        let temp_file = NamedTempFile::new().unwrap();
+        table.save_to_file(temp_file.path()).unwrap();
+        let loaded = HashTable::load_from_file(temp_file.path()).unwrap();
+        assert_eq!(loaded.len(), 4);
+        assert!(loaded.contains_hash(1000, "full_a"));
+        assert!(loaded.contains_hash(2000, "legacy"));
+        let mut loaded = loaded;
+        assert_eq!(
+            loaded.register_partial(1000, "p2", a),
+            PartialMatch::Collision(Some(b.to_path_buf()))
+        );
+    }
+
+    #[test]
+    fn test_choose_original() {
+        let dir = TempDir::new().unwrap();
+        let base = dir.path();
+        let old = base.join("b_old.mp4");
+        let new = base.join("a_new.mp4");
+        fs::write(&old, "x").unwrap();
+        fs::write(&new, "x").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(UNIX_EPOCH + std::time::Duration::from_secs(1_000_000))
+            .unwrap();
+
+        let mut table = HashTable::new();
+        table.insert(1, "h".to_string(), &old);
+        // 原始檔案較舊，保留原始檔案
+        assert_eq!(
+            table.choose_original(1, "h", &new, KeepPolicy::Oldest, base),
+            KeepDecision::Original(Some(old.clone()))
+        );
+        // 原始檔案不在可取代的資料夾中
+        assert_eq!(
+            table.choose_original(1, "h", &new, KeepPolicy::Newest, &base.join("other")),
+            KeepDecision::Original(Some(old.clone()))
+        );
+        assert_eq!(
+            table.choose_original(1, "h", &new, KeepPolicy::Newest, base),
+            KeepDecision::Replaced(old.clone())
+        );
+        assert_eq!(table.original_path(1, "h"), Some(new.as_path()));
+        // 被取代後以新的原始檔案比對
+        assert_eq!(
+            table.choose_original(1, "h", &old, KeepPolicy::Newest, base),
+            KeepDecision::Original(Some(new.clone()))
+        );
+        // 路徑長度相同時保留路徑排序較前者
+        assert_eq!(
+            table.choose_original(1, "h", &old, KeepPolicy::ShortestPath, base),
+            KeepDecision::Original(Some(new))
+        );
     }
 
     #[test]
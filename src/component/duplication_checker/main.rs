@@ -1,19 +1,39 @@
+use super::duplicate_report::{
+    DUPLICATE_REPORT_CSV, DUPLICATE_REPORT_JSON, DuplicateGroup, write_csv_report,
+    write_json_report,
+};
 use super::duplication_detector::{DuplicationDetector, DuplicationResult};
-use crate::config::Config;
+use super::folder_deduplicator::FolderDeduplicator;
+use super::hash_import::import_into;
+use super::hash_table::{HashTable, HashTableLock, resolve_hash_table_path};
+use super::perceptual_match::{SignatureStore, SimilarVideo, signature_store_path};
+use crate::component::path_prompt::{prompt_directory, prompt_new_directory};
+use crate::component::scan_options::prompt_size_filter;
+use crate::component::{Interaction, OutputFormat, PreflightFailed, RunStatus, report};
 use crate::config::save::{add_recent_path, save_settings};
-use crate::tools::validate_directory_exists;
-use anyhow::Result;
+use crate::config::{Config, DuplicateAction, KeepPolicy, MatchMode, PathFeature, UserSettings};
+use crate::tools::{
+    ExcludeFilter, check_dependencies, expand_path, format_size, validate_directory_exists,
+};
+use anyhow::{Context, Result};
 use console::style;
 use dialoguer::theme::ColorfulTheme;
-use dialoguer::{Input, Select};
+use dialoguer::{Confirm, Input, Select};
 use log::{info, warn};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 
+/// 報告模式顯示的重複檔案組數
+const TOP_OFFENDERS: usize = 10;
+
 pub struct DuplicationChecker {
     config: Config,
     shutdown_signal: Arc<AtomicBool>,
+    interaction: Interaction,
+    output: OutputFormat,
+    /// 不逐項詢問時是否只產生報告
+    dry_run: bool,
 }
 
 impl DuplicationChecker {
@@ -21,121 +41,810 @@ impl DuplicationChecker {
         Self {
             config,
             shutdown_signal,
+            interaction: Interaction::Prompt,
+            output: OutputFormat::Human,
+            dry_run: false,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_interaction(mut self, interaction: Interaction) -> Self {
+        self.interaction = interaction;
+        self
+    }
+
+    #[must_use]
+    pub fn with_output(mut self, output: OutputFormat) -> Self {
+        self.output = output;
+        self
+    }
+
+    /// 不逐項詢問時只產生報告，不處理檔案也不更新紀錄
+    #[must_use]
+    pub const fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn run(&self) -> Result<RunStatus> {
+        report!(
+            self.output,
+            "{}",
+            style("=== 資料分析紀錄與去重 ===").cyan().bold()
+        );
+        report!(self.output, "{}", style("(按 ESC 返回主選單)").dim());
+
+        let Some(hash_table_path) = self.prompt_hash_table()? else {
+            return Ok(RunStatus::Completed); // ESC pressed
+        };
+        let entries = HashTable::load_from_file(&hash_table_path)?.len();
+        report!(
+            self.output,
+            "{}",
+            style(format!(
+                "使用中的紀錄: {}（{entries} 筆）",
+                hash_table_path.display()
+            ))
+            .dim()
+        );
+
+        let options = [
+            "掃描資料夾並檢查重複檔案",
+            "清理紀錄（移除原始檔案已不存在的項目）",
+            "清空紀錄",
+            "匯入其他工具的掃描結果（czkawka / rmlint / CSV）",
+        ];
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("請選擇操作")
+            .items(options)
+            .default(0)
+            .interact_opt()?;
+
+        match selection {
+            Some(0) => return self.run_detection(&hash_table_path),
+            Some(1) => self.prune_hash_table(&hash_table_path)?,
+            Some(2) => self.clear_hash_table(&hash_table_path)?,
+            Some(3) => self.import_hash_table(&hash_table_path)?,
+            None => {} // ESC pressed
+            _ => unreachable!(),
+        }
+        Ok(RunStatus::Completed)
+    }
+
+    /// 選擇要使用的紀錄（沒有具名紀錄時直接使用預設紀錄）
+    fn prompt_hash_table(&self) -> Result<Option<PathBuf>> {
+        let settings = &self.config.settings;
+        let configured = settings.hash_table_path.as_deref();
+        if settings.hash_tables.is_empty() {
+            return Ok(Some(resolve_hash_table_path(configured, None)));
         }
+
+        let options: Vec<String> = std::iter::once("預設紀錄".to_string())
+            .chain(settings.hash_tables.iter().cloned())
+            .collect();
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("請選擇要使用的紀錄")
+            .items(&options)
+            .default(0)
+            .interact_opt()?;
+
+        Ok(selection.map(|idx| {
+            let name = idx.checked_sub(1).map(|i| settings.hash_tables[i].as_str());
+            resolve_hash_table_path(configured, name)
+        }))
     }
 
-    pub fn run(&self) -> Result<()> {
-        println!("{}", style("=== 資料分析紀錄與去重 ===").cyan().bold());
+    /// 不詢問路徑與選項，以預設紀錄檢查指定資料夾（命令列執行）
+    pub fn run_in(&self, directory: &Path) -> Result<RunStatus> {
+        report!(
+            self.output,
+            "{}",
+            style("=== 資料分析紀錄與去重 ===").cyan().bold()
+        );
+        validate_directory_exists(directory).context(PreflightFailed)?;
+        let hash_table_path =
+            resolve_hash_table_path(self.config.settings.hash_table_path.as_deref(), None);
+        report!(
+            self.output,
+            "{}",
+            style(format!("使用中的紀錄: {}", hash_table_path.display())).dim()
+        );
+        self.detect_in(
+            &hash_table_path,
+            directory,
+            &mut self.config.settings.clone(),
+        )
+    }
 
-        let Some(input_path) = self.prompt_input_path()? else {
-            return Ok(()); // ESC pressed
+    fn run_detection(&self, hash_table_path: &Path) -> Result<RunStatus> {
+        let mut settings = self.config.settings.clone();
+        let Some(input_path) = self.prompt_input_path(&mut settings)? else {
+            return Ok(RunStatus::Completed); // ESC pressed
         };
         let directory = PathBuf::from(&input_path);
 
         validate_directory_exists(&directory)?;
 
-        // 更新路徑歷史並儲存
+        // 路徑歷史與本次變更的選項一併儲存
+        add_recent_path(&mut settings, PathFeature::Duplication, &input_path);
+        let status = self.detect_in(hash_table_path, &directory, &mut settings);
+        if let Err(e) = save_settings(&settings) {
+            warn!("無法儲存設定: {e}");
+        }
+        status
+    }
+
+    /// 詢問選項並檢查重複檔案，選項的變更寫入 `settings`，由呼叫端儲存
+    fn detect_in(
+        &self,
+        hash_table_path: &Path,
+        directory: &Path,
+        settings: &mut UserSettings,
+    ) -> Result<RunStatus> {
+        let prompts = self.interaction.prompts_options();
+        let folder_mode = if prompts {
+            let Some(folder_mode) = Self::prompt_folder_mode()? else {
+                return Ok(RunStatus::Completed); // ESC pressed
+            };
+            folder_mode
+        } else {
+            false
+        };
+        let action = self.config.settings.duplication.action;
+        let dry_run = if prompts {
+            let Some(dry_run) = Self::prompt_dry_run(action, folder_mode)? else {
+                return Ok(RunStatus::Completed); // ESC pressed
+            };
+            dry_run
+        } else {
+            self.dry_run
+        };
+        if !prompts
+            && !dry_run
+            && !self.interaction.confirm(
+                &format!(
+                    "確定要處理 {} 中的重複檔案（{action}）嗎？",
+                    directory.display()
+                ),
+                true,
+            )?
         {
-            let mut settings = self.config.settings.clone();
-            add_recent_path(&mut settings, &input_path);
-            if let Err(e) = save_settings(&settings) {
-                warn!("無法儲存路徑歷史: {e}");
+            report!(self.output, "{}", style("操作已取消").yellow());
+            return Ok(RunStatus::Completed);
+        }
+        if dry_run {
+            report!(
+                self.output,
+                "{}",
+                style(if folder_mode {
+                    "僅產生重複檔案報告，不處理檔案"
+                } else {
+                    "僅產生重複檔案報告，不處理檔案也不更新紀錄"
+                })
+                .dim()
+            );
+        } else {
+            report!(
+                self.output,
+                "{}",
+                style(format!("重複檔案處理方式: {action}")).dim()
+            );
+        }
+        if folder_mode {
+            return self.run_folder_detection(
+                hash_table_path,
+                directory,
+                action,
+                dry_run,
+                settings,
+            );
+        }
+        let reference_directory = if prompts {
+            self.prompt_reference_directory()?
+        } else {
+            None
+        };
+        let Some(keep_policy) = self.prompt_record_keep_policy(settings)? else {
+            return Ok(RunStatus::Completed); // ESC pressed
+        };
+        let Some(match_mode) = self.prompt_match_mode(settings)? else {
+            return Ok(RunStatus::Completed); // ESC pressed
+        };
+        let size_filter =
+            prompt_size_filter(&self.config.settings, self.interaction, &self.output)?;
+        report!(self.output, "{}", style("掃描檔案中...").dim());
+
+        let mut detector = DuplicationDetector::new(
+            hash_table_path,
+            directory,
+            action,
+            dry_run,
+            Arc::clone(&self.shutdown_signal),
+        )?
+        .with_exclude_filter(ExcludeFilter::new(&self.config.settings.exclude_globs))
+        .with_size_filter(size_filter)
+        .with_deletion_mode(self.config.settings.deletion_mode)
+        .with_hash_threads(self.config.settings.duplication.hash_threads)
+        .with_keep_policy(keep_policy);
+        if let Some(reference_directory) = reference_directory {
+            detector = detector.with_reference_directory(reference_directory);
+        }
+        if match_mode == MatchMode::Perceptual {
+            detector = detector.with_perceptual_match(
+                self.config.settings.duplication.perceptual.clone(),
+                self.config.file_type_table.clone(),
+            );
+        }
+
+        let result = detector.detect_and_move_duplicates(directory)?;
+
+        self.print_summary(&result, dry_run, false);
+        if dry_run && !result.duplicate_groups.is_empty() {
+            if self.output.is_human() {
+                self.print_top_offenders(&result.duplicate_groups);
             }
+            self.write_reports(hash_table_path, &result.duplicate_groups);
+        }
+        if self.output.is_human() && !result.similar_videos.is_empty() {
+            self.print_similar_videos(&result.similar_videos, action, dry_run);
         }
 
-        println!("{}", style("掃描檔案中...").dim());
+        Ok(result.status())
+    }
 
-        let hash_table_path = self.get_hash_table_path();
+    /// 資料夾模式：只比對此資料夾內的檔案，不使用 hash table
+    fn run_folder_detection(
+        &self,
+        hash_table_path: &Path,
+        directory: &Path,
+        action: DuplicateAction,
+        dry_run: bool,
+        settings: &mut UserSettings,
+    ) -> Result<RunStatus> {
+        let Some(keep_policy) = self.prompt_keep_policy(settings)? else {
+            return Ok(RunStatus::Completed); // ESC pressed
+        };
+        let size_filter =
+            prompt_size_filter(&self.config.settings, self.interaction, &self.output)?;
+        report!(self.output, "{}", style("掃描檔案中...").dim());
 
-        let mut detector = DuplicationDetector::new(
-            &hash_table_path,
-            &directory,
+        let result = FolderDeduplicator::new(
+            directory,
+            action,
+            dry_run,
             Arc::clone(&self.shutdown_signal),
-        )?;
+        )
+        .with_keep_policy(keep_policy)
+        .with_deletion_mode(self.config.settings.deletion_mode)
+        .with_hash_threads(self.config.settings.duplication.hash_threads)
+        .with_exclude_filter(ExcludeFilter::new(&self.config.settings.exclude_globs))
+        .with_size_filter(size_filter)
+        .detect_and_handle_duplicates(directory)?;
 
-        let result = detector.detect_and_move_duplicates(&directory)?;
+        self.print_summary(&result, dry_run, true);
+        if dry_run && !result.duplicate_groups.is_empty() {
+            if self.output.is_human() {
+                self.print_top_offenders(&result.duplicate_groups);
+            }
+            self.write_reports(hash_table_path, &result.duplicate_groups);
+        }
 
-        self.print_summary(&result);
+        Ok(result.status())
+    }
 
-        Ok(())
+    /// 選擇紀錄模式或資料夾模式，回傳是否為資料夾模式
+    fn prompt_folder_mode() -> Result<Option<bool>> {
+        let options = [
+            "紀錄模式（與 hash table 中先前掃描過的檔案比對）",
+            "資料夾模式（只比對此資料夾內的檔案，不使用紀錄）",
+        ];
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("請選擇比對範圍")
+            .items(options)
+            .default(0)
+            .interact_opt()?;
+        Ok(selection.map(|idx| idx == 1))
     }
 
-    fn prompt_input_path(&self) -> Result<Option<String>> {
-        let recent_paths = &self.config.settings.recent_paths;
+    /// 選擇每組重複檔案保留哪一個，變更時寫回設定
+    fn prompt_keep_policy(&self, settings: &mut UserSettings) -> Result<Option<KeepPolicy>> {
+        let current = settings.duplication.keep_policy;
+        let options: Vec<String> = KeepPolicy::ALL.iter().map(ToString::to_string).collect();
+        let default = KeepPolicy::ALL
+            .iter()
+            .position(|&p| p == current)
+            .unwrap_or(0);
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("每組重複檔案要保留哪一個？")
+            .items(&options)
+            .default(default)
+            .interact_opt()?;
+        let Some(idx) = selection else {
+            return Ok(None);
+        };
 
-        // 如果沒有歷史路徑，直接輸入
-        if recent_paths.is_empty() {
-            let path: String = Input::new()
-                .with_prompt("請輸入要檢查的資料夾路徑")
-                .interact_text()?;
-            return Ok(Some(path.trim().to_string()));
-        }
+        let keep_policy = KeepPolicy::ALL[idx];
+        settings.duplication.keep_policy = keep_policy;
+        Ok(Some(keep_policy))
+    }
 
-        // 建立選項清單：歷史路徑 + 輸入新路徑
-        let mut options: Vec<String> = recent_paths
+    /// 選擇紀錄模式下重複時保留的檔案（`Some(None)` 為保留原始檔案），變更時寫回設定
+    fn prompt_record_keep_policy(
+        &self,
+        settings: &mut UserSettings,
+    ) -> Result<Option<Option<KeepPolicy>>> {
+        let current = settings.duplication.record_keep_policy;
+        if !self.interaction.prompts_options() {
+            return Ok(Some(current));
+        }
+        let choices: Vec<Option<KeepPolicy>> = std::iter::once(None)
+            .chain(KeepPolicy::ALL.map(Some))
+            .collect();
+        let options: Vec<String> = choices
             .iter()
-            .enumerate()
-            .map(|(i, p)| {
-                let exists = Path::new(p).exists();
-                let indicator = if exists { "✓" } else { "✗" };
-                format!("{} [{}] {}", i + 1, indicator, p)
+            .map(|choice| {
+                choice.map_or_else(
+                    || "保留紀錄中的原始檔案（先登記的檔案）".to_string(),
+                    |policy| format!("{policy}（只取代本次掃描資料夾中的原始檔案）"),
+                )
             })
             .collect();
-        options.push("輸入新路徑...".to_string());
+        let default = choices.iter().position(|&c| c == current).unwrap_or(0);
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("與紀錄中的檔案重複時要保留哪一個？")
+            .items(&options)
+            .default(default)
+            .interact_opt()?;
+        let Some(idx) = selection else {
+            return Ok(None);
+        };
 
-        println!("{}", style("(按 ESC 返回主選單)").dim());
+        let keep_policy = choices[idx];
+        settings.duplication.record_keep_policy = keep_policy;
+        Ok(Some(keep_policy))
+    }
+
+    /// 選擇紀錄模式的比對方式，變更時寫回設定
+    ///
+    /// 畫面相似比對需要 ffmpeg 與 ffprobe，找不到時改為只比對內容完全相同的檔案
+    fn prompt_match_mode(&self, settings: &mut UserSettings) -> Result<Option<MatchMode>> {
+        let current = settings.duplication.match_mode;
+        let match_mode = if self.interaction.prompts_options() {
+            let options: Vec<String> = MatchMode::ALL.iter().map(ToString::to_string).collect();
+            let default = MatchMode::ALL
+                .iter()
+                .position(|&m| m == current)
+                .unwrap_or(0);
+            let selection = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt("請選擇比對方式")
+                .items(&options)
+                .default(default)
+                .interact_opt()?;
+            let Some(idx) = selection else {
+                return Ok(None);
+            };
 
+            let match_mode = MatchMode::ALL[idx];
+            settings.duplication.match_mode = match_mode;
+            match_mode
+        } else {
+            current
+        };
+
+        if match_mode == MatchMode::Perceptual && !check_dependencies().video_tools_ready() {
+            warn!("找不到 ffmpeg 或 ffprobe，無法比對畫面");
+            report!(
+                self.output,
+                "{}",
+                style("找不到 ffmpeg 或 ffprobe，改為只比對內容完全相同的檔案").yellow()
+            );
+            return Ok(Some(MatchMode::Exact));
+        }
+        Ok(Some(match_mode))
+    }
+
+    fn prompt_input_path(&self, settings: &mut UserSettings) -> Result<Option<String>> {
+        prompt_directory(
+            settings,
+            PathFeature::Duplication,
+            "請輸入要檢查的資料夾路徑",
+            &self.output,
+        )
+    }
+
+    /// 選擇處理重複檔案或只回報，ESC 回傳 `None`
+    fn prompt_dry_run(action: DuplicateAction, folder_mode: bool) -> Result<Option<bool>> {
+        let report_only = if folder_mode {
+            "只產生報告（不處理檔案）"
+        } else {
+            "只產生報告（不處理檔案、不更新紀錄）"
+        };
+        let options = [
+            format!("找出並處理重複檔案（{action}）"),
+            report_only.to_string(),
+        ];
         let selection = Select::with_theme(&ColorfulTheme::default())
-            .with_prompt("請選擇路徑")
+            .with_prompt("只產生報告，還是處理重複檔案？")
             .items(&options)
             .default(0)
             .interact_opt()?;
+        Ok(selection.map(|idx| idx == 1))
+    }
 
-        match selection {
-            None => Ok(None),
-            Some(idx) if idx < recent_paths.len() => Ok(Some(recent_paths[idx].clone())),
-            Some(_) => {
-                let path: String = Input::new()
-                    .with_prompt("請輸入要檢查的資料夾路徑")
-                    .interact_text()?;
-                Ok(Some(path.trim().to_string()))
+    /// 顯示可釋放空間最多的重複檔案
+    fn print_top_offenders(&self, groups: &[DuplicateGroup]) {
+        report!(self.output);
+        report!(
+            self.output,
+            "{}",
+            style(format!("佔用空間最多的重複檔案（前 {TOP_OFFENDERS} 組）:")).dim()
+        );
+        for group in groups.iter().take(TOP_OFFENDERS) {
+            let name = group
+                .duplicates
+                .first()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default();
+            report!(
+                self.output,
+                "  {} {} × {}（{}）",
+                style("•").dim(),
+                style(format_size(group.wasted_bytes())).yellow(),
+                group.duplicates.len(),
+                name
+            );
+        }
+    }
+
+    /// 列出畫面相似的影片；刪除與硬連結模式下只回報，不處理
+    fn print_similar_videos(
+        &self,
+        videos: &[SimilarVideo],
+        action: DuplicateAction,
+        dry_run: bool,
+    ) {
+        report!(self.output);
+        report!(
+            self.output,
+            "{}",
+            style(format!("畫面相似的影片（前 {TOP_OFFENDERS} 個）:")).dim()
+        );
+        for video in videos.iter().take(TOP_OFFENDERS) {
+            report!(
+                self.output,
+                "  {} {} ≈ {}（距離 {}）",
+                style("•").dim(),
+                video.path.display(),
+                video.original.display(),
+                video.distance
+            );
+        }
+        if !dry_run && action != DuplicateAction::Move {
+            report!(
+                self.output,
+                "{}",
+                style("畫面相似的影片內容並不相同，只在移動模式下處理，請手動確認").yellow()
+            );
+        }
+    }
+
+    /// 將重複檔案報告寫在 hash table 旁
+    ///
+    /// 不寫入掃描資料夾，避免報告在下次掃描時被當成一般檔案處理
+    fn write_reports(&self, hash_table_path: &Path, groups: &[DuplicateGroup]) {
+        let json_path = hash_table_path.with_file_name(DUPLICATE_REPORT_JSON);
+        let csv_path = hash_table_path.with_file_name(DUPLICATE_REPORT_CSV);
+        let written = write_json_report(&json_path, groups)
+            .and_then(|()| write_csv_report(&csv_path, groups));
+        match written {
+            Ok(()) if !self.output.is_human() => {
+                info!(
+                    "重複檔案報告: {}、{}",
+                    json_path.display(),
+                    csv_path.display()
+                );
+            }
+            Ok(()) => {
+                report!(self.output);
+                report!(self.output, "{}", style("重複檔案報告:").dim());
+                report!(self.output, "  {}", json_path.display());
+                report!(self.output, "  {}", csv_path.display());
             }
+            Err(e) => warn!("無法寫入重複檔案報告: {e:#}"),
         }
     }
 
-    fn get_hash_table_path(&self) -> PathBuf {
-        // 存放在程式執行的當前目錄，方便與程式一起移動
-        PathBuf::from("hash_table.json")
+    /// 詢問是否先索引參考資料夾（參考資料夾內的檔案不會被處理）
+    fn prompt_reference_directory(&self) -> Result<Option<PathBuf>> {
+        let use_reference = Confirm::new()
+            .with_prompt(
+                "是否與參考資料夾比對？（只處理掃描資料夾中的重複檔案，參考資料夾不會被修改）",
+            )
+            .default(false)
+            .interact()?;
+        if !use_reference {
+            return Ok(None);
+        }
+
+        prompt_new_directory("請輸入參考資料夾路徑", &self.output)
+    }
+
+    /// 移除原始檔案已不存在的紀錄
+    fn prune_hash_table(&self, hash_table_path: &Path) -> Result<()> {
+        let _lock = HashTableLock::acquire(hash_table_path)?;
+        let mut table = HashTable::load_from_file(hash_table_path)?;
+        if table.is_empty() {
+            report!(self.output, "{}", style("紀錄是空的，不需要清理").yellow());
+            return Ok(());
+        }
+
+        report!(self.output, "{}", style("檢查紀錄中的檔案...").dim());
+        let removed = table.prune_missing();
+        if removed > 0 {
+            table.save_to_file(hash_table_path)?;
+        }
+
+        report!(self.output);
+        report!(
+            self.output,
+            "  已移除: {} 筆（原始檔案已不存在）",
+            style(removed).green()
+        );
+        report!(self.output, "  剩餘: {} 筆", table.len());
+        info!("清理 hash table - 移除: {removed}, 剩餘: {}", table.len());
+
+        let store_path = signature_store_path(hash_table_path);
+        let mut signatures = SignatureStore::load_from_file(&store_path)?;
+        if !signatures.is_empty() {
+            let removed = signatures.prune_missing();
+            if removed > 0 {
+                signatures.save_to_file(&store_path)?;
+            }
+            report!(
+                self.output,
+                "  影片指紋: 移除 {} 筆，剩餘 {} 筆",
+                style(removed).green(),
+                signatures.len()
+            );
+            info!("清理影片指紋 - 移除: {removed}, 剩餘: {}", signatures.len());
+        }
+        Ok(())
+    }
+
+    /// 清空所有紀錄
+    fn clear_hash_table(&self, hash_table_path: &Path) -> Result<()> {
+        let _lock = HashTableLock::acquire(hash_table_path)?;
+        let mut table = HashTable::load_from_file(hash_table_path)?;
+        if table.is_empty() {
+            report!(self.output, "{}", style("紀錄是空的").yellow());
+            return Ok(());
+        }
+
+        let confirm = Confirm::new()
+            .with_prompt(format!(
+                "確定要清空 {} 筆紀錄嗎？之後的掃描將無法辨識先前出現過的檔案",
+                table.len()
+            ))
+            .default(false)
+            .interact()?;
+        if !confirm {
+            report!(self.output, "{}", style("操作已取消").yellow());
+            return Ok(());
+        }
+
+        table.clear();
+        table.save_to_file(hash_table_path)?;
+        let store_path = signature_store_path(hash_table_path);
+        if store_path.exists() {
+            std::fs::remove_file(&store_path)
+                .with_context(|| format!("無法刪除指紋紀錄: {}", store_path.display()))?;
+        }
+        report!(self.output, "{}", style("紀錄已清空").green());
+        info!("已清空 hash table: {}", hash_table_path.display());
+        Ok(())
+    }
+
+    /// 匯入 czkawka、rmlint 或 CSV 的掃描結果
+    ///
+    /// 非 BLAKE3 的 hash 只匯入大小與路徑，之後掃描到相同大小的檔案時才計算 hash
+    fn import_hash_table(&self, hash_table_path: &Path) -> Result<()> {
+        let input: String = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("請輸入要匯入的檔案路徑")
+            .interact_text()?;
+        let source = expand_path(input.trim());
+
+        let _lock = HashTableLock::acquire(hash_table_path)?;
+        let mut table = HashTable::load_from_file(hash_table_path)?;
+        let result = import_into(&mut table, &source)?;
+        if result.imported > 0 {
+            table.save_to_file(hash_table_path)?;
+        }
+
+        report!(self.output);
+        report!(self.output, "  格式: {}", result.format);
+        report!(self.output, "  匯入: {} 筆", style(result.imported).green());
+        report!(self.output, "  合併: {} 筆（已有紀錄）", result.merged);
+        if result.skipped > 0 {
+            report!(
+                self.output,
+                "  略過: {} 筆（缺少大小或格式錯誤）",
+                style(result.skipped).yellow()
+            );
+        }
+        if result.with_hash < result.imported + result.merged {
+            report!(
+                self.output,
+                "{}",
+                style("  未提供 BLAKE3 hash 的檔案會在之後掃描到相同大小的檔案時計算").dim()
+            );
+        }
+        info!(
+            "匯入 {} ({}) - 匯入: {}, 合併: {}, 略過: {}",
+            source.display(),
+            result.format,
+            result.imported,
+            result.merged,
+            result.skipped
+        );
+        Ok(())
     }
 
-    fn print_summary(&self, result: &DuplicationResult) {
-        println!();
-        println!("{}", style("=== 去重任務摘要 ===").cyan().bold());
-        println!("  總計掃描: {} 個檔案", result.total_files);
-        println!("  發現重複: {} 個", style(result.duplicates_found).yellow());
-        println!(
-            "  已移動重複: {} 個",
-            style(result.duplicates_moved).green()
+    fn print_summary(&self, result: &DuplicationResult, dry_run: bool, folder_mode: bool) {
+        if self.output.emit_json(result) {
+            return;
+        }
+        report!(self.output);
+        report!(
+            self.output,
+            "{}",
+            style("=== 去重任務摘要 ===").cyan().bold()
         );
-        println!(
-            "  新增紀錄: {} 個",
-            style(result.new_files_registered).green()
+        if result.reference_files_indexed > 0 {
+            report!(
+                self.output,
+                "  參考資料夾索引: {} 個檔案",
+                result.reference_files_indexed
+            );
+        }
+        report!(self.output, "  總計掃描: {} 個檔案", result.total_files);
+        if result.aborted {
+            report!(
+                self.output,
+                "  {}",
+                style(format!(
+                    "已中斷：處理 {} / {} 個檔案（{}）後停止",
+                    result.files_processed,
+                    result.total_files,
+                    format_size(result.bytes_processed)
+                ))
+                .yellow()
+            );
+        }
+        if result.reference_filtered_by_size > 0 {
+            report!(
+                self.output,
+                "  參考資料夾依檔案大小略過: {} 個",
+                style(result.reference_filtered_by_size).dim()
+            );
+        }
+        if result.filtered_by_size > 0 {
+            report!(
+                self.output,
+                "  依檔案大小略過: {} 個",
+                style(result.filtered_by_size).dim()
+            );
+        }
+        report!(
+            self.output,
+            "  發現重複: {} 個",
+            style(result.duplicates_found).yellow()
         );
+        if result.bytes_reclaimable > 0 {
+            report!(
+                self.output,
+                "  重複檔案佔用: {}",
+                style(format_size(result.bytes_reclaimable)).yellow()
+            );
+        }
+        if result.duplicates_moved > 0 {
+            report!(
+                self.output,
+                "  已移動重複: {} 個",
+                style(result.duplicates_moved).green()
+            );
+        }
+        if result.duplicates_trashed > 0 {
+            report!(
+                self.output,
+                "  已送到資源回收筒: {} 個",
+                style(result.duplicates_trashed).green()
+            );
+        }
+        if result.duplicates_deleted > 0 {
+            report!(
+                self.output,
+                "  已刪除重複: {} 個",
+                style(result.duplicates_deleted).green()
+            );
+        }
+        if result.duplicates_hardlinked > 0 {
+            report!(
+                self.output,
+                "  已以硬連結取代: {} 個",
+                style(result.duplicates_hardlinked).green()
+            );
+        }
+        if result.bytes_reclaimed > 0 {
+            report!(
+                self.output,
+                "  已釋放: {}",
+                style(format_size(result.bytes_reclaimed)).green()
+            );
+        }
+        // 資料夾模式不使用紀錄
+        if !folder_mode {
+            if dry_run {
+                report!(
+                    self.output,
+                    "  未記錄的檔案: {} 個",
+                    style(result.new_files_registered).green()
+                );
+            } else {
+                report!(
+                    self.output,
+                    "  新增紀錄: {} 個",
+                    style(result.new_files_registered).green()
+                );
+            }
+        }
+        if result.already_recorded > 0 {
+            report!(
+                self.output,
+                "  已在紀錄中（原始檔案）: {} 個",
+                style(result.already_recorded).dim()
+            );
+        }
         if result.errors > 0 {
-            println!("  錯誤: {} 個", style(result.errors).red());
+            report!(self.output, "  錯誤: {} 個", style(result.errors).red());
         }
 
+        if !result.similar_videos.is_empty() {
+            report!(
+                self.output,
+                "  畫面相似: {} 個",
+                style(result.similar_videos.len()).yellow()
+            );
+        }
         if result.duplicates_moved > 0 {
-            println!();
-            println!(
+            report!(self.output);
+            report!(
+                self.output,
                 "{}",
                 style("重複檔案已移動到 duplication_file 資料夾").yellow()
             );
         }
+        if result.duplicates_trashed > 0 {
+            report!(self.output);
+            report!(
+                self.output,
+                "{}",
+                style("重複檔案已送到資源回收筒").yellow()
+            );
+        }
+
+        if dry_run && result.duplicates_found > 0 {
+            report!(self.output);
+            report!(
+                self.output,
+                "{}",
+                style("僅產生報告，重複檔案未處理").yellow()
+            );
+        }
 
         info!(
             "去重任務完成 - 總計: {}, 重複: {}, 新增: {}, 錯誤: {}",
             result.total_files, result.duplicates_found, result.new_files_registered, result.errors
         );
+        self.output.report_log_file();
     }
 }
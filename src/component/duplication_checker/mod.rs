@@ -1,11 +1,28 @@
 //! 資料分析紀錄與去重元件
 //!
-//! 使用 BLAKE3 hash 來識別重複檔案，並將重複檔案移動到指定目錄
+//! 使用 BLAKE3 hash 來識別重複檔案，並依設定移動、刪除重複檔案，或以硬連結取代。
+//! 紀錄模式以 hash table 跨次比對，資料夾模式只比對單一資料夾內的檔案。
+//! 紀錄模式可另以影格的感知 hash 找出重新編碼過的相同影片
 
+mod duplicate_report;
 mod duplication_detector;
+mod folder_deduplicator;
+mod hash_import;
 mod hash_table;
 mod main;
+mod perceptual_match;
 
+pub use duplicate_report::{
+    DUPLICATE_REPORT_CSV, DUPLICATE_REPORT_JSON, DuplicateGroup, write_csv_report,
+    write_json_report,
+};
 pub use duplication_detector::{DuplicationDetector, DuplicationResult};
-pub use hash_table::HashTable;
+pub use folder_deduplicator::FolderDeduplicator;
+pub use hash_import::{ImportFormat, ImportResult, import_into};
+pub use hash_table::{
+    DEFAULT_HASH_TABLE_FILE, HashTable, is_valid_table_name, resolve_hash_table_path,
+};
 pub use main::DuplicationChecker;
+pub use perceptual_match::{
+    SignatureRecord, SignatureStore, SimilarVideo, compute_signature, signature_store_path,
+};
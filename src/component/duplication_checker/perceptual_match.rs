@@ -0,0 +1,359 @@
+//! 畫面相似比對
+//!
+//! 內容不同但畫面相同的影片（重新編碼、縮放）BLAKE3 無法辨識，
+//! 改以均勻取樣影格的 dHash 作為影片指紋，與紀錄中的指紋比較 Hamming 距離。
+//! 指紋存放在 hash table 旁的 `<hash table 檔名>.signatures`
+
+use super::hash_table::{replace_file, sibling_path};
+use crate::component::contact_sheet_generator::{
+    create_thumbnail_tasks, extract_thumbnail, select_uniform_timestamps,
+};
+use crate::config::{FileTypeTable, PerceptualSettings};
+use crate::tools::{
+    FileInfo, HashAborted, VideoSignature, ensure_directory_exists, get_video_info, image_dhash,
+};
+use anyhow::{Context, Result, bail};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use uuid::Uuid;
+
+/// 指紋紀錄檔的路徑
+#[must_use]
+pub fn signature_store_path(hash_table_path: &Path) -> PathBuf {
+    sibling_path(hash_table_path, ".signatures")
+}
+
+/// 單一影片的指紋紀錄
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignatureRecord {
+    pub path: PathBuf,
+    pub size: u64,
+    #[serde(flatten)]
+    pub signature: VideoSignature,
+}
+
+/// 影片指紋紀錄
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SignatureStore {
+    records: Vec<SignatureRecord>,
+}
+
+impl SignatureStore {
+    /// 讀取指紋紀錄，檔案不存在時為空紀錄
+    pub fn load_from_file(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("無法讀取指紋紀錄: {}", path.display()))?;
+        if content.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        serde_json::from_str(&content)
+            .with_context(|| format!("無法解析指紋紀錄: {}", path.display()))
+    }
+
+    pub fn save_to_file(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self).with_context(|| "無法序列化指紋紀錄")?;
+        replace_file(path, &content).with_context(|| "無法儲存指紋紀錄")
+    }
+
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    #[must_use]
+    pub fn contains_path(&self, path: &Path) -> bool {
+        self.records.iter().any(|record| record.path == path)
+    }
+
+    /// 新增紀錄，相同路徑的舊紀錄會被取代
+    pub fn insert(&mut self, record: SignatureRecord) {
+        self.records.retain(|existing| existing.path != record.path);
+        self.records.push(record);
+    }
+
+    /// 找出距離最小且不超過 `threshold` 的紀錄（不含 `path` 本身）
+    #[must_use]
+    pub fn find_similar(
+        &self,
+        signature: &VideoSignature,
+        threshold: u32,
+        path: &Path,
+    ) -> Option<(&SignatureRecord, u32)> {
+        self.records
+            .iter()
+            .filter(|record| record.path != path)
+            .filter_map(|record| {
+                let distance = record.signature.distance(signature)?;
+                (distance <= threshold).then_some((record, distance))
+            })
+            .min_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.path.cmp(&b.0.path)))
+    }
+
+    /// 移除影片已不存在的紀錄，回傳移除的筆數
+    pub fn prune_missing(&mut self) -> usize {
+        let before = self.records.len();
+        self.records.retain(|record| record.path.is_file());
+        before - self.records.len()
+    }
+}
+
+/// 與紀錄中的影片畫面相似的影片
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SimilarVideo {
+    pub path: PathBuf,
+    pub size: u64,
+    /// 紀錄中畫面相似的影片
+    pub original: PathBuf,
+    /// 平均每影格的 Hamming 距離
+    pub distance: u32,
+}
+
+/// 計算影片指紋並與紀錄比對
+#[derive(Debug)]
+pub(super) struct PerceptualMatcher {
+    settings: PerceptualSettings,
+    file_type_table: FileTypeTable,
+    store: Mutex<SignatureStore>,
+}
+
+impl PerceptualMatcher {
+    pub fn new(settings: PerceptualSettings, file_type_table: FileTypeTable) -> Self {
+        Self {
+            settings,
+            file_type_table,
+            store: Mutex::new(SignatureStore::default()),
+        }
+    }
+
+    /// 載入 hash table 對應的指紋紀錄
+    pub fn load(&self, hash_table_path: &Path) -> Result<()> {
+        let store = SignatureStore::load_from_file(&signature_store_path(hash_table_path))?;
+        *self.lock()? = store;
+        Ok(())
+    }
+
+    pub fn save(&self, hash_table_path: &Path) -> Result<()> {
+        self.lock()?
+            .save_to_file(&signature_store_path(hash_table_path))
+    }
+
+    /// 比對新影片：找到相似影片時回傳，否則登記此影片的指紋
+    ///
+    /// 非影片檔或無法取樣的影片（僅記錄警告）回傳 `None`；收到中斷訊號時回傳 [`HashAborted`]
+    pub fn match_or_register(
+        &self,
+        file: &FileInfo,
+        shutdown_signal: &AtomicBool,
+    ) -> Result<Option<SimilarVideo>> {
+        let Some(signature) = self.signature(file, shutdown_signal)? else {
+            return Ok(None);
+        };
+
+        // 比對與登記在同一次鎖定內完成，避免兩部相似影片同時被視為新影片
+        let mut store = self.lock()?;
+        if let Some((record, distance)) =
+            store.find_similar(&signature, self.settings.threshold, &file.path)
+        {
+            return Ok(Some(SimilarVideo {
+                path: file.path.clone(),
+                size: file.size,
+                original: record.path.clone(),
+                distance,
+            }));
+        }
+        store.insert(SignatureRecord {
+            path: file.path.clone(),
+            size: file.size,
+            signature,
+        });
+        Ok(None)
+    }
+
+    /// 登記參考影片的指紋（已有紀錄時不重新計算）
+    pub fn register(&self, file: &FileInfo, shutdown_signal: &AtomicBool) -> Result<()> {
+        if self.lock()?.contains_path(&file.path) {
+            return Ok(());
+        }
+        if let Some(signature) = self.signature(file, shutdown_signal)? {
+            self.lock()?.insert(SignatureRecord {
+                path: file.path.clone(),
+                size: file.size,
+                signature,
+            });
+        }
+        Ok(())
+    }
+
+    /// 影片的指紋，非影片檔或取樣失敗時為 `None`
+    fn signature(
+        &self,
+        file: &FileInfo,
+        shutdown_signal: &AtomicBool,
+    ) -> Result<Option<VideoSignature>> {
+        if !self.file_type_table.is_video_file(&file.path) {
+            return Ok(None);
+        }
+        match compute_signature(&file.path, self.settings.frames, shutdown_signal) {
+            Ok(signature) => Ok(Some(signature)),
+            Err(e) if e.is::<HashAborted>() => Err(e),
+            Err(e) => {
+                warn!(
+                    "無法計算影片指紋，略過畫面比對 {}: {e:#}",
+                    file.path.display()
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, SignatureStore>> {
+        self.store
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock failed: {e}"))
+    }
+}
+
+/// 均勻取樣影格並計算影片指紋
+///
+/// 擷取影格失敗時回傳錯誤，不使用全黑替代圖片，避免不同影片因替代圖片而被視為相似
+pub fn compute_signature(
+    path: &Path,
+    frames: usize,
+    shutdown_signal: &AtomicBool,
+) -> Result<VideoSignature> {
+    let info = get_video_info(path)?;
+    let timestamps = select_uniform_timestamps(info.duration_seconds, frames);
+    if timestamps.is_empty() {
+        bail!("影片長度為 0，無法取樣: {}", path.display());
+    }
+
+    let work_dir =
+        std::env::temp_dir().join(format!("auto_video_organize_signature_{}", Uuid::new_v4()));
+    ensure_directory_exists(&work_dir)?;
+    let frames = sample_frames(path, &timestamps, &work_dir, shutdown_signal);
+    if fs::remove_dir_all(&work_dir).is_err() {
+        warn!("無法清理暫存目錄: {}", work_dir.display());
+    }
+
+    let signature = VideoSignature {
+        duration_seconds: info.duration_seconds,
+        frames: frames?,
+    };
+    info!(
+        "影片指紋: {}（{} 個影格）",
+        path.display(),
+        signature.frames.len()
+    );
+    Ok(signature)
+}
+
+fn sample_frames(
+    path: &Path,
+    timestamps: &[f64],
+    work_dir: &Path,
+    shutdown_signal: &AtomicBool,
+) -> Result<Vec<u64>> {
+    create_thumbnail_tasks(path, timestamps, work_dir)
+        .iter()
+        .map(|task| {
+            if shutdown_signal.load(Ordering::SeqCst) {
+                return Err(HashAborted.into());
+            }
+            let result = extract_thumbnail(task);
+            if !result.success || result.error_message.is_some() {
+                bail!(
+                    "無法擷取第 {} 個影格: {}",
+                    task.index + 1,
+                    result.error_message.unwrap_or_default()
+                );
+            }
+            image_dhash(&result.output_path)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn record(path: &str, frames: Vec<u64>) -> SignatureRecord {
+        SignatureRecord {
+            path: PathBuf::from(path),
+            size: 100,
+            signature: VideoSignature {
+                duration_seconds: 60.0,
+                frames,
+            },
+        }
+    }
+
+    #[test]
+    fn test_find_similar() {
+        let mut store = SignatureStore::default();
+        store.insert(record("/a.mp4", vec![0, 0]));
+        store.insert(record("/b.mp4", vec![0b1, 0b1]));
+        store.insert(record("/a.mp4", vec![0b1111, 0b1111]));
+        assert_eq!(store.len(), 2);
+
+        let candidate = record("/c.mp4", vec![0, 0]).signature;
+        let (found, distance) = store
+            .find_similar(&candidate, 5, Path::new("/c.mp4"))
+            .unwrap();
+        assert_eq!(found.path, PathBuf::from("/b.mp4"));
+        assert_eq!(distance, 1);
+        assert!(
+            store
+                .find_similar(&candidate, 0, Path::new("/c.mp4"))
+                .is_none()
+        );
+        // 不與自己比對
+        assert!(
+            store
+                .find_similar(&candidate, 5, Path::new("/b.mp4"))
+                .is_some_and(|(found, _)| found.path == Path::new("/a.mp4"))
+        );
+    }
+
+    #[test]
+    fn test_store_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let hash_table_path = temp_dir.path().join("hash_table.json");
+        let store_path = signature_store_path(&hash_table_path);
+        assert_eq!(
+            store_path,
+            temp_dir.path().join("hash_table.json.signatures")
+        );
+
+        assert!(
+            SignatureStore::load_from_file(&store_path)
+                .unwrap()
+                .is_empty()
+        );
+
+        let video = temp_dir.path().join("video.mp4");
+        fs::write(&video, b"video").unwrap();
+        let mut store = SignatureStore::default();
+        store.insert(record(video.to_str().unwrap(), vec![u64::MAX, 7]));
+        store.insert(record("/missing.mp4", vec![0]));
+        store.save_to_file(&store_path).unwrap();
+
+        let mut loaded = SignatureStore::load_from_file(&store_path).unwrap();
+        assert_eq!(loaded.records, store.records);
+        assert_eq!(loaded.prune_missing(), 1);
+        assert!(loaded.contains_path(&video));
+    }
+}
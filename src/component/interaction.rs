@@ -0,0 +1,39 @@
+//! 元件的互動方式
+//!
+//! 主選單逐項詢問；命令列執行時選項改用設定檔與參數，視需要只保留確認提示
+
+use anyhow::Result;
+use dialoguer::Confirm;
+use log::info;
+
+/// 元件的互動方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Interaction {
+    /// 逐項詢問（主選單）
+    #[default]
+    Prompt,
+    /// 選項取自設定檔與命令列參數，只詢問是否執行
+    ConfirmOnly,
+    /// 完全不詢問，確認提示一律視為同意
+    Unattended,
+}
+
+impl Interaction {
+    /// 是否逐項詢問選項（否則使用設定檔或命令列參數的值）
+    #[must_use]
+    pub fn prompts_options(self) -> bool {
+        self == Self::Prompt
+    }
+
+    /// 詢問是否執行，`Unattended` 時不詢問直接同意並記錄
+    pub fn confirm(self, prompt: &str, default: bool) -> Result<bool> {
+        if self == Self::Unattended {
+            info!("自動確認: {prompt}");
+            return Ok(true);
+        }
+        Ok(Confirm::new()
+            .with_prompt(prompt)
+            .default(default)
+            .interact()?)
+    }
+}
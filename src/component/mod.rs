@@ -5,13 +5,23 @@
 pub mod auto_move_by_type;
 pub mod contact_sheet_generator;
 pub mod duplication_checker;
+mod interaction;
 pub mod orphan_file_mover;
+mod output;
+mod path_prompt;
+mod run_status;
+mod scan_options;
 pub mod video_encoder;
 pub mod video_renamer;
 
 pub use auto_move_by_type::AutoMoveByType;
 pub use contact_sheet_generator::ContactSheetGenerator;
 pub use duplication_checker::DuplicationChecker;
+pub use interaction::Interaction;
 pub use orphan_file_mover::OrphanFileMover;
+pub(crate) use output::report;
+pub use output::{OutputFormat, ResultSink};
+pub(crate) use path_prompt::prompt_output_directory;
+pub use run_status::{PreflightFailed, RunStatus};
 pub use video_encoder::VideoEncoder;
 pub use video_renamer::VideoRenamer;
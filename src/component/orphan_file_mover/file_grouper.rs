@@ -2,20 +2,58 @@
 //!
 //! 掃描資料夾，將檔案依同名分組，並識別孤立檔案
 
-use crate::tools::{ensure_directory_exists, validate_directory_exists};
+use crate::config::StemNormalization;
+use crate::tools::{csv_field, ensure_directory_exists, move_file, validate_directory_exists};
 use anyhow::{Context, Result};
 use log::{debug, info, warn};
-use std::collections::HashMap;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock};
+
+/// 檔名結尾的語言代碼（`.en`、`.zh-TW`、`.pt_BR`），代碼需在 [`ISO_639_1_CODES`] 中
+static REGEX_LANGUAGE_CODE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\.(?P<code>[a-z]{2})(?:[-_][A-Za-z]{2,4})?$").expect("Invalid regex")
+});
+
+/// ISO 639-1 語言代碼
+const ISO_639_1_CODES: [&str; 184] = [
+    "aa", "ab", "ae", "af", "ak", "am", "an", "ar", "as", "av", "ay", "az", "ba", "be", "bg", "bh",
+    "bi", "bm", "bn", "bo", "br", "bs", "ca", "ce", "ch", "co", "cr", "cs", "cu", "cv", "cy", "da",
+    "de", "dv", "dz", "ee", "el", "en", "eo", "es", "et", "eu", "fa", "ff", "fi", "fj", "fo", "fr",
+    "fy", "ga", "gd", "gl", "gn", "gu", "gv", "ha", "he", "hi", "ho", "hr", "ht", "hu", "hy", "hz",
+    "ia", "id", "ie", "ig", "ii", "ik", "io", "is", "it", "iu", "ja", "jv", "ka", "kg", "ki", "kj",
+    "kk", "kl", "km", "kn", "ko", "kr", "ks", "ku", "kv", "kw", "ky", "la", "lb", "lg", "li", "ln",
+    "lo", "lt", "lu", "lv", "mg", "mh", "mi", "mk", "ml", "mn", "mr", "ms", "mt", "my", "na", "nb",
+    "nd", "ne", "ng", "nl", "nn", "no", "nr", "nv", "ny", "oc", "oj", "om", "or", "os", "pa", "pi",
+    "pl", "ps", "pt", "qu", "rm", "rn", "ro", "ru", "rw", "sa", "sc", "sd", "se", "sg", "si", "sk",
+    "sl", "sm", "sn", "so", "sq", "sr", "ss", "st", "su", "sv", "sw", "ta", "te", "tg", "th", "ti",
+    "tk", "tl", "tn", "to", "tr", "ts", "tt", "tw", "ty", "ug", "uk", "ur", "uz", "ve", "vi", "vo",
+    "wa", "wo", "xh", "yi", "yo", "za", "zh", "zu",
+];
+
+/// 檔名結尾語言代碼的起始位置（不含只有語言代碼的名稱）
+fn language_code_start(stem: &str) -> Option<usize> {
+    let captures = REGEX_LANGUAGE_CODE.captures(stem)?;
+    let found = captures.get(0)?;
+    (found.start() > 0 && ISO_639_1_CODES.contains(&&captures["code"])).then_some(found.start())
+}
 
 /// 預設的孤立檔案目標資料夾名稱
 pub const DEFAULT_ORPHAN_FOLDER: &str = "orphan_files";
 
+/// 孤立檔案清單的檔名（寫入掃描的資料夾）
+pub const ORPHAN_REPORT_CSV: &str = "orphan_report.csv";
+
+/// 預設一組完整檔案至少需要的檔案數
+pub const DEFAULT_MIN_GROUP_SIZE: usize = 2;
+
 /// 孤立檔案移動結果
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize)]
 pub struct OrphanMoveResult {
     /// 掃描的總檔案數
     pub total_files: usize,
@@ -23,12 +61,89 @@ pub struct OrphanMoveResult {
     pub files_with_pairs: usize,
     /// 孤立檔案數量（已移動）
     pub orphan_files_moved: usize,
+    /// 使用者檢視時取消勾選而保留的孤立檔案數
+    pub kept_by_user: usize,
     /// 跳過的檔案數（已存在於目標目錄）
     pub skipped: usize,
     /// 錯誤數量
     pub errors: usize,
 }
 
+/// 掃描並分組的結果
+#[derive(Debug, Default)]
+pub struct GroupScan {
+    /// 檔案群組
+    pub groups: Vec<FileGroup>,
+    /// 副檔名在忽略清單中、未加入分組的檔案數
+    pub ignored: usize,
+}
+
+/// 檔案配對方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PairingStrategy {
+    /// 去掉最後一個副檔名後完全相同才配對（`movie.mp4` 與 `movie.jpg`）
+    #[default]
+    ExactStem,
+    /// 第一個 `.` 之前的名稱相同即配對（`movie.mp4` 與 `movie.en.srt`）
+    Prefix,
+}
+
+impl PairingStrategy {
+    /// 取得檔案的分組名稱
+    fn group_key(self, path: &Path) -> Option<String> {
+        match self {
+            Self::ExactStem => path.file_stem().map(|s| s.to_string_lossy().to_string()),
+            Self::Prefix => path.file_name().map(|name| {
+                let name = name.to_string_lossy();
+                name.split('.').next().unwrap_or_default().to_string()
+            }),
+        }
+    }
+}
+
+/// 依規則正規化分組名稱
+///
+/// 依序移除前後空白、語言代碼與指定的結尾（可重複出現，例如 `.en-thumb`），最後轉為小寫；
+/// 語言代碼只移除一次，避免 `my.tv.show` 之類的名稱被逐段截短
+#[must_use]
+pub fn normalize_stem(stem: &str, rules: &StemNormalization) -> String {
+    let mut result = if rules.trim_whitespace {
+        stem.trim()
+    } else {
+        stem
+    };
+
+    let mut strip_language_code = rules.strip_language_codes;
+    loop {
+        let before = result.len();
+        if strip_language_code && let Some(start) = language_code_start(result) {
+            result = &result[..start];
+            strip_language_code = false;
+        }
+        for suffix in rules.strip_suffixes.iter().filter(|s| !s.is_empty()) {
+            let suffix = suffix.to_lowercase();
+            if result.len() > suffix.len()
+                && let Some(end) = result.get(result.len() - suffix.len()..)
+                && end.to_lowercase() == suffix
+            {
+                result = &result[..result.len() - suffix.len()];
+            }
+        }
+        if rules.trim_whitespace {
+            result = result.trim();
+        }
+        if result.len() == before {
+            break;
+        }
+    }
+
+    if rules.ignore_case {
+        result.to_lowercase()
+    } else {
+        result.to_string()
+    }
+}
+
 /// 檔案分組資訊
 #[derive(Debug, Clone)]
 pub struct FileGroup {
@@ -39,28 +154,60 @@ pub struct FileGroup {
 }
 
 impl FileGroup {
-    /// 檢查是否為孤立群組（只有一個檔案）
+    /// 檢查是否為孤立群組（檔案數少於 `min_group_size`）
     #[must_use]
-    pub const fn is_orphan(&self) -> bool {
-        self.files.len() == 1
+    pub const fn is_orphan(&self, min_group_size: usize) -> bool {
+        self.files.len() < min_group_size
     }
 
-    /// 取得孤立檔案（如果是孤立群組）
+    /// 群組內檔案的副檔名（小寫）
     #[must_use]
-    pub fn orphan_file(&self) -> Option<&PathBuf> {
-        if self.is_orphan() {
-            self.files.first()
-        } else {
-            None
-        }
+    pub fn extensions(&self) -> Vec<String> {
+        self.files
+            .iter()
+            .filter_map(|p| p.extension())
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .collect()
+    }
+
+    /// 群組內是否有任一檔案的副檔名在清單中（清單須為小寫）
+    fn has_extension(&self, extensions: &[String]) -> bool {
+        self.extensions().iter().any(|ext| extensions.contains(ext))
     }
 }
 
+/// 去掉開頭的 `.` 並轉為小寫，略過空白項目
+fn normalize_extensions<I, S>(extensions: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    extensions
+        .into_iter()
+        .map(|ext| ext.as_ref().trim().trim_start_matches('.').to_lowercase())
+        .filter(|ext| !ext.is_empty())
+        .collect()
+}
+
 /// 檔案分組器
 pub struct FileGrouper {
     shutdown_signal: Arc<AtomicBool>,
     /// 目標資料夾名稱
     orphan_folder_name: String,
+    /// 檔案配對方式
+    pairing_strategy: PairingStrategy,
+    /// 一組完整檔案至少需要的檔案數，少於此數的群組視為孤立
+    min_group_size: usize,
+    /// 群組須包含其中一種副檔名（小寫）才算完整，空白代表不限制
+    required_extensions: Vec<String>,
+    /// 錨點副檔名（小寫），設定後只以是否含有錨點檔案判斷群組是否孤立
+    anchor_extensions: Vec<String>,
+    /// 分組前的檔名正規化規則，`None` 表示不正規化
+    stem_normalization: Option<StemNormalization>,
+    /// 不處理的副檔名（小寫），掃描時直接略過，優先於強制孤立清單
+    ignore_extensions: Vec<String>,
+    /// 一律視為孤立檔案的副檔名（小寫），即使有同名檔案也會移動
+    always_orphan_extensions: Vec<String>,
 }
 
 impl FileGrouper {
@@ -70,7 +217,149 @@ impl FileGrouper {
         Self {
             shutdown_signal,
             orphan_folder_name: DEFAULT_ORPHAN_FOLDER.to_string(),
+            pairing_strategy: PairingStrategy::default(),
+            min_group_size: DEFAULT_MIN_GROUP_SIZE,
+            required_extensions: Vec::new(),
+            anchor_extensions: Vec::new(),
+            stem_normalization: None,
+            ignore_extensions: Vec::new(),
+            always_orphan_extensions: Vec::new(),
+        }
+    }
+
+    /// 設定不處理的副檔名（例如 `part`、`json`、`log`），這些檔案不分組也不移動
+    #[must_use]
+    pub fn with_ignore_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.ignore_extensions = normalize_extensions(extensions);
+        self
+    }
+
+    /// 設定一律視為孤立檔案的副檔名（例如 `tmp`）
+    ///
+    /// 這些檔案不計入群組是否完整的判斷
+    #[must_use]
+    pub fn with_always_orphan_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.always_orphan_extensions = normalize_extensions(extensions);
+        self
+    }
+
+    /// 設定分組前的檔名正規化規則
+    #[must_use]
+    pub fn with_stem_normalization(mut self, rules: StemNormalization) -> Self {
+        self.stem_normalization = Some(rules);
+        self
+    }
+
+    /// 是否套用檔名正規化
+    #[must_use]
+    pub const fn normalizes_stems(&self) -> bool {
+        self.stem_normalization.is_some()
+    }
+
+    /// 設定群組必須包含的副檔名（任一即可），例如只保留含有影片的群組
+    #[must_use]
+    pub fn with_required_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.required_extensions = normalize_extensions(extensions);
+        self
+    }
+
+    /// 設定錨點副檔名（錨點模式）
+    ///
+    /// 含有錨點檔案的群組一律保留（包含單獨的錨點檔案），沒有錨點檔案的群組整組視為孤立，
+    /// 此時不再檢查群組的檔案數與必要副檔名
+    #[must_use]
+    pub fn with_anchor_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.anchor_extensions = normalize_extensions(extensions);
+        self
+    }
+
+    /// 群組是否為孤立群組：錨點模式下缺少錨點檔案，否則為檔案數不足或缺少必要的副檔名
+    ///
+    /// 強制孤立的檔案不計入判斷，群組只剩強制孤立的檔案時視為孤立
+    fn is_orphan_group(&self, group: &FileGroup) -> bool {
+        let group = FileGroup {
+            stem: String::new(),
+            files: group
+                .files
+                .iter()
+                .filter(|path| !self.is_always_orphan(path))
+                .cloned()
+                .collect(),
+        };
+        if group.files.is_empty() {
+            return true;
+        }
+        if !self.anchor_extensions.is_empty() {
+            return !group.has_extension(&self.anchor_extensions);
         }
+        group.is_orphan(self.min_group_size)
+            || (!self.required_extensions.is_empty()
+                && !group.has_extension(&self.required_extensions))
+    }
+
+    /// 檔案的副檔名是否在清單中
+    fn extension_in(path: &Path, extensions: &[String]) -> bool {
+        path.extension()
+            .is_some_and(|ext| extensions.contains(&ext.to_string_lossy().to_lowercase()))
+    }
+
+    /// 檔案是否一律視為孤立檔案
+    fn is_always_orphan(&self, path: &Path) -> bool {
+        Self::extension_in(path, &self.always_orphan_extensions)
+    }
+
+    /// 群組中要移動的檔案：孤立群組的所有檔案，或完整群組中強制孤立的檔案
+    fn orphans_in<'a>(&self, group: &'a FileGroup) -> Vec<&'a PathBuf> {
+        if self.is_orphan_group(group) {
+            group.files.iter().collect()
+        } else {
+            group
+                .files
+                .iter()
+                .filter(|path| self.is_always_orphan(path))
+                .collect()
+        }
+    }
+
+    /// 完整群組中因副檔名而強制視為孤立的檔案數
+    #[must_use]
+    pub fn count_forced_orphans(&self, groups: &[FileGroup]) -> usize {
+        groups
+            .iter()
+            .filter(|g| !self.is_orphan_group(g))
+            .flat_map(|g| &g.files)
+            .filter(|path| self.is_always_orphan(path))
+            .count()
+    }
+
+    /// 設定一組完整檔案至少需要的檔案數（例如影片、預覽圖與字幕為 3）
+    #[must_use]
+    pub const fn with_min_group_size(mut self, min_group_size: usize) -> Self {
+        self.min_group_size = min_group_size;
+        self
+    }
+
+    /// 設定檔案配對方式
+    #[must_use]
+    pub const fn with_pairing_strategy(mut self, strategy: PairingStrategy) -> Self {
+        self.pairing_strategy = strategy;
+        self
     }
 
     /// 設定目標資料夾名稱
@@ -82,11 +371,17 @@ impl FileGrouper {
 
     /// 掃描並分組檔案
     pub fn scan_and_group(&self, directory: &Path) -> Result<Vec<FileGroup>> {
+        self.scan(directory).map(|scan| scan.groups)
+    }
+
+    /// 掃描並分組檔案，同時統計因忽略清單而略過的檔案
+    pub fn scan(&self, directory: &Path) -> Result<GroupScan> {
         validate_directory_exists(directory)?;
 
         info!("開始掃描目錄: {}", directory.display());
 
         let mut groups: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        let mut ignored = 0;
 
         // 讀取目錄中的檔案
         let entries = fs::read_dir(directory)
@@ -121,10 +416,28 @@ impl FileGrouper {
                 continue;
             }
 
-            // 取得檔案名稱（不含副檔名）
-            let stem = match path.file_stem() {
-                Some(s) => s.to_string_lossy().to_string(),
-                None => continue,
+            // 跳過先前匯出的孤立檔案清單
+            if path
+                .file_name()
+                .is_some_and(|name| name == ORPHAN_REPORT_CSV)
+            {
+                continue;
+            }
+
+            // 忽略清單中的檔案不分組也不移動
+            if Self::extension_in(&path, &self.ignore_extensions) {
+                debug!("忽略檔案: {}", path.display());
+                ignored += 1;
+                continue;
+            }
+
+            // 依配對方式取得分組名稱
+            let Some(stem) = self.pairing_strategy.group_key(&path) else {
+                continue;
+            };
+            let stem = match &self.stem_normalization {
+                Some(rules) => normalize_stem(&stem, rules),
+                None => stem,
             };
 
             // 跳過空名稱
@@ -141,9 +454,15 @@ impl FileGrouper {
             .map(|(stem, files)| FileGroup { stem, files })
             .collect();
 
-        info!("掃描完成，找到 {} 個檔案群組", result.len());
+        info!(
+            "掃描完成，找到 {} 個檔案群組，忽略 {ignored} 個檔案",
+            result.len()
+        );
 
-        Ok(result)
+        Ok(GroupScan {
+            groups: result,
+            ignored,
+        })
     }
 
     /// 移動孤立檔案到目標資料夾
@@ -151,6 +470,16 @@ impl FileGrouper {
         &self,
         groups: &[FileGroup],
         base_dir: &Path,
+    ) -> Result<OrphanMoveResult> {
+        self.move_selected_orphans(groups, base_dir, None)
+    }
+
+    /// 只移動 `selected` 中的孤立檔案（`None` 表示全部），其餘孤立檔案計為使用者保留
+    pub fn move_selected_orphans(
+        &self,
+        groups: &[FileGroup],
+        base_dir: &Path,
+        selected: Option<&HashSet<PathBuf>>,
     ) -> Result<OrphanMoveResult> {
         let orphan_dir = base_dir.join(&self.orphan_folder_name);
         ensure_directory_exists(&orphan_dir)?;
@@ -161,6 +490,7 @@ impl FileGrouper {
 
         let mut total_files = 0;
         let mut files_with_pairs = 0;
+        let mut kept_by_user = 0;
 
         for group in groups {
             if self.shutdown_signal.load(Ordering::SeqCst) {
@@ -170,48 +500,41 @@ impl FileGrouper {
 
             total_files += group.files.len();
 
-            if group.is_orphan() {
-                // 孤立檔案，需要移動
-                if let Some(orphan_path) = group.orphan_file() {
-                    let file_name = orphan_path.file_name().unwrap_or_default();
-                    let target_path = orphan_dir.join(file_name);
-
-                    // 檢查目標是否已存在
-                    if target_path.exists() {
-                        debug!("跳過已存在的檔案: {}", target_path.display());
-                        skipped_count.fetch_add(1, Ordering::SeqCst);
-                        continue;
-                    }
+            // 孤立群組的檔案都需要移動，完整群組只移動強制孤立的檔案
+            let orphans = self.orphans_in(group);
+            files_with_pairs += group.files.len() - orphans.len();
+            for orphan_path in orphans {
+                if selected.is_some_and(|selected| !selected.contains(orphan_path)) {
+                    debug!("使用者保留: {}", orphan_path.display());
+                    kept_by_user += 1;
+                    continue;
+                }
 
-                    // 移動檔案
-                    match fs::rename(orphan_path, &target_path) {
-                        Ok(()) => {
-                            debug!(
-                                "移動孤立檔案: {} -> {}",
-                                orphan_path.display(),
-                                target_path.display()
-                            );
-                            moved_count.fetch_add(1, Ordering::SeqCst);
-                        }
-                        Err(e) => {
-                            // 嘗試複製後刪除（跨檔案系統）
-                            if let Err(copy_err) = self.copy_and_delete(orphan_path, &target_path) {
-                                warn!(
-                                    "移動檔案失敗 {}: {} (原始錯誤: {})",
-                                    orphan_path.display(),
-                                    copy_err,
-                                    e
-                                );
-                                error_count.fetch_add(1, Ordering::SeqCst);
-                            } else {
-                                moved_count.fetch_add(1, Ordering::SeqCst);
-                            }
-                        }
+                let file_name = orphan_path.file_name().unwrap_or_default();
+                let target_path = orphan_dir.join(file_name);
+
+                // 檢查目標是否已存在
+                if target_path.exists() {
+                    debug!("跳過已存在的檔案: {}", target_path.display());
+                    skipped_count.fetch_add(1, Ordering::SeqCst);
+                    continue;
+                }
+
+                // 移動檔案（跨檔案系統時複製後刪除）
+                match move_file(orphan_path, &target_path) {
+                    Ok(()) => {
+                        debug!(
+                            "移動孤立檔案: {} -> {}",
+                            orphan_path.display(),
+                            target_path.display()
+                        );
+                        moved_count.fetch_add(1, Ordering::SeqCst);
+                    }
+                    Err(e) => {
+                        warn!("移動檔案失敗 {}: {e:#}", orphan_path.display());
+                        error_count.fetch_add(1, Ordering::SeqCst);
                     }
                 }
-            } else {
-                // 有對應檔案，保留
-                files_with_pairs += group.files.len();
             }
         }
 
@@ -219,38 +542,50 @@ impl FileGrouper {
             total_files,
             files_with_pairs,
             orphan_files_moved: moved_count.load(Ordering::SeqCst),
+            kept_by_user,
             skipped: skipped_count.load(Ordering::SeqCst),
             errors: error_count.load(Ordering::SeqCst),
         })
     }
 
-    /// 複製檔案後刪除原檔案
-    fn copy_and_delete(&self, source: &Path, target: &Path) -> Result<()> {
-        fs::copy(source, target).with_context(|| {
-            format!("複製檔案失敗: {} -> {}", source.display(), target.display())
-        })?;
-
-        fs::remove_file(source).with_context(|| format!("刪除原檔案失敗: {}", source.display()))?;
-
-        Ok(())
+    /// 將孤立檔案清單（路徑、大小、群組名稱）寫入 CSV，回傳寫入的檔案數
+    pub fn write_orphan_report(&self, groups: &[FileGroup], path: &Path) -> Result<usize> {
+        let mut content = String::from("path,size,group\n");
+        let mut count = 0;
+        for group in groups {
+            for file in self.orphans_in(group) {
+                let size = fs::metadata(file).map_or(0, |m| m.len());
+                let _ = writeln!(
+                    content,
+                    "{},{size},{}",
+                    csv_field(&file.to_string_lossy()),
+                    csv_field(&group.stem)
+                );
+                count += 1;
+            }
+        }
+        fs::write(path, content)
+            .with_context(|| format!("無法寫入孤立檔案清單: {}", path.display()))?;
+        Ok(count)
     }
 
     /// 取得孤立檔案列表（不執行移動）
     #[must_use]
-    pub fn get_orphan_files(groups: &[FileGroup]) -> Vec<&PathBuf> {
-        groups.iter().filter_map(|g| g.orphan_file()).collect()
+    pub fn get_orphan_files<'a>(&self, groups: &'a [FileGroup]) -> Vec<&'a PathBuf> {
+        groups.iter().flat_map(|g| self.orphans_in(g)).collect()
     }
 
     /// 取得有對應檔案的群組列表
     #[must_use]
-    pub fn get_paired_groups(groups: &[FileGroup]) -> Vec<&FileGroup> {
-        groups.iter().filter(|g| !g.is_orphan()).collect()
+    pub fn get_paired_groups<'a>(&self, groups: &'a [FileGroup]) -> Vec<&'a FileGroup> {
+        groups.iter().filter(|g| !self.is_orphan_group(g)).collect()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
     use tempfile::TempDir;
 
     fn create_test_grouper() -> FileGrouper {
@@ -264,7 +599,7 @@ mod tests {
             stem: "test".to_string(),
             files: vec![PathBuf::from("/test/test.mp4")],
         };
-        assert!(orphan.is_orphan());
+        assert!(orphan.is_orphan(DEFAULT_MIN_GROUP_SIZE));
 
         let paired = FileGroup {
             stem: "video".to_string(),
@@ -273,7 +608,8 @@ mod tests {
                 PathBuf::from("/test/video.jpg"),
             ],
         };
-        assert!(!paired.is_orphan());
+        assert!(!paired.is_orphan(DEFAULT_MIN_GROUP_SIZE));
+        assert!(paired.is_orphan(3));
     }
 
     #[test]
@@ -297,13 +633,37 @@ mod tests {
 
         assert_eq!(groups.len(), 3); // video1, orphan, multi
 
-        let orphan_files = FileGrouper::get_orphan_files(&groups);
+        let orphan_files = grouper.get_orphan_files(&groups);
         assert_eq!(orphan_files.len(), 1);
 
-        let paired_groups = FileGrouper::get_paired_groups(&groups);
+        let paired_groups = grouper.get_paired_groups(&groups);
         assert_eq!(paired_groups.len(), 2); // video1 和 multi
     }
 
+    #[test]
+    fn test_prefix_pairing() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+
+        fs::write(base_path.join("movie.mp4"), "video").unwrap();
+        fs::write(base_path.join("movie.en.srt"), "subtitle").unwrap();
+        fs::write(base_path.join("movie.poster.jpg"), "poster").unwrap();
+        fs::write(base_path.join("other.mp4"), "video").unwrap();
+
+        // 完全同名才配對時，字幕與海報都是孤立檔案
+        let grouper = create_test_grouper();
+        let groups = grouper.scan_and_group(base_path).unwrap();
+        assert_eq!(grouper.get_orphan_files(&groups).len(), 4);
+
+        let grouper = create_test_grouper().with_pairing_strategy(PairingStrategy::Prefix);
+        let groups = grouper.scan_and_group(base_path).unwrap();
+        let paired = grouper.get_paired_groups(&groups);
+        assert_eq!(paired.len(), 1);
+        assert_eq!(paired[0].stem, "movie");
+        assert_eq!(paired[0].files.len(), 3);
+        assert_eq!(grouper.get_orphan_files(&groups).len(), 1);
+    }
+
     #[test]
     fn test_move_orphan_files() {
         let temp_dir = TempDir::new().unwrap();
@@ -333,6 +693,308 @@ mod tests {
         assert!(base_path.join("orphan_files/orphan2.doc").exists());
     }
 
+    #[test]
+    fn test_min_group_size_moves_incomplete_sets() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+
+        // 完整的一組：影片、預覽圖與字幕
+        fs::write(base_path.join("full.mp4"), "video").unwrap();
+        fs::write(base_path.join("full.jpg"), "thumbnail").unwrap();
+        fs::write(base_path.join("full.srt"), "subtitle").unwrap();
+        // 缺少字幕
+        fs::write(base_path.join("partial.mp4"), "video").unwrap();
+        fs::write(base_path.join("partial.jpg"), "thumbnail").unwrap();
+
+        let grouper = create_test_grouper().with_min_group_size(3);
+        let groups = grouper.scan_and_group(base_path).unwrap();
+        assert_eq!(grouper.get_orphan_files(&groups).len(), 2);
+
+        let result = grouper.move_orphan_files(&groups, base_path).unwrap();
+        assert_eq!(result.total_files, 5);
+        assert_eq!(result.files_with_pairs, 3);
+        assert_eq!(result.orphan_files_moved, 2);
+        assert!(base_path.join("full.srt").exists());
+        assert!(base_path.join("orphan_files/partial.mp4").exists());
+        assert!(base_path.join("orphan_files/partial.jpg").exists());
+    }
+
+    #[test]
+    fn test_required_extensions() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+
+        fs::write(base_path.join("video.MP4"), "video").unwrap();
+        fs::write(base_path.join("video.jpg"), "thumbnail").unwrap();
+        // 沒有影片的群組
+        fs::write(base_path.join("junk.jpg"), "image").unwrap();
+        fs::write(base_path.join("junk.txt"), "text").unwrap();
+
+        let grouper = create_test_grouper().with_required_extensions([".mp4", "mkv"]);
+        let groups = grouper.scan_and_group(base_path).unwrap();
+        let paired = grouper.get_paired_groups(&groups);
+        assert_eq!(paired.len(), 1);
+        assert_eq!(paired[0].stem, "video");
+
+        let result = grouper.move_orphan_files(&groups, base_path).unwrap();
+        assert_eq!(result.orphan_files_moved, 2);
+        assert!(base_path.join("video.jpg").exists());
+        assert!(base_path.join("orphan_files/junk.jpg").exists());
+        assert!(base_path.join("orphan_files/junk.txt").exists());
+    }
+
+    #[test]
+    fn test_anchor_extensions() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+
+        // 影片已刪除，只剩預覽圖
+        fs::write(base_path.join("deleted.jpg"), "thumbnail").unwrap();
+        fs::write(base_path.join("deleted.nfo"), "info").unwrap();
+        // 完整的一組
+        fs::write(base_path.join("video.mkv"), "video").unwrap();
+        fs::write(base_path.join("video.jpg"), "thumbnail").unwrap();
+        // 單獨的影片保留
+        fs::write(base_path.join("lone.MP4"), "video").unwrap();
+
+        let grouper = create_test_grouper()
+            .with_min_group_size(3)
+            .with_anchor_extensions([".mp4", "mkv"]);
+        let groups = grouper.scan_and_group(base_path).unwrap();
+        assert_eq!(grouper.get_paired_groups(&groups).len(), 2);
+
+        let result = grouper.move_orphan_files(&groups, base_path).unwrap();
+        assert_eq!(result.total_files, 5);
+        assert_eq!(result.files_with_pairs, 3);
+        assert_eq!(result.orphan_files_moved, 2);
+        assert!(base_path.join("lone.MP4").exists());
+        assert!(base_path.join("video.jpg").exists());
+        assert!(base_path.join("orphan_files/deleted.jpg").exists());
+        assert!(base_path.join("orphan_files/deleted.nfo").exists());
+    }
+
+    #[test]
+    fn test_any_pairing_moves_lone_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+
+        // 未設定錨點時，單獨的影片與 README 都是孤立檔案
+        fs::write(base_path.join("lone.mp4"), "video").unwrap();
+        fs::write(base_path.join("README.txt"), "readme").unwrap();
+        fs::write(base_path.join("shot.jpg"), "thumbnail").unwrap();
+        fs::write(base_path.join("shot.png"), "thumbnail").unwrap();
+
+        let grouper = create_test_grouper();
+        let groups = grouper.scan_and_group(base_path).unwrap();
+        let orphans = grouper.get_orphan_files(&groups);
+        assert_eq!(orphans.len(), 2);
+        assert!(orphans.iter().any(|p| p.ends_with("lone.mp4")));
+
+        // 錨點模式下沒有影片的同名群組仍是孤立
+        let grouper = create_test_grouper().with_anchor_extensions(["mp4"]);
+        let groups = grouper.scan_and_group(base_path).unwrap();
+        let orphans = grouper.get_orphan_files(&groups);
+        assert_eq!(orphans.len(), 3);
+        assert!(!orphans.iter().any(|p| p.ends_with("lone.mp4")));
+    }
+
+    fn normalization() -> StemNormalization {
+        StemNormalization {
+            enabled: true,
+            ..StemNormalization::default()
+        }
+    }
+
+    #[test]
+    fn test_normalize_stem_strips_suffixes() {
+        let rules = normalization();
+        assert_eq!(normalize_stem("Movie.Name-poster", &rules), "movie.name");
+        assert_eq!(normalize_stem("Movie.Name-FANART", &rules), "movie.name");
+        assert_eq!(normalize_stem("Movie.Name.en-thumb", &rules), "movie.name");
+        // 只有結尾本身時不移除
+        assert_eq!(normalize_stem("-poster", &rules), "-poster");
+    }
+
+    #[test]
+    fn test_normalize_stem_strips_language_codes() {
+        let rules = normalization();
+        assert_eq!(normalize_stem("Movie.Name.en", &rules), "movie.name");
+        assert_eq!(normalize_stem("Movie.Name.zh-TW", &rules), "movie.name");
+        assert_eq!(normalize_stem("Movie.Name.pt_BR", &rules), "movie.name");
+        // 大寫開頭的名稱片段不是語言代碼
+        assert_eq!(normalize_stem("Movie.Up", &rules), "movie.up");
+        // 只移除已知的語言代碼，且只移除一次
+        assert_eq!(normalize_stem("my.tv.show", &rules), "my.tv.show");
+        assert_eq!(normalize_stem("my.tv", &rules), "my.tv");
+        assert_eq!(normalize_stem("Movie.de.en", &rules), "movie.de");
+
+        let rules = StemNormalization {
+            strip_language_codes: false,
+            ..normalization()
+        };
+        assert_eq!(normalize_stem("Movie.Name.en", &rules), "movie.name.en");
+    }
+
+    #[test]
+    fn test_normalize_stem_case_and_whitespace() {
+        let rules = normalization();
+        assert_eq!(normalize_stem("  Movie Name ", &rules), "movie name");
+
+        let rules = StemNormalization {
+            ignore_case: false,
+            trim_whitespace: false,
+            ..normalization()
+        };
+        assert_eq!(normalize_stem(" Movie-Poster", &rules), " Movie");
+    }
+
+    #[test]
+    fn test_stem_normalization_pairs_companions() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+
+        fs::write(base_path.join("Movie.Name.mkv"), "video").unwrap();
+        fs::write(base_path.join("Movie.Name-poster.jpg"), "poster").unwrap();
+        fs::write(base_path.join("movie.name.en.srt"), "subtitle").unwrap();
+        fs::write(base_path.join("Other-fanart.jpg"), "fanart").unwrap();
+
+        // 未正規化時全部都是孤立檔案
+        let grouper = create_test_grouper();
+        let groups = grouper.scan_and_group(base_path).unwrap();
+        assert_eq!(grouper.get_orphan_files(&groups).len(), 4);
+
+        let grouper = create_test_grouper().with_stem_normalization(normalization());
+        let groups = grouper.scan_and_group(base_path).unwrap();
+        let paired = grouper.get_paired_groups(&groups);
+        assert_eq!(paired.len(), 1);
+        assert_eq!(paired[0].stem, "movie.name");
+        assert_eq!(paired[0].files.len(), 3);
+        let orphans = grouper.get_orphan_files(&groups);
+        assert_eq!(orphans.len(), 1);
+        assert!(orphans[0].ends_with("Other-fanart.jpg"));
+    }
+
+    #[test]
+    fn test_ignore_extensions() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+
+        fs::write(base_path.join("deleted.nfo"), "info").unwrap();
+        fs::write(base_path.join("download.part"), "partial").unwrap();
+        fs::write(base_path.join("state.JSON"), "{}").unwrap();
+
+        let grouper = create_test_grouper().with_ignore_extensions(["part", ".json"]);
+        let scan = grouper.scan(base_path).unwrap();
+        assert_eq!(scan.ignored, 2);
+        assert_eq!(scan.groups.len(), 1);
+
+        let result = grouper.move_orphan_files(&scan.groups, base_path).unwrap();
+        assert_eq!(result.total_files, 1);
+        assert_eq!(result.orphan_files_moved, 1);
+        assert!(base_path.join("download.part").exists());
+        assert!(base_path.join("state.JSON").exists());
+        assert!(base_path.join("orphan_files/deleted.nfo").exists());
+    }
+
+    #[test]
+    fn test_always_orphan_extensions() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+
+        fs::write(base_path.join("movie.mp4"), "video").unwrap();
+        fs::write(base_path.join("movie.jpg"), "thumbnail").unwrap();
+        fs::write(base_path.join("movie.tmp"), "temp").unwrap();
+        // 只有暫存檔作為對應時不算完整
+        fs::write(base_path.join("lone.mp4"), "video").unwrap();
+        fs::write(base_path.join("lone.tmp"), "temp").unwrap();
+
+        let grouper = create_test_grouper().with_always_orphan_extensions(["tmp"]);
+        let groups = grouper.scan_and_group(base_path).unwrap();
+        assert_eq!(grouper.get_paired_groups(&groups).len(), 1);
+        assert_eq!(grouper.count_forced_orphans(&groups), 1);
+        assert_eq!(grouper.get_orphan_files(&groups).len(), 3);
+
+        let result = grouper.move_orphan_files(&groups, base_path).unwrap();
+        assert_eq!(result.total_files, 5);
+        assert_eq!(result.files_with_pairs, 2);
+        assert_eq!(result.orphan_files_moved, 3);
+        assert!(base_path.join("movie.mp4").exists());
+        assert!(base_path.join("movie.jpg").exists());
+        assert!(base_path.join("orphan_files/movie.tmp").exists());
+        assert!(base_path.join("orphan_files/lone.mp4").exists());
+    }
+
+    #[test]
+    fn test_ignore_takes_precedence_over_always_orphan() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+
+        fs::write(base_path.join("movie.mp4"), "video").unwrap();
+        fs::write(base_path.join("movie.jpg"), "thumbnail").unwrap();
+        fs::write(base_path.join("movie.log"), "log").unwrap();
+        fs::write(base_path.join("movie.tmp"), "temp").unwrap();
+
+        let grouper = create_test_grouper()
+            .with_ignore_extensions(["log"])
+            .with_always_orphan_extensions(["log", "tmp"]);
+        let scan = grouper.scan(base_path).unwrap();
+        assert_eq!(scan.ignored, 1);
+
+        let result = grouper.move_orphan_files(&scan.groups, base_path).unwrap();
+        assert_eq!(result.orphan_files_moved, 1);
+        assert!(base_path.join("movie.log").exists());
+        assert!(base_path.join("orphan_files/movie.tmp").exists());
+    }
+
+    #[test]
+    fn test_move_selected_orphans() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+
+        fs::write(base_path.join("paired.mp4"), "video").unwrap();
+        fs::write(base_path.join("paired.jpg"), "thumbnail").unwrap();
+        fs::write(base_path.join("orphan1.txt"), "alone1").unwrap();
+        fs::write(base_path.join("orphan2.doc"), "alone2").unwrap();
+
+        let grouper = create_test_grouper();
+        let groups = grouper.scan_and_group(base_path).unwrap();
+        let selected: HashSet<PathBuf> = [base_path.join("orphan1.txt")].into_iter().collect();
+        let result = grouper
+            .move_selected_orphans(&groups, base_path, Some(&selected))
+            .unwrap();
+
+        assert_eq!(result.files_with_pairs, 2);
+        assert_eq!(result.orphan_files_moved, 1);
+        assert_eq!(result.kept_by_user, 1);
+        assert!(base_path.join("orphan_files/orphan1.txt").exists());
+        assert!(base_path.join("orphan2.doc").exists());
+    }
+
+    #[test]
+    fn test_write_orphan_report() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_path = temp_dir.path();
+
+        fs::write(base_path.join("paired.mp4"), "video").unwrap();
+        fs::write(base_path.join("paired.jpg"), "thumbnail").unwrap();
+        fs::write(base_path.join("a, b.srt"), "subtitle").unwrap();
+
+        let grouper = create_test_grouper();
+        let groups = grouper.scan_and_group(base_path).unwrap();
+        let report = base_path.join(ORPHAN_REPORT_CSV);
+        assert_eq!(grouper.write_orphan_report(&groups, &report).unwrap(), 1);
+
+        let csv = fs::read_to_string(&report).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "path,size,group");
+        assert_eq!(
+            lines[1],
+            format!("\"{}\",8,\"a, b\"", base_path.join("a, b.srt").display())
+        );
+        // 寫入清單不移動任何檔案
+        assert!(base_path.join("a, b.srt").exists());
+    }
+
     #[test]
     fn test_skip_hidden_files() {
         let temp_dir = TempDir::new().unwrap();
@@ -365,4 +1027,27 @@ mod tests {
         assert_eq!(result.orphan_files_moved, 1);
         assert!(base_path.join("moved_files/orphan.txt").exists());
     }
+
+    #[test]
+    fn test_result_json_schema() {
+        let result = OrphanMoveResult {
+            total_files: 5,
+            files_with_pairs: 2,
+            orphan_files_moved: 2,
+            kept_by_user: 1,
+            skipped: 0,
+            errors: 0,
+        };
+        assert_eq!(
+            serde_json::to_value(&result).unwrap(),
+            json!({
+                "total_files": 5,
+                "files_with_pairs": 2,
+                "orphan_files_moved": 2,
+                "kept_by_user": 1,
+                "skipped": 0,
+                "errors": 0
+            })
+        );
+    }
 }
@@ -2,23 +2,45 @@
 //!
 //! 掃描資料夾，將沒有對應檔案（同名不同副檔名）的孤立檔案移動到指定目錄
 
-use super::file_grouper::{FileGroup, FileGrouper, OrphanMoveResult};
-use crate::config::Config;
+use super::file_grouper::{
+    DEFAULT_MIN_GROUP_SIZE, FileGroup, FileGrouper, GroupScan, ORPHAN_REPORT_CSV, OrphanMoveResult,
+    PairingStrategy,
+};
+use crate::component::path_prompt::prompt_directory;
+use crate::component::{Interaction, OutputFormat, PreflightFailed, RunStatus, report};
 use crate::config::save::{add_recent_path, save_settings};
-use crate::tools::validate_directory_exists;
-use anyhow::Result;
+use crate::config::{Config, FileCategory, OrphanPairingMode, PathFeature, UserSettings};
+use crate::tools::{format_size, validate_directory_exists};
+use anyhow::{Context, Result};
 use console::style;
 use dialoguer::theme::ColorfulTheme;
-use dialoguer::{Confirm, Input, Select};
+use dialoguer::{Confirm, Input, MultiSelect, Select};
 use log::{info, warn};
+use std::collections::HashSet;
+use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 
+/// 顯示摘要後的處理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OrphanAction {
+    /// 移動所有孤立檔案
+    MoveAll,
+    /// 逐一勾選要移動的孤立檔案
+    Review,
+    /// 只匯出孤立檔案清單
+    ExportReport,
+}
+
 /// 孤立檔案移動元件
 pub struct OrphanFileMover {
     config: Config,
     shutdown_signal: Arc<AtomicBool>,
+    interaction: Interaction,
+    output: OutputFormat,
+    /// 不逐項詢問時只匯出孤立檔案清單
+    report_only: bool,
 }
 
 impl OrphanFileMover {
@@ -26,128 +48,426 @@ impl OrphanFileMover {
         Self {
             config,
             shutdown_signal,
+            interaction: Interaction::Prompt,
+            output: OutputFormat::Human,
+            report_only: false,
         }
     }
 
-    pub fn run(&self) -> Result<()> {
-        println!(
+    #[must_use]
+    pub const fn with_interaction(mut self, interaction: Interaction) -> Self {
+        self.interaction = interaction;
+        self
+    }
+
+    #[must_use]
+    pub fn with_output(mut self, output: OutputFormat) -> Self {
+        self.output = output;
+        self
+    }
+
+    /// 不逐項詢問時只匯出孤立檔案清單，不移動檔案
+    #[must_use]
+    pub const fn with_report_only(mut self, report_only: bool) -> Self {
+        self.report_only = report_only;
+        self
+    }
+
+    pub fn run(&self) -> Result<RunStatus> {
+        report!(
+            self.output,
             "{}",
             style("=== 移動孤立檔案（無對應檔案） ===").cyan().bold()
         );
 
         // 取得輸入路徑
-        let Some(input_path) = self.prompt_input_path()? else {
-            return Ok(()); // ESC pressed
+        let mut settings = self.config.settings.clone();
+        let Some(input_path) = self.prompt_input_path(&mut settings)? else {
+            return Ok(RunStatus::Completed); // ESC pressed
         };
         let directory = PathBuf::from(&input_path);
 
         validate_directory_exists(&directory)?;
 
-        // 更新路徑歷史並儲存
-        {
-            let mut settings = self.config.settings.clone();
-            add_recent_path(&mut settings, &input_path);
-            if let Err(e) = save_settings(&settings) {
-                warn!("無法儲存路徑歷史: {e}");
-            }
+        // 路徑歷史與本次變更的選項一併儲存
+        add_recent_path(&mut settings, PathFeature::OrphanFiles, &input_path);
+        let status = self.move_orphans_in(&directory, &mut settings);
+        if let Err(e) = save_settings(&settings) {
+            warn!("無法儲存設定: {e}");
         }
+        status
+    }
+
+    /// 不詢問路徑與選項，依設定檔處理指定資料夾的孤立檔案（命令列執行）
+    pub fn run_in(&self, directory: &Path) -> Result<RunStatus> {
+        report!(
+            self.output,
+            "{}",
+            style("=== 移動孤立檔案（無對應檔案） ===").cyan().bold()
+        );
+        validate_directory_exists(directory).context(PreflightFailed)?;
+        self.move_orphans_in(directory, &mut self.config.settings.clone())
+    }
+
+    /// 詢問選項並移動孤立檔案，選項的變更寫入 `settings`，由呼叫端儲存
+    fn move_orphans_in(&self, directory: &Path, settings: &mut UserSettings) -> Result<RunStatus> {
+        let Some(pairing_strategy) = self.prompt_pairing_strategy()? else {
+            return Ok(RunStatus::Completed);
+        };
+
+        let normalize_stems = self.prompt_stem_normalization()?;
+        let (ignore_extensions, always_orphan_extensions) =
+            self.prompt_extension_lists(settings)?;
+
+        let Some(pairing_mode) = self.prompt_pairing_mode()? else {
+            return Ok(RunStatus::Completed);
+        };
 
         // 建立分組器
-        let grouper = FileGrouper::new(Arc::clone(&self.shutdown_signal));
+        let mut grouper = FileGrouper::new(Arc::clone(&self.shutdown_signal))
+            .with_pairing_strategy(pairing_strategy)
+            .with_ignore_extensions(ignore_extensions)
+            .with_always_orphan_extensions(always_orphan_extensions);
+        if normalize_stems {
+            grouper = grouper
+                .with_stem_normalization(self.config.settings.orphan.stem_normalization.clone());
+        }
+        let grouper = match pairing_mode {
+            OrphanPairingMode::AnyPairing => grouper
+                .with_min_group_size(self.prompt_min_group_size()?)
+                .with_required_extensions(self.prompt_required_extensions()?),
+            OrphanPairingMode::Anchor => {
+                grouper.with_anchor_extensions(self.prompt_anchor_extensions()?)
+            }
+        };
 
         // 掃描並分組
-        println!("{}", style("掃描檔案中...").dim());
-        let groups = grouper.scan_and_group(&directory)?;
+        report!(self.output, "{}", style("掃描檔案中...").dim());
+        let GroupScan { groups, ignored } = grouper.scan(directory)?;
 
         if groups.is_empty() {
-            println!("{}", style("找不到任何檔案").yellow());
-            return Ok(());
+            report!(self.output, "{}", style("找不到任何檔案").yellow());
+            return Ok(RunStatus::Completed);
         }
 
         // 顯示分組摘要
-        self.print_group_summary(&groups);
+        self.print_group_summary(&grouper, &groups, ignored);
+
+        let Some(action) = self.prompt_action()? else {
+            report!(self.output, "{}", style("操作已取消").yellow());
+            return Ok(RunStatus::Completed);
+        };
+
+        let selected = match action {
+            OrphanAction::ExportReport => {
+                let report_path = directory.join(ORPHAN_REPORT_CSV);
+                let count = grouper.write_orphan_report(&groups, &report_path)?;
+                report!(
+                    self.output,
+                    "{}",
+                    style(format!(
+                        "已將 {count} 個孤立檔案寫入 {}（未移動任何檔案）",
+                        report_path.display()
+                    ))
+                    .green()
+                );
+                return Ok(RunStatus::Completed);
+            }
+            OrphanAction::Review => {
+                let Some(selected) = self.review_orphans(&grouper, &groups)? else {
+                    report!(self.output, "{}", style("操作已取消").yellow());
+                    return Ok(RunStatus::Completed);
+                };
+                Some(selected)
+            }
+            OrphanAction::MoveAll => None,
+        };
 
         // 確認是否執行
         if !self.confirm_move()? {
-            println!("{}", style("操作已取消").yellow());
-            return Ok(());
+            report!(self.output, "{}", style("操作已取消").yellow());
+            return Ok(RunStatus::Completed);
         }
 
         // 檢查中斷訊號
         if self.shutdown_signal.load(Ordering::SeqCst) {
             warn!("收到中斷訊號，停止處理");
-            return Ok(());
+            return Ok(RunStatus::Aborted);
         }
 
         // 移動孤立檔案
-        println!("{}", style("移動孤立檔案中...").cyan());
-        let result = grouper.move_orphan_files(&groups, &directory)?;
+        report!(self.output, "{}", style("移動孤立檔案中...").cyan());
+        let result = grouper.move_selected_orphans(&groups, directory, selected.as_ref())?;
 
         self.print_result(&result);
 
-        Ok(())
+        Ok(RunStatus::from_failures(result.errors))
     }
 
-    fn prompt_input_path(&self) -> Result<Option<String>> {
-        let recent_paths = &self.config.settings.recent_paths;
+    fn prompt_input_path(&self, settings: &mut UserSettings) -> Result<Option<String>> {
+        prompt_directory(
+            settings,
+            PathFeature::OrphanFiles,
+            "請輸入要處理的資料夾路徑",
+            &self.output,
+        )
+    }
+
+    fn prompt_pairing_strategy(&self) -> Result<Option<PairingStrategy>> {
+        if !self.interaction.prompts_options() {
+            return Ok(Some(PairingStrategy::ExactStem));
+        }
+        let options = [
+            "完全同名（movie.mp4 ↔ movie.jpg）",
+            "名稱開頭相同（movie.mp4 ↔ movie.en.srt）",
+        ];
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("如何判斷檔案是否有對應？")
+            .items(options)
+            .default(0)
+            .interact_opt()?;
+
+        Ok(selection.map(|idx| {
+            if idx == 0 {
+                PairingStrategy::ExactStem
+            } else {
+                PairingStrategy::Prefix
+            }
+        }))
+    }
 
-        // 如果沒有歷史路徑，直接輸入
-        if recent_paths.is_empty() {
-            let path: String = Input::new()
-                .with_prompt("請輸入要處理的資料夾路徑")
-                .interact_text()?;
-            return Ok(Some(path.trim().to_string()));
+    /// 顯示設定檔中的正規化規則並詢問是否套用
+    fn prompt_stem_normalization(&self) -> Result<bool> {
+        let rules = &self.config.settings.orphan.stem_normalization;
+        let mut applied = Vec::new();
+        if !rules.strip_suffixes.is_empty() {
+            applied.push(format!("移除結尾 {}", rules.strip_suffixes.join(" ")));
         }
+        if rules.strip_language_codes {
+            applied.push("移除語言代碼（.en、.zh-TW）".to_string());
+        }
+        if rules.ignore_case {
+            applied.push("不分大小寫".to_string());
+        }
+        if rules.trim_whitespace {
+            applied.push("移除前後空白".to_string());
+        }
+        if applied.is_empty() {
+            return Ok(false);
+        }
+        if !self.interaction.prompts_options() {
+            return Ok(rules.enabled);
+        }
+
+        report!(
+            self.output,
+            "{}",
+            style(format!("正規化規則：{}", applied.join("、"))).dim()
+        );
+        let confirm = Confirm::new()
+            .with_prompt("分組前是否先正規化檔名？")
+            .default(rules.enabled)
+            .interact()?;
+        Ok(confirm)
+    }
 
-        // 建立選項清單：歷史路徑 + 輸入新路徑
-        let mut options: Vec<String> = recent_paths
+    /// 輸入忽略與強制孤立的副檔名清單，變更時寫回設定
+    fn prompt_extension_lists(
+        &self,
+        settings: &mut UserSettings,
+    ) -> Result<(Vec<String>, Vec<String>)> {
+        let orphan = &settings.orphan;
+        if !self.interaction.prompts_options() {
+            return Ok((
+                orphan.ignore_extensions.clone(),
+                orphan.always_orphan_extensions.clone(),
+            ));
+        }
+        let ignore = prompt_extension_list(
+            "不處理的副檔名（以逗號分隔，例如 part,json,log；留空則不限制）",
+            &orphan.ignore_extensions,
+        )?;
+        let always_orphan = prompt_extension_list(
+            "一律視為孤立檔案的副檔名（以逗號分隔，例如 tmp；留空則不限制）",
+            &orphan.always_orphan_extensions,
+        )?;
+
+        settings.orphan.ignore_extensions.clone_from(&ignore);
+        settings
+            .orphan
+            .always_orphan_extensions
+            .clone_from(&always_orphan);
+        Ok((ignore, always_orphan))
+    }
+
+    fn prompt_pairing_mode(&self) -> Result<Option<OrphanPairingMode>> {
+        if !self.interaction.prompts_options() {
+            return Ok(Some(self.config.settings.orphan.pairing_mode));
+        }
+        let modes = [OrphanPairingMode::AnyPairing, OrphanPairingMode::Anchor];
+        let default = modes
             .iter()
-            .enumerate()
-            .map(|(i, p)| {
-                let exists = Path::new(p).exists();
-                let indicator = if exists { "✓" } else { "✗" };
-                format!("{} [{}] {}", i + 1, indicator, p)
+            .position(|m| *m == self.config.settings.orphan.pairing_mode)
+            .unwrap_or(0);
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("如何判斷群組是否完整？")
+            .items(modes)
+            .default(default)
+            .interact_opt()?;
+        Ok(selection.map(|idx| modes[idx]))
+    }
+
+    /// 詢問錨點副檔名，預設為設定檔中的清單，未設定時為檔案類型表中的影片副檔名
+    fn prompt_anchor_extensions(&self) -> Result<Vec<String>> {
+        let configured = &self.config.settings.orphan.anchor_extensions;
+        let mut defaults: Vec<String> = if configured.is_empty() {
+            self.config
+                .file_type_table
+                .extensions_for_category(FileCategory::Video)
+                .into_iter()
+                .collect()
+        } else {
+            configured.clone()
+        };
+        defaults.sort();
+        if !self.interaction.prompts_options() {
+            return Ok(defaults);
+        }
+        let default_text = defaults
+            .iter()
+            .map(|ext| ext.trim_start_matches('.'))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let input: String = Input::new()
+            .with_prompt("錨點副檔名（以逗號分隔，沒有錨點檔案的群組會被移動）")
+            .default(default_text)
+            .validate_with(|input: &String| {
+                if input.split(',').any(|ext| !ext.trim().is_empty()) {
+                    Ok(())
+                } else {
+                    Err("至少需要一個副檔名")
+                }
             })
-            .collect();
-        options.push("輸入新路徑...".to_string());
+            .interact_text()?;
+        Ok(input
+            .split(',')
+            .map(str::trim)
+            .filter(|ext| !ext.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn prompt_min_group_size(&self) -> Result<usize> {
+        if !self.interaction.prompts_options() {
+            return Ok(DEFAULT_MIN_GROUP_SIZE);
+        }
+        let size: usize = Input::new()
+            .with_prompt("一組完整檔案至少需要幾個檔案？（少於此數的群組會被移動）")
+            .default(DEFAULT_MIN_GROUP_SIZE)
+            .validate_with(|n: &usize| {
+                if *n >= 2 {
+                    Ok(())
+                } else {
+                    Err("至少需要 2 個檔案")
+                }
+            })
+            .interact_text()?;
+        Ok(size)
+    }
 
-        println!("{}", style("(按 ESC 返回主選單)").dim());
+    fn prompt_required_extensions(&self) -> Result<Vec<String>> {
+        if !self.interaction.prompts_options() {
+            return Ok(Vec::new());
+        }
+        let input: String = Input::new()
+            .with_prompt("群組必須包含的副檔名（以逗號分隔，例如 mp4,mkv；留空則不限制）")
+            .allow_empty(true)
+            .interact_text()?;
+        Ok(input
+            .split(',')
+            .map(str::trim)
+            .filter(|ext| !ext.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
 
+    fn prompt_action(&self) -> Result<Option<OrphanAction>> {
+        if !self.interaction.prompts_options() {
+            return Ok(Some(if self.report_only {
+                OrphanAction::ExportReport
+            } else {
+                OrphanAction::MoveAll
+            }));
+        }
+        let actions = [
+            OrphanAction::MoveAll,
+            OrphanAction::Review,
+            OrphanAction::ExportReport,
+        ];
+        let options = [
+            "移動所有孤立檔案".to_string(),
+            "逐一檢視，取消勾選要保留的檔案".to_string(),
+            format!("只匯出完整清單到 {ORPHAN_REPORT_CSV}（不移動）"),
+        ];
         let selection = Select::with_theme(&ColorfulTheme::default())
-            .with_prompt("請選擇路徑")
+            .with_prompt("請選擇處理方式")
             .items(&options)
             .default(0)
             .interact_opt()?;
+        Ok(selection.map(|idx| actions[idx]))
+    }
 
-        match selection {
-            None => Ok(None),
-            Some(idx) if idx < recent_paths.len() => Ok(Some(recent_paths[idx].clone())),
-            Some(_) => {
-                let path: String = Input::new()
-                    .with_prompt("請輸入要處理的資料夾路徑")
-                    .interact_text()?;
-                Ok(Some(path.trim().to_string()))
-            }
-        }
+    /// 以勾選清單檢視孤立檔案，回傳要移動的檔案（ESC 取消時為 `None`）
+    fn review_orphans(
+        &self,
+        grouper: &FileGrouper,
+        groups: &[FileGroup],
+    ) -> Result<Option<HashSet<PathBuf>>> {
+        let orphans = grouper.get_orphan_files(groups);
+        let items: Vec<String> = orphans
+            .iter()
+            .map(|path| {
+                let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                let size = fs::metadata(path).map_or(0, |m| m.len());
+                format!("{file_name} ({})", format_size(size))
+            })
+            .collect();
+        let defaults = vec![true; items.len()];
+
+        report!(
+            self.output,
+            "{}",
+            style("空白鍵切換勾選，Enter 確認，ESC 取消").dim()
+        );
+        let selection = MultiSelect::with_theme(&ColorfulTheme::default())
+            .with_prompt("勾選要移動的孤立檔案")
+            .items(&items)
+            .defaults(&defaults)
+            .interact_opt()?;
+
+        Ok(selection.map(|indices| {
+            indices
+                .into_iter()
+                .map(|idx| orphans[idx].clone())
+                .collect()
+        }))
     }
 
     fn confirm_move(&self) -> Result<bool> {
-        let confirm = Confirm::new()
-            .with_prompt("確定要移動孤立檔案嗎？")
-            .default(true)
-            .interact()?;
-        Ok(confirm)
+        self.interaction.confirm("確定要移動孤立檔案嗎？", true)
     }
 
-    fn print_group_summary(&self, groups: &[FileGroup]) {
-        let orphan_files = FileGrouper::get_orphan_files(groups);
-        let paired_groups = FileGrouper::get_paired_groups(groups);
+    fn print_group_summary(&self, grouper: &FileGrouper, groups: &[FileGroup], ignored: usize) {
+        let orphan_files = grouper.get_orphan_files(groups);
+        let paired_groups = grouper.get_paired_groups(groups);
 
         let total_files: usize = groups.iter().map(|g| g.files.len()).sum();
         let paired_files: usize = paired_groups.iter().map(|g| g.files.len()).sum();
 
-        println!();
-        println!(
+        report!(self.output);
+        report!(
+            self.output,
             "{}",
             style(format!(
                 "掃描到 {} 個檔案，{} 個群組",
@@ -156,11 +476,30 @@ impl OrphanFileMover {
             ))
             .green()
         );
-        println!();
+        if ignored > 0 {
+            report!(
+                self.output,
+                "{}",
+                style(format!("忽略 {ignored} 個檔案（不處理的副檔名）")).dim()
+            );
+        }
+        let forced = grouper.count_forced_orphans(groups);
+        if forced > 0 {
+            report!(
+                self.output,
+                "{}",
+                style(format!(
+                    "{forced} 個檔案雖有對應檔案，仍因副檔名視為孤立檔案"
+                ))
+                .dim()
+            );
+        }
+        report!(self.output);
 
         // 顯示有對應檔案的群組
         if !paired_groups.is_empty() {
-            println!(
+            report!(
+                self.output,
                 "{}",
                 style(format!(
                     "有對應檔案的群組（保留） - {} 組，{} 個檔案：",
@@ -173,35 +512,39 @@ impl OrphanFileMover {
             // 只顯示前 10 個
             let display_count = paired_groups.len().min(10);
             for group in paired_groups.iter().take(display_count) {
-                let extensions: Vec<String> = group
-                    .files
-                    .iter()
-                    .filter_map(|p| p.extension())
-                    .map(|e| e.to_string_lossy().to_string())
-                    .collect();
-                println!(
+                report!(
+                    self.output,
                     "  {} {} ({})",
                     style("✓").green(),
                     group.stem,
-                    extensions.join(", ")
+                    group.extensions().join(", ")
                 );
+                // 正規化後的名稱與原檔名不同，列出原檔名以確認配對
+                if grouper.normalizes_stems() {
+                    for file in &group.files {
+                        let file_name = file.file_name().unwrap_or_default().to_string_lossy();
+                        report!(self.output, "      {}", style(file_name).dim());
+                    }
+                }
             }
             if paired_groups.len() > display_count {
-                println!(
+                report!(
+                    self.output,
                     "  {} ...還有 {} 組",
                     style("⋯").dim(),
                     paired_groups.len() - display_count
                 );
             }
-            println!();
+            report!(self.output);
         }
 
         // 顯示孤立檔案
         if orphan_files.is_empty() {
-            println!("{}", style("沒有發現孤立檔案").green());
-            println!();
+            report!(self.output, "{}", style("沒有發現孤立檔案").green());
+            report!(self.output);
         } else {
-            println!(
+            report!(
+                self.output,
                 "{}",
                 style(format!("孤立檔案（將移動） - {} 個：", orphan_files.len())).yellow()
             );
@@ -213,43 +556,81 @@ impl OrphanFileMover {
                     .file_name()
                     .map(|n| n.to_string_lossy().to_string())
                     .unwrap_or_default();
-                println!("  {} {}", style("→").yellow(), file_name);
+                report!(self.output, "  {} {}", style("→").yellow(), file_name);
             }
             if orphan_files.len() > display_count {
-                println!(
+                report!(
+                    self.output,
                     "  {} ...還有 {} 個",
                     style("⋯").dim(),
                     orphan_files.len() - display_count
                 );
             }
-            println!();
+            report!(self.output);
         }
     }
 
     fn print_result(&self, result: &OrphanMoveResult) {
-        println!();
-        println!("{}", style("=== 處理結果 ===").cyan().bold());
-        println!("  總檔案數: {}", result.total_files);
-        println!(
+        if self.output.emit_json(result) {
+            return;
+        }
+        report!(self.output);
+        report!(self.output, "{}", style("=== 處理結果 ===").cyan().bold());
+        report!(self.output, "  總檔案數: {}", result.total_files);
+        report!(
+            self.output,
             "  有對應檔案（保留）: {} 個",
             style(result.files_with_pairs).green()
         );
-        println!(
+        report!(
+            self.output,
             "  孤立檔案（已移動）: {} 個",
             style(result.orphan_files_moved).yellow()
         );
 
+        if result.kept_by_user > 0 {
+            report!(
+                self.output,
+                "  使用者保留（取消勾選）: {} 個",
+                style(result.kept_by_user).cyan()
+            );
+        }
+
         if result.skipped > 0 {
-            println!("  已跳過（目標已存在）: {} 個", style(result.skipped).dim());
+            report!(
+                self.output,
+                "  已跳過（目標已存在）: {} 個",
+                style(result.skipped).dim()
+            );
         }
 
         if result.errors > 0 {
-            println!("  失敗: {} 個", style(result.errors).red());
+            report!(self.output, "  失敗: {} 個", style(result.errors).red());
         }
 
         info!(
-            "孤立檔案處理完成 - 保留: {}, 移動: {}, 跳過: {}, 失敗: {}",
-            result.files_with_pairs, result.orphan_files_moved, result.skipped, result.errors
+            "孤立檔案處理完成 - 保留: {}, 移動: {}, 使用者保留: {}, 跳過: {}, 失敗: {}",
+            result.files_with_pairs,
+            result.orphan_files_moved,
+            result.kept_by_user,
+            result.skipped,
+            result.errors
         );
+        self.output.report_log_file();
     }
 }
+
+/// 以逗號分隔的副檔名清單，預填目前的設定
+fn prompt_extension_list(prompt: &str, current: &[String]) -> Result<Vec<String>> {
+    let input: String = Input::new()
+        .with_prompt(prompt)
+        .with_initial_text(current.join(","))
+        .allow_empty(true)
+        .interact_text()?;
+    Ok(input
+        .split(',')
+        .map(str::trim)
+        .filter(|ext| !ext.is_empty())
+        .map(str::to_string)
+        .collect())
+}
@@ -6,5 +6,8 @@
 mod file_grouper;
 mod main;
 
-pub use file_grouper::{FileGroup, FileGrouper, OrphanMoveResult};
+pub use file_grouper::{
+    FileGroup, FileGrouper, GroupScan, ORPHAN_REPORT_CSV, OrphanMoveResult, PairingStrategy,
+    normalize_stem,
+};
 pub use main::OrphanFileMover;
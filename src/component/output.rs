@@ -0,0 +1,157 @@
+//! 結果的輸出方式
+//!
+//! 預設顯示彩色摘要；JSON 模式改為將結果結構以一行 JSON 寫到 stdout 或檔案，方便腳本解析；
+//! 收集模式將結果交給呼叫端（批次工作彙整各步驟的摘要）。
+//! 各元件的畫面輸出都經由 [`report!`] 決定是否顯示，JSON 模式下 stdout 只有結果
+
+use crate::logging;
+use console::style;
+use log::warn;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// 結果的輸出方式
+#[derive(Debug, Clone, Default)]
+pub enum OutputFormat {
+    /// 彩色的人類可讀摘要
+    #[default]
+    Human,
+    /// 一行 JSON 寫到 stdout
+    Json,
+    /// JSON 寫入檔案（覆寫既有內容）
+    JsonFile(PathBuf),
+    /// 將結果收集到 `sink`；`quiet` 時略過人類可讀的摘要
+    Collect { sink: ResultSink, quiet: bool },
+}
+
+impl OutputFormat {
+    /// 是否顯示人類可讀的摘要與裝飾輸出
+    #[must_use]
+    pub const fn is_human(&self) -> bool {
+        match self {
+            Self::Human => true,
+            Self::Json | Self::JsonFile(_) => false,
+            Self::Collect { quiet, .. } => !*quiet,
+        }
+    }
+
+    /// 輸出一行人類可讀的文字；JSON 與安靜的收集模式下略過，讓 stdout 只有結果 JSON
+    pub fn line(&self, text: fmt::Arguments<'_>) {
+        if self.is_human() {
+            println!("{text}");
+        }
+    }
+
+    /// 在摘要最後提示日誌檔位置（只在彩色摘要模式顯示）
+    pub fn report_log_file(&self) {
+        if let (Self::Human, Some(path)) = (self, logging::active_log_file()) {
+            println!("{}", style(format!("詳細紀錄: {}", path.display())).dim());
+        }
+    }
+
+    /// 交出結果：JSON 模式寫到 stdout 或檔案，收集模式存入 sink；
+    /// 回傳 `true` 時呼叫端略過人類可讀的摘要
+    pub fn emit_json<T: Serialize>(&self, result: &T) -> bool {
+        match self {
+            Self::Human => false,
+            Self::Json => {
+                match serde_json::to_string(result) {
+                    Ok(json) => println!("{json}"),
+                    Err(e) => warn!("無法輸出 JSON 結果: {e}"),
+                }
+                true
+            }
+            Self::JsonFile(path) => {
+                let written = serde_json::to_string_pretty(result)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|json| fs::write(path, json).map_err(anyhow::Error::from));
+                if let Err(e) = written {
+                    warn!("無法寫入 JSON 結果 {}: {e}", path.display());
+                }
+                true
+            }
+            Self::Collect { sink, quiet } => {
+                match serde_json::to_value(result) {
+                    Ok(value) => sink.push(value),
+                    Err(e) => warn!("無法收集 JSON 結果: {e}"),
+                }
+                *quiet
+            }
+        }
+    }
+}
+
+/// 以 `println!` 的語法透過 [`OutputFormat::line`] 輸出，各元件的畫面輸出都經由此巨集
+macro_rules! report {
+    ($output:expr) => {
+        $output.line(format_args!(""))
+    };
+    ($output:expr, $($arg:tt)*) => {
+        $output.line(format_args!($($arg)*))
+    };
+}
+pub(crate) use report;
+
+/// 收集模式下存放各元件結果的共用容器
+#[derive(Debug, Clone, Default)]
+pub struct ResultSink(Arc<Mutex<Vec<Value>>>);
+
+impl ResultSink {
+    fn push(&self, value: Value) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(value);
+    }
+
+    /// 取出目前收集到的結果並清空；只有一筆時直接回傳該筆
+    #[must_use]
+    pub fn take(&self) -> Option<Value> {
+        let mut values =
+            std::mem::take(&mut *self.0.lock().unwrap_or_else(PoisonError::into_inner));
+        match values.len() {
+            0 => None,
+            1 => values.pop(),
+            _ => Some(Value::Array(values)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_collect_output() {
+        let sink = ResultSink::default();
+        let output = OutputFormat::Collect {
+            sink: sink.clone(),
+            quiet: false,
+        };
+        assert!(output.is_human());
+        assert!(!output.emit_json(&json!({ "moved": 1 })));
+        assert_eq!(sink.take(), Some(json!({ "moved": 1 })));
+        assert_eq!(sink.take(), None);
+
+        output.emit_json(&1);
+        output.emit_json(&2);
+        assert_eq!(sink.take(), Some(json!([1, 2])));
+    }
+
+    #[test]
+    fn test_json_file_output() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("result.json");
+        let output = OutputFormat::JsonFile(path.clone());
+        assert!(!output.is_human());
+        assert!(output.emit_json(&json!({ "moved": 1 })));
+
+        let written: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written, json!({ "moved": 1 }));
+    }
+}
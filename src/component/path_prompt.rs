@@ -0,0 +1,208 @@
+//! 各元件共用的資料夾路徑提示
+//!
+//! 列出功能自己的釘選與最近使用路徑，並可在選單中釘選或移除路徑。
+//! 輸入的路徑會展開 `~` 與環境變數並立即檢查，無效時顯示錯誤後重新詢問，不會中斷整個流程
+
+use super::{OutputFormat, report};
+use crate::config::save::save_settings;
+use crate::config::{PathFeature, UserSettings};
+use crate::tools::{expand_path, validate_directory_exists};
+use anyhow::Result;
+use console::style;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{Input, MultiSelect, Select};
+use log::warn;
+use std::path::PathBuf;
+
+/// 從功能的路徑清單選擇資料夾，或輸入新路徑；按 ESC（或輸入留空）時回傳 `None`
+///
+/// 回傳展開後且確認存在的資料夾路徑。在選單中釘選或移除的路徑會立即寫回 `settings` 並儲存
+pub fn prompt_directory(
+    settings: &mut UserSettings,
+    feature: PathFeature,
+    input_prompt: &str,
+    output: &OutputFormat,
+) -> Result<Option<String>> {
+    loop {
+        let list = settings.recent_path_list(feature);
+        // 如果沒有歷史路徑，直接輸入
+        if list.is_empty() {
+            return prompt_new_directory(input_prompt, output).map(|dir| dir.map(path_string));
+        }
+
+        let entries: Vec<String> = list.entries().map(str::to_string).collect();
+        let mut options: Vec<String> = entries
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let indicator = if expand_path(p).exists() {
+                    "✓"
+                } else {
+                    "✗"
+                };
+                let pin = if list.is_pinned(p) { "★ " } else { "" };
+                format!("{} [{indicator}] {pin}{p}", i + 1)
+            })
+            .collect();
+        options.push("輸入新路徑...".to_string());
+        options.push("管理路徑（釘選、移除）...".to_string());
+
+        report!(output, "{}", style("(按 ESC 返回主選單)").dim());
+
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("請選擇路徑")
+            .items(&options)
+            .default(0)
+            .interact_opt()?;
+
+        match selection {
+            None => return Ok(None),
+            Some(idx) if idx < entries.len() => {
+                let directory = expand_path(&entries[idx]);
+                match validate_directory_exists(&directory) {
+                    Ok(()) => return Ok(Some(path_string(directory))),
+                    // 歷史路徑已失效，回到清單重新選擇
+                    Err(e) => report!(output, "{}", style(e).red()),
+                }
+            }
+            Some(idx) if idx == entries.len() => {
+                if let Some(directory) = prompt_new_directory(input_prompt, output)? {
+                    return Ok(Some(path_string(directory)));
+                }
+            }
+            Some(_) => {
+                if manage_paths(settings, feature, &entries, output)?
+                    && let Err(e) = save_settings(settings)
+                {
+                    warn!("無法儲存路徑歷史: {e}");
+                }
+            }
+        }
+    }
+}
+
+/// 輸入資料夾路徑，路徑無效時顯示錯誤並重新輸入；留空時回傳 `None`
+pub fn prompt_new_directory(prompt: &str, output: &OutputFormat) -> Result<Option<PathBuf>> {
+    report!(output, "{}", style("(可使用 ~ 與 $VAR，留空返回)").dim());
+    let input: String = Input::new()
+        .with_prompt(prompt)
+        .allow_empty(true)
+        .validate_with(|input: &String| -> Result<(), String> {
+            let input = input.trim();
+            if input.is_empty() {
+                return Ok(());
+            }
+            validate_directory_exists(&expand_path(input)).map_err(|e| e.to_string())
+        })
+        .interact_text()?;
+    let input = input.trim();
+    Ok((!input.is_empty()).then(|| expand_path(input)))
+}
+
+/// 輸入輸出用的資料夾路徑（不存在時由呼叫端建立），留空時回傳 `None`
+///
+/// 路徑同樣展開 `~` 與環境變數；已存在但不是資料夾時顯示錯誤並重新輸入
+pub fn prompt_output_directory(
+    prompt: &str,
+    initial: &str,
+    output: &OutputFormat,
+) -> Result<Option<PathBuf>> {
+    report!(output, "{}", style("(可使用 ~ 與 $VAR)").dim());
+    let input: String = Input::new()
+        .with_prompt(prompt)
+        .with_initial_text(initial)
+        .allow_empty(true)
+        .validate_with(|input: &String| -> Result<(), String> {
+            let path = expand_path(input.trim());
+            if path.exists() && !path.is_dir() {
+                return Err(format!("路徑不是資料夾: {}", path.display()));
+            }
+            Ok(())
+        })
+        .interact_text()?;
+    let input = input.trim();
+    Ok((!input.is_empty()).then(|| expand_path(input)))
+}
+
+/// 使用者輸入的路徑都來自 UTF-8 字串，轉回字串不會遺失內容
+fn path_string(path: PathBuf) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// 釘選或移除路徑，回傳清單是否有變更
+fn manage_paths(
+    settings: &mut UserSettings,
+    feature: PathFeature,
+    entries: &[String],
+    output: &OutputFormat,
+) -> Result<bool> {
+    let actions = ["釘選 / 取消釘選", "移除路徑", "返回"];
+    let action = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("管理路徑")
+        .items(actions)
+        .default(0)
+        .interact_opt()?;
+    if !matches!(action, Some(0 | 1)) {
+        return Ok(false);
+    }
+
+    report!(
+        output,
+        "{}",
+        style("空白鍵切換勾選，Enter 確認，ESC 取消").dim()
+    );
+    if action == Some(0) {
+        pin_paths(settings, feature, entries)
+    } else {
+        remove_paths(settings, feature, entries)
+    }
+}
+
+fn pin_paths(
+    settings: &mut UserSettings,
+    feature: PathFeature,
+    entries: &[String],
+) -> Result<bool> {
+    let list = settings.recent_path_list(feature);
+    let defaults: Vec<bool> = entries.iter().map(|p| list.is_pinned(p)).collect();
+    let Some(selected) = MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("勾選要釘選的路徑（釘選的路徑不會因數量上限被移除）")
+        .items(entries)
+        .defaults(&defaults)
+        .interact_opt()?
+    else {
+        return Ok(false);
+    };
+
+    let limit = settings.max_recent_paths();
+    let list = settings.recent_path_list_mut(feature);
+    let before = list.clone();
+    for (idx, path) in entries.iter().enumerate() {
+        if selected.contains(&idx) {
+            list.pin(path);
+        } else {
+            list.unpin(path, limit);
+        }
+    }
+    Ok(*list != before)
+}
+
+fn remove_paths(
+    settings: &mut UserSettings,
+    feature: PathFeature,
+    entries: &[String],
+) -> Result<bool> {
+    let Some(selected) = MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("勾選要從清單移除的路徑")
+        .items(entries)
+        .interact_opt()?
+    else {
+        return Ok(false);
+    };
+
+    let list = settings.recent_path_list_mut(feature);
+    for &idx in &selected {
+        list.remove(&entries[idx]);
+    }
+    Ok(!selected.is_empty())
+}
@@ -0,0 +1,71 @@
+//! 功能執行的整體結果
+//!
+//! 命令列執行時依此決定結束代碼；互動選單只顯示摘要，執行後照常回到選單
+
+use serde::Serialize;
+use std::fmt;
+
+/// 功能執行的整體結果，數值越大越嚴重
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    /// 全部完成（包含沒有需要處理的檔案或使用者取消）
+    #[default]
+    Completed,
+    /// 執行完畢，但有檔案處理失敗
+    CompletedWithFailures,
+    /// 收到中斷訊號而提前停止
+    Aborted,
+}
+
+impl RunStatus {
+    /// 依處理失敗的檔案數決定結果
+    #[must_use]
+    pub const fn from_failures(failures: usize) -> Self {
+        if failures == 0 {
+            Self::Completed
+        } else {
+            Self::CompletedWithFailures
+        }
+    }
+
+    /// 兩個結果中較嚴重的一個
+    #[must_use]
+    pub fn worst(self, other: Self) -> Self {
+        self.max(other)
+    }
+}
+
+/// 開始處理前的檢查失敗（缺少相依程式或 ffmpeg 功能、輸入路徑不存在、參數無效）
+///
+/// 以 `.context(PreflightFailed)` 附加在錯誤上，呼叫端以 `error.is::<PreflightFailed>()` 區分
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PreflightFailed;
+
+impl fmt::Display for PreflightFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "執行前檢查失敗")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_status() {
+        assert_eq!(RunStatus::from_failures(0), RunStatus::Completed);
+        assert_eq!(
+            RunStatus::from_failures(2),
+            RunStatus::CompletedWithFailures
+        );
+        assert_eq!(
+            RunStatus::CompletedWithFailures.worst(RunStatus::Completed),
+            RunStatus::CompletedWithFailures
+        );
+        assert_eq!(
+            RunStatus::Aborted.worst(RunStatus::CompletedWithFailures),
+            RunStatus::Aborted
+        );
+    }
+}
@@ -0,0 +1,88 @@
+//! 各元件共用的掃描選項提示
+
+use super::{Interaction, OutputFormat, report};
+use crate::config::UserSettings;
+use crate::tools::SizeFilter;
+use anyhow::Result;
+use console::style;
+use dialoguer::{Confirm, Input};
+use log::warn;
+
+/// 取得本次掃描的檔案大小篩選（預設沿用設定，可在本次調整）
+///
+/// 不逐項詢問時直接使用設定中的篩選
+pub fn prompt_size_filter(
+    settings: &UserSettings,
+    interaction: Interaction,
+    output: &OutputFormat,
+) -> Result<SizeFilter> {
+    let configured = SizeFilter::parse(
+        settings.min_file_size.as_deref(),
+        settings.max_file_size.as_deref(),
+    )
+    .unwrap_or_else(|e| {
+        warn!("設定中的檔案大小篩選無效，改為不限制: {e:#}");
+        SizeFilter::default()
+    });
+    report!(
+        output,
+        "{}",
+        style(format!("檔案大小篩選: {}", configured.describe())).dim()
+    );
+
+    if !interaction.prompts_options() {
+        return Ok(configured);
+    }
+    let adjust = Confirm::new()
+        .with_prompt("本次是否調整檔案大小篩選？")
+        .default(false)
+        .interact()?;
+    if !adjust {
+        return Ok(configured);
+    }
+
+    loop {
+        let min = prompt_size(
+            "檔案大小下限（例如 10MB，留空 = 不限制）",
+            settings.min_file_size.as_deref(),
+        )?;
+        let max = prompt_size(
+            "檔案大小上限（例如 50GB，留空 = 不限制）",
+            settings.max_file_size.as_deref(),
+        )?;
+        match SizeFilter::parse(Some(&min), Some(&max)) {
+            Ok(filter) => return Ok(filter),
+            Err(e) => report!(output, "{}", style(format!("{e:#}")).red()),
+        }
+    }
+}
+
+fn prompt_size(prompt: &str, current: Option<&str>) -> Result<String> {
+    let input: String = Input::new()
+        .with_prompt(prompt)
+        .with_initial_text(current.unwrap_or_default())
+        .allow_empty(true)
+        .validate_with(|input: &String| {
+            SizeFilter::parse(Some(input), None)
+                .map(|_| ())
+                .map_err(|e| format!("{e:#}"))
+        })
+        .interact_text()?;
+    Ok(input)
+}
+
+/// 顯示因檔案大小被略過的檔案數
+pub fn report_size_filtered(filter: &SizeFilter, output: &OutputFormat) {
+    let rejected = filter.rejected();
+    if rejected > 0 {
+        report!(
+            output,
+            "{}",
+            style(format!(
+                "已略過 {rejected} 個不在大小範圍內的檔案（{}）",
+                filter.describe()
+            ))
+            .dim()
+        );
+    }
+}
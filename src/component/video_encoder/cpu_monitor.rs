@@ -2,39 +2,160 @@ use std::thread;
 use std::time::Duration;
 use sysinfo::System;
 
-pub struct CpuMonitor {
-    pub system: System,
-    usage_threshold: f32,
+/// 記憶體讀數（位元組）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoryReading {
+    pub total: u64,
+    pub available: u64,
 }
 
-impl CpuMonitor {
+impl MemoryReading {
+    /// 已使用的記憶體
     #[must_use]
-    pub fn new(usage_threshold: f32) -> Self {
+    pub const fn used(&self) -> u64 {
+        self.total.saturating_sub(self.available)
+    }
+
+    /// 已使用百分比
+    #[must_use]
+    pub fn used_percent(&self) -> f32 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        (self.used() as f64 / self.total as f64 * 100.0) as f32
+    }
+}
+
+/// 最低可用記憶體門檻（任一條件不足即不再新增任務，0 = 不檢查該條件）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoryThreshold {
+    /// 最低可用記憶體（MB）
+    pub min_available_mb: u64,
+    /// 最低可用百分比
+    pub min_available_percent: u8,
+}
+
+impl MemoryThreshold {
+    /// 讀數是否高於門檻
+    #[must_use]
+    pub fn allows(&self, reading: &MemoryReading) -> bool {
+        let min_bytes = self.min_available_mb.saturating_mul(1024 * 1024);
+        let min_by_percent = reading.total / 100 * u64::from(self.min_available_percent);
+        reading.available >= min_bytes && reading.available >= min_by_percent
+    }
+}
+
+/// 系統資源讀數來源（可替換為測試替身）
+pub trait SystemStatSource {
+    /// 目前整體 CPU 使用率（0-100）
+    fn cpu_usage(&mut self) -> f32;
+    /// 目前記憶體狀態
+    fn memory(&mut self) -> MemoryReading;
+}
+
+/// 以 sysinfo 讀取系統資源
+pub struct SysinfoStatSource {
+    system: System,
+}
+
+impl SysinfoStatSource {
+    #[must_use]
+    pub fn new() -> Self {
         let mut system = System::new_all();
         system.refresh_cpu_all();
         thread::sleep(Duration::from_millis(200));
         system.refresh_cpu_all();
+        Self { system }
+    }
+}
+
+impl Default for SysinfoStatSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SystemStatSource for SysinfoStatSource {
+    fn cpu_usage(&mut self) -> f32 {
+        self.system.refresh_cpu_all();
+        self.system.global_cpu_usage()
+    }
+
+    fn memory(&mut self) -> MemoryReading {
+        self.system.refresh_memory();
+        MemoryReading {
+            total: self.system.total_memory(),
+            available: self.system.available_memory(),
+        }
+    }
+}
+
+pub struct CpuMonitor<S: SystemStatSource = SysinfoStatSource> {
+    source: S,
+    usage_threshold: f32,
+    memory_threshold: MemoryThreshold,
+    last_usage: f32,
+    last_memory: MemoryReading,
+}
+
+impl CpuMonitor<SysinfoStatSource> {
+    #[must_use]
+    pub fn new(usage_threshold: f32) -> Self {
+        Self::with_source(SysinfoStatSource::new(), usage_threshold)
+    }
+}
+
+impl<S: SystemStatSource> CpuMonitor<S> {
+    #[must_use]
+    pub fn with_source(source: S, usage_threshold: f32) -> Self {
         Self {
-            system,
+            source,
             usage_threshold,
+            memory_threshold: MemoryThreshold::default(),
+            last_usage: 0.0,
+            last_memory: MemoryReading::default(),
         }
     }
 
-    pub fn refresh(&mut self) {
-        self.system.refresh_cpu_all();
+    /// 設定最低可用記憶體門檻
+    #[must_use]
+    pub const fn with_memory_threshold(mut self, memory_threshold: MemoryThreshold) -> Self {
+        self.memory_threshold = memory_threshold;
+        self
     }
 
     pub fn current_usage(&mut self) -> f32 {
-        self.refresh();
-        self.system.global_cpu_usage()
+        self.last_usage = self.source.cpu_usage();
+        self.last_usage
+    }
+
+    /// 最近一次讀取的 CPU 使用率
+    pub const fn last_usage(&self) -> f32 {
+        self.last_usage
+    }
+
+    pub fn current_memory(&mut self) -> MemoryReading {
+        self.last_memory = self.source.memory();
+        self.last_memory
+    }
+
+    /// 最近一次讀取的記憶體狀態
+    pub const fn last_memory(&self) -> MemoryReading {
+        self.last_memory
     }
 
     pub fn usage_threshold(&self) -> f32 {
         self.usage_threshold
     }
 
+    /// 可用記憶體是否高於門檻
+    pub fn has_enough_memory(&mut self) -> bool {
+        let reading = self.current_memory();
+        self.memory_threshold.allows(&reading)
+    }
+
     pub fn can_spawn_new_task(&mut self) -> bool {
-        self.current_usage() < self.usage_threshold
+        self.current_usage() < self.usage_threshold && self.has_enough_memory()
     }
 }
 
@@ -48,9 +169,80 @@ impl Default for CpuMonitor {
 mod tests {
     use super::*;
 
+    const GB: u64 = 1024 * 1024 * 1024;
+
+    struct FakeStats {
+        cpu: f32,
+        memory: MemoryReading,
+    }
+
+    impl SystemStatSource for FakeStats {
+        fn cpu_usage(&mut self) -> f32 {
+            self.cpu
+        }
+
+        fn memory(&mut self) -> MemoryReading {
+            self.memory
+        }
+    }
+
+    fn monitor(cpu: f32, available_gb: u64) -> CpuMonitor<FakeStats> {
+        let stats = FakeStats {
+            cpu,
+            memory: MemoryReading {
+                total: 16 * GB,
+                available: available_gb * GB,
+            },
+        };
+        CpuMonitor::with_source(stats, 80.0).with_memory_threshold(MemoryThreshold {
+            min_available_mb: 2048,
+            min_available_percent: 10,
+        })
+    }
+
     #[test]
     fn test_cpu_monitor_creation() {
         let monitor = CpuMonitor::new(80.0);
         assert_eq!(monitor.usage_threshold, 80.0);
     }
+
+    #[test]
+    fn test_can_spawn_checks_cpu_and_memory() {
+        assert!(monitor(50.0, 8).can_spawn_new_task());
+        assert!(!monitor(90.0, 8).can_spawn_new_task());
+        assert!(!monitor(50.0, 1).can_spawn_new_task());
+    }
+
+    #[test]
+    fn test_memory_threshold() {
+        let reading = MemoryReading {
+            total: 64 * GB,
+            available: 4 * GB,
+        };
+        // 4 GB 高於 2 GB，但低於 64 GB 的 10%
+        let threshold = MemoryThreshold {
+            min_available_mb: 2048,
+            min_available_percent: 10,
+        };
+        assert!(!threshold.allows(&reading));
+        assert!(MemoryThreshold::default().allows(&reading));
+        assert!(
+            MemoryThreshold {
+                min_available_mb: 2048,
+                min_available_percent: 0,
+            }
+            .allows(&reading)
+        );
+    }
+
+    #[test]
+    fn test_memory_reading_used_percent() {
+        let reading = MemoryReading {
+            total: 16 * GB,
+            available: 4 * GB,
+        };
+        assert_eq!(reading.used(), 12 * GB);
+        assert!((reading.used_percent() - 75.0).abs() < 0.01);
+        assert_eq!(MemoryReading::default().used_percent(), 0.0);
+    }
 }
@@ -0,0 +1,131 @@
+//! 編碼紀錄檔
+//!
+//! 每次執行結束後將各檔案的結果寫成 `encode_log_<時間戳>.json`，方便事後稽核。
+
+use super::task_scheduler::{EncodingTask, TaskStatus};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 單一檔案的編碼結果
+#[derive(Serialize)]
+struct EncodeLogEntry<'a> {
+    source: &'a Path,
+    destination: &'a Path,
+    status: TaskStatus,
+    error_message: Option<&'a str>,
+    attempts: u32,
+    source_size: Option<u64>,
+    output_size: Option<u64>,
+    elapsed_ms: Option<u64>,
+    log_path: Option<&'a Path>,
+}
+
+impl<'a> From<&'a EncodingTask> for EncodeLogEntry<'a> {
+    fn from(task: &'a EncodingTask) -> Self {
+        Self {
+            source: &task.source_path,
+            destination: &task.destination_path,
+            status: task.status,
+            error_message: task.error_message.as_deref(),
+            attempts: task.attempts,
+            source_size: task.source_size,
+            output_size: task.output_size,
+            elapsed_ms: task.elapsed_ms,
+            log_path: task.log_path.as_deref(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct EncodeLog<'a> {
+    /// 寫入時間（UNIX 秒）
+    created_at: u64,
+    tasks: Vec<EncodeLogEntry<'a>>,
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// 紀錄檔名稱（`encode_log_<UNIX 秒>.json`）
+#[must_use]
+pub fn encode_log_file_name(time: SystemTime) -> String {
+    format!("encode_log_{}.json", unix_secs(time))
+}
+
+/// 將任務結果寫入紀錄檔
+pub fn write_encode_log(path: &Path, tasks: &[EncodingTask]) -> Result<()> {
+    let log = EncodeLog {
+        created_at: unix_secs(SystemTime::now()),
+        tasks: tasks.iter().map(EncodeLogEntry::from).collect(),
+    };
+    let content = serde_json::to_string_pretty(&log)?;
+    fs::write(path, content).with_context(|| format!("無法寫入編碼紀錄: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_encode_log_file_name() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        assert_eq!(encode_log_file_name(time), "encode_log_1700000000.json");
+    }
+
+    #[test]
+    fn test_write_encode_log() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("encode_log.json");
+        let tasks = vec![
+            EncodingTask {
+                source_path: PathBuf::from("/videos/a.mp4"),
+                destination_path: PathBuf::from("/videos/a.convert.mkv"),
+                duration_ms: Some(60_000),
+                status: TaskStatus::Completed,
+                error_message: None,
+                attempts: 1,
+                source_size: Some(1000),
+                output_size: Some(400),
+                elapsed_ms: Some(12_345),
+                log_path: None,
+            },
+            EncodingTask {
+                source_path: PathBuf::from("/videos/b.mp4"),
+                destination_path: PathBuf::from("/videos/b.convert.mkv"),
+                duration_ms: None,
+                status: TaskStatus::Failed,
+                error_message: Some("Invalid data".to_string()),
+                attempts: 2,
+                source_size: Some(2000),
+                output_size: None,
+                elapsed_ms: Some(800),
+                log_path: Some(PathBuf::from("/videos/logs/b.log")),
+            },
+        ];
+
+        write_encode_log(&path, &tasks).unwrap();
+
+        let log: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let entries = log["tasks"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["source"], "/videos/a.mp4");
+        assert_eq!(entries[0]["destination"], "/videos/a.convert.mkv");
+        assert_eq!(entries[0]["status"], "completed");
+        assert_eq!(entries[0]["output_size"], 400);
+        assert_eq!(entries[0]["elapsed_ms"], 12_345);
+        assert_eq!(entries[1]["status"], "failed");
+        assert_eq!(entries[1]["error_message"], "Invalid data");
+        assert!(entries[1]["output_size"].is_null());
+        assert_eq!(entries[1]["log_path"], "/videos/logs/b.log");
+    }
+}
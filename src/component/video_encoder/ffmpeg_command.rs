@@ -1,27 +1,120 @@
+use crate::config::{AudioMode, DEFAULT_CRF, DEFAULT_PRESET, EncodeBackend, EncoderCodec, MAX_CRF};
+use crate::tools::resolve_ffmpeg;
+use anyhow::Result;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
 pub struct FfmpegCommand {
     source_path: PathBuf,
     destination_path: PathBuf,
+    codec: EncoderCodec,
+    backend: EncodeBackend,
+    crf: u8,
+    preset: String,
+    audio_mode: AudioMode,
+    keep_all_audio: bool,
+    keep_subtitles: bool,
+    /// 來源各字幕串流的編碼名稱（ffprobe 取得，用來決定如何寫入 Matroska）
+    subtitle_codecs: Vec<String>,
+}
+
+/// 字幕寫入 Matroska 的方式：MP4/MOV 的 `mov_text` 無法放進 Matroska，改轉為 SRT，其餘直接複製
+fn matroska_subtitle_codec(codec: &str) -> &'static str {
+    match codec {
+        "mov_text" => "srt",
+        _ => "copy",
+    }
 }
 
 impl FfmpegCommand {
     #[must_use]
     pub fn new(source_path: &Path) -> Self {
-        let destination_path = Self::generate_destination_path(source_path);
+        let destination_path = Self::generate_destination_path(source_path, None);
         Self {
             source_path: source_path.to_path_buf(),
             destination_path,
+            codec: EncoderCodec::default(),
+            backend: EncodeBackend::default(),
+            crf: DEFAULT_CRF,
+            preset: DEFAULT_PRESET.to_string(),
+            audio_mode: AudioMode::default(),
+            keep_all_audio: false,
+            keep_subtitles: false,
+            subtitle_codecs: Vec::new(),
         }
     }
 
-    fn generate_destination_path(source_path: &Path) -> PathBuf {
+    /// 指定影片編碼器（預設為 x265）
+    #[must_use]
+    pub const fn with_codec(mut self, codec: EncoderCodec) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// 指定編碼後端（預設為軟體編碼）
+    ///
+    /// GPU 後端固定輸出 HEVC，忽略 `with_codec` 的設定
+    #[must_use]
+    pub const fn with_backend(mut self, backend: EncodeBackend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// 指定音訊處理方式（預設為 FLAC）
+    #[must_use]
+    pub const fn with_audio(mut self, audio_mode: AudioMode) -> Self {
+        self.audio_mode = audio_mode;
+        self
+    }
+
+    /// 保留所有音軌（預設只保留第一條）
+    #[must_use]
+    pub const fn with_all_audio(mut self, keep_all_audio: bool) -> Self {
+        self.keep_all_audio = keep_all_audio;
+        self
+    }
+
+    /// 保留內嵌字幕（直接複製，不重新編碼）
+    #[must_use]
+    pub const fn with_subtitles(mut self, keep_subtitles: bool) -> Self {
+        self.keep_subtitles = keep_subtitles;
+        self
+    }
+
+    /// 指定來源各字幕串流的編碼名稱（搭配 `with_subtitles`）
+    #[must_use]
+    pub fn with_subtitle_codecs(mut self, subtitle_codecs: Vec<String>) -> Self {
+        self.subtitle_codecs = subtitle_codecs;
+        self
+    }
+
+    /// 將輸出寫到指定資料夾（保留原始檔名）
+    #[must_use]
+    pub fn with_output_dir(mut self, output_dir: &Path) -> Self {
+        self.destination_path =
+            Self::generate_destination_path(&self.source_path, Some(output_dir));
+        self
+    }
+
+    /// 指定 CRF 與 preset
+    #[must_use]
+    pub fn with_quality(mut self, crf: u8, preset: &str) -> Self {
+        self.crf = crf;
+        self.preset = preset.to_string();
+        self
+    }
+
+    /// 所有編碼器皆輸出 Matroska，因此目的檔固定為 `.convert.mkv`
+    ///
+    /// 未指定輸出資料夾時寫在來源檔旁
+    fn generate_destination_path(source_path: &Path, output_dir: Option<&Path>) -> PathBuf {
         let file_stem = source_path
             .file_stem()
             .and_then(|s| s.to_str())
             .unwrap_or("output");
-        let parent = source_path.parent().unwrap_or(Path::new("."));
+        let parent = output_dir
+            .or_else(|| source_path.parent())
+            .unwrap_or(Path::new("."));
         parent.join(format!("{file_stem}.convert.mkv"))
     }
 
@@ -30,13 +123,13 @@ impl FfmpegCommand {
         &self.destination_path
     }
 
-    #[must_use]
-    pub fn build_command(&self) -> Command {
-        let mut cmd = Command::new("ffmpeg");
+    #[rustfmt::skip]
+    pub fn build_command(&self) -> Result<Command> {
+        let mut cmd = Command::new(resolve_ffmpeg()?);
 
         cmd.args([
             "-hide_banner",
-            "-nostdin",
+            // 不使用 -nostdin：中斷時由 stdin 送出 q 讓 ffmpeg 正常收尾
             // 將進度輸出成 key=value 格式到 stdout，便於程式解析
             "-progress", "pipe:1",
             "-stats_period", "0.5",
@@ -51,39 +144,240 @@ impl FfmpegCommand {
             "-flags:v", "+bitexact",
             "-flags:a", "+bitexact",
             "-i", &format!("file:{}", self.source_path.display()),
-            "-map", "0:v:0",
-            "-map", "0:a:0?",
-            "-sn", "-dn",
-            "-map", "-0:s",
-            "-map", "-0:d",
-            "-map", "-0:t",
-            "-map", "-0:v:m:attached_pic",
+        ]);
+        cmd.args(self.stream_args());
+        cmd.args([
             "-map_metadata", "-1",
-            "-map_metadata:s", "-1",
             "-map_chapters", "-1",
             "-avoid_negative_ts", "make_zero",
-            "-vf", "scale=round(iw*if(sar\\,sar\\,1)/2)*2:round(ih/2)*2,setsar=1,format=yuv420p10le",
-            "-c:v", "libx265",
-            "-profile:v", "main10",
-            "-pix_fmt", "yuv420p10le",
-            "-udu_sei", "0",
-            "-preset", "fast",
-            "-g", "60",
-            "-keyint_min", "60",
-            "-crf", "16",
-            "-x265-params", "no-info=1:pmode=1:limit-sao=1:cutree=1:rc-lookahead=30:bframes=4:b-adapt=2:psy-rd=1.0:psy-rdoq=0.5:open-gop=0",
-            "-bsf:v", "filter_units=remove_types=35|38-40",
-            "-c:a", "flac",
-            "-ar", "48000",
-            "-ac", "2",
-            "-f", "matroska",
         ]);
+        cmd.args(self.video_args());
+        cmd.args(self.audio_args());
+        cmd.args(self.subtitle_args());
+        cmd.args(["-f", "matroska"]);
         cmd.arg(&self.destination_path);
 
-        cmd
+        Ok(cmd)
+    }
+
+    /// 串流選擇參數
+    ///
+    /// 預設只保留第一條視訊與音軌；保留多音軌或字幕時一併保留串流的
+    /// 語言與標題標籤，否則無法分辨各音軌與字幕
+    #[rustfmt::skip]
+    fn stream_args(&self) -> Vec<&'static str> {
+        let mut args = vec!["-map", "0:v:0"];
+        args.extend(if self.keep_all_audio {
+            ["-map", "0:a?"]
+        } else {
+            ["-map", "0:a:0?"]
+        });
+        if self.keep_subtitles {
+            args.extend(["-map", "0:s?"]);
+        } else {
+            args.extend(["-sn", "-map", "-0:s"]);
+        }
+        args.extend([
+            "-dn",
+            "-map", "-0:d",
+            "-map", "-0:t",
+            "-map", "-0:v:m:attached_pic",
+        ]);
+        if !self.keep_all_audio && !self.keep_subtitles {
+            args.extend(["-map_metadata:s", "-1"]);
+        }
+        args
+    }
+
+    /// 字幕參數
+    ///
+    /// 預設直接複製，Matroska 無法容納的字幕串流另外指定轉換格式（後面的個別設定優先）
+    fn subtitle_args(&self) -> Vec<String> {
+        if !self.keep_subtitles {
+            return Vec::new();
+        }
+        let mut args = vec!["-c:s".to_string(), "copy".to_string()];
+        for (index, codec) in self.subtitle_codecs.iter().enumerate() {
+            let target = matroska_subtitle_codec(codec);
+            if target != "copy" {
+                args.extend([format!("-c:s:{index}"), target.to_string()]);
+            }
+        }
+        args
+    }
+
+    /// 音訊參數
+    ///
+    /// 直接複製時不做取樣率與聲道的正規化
+    #[rustfmt::skip]
+    fn audio_args(&self) -> Vec<String> {
+        let mut args: Vec<String> = match self.audio_mode {
+            AudioMode::Copy => return vec!["-c:a".into(), "copy".into()],
+            AudioMode::Flac => vec!["-c:a".into(), "flac".into()],
+            AudioMode::Aac { bitrate } => vec![
+                "-c:a".into(), "aac".into(),
+                "-b:a".into(), format!("{bitrate}k"),
+            ],
+        };
+        args.extend([
+            "-ar".into(), "48000".into(),
+            "-ac".into(), "2".into(),
+        ]);
+        args
+    }
+
+    /// 依編碼後端產生視訊編碼參數
+    fn video_args(&self) -> Vec<String> {
+        match self.backend {
+            EncodeBackend::Software => self.software_video_args(),
+            EncodeBackend::Nvenc | EncodeBackend::Qsv | EncodeBackend::VideoToolbox => {
+                self.gpu_video_args()
+            }
+        }
+    }
+
+    /// 軟體編碼器參數
+    #[rustfmt::skip]
+    fn software_video_args(&self) -> Vec<String> {
+        let args: &[&str] = match self.codec {
+            EncoderCodec::X265 => &[
+                "-vf", "scale=round(iw*if(sar\\,sar\\,1)/2)*2:round(ih/2)*2,setsar=1,format=yuv420p10le",
+                "-c:v", "libx265",
+                "-profile:v", "main10",
+                "-pix_fmt", "yuv420p10le",
+                "-udu_sei", "0",
+                "-g", "60",
+                "-keyint_min", "60",
+                "-x265-params", "no-info=1:pmode=1:limit-sao=1:cutree=1:rc-lookahead=30:bframes=4:b-adapt=2:psy-rd=1.0:psy-rdoq=0.5:open-gop=0",
+                "-bsf:v", "filter_units=remove_types=35|38-40",
+            ],
+            EncoderCodec::X264 => &[
+                "-vf", "scale=round(iw*if(sar\\,sar\\,1)/2)*2:round(ih/2)*2,setsar=1,format=yuv420p",
+                "-c:v", "libx264",
+                "-profile:v", "high",
+                "-pix_fmt", "yuv420p",
+                "-g", "60",
+                "-keyint_min", "60",
+                "-x264-params", "rc-lookahead=30:bframes=4:b-adapt=2:open-gop=0",
+            ],
+            EncoderCodec::SvtAv1 => &[
+                "-vf", "scale=round(iw*if(sar\\,sar\\,1)/2)*2:round(ih/2)*2,setsar=1,format=yuv420p10le",
+                "-c:v", "libsvtav1",
+                "-pix_fmt", "yuv420p10le",
+                "-g", "240",
+                "-svtav1-params", "tune=0:enable-overlays=1:scd=1",
+            ],
+        };
+        let preset = match self.codec {
+            EncoderCodec::SvtAv1 => svtav1_preset(&self.preset),
+            EncoderCodec::X265 | EncoderCodec::X264 => self.preset.clone(),
+        };
+
+        let mut args: Vec<String> = args.iter().map(ToString::to_string).collect();
+        args.extend([
+            "-preset".to_string(),
+            preset,
+            "-crf".to_string(),
+            self.crf.to_string(),
+        ]);
+        args
+    }
+
+    /// GPU HEVC 10-bit 編碼參數
+    ///
+    /// GPU 編碼器不支援 `-x265-params` 與 `-crf`，
+    /// 品質改以各自的參數指定：NVENC `-cq`、QSV `-global_quality`、VideoToolbox `-q:v`
+    #[rustfmt::skip]
+    fn gpu_video_args(&self) -> Vec<String> {
+        let crf = self.crf.to_string();
+        let mut args: Vec<String> = [
+            "-vf", "scale=round(iw*if(sar\\,sar\\,1)/2)*2:round(ih/2)*2,setsar=1,format=p010le",
+            "-profile:v", "main10",
+            "-pix_fmt", "p010le",
+            "-g", "60",
+        ]
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+
+        let backend_args = match self.backend {
+            EncodeBackend::Nvenc => vec![
+                "-c:v".to_string(), "hevc_nvenc".to_string(),
+                "-preset".to_string(), nvenc_preset(&self.preset),
+                "-rc".to_string(), "vbr".to_string(),
+                "-cq".to_string(), crf,
+                "-b:v".to_string(), "0".to_string(),
+            ],
+            EncodeBackend::Qsv => vec![
+                "-c:v".to_string(), "hevc_qsv".to_string(),
+                "-preset".to_string(), qsv_preset(&self.preset),
+                "-global_quality".to_string(), crf,
+            ],
+            EncodeBackend::VideoToolbox => vec![
+                "-c:v".to_string(), "hevc_videotoolbox".to_string(),
+                "-q:v".to_string(), videotoolbox_quality(self.crf).to_string(),
+            ],
+            EncodeBackend::Software => Vec::new(),
+        };
+        args.extend(backend_args);
+        args
     }
 }
 
+/// 將 x264/x265 的 preset 名稱轉換為 NVENC 的 p1-p7（越大越慢、品質越高）
+fn nvenc_preset(preset: &str) -> String {
+    let level = match preset {
+        "ultrafast" | "superfast" => 1,
+        "veryfast" => 2,
+        "faster" => 3,
+        "medium" => 5,
+        "slow" => 6,
+        "slower" | "veryslow" | "placebo" => 7,
+        p if p.len() == 2 && p.starts_with('p') => return p.to_string(),
+        _ => 4,
+    };
+    format!("p{level}")
+}
+
+/// QSV 只接受 veryfast 到 veryslow，超出範圍的名稱取最接近的值
+fn qsv_preset(preset: &str) -> String {
+    match preset {
+        "ultrafast" | "superfast" => "veryfast",
+        "placebo" => "veryslow",
+        "veryfast" | "faster" | "fast" | "medium" | "slow" | "slower" | "veryslow" => preset,
+        _ => "fast",
+    }
+    .to_string()
+}
+
+/// 將 CRF（0-51，越小越好）換算為 VideoToolbox 的 `-q:v`（1-100，越大越好）
+fn videotoolbox_quality(crf: u8) -> u8 {
+    let crf = u32::from(crf.min(MAX_CRF));
+    (100 - crf * 99 / u32::from(MAX_CRF)) as u8
+}
+
+/// 將 x264/x265 的 preset 名稱轉換為 SVT-AV1 的數字 preset（0-13，越大越快）
+///
+/// 已是數字時直接使用
+fn svtav1_preset(preset: &str) -> String {
+    if preset.parse::<u8>().is_ok() {
+        return preset.to_string();
+    }
+    let level = match preset {
+        "ultrafast" => 12,
+        "superfast" => 11,
+        "veryfast" => 10,
+        "faster" => 9,
+        "medium" => 6,
+        "slow" => 4,
+        "slower" => 3,
+        "veryslow" => 2,
+        "placebo" => 1,
+        _ => 8,
+    };
+    level.to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -107,4 +401,177 @@ mod tests {
             Path::new("/videos/test.video.name.convert.mkv")
         );
     }
+
+    #[test]
+    fn test_destination_path_in_output_dir() {
+        let cmd = FfmpegCommand::new(Path::new("/videos/test.mp4"))
+            .with_output_dir(Path::new("/encoded"));
+        assert_eq!(
+            cmd.destination_path(),
+            Path::new("/encoded/test.convert.mkv")
+        );
+    }
+
+    #[test]
+    fn test_stream_args_default_keeps_first_audio_only() {
+        let args = FfmpegCommand::new(Path::new("/videos/test.mp4")).stream_args();
+        assert!(args.windows(2).any(|w| w == ["-map", "0:a:0?"]));
+        assert!(args.windows(2).any(|w| w == ["-map", "-0:s"]));
+        assert!(args.contains(&"-sn"));
+        assert!(args.windows(2).any(|w| w == ["-map_metadata:s", "-1"]));
+    }
+
+    #[test]
+    fn test_stream_args_keep_audio_and_subtitles() {
+        let args = FfmpegCommand::new(Path::new("/videos/test.mp4"))
+            .with_all_audio(true)
+            .with_subtitles(true)
+            .stream_args();
+        assert!(args.windows(2).any(|w| w == ["-map", "0:a?"]));
+        assert!(args.windows(2).any(|w| w == ["-map", "0:s?"]));
+        assert!(!args.contains(&"-sn"));
+        assert!(!args.contains(&"-0:s"));
+        assert!(!args.contains(&"-map_metadata:s"));
+    }
+
+    #[test]
+    fn test_subtitle_args_convert_mov_text() {
+        let source = Path::new("/videos/test.mp4");
+        assert!(FfmpegCommand::new(source).subtitle_args().is_empty());
+
+        let args = FfmpegCommand::new(source)
+            .with_subtitles(true)
+            .with_subtitle_codecs(vec!["subrip".to_string(), "mov_text".to_string()])
+            .subtitle_args();
+        assert_eq!(args, ["-c:s", "copy", "-c:s:1", "srt"]);
+
+        // 未取得字幕編碼時全部直接複製
+        let args = FfmpegCommand::new(source)
+            .with_subtitles(true)
+            .subtitle_args();
+        assert_eq!(args, ["-c:s", "copy"]);
+    }
+
+    #[test]
+    fn test_audio_args_per_mode() {
+        let source = Path::new("/videos/test.mp4");
+
+        let flac = FfmpegCommand::new(source).audio_args();
+        assert_eq!(flac, ["-c:a", "flac", "-ar", "48000", "-ac", "2"]);
+
+        let copy = FfmpegCommand::new(source)
+            .with_audio(AudioMode::Copy)
+            .audio_args();
+        assert_eq!(copy, ["-c:a", "copy"]);
+
+        let aac = FfmpegCommand::new(source)
+            .with_audio(AudioMode::Aac { bitrate: 160 })
+            .audio_args();
+        assert_eq!(
+            aac,
+            ["-c:a", "aac", "-b:a", "160k", "-ar", "48000", "-ac", "2"]
+        );
+    }
+
+    #[test]
+    fn test_default_codec_is_x265() {
+        let args = FfmpegCommand::new(Path::new("/videos/test.mp4")).video_args();
+        assert!(args.windows(2).any(|w| w == ["-c:v", "libx265"]));
+        assert!(args.windows(2).any(|w| w == ["-profile:v", "main10"]));
+        assert!(args.iter().any(|a| a == "-x265-params"));
+    }
+
+    #[test]
+    fn test_video_args_per_codec() {
+        let source = Path::new("/videos/test.mp4");
+
+        let x264 = FfmpegCommand::new(source)
+            .with_codec(EncoderCodec::X264)
+            .video_args();
+        assert!(x264.windows(2).any(|w| w == ["-c:v", "libx264"]));
+        assert!(x264.windows(2).any(|w| w == ["-pix_fmt", "yuv420p"]));
+        assert!(!x264.iter().any(|a| a == "-x265-params"));
+
+        let av1 = FfmpegCommand::new(source)
+            .with_codec(EncoderCodec::SvtAv1)
+            .video_args();
+        assert!(av1.windows(2).any(|w| w == ["-c:v", "libsvtav1"]));
+        assert!(av1.windows(2).any(|w| w == ["-pix_fmt", "yuv420p10le"]));
+    }
+
+    #[test]
+    fn test_video_args_use_crf_and_preset() {
+        let args = FfmpegCommand::new(Path::new("/videos/test.mp4"))
+            .with_quality(22, "slow")
+            .video_args();
+        assert!(args.windows(2).any(|w| w == ["-crf", "22"]));
+        assert!(args.windows(2).any(|w| w == ["-preset", "slow"]));
+
+        let default_args = FfmpegCommand::new(Path::new("/videos/test.mp4")).video_args();
+        assert!(default_args.windows(2).any(|w| w == ["-crf", "16"]));
+        assert!(default_args.windows(2).any(|w| w == ["-preset", "fast"]));
+    }
+
+    #[test]
+    fn test_svtav1_preset_mapping() {
+        assert_eq!(svtav1_preset("fast"), "8");
+        assert_eq!(svtav1_preset("slow"), "4");
+        assert_eq!(svtav1_preset("10"), "10");
+
+        let args = FfmpegCommand::new(Path::new("/videos/test.mp4"))
+            .with_codec(EncoderCodec::SvtAv1)
+            .with_quality(30, "medium")
+            .video_args();
+        assert!(args.windows(2).any(|w| w == ["-preset", "6"]));
+    }
+
+    #[test]
+    fn test_destination_is_mkv_for_all_codecs() {
+        let source = Path::new("/videos/test.mp4");
+        for codec in [EncoderCodec::X265, EncoderCodec::X264, EncoderCodec::SvtAv1] {
+            let cmd = FfmpegCommand::new(source).with_codec(codec);
+            assert_eq!(
+                cmd.destination_path(),
+                Path::new("/videos/test.convert.mkv")
+            );
+        }
+    }
+
+    #[test]
+    fn test_gpu_backends_use_hevc_without_x265_params() {
+        let source = Path::new("/videos/test.mp4");
+        let cases = [
+            (EncodeBackend::Nvenc, "hevc_nvenc", "-cq"),
+            (EncodeBackend::Qsv, "hevc_qsv", "-global_quality"),
+            (EncodeBackend::VideoToolbox, "hevc_videotoolbox", "-q:v"),
+        ];
+
+        for (backend, encoder, quality_flag) in cases {
+            let args = FfmpegCommand::new(source)
+                .with_codec(EncoderCodec::X264)
+                .with_backend(backend)
+                .video_args();
+            assert!(args.windows(2).any(|w| w == ["-c:v", encoder]));
+            assert!(args.iter().any(|a| a == quality_flag));
+            assert!(args.windows(2).any(|w| w == ["-pix_fmt", "p010le"]));
+            assert!(!args.iter().any(|a| a == "-x265-params" || a == "-crf"));
+        }
+    }
+
+    #[test]
+    fn test_gpu_quality_mapping() {
+        let args = FfmpegCommand::new(Path::new("/videos/test.mp4"))
+            .with_backend(EncodeBackend::Nvenc)
+            .with_quality(20, "slow")
+            .video_args();
+        assert!(args.windows(2).any(|w| w == ["-cq", "20"]));
+        assert!(args.windows(2).any(|w| w == ["-preset", "p6"]));
+
+        assert_eq!(qsv_preset("ultrafast"), "veryfast");
+        assert_eq!(qsv_preset("slow"), "slow");
+        assert_eq!(nvenc_preset("p3"), "p3");
+        assert_eq!(videotoolbox_quality(0), 100);
+        assert_eq!(videotoolbox_quality(MAX_CRF), 1);
+        assert_eq!(videotoolbox_quality(DEFAULT_CRF), 69);
+    }
 }
@@ -0,0 +1,160 @@
+//! 編碼期間的鍵盤控制
+//!
+//! 將終端機切換為非標準模式（不需按 Enter、不回顯）逐鍵讀取，結束時還原。
+//! 暫停時以 SIGSTOP 暫停執行中的 ffmpeg；目前只支援 Unix，其他平台不啟用鍵盤控制
+
+/// 鍵盤指令
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyCommand {
+    /// 停止啟動新任務並暫停執行中的任務
+    Pause,
+    /// 繼續執行
+    Resume,
+}
+
+impl KeyCommand {
+    /// 對應的按鍵（不分大小寫）
+    #[must_use]
+    pub const fn from_byte(byte: u8) -> Option<Self> {
+        match byte.to_ascii_lowercase() {
+            b'p' => Some(Self::Pause),
+            b'r' => Some(Self::Resume),
+            _ => None,
+        }
+    }
+}
+
+/// 非阻塞的按鍵讀取，drop 時還原終端機設定
+pub struct KeyboardControl {
+    #[cfg(unix)]
+    original: nix::sys::termios::Termios,
+}
+
+#[cfg(unix)]
+impl KeyboardControl {
+    /// 標準輸入是終端機時切換為非標準模式，否則回傳 `None`
+    #[must_use]
+    pub fn new() -> Option<Self> {
+        use nix::sys::termios::{
+            LocalFlags, SetArg, SpecialCharacterIndices, tcgetattr, tcsetattr,
+        };
+        use std::io::IsTerminal;
+
+        let stdin = std::io::stdin();
+        if !stdin.is_terminal() {
+            return None;
+        }
+        let original = tcgetattr(&stdin).ok()?;
+        let mut raw = original.clone();
+        // 保留 ISIG，Ctrl-C 仍會送出中斷訊號
+        raw.local_flags
+            .remove(LocalFlags::ICANON | LocalFlags::ECHO);
+        raw.control_chars[SpecialCharacterIndices::VMIN as usize] = 0;
+        raw.control_chars[SpecialCharacterIndices::VTIME as usize] = 0;
+        tcsetattr(&stdin, SetArg::TCSANOW, &raw).ok()?;
+        Some(Self { original })
+    }
+
+    /// 讀出所有已輸入的按鍵，回傳最後一個可辨識的指令
+    #[must_use]
+    pub fn poll(&self) -> Option<KeyCommand> {
+        let stdin = std::io::stdin();
+        let mut buf = [0u8; 32];
+        let mut command = None;
+        while let Ok(len @ 1..) = nix::unistd::read(&stdin, &mut buf) {
+            command = buf[..len]
+                .iter()
+                .rev()
+                .find_map(|&byte| KeyCommand::from_byte(byte))
+                .or(command);
+        }
+        command
+    }
+}
+
+#[cfg(unix)]
+impl Drop for KeyboardControl {
+    fn drop(&mut self) {
+        use nix::sys::termios::{SetArg, tcsetattr};
+
+        if let Err(e) = tcsetattr(std::io::stdin(), SetArg::TCSANOW, &self.original) {
+            log::warn!("無法還原終端機設定: {e}");
+        }
+    }
+}
+
+#[cfg(not(unix))]
+impl KeyboardControl {
+    /// 此平台不支援非阻塞的按鍵讀取
+    #[must_use]
+    pub const fn new() -> Option<Self> {
+        None
+    }
+
+    #[must_use]
+    pub const fn poll(&self) -> Option<KeyCommand> {
+        None
+    }
+}
+
+/// 暫停程序，回傳是否成功（不支援的平台回傳 `false`）
+#[cfg(unix)]
+pub fn suspend_process(pid: u32) -> bool {
+    send_signal(pid, nix::sys::signal::Signal::SIGSTOP)
+}
+
+/// 繼續已暫停的程序
+#[cfg(unix)]
+pub fn resume_process(pid: u32) -> bool {
+    send_signal(pid, nix::sys::signal::Signal::SIGCONT)
+}
+
+#[cfg(unix)]
+fn send_signal(pid: u32, signal: nix::sys::signal::Signal) -> bool {
+    let Ok(raw) = i32::try_from(pid) else {
+        return false;
+    };
+    match nix::sys::signal::kill(nix::unistd::Pid::from_raw(raw), signal) {
+        Ok(()) => true,
+        Err(e) => {
+            log::warn!("無法送出 {signal} 給程序 [{pid}]: {e}");
+            false
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub const fn suspend_process(_pid: u32) -> bool {
+    false
+}
+
+#[cfg(not(unix))]
+pub const fn resume_process(_pid: u32) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_command_from_byte() {
+        assert_eq!(KeyCommand::from_byte(b'p'), Some(KeyCommand::Pause));
+        assert_eq!(KeyCommand::from_byte(b'P'), Some(KeyCommand::Pause));
+        assert_eq!(KeyCommand::from_byte(b'r'), Some(KeyCommand::Resume));
+        assert_eq!(KeyCommand::from_byte(b'q'), None);
+        assert_eq!(KeyCommand::from_byte(b'\n'), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_suspend_and_resume_process() {
+        use std::process::Command;
+
+        let mut child = Command::new("sleep").arg("5").spawn().unwrap();
+        assert!(suspend_process(child.id()));
+        assert!(resume_process(child.id()));
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+}
@@ -1,19 +1,89 @@
+use super::encode_log::encode_log_file_name;
+use super::queue_state::{load_queue_state, reconcile_tasks, remove_queue_state};
+use super::size_report::{SIZE_REPORT_FILE_NAME, SizeReport};
+use super::stability_checker::{DeferredRecheckQueue, StabilityChecker};
 use super::task_scheduler::{EncodingTask, TaskScheduler, TaskStatus};
-use crate::config::Config;
+use crate::component::path_prompt::prompt_directory;
+use crate::component::scan_options::{prompt_size_filter, report_size_filtered};
+use crate::component::{Interaction, OutputFormat, PreflightFailed, RunStatus, report};
 use crate::config::save::{add_recent_path, save_settings};
-use crate::tools::{scan_video_files, validate_directory_exists};
-use anyhow::Result;
+use crate::config::{Config, PathFeature, UserSettings};
+use crate::tools::{
+    ExcludeFilter, VideoFileInfo, check_space, format_size, probe_capabilities, scan_video_files,
+    validate_directory_exists,
+};
+use anyhow::{Context, Result};
 use console::style;
-use dialoguer::theme::ColorfulTheme;
-use dialoguer::{Input, Select};
+use dialoguer::Confirm;
 use log::{error, info, warn};
+use serde::Serialize;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
+
+/// 1 GiB（位元組）
+const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
 
 pub struct VideoEncoder {
     config: Config,
     shutdown_signal: Arc<AtomicBool>,
+    interaction: Interaction,
+    output: OutputFormat,
+}
+
+/// 未排入佇列的檔案統計
+#[derive(Debug, Default)]
+struct SkipSummary {
+    /// 仍在寫入中的檔案數
+    still_growing: usize,
+}
+
+/// 編碼任務摘要
+#[derive(Debug, Serialize)]
+struct EncodeSummary {
+    total: usize,
+    completed: usize,
+    /// 重試後成功的任務數（包含在 `completed`）
+    retried: usize,
+    /// 編碼後較大而保留原檔的任務數
+    kept_original: usize,
+    /// 已是 HEVC 而跳過的檔案數
+    already_hevc: usize,
+    /// 仍在寫入中而跳過的檔案數
+    still_growing: usize,
+    /// 尚未處理、保留在佇列中的任務數
+    unfinished: usize,
+    failed: usize,
+    /// 失敗任務的 ffmpeg 錯誤記錄
+    failed_logs: Vec<PathBuf>,
+    size_report: SizeReport,
+}
+
+impl EncodeSummary {
+    fn new(tasks: &[EncodingTask], skipped: &SkipSummary) -> Self {
+        let count = |status: TaskStatus| tasks.iter().filter(|t| t.status == status).count();
+        Self {
+            total: tasks.len(),
+            completed: count(TaskStatus::Completed),
+            retried: tasks.iter().filter(|t| t.succeeded_after_retry()).count(),
+            kept_original: count(TaskStatus::KeptOriginal),
+            already_hevc: count(TaskStatus::Skipped),
+            still_growing: skipped.still_growing,
+            unfinished: count(TaskStatus::Pending),
+            failed: count(TaskStatus::Failed),
+            failed_logs: tasks
+                .iter()
+                .filter(|t| t.status == TaskStatus::Failed)
+                .filter_map(|t| t.log_path.clone())
+                .collect(),
+            size_report: SizeReport::from_tasks(tasks),
+        }
+    }
+
+    const fn status(&self) -> RunStatus {
+        RunStatus::from_failures(self.failed)
+    }
 }
 
 impl VideoEncoder {
@@ -21,14 +91,34 @@ impl VideoEncoder {
         Self {
             config,
             shutdown_signal,
+            interaction: Interaction::Prompt,
+            output: OutputFormat::Human,
         }
     }
 
-    pub fn run(&self) -> Result<()> {
-        println!("{}", style("=== 影片重新編碼 ===").cyan().bold());
+    #[must_use]
+    pub const fn with_interaction(mut self, interaction: Interaction) -> Self {
+        self.interaction = interaction;
+        self
+    }
+
+    #[must_use]
+    pub fn with_output(mut self, output: OutputFormat) -> Self {
+        self.output = output;
+        self
+    }
 
-        let Some(input_path) = self.prompt_input_path()? else {
-            return Ok(()); // ESC pressed
+    pub fn run(&self) -> Result<RunStatus> {
+        report!(
+            self.output,
+            "{}",
+            style("=== 影片重新編碼 ===").cyan().bold()
+        );
+        self.check_ffmpeg_capabilities()?;
+
+        let mut settings = self.config.settings.clone();
+        let Some(input_path) = self.prompt_input_path(&mut settings)? else {
+            return Ok(RunStatus::Completed); // ESC pressed
         };
         let directory = PathBuf::from(&input_path);
 
@@ -36,33 +126,95 @@ impl VideoEncoder {
 
         // 更新路徑歷史並儲存
         {
-            let mut settings = self.config.settings.clone();
-            add_recent_path(&mut settings, &input_path);
+            add_recent_path(&mut settings, PathFeature::VideoEncoder, &input_path);
             if let Err(e) = save_settings(&settings) {
                 warn!("無法儲存路徑歷史: {e}");
             }
         }
 
-        println!("{}", style("掃描影片檔案中...").dim());
-        let video_files = scan_video_files(&directory, &self.config.file_type_table)?;
+        self.encode_directory(&directory)
+    }
+
+    /// 不詢問路徑，直接編碼指定資料夾（命令列執行）
+    pub fn run_in(&self, directory: &Path) -> Result<RunStatus> {
+        report!(
+            self.output,
+            "{}",
+            style("=== 影片重新編碼 ===").cyan().bold()
+        );
+        self.check_ffmpeg_capabilities().context(PreflightFailed)?;
+        validate_directory_exists(directory).context(PreflightFailed)?;
+        self.encode_directory(directory)
+    }
+
+    /// 確認 ffmpeg 具備編碼所需的濾鏡與編碼器
+    fn check_ffmpeg_capabilities(&self) -> Result<()> {
+        let encoder_settings = &self.config.settings.video_encoder;
+        let mut encoders = vec![encoder_settings.ffmpeg_encoder()];
+        encoders.extend(encoder_settings.audio_mode.ffmpeg_encoder());
+        probe_capabilities()?.ensure(&["scale", "setsar", "format"], &encoders)
+    }
+
+    fn encode_directory(&self, directory: &Path) -> Result<RunStatus> {
+        // 偵測上次中斷留下的佇列
+        if let Some(tasks) = self.prompt_resume(directory)? {
+            return self.resume_queue(directory, tasks);
+        }
+
+        let size_filter =
+            prompt_size_filter(&self.config.settings, self.interaction, &self.output)?;
+
+        report!(self.output, "{}", style("掃描影片檔案中...").dim());
+        let video_files = scan_video_files(
+            directory,
+            &self.config.file_type_table,
+            &ExcludeFilter::new(&self.config.settings.exclude_globs),
+            Some(&size_filter),
+            true,
+            &self.shutdown_signal,
+        )?;
+        report_size_filtered(&size_filter, &self.output);
 
         if video_files.is_empty() {
-            println!("{}", style("找不到任何影片檔案").yellow());
-            return Ok(());
+            report!(self.output, "{}", style("找不到任何影片檔案").yellow());
+            return Ok(RunStatus::Completed);
         }
 
-        println!(
+        let (video_files, growing_files) = self.split_growing_files(video_files);
+
+        let encoder_settings = &self.config.settings.video_encoder;
+        if !encoder_settings.reencode_hevc && encoder_settings.outputs_hevc() {
+            report!(self.output, "{}", style("檢查影片編碼格式...").dim());
+        }
+        let mut scheduler = TaskScheduler::new(
+            &video_files,
+            directory,
+            Arc::clone(&self.shutdown_signal),
+            encoder_settings,
+        )?
+        .with_deletion_mode(self.config.settings.deletion_mode);
+
+        let pending_files: Vec<&VideoFileInfo> = video_files
+            .iter()
+            .zip(scheduler.tasks())
+            .filter(|(_, task)| task.status != TaskStatus::Skipped)
+            .map(|(file, _)| file)
+            .collect();
+
+        report!(
+            self.output,
             "{}",
             style(format!(
                 "找到 {} 個影片檔案，依檔案大小排序（由小到大）：",
-                video_files.len()
+                pending_files.len()
             ))
             .green()
         );
 
-        for (index, file) in video_files.iter().enumerate() {
+        for (index, file) in pending_files.iter().enumerate() {
             let size_mb = file.size as f64 / 1024.0 / 1024.0;
-            println!(
+            report!(
+                self.output,
                 "  {}. {} ({:.2} MB)",
                 index + 1,
                 file.path.file_name().unwrap_or_default().to_string_lossy(),
@@ -70,11 +222,35 @@ impl VideoEncoder {
             );
         }
 
-        println!();
+        self.print_hevc_files(scheduler.tasks());
+
+        let mut skipped = SkipSummary {
+            still_growing: growing_files.len(),
+        };
+        let mut deferred = DeferredRecheckQueue::default();
+        if !growing_files.is_empty() {
+            self.print_growing_files(&growing_files);
+            let recheck = !self.interaction.prompts_options()
+                || Confirm::new()
+                    .with_prompt("是否在批次結束時重新檢查，並加入已完成寫入的檔案？")
+                    .default(true)
+                    .interact()?;
+            if recheck {
+                deferred = DeferredRecheckQueue::new(growing_files);
+            }
+        }
+
+        if pending_files.is_empty() && deferred.is_empty() {
+            report!(self.output, "{}", style("沒有可編碼的影片檔案").yellow());
+            scheduler.clear_state();
+            return Ok(RunStatus::Completed);
+        }
+
+        report!(self.output);
         // 顯示轉檔後處理設定
-        let encoder_settings = &self.config.settings.video_encoder;
         if encoder_settings.post_encode_action != crate::config::PostEncodeAction::None {
-            println!(
+            report!(
+                self.output,
                 "{}",
                 style(format!(
                     "轉檔後處理: {}",
@@ -84,88 +260,508 @@ impl VideoEncoder {
             );
         }
 
-        println!("{}", style("開始編碼任務...").cyan());
+        report!(
+            self.output,
+            "{}",
+            style(format!(
+                "編碼器: {}，後端: {}，CRF: {}，Preset: {}，音訊: {}",
+                encoder_settings.codec,
+                encoder_settings.backend,
+                encoder_settings.effective_crf(),
+                encoder_settings.preset,
+                encoder_settings.audio_mode
+            ))
+            .dim()
+        );
+        if let Some(output_dir) = &encoder_settings.output_dir {
+            report!(
+                self.output,
+                "{}",
+                style(format!("輸出資料夾: {}", output_dir.display())).dim()
+            );
+        }
 
-        let mut scheduler = TaskScheduler::new(
-            video_files,
-            &directory,
+        let required: u64 = pending_files.iter().map(|file| file.size).sum();
+        if !self.confirm_disk_space(directory, required)? {
+            scheduler.clear_state();
+            return Ok(RunStatus::Completed);
+        }
+        report!(self.output, "{}", style("開始編碼任務...").cyan());
+
+        if let Err(e) = scheduler.run() {
+            error!("編碼任務執行失敗: {e}");
+            return Err(e);
+        }
+
+        if !deferred.is_empty() && !scheduler.stopped_for_disk_space() {
+            skipped.still_growing = self.run_deferred_recheck(&mut scheduler, &mut deferred)?;
+        }
+        if !self.shutdown_signal.load(Ordering::SeqCst) && !scheduler.stopped_for_disk_space() {
+            scheduler.clear_state();
+        }
+        Self::write_reports(&scheduler, directory);
+
+        let summary = EncodeSummary::new(scheduler.tasks(), &skipped);
+        self.print_summary(&summary);
+        Ok(summary.status())
+    }
+
+    /// 偵測未完成的佇列狀態檔並詢問是否繼續
+    ///
+    /// 選擇不繼續時刪除狀態檔，改為重新掃描；不逐項詢問時一律繼續
+    fn prompt_resume(&self, directory: &Path) -> Result<Option<Vec<EncodingTask>>> {
+        let tasks = match load_queue_state(directory) {
+            Ok(Some(tasks)) => tasks,
+            Ok(None) => return Ok(None),
+            Err(e) => {
+                warn!("無法讀取佇列狀態，改為重新掃描: {e:#}");
+                return Ok(None);
+            }
+        };
+
+        let unfinished = tasks
+            .iter()
+            .filter(|t| matches!(t.status, TaskStatus::Pending | TaskStatus::Running))
+            .count();
+        if unfinished == 0 {
+            remove_queue_state(directory)?;
+            return Ok(None);
+        }
+
+        report!(
+            self.output,
+            "{}",
+            style(format!(
+                "偵測到未完成的編碼佇列：{} 個檔案中有 {unfinished} 個尚未完成",
+                tasks.len()
+            ))
+            .yellow()
+        );
+        let resume = !self.interaction.prompts_options()
+            || Confirm::new()
+                .with_prompt("是否從上次中斷處繼續？")
+                .default(true)
+                .interact()?;
+        if !resume {
+            remove_queue_state(directory)?;
+            return Ok(None);
+        }
+
+        Ok(Some(tasks))
+    }
+
+    /// 從狀態檔繼續上次中斷的佇列
+    fn resume_queue(&self, directory: &Path, mut tasks: Vec<EncodingTask>) -> Result<RunStatus> {
+        let report = reconcile_tasks(&mut tasks);
+        if report.already_encoded > 0 {
+            report!(
+                self.output,
+                "{}",
+                style(format!(
+                    "{} 個檔案的輸出已完整，標記為完成",
+                    report.already_encoded
+                ))
+                .dim()
+            );
+        }
+        if report.reset_running > 0 {
+            report!(
+                self.output,
+                "{}",
+                style(format!(
+                    "{} 個中斷的任務已刪除不完整輸出，將重新編碼",
+                    report.reset_running
+                ))
+                .dim()
+            );
+        }
+        if report.missing_source > 0 {
+            report!(
+                self.output,
+                "{}",
+                style(format!(
+                    "{} 個來源檔案已不存在，已移出佇列",
+                    report.missing_source
+                ))
+                .yellow()
+            );
+        }
+
+        let required: u64 = tasks
+            .iter()
+            .filter(|t| t.status == TaskStatus::Pending)
+            .filter_map(|t| t.source_size)
+            .sum();
+        if !self.confirm_disk_space(directory, required)? {
+            return Ok(RunStatus::Completed);
+        }
+
+        report!(self.output, "{}", style("繼續編碼任務...").cyan());
+        let mut scheduler = TaskScheduler::resume(
+            tasks,
+            directory,
             Arc::clone(&self.shutdown_signal),
-            encoder_settings,
-        )?;
+            &self.config.settings.video_encoder,
+        )?
+        .with_deletion_mode(self.config.settings.deletion_mode);
 
         if let Err(e) = scheduler.run() {
             error!("編碼任務執行失敗: {e}");
             return Err(e);
         }
+        if !self.shutdown_signal.load(Ordering::SeqCst) && !scheduler.stopped_for_disk_space() {
+            scheduler.clear_state();
+        }
+        Self::write_reports(&scheduler, directory);
 
-        self.print_summary(scheduler.tasks());
+        let summary = EncodeSummary::new(scheduler.tasks(), &SkipSummary::default());
+        self.print_summary(&summary);
+        Ok(summary.status())
+    }
 
-        Ok(())
+    /// 預估輸出所需空間（來源大小總和加上執行中的最低可用空間），不足時詢問是否仍要開始
+    ///
+    /// 無法取得可用空間時只記錄警告並繼續
+    fn confirm_disk_space(&self, directory: &Path, source_total: u64) -> Result<bool> {
+        let encoder_settings = &self.config.settings.video_encoder;
+        let target = encoder_settings.output_dir.as_deref().unwrap_or(directory);
+        let floor = encoder_settings
+            .min_free_space_mb
+            .saturating_mul(1024 * 1024);
+        let check = match check_space(target, source_total.saturating_add(floor)) {
+            Ok(check) => check,
+            Err(e) => {
+                warn!("無法檢查可用空間，略過檢查: {e:#}");
+                return Ok(true);
+            }
+        };
+        if check.is_sufficient() {
+            return Ok(true);
+        }
+
+        report!(
+            self.output,
+            "{}",
+            style(format!(
+                "輸出位置可用空間可能不足：預估需要 {:.2} GB，剩餘 {:.2} GB",
+                check.required as f64 / GIB,
+                check.available as f64 / GIB
+            ))
+            .yellow()
+        );
+        report!(
+            self.output,
+            "{}",
+            style("編碼中可用空間低於門檻時會停止新增任務，剩餘任務可稍後繼續").dim()
+        );
+        self.interaction.confirm("仍要開始編碼？", false)
     }
 
-    fn prompt_input_path(&self) -> Result<Option<String>> {
-        let recent_paths = &self.config.settings.recent_paths;
+    /// 在基準目錄寫入本次執行的編碼紀錄與容量報告，失敗時只記錄警告
+    fn write_reports(scheduler: &TaskScheduler, directory: &Path) {
+        let path = directory.join(encode_log_file_name(SystemTime::now()));
+        if let Err(e) = scheduler.write_log(&path) {
+            warn!("{e:#}");
+        }
 
-        // 如果沒有歷史路徑，直接輸入
-        if recent_paths.is_empty() {
-            let path: String = Input::new()
-                .with_prompt("請輸入影片資料夾路徑")
-                .interact_text()?;
-            return Ok(Some(path.trim().to_string()));
+        let report = SizeReport::from_tasks(scheduler.tasks());
+        if !report.is_empty()
+            && let Err(e) = report.write(&directory.join(SIZE_REPORT_FILE_NAME))
+        {
+            warn!("{e:#}");
         }
+    }
 
-        // 建立選項清單：歷史路徑 + 輸入新路徑
-        let mut options: Vec<String> = recent_paths
+    fn print_hevc_files(&self, tasks: &[EncodingTask]) {
+        let hevc_files: Vec<&EncodingTask> = tasks
             .iter()
-            .enumerate()
-            .map(|(i, p)| {
-                let exists = Path::new(p).exists();
-                let indicator = if exists { "✓" } else { "✗" };
-                format!("{} [{}] {}", i + 1, indicator, p)
-            })
+            .filter(|t| t.status == TaskStatus::Skipped)
             .collect();
-        options.push("輸入新路徑...".to_string());
-
-        println!("{}", style("(按 ESC 返回主選單)").dim());
-
-        let selection = Select::with_theme(&ColorfulTheme::default())
-            .with_prompt("請選擇路徑")
-            .items(&options)
-            .default(0)
-            .interact_opt()?;
-
-        match selection {
-            None => Ok(None),
-            Some(idx) if idx < recent_paths.len() => Ok(Some(recent_paths[idx].clone())),
-            Some(_) => {
-                let path: String = Input::new()
-                    .with_prompt("請輸入影片資料夾路徑")
-                    .interact_text()?;
-                Ok(Some(path.trim().to_string()))
+        if hevc_files.is_empty() {
+            return;
+        }
+
+        report!(self.output);
+        report!(
+            self.output,
+            "{}",
+            style(format!("{} 個檔案已是 HEVC，已跳過：", hevc_files.len())).yellow()
+        );
+        for task in hevc_files {
+            report!(
+                self.output,
+                "  {} {} {}",
+                style("✓").dim(),
+                task.source_path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy(),
+                style("(已是 HEVC — 已跳過)").dim()
+            );
+        }
+    }
+
+    /// 分離仍在寫入中的檔案（間隔為 0 時不檢查）
+    fn split_growing_files(
+        &self,
+        video_files: Vec<VideoFileInfo>,
+    ) -> (Vec<VideoFileInfo>, Vec<VideoFileInfo>) {
+        let interval_secs = self.config.settings.video_encoder.stability_check_secs;
+        if interval_secs == 0 {
+            return (video_files, Vec::new());
+        }
+
+        report!(
+            self.output,
+            "{}",
+            style(format!("檢查檔案是否仍在寫入中（{interval_secs} 秒）...")).dim()
+        );
+        let checker = StabilityChecker::new(Duration::from_secs(interval_secs));
+        let report = checker.check(video_files);
+        (report.stable, report.growing)
+    }
+
+    fn print_growing_files(&self, growing_files: &[VideoFileInfo]) {
+        report!(self.output);
+        report!(
+            self.output,
+            "{}",
+            style(format!(
+                "{} 個檔案仍在寫入中，已跳過：",
+                growing_files.len()
+            ))
+            .yellow()
+        );
+        for file in growing_files {
+            report!(
+                self.output,
+                "  {} {} {}",
+                style("⏸").yellow(),
+                file.path.file_name().unwrap_or_default().to_string_lossy(),
+                style("(仍在寫入中 — 已跳過)").dim()
+            );
+        }
+    }
+
+    /// 批次結束後重新檢查寫入中的檔案，將已穩定者加入佇列再執行一輪
+    ///
+    /// 回傳仍在寫入中的檔案數
+    fn run_deferred_recheck(
+        &self,
+        scheduler: &mut TaskScheduler,
+        deferred: &mut DeferredRecheckQueue,
+    ) -> Result<usize> {
+        if self.shutdown_signal.load(Ordering::SeqCst) {
+            return Ok(deferred.len());
+        }
+
+        report!(self.output);
+        report!(
+            self.output,
+            "{}",
+            style(format!("重新檢查 {} 個寫入中的檔案...", deferred.len())).cyan()
+        );
+        let interval = Duration::from_secs(self.config.settings.video_encoder.stability_check_secs);
+        let ready = deferred.recheck(&StabilityChecker::new(interval));
+
+        if ready.is_empty() {
+            report!(self.output, "{}", style("沒有檔案完成寫入").yellow());
+            return Ok(deferred.len());
+        }
+
+        report!(
+            self.output,
+            "{}",
+            style(format!("{} 個檔案已完成寫入，加入佇列", ready.len())).green()
+        );
+        scheduler.enqueue(&ready);
+        if let Err(e) = scheduler.run() {
+            error!("編碼任務執行失敗: {e}");
+            return Err(e);
+        }
+
+        Ok(deferred.len())
+    }
+
+    fn prompt_input_path(&self, settings: &mut UserSettings) -> Result<Option<String>> {
+        prompt_directory(
+            settings,
+            PathFeature::VideoEncoder,
+            "請輸入影片資料夾路徑",
+            &self.output,
+        )
+    }
+
+    fn print_summary(&self, summary: &EncodeSummary) {
+        info!(
+            "編碼任務完成 - 成功: {}（重試後成功 {}）, 失敗: {}, 保留原檔: {}, HEVC 跳過: {}, 寫入中跳過: {}",
+            summary.completed,
+            summary.retried,
+            summary.failed,
+            summary.kept_original,
+            summary.already_hevc,
+            summary.still_growing
+        );
+        if self.output.emit_json(summary) {
+            return;
+        }
+
+        report!(self.output);
+        report!(
+            self.output,
+            "{}",
+            style("=== 編碼任務摘要 ===").cyan().bold()
+        );
+        report!(self.output, "  總計: {} 個檔案", summary.total);
+        report!(
+            self.output,
+            "  成功: {} 個",
+            style(summary.completed).green()
+        );
+        if summary.retried > 0 {
+            report!(
+                self.output,
+                "    其中重試後成功: {} 個",
+                style(summary.retried).yellow()
+            );
+        }
+        if summary.kept_original > 0 {
+            report!(
+                self.output,
+                "  保留原檔（編碼後較大）: {} 個",
+                style(summary.kept_original).yellow()
+            );
+        }
+        if summary.already_hevc > 0 {
+            report!(
+                self.output,
+                "  已是 HEVC（已跳過）: {} 個",
+                style(summary.already_hevc).yellow()
+            );
+        }
+        if summary.still_growing > 0 {
+            report!(
+                self.output,
+                "  仍在寫入中（已跳過）: {} 個",
+                style(summary.still_growing).yellow()
+            );
+        }
+        if summary.unfinished > 0 {
+            report!(
+                self.output,
+                "  尚未處理（已保留在佇列）: {} 個",
+                style(summary.unfinished).yellow()
+            );
+        }
+        if summary.failed > 0 {
+            report!(self.output, "  失敗: {} 個", style(summary.failed).red());
+            report!(self.output);
+            report!(
+                self.output,
+                "{}",
+                style("失敗的檔案已移動到 fail 資料夾").yellow()
+            );
+            if !summary.failed_logs.is_empty() {
+                report!(self.output, "{}", style("ffmpeg 錯誤記錄:").yellow());
+                for log_path in &summary.failed_logs {
+                    report!(self.output, "  {}", log_path.display());
+                }
             }
         }
+
+        self.print_size_report(&summary.size_report);
+        self.output.report_log_file();
     }
 
-    fn print_summary(&self, tasks: &[EncodingTask]) {
-        let completed = tasks
-            .iter()
-            .filter(|t| t.status == TaskStatus::Completed)
-            .count();
-        let failed = tasks
-            .iter()
-            .filter(|t| t.status == TaskStatus::Failed)
-            .count();
+    /// 顯示每個檔案與整批的編碼前後大小
+    fn print_size_report(&self, report: &SizeReport) {
+        if report.is_empty() {
+            return;
+        }
 
-        println!();
-        println!("{}", style("=== 編碼任務摘要 ===").cyan().bold());
-        println!("  總計: {} 個檔案", tasks.len());
-        println!("  成功: {} 個", style(completed).green());
-        if failed > 0 {
-            println!("  失敗: {} 個", style(failed).red());
-            println!();
-            println!("{}", style("失敗的檔案已移動到 fail 資料夾").yellow());
+        report!(self.output);
+        report!(self.output, "{}", style("=== 容量變化 ===").cyan().bold());
+        for entry in &report.entries {
+            let line = format!(
+                "  {}: {:.2} MB → {:.2} MB ({:+.1}%)",
+                entry
+                    .source
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy(),
+                entry.source_size as f64 / 1024.0 / 1024.0,
+                entry.output_size as f64 / 1024.0 / 1024.0,
+                -entry.saved_percent
+            );
+            if entry.is_larger() {
+                report!(
+                    self.output,
+                    "{} {}",
+                    style(line).yellow(),
+                    style("⚠ 編碼後較大").yellow()
+                );
+            } else {
+                report!(self.output, "{line}");
+            }
         }
+        report!(
+            self.output,
+            "  總計: {:.2} MB → {:.2} MB，節省 {}",
+            report.total_source_size as f64 / 1024.0 / 1024.0,
+            report.total_output_size as f64 / 1024.0 / 1024.0,
+            style(format!("{:.1}%", report.saved_percent)).green()
+        );
+        if report.bytes_reclaimed > 0 {
+            report!(
+                self.output,
+                "  已釋放: {}",
+                style(format_size(report.bytes_reclaimed)).green()
+            );
+        }
+
+        let larger = report.larger_entries().count();
+        if larger > 0 {
+            report!(self.output);
+            report!(
+                self.output,
+                "{}",
+                style(format!(
+                    "{larger} 個檔案編碼後比原始檔案大，可考慮開啟「較大時保留原檔」"
+                ))
+                .yellow()
+            );
+        }
+    }
+}
 
-        info!("編碼任務完成 - 成功: {completed}, 失敗: {failed}");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_summary_json_schema() {
+        let summary = EncodeSummary::new(&[], &SkipSummary { still_growing: 1 });
+        assert_eq!(
+            serde_json::to_value(&summary).unwrap(),
+            json!({
+                "total": 0,
+                "completed": 0,
+                "retried": 0,
+                "kept_original": 0,
+                "already_hevc": 0,
+                "still_growing": 1,
+                "unfinished": 0,
+                "failed": 0,
+                "failed_logs": [],
+                "size_report": {
+                    "entries": [],
+                    "total_source_size": 0,
+                    "total_output_size": 0,
+                    "bytes_reclaimed": 0,
+                    "saved_percent": 0.0
+                }
+            })
+        );
     }
 }
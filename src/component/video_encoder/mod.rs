@@ -3,11 +3,28 @@
 //! 使用 ffmpeg 將影片轉換為 HEVC/x265 格式
 
 mod cpu_monitor;
+mod encode_log;
 mod ffmpeg_command;
+mod keyboard;
 mod main;
+mod queue_state;
+mod size_report;
+mod stability_checker;
 mod task_scheduler;
 
-pub use cpu_monitor::CpuMonitor;
+pub use cpu_monitor::{
+    CpuMonitor, MemoryReading, MemoryThreshold, SysinfoStatSource, SystemStatSource,
+};
+pub use encode_log::{encode_log_file_name, write_encode_log};
 pub use ffmpeg_command::FfmpegCommand;
 pub use main::VideoEncoder;
+pub use queue_state::{
+    QUEUE_STATE_FILE_NAME, ReconcileReport, load_queue_state, reconcile_tasks, remove_queue_state,
+    save_queue_state,
+};
+pub use size_report::{SIZE_REPORT_FILE_NAME, SizeReport, SizeReportEntry};
+pub use stability_checker::{
+    DeferredRecheckQueue, FileSnapshot, FileStatSource, FsStatSource, StabilityChecker,
+    StabilityReport,
+};
 pub use task_scheduler::{EncodingTask, TaskScheduler, TaskStatus};
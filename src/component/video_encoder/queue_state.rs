@@ -0,0 +1,234 @@
+//! 編碼佇列狀態檔
+//!
+//! 每次任務狀態變更時將佇列寫入基準目錄的 `.encode_queue.json`，
+//! 程式中斷（斷電、重開機）後可從狀態檔繼續未完成的任務。
+
+use super::task_scheduler::{EncodingTask, TaskStatus};
+use anyhow::{Context, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 佇列狀態檔名稱
+pub const QUEUE_STATE_FILE_NAME: &str = ".encode_queue.json";
+
+/// 輸出檔有效的最小大小（位元組）
+const MIN_VALID_OUTPUT_SIZE: u64 = 1024;
+
+/// 寫入用的佇列狀態（借用任務清單，避免複製）
+#[derive(Serialize)]
+struct QueueStateRef<'a> {
+    tasks: &'a [EncodingTask],
+}
+
+/// 讀取用的佇列狀態
+#[derive(Deserialize)]
+struct QueueState {
+    tasks: Vec<EncodingTask>,
+}
+
+/// 續傳前的整理結果
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ReconcileReport {
+    /// 輸出檔已完整，直接標記為完成的任務數
+    pub already_encoded: usize,
+    /// 上次中斷時執行中、已重設為等待的任務數
+    pub reset_running: usize,
+    /// 來源檔已不存在而移除的任務數
+    pub missing_source: usize,
+}
+
+/// 狀態檔路徑
+#[must_use]
+pub fn queue_state_path(base_directory: &Path) -> PathBuf {
+    base_directory.join(QUEUE_STATE_FILE_NAME)
+}
+
+/// 讀取狀態檔，不存在時回傳 `None`
+pub fn load_queue_state(base_directory: &Path) -> Result<Option<Vec<EncodingTask>>> {
+    let path = queue_state_path(base_directory);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("無法讀取佇列狀態檔: {}", path.display()))?;
+    let state: QueueState = serde_json::from_str(&content)
+        .with_context(|| format!("佇列狀態檔格式錯誤: {}", path.display()))?;
+    Ok(Some(state.tasks))
+}
+
+/// 寫入狀態檔
+///
+/// 先寫入暫存檔再改名，避免寫到一半中斷留下損壞的狀態檔
+pub fn save_queue_state(base_directory: &Path, tasks: &[EncodingTask]) -> Result<()> {
+    let path = queue_state_path(base_directory);
+    let temp_path = path.with_extension("json.tmp");
+    let content = serde_json::to_string_pretty(&QueueStateRef { tasks })?;
+
+    fs::write(&temp_path, content)
+        .with_context(|| format!("無法寫入佇列狀態檔: {}", temp_path.display()))?;
+    fs::rename(&temp_path, &path)
+        .with_context(|| format!("無法更新佇列狀態檔: {}", path.display()))?;
+    Ok(())
+}
+
+/// 刪除狀態檔（不存在時忽略）
+pub fn remove_queue_state(base_directory: &Path) -> Result<()> {
+    let path = queue_state_path(base_directory);
+    if path.exists() {
+        fs::remove_file(&path)
+            .with_context(|| format!("無法刪除佇列狀態檔: {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// 輸出檔是否存在且有效（大於 1KB）
+#[must_use]
+pub fn is_valid_output(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|m| m.is_file() && m.len() > MIN_VALID_OUTPUT_SIZE)
+}
+
+/// 整理上次中斷留下的佇列
+///
+/// - 執行中的任務：刪除不完整的輸出檔並重設為等待
+/// - 等待中且輸出檔已完整：標記為完成，不重新編碼
+/// - 等待中但來源檔已不存在：移出佇列
+pub fn reconcile_tasks(tasks: &mut Vec<EncodingTask>) -> ReconcileReport {
+    let mut report = ReconcileReport::default();
+
+    for task in tasks.iter_mut() {
+        if task.status == TaskStatus::Running {
+            if task.destination_path.exists() {
+                match fs::remove_file(&task.destination_path) {
+                    Ok(()) => info!("已刪除中斷的輸出檔案: {}", task.destination_path.display()),
+                    Err(e) => warn!(
+                        "無法刪除中斷的輸出檔案 {}: {e}",
+                        task.destination_path.display()
+                    ),
+                }
+            }
+            task.status = TaskStatus::Pending;
+            report.reset_running += 1;
+        } else if task.status == TaskStatus::Pending && is_valid_output(&task.destination_path) {
+            task.status = TaskStatus::Completed;
+            report.already_encoded += 1;
+        }
+    }
+
+    tasks.retain(|task| {
+        let keep = task.status != TaskStatus::Pending || task.source_path.exists();
+        if !keep {
+            warn!("來源檔案已不存在，移出佇列: {}", task.source_path.display());
+            report.missing_source += 1;
+        }
+        keep
+    });
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn task(dir: &Path, name: &str, status: TaskStatus) -> EncodingTask {
+        EncodingTask {
+            source_path: dir.join(format!("{name}.mp4")),
+            destination_path: dir.join(format!("{name}.convert.mkv")),
+            duration_ms: Some(60_000),
+            status,
+            error_message: None,
+            attempts: 0,
+            source_size: None,
+            output_size: None,
+            elapsed_ms: None,
+            log_path: None,
+        }
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let dir = TempDir::new().unwrap();
+        assert!(load_queue_state(dir.path()).unwrap().is_none());
+
+        let mut failed = task(dir.path(), "b", TaskStatus::Failed);
+        failed.error_message = Some("broken".to_string());
+        let tasks = vec![
+            task(dir.path(), "a", TaskStatus::Running),
+            failed,
+            task(dir.path(), "c", TaskStatus::Skipped),
+        ];
+        save_queue_state(dir.path(), &tasks).unwrap();
+
+        let content = fs::read_to_string(queue_state_path(dir.path())).unwrap();
+        assert!(content.contains("\"status\": \"running\""));
+
+        let loaded = load_queue_state(dir.path()).unwrap().unwrap();
+        assert_eq!(loaded.len(), 3);
+        assert_eq!(loaded[0].source_path, tasks[0].source_path);
+        assert_eq!(loaded[0].destination_path, tasks[0].destination_path);
+        assert_eq!(loaded[0].status, TaskStatus::Running);
+        assert_eq!(loaded[0].duration_ms, Some(60_000));
+        assert_eq!(loaded[1].status, TaskStatus::Failed);
+        assert_eq!(loaded[1].error_message.as_deref(), Some("broken"));
+        assert_eq!(loaded[2].status, TaskStatus::Skipped);
+
+        remove_queue_state(dir.path()).unwrap();
+        assert!(load_queue_state(dir.path()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_reconcile_tasks() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path();
+
+        // 中斷時執行中：輸出不完整
+        let running = task(path, "running", TaskStatus::Running);
+        fs::write(&running.source_path, b"source").unwrap();
+        fs::write(&running.destination_path, vec![0u8; 4096]).unwrap();
+
+        // 等待中但輸出已完整
+        let encoded = task(path, "encoded", TaskStatus::Pending);
+        fs::write(&encoded.source_path, b"source").unwrap();
+        fs::write(&encoded.destination_path, vec![0u8; 4096]).unwrap();
+
+        // 等待中且輸出太小，仍需編碼
+        let pending = task(path, "pending", TaskStatus::Pending);
+        fs::write(&pending.source_path, b"source").unwrap();
+        fs::write(&pending.destination_path, b"tiny").unwrap();
+
+        // 等待中但來源已消失
+        let missing = task(path, "missing", TaskStatus::Pending);
+
+        let completed = task(path, "done", TaskStatus::Completed);
+        let skipped = task(path, "hevc", TaskStatus::Skipped);
+
+        let mut tasks = vec![running, encoded, pending, missing, completed, skipped];
+        let report = reconcile_tasks(&mut tasks);
+
+        assert_eq!(
+            report,
+            ReconcileReport {
+                already_encoded: 1,
+                reset_running: 1,
+                missing_source: 1,
+            }
+        );
+        let statuses: Vec<TaskStatus> = tasks.iter().map(|t| t.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                TaskStatus::Pending,
+                TaskStatus::Completed,
+                TaskStatus::Pending,
+                TaskStatus::Completed,
+                TaskStatus::Skipped,
+            ]
+        );
+        assert!(!path.join("running.convert.mkv").exists());
+        assert!(path.join("encoded.convert.mkv").exists());
+    }
+}
@@ -0,0 +1,159 @@
+//! 編碼前後容量統計
+//!
+//! 統計成功編碼的檔案在編碼前後的大小與節省比例，
+//! 並寫入基準目錄的 `encode_report.json` 供事後分析。
+
+use super::task_scheduler::{EncodingTask, TaskStatus};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 容量報告檔名稱
+pub const SIZE_REPORT_FILE_NAME: &str = "encode_report.json";
+
+/// 單一檔案的容量變化
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SizeReportEntry {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    pub source_size: u64,
+    pub output_size: u64,
+    /// 節省比例（%），負值代表編碼後變大
+    pub saved_percent: f64,
+}
+
+impl SizeReportEntry {
+    /// 編碼後是否比原始檔案大
+    #[must_use]
+    pub const fn is_larger(&self) -> bool {
+        self.output_size > self.source_size
+    }
+}
+
+/// 整批編碼的容量變化
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SizeReport {
+    pub entries: Vec<SizeReportEntry>,
+    pub total_source_size: u64,
+    pub total_output_size: u64,
+    /// 釋放的空間（編碼後整批變大時為 0）
+    pub bytes_reclaimed: u64,
+    pub saved_percent: f64,
+}
+
+impl SizeReport {
+    /// 統計成功且大小已知的任務
+    #[must_use]
+    pub fn from_tasks(tasks: &[EncodingTask]) -> Self {
+        let entries: Vec<SizeReportEntry> = tasks
+            .iter()
+            .filter(|task| task.status == TaskStatus::Completed)
+            .filter_map(|task| {
+                let source_size = task.source_size?;
+                let output_size = task.output_size?;
+                Some(SizeReportEntry {
+                    source: task.source_path.clone(),
+                    destination: task.destination_path.clone(),
+                    source_size,
+                    output_size,
+                    saved_percent: saved_percent(source_size, output_size),
+                })
+            })
+            .collect();
+
+        let total_source_size = entries.iter().map(|e| e.source_size).sum();
+        let total_output_size = entries.iter().map(|e| e.output_size).sum();
+        Self {
+            entries,
+            total_source_size,
+            total_output_size,
+            bytes_reclaimed: total_source_size.saturating_sub(total_output_size),
+            saved_percent: saved_percent(total_source_size, total_output_size),
+        }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 編碼後變大的檔案
+    pub fn larger_entries(&self) -> impl Iterator<Item = &SizeReportEntry> {
+        self.entries.iter().filter(|e| e.is_larger())
+    }
+
+    /// 寫入 JSON 報告
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content).with_context(|| format!("無法寫入容量報告: {}", path.display()))
+    }
+}
+
+/// 節省比例（%），原始大小為 0 時回傳 0
+fn saved_percent(before: u64, after: u64) -> f64 {
+    if before == 0 {
+        return 0.0;
+    }
+    (before as f64 - after as f64) / before as f64 * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn task(name: &str, status: TaskStatus, sizes: Option<(u64, u64)>) -> EncodingTask {
+        EncodingTask {
+            source_path: PathBuf::from(format!("/videos/{name}.mp4")),
+            destination_path: PathBuf::from(format!("/videos/{name}.convert.mkv")),
+            duration_ms: None,
+            status,
+            error_message: None,
+            attempts: 1,
+            source_size: sizes.map(|(source, _)| source),
+            output_size: sizes.map(|(_, output)| output),
+            elapsed_ms: None,
+            log_path: None,
+        }
+    }
+
+    #[test]
+    fn test_size_report_totals() {
+        let tasks = vec![
+            task("a", TaskStatus::Completed, Some((1000, 400))),
+            task("b", TaskStatus::Completed, Some((1000, 1200))),
+            task("c", TaskStatus::Failed, Some((5000, 0))),
+            task("d", TaskStatus::Completed, None),
+        ];
+
+        let report = SizeReport::from_tasks(&tasks);
+        assert_eq!(report.entries.len(), 2);
+        assert_eq!(report.total_source_size, 2000);
+        assert_eq!(report.total_output_size, 1600);
+        assert_eq!(report.bytes_reclaimed, 400);
+        assert!((report.saved_percent - 20.0).abs() < f64::EPSILON);
+        assert!((report.entries[0].saved_percent - 60.0).abs() < f64::EPSILON);
+        assert!((report.entries[1].saved_percent + 20.0).abs() < f64::EPSILON);
+
+        let larger: Vec<_> = report.larger_entries().collect();
+        assert_eq!(larger.len(), 1);
+        assert_eq!(larger[0].source, PathBuf::from("/videos/b.mp4"));
+    }
+
+    #[test]
+    fn test_size_report_write() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(SIZE_REPORT_FILE_NAME);
+        let report = SizeReport::from_tasks(&[task("a", TaskStatus::Completed, Some((800, 200)))]);
+
+        report.write(&path).unwrap();
+
+        let value: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(value["total_source_size"], 800);
+        assert_eq!(value["total_output_size"], 200);
+        assert_eq!(value["entries"][0]["saved_percent"], 75.0);
+        assert!(SizeReport::from_tasks(&[]).is_empty());
+    }
+}
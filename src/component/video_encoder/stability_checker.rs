@@ -0,0 +1,299 @@
+//! 寫入中檔案偵測
+//!
+//! 錄影軟體（例如 OBS）仍在寫入的檔案若被編碼，只會得到截斷的結果，
+//! 且轉檔後處理可能在錄影途中把原始檔案移走。
+//! 因此在排入佇列前先取樣兩次檔案狀態，大小與修改時間皆相同才視為穩定。
+
+use crate::tools::VideoFileInfo;
+use anyhow::{Context, Result};
+use log::{debug, info};
+use rayon::prelude::*;
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// 檔案狀態快照
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileSnapshot {
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+/// 檔案狀態來源（可替換為測試替身）
+pub trait FileStatSource: Sync {
+    fn snapshot(&self, path: &Path) -> Result<FileSnapshot>;
+}
+
+/// 從檔案系統讀取檔案狀態
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FsStatSource;
+
+impl FileStatSource for FsStatSource {
+    fn snapshot(&self, path: &Path) -> Result<FileSnapshot> {
+        let metadata =
+            fs::metadata(path).with_context(|| format!("無法讀取檔案狀態: {}", path.display()))?;
+        Ok(FileSnapshot {
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+/// 穩定性檢查結果
+#[derive(Debug, Default)]
+pub struct StabilityReport {
+    /// 大小與修改時間未變動的檔案
+    pub stable: Vec<VideoFileInfo>,
+    /// 仍在寫入中的檔案
+    pub growing: Vec<VideoFileInfo>,
+}
+
+/// 判斷兩次快照是否代表檔案已停止寫入
+#[must_use]
+pub fn is_stable(first: &FileSnapshot, second: &FileSnapshot) -> bool {
+    first == second
+}
+
+/// 檔案穩定性檢查器
+pub struct StabilityChecker<S: FileStatSource = FsStatSource> {
+    stat_source: S,
+    interval: Duration,
+}
+
+impl StabilityChecker<FsStatSource> {
+    #[must_use]
+    pub const fn new(interval: Duration) -> Self {
+        Self::with_stat_source(FsStatSource, interval)
+    }
+}
+
+impl<S: FileStatSource> StabilityChecker<S> {
+    #[must_use]
+    pub const fn with_stat_source(stat_source: S, interval: Duration) -> Self {
+        Self {
+            stat_source,
+            interval,
+        }
+    }
+
+    /// 檢查所有候選檔案
+    ///
+    /// 所有檔案同時取樣、只等待一次間隔，
+    /// 因此啟動時間不會隨檔案數量線性增加。
+    pub fn check(&self, files: Vec<VideoFileInfo>) -> StabilityReport {
+        if files.is_empty() {
+            return StabilityReport::default();
+        }
+
+        let first: Vec<Option<FileSnapshot>> = self.snapshot_all(&files);
+        thread::sleep(self.interval);
+        let second: Vec<Option<FileSnapshot>> = self.snapshot_all(&files);
+
+        let mut report = StabilityReport::default();
+        for ((mut file, before), after) in files.into_iter().zip(first).zip(second) {
+            match (before, after) {
+                (Some(before), Some(after)) if is_stable(&before, &after) => {
+                    file.size = after.size;
+                    report.stable.push(file);
+                }
+                _ => {
+                    debug!("檔案仍在寫入中: {}", file.path.display());
+                    report.growing.push(file);
+                }
+            }
+        }
+
+        info!(
+            "穩定性檢查完成 - 穩定: {}, 寫入中: {}",
+            report.stable.len(),
+            report.growing.len()
+        );
+
+        report
+    }
+
+    fn snapshot_all(&self, files: &[VideoFileInfo]) -> Vec<Option<FileSnapshot>> {
+        files
+            .par_iter()
+            .map(|file| self.stat_source.snapshot(&file.path).ok())
+            .collect()
+    }
+}
+
+/// 延後重新檢查的佇列
+///
+/// 保存首次檢查時仍在寫入的檔案，待批次結束後再檢查一次
+#[derive(Debug, Default)]
+pub struct DeferredRecheckQueue {
+    files: Vec<VideoFileInfo>,
+}
+
+impl DeferredRecheckQueue {
+    #[must_use]
+    pub const fn new(files: Vec<VideoFileInfo>) -> Self {
+        Self { files }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    #[must_use]
+    pub fn files(&self) -> &[VideoFileInfo] {
+        &self.files
+    }
+
+    /// 重新檢查佇列中的檔案，回傳已穩定的檔案，仍在寫入的留在佇列中
+    pub fn recheck<S: FileStatSource>(
+        &mut self,
+        checker: &StabilityChecker<S>,
+    ) -> Vec<VideoFileInfo> {
+        let report = checker.check(std::mem::take(&mut self.files));
+        self.files = report.growing;
+        report.stable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+
+    /// 模擬檔案狀態：每次取樣時依設定讓檔案成長
+    struct FakeStatSource {
+        /// path -> (目前大小, 剩餘成長次數)
+        files: Mutex<HashMap<PathBuf, (u64, usize)>>,
+    }
+
+    impl FakeStatSource {
+        fn new(entries: &[(&str, usize)]) -> Self {
+            let files = entries
+                .iter()
+                .map(|(name, growth)| (PathBuf::from(name), (1000, *growth)))
+                .collect();
+            Self {
+                files: Mutex::new(files),
+            }
+        }
+    }
+
+    impl FileStatSource for FakeStatSource {
+        fn snapshot(&self, path: &Path) -> Result<FileSnapshot> {
+            let mut files = self.files.lock().unwrap();
+            let entry = files
+                .get_mut(path)
+                .ok_or_else(|| anyhow::anyhow!("檔案不存在: {}", path.display()))?;
+            if entry.1 > 0 {
+                entry.0 += 500;
+                entry.1 -= 1;
+            }
+            Ok(FileSnapshot {
+                size: entry.0,
+                modified: None,
+            })
+        }
+    }
+
+    fn video(name: &str) -> VideoFileInfo {
+        VideoFileInfo {
+            path: PathBuf::from(name),
+            size: 1000,
+            duration_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_is_stable() {
+        let now = SystemTime::now();
+        let a = FileSnapshot {
+            size: 10,
+            modified: Some(now),
+        };
+        assert!(is_stable(&a, &a));
+
+        let grown = FileSnapshot { size: 20, ..a };
+        assert!(!is_stable(&a, &grown));
+
+        let touched = FileSnapshot {
+            modified: Some(now + Duration::from_secs(1)),
+            ..a
+        };
+        assert!(!is_stable(&a, &touched));
+    }
+
+    #[test]
+    fn test_check_separates_growing_files() {
+        let source = FakeStatSource::new(&[("/v/done.mp4", 0), ("/v/recording.mp4", usize::MAX)]);
+        let checker = StabilityChecker::with_stat_source(source, Duration::ZERO);
+
+        let report = checker.check(vec![video("/v/done.mp4"), video("/v/recording.mp4")]);
+
+        assert_eq!(report.stable.len(), 1);
+        assert_eq!(report.stable[0].path, PathBuf::from("/v/done.mp4"));
+        assert_eq!(report.growing.len(), 1);
+        assert_eq!(report.growing[0].path, PathBuf::from("/v/recording.mp4"));
+    }
+
+    #[test]
+    fn test_check_treats_missing_file_as_unstable() {
+        let source = FakeStatSource::new(&[]);
+        let checker = StabilityChecker::with_stat_source(source, Duration::ZERO);
+
+        let report = checker.check(vec![video("/v/gone.mp4")]);
+
+        assert!(report.stable.is_empty());
+        assert_eq!(report.growing.len(), 1);
+    }
+
+    #[test]
+    fn test_check_waits_once_for_all_files() {
+        let entries: Vec<(String, usize)> = (0..20).map(|i| (format!("/v/{i}.mp4"), 0)).collect();
+        let refs: Vec<(&str, usize)> = entries.iter().map(|(n, g)| (n.as_str(), *g)).collect();
+        let source = FakeStatSource::new(&refs);
+        let checker = StabilityChecker::with_stat_source(source, Duration::from_millis(50));
+
+        let files = entries.iter().map(|(n, _)| video(n)).collect();
+        let started = std::time::Instant::now();
+        let report = checker.check(files);
+
+        assert_eq!(report.stable.len(), 20);
+        assert!(started.elapsed() < Duration::from_millis(50 * 5));
+    }
+
+    #[test]
+    fn test_deferred_queue_adds_file_that_stabilizes() {
+        // 前兩次取樣仍在成長，之後停止
+        let source = FakeStatSource::new(&[("/v/late.mp4", 2), ("/v/live.mp4", usize::MAX)]);
+        let checker = StabilityChecker::with_stat_source(source, Duration::ZERO);
+
+        let report = checker.check(vec![video("/v/late.mp4"), video("/v/live.mp4")]);
+        assert!(report.stable.is_empty());
+
+        let mut queue = DeferredRecheckQueue::new(report.growing);
+        assert_eq!(queue.len(), 2);
+
+        let ready = queue.recheck(&checker);
+        assert_eq!(ready.len(), 1);
+        assert_eq!(ready[0].path, PathBuf::from("/v/late.mp4"));
+        assert_eq!(ready[0].size, 2000);
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.files()[0].path, PathBuf::from("/v/live.mp4"));
+    }
+
+    #[test]
+    fn test_check_empty_input() {
+        let checker = StabilityChecker::with_stat_source(FakeStatSource::new(&[]), Duration::ZERO);
+        let report = checker.check(Vec::new());
+        assert!(report.stable.is_empty());
+        assert!(report.growing.is_empty());
+    }
+}
@@ -1,48 +1,348 @@
-use super::cpu_monitor::CpuMonitor;
+use super::cpu_monitor::{CpuMonitor, MemoryThreshold};
+use super::encode_log::write_encode_log;
 use super::ffmpeg_command::FfmpegCommand;
-use crate::config::{PostEncodeAction, VideoEncoderSettings};
-use crate::tools::{VideoFileInfo, ensure_directory_exists};
+use super::keyboard::{KeyCommand, KeyboardControl, resume_process, suspend_process};
+use super::queue_state::{is_valid_output, remove_queue_state, save_queue_state};
+use crate::config::{
+    AudioMode, DeletionMode, EncodeBackend, EncoderCodec, PostEncodeAction, VideoEncoderSettings,
+};
+use crate::tools::{
+    VideoFileInfo, available_space, dispose_file, dispose_file_as, ensure_directory_exists,
+    get_video_info, move_file, remove_or_trash, unique_destination,
+};
 use anyhow::{Context, Result};
-use console::Term;
-use log::{error, info, warn};
+use console::{Term, style};
+use log::{debug, error, info, warn};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::io::{BufRead, BufReader};
+use std::fs::{File, FileTimes};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
-use std::process::{Child, ChildStdout, Stdio};
+use std::process::{Child, ChildStdin, ChildStdout, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use std::{fs, thread};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TaskStatus {
     Pending,
     Running,
     Completed,
     Failed,
+    /// 已是 HEVC，不需重新編碼
+    Skipped,
+    /// 編碼後檔案比原始檔案大，已刪除輸出並保留原檔
+    KeptOriginal,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct EncodingTask {
     pub source_path: PathBuf,
     pub destination_path: PathBuf,
     pub duration_ms: Option<u64>,
     pub status: TaskStatus,
     pub error_message: Option<String>,
+    /// 已嘗試編碼的次數
+    #[serde(default)]
+    pub attempts: u32,
+    /// 來源檔案大小（位元組）
+    #[serde(default)]
+    pub source_size: Option<u64>,
+    /// 編碼完成時的輸出檔案大小（位元組）
+    #[serde(default)]
+    pub output_size: Option<u64>,
+    /// 累計編碼耗時（毫秒，含重試）
+    #[serde(default)]
+    pub elapsed_ms: Option<u64>,
+    /// ffmpeg 錯誤輸出的記錄檔
+    #[serde(default)]
+    pub log_path: Option<PathBuf>,
 }
 
 impl EncodingTask {
+    /// 建立任務，指定 `output_dir` 時輸出寫到該資料夾
+    ///
+    /// 輸出資料夾下保留來源相對於 `base_directory` 的子資料夾結構，
+    /// 避免不同資料夾的同名來源寫到同一個輸出檔
     #[must_use]
-    pub fn new(video_info: &VideoFileInfo) -> Self {
-        let ffmpeg_cmd = FfmpegCommand::new(&video_info.path);
+    pub fn new(
+        video_info: &VideoFileInfo,
+        output_dir: Option<&Path>,
+        base_directory: &Path,
+    ) -> Self {
+        let mut ffmpeg_cmd = FfmpegCommand::new(&video_info.path);
+        if let Some(output_dir) = output_dir {
+            let output_dir = mirrored_output_dir(output_dir, base_directory, &video_info.path);
+            ffmpeg_cmd = ffmpeg_cmd.with_output_dir(&output_dir);
+        }
         Self {
             source_path: video_info.path.clone(),
             destination_path: ffmpeg_cmd.destination_path().to_path_buf(),
             duration_ms: video_info.duration_ms,
             status: TaskStatus::Pending,
             error_message: None,
+            attempts: 0,
+            source_size: Some(video_info.size),
+            output_size: None,
+            elapsed_ms: None,
+            log_path: None,
         }
     }
+
+    /// 開始新的一次編碼嘗試
+    pub const fn start_attempt(&mut self) {
+        self.attempts += 1;
+        self.status = TaskStatus::Running;
+    }
+
+    /// 取消被中斷的嘗試：重設為等待，且不計入嘗試次數
+    pub const fn cancel_attempt(&mut self) {
+        self.attempts = self.attempts.saturating_sub(1);
+        self.status = TaskStatus::Pending;
+    }
+
+    /// 記錄一次失敗
+    ///
+    /// 嘗試次數未超過 `max_retries + 1` 時重設為等待並回傳 `true`，否則標記為失敗
+    pub fn record_failure(&mut self, message: String, max_retries: u32) -> bool {
+        self.error_message = Some(message);
+        if self.attempts <= max_retries {
+            self.status = TaskStatus::Pending;
+            true
+        } else {
+            self.status = TaskStatus::Failed;
+            false
+        }
+    }
+
+    /// 累加一次編碼嘗試的耗時
+    pub fn add_elapsed(&mut self, elapsed: Duration) {
+        let elapsed_ms = u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX);
+        self.elapsed_ms = Some(self.elapsed_ms.unwrap_or(0).saturating_add(elapsed_ms));
+    }
+
+    /// 是否在重試後才成功
+    #[must_use]
+    pub fn succeeded_after_retry(&self) -> bool {
+        self.status == TaskStatus::Completed && self.attempts > 1
+    }
+}
+
+/// 判斷是否編碼到結尾的容許誤差（毫秒）
+const END_TOLERANCE_MS: u64 = 500;
+
+/// 判斷已編碼長度是否到達影片結尾（容許 0.5 秒誤差）
+///
+/// 送出 `q` 後 ffmpeg 會正常收尾，但輸出只包含已編碼的部分，
+/// 因此只有長度已到結尾的輸出才能視為完成；長度未知時一律視為未完成
+const fn encoded_to_end(current_ms: u64, total_ms: Option<u64>) -> bool {
+    match total_ms {
+        Some(total) => current_ms.saturating_add(END_TOLERANCE_MS) >= total,
+        None => false,
+    }
+}
+
+/// 刪除失敗任務留下的不完整輸出（重試前呼叫）
+fn remove_partial_output(path: &Path) {
+    if !path.exists() {
+        return;
+    }
+    match fs::remove_file(path) {
+        Ok(()) => info!("已刪除失敗的輸出檔案: {}", path.display()),
+        Err(e) => warn!("無法刪除失敗的輸出檔案 {}: {e}", path.display()),
+    }
+}
+
+/// 來源在輸出資料夾中對應的子資料夾（來源不在 `base_directory` 下時直接使用輸出資料夾）
+fn mirrored_output_dir(output_dir: &Path, base_directory: &Path, source_path: &Path) -> PathBuf {
+    source_path
+        .parent()
+        .and_then(|parent| parent.strip_prefix(base_directory).ok())
+        .map_or_else(
+            || output_dir.to_path_buf(),
+            |relative| output_dir.join(relative),
+        )
+}
+
+/// 任務記錄檔路徑（`<logs>/<來源檔名主幹>.<來源完整路徑的短 hash>.log`）
+///
+/// 加上完整路徑的 hash，避免不同資料夾或不同副檔名的同名來源共用記錄檔
+fn task_log_path(log_directory: &Path, source_path: &Path) -> PathBuf {
+    let file_stem = source_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("output");
+    let hash = blake3::hash(source_path.as_os_str().as_encoded_bytes()).to_hex();
+    log_directory.join(format!("{file_stem}.{}.log", &hash[..8]))
+}
+
+/// 以附加模式開啟記錄檔並寫入本次嘗試的標頭，回傳檔案與本次內容的起始位置
+fn open_task_log(path: &Path, attempt: u32) -> std::io::Result<(File, u64)> {
+    let mut file = File::options().create(true).append(true).open(path)?;
+    writeln!(file, "=== 第 {attempt} 次嘗試 ===")?;
+    let offset = file.metadata()?.len();
+    Ok((file, offset))
+}
+
+/// 讀取記錄檔中本次嘗試寫入的內容
+fn read_task_log(path: &Path, offset: u64) -> Option<String> {
+    let mut file = File::open(path).ok()?;
+    file.seek(SeekFrom::Start(offset)).ok()?;
+    let mut content = Vec::new();
+    file.read_to_end(&mut content).ok()?;
+    let content = String::from_utf8_lossy(&content).trim().to_string();
+    (!content.is_empty()).then_some(content)
+}
+
+/// 輸出檔比來源檔大超過容許百分比時回傳（來源大小, 輸出大小）
+fn output_larger_than_source(task: &EncodingTask, tolerance_percent: u8) -> Option<(u64, u64)> {
+    let source_size = fs::metadata(&task.source_path).ok()?.len();
+    let output_size = fs::metadata(&task.destination_path).ok()?.len();
+    let allowed = source_size.saturating_add(source_size / 100 * u64::from(tolerance_percent));
+    (output_size > allowed).then_some((source_size, output_size))
+}
+
+/// 將來源檔的存取與修改時間套用到目的檔
+fn copy_file_times(source: &Path, destination: &Path) -> Result<()> {
+    let metadata = fs::metadata(source)
+        .with_context(|| format!("無法讀取來源檔案資訊: {}", source.display()))?;
+    let mut times = FileTimes::new().set_modified(metadata.modified()?);
+    if let Ok(accessed) = metadata.accessed() {
+        times = times.set_accessed(accessed);
+    }
+
+    File::options()
+        .write(true)
+        .open(destination)
+        .and_then(|file| file.set_times(times))
+        .with_context(|| format!("無法設定檔案時間: {}", destination.display()))
+}
+
+/// 將輸出檔放到原始檔案旁，並將原始檔案移到 `disposal_folder`（或依設定送到資源回收筒），回傳輸出的最終路徑
+///
+/// `use_original_name` 時輸出改用原始檔名（`.mkv`），否則保留 `.convert.mkv`；
+/// 目標已有其他檔案時加上編號。原始檔案只在輸出就位後才移走：
+/// 目標與原始檔案同名時先將原始檔案改名保留，移動失敗則改回原名，任何失敗都不會同時失去兩個檔案
+fn replace_in_place(
+    source: &Path,
+    output: &Path,
+    use_original_name: bool,
+    disposal_folder: &Path,
+    deletion_mode: DeletionMode,
+) -> Result<PathBuf> {
+    let directory = source
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("無法取得原始檔案的資料夾: {}", source.display()))?;
+    let file_name = if use_original_name {
+        let stem = source
+            .file_stem()
+            .ok_or_else(|| anyhow::anyhow!("無法取得檔案名稱"))?;
+        let mut name = stem.to_os_string();
+        name.push(".mkv");
+        name
+    } else {
+        output
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("無法取得檔案名稱"))?
+            .to_os_string()
+    };
+    let target = directory.join(&file_name);
+
+    if target == source {
+        let mut backup_name = file_name.clone();
+        backup_name.push(".replacing");
+        let backup = directory.join(backup_name);
+        if backup.exists() {
+            anyhow::bail!("暫存檔案已存在，略過取代: {}", backup.display());
+        }
+        fs::rename(source, &backup)
+            .with_context(|| format!("無法暫時改名原始檔案: {}", source.display()))?;
+        if let Err(e) = move_file(output, &target) {
+            if let Err(restore) = fs::rename(&backup, source) {
+                error!(
+                    "無法還原原始檔案，檔案保留在 {}: {restore}",
+                    backup.display()
+                );
+            }
+            return Err(e.context(format!("無法移動轉檔檔案到 {}", target.display())));
+        }
+        let source_name = source
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("無法取得檔案名稱"))?;
+        dispose_file_as(&backup, source_name, disposal_folder, deletion_mode)
+            .with_context(|| format!("已放置轉檔檔案，但無法移走原始檔案: {}", backup.display()))?;
+        return Ok(target);
+    }
+
+    let target = if target == output {
+        target
+    } else {
+        let target = unique_destination(directory, &file_name);
+        move_file(output, &target)
+            .with_context(|| format!("無法移動轉檔檔案到 {}", target.display()))?;
+        target
+    };
+    dispose_file(source, disposal_folder, deletion_mode)
+        .with_context(|| format!("已放置轉檔檔案，但無法移走原始檔案: {}", source.display()))?;
+    Ok(target)
+}
+
+/// 1 GiB（位元組），顯示記憶體與磁碟用量用
+const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// 是否還有空位啟動新任務
+///
+/// `max_limit` 是使用者設定的硬上限（`max_parallel`，GPU 編碼另受 `max_gpu_parallel` 限制），
+/// 不論 CPU 使用率多低都不會超過；設為 1 即逐一轉檔
+const fn has_free_slot(running: usize, current_limit: usize, max_limit: Option<usize>) -> bool {
+    if running >= current_limit {
+        return false;
+    }
+    match max_limit {
+        Some(maxp) => running < maxp,
+        None => true,
+    }
+}
+
+/// 平均編碼速度的平滑係數（越大越偏重最新的速度）
+const SPEED_SMOOTHING: f64 = 0.2;
+
+/// 依目前平均速度更新滾動平均，尚無平均時直接採用
+fn smoothed_speed(average: Option<f64>, current: f64) -> f64 {
+    average.map_or(current, |avg| {
+        avg.mul_add(1.0 - SPEED_SMOOTHING, current * SPEED_SMOOTHING)
+    })
+}
+
+/// 估計全部任務完成的剩餘時間（毫秒）
+///
+/// 執行中任務以 `(current_ms, total_ms, speed)` 表示，沒有自己的速度時使用平均速度；
+/// 等待中任務以影片長度除以平均速度估計。各任務的剩餘時間加總後，
+/// 除以同時執行的任務數。無法取得速度或影片長度的任務不列入，沒有平均速度時回傳 `None`
+fn estimate_remaining_ms(
+    running: &[(u64, Option<u64>, Option<f64>)],
+    pending_durations: &[u64],
+    average_speed: Option<f64>,
+    parallel: usize,
+) -> Option<u64> {
+    let average_speed = average_speed.filter(|speed| *speed > 0.0)?;
+    let running_ms: f64 = running
+        .iter()
+        .filter_map(|&(current, total, speed)| {
+            let remaining = total?.saturating_sub(current);
+            let speed = speed.filter(|s| *s > 0.0).unwrap_or(average_speed);
+            Some(remaining as f64 / speed)
+        })
+        .sum();
+    let pending_ms: f64 = pending_durations
+        .iter()
+        .map(|&duration| duration as f64 / average_speed)
+        .sum();
+    let total_ms = (running_ms + pending_ms) / parallel.max(1) as f64;
+    Some(total_ms.round() as u64)
 }
 
 #[derive(Debug, Clone)]
@@ -54,11 +354,52 @@ struct ProgressState {
     last_update: Instant,
 }
 
+/// 任務的執行時間，暫停的時間不計入
+#[derive(Debug, Clone, Copy)]
+struct RunTimer {
+    started_at: Instant,
+    /// 已結束的暫停累計時間
+    paused_for: Duration,
+    /// 目前這次暫停的開始時間
+    paused_at: Option<Instant>,
+}
+
+impl RunTimer {
+    fn start() -> Self {
+        Self {
+            started_at: Instant::now(),
+            paused_for: Duration::ZERO,
+            paused_at: None,
+        }
+    }
+
+    fn pause(&mut self) {
+        self.paused_at.get_or_insert_with(Instant::now);
+    }
+
+    fn resume(&mut self) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused_for += paused_at.elapsed();
+        }
+    }
+
+    /// 開始後扣除暫停時間的執行時間
+    fn elapsed(&self) -> Duration {
+        let paused = self.paused_for + self.paused_at.map_or(Duration::ZERO, |at| at.elapsed());
+        self.started_at.elapsed().saturating_sub(paused)
+    }
+}
+
 struct RunningProcess {
     child: Child,
+    /// 中斷時用來送出 `q` 的 stdin
+    stdin: Option<ChildStdin>,
     task_index: usize,
     destination_path: PathBuf,
     progress: Arc<Mutex<ProgressState>>,
+    timer: RunTimer,
+    /// 本次嘗試在記錄檔中的起始位置（無記錄檔時為 `None`）
+    log_offset: Option<u64>,
 }
 
 pub struct TaskScheduler {
@@ -72,24 +413,93 @@ pub struct TaskScheduler {
     last_scale_check: Instant,
     scale_cooldown: Duration,
     shutdown_signal: Arc<AtomicBool>,
+    base_directory: PathBuf,
     fail_directory: PathBuf,
     finish_directory: PathBuf,
+    /// ffmpeg 錯誤輸出記錄檔資料夾
+    log_directory: PathBuf,
+    /// 成功後是否保留記錄檔
+    keep_success_logs: bool,
     post_encode_action: PostEncodeAction,
+    codec: EncoderCodec,
+    backend: EncodeBackend,
+    crf: u8,
+    preset: String,
+    /// 是否跳過已是 HEVC 的來源
+    skip_hevc: bool,
+    /// 輸出檔沿用來源的修改時間
+    preserve_mtime: bool,
+    /// 輸出比來源大時保留原始檔案
+    keep_smaller: bool,
+    /// 輸出可比來源大的容許百分比
+    keep_smaller_tolerance_percent: u8,
+    /// 失敗後的重試次數
+    max_retries: u32,
+    /// 中斷時等待 ffmpeg 自行結束的時間
+    shutdown_grace: Duration,
+    /// 轉檔輸出資料夾（None = 與來源相同）
+    output_dir: Option<PathBuf>,
+    /// 音訊處理方式
+    audio_mode: AudioMode,
+    /// 保留所有音軌
+    keep_all_audio: bool,
+    /// 保留內嵌字幕
+    keep_subtitles: bool,
+    /// 輸出位置最低可用空間（位元組，0 = 不檢查）
+    min_free_space: u64,
+    /// 可用空間低於門檻後停止新增任務
+    stopped_for_disk_space: bool,
+    /// 失敗的輸出檔案（以及取代時的原始檔案）直接刪除或送到資源回收筒
+    deletion_mode: DeletionMode,
+    /// 取代原始檔案時，輸出改用原始檔名
+    replace_use_original_name: bool,
+    /// 執行期間的鍵盤控制（標準輸入不是終端機或平台不支援時為 `None`）
+    keyboard: Option<KeyboardControl>,
+    /// 暫停中：不啟動新任務，執行中的任務在支援的平台上一併暫停
+    paused: bool,
+    /// 執行中任務編碼速度的滾動平均，用來估計剩餘時間
+    average_speed: Option<f64>,
 }
 
 impl TaskScheduler {
+    /// 建立排程器
+    ///
+    /// 輸出為 HEVC 且未設定強制重新編碼（`reencode_hevc`）時，
+    /// 先以 ffprobe 檢查來源，已是 HEVC 的檔案標記為 [`TaskStatus::Skipped`]
     pub fn new(
-        video_files: Vec<VideoFileInfo>,
+        video_files: &[VideoFileInfo],
+        base_directory: &Path,
+        shutdown_signal: Arc<AtomicBool>,
+        encoder_settings: &VideoEncoderSettings,
+    ) -> Result<Self> {
+        let skip_hevc = Self::should_skip_hevc(encoder_settings);
+        let tasks = Self::build_tasks(
+            video_files,
+            skip_hevc,
+            encoder_settings.output_dir.as_deref(),
+            base_directory,
+        );
+        Self::resume(tasks, base_directory, shutdown_signal, encoder_settings)
+    }
+
+    /// 以既有的任務清單建立排程器（用於從狀態檔繼續）
+    pub fn resume(
+        tasks: Vec<EncodingTask>,
         base_directory: &Path,
         shutdown_signal: Arc<AtomicBool>,
         encoder_settings: &VideoEncoderSettings,
     ) -> Result<Self> {
         let fail_directory = base_directory.join("fail");
         let finish_directory = base_directory.join("finish");
+        let log_directory = base_directory.join("logs");
         ensure_directory_exists(&fail_directory)?;
+        ensure_directory_exists(&log_directory)?;
+        if let Some(output_dir) = &encoder_settings.output_dir {
+            ensure_directory_exists(output_dir)?;
+        }
 
         // 只有在需要時才建立 finish 目錄
-        if encoder_settings.post_encode_action != PostEncodeAction::None {
+        if encoder_settings.post_encode_action.uses_finish_directory() {
             ensure_directory_exists(&finish_directory)?;
         }
 
@@ -99,19 +509,21 @@ impl TaskScheduler {
             .initial_max_parallel
             .unwrap_or_else(|| std::cmp::max(1, cpu_count / 4));
 
-        let max_parallel_limit = encoder_settings.max_parallel;
+        // GPU 編碼受編碼工作階段數量限制，另以 max_gpu_parallel 封頂
+        let max_parallel_limit = encoder_settings.effective_max_parallel();
         if let Some(maxp) = max_parallel_limit
             && initial_limit > maxp
         {
             initial_limit = maxp.max(1);
         }
 
-        let tasks = video_files.iter().map(EncodingTask::new).collect();
-
-        Ok(Self {
+        let scheduler = Self {
             tasks,
             running_processes: HashMap::new(),
-            cpu_monitor: CpuMonitor::default(),
+            cpu_monitor: CpuMonitor::default().with_memory_threshold(MemoryThreshold {
+                min_available_mb: encoder_settings.min_free_memory_mb,
+                min_available_percent: encoder_settings.min_free_memory_percent,
+            }),
             term: Term::buffered_stdout(),
             last_render_lines: 0,
             current_parallel_limit: initial_limit,
@@ -119,10 +531,89 @@ impl TaskScheduler {
             last_scale_check: Instant::now(),
             scale_cooldown: Duration::from_secs(5),
             shutdown_signal,
+            base_directory: base_directory.to_path_buf(),
             fail_directory,
             finish_directory,
+            log_directory,
+            keep_success_logs: encoder_settings.keep_success_logs,
             post_encode_action: encoder_settings.post_encode_action,
-        })
+            codec: encoder_settings.codec,
+            backend: encoder_settings.backend,
+            crf: encoder_settings.effective_crf(),
+            preset: encoder_settings.preset.clone(),
+            skip_hevc: Self::should_skip_hevc(encoder_settings),
+            preserve_mtime: encoder_settings.preserve_mtime,
+            keep_smaller: encoder_settings.keep_smaller,
+            keep_smaller_tolerance_percent: encoder_settings.keep_smaller_tolerance_percent,
+            max_retries: encoder_settings.max_retries,
+            shutdown_grace: Duration::from_secs(encoder_settings.shutdown_grace_secs),
+            output_dir: encoder_settings.output_dir.clone(),
+            audio_mode: encoder_settings.audio_mode,
+            keep_all_audio: encoder_settings.keep_all_audio,
+            keep_subtitles: encoder_settings.keep_subtitles,
+            min_free_space: encoder_settings
+                .min_free_space_mb
+                .saturating_mul(1024 * 1024),
+            stopped_for_disk_space: false,
+            deletion_mode: DeletionMode::default(),
+            replace_use_original_name: encoder_settings.replace_use_original_name,
+            keyboard: None,
+            paused: false,
+            average_speed: None,
+        };
+        scheduler.persist_state();
+        Ok(scheduler)
+    }
+
+    /// 設定失敗的輸出檔案直接刪除或送到資源回收筒
+    #[must_use]
+    pub const fn with_deletion_mode(mut self, deletion_mode: DeletionMode) -> Self {
+        self.deletion_mode = deletion_mode;
+        self
+    }
+
+    /// 設定強制重新編碼，或輸出不是 HEVC 時不跳過
+    fn should_skip_hevc(encoder_settings: &VideoEncoderSettings) -> bool {
+        !encoder_settings.reencode_hevc && encoder_settings.outputs_hevc()
+    }
+
+    fn build_tasks(
+        video_files: &[VideoFileInfo],
+        skip_hevc: bool,
+        output_dir: Option<&Path>,
+        base_directory: &Path,
+    ) -> Vec<EncodingTask> {
+        video_files
+            .par_iter()
+            .map(|file| {
+                let mut task = EncodingTask::new(file, output_dir, base_directory);
+                if skip_hevc && Self::is_already_hevc(&file.path) {
+                    debug!("已是 HEVC，跳過: {}", file.path.display());
+                    task.status = TaskStatus::Skipped;
+                }
+                task
+            })
+            .collect()
+    }
+
+    fn is_already_hevc(path: &Path) -> bool {
+        get_video_info(path)
+            .map(|info| info.is_hevc())
+            .unwrap_or(false)
+    }
+
+    /// 將目前佇列寫入狀態檔，失敗時只記錄警告
+    fn persist_state(&self) {
+        if let Err(e) = save_queue_state(&self.base_directory, &self.tasks) {
+            warn!("無法儲存佇列狀態: {e}");
+        }
+    }
+
+    /// 所有任務結束後刪除狀態檔
+    pub fn clear_state(&self) {
+        if let Err(e) = remove_queue_state(&self.base_directory) {
+            warn!("{e}");
+        }
     }
 
     fn format_ms(ms: u64) -> String {
@@ -172,19 +663,43 @@ impl TaskScheduler {
     pub fn run(&mut self) -> Result<()> {
         info!("開始編碼任務，共 {} 個檔案", self.tasks.len());
 
+        self.keyboard = KeyboardControl::new();
+        let result = self.run_until_done();
+        // 發生錯誤時不可讓 ffmpeg 停在暫停狀態
+        if self.paused {
+            self.resume_tasks();
+        }
+        self.keyboard = None;
+        result
+    }
+
+    fn run_until_done(&mut self) -> Result<()> {
         while !self.is_all_completed() {
             if self.shutdown_signal.load(Ordering::SeqCst) {
                 self.handle_shutdown()?;
                 return Ok(());
             }
 
-            let cpu_usage = self.cpu_monitor.current_usage();
-            self.scale_up_if_possible(cpu_usage);
+            match self.keyboard.as_ref().and_then(KeyboardControl::poll) {
+                Some(KeyCommand::Pause) if !self.paused => self.pause_tasks(),
+                Some(KeyCommand::Resume) if self.paused => self.resume_tasks(),
+                _ => {}
+            }
 
+            let cpu_usage = self.cpu_monitor.current_usage();
             self.check_completed_processes()?;
-            self.spawn_new_tasks_if_possible(cpu_usage)?;
+            if !self.paused {
+                self.scale_up_if_possible(cpu_usage);
+                self.spawn_new_tasks_if_possible(cpu_usage)?;
+            }
             self.print_status();
 
+            // 空間不足時等執行中的任務結束後停止，剩餘任務留在佇列中
+            if self.stopped_for_disk_space && self.running_processes.is_empty() {
+                warn!("輸出位置可用空間不足，停止編碼，剩餘任務保留在佇列中");
+                return Ok(());
+            }
+
             thread::sleep(Duration::from_secs(1));
         }
 
@@ -192,28 +707,59 @@ impl TaskScheduler {
         Ok(())
     }
 
+    /// 停止啟動新任務，並暫停執行中的 ffmpeg（平台不支援時讓它們繼續到結束）
+    fn pause_tasks(&mut self) {
+        self.paused = true;
+        let mut suspended = 0;
+        for (&pid, process) in &mut self.running_processes {
+            if suspend_process(pid) {
+                process.timer.pause();
+                suspended += 1;
+            }
+        }
+        info!(
+            "已暫停編碼：不再啟動新任務，暫停 {suspended}/{} 個執行中的任務",
+            self.running_processes.len()
+        );
+    }
+
+    fn resume_tasks(&mut self) {
+        self.paused = false;
+        for (&pid, process) in &mut self.running_processes {
+            resume_process(pid);
+            process.timer.resume();
+        }
+        info!("繼續編碼");
+    }
+
     fn is_all_completed(&self) -> bool {
         self.tasks
             .iter()
-            .all(|t| matches!(t.status, TaskStatus::Completed | TaskStatus::Failed))
+            .all(|t| !matches!(t.status, TaskStatus::Pending | TaskStatus::Running))
             && self.running_processes.is_empty()
     }
 
     fn spawn_new_tasks_if_possible(&mut self, mut cpu_usage: f32) -> Result<()> {
         loop {
-            if self.running_processes.len() >= self.current_parallel_limit {
-                break;
-            }
-            if let Some(maxp) = self.max_parallel_limit
-                && self.running_processes.len() >= maxp
-            {
+            if !has_free_slot(
+                self.running_processes.len(),
+                self.current_parallel_limit,
+                self.max_parallel_limit,
+            ) {
                 break;
             }
             // 88% 以上視為接近飽和，停止新增
             if cpu_usage >= 88.0 {
                 break;
             }
+            // 可用記憶體不足時停止新增（至少保留一個任務執行，避免永遠等待）
+            if !self.running_processes.is_empty() && !self.cpu_monitor.has_enough_memory() {
+                break;
+            }
             if let Some(task_index) = self.find_next_pending_task() {
+                if !self.has_free_space_for(task_index) {
+                    break;
+                }
                 self.spawn_task(task_index)?;
                 // 更新 CPU 使用率再決定是否繼續新增
                 cpu_usage = self.cpu_monitor.current_usage();
@@ -224,6 +770,46 @@ impl TaskScheduler {
         Ok(())
     }
 
+    /// 任務輸出位置的可用空間是否高於門檻
+    ///
+    /// 一旦低於門檻即不再新增任務；無法取得可用空間時不阻擋
+    fn has_free_space_for(&mut self, task_index: usize) -> bool {
+        if self.stopped_for_disk_space {
+            return false;
+        }
+        if self.min_free_space == 0 {
+            return true;
+        }
+
+        let target = self.tasks[task_index]
+            .destination_path
+            .parent()
+            .unwrap_or(&self.base_directory);
+        match available_space(target) {
+            Ok(available) if available < self.min_free_space => {
+                warn!(
+                    "{} 可用空間剩 {:.2} GB，低於門檻 {} MB，停止新增任務",
+                    target.display(),
+                    available as f64 / GIB,
+                    self.min_free_space / 1024 / 1024
+                );
+                self.stopped_for_disk_space = true;
+                false
+            }
+            Ok(_) => true,
+            Err(e) => {
+                debug!("無法取得可用空間，略過檢查: {e:#}");
+                true
+            }
+        }
+    }
+
+    /// 是否因可用空間不足而提前停止
+    #[must_use]
+    pub const fn stopped_for_disk_space(&self) -> bool {
+        self.stopped_for_disk_space
+    }
+
     /// 從 ffmpeg 標準輸出讀取進度資訊
     fn spawn_progress_reader(stdout: Option<ChildStdout>, progress: Arc<Mutex<ProgressState>>) {
         if stdout.is_none() {
@@ -306,16 +892,50 @@ impl TaskScheduler {
 
     fn spawn_task(&mut self, task_index: usize) -> Result<()> {
         let task = &mut self.tasks[task_index];
-        let ffmpeg_cmd = FfmpegCommand::new(&task.source_path);
-
-        let mut command = ffmpeg_cmd.build_command();
+        // 以任務記錄的目的檔所在資料夾為準，續傳時不受設定變更影響
+        let output_dir = task
+            .destination_path
+            .parent()
+            .unwrap_or(Path::new("."))
+            .to_path_buf();
+        // 輸出資料夾下的子資料夾在啟動任務時才建立
+        ensure_directory_exists(&output_dir)?;
+        let ffmpeg_cmd = FfmpegCommand::new(&task.source_path)
+            .with_output_dir(&output_dir)
+            .with_codec(self.codec)
+            .with_backend(self.backend)
+            .with_quality(self.crf, &self.preset)
+            .with_audio(self.audio_mode)
+            .with_all_audio(self.keep_all_audio)
+            .with_subtitles(self.keep_subtitles)
+            .with_subtitle_codecs(Self::subtitle_codecs(
+                self.keep_subtitles,
+                &task.source_path,
+            ));
+
+        let mut command = ffmpeg_cmd.build_command()?;
+        command.stdin(Stdio::piped());
         command.stdout(Stdio::piped());
-        command.stderr(Stdio::piped());
+
+        // stderr 寫入記錄檔，避免錯誤訊息在程式結束後遺失；無法開啟時退回管線讀取
+        let log_path = task_log_path(&self.log_directory, &task.source_path);
+        let log_offset = match open_task_log(&log_path, task.attempts + 1) {
+            Ok((file, offset)) => {
+                command.stderr(file);
+                task.log_path = Some(log_path);
+                Some(offset)
+            }
+            Err(e) => {
+                warn!("無法建立記錄檔 {}: {e}", log_path.display());
+                command.stderr(Stdio::piped());
+                None
+            }
+        };
 
         match command.spawn() {
             Ok(mut child) => {
                 let pid = child.id();
-                task.status = TaskStatus::Running;
+                task.start_attempt();
 
                 info!(
                     "啟動編碼任務 [{}]: {} -> {}",
@@ -342,10 +962,13 @@ impl TaskScheduler {
                 self.running_processes.insert(
                     pid,
                     RunningProcess {
+                        stdin: child.stdin.take(),
                         child,
                         task_index,
                         destination_path: task.destination_path.clone(),
                         progress,
+                        timer: RunTimer::start(),
+                        log_offset,
                     },
                 );
             }
@@ -356,6 +979,7 @@ impl TaskScheduler {
             }
         }
 
+        self.persist_state();
         Ok(())
     }
 
@@ -375,56 +999,73 @@ impl TaskScheduler {
             }
         }
 
+        let state_changed = !completed_pids.is_empty();
         for (pid, exit_success) in completed_pids {
             if let Some(mut process) = self.running_processes.remove(&pid) {
                 let task = &mut self.tasks[process.task_index];
+                task.add_elapsed(process.timer.elapsed());
 
                 // 檢查輸出檔案是否存在且有效（大於 1KB）
-                let output_valid = task.destination_path.exists()
-                    && fs::metadata(&task.destination_path)
-                        .map(|m| m.len() > 1024)
-                        .unwrap_or(false);
+                let output_valid = is_valid_output(&task.destination_path);
 
                 if exit_success {
-                    task.status = TaskStatus::Completed;
-                    info!("編碼完成 [{}]: {}", pid, task.destination_path.display());
+                    info!(
+                        "編碼完成 [{}]: {}（耗時 {:.0} 秒）",
+                        pid,
+                        task.destination_path.display(),
+                        process.timer.elapsed().as_secs_f64()
+                    );
 
-                    if let Err(e) = self.handle_post_encode_action(process.task_index) {
-                        warn!("轉檔後處理失敗: {}", e);
-                    }
+                    self.complete_task(process.task_index);
+                } else if self.shutdown_signal.load(Ordering::SeqCst) {
+                    // 中斷期間的失敗多半是 ffmpeg 一同收到中斷信號，
+                    // 不重試也不移到 fail，重設為等待以便下次繼續
+                    warn!("編碼在中斷期間結束 [{pid}]，保留為等待狀態");
+                    task.cancel_attempt();
+                    remove_partial_output(&task.destination_path);
                 } else if output_valid {
                     // FFmpeg 退出碼非零但輸出檔案有效，視為成功（來源檔可能有損壞的 frame）
-                    task.status = TaskStatus::Completed;
                     warn!(
                         "編碼完成但有警告 [{}]: {} (來源檔案可能有損壞的 frame)",
                         pid,
                         task.destination_path.display()
                     );
 
-                    if let Err(e) = self.handle_post_encode_action(process.task_index) {
-                        warn!("轉檔後處理失敗: {}", e);
-                    }
+                    self.complete_task(process.task_index);
                 } else {
-                    let stderr = process.child.stderr.take();
-                    let error_msg = stderr
-                        .map(|s| {
-                            BufReader::new(s)
-                                .lines()
-                                .map_while(Result::ok)
-                                .collect::<Vec<_>>()
-                                .join("\n")
+                    let from_log = process
+                        .log_offset
+                        .zip(task.log_path.as_deref())
+                        .and_then(|(offset, path)| read_task_log(path, offset));
+                    let error_msg = from_log
+                        .or_else(|| {
+                            process.child.stderr.take().map(|s| {
+                                BufReader::new(s)
+                                    .lines()
+                                    .map_while(Result::ok)
+                                    .collect::<Vec<_>>()
+                                    .join("\n")
+                            })
                         })
                         .unwrap_or_else(|| "未知錯誤".to_string());
 
-                    task.status = TaskStatus::Failed;
-                    task.error_message = Some(error_msg.clone());
-                    error!("編碼失敗 [{pid}]: {error_msg}");
-
-                    self.handle_failed_task(process.task_index)?;
+                    if task.record_failure(error_msg.clone(), self.max_retries) {
+                        warn!(
+                            "編碼失敗 [{pid}]，稍後重試（已嘗試 {} 次）: {error_msg}",
+                            task.attempts
+                        );
+                        remove_partial_output(&task.destination_path);
+                    } else {
+                        error!("編碼失敗 [{pid}]: {error_msg}");
+                        self.handle_failed_task(process.task_index)?;
+                    }
                 }
             }
         }
 
+        if state_changed {
+            self.persist_state();
+        }
         Ok(())
     }
 
@@ -432,13 +1073,21 @@ impl TaskScheduler {
         let task = &self.tasks[task_index];
 
         if task.destination_path.exists() {
-            fs::remove_file(&task.destination_path).with_context(|| {
-                format!(
-                    "無法刪除失敗的輸出檔案: {}",
+            let trashed = remove_or_trash(&task.destination_path, self.deletion_mode)
+                .with_context(|| {
+                    format!(
+                        "無法刪除失敗的輸出檔案: {}",
+                        task.destination_path.display()
+                    )
+                })?;
+            if trashed {
+                info!(
+                    "已將失敗的輸出檔案送到資源回收筒: {}",
                     task.destination_path.display()
-                )
-            })?;
-            info!("已刪除失敗的輸出檔案: {}", task.destination_path.display());
+                );
+            } else {
+                info!("已刪除失敗的輸出檔案: {}", task.destination_path.display());
+            }
         }
 
         let file_name = task
@@ -463,14 +1112,75 @@ impl TaskScheduler {
         Ok(())
     }
 
-    /// 處理轉檔成功後的動作
-    fn handle_post_encode_action(&self, task_index: usize) -> Result<()> {
+    /// 編碼成功後的收尾
+    ///
+    /// 啟用 `keep_smaller` 且輸出比來源大超過容許百分比時刪除輸出、保留原始檔案，
+    /// 且不執行轉檔後處理（避免移走原檔後又刪除唯一的輸出）；
+    /// 否則保留修改時間，再執行轉檔後處理
+    fn complete_task(&mut self, task_index: usize) {
+        let task = &mut self.tasks[task_index];
+        if !self.keep_success_logs
+            && let Some(log_path) = task.log_path.take()
+            && let Err(e) = fs::remove_file(&log_path)
+        {
+            debug!("無法刪除記錄檔 {}: {e}", log_path.display());
+        }
+        task.output_size = fs::metadata(&task.destination_path).ok().map(|m| m.len());
+        if self.keep_smaller
+            && let Some((source_size, output_size)) =
+                output_larger_than_source(task, self.keep_smaller_tolerance_percent)
+        {
+            info!(
+                "編碼後檔案較大（{:.2} MB > {:.2} MB），保留原始檔案: {}",
+                output_size as f64 / 1024.0 / 1024.0,
+                source_size as f64 / 1024.0 / 1024.0,
+                task.source_path.display()
+            );
+            if let Err(e) = fs::remove_file(&task.destination_path) {
+                warn!(
+                    "無法刪除較大的輸出檔案 {}: {e}",
+                    task.destination_path.display()
+                );
+            }
+            task.status = TaskStatus::KeptOriginal;
+            return;
+        }
+        if let (Some(source_size), Some(output_size)) = (task.source_size, task.output_size)
+            && output_size > source_size
+        {
+            warn!(
+                "編碼後檔案比原始檔案大（{:.2} MB > {:.2} MB）: {}",
+                output_size as f64 / 1024.0 / 1024.0,
+                source_size as f64 / 1024.0 / 1024.0,
+                task.destination_path.display()
+            );
+        }
+        task.status = TaskStatus::Completed;
+
+        // 必須在轉檔後處理移動檔案之前讀取來源的修改時間
+        if self.preserve_mtime {
+            let task = &self.tasks[task_index];
+            if let Err(e) = copy_file_times(&task.source_path, &task.destination_path) {
+                warn!("無法保留原始修改時間: {e}");
+            }
+        }
+
+        match self.handle_post_encode_action(task_index) {
+            // 容量報告與紀錄要指向轉檔檔案的最終位置
+            Ok(Some(moved_to)) => self.tasks[task_index].destination_path = moved_to,
+            Ok(None) => {}
+            Err(e) => warn!("轉檔後處理失敗: {}", e),
+        }
+    }
+
+    /// 處理轉檔成功後的動作，轉檔檔案被移動時回傳新的路徑
+    fn handle_post_encode_action(&self, task_index: usize) -> Result<Option<PathBuf>> {
         let task = &self.tasks[task_index];
 
         match self.post_encode_action {
             PostEncodeAction::None => {
                 // 不做任何動作
-                Ok(())
+                Ok(None)
             }
             PostEncodeAction::MoveOldToFinish => {
                 // 移動舊影片（原始檔案）到 finish 資料夾
@@ -489,7 +1199,7 @@ impl TaskScheduler {
                 })?;
 
                 info!("已移動原始檔案到 finish 資料夾: {}", finish_path.display());
-                Ok(())
+                Ok(None)
             }
             PostEncodeAction::MoveNewToFinish => {
                 // 移動新影片（轉檔後檔案）到 finish 資料夾
@@ -508,19 +1218,80 @@ impl TaskScheduler {
                 })?;
 
                 info!("已移動轉檔檔案到 finish 資料夾: {}", finish_path.display());
-                Ok(())
+                Ok(Some(finish_path))
+            }
+            PostEncodeAction::ReplaceInPlace => {
+                // 轉檔檔案放到原始檔案旁，就位後才將原始檔案移到 finish 資料夾
+                let final_path = replace_in_place(
+                    &task.source_path,
+                    &task.destination_path,
+                    self.replace_use_original_name,
+                    &self.finish_directory,
+                    self.deletion_mode,
+                )?;
+                info!(
+                    "已以轉檔檔案取代原始檔案: {} -> {}",
+                    task.source_path.display(),
+                    final_path.display()
+                );
+                Ok(Some(final_path))
             }
         }
     }
 
+    /// 中斷所有執行中的任務
+    ///
+    /// 先對 ffmpeg 送出 `q` 讓容器正常收尾，等待寬限時間後仍未結束才強制終止。
+    /// 在寬限時間內剛好編碼到結尾的任務視為完成；其餘輸出不完整，刪除後重設為等待
     fn handle_shutdown(&mut self) -> Result<()> {
         warn!("收到中斷信號，正在停止所有任務...");
+        // 暫停中的 ffmpeg 無法讀取結束指令
+        if self.paused {
+            self.resume_tasks();
+        }
+
+        for (pid, process) in &mut self.running_processes {
+            if let Some(mut stdin) = process.stdin.take()
+                && let Err(e) = stdin.write_all(b"q").and_then(|()| stdin.flush())
+            {
+                debug!("無法送出結束指令 [{pid}]: {e}");
+            }
+        }
 
-        for (pid, mut process) in self.running_processes.drain() {
-            warn!("終止程序 [{pid}]");
-            let _ = process.child.kill();
+        let exited = self.wait_for_exit(self.shutdown_grace);
+
+        let running = std::mem::take(&mut self.running_processes);
+        for (pid, mut process) in running {
+            let exit_success = exited.get(&pid).copied();
+            if exit_success.is_none() {
+                warn!(
+                    "程序未在 {} 秒內結束，強制終止 [{pid}]",
+                    self.shutdown_grace.as_secs()
+                );
+                let _ = process.child.kill();
+            }
             let _ = process.child.wait();
+            self.tasks[process.task_index].add_elapsed(process.timer.elapsed());
+
+            let reached_end = process
+                .progress
+                .lock()
+                .map(|state| encoded_to_end(state.current_ms, state.total_ms))
+                .unwrap_or(false);
+            if exit_success == Some(true)
+                && reached_end
+                && is_valid_output(&process.destination_path)
+            {
+                info!(
+                    "編碼已在中斷前完成 [{pid}]: {}",
+                    process.destination_path.display()
+                );
+                self.complete_task(process.task_index);
+                continue;
+            }
 
+            // 重設為等待，下次可從狀態檔繼續
+            self.tasks[process.task_index].cancel_attempt();
             if process.destination_path.exists() {
                 if let Err(e) = fs::remove_file(&process.destination_path) {
                     error!(
@@ -537,9 +1308,39 @@ impl TaskScheduler {
             }
         }
 
+        self.persist_state();
         Ok(())
     }
 
+    /// 在期限內等待所有程序結束，回傳已結束程序的成功與否
+    fn wait_for_exit(&mut self, grace: Duration) -> HashMap<u32, bool> {
+        let deadline = Instant::now() + grace;
+        let mut exited = HashMap::new();
+
+        loop {
+            for (pid, process) in &mut self.running_processes {
+                if exited.contains_key(pid) {
+                    continue;
+                }
+                match process.child.try_wait() {
+                    Ok(Some(status)) => {
+                        exited.insert(*pid, status.success());
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        warn!("無法檢查程序狀態 [{pid}]: {e}");
+                        exited.insert(*pid, false);
+                    }
+                }
+            }
+
+            if exited.len() == self.running_processes.len() || Instant::now() >= deadline {
+                return exited;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+
     fn print_status(&mut self) {
         let pending = self
             .tasks
@@ -558,23 +1359,30 @@ impl TaskScheduler {
             .filter(|t| t.status == TaskStatus::Failed)
             .count();
 
+        let mut progresses: Vec<_> = self
+            .running_processes
+            .values()
+            .filter_map(|p| p.progress.lock().ok().map(|state| state.clone()))
+            .collect();
+        let eta = self.estimate_eta(&progresses);
+
+        let memory = self.cpu_monitor.current_memory();
         let mut lines = Vec::new();
         lines.push(format!(
-            "[狀態] 等待: {} | 執行中: {} | 完成: {} | 失敗: {} | CPU: {:.1}%",
+            "{} 等待: {} | 執行中: {} | 完成: {} | 失敗: {} | ETA: {} | CPU: {:.1}% | 記憶體: {:.1}/{:.1} GB ({:.0}%)",
+            if self.paused { "[已暫停]" } else { "[狀態]" },
             pending,
             running,
             completed,
             failed,
-            self.cpu_monitor.system.global_cpu_usage()
+            eta.map_or_else(|| "--:--:--".to_string(), Self::format_ms),
+            self.cpu_monitor.last_usage(),
+            memory.used() as f64 / GIB,
+            memory.total as f64 / GIB,
+            memory.used_percent()
         ));
 
-        if !self.running_processes.is_empty() {
-            let mut progresses: Vec<_> = self
-                .running_processes
-                .values()
-                .filter_map(|p| p.progress.lock().ok().map(|state| state.clone()))
-                .collect();
-
+        if !progresses.is_empty() {
             progresses.sort_by_key(|b| std::cmp::Reverse(b.current_ms));
 
             for prog in progresses.iter().take(8) {
@@ -604,6 +1412,15 @@ impl TaskScheduler {
             }
         }
 
+        if self.keyboard.is_some() {
+            let hint = if self.paused {
+                "按 r 繼續，Ctrl-C 中斷"
+            } else {
+                "按 p 暫停，Ctrl-C 中斷"
+            };
+            lines.push(style(hint).dim().to_string());
+        }
+
         // 清除上一輪並重新繪製，避免畫面跳動與殘影
         let _ = self.term.clear_last_lines(self.last_render_lines);
         for line in &lines {
@@ -613,8 +1430,407 @@ impl TaskScheduler {
         self.last_render_lines = lines.len();
     }
 
+    /// 更新平均編碼速度並估計全部任務的剩餘時間（毫秒）
+    fn estimate_eta(&mut self, progresses: &[ProgressState]) -> Option<u64> {
+        let speeds: Vec<f64> = progresses
+            .iter()
+            .filter_map(|p| p.speed)
+            .filter(|speed| *speed > 0.0)
+            .collect();
+        // 暫停期間 ffmpeg 回報的速度已過時，不列入平均
+        if !self.paused && !speeds.is_empty() {
+            let current = speeds.iter().sum::<f64>() / speeds.len() as f64;
+            self.average_speed = Some(smoothed_speed(self.average_speed, current));
+        }
+
+        let running: Vec<_> = progresses
+            .iter()
+            .map(|p| (p.current_ms, p.total_ms, p.speed))
+            .collect();
+        let pending: Vec<u64> = self
+            .tasks
+            .iter()
+            .filter(|t| t.status == TaskStatus::Pending)
+            .filter_map(|t| t.duration_ms)
+            .collect();
+        let parallel = progresses.len().max(
+            self.current_parallel_limit
+                .min(self.max_parallel_limit.unwrap_or(usize::MAX)),
+        );
+        estimate_remaining_ms(&running, &pending, self.average_speed, parallel)
+    }
+
+    /// 保留字幕時讀取來源的字幕編碼，無法讀取時回傳空清單（字幕全部直接複製）
+    fn subtitle_codecs(keep_subtitles: bool, source_path: &Path) -> Vec<String> {
+        if !keep_subtitles {
+            return Vec::new();
+        }
+        get_video_info(source_path).map_or_else(
+            |e| {
+                debug!("無法讀取字幕編碼 {}: {e:#}", source_path.display());
+                Vec::new()
+            },
+            |info| info.subtitle_codecs,
+        )
+    }
+
+    /// 將更多影片加入佇列（例如延後檢查後已穩定的檔案）
+    pub fn enqueue(&mut self, video_files: &[VideoFileInfo]) {
+        let tasks = Self::build_tasks(
+            video_files,
+            self.skip_hevc,
+            self.output_dir.as_deref(),
+            &self.base_directory,
+        );
+        self.tasks.extend(tasks);
+        self.persist_state();
+    }
+
+    /// 將各任務的結果寫入 JSON 紀錄檔
+    pub fn write_log(&self, path: &Path) -> Result<()> {
+        write_encode_log(path, &self.tasks)?;
+        info!("已寫入編碼紀錄: {}", path.display());
+        Ok(())
+    }
+
     #[must_use]
     pub fn tasks(&self) -> &[EncodingTask] {
         &self.tasks
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_copy_file_times() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("source.mp4");
+        let destination = dir.path().join("source.convert.mkv");
+        fs::write(&source, b"source").unwrap();
+        fs::write(&destination, b"encoded").unwrap();
+
+        let old = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        File::options()
+            .write(true)
+            .open(&source)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        copy_file_times(&source, &destination).unwrap();
+        assert_eq!(fs::metadata(&destination).unwrap().modified().unwrap(), old);
+    }
+
+    #[test]
+    fn test_output_larger_than_source() {
+        let dir = TempDir::new().unwrap();
+        let mut task = EncodingTask {
+            source_path: dir.path().join("a.mp4"),
+            destination_path: dir.path().join("a.convert.mkv"),
+            duration_ms: None,
+            status: TaskStatus::Running,
+            error_message: None,
+            attempts: 1,
+            source_size: None,
+            output_size: None,
+            elapsed_ms: None,
+            log_path: None,
+        };
+        assert_eq!(output_larger_than_source(&task, 0), None);
+
+        fs::write(&task.source_path, vec![0u8; 100]).unwrap();
+        fs::write(&task.destination_path, vec![0u8; 150]).unwrap();
+        assert_eq!(output_larger_than_source(&task, 0), Some((100, 150)));
+        // 在容許範圍內視為未變大
+        assert_eq!(output_larger_than_source(&task, 50), None);
+        assert_eq!(output_larger_than_source(&task, 49), Some((100, 150)));
+
+        task.destination_path = dir.path().join("b.convert.mkv");
+        fs::write(&task.destination_path, vec![0u8; 50]).unwrap();
+        assert_eq!(output_larger_than_source(&task, 0), None);
+    }
+
+    fn pending_task() -> EncodingTask {
+        EncodingTask {
+            source_path: PathBuf::from("/videos/a.mp4"),
+            destination_path: PathBuf::from("/videos/a.convert.mkv"),
+            duration_ms: None,
+            status: TaskStatus::Pending,
+            error_message: None,
+            attempts: 0,
+            source_size: None,
+            output_size: None,
+            elapsed_ms: None,
+            log_path: None,
+        }
+    }
+
+    #[test]
+    fn test_task_fails_once_then_succeeds() {
+        let mut task = pending_task();
+
+        task.start_attempt();
+        assert!(task.record_failure("disk hiccup".to_string(), 1));
+        assert_eq!(task.status, TaskStatus::Pending);
+
+        task.start_attempt();
+        task.status = TaskStatus::Completed;
+        assert_eq!(task.attempts, 2);
+        assert!(task.succeeded_after_retry());
+    }
+
+    #[test]
+    fn test_interrupted_attempt_not_counted() {
+        let mut task = pending_task();
+
+        task.start_attempt();
+        task.cancel_attempt();
+        assert_eq!(task.status, TaskStatus::Pending);
+        assert_eq!(task.attempts, 0);
+
+        // 中斷後再次執行仍有完整的重試次數
+        task.start_attempt();
+        assert!(task.record_failure("disk hiccup".to_string(), 1));
+        task.start_attempt();
+        task.status = TaskStatus::Completed;
+        assert_eq!(task.attempts, 2);
+    }
+
+    #[test]
+    fn test_add_elapsed_accumulates_attempts() {
+        let mut task = pending_task();
+        assert_eq!(task.elapsed_ms, None);
+
+        task.add_elapsed(Duration::from_millis(1500));
+        task.add_elapsed(Duration::from_millis(500));
+        assert_eq!(task.elapsed_ms, Some(2000));
+    }
+
+    #[test]
+    fn test_run_timer_excludes_paused_time() {
+        let mut timer = RunTimer::start();
+        timer.pause();
+        thread::sleep(Duration::from_millis(200));
+        // 暫停中也不計入
+        assert!(timer.elapsed() < Duration::from_millis(100));
+        timer.resume();
+        thread::sleep(Duration::from_millis(50));
+
+        let elapsed = timer.elapsed();
+        assert!(elapsed >= Duration::from_millis(50));
+        assert!(elapsed < Duration::from_millis(150));
+        assert!(timer.started_at.elapsed() >= Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_task_fails_after_retries_exhausted() {
+        let mut task = pending_task();
+
+        task.start_attempt();
+        assert!(task.record_failure("error".to_string(), 1));
+        task.start_attempt();
+        assert!(!task.record_failure("error".to_string(), 1));
+        assert_eq!(task.status, TaskStatus::Failed);
+
+        // 不重試時第一次失敗即標記為失敗
+        let mut task = pending_task();
+        task.start_attempt();
+        assert!(!task.record_failure("error".to_string(), 0));
+        assert!(!task.succeeded_after_retry());
+    }
+
+    #[test]
+    fn test_task_log_keeps_each_attempt() {
+        let dir = TempDir::new().unwrap();
+        let path = task_log_path(dir.path(), Path::new("/videos/movie.mp4"));
+        assert_eq!(path.parent(), Some(dir.path()));
+        assert!(
+            path.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with("movie.")
+        );
+
+        let (mut file, offset) = open_task_log(&path, 1).unwrap();
+        writeln!(file, "first error").unwrap();
+        assert_eq!(read_task_log(&path, offset).as_deref(), Some("first error"));
+
+        let (mut file, offset) = open_task_log(&path, 2).unwrap();
+        assert_eq!(read_task_log(&path, offset), None);
+        writeln!(file, "second error").unwrap();
+        assert_eq!(
+            read_task_log(&path, offset).as_deref(),
+            Some("second error")
+        );
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("=== 第 1 次嘗試 ===\nfirst error"));
+        assert!(content.contains("=== 第 2 次嘗試 ===\nsecond error"));
+    }
+
+    fn video_file(path: &str) -> VideoFileInfo {
+        VideoFileInfo {
+            path: PathBuf::from(path),
+            size: 1000,
+            duration_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_output_dir_mirrors_source_folders() {
+        let base = Path::new("/lib");
+        let output_dir = Some(Path::new("/encoded"));
+        let a = EncodingTask::new(&video_file("/lib/a/x.mp4"), output_dir, base);
+        let b = EncodingTask::new(&video_file("/lib/b/x.mp4"), output_dir, base);
+        let top = EncodingTask::new(&video_file("/lib/y.mp4"), output_dir, base);
+        let outside = EncodingTask::new(&video_file("/other/z.mp4"), output_dir, base);
+        assert_eq!(a.destination_path, Path::new("/encoded/a/x.convert.mkv"));
+        assert_eq!(b.destination_path, Path::new("/encoded/b/x.convert.mkv"));
+        assert_eq!(top.destination_path, Path::new("/encoded/y.convert.mkv"));
+        assert_eq!(
+            outside.destination_path,
+            Path::new("/encoded/z.convert.mkv")
+        );
+
+        let beside = EncodingTask::new(&video_file("/lib/a/x.mp4"), None, base);
+        assert_eq!(beside.destination_path, Path::new("/lib/a/x.convert.mkv"));
+    }
+
+    #[test]
+    fn test_task_log_path_unique_for_same_stem() {
+        let dir = Path::new("/logs");
+        let a = task_log_path(dir, Path::new("/videos/a/movie.mp4"));
+        let b = task_log_path(dir, Path::new("/videos/b/movie.mp4"));
+        let c = task_log_path(dir, Path::new("/videos/a/movie.avi"));
+        assert_ne!(a, b);
+        assert_ne!(a, c);
+        assert_ne!(b, c);
+        assert_eq!(a, task_log_path(dir, Path::new("/videos/a/movie.mp4")));
+    }
+
+    #[test]
+    fn test_encoded_to_end() {
+        assert!(encoded_to_end(60_000, Some(60_000)));
+        assert!(encoded_to_end(59_700, Some(60_000)));
+        assert!(!encoded_to_end(30_000, Some(60_000)));
+        assert!(!encoded_to_end(60_000, None));
+    }
+
+    #[test]
+    fn test_has_free_slot_respects_hard_limit() {
+        // 動態上限尚有空位，但硬上限為 1 時只能逐一轉檔
+        assert!(has_free_slot(0, 4, Some(1)));
+        assert!(!has_free_slot(1, 4, Some(1)));
+
+        assert!(has_free_slot(2, 4, None));
+        assert!(!has_free_slot(4, 4, None));
+        assert!(!has_free_slot(3, 3, Some(8)));
+    }
+
+    #[test]
+    fn test_replace_in_place_keeps_convert_name() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("a.mp4");
+        let output = dir.path().join("a.convert.mkv");
+        fs::write(&source, b"source").unwrap();
+        fs::write(&output, b"output").unwrap();
+
+        let finish = dir.path().join("finish");
+        let final_path =
+            replace_in_place(&source, &output, false, &finish, DeletionMode::MoveToFolder).unwrap();
+        assert_eq!(final_path, output);
+        assert!(!source.exists());
+        assert_eq!(fs::read(&output).unwrap(), b"output");
+        assert_eq!(fs::read(finish.join("a.mp4")).unwrap(), b"source");
+    }
+
+    #[test]
+    fn test_replace_in_place_uses_original_name() {
+        let dir = TempDir::new().unwrap();
+        let output_dir = dir.path().join("encoded");
+        fs::create_dir(&output_dir).unwrap();
+        let source = dir.path().join("a.mkv");
+        let output = output_dir.join("a.convert.mkv");
+        fs::write(&source, b"source").unwrap();
+        fs::write(&output, b"output").unwrap();
+
+        // 目標與原始檔案同名：輸出就位後才將原始檔案以原名移到 finish
+        let finish = dir.path().join("finish");
+        let final_path =
+            replace_in_place(&source, &output, true, &finish, DeletionMode::MoveToFolder).unwrap();
+        assert_eq!(final_path, source);
+        assert_eq!(fs::read(&source).unwrap(), b"output");
+        assert!(!output.exists());
+        assert!(!dir.path().join("a.mkv.replacing").exists());
+        assert_eq!(fs::read(finish.join("a.mkv")).unwrap(), b"source");
+
+        // 目標已有其他檔案時加上編號
+        let source = dir.path().join("b.mp4");
+        let output = output_dir.join("b.convert.mkv");
+        fs::write(&source, b"source").unwrap();
+        fs::write(dir.path().join("b.mkv"), b"other").unwrap();
+        fs::write(&output, b"output").unwrap();
+        let final_path =
+            replace_in_place(&source, &output, true, &finish, DeletionMode::MoveToFolder).unwrap();
+        assert_eq!(final_path, dir.path().join("b_1.mkv"));
+        assert!(!source.exists());
+        assert_eq!(fs::read(finish.join("b.mp4")).unwrap(), b"source");
+        assert_eq!(fs::read(dir.path().join("b.mkv")).unwrap(), b"other");
+    }
+
+    #[test]
+    fn test_replace_in_place_failure_keeps_source() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("a.mkv");
+        fs::write(&source, b"source").unwrap();
+        let missing_output = dir.path().join("encoded").join("a.convert.mkv");
+        let finish = dir.path().join("finish");
+
+        for use_original_name in [true, false] {
+            assert!(
+                replace_in_place(
+                    &source,
+                    &missing_output,
+                    use_original_name,
+                    &finish,
+                    DeletionMode::MoveToFolder
+                )
+                .is_err()
+            );
+            assert_eq!(fs::read(&source).unwrap(), b"source");
+            assert!(!dir.path().join("a.mkv.replacing").exists());
+            assert!(!finish.exists());
+        }
+    }
+
+    #[test]
+    fn test_estimate_remaining_ms() {
+        // 執行中：剩 60 秒、速度 2x → 30 秒；剩 20 秒、沒有速度改用平均 1x → 20 秒
+        // 等待中：120 秒、平均 1x → 120 秒；兩個任務同時執行 → (30 + 20 + 120) / 2
+        let running = [
+            (40_000, Some(100_000), Some(2.0)),
+            (10_000, Some(30_000), None),
+            (5_000, None, Some(1.5)),
+        ];
+        assert_eq!(
+            estimate_remaining_ms(&running, &[120_000], Some(1.0), 2),
+            Some(85_000)
+        );
+        assert_eq!(
+            estimate_remaining_ms(&[], &[60_000], Some(2.0), 0),
+            Some(30_000)
+        );
+        assert_eq!(estimate_remaining_ms(&running, &[120_000], None, 2), None);
+        assert_eq!(estimate_remaining_ms(&[], &[], Some(0.0), 1), None);
+    }
+
+    #[test]
+    fn test_smoothed_speed() {
+        assert!((smoothed_speed(None, 1.5) - 1.5).abs() < f64::EPSILON);
+        assert!((smoothed_speed(Some(1.0), 2.0) - 1.2).abs() < 1e-9);
+    }
+}
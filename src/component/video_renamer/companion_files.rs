@@ -0,0 +1,142 @@
+//! 附屬檔案模組
+//!
+//! 找出與影片同名的字幕、預覽圖等檔案（`movie.srt`、`movie.en.srt`、`movie.jpg`），
+//! 讓影片重新命名時一併改名，維持配對關係
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 找出與影片同一資料夾、檔名以「影片名稱.」開頭的檔案
+///
+/// 隱藏檔案與 `is_video` 判斷為影片的檔案不列入（影片會各自重新命名）。
+/// 同時符合多個影片名稱時歸給名稱最長的影片，例如 `show.s01.srt` 屬於
+/// `show.s01.mp4` 而不是 `show.mp4`
+pub fn find_companions(video: &Path, is_video: impl Fn(&Path) -> bool) -> Vec<PathBuf> {
+    let (Some(parent), Some(stem)) = (video.parent(), video.file_stem()) else {
+        return Vec::new();
+    };
+    let stem = stem.to_string_lossy();
+    let Ok(entries) = fs::read_dir(parent) else {
+        return Vec::new();
+    };
+
+    let (videos, others): (Vec<PathBuf>, Vec<PathBuf>) = entries
+        .filter_map(std::result::Result::ok)
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .map(|entry| entry.path())
+        .filter(|path| path != video)
+        .partition(|path| is_video(path));
+    let video_stems: Vec<String> = videos
+        .iter()
+        .filter_map(|path| path.file_stem())
+        .map(|stem| stem.to_string_lossy().to_string())
+        .collect();
+
+    let mut companions: Vec<PathBuf> = others
+        .into_iter()
+        .filter(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            !name.starts_with('.')
+                && companion_suffix(&name, &stem).is_some()
+                && !video_stems.iter().any(|other| {
+                    other.len() > stem.len() && companion_suffix(&name, other).is_some()
+                })
+        })
+        .collect();
+    companions.sort();
+    companions
+}
+
+/// 附屬檔案名稱在影片名稱之後的部分（含前導點，例如 `.en.srt`）
+#[must_use]
+pub fn companion_suffix<'a>(companion_name: &'a str, video_stem: &str) -> Option<&'a str> {
+    companion_name
+        .strip_prefix(video_stem)
+        .filter(|suffix| suffix.starts_with('.') && suffix.len() > 1)
+}
+
+/// 依影片的新檔名產生附屬檔案的新檔名，保留附屬檔案自己的副檔名
+#[must_use]
+pub fn companion_new_name(new_video_name: &str, suffix: &str) -> String {
+    let new_stem = Path::new(new_video_name).file_stem().map_or_else(
+        || new_video_name.to_string(),
+        |s| s.to_string_lossy().to_string(),
+    );
+    format!("{new_stem}{suffix}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_companions() {
+        let dir = TempDir::new().unwrap();
+        let base = dir.path();
+        for name in [
+            "movie.mp4",
+            "movie.srt",
+            "movie.en.srt",
+            "movie.jpg",
+            "movie.mkv",
+            "movie2.srt",
+            "movies.txt",
+            ".movie.srt",
+        ] {
+            fs::write(base.join(name), name).unwrap();
+        }
+        fs::create_dir(base.join("movie.extras")).unwrap();
+
+        let is_video = |path: &Path| path.extension().is_some_and(|e| e == "mp4" || e == "mkv");
+        let companions = find_companions(&base.join("movie.mp4"), is_video);
+        let names: Vec<String> = companions
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, ["movie.en.srt", "movie.jpg", "movie.srt"]);
+    }
+
+    #[test]
+    fn test_find_companions_prefers_longest_video_name() {
+        let dir = TempDir::new().unwrap();
+        let base = dir.path();
+        for name in ["show.mp4", "show.s01.mp4", "show.srt", "show.s01.srt"] {
+            fs::write(base.join(name), name).unwrap();
+        }
+
+        let is_video = |path: &Path| path.extension().is_some_and(|e| e == "mp4");
+        let names = |video: &str| -> Vec<String> {
+            find_companions(&base.join(video), is_video)
+                .iter()
+                .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+                .collect()
+        };
+        assert_eq!(names("show.mp4"), ["show.srt"]);
+        assert_eq!(names("show.s01.mp4"), ["show.s01.srt"]);
+    }
+
+    #[test]
+    fn test_companion_suffix() {
+        assert_eq!(companion_suffix("movie.srt", "movie"), Some(".srt"));
+        assert_eq!(companion_suffix("movie.en.srt", "movie"), Some(".en.srt"));
+        assert_eq!(
+            companion_suffix("Movie.Name.zh-TW.ass", "Movie.Name"),
+            Some(".zh-TW.ass")
+        );
+        assert_eq!(companion_suffix("movie2.srt", "movie"), None);
+        assert_eq!(companion_suffix("movie.", "movie"), None);
+    }
+
+    #[test]
+    fn test_companion_new_name_keeps_multi_dot_extension() {
+        assert_eq!(
+            companion_new_name("[1] movie_uuid.mp4", ".en.srt"),
+            "[1] movie_uuid.en.srt"
+        );
+        assert_eq!(
+            companion_new_name("[1] movie_uuid.convert.mp4", ".jpg"),
+            "[1] movie_uuid.convert.jpg"
+        );
+    }
+}
@@ -2,6 +2,8 @@
 //!
 //! 負責清理檔名中的非法字元、UUID、重複的 .convert 等
 
+use super::filename_template::{TemplateValues, render_template};
+use crate::config::DEFAULT_FILENAME_TEMPLATE;
 use regex::Regex;
 use std::sync::LazyLock;
 
@@ -16,6 +18,14 @@ pub struct CleanedFilename {
     pub has_convert: bool,
 }
 
+impl CleanedFilename {
+    /// 含 `.convert` 時為 `.convert`，否則為空字串
+    #[must_use]
+    pub const fn convert_suffix(&self) -> &'static str {
+        if self.has_convert { ".convert" } else { "" }
+    }
+}
+
 /// 檔名清理器
 pub struct FilenameCleaner {
     regex_leading_number: &'static Regex,
@@ -80,6 +90,12 @@ impl FilenameCleaner {
         }
     }
 
+    /// 檔名是否已含重新命名時加入的 UUID（`[uuid]` 或 `_uuid`）
+    #[must_use]
+    pub fn has_uuid(&self, filename: &str) -> bool {
+        self.regex_uuid_bracket.is_match(filename) || self.regex_uuid_underscore.is_match(filename)
+    }
+
     /// 分離副檔名（處理 .convert.ext 的情況）
     fn split_extension(&self, filename: &str) -> (String, String) {
         let parts: Vec<&str> = filename.rsplitn(2, '.').collect();
@@ -137,26 +153,34 @@ impl FilenameCleaner {
         result
     }
 
-    /// 產生新檔名
+    /// 以預設樣板產生新檔名（`[index] base_uuid.convert.ext`，不含 UUID 時為 `[index] base.convert.ext`）
     ///
     /// # Arguments
     /// * `index` - 編號
+    /// * `index_width` - 編號補零後的寬度（0 表示不補零）
     /// * `cleaned` - 清理後的檔名結構
-    /// * `new_uuid` - 新的 UUID
+    /// * `new_uuid` - 新的 UUID，`None` 時省略 `_uuid` 片段
     ///
     /// # Returns
     /// 格式化後的新檔名
     pub fn format_new_filename(
         &self,
         index: usize,
+        index_width: usize,
         cleaned: &CleanedFilename,
-        new_uuid: &str,
+        new_uuid: Option<&str>,
     ) -> String {
-        let convert_suffix = if cleaned.has_convert { ".convert" } else { "" };
-
-        format!(
-            "[{}] {}_{}{}.{}",
-            index, cleaned.base_name, new_uuid, convert_suffix, cleaned.extension
+        render_template(
+            DEFAULT_FILENAME_TEMPLATE,
+            &TemplateValues {
+                index,
+                index_width,
+                name: &cleaned.base_name,
+                uuid: new_uuid,
+                duration: "",
+                convert: cleaned.convert_suffix(),
+                ext: &cleaned.extension,
+            },
         )
     }
 }
@@ -169,6 +193,14 @@ mod tests {
         FilenameCleaner::new()
     }
 
+    #[test]
+    fn test_has_uuid() {
+        let cleaner = cleaner();
+        assert!(cleaner.has_uuid("[1] video_123e4567-e89b-12d3-a456-426614174000.mp4"));
+        assert!(cleaner.has_uuid("video [123e4567-e89b-12d3-a456-426614174000].mp4"));
+        assert!(!cleaner.has_uuid("[1] video.mp4"));
+    }
+
     #[test]
     fn test_clean_simple_filename() {
         let result = cleaner().clean("my video.mp4");
@@ -252,8 +284,12 @@ mod tests {
             extension: "mp4".to_string(),
             has_convert: false,
         };
-        let result =
-            cleaner().format_new_filename(1, &cleaned, "aaaaaaaa-bbbb-cccc-dddd-eeeeeeeeeeee");
+        let result = cleaner().format_new_filename(
+            1,
+            0,
+            &cleaned,
+            Some("aaaaaaaa-bbbb-cccc-dddd-eeeeeeeeeeee"),
+        );
         assert_eq!(
             result,
             "[1] my video_aaaaaaaa-bbbb-cccc-dddd-eeeeeeeeeeee.mp4"
@@ -267,14 +303,52 @@ mod tests {
             extension: "mp4".to_string(),
             has_convert: true,
         };
-        let result =
-            cleaner().format_new_filename(1, &cleaned, "aaaaaaaa-bbbb-cccc-dddd-eeeeeeeeeeee");
+        let result = cleaner().format_new_filename(
+            1,
+            0,
+            &cleaned,
+            Some("aaaaaaaa-bbbb-cccc-dddd-eeeeeeeeeeee"),
+        );
         assert_eq!(
             result,
             "[1] my video_aaaaaaaa-bbbb-cccc-dddd-eeeeeeeeeeee.convert.mp4"
         );
     }
 
+    #[test]
+    fn test_format_new_filename_without_uuid() {
+        let cleaned = CleanedFilename {
+            base_name: "my video".to_string(),
+            extension: "mp4".to_string(),
+            has_convert: true,
+        };
+        assert_eq!(
+            cleaner().format_new_filename(1, 0, &cleaned, None),
+            "[1] my video.convert.mp4"
+        );
+    }
+
+    #[test]
+    fn test_format_new_filename_zero_padded() {
+        let cleaned = CleanedFilename {
+            base_name: "my video".to_string(),
+            extension: "mp4".to_string(),
+            has_convert: false,
+        };
+        let uuid = "aaaaaaaa-bbbb-cccc-dddd-eeeeeeeeeeee";
+        assert_eq!(
+            cleaner().format_new_filename(7, 3, &cleaned, Some(uuid)),
+            format!("[007] my video_{uuid}.mp4")
+        );
+        // 編號超過寬度時不截斷
+        assert_eq!(
+            cleaner().format_new_filename(1234, 3, &cleaned, Some(uuid)),
+            format!("[1234] my video_{uuid}.mp4")
+        );
+        // 補零的編號在重新命名時會被移除
+        assert_eq!(cleaner().clean("[007] my video.mp4").base_name, "my video");
+    }
+
     #[test]
     fn test_clean_filename_no_extension() {
         let result = cleaner().clean("my video");
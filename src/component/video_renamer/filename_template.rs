@@ -0,0 +1,401 @@
+//! 檔名樣板模組
+//!
+//! 以 `{index:03} - {name} ({duration}).{ext}` 形式的樣板產生新檔名，
+//! 字面上的大括號寫成 `{{` 與 `}}`
+
+use crate::config::DEFAULT_FILENAME_TEMPLATE;
+use regex::Regex;
+
+/// 樣板可用的欄位
+const TOKENS: [&str; 6] = ["index", "name", "uuid", "duration", "convert", "ext"];
+
+/// `{uuid}` 產生的 UUID
+const UUID_PATTERN: &str =
+    "[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}";
+
+/// 檔名中不可出現的字元（欄位以外的文字）
+const ILLEGAL_CHARS: [char; 9] = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// 單一檔案的樣板欄位值
+#[derive(Debug, Clone)]
+pub struct TemplateValues<'a> {
+    /// 編號
+    pub index: usize,
+    /// `{index}` 未指定寬度時的補零寬度（0 表示不補零）
+    pub index_width: usize,
+    /// 清理後的檔名（不含副檔名）
+    pub name: &'a str,
+    /// 新的 UUID，`None` 時 `{uuid}` 與緊接在前的 `_` 都會省略
+    pub uuid: Option<&'a str>,
+    /// 格式化後的影片時長
+    pub duration: &'a str,
+    /// 原檔名含 `.convert` 時為 `.convert`，否則為空字串
+    pub convert: &'a str,
+    /// 副檔名（不含前導點）
+    pub ext: &'a str,
+}
+
+/// 樣板中的片段
+#[derive(Debug, PartialEq, Eq)]
+enum Segment<'a> {
+    Literal(&'a str),
+    /// 欄位名稱與補零寬度（`{index:03}` 為 `Some(3)`）
+    Token(&'a str, Option<usize>),
+}
+
+/// 解析樣板
+fn parse(template: &str) -> Result<Vec<Segment<'_>>, String> {
+    let mut segments = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find(['{', '}']) {
+        if start > 0 {
+            segments.push(Segment::Literal(&rest[..start]));
+        }
+        let tail = &rest[start..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            segments.push(Segment::Literal(&tail[..1]));
+            rest = &tail[2..];
+            continue;
+        }
+        if tail.starts_with('}') {
+            return Err("樣板中的 } 沒有對應的 {（字面上的 } 請寫成 }}）".to_string());
+        }
+        let Some(len) = tail.find('}') else {
+            return Err("樣板中的 { 沒有對應的 }（字面上的 { 請寫成 {{）".to_string());
+        };
+        let body = &tail[1..len];
+        let (name, spec) = match body.split_once(':') {
+            Some((name, spec)) => (name, Some(spec)),
+            None => (body, None),
+        };
+        if !TOKENS.contains(&name) {
+            return Err(format!("不支援的欄位 {{{body}}}"));
+        }
+        let width = match spec {
+            Some(spec) if name == "index" => Some(
+                spec.parse::<usize>()
+                    .map_err(|_| format!("無效的編號寬度 {{{body}}}"))?,
+            ),
+            Some(_) => return Err(format!("只有 {{index}} 可以指定寬度：{{{body}}}")),
+            None => None,
+        };
+        segments.push(Segment::Token(name, width));
+        rest = &tail[len + 1..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Literal(rest));
+    }
+    Ok(segments)
+}
+
+/// 檢查檔名樣板
+///
+/// 必須包含 `{index}` 與 `{name}` 以避免不同檔案產生相同檔名，以及保留副檔名的 `{ext}`；
+/// 欄位以外不可有檔名非法字元
+pub fn validate_template(template: &str) -> Result<(), String> {
+    let segments = parse(template)?;
+    for required in ["index", "name", "ext"] {
+        if !segments
+            .iter()
+            .any(|s| matches!(s, Segment::Token(name, _) if *name == required))
+        {
+            return Err(format!("樣板必須包含 {{{required}}}"));
+        }
+    }
+    if segments
+        .iter()
+        .any(|s| matches!(s, Segment::Literal(text) if text.contains(ILLEGAL_CHARS)))
+    {
+        return Err("樣板不可包含 < > : \" / \\ | ? * 等字元".to_string());
+    }
+    Ok(())
+}
+
+/// 樣板是否使用指定欄位，樣板無效時回傳 `false`
+#[must_use]
+pub fn template_uses(template: &str, token: &str) -> bool {
+    parse(template).is_ok_and(|segments| {
+        segments
+            .iter()
+            .any(|s| matches!(s, Segment::Token(name, _) if *name == token))
+    })
+}
+
+/// 解析樣板，樣板無效時改用預設樣板
+fn parse_or_default(template: &str) -> Vec<Segment<'_>> {
+    match validate_template(template) {
+        Ok(()) => parse(template),
+        Err(_) => parse(DEFAULT_FILENAME_TEMPLATE),
+    }
+    .unwrap_or_default()
+}
+
+/// 依樣板產生檔名，樣板無效時使用預設樣板
+#[must_use]
+pub fn render_template(template: &str, values: &TemplateValues<'_>) -> String {
+    let segments = parse_or_default(template);
+
+    let mut output = String::new();
+    for segment in &segments {
+        match *segment {
+            Segment::Literal(text) => output.push_str(text),
+            Segment::Token("index", width) => {
+                let width = width.unwrap_or(values.index_width);
+                output.push_str(&format!("{:0width$}", values.index));
+            }
+            Segment::Token("name", _) => output.push_str(values.name),
+            Segment::Token("uuid", _) => match values.uuid {
+                Some(uuid) => output.push_str(uuid),
+                None => {
+                    if output.ends_with('_') {
+                        output.pop();
+                    }
+                }
+            },
+            Segment::Token("duration", _) => output.push_str(values.duration),
+            Segment::Token("convert", _) => output.push_str(values.convert),
+            Segment::Token(_, _) => output.push_str(values.ext),
+        }
+    }
+    output
+}
+
+/// 比對依樣板產生的檔名，取回原本的 `{name}`
+///
+/// 以同一個樣板再次重新命名時先移除樣板加入的編號、時長與 UUID，避免這些片段一再重複
+pub struct TemplateMatcher {
+    regex: Regex,
+}
+
+impl TemplateMatcher {
+    /// 樣板無效時使用預設樣板
+    #[must_use]
+    pub fn new(template: &str) -> Self {
+        let segments = parse_or_default(template);
+        let mut pattern = String::from("^");
+        let mut captured: Vec<&str> = Vec::new();
+        for (i, segment) in segments.iter().enumerate() {
+            match *segment {
+                Segment::Literal(text) => {
+                    // 省略 UUID 時緊接在前的 `_` 也會省略，改由 UUID 的片段一併比對
+                    let text = match segments.get(i + 1) {
+                        Some(Segment::Token("uuid", _)) => text.strip_suffix('_').unwrap_or(text),
+                        _ => text,
+                    };
+                    pattern.push_str(&regex::escape(text));
+                }
+                Segment::Token("uuid", _) => {
+                    let underscore = i
+                        .checked_sub(1)
+                        .and_then(|prev| segments.get(prev))
+                        .is_some_and(
+                            |prev| matches!(prev, Segment::Literal(text) if text.ends_with('_')),
+                        );
+                    let prefix = if underscore { "_" } else { "" };
+                    pattern.push_str(&format!("(?:{prefix}{UUID_PATTERN})?"));
+                }
+                Segment::Token(name, _) => {
+                    let body = match name {
+                        "index" => r"\d+",
+                        "name" => ".+?",
+                        "duration" => "[0-9-]*",
+                        "convert" => r"(?i:\.convert)*",
+                        _ => r"[^.]+",
+                    };
+                    // 重複出現的欄位只擷取第一個
+                    if matches!(name, "index" | "name" | "convert" | "ext")
+                        && !captured.contains(&name)
+                    {
+                        captured.push(name);
+                        pattern.push_str(&format!("(?P<{name}>{body})"));
+                    } else {
+                        pattern.push_str(&format!("(?:{body})"));
+                    }
+                }
+            }
+        }
+        pattern.push('$');
+        Self {
+            regex: Regex::new(&pattern).expect("Invalid regex"),
+        }
+    }
+
+    /// 檔名符合樣板時回傳原本的檔名（`{name}` 加上 `.convert` 與副檔名），否則回傳 `None`
+    #[must_use]
+    pub fn original_name(&self, filename: &str) -> Option<String> {
+        let captures = self.regex.captures(filename)?;
+        let part = |name: &str| captures.name(name).map_or("", |m| m.as_str());
+        Some(format!(
+            "{}{}.{}",
+            part("name"),
+            part("convert"),
+            part("ext")
+        ))
+    }
+
+    /// 檔名符合樣板時回傳其中的編號，樣板沒有 `{index}` 或不符合時回傳 `None`
+    #[must_use]
+    pub fn index(&self, filename: &str) -> Option<usize> {
+        self.regex
+            .captures(filename)?
+            .name("index")?
+            .as_str()
+            .parse()
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::video_renamer::filename_cleaner::FilenameCleaner;
+
+    fn values() -> TemplateValues<'static> {
+        TemplateValues {
+            index: 7,
+            index_width: 0,
+            name: "my video",
+            uuid: Some("aaaaaaaa-bbbb-cccc-dddd-eeeeeeeeeeee"),
+            duration: "01-02-03",
+            convert: "",
+            ext: "mp4",
+        }
+    }
+
+    #[test]
+    fn test_render_custom_template() {
+        assert_eq!(
+            render_template("{index:03} - {name} ({duration}).{ext}", &values()),
+            "007 - my video (01-02-03).mp4"
+        );
+    }
+
+    #[test]
+    fn test_render_default_template() {
+        let values = TemplateValues {
+            convert: ".convert",
+            index_width: 2,
+            ..values()
+        };
+        assert_eq!(
+            render_template(DEFAULT_FILENAME_TEMPLATE, &values),
+            "[07] my video_aaaaaaaa-bbbb-cccc-dddd-eeeeeeeeeeee.convert.mp4"
+        );
+    }
+
+    #[test]
+    fn test_render_without_uuid() {
+        let values = TemplateValues {
+            uuid: None,
+            ..values()
+        };
+        assert_eq!(
+            render_template(DEFAULT_FILENAME_TEMPLATE, &values),
+            "[7] my video.mp4"
+        );
+        assert_eq!(
+            render_template("{index:02} - {name}_{uuid}_x.{ext}", &values),
+            "07 - my video_x.mp4"
+        );
+    }
+
+    #[test]
+    fn test_invalid_template_falls_back_to_default() {
+        assert_eq!(
+            render_template("{name}.{ext}", &values()),
+            "[7] my video_aaaaaaaa-bbbb-cccc-dddd-eeeeeeeeeeee.mp4"
+        );
+    }
+
+    #[test]
+    fn test_validate_template() {
+        assert!(validate_template(DEFAULT_FILENAME_TEMPLATE).is_ok());
+        assert!(validate_template("{index:03} - {name}.{ext}").is_ok());
+        assert!(validate_template("{name}.{ext}").is_err());
+        assert!(validate_template("{index}.{ext}").is_err());
+        assert!(validate_template("{index} {name} {size}.{ext}").is_err());
+        assert!(validate_template("{index:abc} {name}.{ext}").is_err());
+        assert!(validate_template("{index} {name:3}.{ext}").is_err());
+        assert!(validate_template("{index} {name.{ext}").is_err());
+        assert!(validate_template("{index}} {name}.{ext}").is_err());
+        assert!(validate_template("{index}/{name}.{ext}").is_err());
+        assert!(validate_template("{index} {name}").is_err());
+    }
+
+    #[test]
+    fn test_template_uses() {
+        assert!(template_uses(
+            "{index} {name} ({duration}).{ext}",
+            "duration"
+        ));
+        assert!(!template_uses(DEFAULT_FILENAME_TEMPLATE, "duration"));
+        assert!(!template_uses(
+            "{{duration}} {index} {name}.{ext}",
+            "duration"
+        ));
+    }
+
+    #[test]
+    fn test_template_matcher_recovers_name() {
+        let matcher = TemplateMatcher::new(DEFAULT_FILENAME_TEMPLATE);
+        assert_eq!(
+            matcher.original_name("[07] my video_aaaaaaaa-bbbb-cccc-dddd-eeeeeeeeeeee.convert.mp4"),
+            Some("my video.convert.mp4".to_string())
+        );
+        assert_eq!(
+            matcher.original_name("[7] my video.mp4"),
+            Some("my video.mp4".to_string())
+        );
+        assert_eq!(matcher.original_name("my video.mp4"), None);
+
+        let matcher = TemplateMatcher::new("{index:03} - {name} ({duration}).{ext}");
+        assert_eq!(
+            matcher.original_name("007 - my video (01-02-03).mp4"),
+            Some("my video.mp4".to_string())
+        );
+        assert_eq!(matcher.original_name("my video (1).mp4"), None);
+        assert_eq!(matcher.index("007 - my video (01-02-03).mp4"), Some(7));
+        assert_eq!(matcher.index("my video (1).mp4"), None);
+        assert_eq!(
+            TemplateMatcher::new("{name}.{ext}").index("my video.mp4"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rename_twice_is_idempotent() {
+        let cleaner = FilenameCleaner::new();
+        for template in [
+            DEFAULT_FILENAME_TEMPLATE,
+            "{index:03} - {name} ({duration}).{ext}",
+            "{name}_{index}_{uuid}{convert}.{ext}",
+        ] {
+            let matcher = TemplateMatcher::new(template);
+            let rename = |filename: &str| {
+                let original = matcher
+                    .original_name(filename)
+                    .unwrap_or_else(|| filename.to_string());
+                let cleaned = cleaner.clean(&original);
+                render_template(
+                    template,
+                    &TemplateValues {
+                        name: &cleaned.base_name,
+                        convert: cleaned.convert_suffix(),
+                        ext: &cleaned.extension,
+                        ..values()
+                    },
+                )
+            };
+            let once = rename("my video.convert.mp4");
+            assert_eq!(rename(&once), once, "{template}");
+        }
+    }
+
+    #[test]
+    fn test_render_escaped_braces() {
+        let template = "{{{index}}} {name} {{x}}.{ext}";
+        assert!(validate_template(template).is_ok());
+        assert_eq!(render_template(template, &values()), "{7} my video {x}.mp4");
+    }
+}
@@ -2,21 +2,41 @@
 //!
 //! 協調影片掃描、排序和重新命名的整體流程
 
-use super::filename_cleaner::FilenameCleaner;
-use super::video_sorter::{VideoSorter, VideoWithDuration};
-use crate::config::Config;
+use super::companion_files::{companion_new_name, companion_suffix, find_companions};
+use super::filename_cleaner::{CleanedFilename, FilenameCleaner};
+use super::filename_template::{
+    TemplateMatcher, TemplateValues, render_template, template_uses, validate_template,
+};
+use super::rename_map::{
+    RENAME_MAP_CSV, RenameMapEntry, RevertResult, has_rename_map, mark_rename_map_reverted,
+    rename_map_path, revert_rename_map, write_rename_map,
+};
+use super::rename_undo::{has_rename_undo, undo_last_rename, write_rename_undo};
+use super::video_sorter::{SortKey, VideoSorter, VideoWithDuration};
+use crate::component::auto_move_by_type::{FileDate, MoveRecord, UndoResult};
+use crate::component::path_prompt::prompt_directory;
+use crate::component::scan_options::{prompt_size_filter, report_size_filtered};
+use crate::component::{Interaction, OutputFormat, PreflightFailed, RunStatus, report};
 use crate::config::save::{add_recent_path, save_settings};
-use crate::tools::{scan_video_files, validate_directory_exists};
-use anyhow::Result;
+use crate::config::{Config, PathFeature, UserSettings};
+use crate::tools::{
+    ExcludeFilter, check_dependencies, format_size, numbered_path, scan_video_files,
+    validate_directory_exists,
+};
+use anyhow::{Context, Result, anyhow};
 use console::style;
 use dialoguer::theme::ColorfulTheme;
 use dialoguer::{Confirm, Input, Select};
 use indicatif::{ProgressBar, ProgressStyle};
 use log::warn;
+use rust_i18n::t;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
 /// 影片重新命名器
@@ -25,14 +45,183 @@ pub struct VideoRenamer {
     shutdown_signal: Arc<AtomicBool>,
     filename_cleaner: FilenameCleaner,
     video_sorter: VideoSorter,
+    interaction: Interaction,
+    output: OutputFormat,
+    /// 排序方式（不逐項詢問時使用，否則為預設選項）
+    sort_key: SortKey,
+    /// 起始編號（不逐項詢問時使用，否則為預設值）
+    start_index: usize,
+    /// 不逐項詢問時只顯示預覽
+    preview_only: bool,
+}
+
+/// 編號補零寬度上限
+const MAX_INDEX_WIDTH: usize = 10;
+
+/// 編號方式
+#[derive(Debug, Clone, Copy)]
+struct NumberingOptions {
+    /// 起始編號
+    start_index: usize,
+    /// 編號補零後的寬度（0 表示不補零）
+    index_width: usize,
+    /// 是否反轉排序（由大到小）
+    descending: bool,
+}
+
+impl NumberingOptions {
+    /// 依編號順序列出影片與其編號
+    fn numbered<'a>(
+        self,
+        videos: &'a [VideoWithDuration],
+    ) -> impl Iterator<Item = (usize, &'a VideoWithDuration)> {
+        let ordered: Box<dyn Iterator<Item = &'a VideoWithDuration>> = if self.descending {
+            Box::new(videos.iter().rev())
+        } else {
+            Box::new(videos.iter())
+        };
+        ordered
+            .enumerate()
+            .map(move |(i, video)| (self.start_index + i, video))
+    }
+
+    /// 依序列出各組影片與其編號，每組各自從該組的起始編號開始
+    fn numbered_groups(
+        self,
+        groups: &[VideoGroup],
+    ) -> impl Iterator<Item = (&VideoGroup, usize, &VideoWithDuration)> {
+        groups.iter().flat_map(move |group| {
+            let numbering = Self {
+                start_index: group.start_index,
+                ..self
+            };
+            numbering
+                .numbered(&group.videos)
+                .map(move |(index, video)| (group, index, video))
+        })
+    }
+}
+
+/// 同一資料夾中依序編號的影片
+#[derive(Debug)]
+struct VideoGroup {
+    directory: PathBuf,
+    videos: Vec<VideoWithDuration>,
+    /// 本組的起始編號
+    start_index: usize,
+}
+
+/// 依所在資料夾分組並保留排序順序，不分資料夾編號時全部為同一組
+fn group_by_directory(
+    directory: &Path,
+    videos: Vec<VideoWithDuration>,
+    per_directory: bool,
+    start_index: usize,
+) -> Vec<VideoGroup> {
+    if !per_directory {
+        return vec![VideoGroup {
+            directory: directory.to_path_buf(),
+            videos,
+            start_index,
+        }];
+    }
+    let mut groups: BTreeMap<PathBuf, Vec<VideoWithDuration>> = BTreeMap::new();
+    for video in videos {
+        let parent = video.path.parent().unwrap_or(directory).to_path_buf();
+        groups.entry(parent).or_default().push(video);
+    }
+    groups
+        .into_iter()
+        .map(|(directory, videos)| VideoGroup {
+            directory,
+            videos,
+            start_index,
+        })
+        .collect()
+}
+
+/// 略過已重新命名的影片時，各組接在這些影片已使用的最大編號之後編號，避免同一組出現重複的編號
+///
+/// 分資料夾編號時只計入同一資料夾中的影片；檔名不符合樣板的影片不影響編號
+fn continue_after_renamed(
+    groups: &mut [VideoGroup],
+    renamed: &[VideoWithDuration],
+    matcher: &TemplateMatcher,
+    per_directory: bool,
+) {
+    for group in groups {
+        let used = renamed
+            .iter()
+            .filter(|video| !per_directory || video.path.parent() == Some(&group.directory))
+            .filter_map(|video| matcher.index(&video.path.file_name()?.to_string_lossy()))
+            .max();
+        if let Some(used) = used {
+            group.start_index = group.start_index.max(used + 1);
+        }
+    }
+}
+
+/// 重新命名的執行方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RenameMode {
+    /// 預覽後確認並重新命名
+    Rename,
+    /// 只顯示預覽，不重新命名
+    PreviewOnly,
+    /// 復原上一次重新命名
+    Undo,
+    /// 依對照表改回原檔名（沒有復原紀錄時）
+    RevertMap,
 }
 
 /// 重新命名結果統計
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize)]
 struct RenameResult {
     success_count: usize,
     skip_count: usize,
     error_count: usize,
+    /// 一併重新命名的附屬檔案數
+    companion_success_count: usize,
+    /// 新檔名已存在而跳過的附屬檔案數
+    companion_skip_count: usize,
+    /// 重新命名失敗的附屬檔案數
+    companion_error_count: usize,
+    /// 是否已寫入復原紀錄
+    undo_log_written: bool,
+    /// 是否已寫入對照表
+    rename_map_written: bool,
+    /// 新檔名已被使用而加上編號的影片（改名後的路徑）
+    suffixed: Vec<PathBuf>,
+}
+
+/// 影片重新命名後的路徑
+#[derive(Debug, PartialEq, Eq)]
+struct RenameTarget {
+    path: PathBuf,
+    /// 新檔名已被使用而加上編號
+    suffixed: bool,
+}
+
+/// 一部影片的重新命名計畫
+struct PlannedRename<'a> {
+    group: &'a VideoGroup,
+    index: usize,
+    video: &'a VideoWithDuration,
+    /// 依樣板產生的新檔名
+    new_name: String,
+    /// 改名後的路徑，檔名未變更時為 `None`
+    target: Option<RenameTarget>,
+    /// 附屬檔案與其新路徑
+    companions: Vec<(PathBuf, PathBuf)>,
+    /// 新檔名已被使用而跳過的附屬檔案數
+    companion_skips: usize,
+}
+
+/// 第一階段已改為暫時檔名的檔案
+struct StagedFile {
+    original: PathBuf,
+    staging: PathBuf,
+    target: PathBuf,
 }
 
 impl VideoRenamer {
@@ -42,175 +231,720 @@ impl VideoRenamer {
             shutdown_signal,
             filename_cleaner: FilenameCleaner::new(),
             video_sorter: VideoSorter::new(),
+            interaction: Interaction::Prompt,
+            output: OutputFormat::Human,
+            sort_key: SortKey::default(),
+            start_index: 1,
+            preview_only: false,
         }
     }
 
-    pub fn run(&self) -> Result<()> {
-        println!("{}", style("=== 影片依時長排序重新命名 ===").cyan().bold());
+    #[must_use]
+    pub const fn with_interaction(mut self, interaction: Interaction) -> Self {
+        self.interaction = interaction;
+        self
+    }
+
+    #[must_use]
+    pub fn with_output(mut self, output: OutputFormat) -> Self {
+        self.output = output;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_sort_key(mut self, sort_key: SortKey) -> Self {
+        self.sort_key = sort_key;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_start_index(mut self, start_index: usize) -> Self {
+        self.start_index = start_index;
+        self
+    }
+
+    /// 不逐項詢問時只顯示預覽，不重新命名
+    #[must_use]
+    pub const fn with_preview_only(mut self, preview_only: bool) -> Self {
+        self.preview_only = preview_only;
+        self
+    }
+
+    pub fn run(&self) -> Result<RunStatus> {
+        report!(
+            self.output,
+            "{}",
+            style("=== 影片排序重新命名 ===").cyan().bold()
+        );
 
-        let Some(input_path) = self.prompt_input_path()? else {
-            return Ok(()); // ESC pressed
+        let mut settings = self.config.settings.clone();
+        let Some(input_path) = self.prompt_input_path(&mut settings)? else {
+            return Ok(RunStatus::Completed); // ESC pressed
         };
         let directory = PathBuf::from(&input_path);
 
         validate_directory_exists(&directory)?;
 
-        // 更新路徑歷史並儲存
-        {
-            let mut settings = self.config.settings.clone();
-            add_recent_path(&mut settings, &input_path);
-            if let Err(e) = save_settings(&settings) {
-                warn!("無法儲存路徑歷史: {e}");
-            }
+        // 路徑歷史與本次變更的選項一併儲存
+        add_recent_path(&mut settings, PathFeature::Renamer, &input_path);
+        let status = self.rename_in(&directory, &mut settings);
+        if let Err(e) = save_settings(&settings) {
+            warn!("無法儲存設定: {e}");
         }
+        status
+    }
+
+    /// 不詢問路徑與選項，依設定檔重新命名指定資料夾中的影片（命令列執行）
+    pub fn run_in(&self, directory: &Path) -> Result<RunStatus> {
+        report!(
+            self.output,
+            "{}",
+            style("=== 影片排序重新命名 ===").cyan().bold()
+        );
+        validate_directory_exists(directory).context(PreflightFailed)?;
+        self.rename_in(directory, &mut self.config.settings.clone())
+    }
+
+    /// 詢問選項並重新命名，選項的變更寫入 `settings`，由呼叫端儲存
+    fn rename_in(&self, directory: &Path, settings: &mut UserSettings) -> Result<RunStatus> {
+        let mode = match self.prompt_mode(directory)? {
+            None => return Ok(RunStatus::Completed), // ESC pressed
+            Some(RenameMode::Undo) => return self.run_undo(directory),
+            Some(RenameMode::RevertMap) => return self.run_revert_map(directory),
+            Some(mode) => mode,
+        };
 
         let start_index = self.prompt_start_index()?;
 
-        println!("{}", style("掃描影片檔案中...").dim());
-        let video_files = scan_video_files(&directory, &self.config.file_type_table)?;
+        let Some(sort_key) = self.prompt_sort_key()? else {
+            return Ok(RunStatus::Completed); // ESC pressed
+        };
+        let numbering = NumberingOptions {
+            start_index,
+            descending: self.prompt_descending()?,
+            index_width: self.prompt_index_width()?,
+        };
+        let template = self.prompt_filename_template(settings)?;
+        let rename_companions = self.prompt_rename_companions()?;
+        let include_uuid = self.prompt_include_uuid(settings)?;
+        let per_directory = self.prompt_number_per_directory(settings)?;
+        let skip_renamed = self.prompt_skip_renamed(settings)?;
+
+        // 檔名樣板使用 `{duration}` 時，不論排序方式都需要讀取時長
+        let read_duration = template_uses(&template, "duration");
+        // 只有排序方式或檔名樣板需要影片資訊時才需要 ffprobe
+        if (sort_key.needs_video_info() || read_duration) && !check_dependencies().ffprobe_ready() {
+            return Err(anyhow!(
+                "{}",
+                t!(
+                    "dependency.command_unavailable",
+                    details = t!("dependency.ffprobe_missing")
+                )
+            ))
+            .context(PreflightFailed);
+        }
+
+        let size_filter =
+            prompt_size_filter(&self.config.settings, self.interaction, &self.output)?;
+
+        report!(self.output, "{}", style("掃描影片檔案中...").dim());
+        let video_files = scan_video_files(
+            directory,
+            &self.config.file_type_table,
+            &ExcludeFilter::new(&self.config.settings.exclude_globs),
+            Some(&size_filter),
+            false,
+            &self.shutdown_signal,
+        )?;
+        report_size_filtered(&size_filter, &self.output);
 
         if video_files.is_empty() {
-            println!("{}", style("找不到任何影片檔案").yellow());
-            return Ok(());
+            report!(self.output, "{}", style("找不到任何影片檔案").yellow());
+            return Ok(RunStatus::Completed);
         }
 
-        println!(
+        report!(
+            self.output,
             "{}",
             style(format!("找到 {} 個影片檔案", video_files.len())).green()
         );
 
-        println!("{}", style("取得影片時長中...").dim());
-        let (sorted_videos, failed_count) = self
-            .video_sorter
-            .sort_by_duration(video_files, &self.shutdown_signal)?;
+        report!(self.output, "{}", style("取得影片資訊中...").dim());
+        let (sorted_videos, failed_count) = self.video_sorter.sort_by(
+            video_files,
+            sort_key,
+            read_duration,
+            &self.shutdown_signal,
+        )?;
 
         if self.shutdown_signal.load(Ordering::SeqCst) {
-            println!("{}", style("操作已取消").yellow());
-            return Ok(());
+            report!(self.output, "{}", style("操作已取消").yellow());
+            return Ok(RunStatus::Aborted);
         }
 
         if failed_count > 0 {
-            println!(
+            report!(
+                self.output,
                 "{}",
-                style(format!("警告：{} 個檔案無法取得時長，已跳過", failed_count)).yellow()
+                style(format!(
+                    "警告：{} 個檔案無法取得影片資訊，已跳過",
+                    failed_count
+                ))
+                .yellow()
+            );
+        }
+
+        let (sorted_videos, already_renamed): (Vec<_>, Vec<_>) =
+            sorted_videos.into_iter().partition(|video| {
+                !skip_renamed
+                    || !self
+                        .filename_cleaner
+                        .has_uuid(&video.path.file_name().unwrap_or_default().to_string_lossy())
+            });
+        if !already_renamed.is_empty() {
+            report!(
+                self.output,
+                "{}",
+                style(format!(
+                    "已略過 {} 個先前已重新命名（檔名含 UUID）的影片",
+                    already_renamed.len()
+                ))
+                .dim()
             );
         }
 
         if sorted_videos.is_empty() {
-            println!("{}", style("沒有可處理的影片檔案").yellow());
-            return Ok(());
+            report!(self.output, "{}", style("沒有可處理的影片檔案").yellow());
+            return Ok(RunStatus::Completed);
         }
 
-        self.display_preview(&sorted_videos, start_index);
+        let mut groups = group_by_directory(directory, sorted_videos, per_directory, start_index);
+        continue_after_renamed(
+            &mut groups,
+            &already_renamed,
+            &TemplateMatcher::new(&template),
+            per_directory,
+        );
+        self.display_preview(
+            &groups,
+            numbering,
+            &template,
+            rename_companions,
+            include_uuid,
+            sort_key,
+        );
+
+        // 僅預覽時不詢問確認，直接結束
+        if mode == RenameMode::PreviewOnly {
+            report!(self.output, "{}", style("僅預覽，未重新命名任何檔案").dim());
+            return Ok(RunStatus::Completed);
+        }
+        report!(
+            self.output,
+            "{}",
+            style(format!(
+                "重新命名後會將新舊檔名寫入 {}",
+                rename_map_path(directory).display()
+            ))
+            .dim()
+        );
 
-        if !self.confirm_rename()? {
-            println!("{}", style("操作已取消").yellow());
-            return Ok(());
+        if !self.confirm_rename(&groups)? {
+            report!(self.output, "{}", style("操作已取消").yellow());
+            return Ok(RunStatus::Completed);
         }
 
-        let result = self.execute_rename(&sorted_videos, start_index)?;
+        let result = self.execute_rename(
+            directory,
+            &groups,
+            numbering,
+            &template,
+            rename_companions,
+            include_uuid,
+        )?;
         self.display_summary(&result);
 
-        Ok(())
+        Ok(RunStatus::from_failures(
+            result.error_count + result.companion_error_count + failed_count,
+        ))
     }
 
-    fn prompt_input_path(&self) -> Result<Option<String>> {
-        let recent_paths = &self.config.settings.recent_paths;
-
-        // 如果沒有歷史路徑，直接輸入
-        if recent_paths.is_empty() {
-            let path: String = Input::new()
-                .with_prompt("請輸入影片資料夾路徑")
-                .interact_text()?;
-            return Ok(Some(path.trim().to_string()));
+    /// 選擇執行方式，資料夾有復原紀錄時才提供復原
+    fn prompt_mode(&self, directory: &Path) -> Result<Option<RenameMode>> {
+        if !self.interaction.prompts_options() {
+            return Ok(Some(if self.preview_only {
+                RenameMode::PreviewOnly
+            } else {
+                RenameMode::Rename
+            }));
+        }
+        let mut modes = vec![RenameMode::Rename, RenameMode::PreviewOnly];
+        let mut options = vec!["重新命名影片", "僅預覽新檔名（不重新命名）"];
+        let revert_map_label = format!("依 {RENAME_MAP_CSV} 改回原檔名");
+        if has_rename_undo(directory) {
+            modes.push(RenameMode::Undo);
+            options.push("復原上一次重新命名");
+        } else if has_rename_map(directory) {
+            modes.push(RenameMode::RevertMap);
+            options.push(&revert_map_label);
         }
-
-        // 建立選項清單：歷史路徑 + 輸入新路徑
-        let mut options: Vec<String> = recent_paths
-            .iter()
-            .enumerate()
-            .map(|(i, p)| {
-                let exists = Path::new(p).exists();
-                let indicator = if exists { "✓" } else { "✗" };
-                format!("{} [{}] {}", i + 1, indicator, p)
-            })
-            .collect();
-        options.push("輸入新路徑...".to_string());
-
-        println!("{}", style("(按 ESC 返回主選單)").dim());
-
         let selection = Select::with_theme(&ColorfulTheme::default())
-            .with_prompt("請選擇路徑")
+            .with_prompt("請選擇操作")
             .items(&options)
             .default(0)
             .interact_opt()?;
+        Ok(selection.map(|idx| modes[idx]))
+    }
 
-        match selection {
-            None => Ok(None),
-            Some(idx) if idx < recent_paths.len() => Ok(Some(recent_paths[idx].clone())),
-            Some(_) => {
-                let path: String = Input::new()
-                    .with_prompt("請輸入影片資料夾路徑")
-                    .interact_text()?;
-                Ok(Some(path.trim().to_string()))
-            }
+    fn run_undo(&self, directory: &Path) -> Result<RunStatus> {
+        if !self.interaction.confirm(
+            &format!(
+                "確定要將上一次重新命名的檔案改回原檔名嗎？（{}）",
+                directory.display()
+            ),
+            true,
+        )? {
+            report!(self.output, "{}", style("操作已取消").yellow());
+            return Ok(RunStatus::Completed);
+        }
+
+        let result = undo_last_rename(directory)?;
+        // 對照表記錄的是同一次重新命名，無法復原的項目仍保留在復原紀錄中
+        if let Err(e) = mark_rename_map_reverted(directory) {
+            warn!("{e:#}");
+        }
+        self.display_undo_result(&result);
+        Ok(RunStatus::from_failures(result.errors))
+    }
+
+    fn run_revert_map(&self, directory: &Path) -> Result<RunStatus> {
+        if !self.interaction.confirm(
+            &format!(
+                "確定要依 {} 將檔案改回原檔名嗎？",
+                rename_map_path(directory).display()
+            ),
+            true,
+        )? {
+            report!(self.output, "{}", style("操作已取消").yellow());
+            return Ok(RunStatus::Completed);
         }
+
+        let result = revert_rename_map(directory)?;
+        self.display_revert_result(&result);
+        Ok(RunStatus::from_failures(result.errors))
+    }
+
+    fn prompt_input_path(&self, settings: &mut UserSettings) -> Result<Option<String>> {
+        prompt_directory(
+            settings,
+            PathFeature::Renamer,
+            "請輸入影片資料夾路徑",
+            &self.output,
+        )
     }
 
     fn prompt_start_index(&self) -> Result<usize> {
+        if !self.interaction.prompts_options() {
+            return Ok(self.start_index);
+        }
         let index: usize = Input::new()
             .with_prompt("請輸入起始編號")
-            .default(1)
+            .default(self.start_index)
             .interact_text()?;
         Ok(index)
     }
 
-    fn confirm_rename(&self) -> Result<bool> {
-        let confirmed = Confirm::new()
-            .with_prompt("確定要重新命名這些檔案嗎？")
+    fn prompt_sort_key(&self) -> Result<Option<SortKey>> {
+        if !self.interaction.prompts_options() {
+            return Ok(Some(self.sort_key));
+        }
+        let options: Vec<String> = SortKey::ALL.iter().map(ToString::to_string).collect();
+        let default = SortKey::ALL
+            .iter()
+            .position(|key| *key == self.sort_key)
+            .unwrap_or(0);
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("請選擇排序方式")
+            .items(&options)
+            .default(default)
+            .interact_opt()?;
+        Ok(selection.map(|idx| SortKey::ALL[idx]))
+    }
+
+    fn prompt_descending(&self) -> Result<bool> {
+        if !self.interaction.prompts_options() {
+            return Ok(false);
+        }
+        let descending = Confirm::new()
+            .with_prompt("是否反轉排序（由大到小編號）？")
             .default(false)
             .interact()?;
-        Ok(confirmed)
+        Ok(descending)
+    }
+
+    fn prompt_index_width(&self) -> Result<usize> {
+        if !self.interaction.prompts_options() {
+            return Ok(0);
+        }
+        let width: usize = Input::new()
+            .with_prompt("編號補零寬度（例如 3 為 [001]，0 表示不補零）")
+            .default(0)
+            .validate_with(|width: &usize| {
+                if *width <= MAX_INDEX_WIDTH {
+                    Ok(())
+                } else {
+                    Err(format!("寬度不可超過 {MAX_INDEX_WIDTH}"))
+                }
+            })
+            .interact_text()?;
+        Ok(width)
     }
 
-    fn display_preview(&self, videos: &[VideoWithDuration], start_index: usize) {
-        println!();
-        println!(
+    /// 輸入檔名樣板，變更時寫回設定
+    fn prompt_filename_template(&self, settings: &mut UserSettings) -> Result<String> {
+        let current = &settings.renamer.filename_template;
+        if !self.interaction.prompts_options() {
+            return Ok(current.clone());
+        }
+        report!(self.output,
             "{}",
-            style("預覽重新命名結果（依時長排序，短到長）：").cyan()
+            style(
+                "可使用 {index}（{index:03} 補零）、{name}、{uuid}、{duration}、{convert}、{ext}，字面上的大括號寫成 {{ 與 }}"
+            )
+            .dim()
         );
-        println!();
+        let template: String = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("檔名樣板")
+            .with_initial_text(current.clone())
+            .validate_with(|input: &String| validate_template(input.trim()))
+            .interact_text()?;
+        let template = template.trim().to_string();
 
-        for (i, video) in videos.iter().enumerate() {
-            let current_index = start_index + i;
-            let current_name = video.path.file_name().unwrap_or_default().to_string_lossy();
-            let cleaned = self.filename_cleaner.clean(&current_name);
-            let preview_uuid = "xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx";
-            let new_name =
-                self.filename_cleaner
-                    .format_new_filename(current_index, &cleaned, preview_uuid);
+        settings.renamer.filename_template.clone_from(&template);
+        Ok(template)
+    }
 
-            let duration_str = format_duration(video.duration_seconds);
+    fn prompt_rename_companions(&self) -> Result<bool> {
+        if !self.interaction.prompts_options() {
+            return Ok(self.config.settings.renamer.rename_companions);
+        }
+        let confirmed = Confirm::new()
+            .with_prompt("是否一併重新命名同名的字幕、預覽圖等附屬檔案？")
+            .default(self.config.settings.renamer.rename_companions)
+            .interact()?;
+        Ok(confirmed)
+    }
+
+    /// 選擇新檔名是否加入 UUID，寫回設定
+    fn prompt_include_uuid(&self, settings: &mut UserSettings) -> Result<bool> {
+        if !self.interaction.prompts_options() {
+            return Ok(settings.renamer.include_uuid);
+        }
+        let include_uuid = Confirm::new()
+            .with_prompt("新檔名是否加入 UUID？")
+            .default(settings.renamer.include_uuid)
+            .interact()?;
+        settings.renamer.include_uuid = include_uuid;
+        Ok(include_uuid)
+    }
+
+    /// 選擇子資料夾是否各自編號，寫回設定
+    fn prompt_number_per_directory(&self, settings: &mut UserSettings) -> Result<bool> {
+        if !self.interaction.prompts_options() {
+            return Ok(settings.renamer.number_per_directory);
+        }
+        let per_directory = Confirm::new()
+            .with_prompt("子資料夾中的影片是否依資料夾各自編號？")
+            .default(settings.renamer.number_per_directory)
+            .interact()?;
+        settings.renamer.number_per_directory = per_directory;
+        Ok(per_directory)
+    }
 
-            println!(
+    /// 選擇是否略過已重新命名的影片，寫回設定
+    fn prompt_skip_renamed(&self, settings: &mut UserSettings) -> Result<bool> {
+        if !self.interaction.prompts_options() {
+            return Ok(settings.renamer.skip_renamed);
+        }
+        let skip_renamed = Confirm::new()
+            .with_prompt("是否略過先前已重新命名（檔名含 UUID）的影片？")
+            .default(settings.renamer.skip_renamed)
+            .interact()?;
+        settings.renamer.skip_renamed = skip_renamed;
+        Ok(skip_renamed)
+    }
+
+    /// 影片的附屬檔案與其新檔名（未啟用時為空）
+    fn companion_renames(
+        &self,
+        video: &Path,
+        new_video_name: &str,
+        enabled: bool,
+    ) -> Vec<(PathBuf, String)> {
+        if !enabled {
+            return Vec::new();
+        }
+        let stem = video.file_stem().unwrap_or_default().to_string_lossy();
+        find_companions(video, |path| {
+            self.config.file_type_table.is_video_file(path)
+        })
+        .into_iter()
+        .filter_map(|companion| {
+            let name = companion.file_name()?.to_string_lossy().to_string();
+            let new_name = companion_new_name(new_video_name, companion_suffix(&name, &stem)?);
+            Some((companion, new_name))
+        })
+        .collect()
+    }
+
+    /// 依樣板產生影片的新檔名
+    /// 清理目前的檔名；檔名是依樣板產生時先取回原本的名稱
+    fn clean_name(&self, matcher: &TemplateMatcher, current_name: &str) -> CleanedFilename {
+        match matcher.original_name(current_name) {
+            Some(original) => self.filename_cleaner.clean(&original),
+            None => self.filename_cleaner.clean(current_name),
+        }
+    }
+
+    fn new_filename(
+        template: &str,
+        index: usize,
+        numbering: NumberingOptions,
+        video: &VideoWithDuration,
+        cleaned: &CleanedFilename,
+        uuid: Option<&str>,
+    ) -> String {
+        // 時長的 `:` 不能出現在檔名中
+        let duration = video
+            .duration_seconds
+            .map(|seconds| format_duration(seconds).replace(':', "-"))
+            .unwrap_or_default();
+        render_template(
+            template,
+            &TemplateValues {
+                index,
+                index_width: numbering.index_width,
+                name: &cleaned.base_name,
+                uuid,
+                duration: &duration,
+                convert: cleaned.convert_suffix(),
+                ext: &cleaned.extension,
+            },
+        )
+    }
+
+    fn confirm_rename(&self, groups: &[VideoGroup]) -> Result<bool> {
+        let total: usize = groups.iter().map(|group| group.videos.len()).sum();
+        let prompt = if groups.len() > 1 {
+            format!(
+                "確定要重新命名 {} 個資料夾中的 {total} 個影片嗎？",
+                groups.len()
+            )
+        } else {
+            format!("確定要重新命名 {total} 個影片嗎？")
+        };
+        self.interaction.confirm(&prompt, false)
+    }
+
+    fn display_preview(
+        &self,
+        groups: &[VideoGroup],
+        numbering: NumberingOptions,
+        template: &str,
+        rename_companions: bool,
+        include_uuid: bool,
+        key: SortKey,
+    ) {
+        let order = if numbering.descending {
+            "由大到小"
+        } else {
+            "由小到大"
+        };
+        report!(self.output);
+        report!(
+            self.output,
+            "{}",
+            style(format!("預覽重新命名結果（{key}，{order}）：")).cyan()
+        );
+        report!(self.output);
+
+        let preview_uuid = "xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx";
+        let plans = self.plan_renames(groups, numbering, template, rename_companions, || {
+            include_uuid.then(|| preview_uuid.to_string())
+        });
+        for plan in &plans {
+            if groups.len() > 1 && plan.index == plan.group.start_index {
+                report!(
+                    self.output,
+                    "{}",
+                    style(format!("資料夾: {}", plan.group.directory.display())).bold()
+                );
+            }
+            let current_name = plan
+                .video
+                .path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy();
+
+            report!(
+                self.output,
                 "  {} ({}):",
-                style(format!("[{}]", current_index)).dim(),
-                style(&duration_str).cyan()
+                style(format!(
+                    "[{:0width$}]",
+                    plan.index,
+                    width = numbering.index_width
+                ))
+                .dim(),
+                style(sort_value(plan.video, key)).cyan()
             );
-            println!("    {} {}", style("舊:").dim(), current_name);
-            println!("    {} {}", style("新:").dim(), new_name);
-            println!();
+            report!(self.output, "    {} {}", style("舊:").dim(), current_name);
+            let Some(target) = &plan.target else {
+                report!(
+                    self.output,
+                    "    {} {}",
+                    style("新:").dim(),
+                    style("（檔名未變更，將跳過）").dim()
+                );
+                report!(self.output);
+                continue;
+            };
+            let final_name = target
+                .path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy();
+            if target.suffixed {
+                report!(
+                    self.output,
+                    "    {} {}",
+                    style("新:").dim(),
+                    style(format!(
+                        "{final_name}（{} 已存在，加上編號）",
+                        plan.new_name
+                    ))
+                    .yellow()
+                );
+            } else {
+                report!(self.output, "    {} {}", style("新:").dim(), final_name);
+            }
+            for (companion, new_path) in &plan.companions {
+                report!(
+                    self.output,
+                    "      {} {} → {}",
+                    style("↳").dim(),
+                    companion.file_name().unwrap_or_default().to_string_lossy(),
+                    new_path.file_name().unwrap_or_default().to_string_lossy()
+                );
+            }
+            report!(self.output);
+        }
+    }
+
+    /// 規劃所有影片與附屬檔案的新路徑
+    ///
+    /// 新檔名被本次即將改名的檔案佔用時不算撞名（例如互換 `[01]` 與 `[02]`），
+    /// 只有其他檔案或本次已分配的路徑才會讓新檔名加上編號
+    fn plan_renames<'a>(
+        &self,
+        groups: &'a [VideoGroup],
+        numbering: NumberingOptions,
+        template: &str,
+        rename_companions: bool,
+        uuid: impl Fn() -> Option<String>,
+    ) -> Vec<PlannedRename<'a>> {
+        let matcher = TemplateMatcher::new(template);
+        let mut plans: Vec<PlannedRename<'a>> = numbering
+            .numbered_groups(groups)
+            .map(|(group, index, video)| {
+                let current_name = video.path.file_name().unwrap_or_default().to_string_lossy();
+                let cleaned = self.clean_name(&matcher, &current_name);
+                let new_name = Self::new_filename(
+                    template,
+                    index,
+                    numbering,
+                    video,
+                    &cleaned,
+                    uuid().as_deref(),
+                );
+                PlannedRename {
+                    group,
+                    index,
+                    video,
+                    new_name,
+                    target: None,
+                    companions: Vec::new(),
+                    companion_skips: 0,
+                }
+            })
+            .collect();
+
+        // 檔名會變更的影片與其附屬檔案都會空出原路徑；附屬檔案依改名前的檔名比對
+        let mut vacating = HashSet::new();
+        let mut found_companions = Vec::with_capacity(plans.len());
+        for plan in &plans {
+            let path = &plan.video.path;
+            let companions = if path.with_file_name(&plan.new_name) == *path {
+                Vec::new()
+            } else {
+                vacating.insert(path.clone());
+                let companions = self.companion_renames(path, &plan.new_name, rename_companions);
+                vacating.extend(companions.iter().map(|(companion, _)| companion.clone()));
+                companions
+            };
+            found_companions.push(companions);
         }
+
+        let mut planned = HashSet::new();
+        let taken = |path: &Path, planned: &HashSet<PathBuf>| {
+            planned.contains(path) || (path.exists() && !vacating.contains(path))
+        };
+        for (plan, companions) in plans.iter_mut().zip(found_companions) {
+            let Some(target) = resolve_target(&plan.video.path, &plan.new_name, |path| {
+                taken(path, &planned)
+            }) else {
+                continue;
+            };
+            planned.insert(target.path.clone());
+            let companions = if target.suffixed {
+                let final_name = target
+                    .path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy();
+                self.companion_renames(&plan.video.path, &final_name, rename_companions)
+            } else {
+                companions
+            };
+            for (companion, name) in companions {
+                let new_path = companion.with_file_name(name);
+                if taken(&new_path, &planned) {
+                    plan.companion_skips += 1;
+                } else {
+                    planned.insert(new_path.clone());
+                    plan.companions.push((companion, new_path));
+                }
+            }
+            plan.target = Some(target);
+        }
+        plans
     }
 
+    /// 依 [`Self::plan_renames`] 的計畫改名，先全部改為暫時檔名再改為新檔名
     fn execute_rename(
         &self,
-        videos: &[VideoWithDuration],
-        start_index: usize,
+        directory: &Path,
+        groups: &[VideoGroup],
+        numbering: NumberingOptions,
+        template: &str,
+        rename_companions: bool,
+        include_uuid: bool,
     ) -> Result<RenameResult> {
         let mut result = RenameResult::default();
+        let mut renames = Vec::new();
 
-        let progress_bar = ProgressBar::new(videos.len() as u64);
+        let total: usize = groups.iter().map(|group| group.videos.len()).sum();
+        let progress_bar = ProgressBar::new(total as u64);
         progress_bar.set_style(
             ProgressStyle::default_bar()
                 .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}")
@@ -219,58 +953,359 @@ impl VideoRenamer {
         );
         progress_bar.set_message("重新命名中...");
 
-        for (i, video) in videos.iter().enumerate() {
+        let plans = self.plan_renames(groups, numbering, template, rename_companions, || {
+            include_uuid.then(|| Uuid::new_v4().to_string())
+        });
+
+        // 第一階段：改為暫時檔名，空出所有原檔名，讓互換檔名不必加上編號
+        let mut staged = Vec::new();
+        for plan in &plans {
             if self.shutdown_signal.load(Ordering::SeqCst) {
-                progress_bar.abandon_with_message("操作已中斷");
                 break;
             }
-
-            let current_index = start_index + i;
-            let current_name = video.path.file_name().unwrap_or_default().to_string_lossy();
-            let cleaned = self.filename_cleaner.clean(&current_name);
-            let new_uuid = Uuid::new_v4().to_string();
-            let new_name =
-                self.filename_cleaner
-                    .format_new_filename(current_index, &cleaned, &new_uuid);
-
-            let new_path = video.path.parent().unwrap_or(&video.path).join(&new_name);
-
-            if new_path.exists() {
+            let Some(target) = &plan.target else {
                 result.skip_count += 1;
                 progress_bar.inc(1);
                 continue;
-            }
+            };
+            let Some(video) = stage_file(&plan.video.path, &target.path) else {
+                result.error_count += 1;
+                progress_bar.inc(1);
+                continue;
+            };
+            result.companion_skip_count += plan.companion_skips;
+            let companions: Vec<StagedFile> = plan
+                .companions
+                .iter()
+                .filter_map(|(companion, new_path)| {
+                    let staged = stage_file(companion, new_path);
+                    if staged.is_none() {
+                        result.companion_error_count += 1;
+                    }
+                    staged
+                })
+                .collect();
+            staged.push((plan, target, video, companions));
+        }
 
-            match fs::rename(&video.path, &new_path) {
-                Ok(()) => {
-                    result.success_count += 1;
+        // 第二階段：改為新檔名；中斷時仍完成已改為暫時檔名的檔案，不留下暫時檔名
+        for (plan, target, video, companions) in staged {
+            if commit_staged(&video) {
+                result.success_count += 1;
+                if target.suffixed {
+                    result.suffixed.push(video.target.clone());
+                }
+                renames.push(MoveRecord {
+                    from: video.original,
+                    to: video.target,
+                    size: plan.video.size,
+                });
+                for companion in companions {
+                    let size = fs::metadata(&companion.staging).map_or(0, |m| m.len());
+                    if commit_staged(&companion) {
+                        result.companion_success_count += 1;
+                        renames.push(MoveRecord {
+                            from: companion.original,
+                            to: companion.target,
+                            size,
+                        });
+                    } else {
+                        result.companion_error_count += 1;
+                    }
                 }
-                Err(_) => {
-                    result.error_count += 1;
+            } else {
+                result.error_count += 1;
+                for companion in companions {
+                    restore_staged(&companion);
                 }
             }
-
             progress_bar.inc(1);
         }
 
-        progress_bar.finish_with_message("完成");
+        if self.shutdown_signal.load(Ordering::SeqCst) {
+            progress_bar.abandon_with_message("操作已中斷");
+        } else {
+            progress_bar.finish_with_message("完成");
+        }
+
+        if !renames.is_empty() {
+            let videos = groups.iter().flat_map(|group| &group.videos);
+            match write_rename_map(directory, &rename_map_entries(directory, videos, &renames)) {
+                Ok(()) => result.rename_map_written = true,
+                Err(e) => warn!("無法寫入重新命名對照表: {e:#}"),
+            }
+            match write_rename_undo(directory, renames) {
+                Ok(()) => result.undo_log_written = true,
+                Err(e) => warn!("無法寫入重新命名復原紀錄: {e:#}"),
+            }
+        }
 
         Ok(result)
     }
 
     fn display_summary(&self, result: &RenameResult) {
-        println!();
-        println!("{}", style("=== 重新命名結果 ===").cyan().bold());
-        println!("  成功: {} 個", style(result.success_count).green());
+        if self.output.emit_json(result) {
+            return;
+        }
+        report!(self.output);
+        report!(
+            self.output,
+            "{}",
+            style("=== 重新命名結果 ===").cyan().bold()
+        );
+        report!(
+            self.output,
+            "  成功: {} 個",
+            style(result.success_count).green()
+        );
+        if !result.suffixed.is_empty() {
+            report!(
+                self.output,
+                "  新檔名已存在而加上編號: {} 個",
+                style(result.suffixed.len()).yellow()
+            );
+            for path in &result.suffixed {
+                report!(
+                    self.output,
+                    "    {}",
+                    path.file_name().unwrap_or_default().to_string_lossy()
+                );
+            }
+        }
         if result.skip_count > 0 {
-            println!("  跳過: {} 個", style(result.skip_count).yellow());
+            report!(
+                self.output,
+                "  跳過（檔名未變更）: {} 個",
+                style(result.skip_count).yellow()
+            );
         }
         if result.error_count > 0 {
-            println!("  失敗: {} 個", style(result.error_count).red());
+            report!(
+                self.output,
+                "  失敗: {} 個",
+                style(result.error_count).red()
+            );
+        }
+        if result.companion_success_count > 0 {
+            report!(
+                self.output,
+                "  附屬檔案: {} 個",
+                style(result.companion_success_count).green()
+            );
+        }
+        if result.companion_skip_count > 0 {
+            report!(
+                self.output,
+                "  附屬檔案跳過（新檔名已存在）: {} 個",
+                style(result.companion_skip_count).yellow()
+            );
+        }
+        if result.companion_error_count > 0 {
+            report!(
+                self.output,
+                "  附屬檔案失敗: {} 個",
+                style(result.companion_error_count).red()
+            );
+        }
+        if result.rename_map_written {
+            report!(
+                self.output,
+                "{}",
+                style(format!("新舊檔名已記錄於 {RENAME_MAP_CSV}")).dim()
+            );
+        }
+        if result.undo_log_written {
+            report!(
+                self.output,
+                "{}",
+                style("可再次選擇此資料夾並選擇「復原」改回原檔名").dim()
+            );
+        }
+        self.output.report_log_file();
+    }
+
+    fn display_undo_result(&self, result: &UndoResult) {
+        report!(self.output);
+        report!(self.output, "{}", style("=== 復原結果 ===").cyan().bold());
+        report!(
+            self.output,
+            "  已改回: {} 個檔案",
+            style(result.restored).green()
+        );
+        if result.skipped > 0 {
+            report!(
+                self.output,
+                "  已略過: {} 個檔案（之後已被移動或變更）",
+                style(result.skipped).yellow()
+            );
+        }
+        if result.errors > 0 {
+            report!(
+                self.output,
+                "  失敗: {} 個檔案（保留在復原紀錄中）",
+                style(result.errors).red()
+            );
+        }
+    }
+
+    fn display_revert_result(&self, result: &RevertResult) {
+        report!(self.output);
+        report!(self.output, "{}", style("=== 還原結果 ===").cyan().bold());
+        report!(
+            self.output,
+            "  已改回: {} 個檔案",
+            style(result.restored).green()
+        );
+        if result.missing > 0 {
+            report!(
+                self.output,
+                "  已略過: {} 個檔案（新檔名已不存在）",
+                style(result.missing).yellow()
+            );
+        }
+        if result.taken > 0 {
+            report!(
+                self.output,
+                "  已略過: {} 個檔案（原檔名已被使用）",
+                style(result.taken).yellow()
+            );
+        }
+        if result.rejected > 0 {
+            report!(
+                self.output,
+                "  已略過: {} 個項目（路徑超出資料夾）",
+                style(result.rejected).yellow()
+            );
+        }
+        if result.errors > 0 {
+            report!(self.output, "  失敗: {} 個檔案", style(result.errors).red());
         }
     }
 }
 
+/// 由重新命名紀錄產生對照表，影片附上時長，路徑改為相對於資料夾
+fn rename_map_entries<'a>(
+    directory: &Path,
+    videos: impl IntoIterator<Item = &'a VideoWithDuration>,
+    renames: &[MoveRecord],
+) -> Vec<RenameMapEntry> {
+    let durations: HashMap<&Path, f64> = videos
+        .into_iter()
+        .filter_map(|video| Some((video.path.as_path(), video.duration_seconds?)))
+        .collect();
+    let renamed_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let relative = |path: &Path| path.strip_prefix(directory).unwrap_or(path).to_path_buf();
+    renames
+        .iter()
+        .map(|record| RenameMapEntry {
+            old_name: relative(&record.from),
+            new_name: relative(&record.to),
+            duration_seconds: durations.get(record.from.as_path()).copied(),
+            renamed_at,
+        })
+        .collect()
+}
+
+/// 影片重新命名後的路徑，新檔名與原檔名相同時回傳 `None`
+///
+/// `taken` 判斷路徑是否已被使用，已被使用時加上編號（`name_1.ext`）避免覆蓋，
+/// 與移動重複檔案時的處理方式相同
+fn resolve_target(
+    video: &Path,
+    new_name: &str,
+    taken: impl Fn(&Path) -> bool,
+) -> Option<RenameTarget> {
+    let target = video.parent()?.join(new_name);
+    if target == video {
+        return None;
+    }
+    let mut path = target.clone();
+    let mut counter = 1;
+    while taken(&path) {
+        path = numbered_path(&target, counter);
+        counter += 1;
+    }
+    Some(RenameTarget {
+        suffixed: path != target,
+        path,
+    })
+}
+
+/// 兩階段改名的第一階段：改為同資料夾中的隱藏暫時檔名（不會被視為附屬檔案），失敗時回傳 `None`
+fn stage_file(original: &Path, target: &Path) -> Option<StagedFile> {
+    let staging = original.with_file_name(format!(".{}.renaming", Uuid::new_v4()));
+    match fs::rename(original, &staging) {
+        Ok(()) => Some(StagedFile {
+            original: original.to_path_buf(),
+            staging,
+            target: target.to_path_buf(),
+        }),
+        Err(e) => {
+            warn!("重新命名失敗 {}: {e}", original.display());
+            None
+        }
+    }
+}
+
+/// 兩階段改名的第二階段：改為新檔名
+///
+/// 新檔名已被其他檔案佔用（例如佔用它的檔案改名失敗）或改名失敗時改回原檔名並回傳 `false`
+fn commit_staged(file: &StagedFile) -> bool {
+    if file.target.exists() {
+        warn!("新檔名已被使用，保留原檔名: {}", file.target.display());
+        restore_staged(file);
+        return false;
+    }
+    match fs::rename(&file.staging, &file.target) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("重新命名失敗 {}: {e}", file.original.display());
+            restore_staged(file);
+            false
+        }
+    }
+}
+
+/// 將暫時檔名改回原檔名，失敗時記錄暫時檔名以便手動改回
+fn restore_staged(file: &StagedFile) {
+    if let Err(e) = fs::rename(&file.staging, &file.original) {
+        warn!(
+            "無法將暫時檔名 {} 改回 {}: {e}",
+            file.staging.display(),
+            file.original.display()
+        );
+    }
+}
+
+/// 顯示影片在排序方式下的排序依據
+fn sort_value(video: &VideoWithDuration, key: SortKey) -> String {
+    match key {
+        SortKey::Duration => video
+            .duration_seconds
+            .map_or_else(|| "未知時長".to_string(), format_duration),
+        SortKey::Size => format_size(video.size),
+        SortKey::Resolution => format!("{}x{}", video.width, video.height),
+        SortKey::CreatedDate => format_date(video.created),
+        SortKey::ModifiedDate => format_date(video.modified),
+        SortKey::NaturalName => video
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string(),
+    }
+}
+
+/// 格式化檔案日期
+fn format_date(time: Option<SystemTime>) -> String {
+    time.and_then(FileDate::from_system_time).map_or_else(
+        || "未知日期".to_string(),
+        |date| format!("{:04}-{:02}-{:02}", date.year, date.month, date.day),
+    )
+}
+
 /// 格式化時長為人類可讀格式
 fn format_duration(seconds: f64) -> String {
     let total_seconds = seconds as u64;
@@ -288,6 +1323,84 @@ fn format_duration(seconds: f64) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::DEFAULT_FILENAME_TEMPLATE;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_resolve_target_suffixes_on_collision() {
+        let dir = TempDir::new().unwrap();
+        let video = dir.path().join("my video.mp4");
+        fs::write(&video, "a").unwrap();
+        fs::write(dir.path().join("[1] my video.mp4"), "b").unwrap();
+        let target = |name: &str, suffixed| {
+            Some(RenameTarget {
+                path: dir.path().join(name),
+                suffixed,
+            })
+        };
+
+        let exists = |path: &Path| path.exists();
+
+        assert_eq!(
+            resolve_target(&video, "[2] my video.mp4", exists),
+            target("[2] my video.mp4", false)
+        );
+        assert_eq!(
+            resolve_target(&video, "[1] my video.mp4", exists),
+            target("[1] my video_1.mp4", true)
+        );
+        assert_eq!(
+            resolve_target(&video, "[2] my video.mp4", |path| {
+                exists(path) || path.ends_with("[2] my video.mp4")
+            }),
+            target("[2] my video_1.mp4", true)
+        );
+        // 檔名未變更時不需改名
+        assert_eq!(resolve_target(&video, "my video.mp4", exists), None);
+    }
+
+    #[test]
+    fn test_execute_rename_swaps_names_without_suffix() {
+        let dir = TempDir::new().unwrap();
+        let base = dir.path();
+        fs::write(base.join("[02] a.mp4"), "a").unwrap();
+        fs::write(base.join("[01] b.mp4"), "b").unwrap();
+        fs::write(base.join("[01] b.srt"), "b subtitle").unwrap();
+        let mut sorted = videos(&[]);
+        for name in ["[02] a.mp4", "[01] b.mp4"] {
+            sorted.extend(videos(&[&base.join(name).to_string_lossy()]));
+        }
+        let groups = group_by_directory(base, sorted, false, 1);
+        let numbering = NumberingOptions {
+            start_index: 1,
+            index_width: 2,
+            descending: false,
+        };
+
+        let renamer = VideoRenamer::new(Config::new().unwrap(), Arc::new(AtomicBool::new(false)));
+        let result = renamer
+            .execute_rename(
+                base,
+                &groups,
+                numbering,
+                "[{index}] {name}.{ext}",
+                true,
+                false,
+            )
+            .unwrap();
+
+        assert_eq!(result.success_count, 2);
+        assert_eq!(result.companion_success_count, 1);
+        assert!(result.suffixed.is_empty());
+        assert_eq!(fs::read_to_string(base.join("[01] a.mp4")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(base.join("[02] b.mp4")).unwrap(), "b");
+        assert_eq!(
+            fs::read_to_string(base.join("[02] b.srt")).unwrap(),
+            "b subtitle"
+        );
+        assert!(!base.join("[01] b.srt").exists());
+    }
 
     #[test]
     fn test_format_duration_seconds_only() {
@@ -308,4 +1421,135 @@ mod tests {
     fn test_format_duration_zero() {
         assert_eq!(format_duration(0.0), "00:00");
     }
+
+    fn videos(names: &[&str]) -> Vec<VideoWithDuration> {
+        names
+            .iter()
+            .map(|name| VideoWithDuration {
+                path: PathBuf::from(name),
+                duration_seconds: None,
+                size: 0,
+                width: 0,
+                height: 0,
+                created: None,
+                modified: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_numbering_descending() {
+        let videos = videos(&["short.mp4", "long.mp4"]);
+        let numbered = |descending| {
+            NumberingOptions {
+                start_index: 5,
+                index_width: 0,
+                descending,
+            }
+            .numbered(&videos)
+            .map(|(index, video)| (index, video.path.to_string_lossy().to_string()))
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            numbered(false),
+            [(5, "short.mp4".to_string()), (6, "long.mp4".to_string())]
+        );
+        assert_eq!(
+            numbered(true),
+            [(5, "long.mp4".to_string()), (6, "short.mp4".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_numbering_per_directory() {
+        let sorted = videos(&[
+            "/v/show b/ep1.mp4",
+            "/v/show a/ep1.mp4",
+            "/v/intro.mp4",
+            "/v/show a/ep2.mp4",
+        ]);
+        let numbering = NumberingOptions {
+            start_index: 1,
+            index_width: 0,
+            descending: false,
+        };
+        let numbered = |per_directory| {
+            let groups = group_by_directory(Path::new("/v"), sorted.clone(), per_directory, 1);
+            numbering
+                .numbered_groups(&groups)
+                .map(|(_, index, video)| (index, video.path.to_string_lossy().to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            numbered(true),
+            [
+                (1, "/v/intro.mp4".to_string()),
+                (1, "/v/show a/ep1.mp4".to_string()),
+                (2, "/v/show a/ep2.mp4".to_string()),
+                (1, "/v/show b/ep1.mp4".to_string()),
+            ]
+        );
+        assert_eq!(
+            numbered(false)
+                .iter()
+                .map(|(index, _)| *index)
+                .collect::<Vec<_>>(),
+            [1, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn test_numbering_continues_after_renamed() {
+        let uuid = "aaaaaaaa-bbbb-cccc-dddd-eeeeeeeeeeee";
+        let renamed = videos(&[
+            &format!("/v/a/[01] one_{uuid}.mp4"),
+            &format!("/v/a/[03] three_{uuid}.mp4"),
+            &format!("/v/b/[07] seven_{uuid}.mp4"),
+            "/v/b/not from template.mp4",
+        ]);
+        let matcher = TemplateMatcher::new(DEFAULT_FILENAME_TEMPLATE);
+        let start_indices = |per_directory| {
+            let sorted = videos(&["/v/a/new.mp4", "/v/b/new.mp4", "/v/c/new.mp4"]);
+            let mut groups = group_by_directory(Path::new("/v"), sorted, per_directory, 1);
+            continue_after_renamed(&mut groups, &renamed, &matcher, per_directory);
+            groups
+                .iter()
+                .map(|group| group.start_index)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(start_indices(true), [4, 8, 1]);
+        assert_eq!(start_indices(false), [8]);
+    }
+
+    #[test]
+    fn test_result_json_schema() {
+        let result = RenameResult {
+            success_count: 3,
+            skip_count: 1,
+            error_count: 0,
+            companion_success_count: 2,
+            companion_skip_count: 0,
+            companion_error_count: 0,
+            undo_log_written: true,
+            rename_map_written: true,
+            suffixed: vec![PathBuf::from("001_a_1.mp4")],
+        };
+        assert_eq!(
+            serde_json::to_value(&result).unwrap(),
+            json!({
+                "success_count": 3,
+                "skip_count": 1,
+                "error_count": 0,
+                "companion_success_count": 2,
+                "companion_skip_count": 0,
+                "companion_error_count": 0,
+                "undo_log_written": true,
+                "rename_map_written": true,
+                "suffixed": ["001_a_1.mp4"]
+            })
+        );
+    }
 }
@@ -1,11 +1,22 @@
-//! 影片依時長排序重新命名元件
+//! 影片排序重新命名元件
 //!
-//! 掃描影片檔案，依照時長排序後重新命名
+//! 掃描影片檔案，依照時長、檔案大小、解析度或建立日期排序後重新命名
 
+mod companion_files;
 mod filename_cleaner;
+mod filename_template;
 mod main;
+mod rename_map;
+mod rename_undo;
 mod video_sorter;
 
+pub use companion_files::{companion_new_name, companion_suffix, find_companions};
 pub use filename_cleaner::{CleanedFilename, FilenameCleaner};
+pub use filename_template::{TemplateValues, render_template, template_uses, validate_template};
 pub use main::VideoRenamer;
-pub use video_sorter::{VideoSorter, VideoWithDuration};
+pub use rename_map::{
+    RENAME_MAP_CSV, RenameMapEntry, RevertResult, read_rename_map, revert_rename_map,
+    write_rename_map,
+};
+pub use rename_undo::{RENAME_UNDO_FILE, has_rename_undo, undo_last_rename, write_rename_undo};
+pub use video_sorter::{SortKey, VideoSorter, VideoWithDuration, sort_videos};
@@ -0,0 +1,254 @@
+//! 重新命名對照表
+//!
+//! 重新命名後將新舊檔名寫入資料夾中的 `rename_map.csv`，作為可閱讀的紀錄；
+//! 檔名以相對於資料夾的路徑記錄，資料夾整個搬移後仍可依此改回原檔名；
+//! 改回原檔名後對照表改名為 `rename_map.reverted.csv`，不再提供還原
+
+use crate::tools::{csv_field, parse_csv_line};
+use anyhow::{Context, Result};
+use log::warn;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// 對照表檔名
+pub const RENAME_MAP_CSV: &str = "rename_map.csv";
+
+/// 已還原的對照表檔名
+pub const REVERTED_RENAME_MAP_CSV: &str = "rename_map.reverted.csv";
+
+const HEADER: &str = "old_name,new_name,duration,renamed_at";
+
+/// 一筆重新命名紀錄
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenameMapEntry {
+    /// 原檔名（相對於重新命名的資料夾）
+    pub old_name: PathBuf,
+    /// 新檔名（相對於重新命名的資料夾）
+    pub new_name: PathBuf,
+    /// 影片時長（秒），附屬檔案為 `None`
+    pub duration_seconds: Option<f64>,
+    /// 重新命名時間（UNIX 秒）
+    pub renamed_at: u64,
+}
+
+/// 依對照表還原的結果
+#[derive(Debug, Default)]
+pub struct RevertResult {
+    /// 已改回原檔名
+    pub restored: usize,
+    /// 新檔名已不存在而略過
+    pub missing: usize,
+    /// 原檔名已被佔用而略過
+    pub taken: usize,
+    /// 路徑超出資料夾而略過
+    pub rejected: usize,
+    /// 改名失敗
+    pub errors: usize,
+}
+
+/// 對照表的路徑
+#[must_use]
+pub fn rename_map_path(directory: &Path) -> PathBuf {
+    directory.join(RENAME_MAP_CSV)
+}
+
+/// 資料夾中是否有重新命名對照表
+#[must_use]
+pub fn has_rename_map(directory: &Path) -> bool {
+    rename_map_path(directory).is_file()
+}
+
+/// 寫入對照表（覆蓋上一次的對照表）
+pub fn write_rename_map(directory: &Path, entries: &[RenameMapEntry]) -> Result<()> {
+    let mut content = format!("{HEADER}\n");
+    for entry in entries {
+        let duration = entry
+            .duration_seconds
+            .map(|seconds| format!("{seconds:.3}"))
+            .unwrap_or_default();
+        let _ = writeln!(
+            content,
+            "{},{},{duration},{}",
+            csv_field(&entry.old_name.to_string_lossy()),
+            csv_field(&entry.new_name.to_string_lossy()),
+            entry.renamed_at
+        );
+    }
+    let path = rename_map_path(directory);
+    fs::write(&path, content).with_context(|| format!("無法寫入對照表: {}", path.display()))
+}
+
+/// 讀取對照表，略過標頭與格式錯誤的列
+pub fn read_rename_map(directory: &Path) -> Result<Vec<RenameMapEntry>> {
+    let path = rename_map_path(directory);
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("無法讀取對照表: {}", path.display()))?;
+
+    let mut entries = Vec::new();
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        if line == HEADER {
+            continue;
+        }
+        let fields = parse_csv_line(line);
+        let [old_name, new_name, duration, renamed_at] = fields.as_slice() else {
+            warn!("略過格式錯誤的對照表列: {line}");
+            continue;
+        };
+        if old_name.is_empty() || new_name.is_empty() {
+            warn!("略過格式錯誤的對照表列: {line}");
+            continue;
+        }
+        entries.push(RenameMapEntry {
+            old_name: PathBuf::from(old_name),
+            new_name: PathBuf::from(new_name),
+            duration_seconds: duration.parse().ok(),
+            renamed_at: renamed_at.parse().unwrap_or(0),
+        });
+    }
+    Ok(entries)
+}
+
+/// 將對照表標記為已還原（改名為 [`REVERTED_RENAME_MAP_CSV`]），之後不再提供依對照表還原
+pub fn mark_rename_map_reverted(directory: &Path) -> Result<()> {
+    let path = rename_map_path(directory);
+    if !path.is_file() {
+        return Ok(());
+    }
+    fs::rename(&path, directory.join(REVERTED_RENAME_MAP_CSV))
+        .with_context(|| format!("無法標記對照表: {}", path.display()))
+}
+
+/// 路徑是否為資料夾內的相對路徑（不含 `..`、根目錄或磁碟代號）
+fn is_within_directory(path: &Path) -> bool {
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// 依對照表將檔案改回原檔名
+///
+/// 由最後一筆往前還原；路徑超出資料夾、新檔名已不存在或原檔名已被佔用的項目略過。
+/// 全部還原後將對照表標記為已還原
+pub fn revert_rename_map(directory: &Path) -> Result<RevertResult> {
+    let mut result = RevertResult::default();
+    for entry in read_rename_map(directory)?.iter().rev() {
+        if !is_within_directory(&entry.old_name) || !is_within_directory(&entry.new_name) {
+            warn!(
+                "略過超出資料夾的對照表項目: {} -> {}",
+                entry.new_name.display(),
+                entry.old_name.display()
+            );
+            result.rejected += 1;
+            continue;
+        }
+        let current = directory.join(&entry.new_name);
+        let original = directory.join(&entry.old_name);
+        if !current.is_file() {
+            result.missing += 1;
+            continue;
+        }
+        if original.exists() {
+            result.taken += 1;
+            continue;
+        }
+        match fs::rename(&current, &original) {
+            Ok(()) => result.restored += 1,
+            Err(e) => {
+                warn!("無法改回原檔名 {}: {e}", current.display());
+                result.errors += 1;
+            }
+        }
+    }
+    if result.taken == 0 && result.errors == 0 {
+        mark_rename_map_reverted(directory)?;
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(old_name: &str, new_name: &str, duration_seconds: Option<f64>) -> RenameMapEntry {
+        RenameMapEntry {
+            old_name: PathBuf::from(old_name),
+            new_name: PathBuf::from(new_name),
+            duration_seconds,
+            renamed_at: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn test_write_and_read_rename_map() {
+        let dir = TempDir::new().unwrap();
+        let entries = vec![
+            entry("my, video.mp4", "[1] my, video.mp4", Some(61.5)),
+            entry("my, video.srt", "[1] my, video.srt", None),
+            entry("show/ep \"1\".mkv", "show/[2] ep \"1\".mkv", Some(1200.0)),
+        ];
+        write_rename_map(dir.path(), &entries).unwrap();
+        assert!(has_rename_map(dir.path()));
+        assert_eq!(read_rename_map(dir.path()).unwrap(), entries);
+    }
+
+    #[test]
+    fn test_rename_and_revert_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let base = dir.path();
+        for name in ["b.mp4", "c.mp4", "d.mp4"] {
+            fs::write(base.join(name), name).unwrap();
+        }
+        let entries = vec![
+            entry("a.mp4", "[1] a.mp4", Some(10.0)),
+            entry("b.mp4", "[2] b.mp4", Some(20.0)),
+            entry("c.mp4", "[3] c.mp4", Some(30.0)),
+            entry("d.mp4", "[4] d.mp4", Some(40.0)),
+        ];
+        for entry in &entries[1..] {
+            fs::rename(base.join(&entry.old_name), base.join(&entry.new_name)).unwrap();
+        }
+        write_rename_map(base, &entries).unwrap();
+        // 原檔名被其他檔案佔用
+        fs::write(base.join("c.mp4"), "other").unwrap();
+
+        let result = revert_rename_map(base).unwrap();
+        assert_eq!(result.restored, 2);
+        assert_eq!(result.missing, 1);
+        assert_eq!(result.taken, 1);
+        assert_eq!(fs::read_to_string(base.join("b.mp4")).unwrap(), "b.mp4");
+        assert_eq!(fs::read_to_string(base.join("d.mp4")).unwrap(), "d.mp4");
+        assert_eq!(fs::read_to_string(base.join("c.mp4")).unwrap(), "other");
+        assert!(base.join("[3] c.mp4").exists());
+        // 還有未還原的項目時保留對照表
+        assert!(has_rename_map(base));
+    }
+
+    #[test]
+    fn test_revert_rejects_paths_outside_directory() {
+        let dir = TempDir::new().unwrap();
+        let base = dir.path().join("videos");
+        fs::create_dir(&base).unwrap();
+        fs::write(base.join("[1] a.mp4"), "a").unwrap();
+        fs::write(base.join("[2] b.mp4"), "b").unwrap();
+        let outside = dir.path().join("outside.mp4");
+        let entries = vec![
+            entry("a.mp4", "[1] a.mp4", None),
+            entry("../outside.mp4", "[2] b.mp4", None),
+            entry(&outside.to_string_lossy(), "[2] b.mp4", None),
+        ];
+        write_rename_map(&base, &entries).unwrap();
+
+        let result = revert_rename_map(&base).unwrap();
+        assert_eq!(result.restored, 1);
+        assert_eq!(result.rejected, 2);
+        assert!(base.join("a.mp4").exists());
+        assert!(base.join("[2] b.mp4").exists());
+        assert!(!outside.exists());
+        // 全部處理完後不再提供依對照表還原
+        assert!(!has_rename_map(&base));
+        assert!(base.join(REVERTED_RENAME_MAP_CSV).exists());
+    }
+}
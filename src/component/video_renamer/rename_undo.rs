@@ -0,0 +1,75 @@
+//! 重新命名復原紀錄
+//!
+//! 重新命名後將新舊檔名寫入影片資料夾中的 `.video_rename_undo.json`，
+//! 格式與依類型整理的復原紀錄相同
+
+use crate::component::auto_move_by_type::{
+    MoveRecord, UndoResult, undo_moves_at, write_undo_log_at,
+};
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+/// 重新命名復原紀錄檔名稱
+pub const RENAME_UNDO_FILE: &str = ".video_rename_undo.json";
+
+/// 重新命名復原紀錄的路徑
+#[must_use]
+pub fn rename_undo_path(directory: &Path) -> PathBuf {
+    directory.join(RENAME_UNDO_FILE)
+}
+
+/// 資料夾中是否有可復原的重新命名紀錄
+#[must_use]
+pub fn has_rename_undo(directory: &Path) -> bool {
+    rename_undo_path(directory).is_file()
+}
+
+/// 寫入重新命名紀錄（覆蓋上一次的紀錄）
+pub fn write_rename_undo(directory: &Path, renames: Vec<MoveRecord>) -> Result<()> {
+    write_undo_log_at(&rename_undo_path(directory), renames)
+}
+
+/// 將上一次重新命名的檔案改回原檔名
+///
+/// 只還原目前仍使用新檔名且大小相同的檔案，原檔名已被佔用的項目保留在紀錄中
+pub fn undo_last_rename(directory: &Path) -> Result<UndoResult> {
+    undo_moves_at(&rename_undo_path(directory), directory)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_undo_last_rename() {
+        let dir = TempDir::new().unwrap();
+        let base = dir.path();
+        fs::write(base.join("[1] a.mp4"), "a").unwrap();
+        fs::write(base.join("[2] b.mp4"), "bb").unwrap();
+        fs::write(base.join("c.mp4"), "occupied").unwrap();
+        let renames = vec![
+            MoveRecord {
+                from: base.join("a.mp4"),
+                to: base.join("[1] a.mp4"),
+                size: 1,
+            },
+            MoveRecord {
+                from: base.join("c.mp4"),
+                to: base.join("[2] b.mp4"),
+                size: 2,
+            },
+        ];
+        write_rename_undo(base, renames).unwrap();
+        assert!(has_rename_undo(base));
+
+        let result = undo_last_rename(base).unwrap();
+        assert_eq!(result.restored, 1);
+        assert_eq!(result.errors, 1);
+        assert_eq!(fs::read_to_string(base.join("a.mp4")).unwrap(), "a");
+        assert!(base.join("[2] b.mp4").exists());
+        // 無法復原的項目保留在紀錄中
+        assert!(has_rename_undo(base));
+    }
+}
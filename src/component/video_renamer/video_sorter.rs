@@ -1,24 +1,152 @@
 //! 影片排序模組
 //!
-//! 負責取得影片時長並依時長排序
+//! 負責取得影片時長、解析度等資訊，並依指定的排序方式排序；
+//! 排序方式不需要時不呼叫 ffprobe
 
 use crate::tools::{VideoFileInfo, get_video_info};
 use anyhow::Result;
 use indicatif::{ProgressBar, ProgressStyle};
 use rayon::prelude::*;
+use std::cmp::Ordering as CmpOrdering;
+use std::fmt;
+use std::fs;
+use std::iter::Peekable;
 use std::path::PathBuf;
+use std::str::Chars;
 use std::sync::Mutex;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::SystemTime;
 
-/// 包含時長資訊的影片結構
+/// 影片排序方式（皆為由小到大、由舊到新）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortKey {
+    /// 依時長（短到長）
+    #[default]
+    Duration,
+    /// 依檔案大小（小到大）
+    Size,
+    /// 依解析度像素數（低到高）
+    Resolution,
+    /// 依建立日期（舊到新）
+    CreatedDate,
+    /// 依修改日期（舊到新）
+    ModifiedDate,
+    /// 依檔名自然排序（`ep2` 在 `ep10` 之前）
+    NaturalName,
+}
+
+impl SortKey {
+    pub const ALL: [Self; 6] = [
+        Self::Duration,
+        Self::Size,
+        Self::Resolution,
+        Self::CreatedDate,
+        Self::ModifiedDate,
+        Self::NaturalName,
+    ];
+
+    /// 是否需要以 ffprobe 讀取影片資訊
+    #[must_use]
+    pub const fn needs_video_info(self) -> bool {
+        matches!(self, Self::Duration | Self::Resolution)
+    }
+}
+
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Duration => write!(f, "依時長排序，短到長"),
+            Self::Size => write!(f, "依檔案大小排序，小到大"),
+            Self::Resolution => write!(f, "依解析度排序，低到高"),
+            Self::CreatedDate => write!(f, "依建立日期排序，舊到新"),
+            Self::ModifiedDate => write!(f, "依修改日期排序，舊到新"),
+            Self::NaturalName => write!(f, "依檔名排序（ep2 在 ep10 之前）"),
+        }
+    }
+}
+
+/// 包含時長等排序資訊的影片結構
 #[derive(Debug, Clone)]
 pub struct VideoWithDuration {
     /// 影片路徑
     pub path: PathBuf,
-    /// 影片時長（秒）
-    pub duration_seconds: f64,
+    /// 影片時長（秒），未讀取影片資訊時為 `None`
+    pub duration_seconds: Option<f64>,
     /// 檔案大小（位元組）
     pub size: u64,
+    /// 影片寬度（像素），未讀取影片資訊時為 0
+    pub width: u32,
+    /// 影片高度（像素），未讀取影片資訊時為 0
+    pub height: u32,
+    /// 檔案建立時間（檔案系統不支援時為修改時間）
+    pub created: Option<SystemTime>,
+    /// 檔案修改時間
+    pub modified: Option<SystemTime>,
+}
+
+impl VideoWithDuration {
+    /// 解析度像素數
+    #[must_use]
+    pub const fn pixel_count(&self) -> u64 {
+        self.width as u64 * self.height as u64
+    }
+}
+
+/// 依排序方式排序，相同時依路徑排序
+pub fn sort_videos(videos: &mut [VideoWithDuration], key: SortKey) {
+    videos.sort_by(|a, b| {
+        let primary = match key {
+            SortKey::Duration => a
+                .duration_seconds
+                .partial_cmp(&b.duration_seconds)
+                .unwrap_or(CmpOrdering::Equal),
+            SortKey::Size => a.size.cmp(&b.size),
+            SortKey::Resolution => a.pixel_count().cmp(&b.pixel_count()),
+            SortKey::CreatedDate => a.created.cmp(&b.created),
+            SortKey::ModifiedDate => a.modified.cmp(&b.modified),
+            SortKey::NaturalName => natural_cmp(
+                &a.path.file_name().unwrap_or_default().to_string_lossy(),
+                &b.path.file_name().unwrap_or_default().to_string_lossy(),
+            ),
+        };
+        primary.then_with(|| a.path.cmp(&b.path))
+    });
+}
+
+/// 自然排序：連續數字依數值比較，其餘字元不分大小寫比較
+fn natural_cmp(a: &str, b: &str) -> CmpOrdering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+    loop {
+        let ordering = match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return CmpOrdering::Equal,
+            (None, Some(_)) => return CmpOrdering::Less,
+            (Some(_), None) => return CmpOrdering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let x = take_digits(&mut a);
+                let y = take_digits(&mut b);
+                let (x, y) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                x.len().cmp(&y.len()).then_with(|| x.cmp(y))
+            }
+            (Some(x), Some(y)) => {
+                a.next();
+                b.next();
+                x.to_lowercase().cmp(y.to_lowercase())
+            }
+        };
+        if ordering != CmpOrdering::Equal {
+            return ordering;
+        }
+    }
+}
+
+/// 取出開頭的連續數字
+fn take_digits(chars: &mut Peekable<Chars<'_>>) -> String {
+    let mut digits = String::new();
+    while let Some(c) = chars.next_if(char::is_ascii_digit) {
+        digits.push(c);
+    }
+    digits
 }
 
 /// 影片排序器
@@ -48,6 +176,21 @@ impl VideoSorter {
         videos: Vec<VideoFileInfo>,
         shutdown_signal: &AtomicBool,
     ) -> Result<(Vec<VideoWithDuration>, usize)> {
+        self.sort_by(videos, SortKey::Duration, true, shutdown_signal)
+    }
+
+    /// 取得影片資訊並依指定方式排序
+    ///
+    /// 時長與解析度來自 ffprobe，建立與修改日期來自檔案系統；排序方式需要或
+    /// `read_video_info` 為 `true` 時才呼叫 ffprobe，無法讀取影片資訊的檔案會被略過並計入失敗數量
+    pub fn sort_by(
+        &self,
+        videos: Vec<VideoFileInfo>,
+        key: SortKey,
+        read_video_info: bool,
+        shutdown_signal: &AtomicBool,
+    ) -> Result<(Vec<VideoWithDuration>, usize)> {
+        let read_video_info = read_video_info || key.needs_video_info();
         let progress_bar = ProgressBar::new(videos.len() as u64);
         progress_bar.set_style(
             ProgressStyle::default_bar()
@@ -55,7 +198,7 @@ impl VideoSorter {
                 .expect("Invalid progress bar template")
                 .progress_chars("#>-"),
         );
-        progress_bar.set_message("取得影片時長中...");
+        progress_bar.set_message("取得影片資訊中...");
 
         let results: Mutex<Vec<VideoWithDuration>> = Mutex::new(Vec::with_capacity(videos.len()));
         let failed_count: Mutex<usize> = Mutex::new(0);
@@ -65,33 +208,44 @@ impl VideoSorter {
                 return;
             }
 
-            match get_video_info(&video.path) {
-                Ok(info) => {
-                    let video_with_duration = VideoWithDuration {
-                        path: video.path.clone(),
-                        duration_seconds: info.duration_seconds,
-                        size: video.size,
-                    };
-                    results.lock().unwrap().push(video_with_duration);
-                }
-                Err(_) => {
-                    *failed_count.lock().unwrap() += 1;
+            let info = if read_video_info {
+                match get_video_info(&video.path) {
+                    Ok(info) => Some(info),
+                    Err(_) => {
+                        *failed_count.lock().unwrap() += 1;
+                        progress_bar.inc(1);
+                        return;
+                    }
                 }
-            }
+            } else {
+                None
+            };
+            let metadata = fs::metadata(&video.path).ok();
+            let modified = metadata.as_ref().and_then(|m| m.modified().ok());
+            let video_with_duration = VideoWithDuration {
+                path: video.path.clone(),
+                duration_seconds: info.as_ref().map(|info| info.duration_seconds),
+                size: video.size,
+                width: info.as_ref().map_or(0, |info| info.width),
+                height: info.as_ref().map_or(0, |info| info.height),
+                created: metadata.and_then(|m| m.created().ok()).or(modified),
+                modified,
+            };
+            results.lock().unwrap().push(video_with_duration);
 
             progress_bar.inc(1);
         });
 
-        progress_bar.finish_with_message("完成");
+        if shutdown_signal.load(Ordering::SeqCst) {
+            progress_bar.abandon_with_message("操作已中斷");
+        } else {
+            progress_bar.finish_with_message("完成");
+        }
 
         let mut sorted_videos = results.into_inner().unwrap();
         let failed = *failed_count.lock().unwrap();
 
-        sorted_videos.sort_by(|a, b| {
-            a.duration_seconds
-                .partial_cmp(&b.duration_seconds)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
+        sort_videos(&mut sorted_videos, key);
 
         Ok((sorted_videos, failed))
     }
@@ -101,35 +255,94 @@ impl VideoSorter {
 mod tests {
     use super::*;
 
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn video(
+        name: &str,
+        duration_seconds: f64,
+        size: u64,
+        (width, height): (u32, u32),
+        created_secs: u64,
+    ) -> VideoWithDuration {
+        VideoWithDuration {
+            path: PathBuf::from(name),
+            duration_seconds: Some(duration_seconds),
+            size,
+            width,
+            height,
+            created: Some(UNIX_EPOCH + Duration::from_secs(created_secs)),
+            modified: Some(UNIX_EPOCH + Duration::from_secs(1000 - created_secs)),
+        }
+    }
+
+    fn sorted_names(videos: &[VideoWithDuration], key: SortKey) -> Vec<String> {
+        let mut videos = videos.to_vec();
+        sort_videos(&mut videos, key);
+        videos
+            .iter()
+            .map(|v| v.path.display().to_string())
+            .collect()
+    }
+
     #[test]
     fn test_video_with_duration_sorting() {
         let mut videos = [
-            VideoWithDuration {
-                path: PathBuf::from("/a.mp4"),
-                duration_seconds: 120.0,
-                size: 1000,
-            },
-            VideoWithDuration {
-                path: PathBuf::from("/b.mp4"),
-                duration_seconds: 60.0,
-                size: 500,
-            },
-            VideoWithDuration {
-                path: PathBuf::from("/c.mp4"),
-                duration_seconds: 180.0,
-                size: 2000,
-            },
+            video("/a.mp4", 120.0, 1000, (1920, 1080), 0),
+            video("/b.mp4", 60.0, 500, (1920, 1080), 0),
+            video("/c.mp4", 180.0, 2000, (1920, 1080), 0),
         ];
 
-        videos.sort_by(|a, b| {
-            a.duration_seconds
-                .partial_cmp(&b.duration_seconds)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
+        sort_videos(&mut videos, SortKey::Duration);
 
-        assert_eq!(videos[0].duration_seconds, 60.0);
-        assert_eq!(videos[1].duration_seconds, 120.0);
-        assert_eq!(videos[2].duration_seconds, 180.0);
+        assert_eq!(videos[0].duration_seconds, Some(60.0));
+        assert_eq!(videos[1].duration_seconds, Some(120.0));
+        assert_eq!(videos[2].duration_seconds, Some(180.0));
+    }
+
+    #[test]
+    fn test_sort_keys() {
+        let videos = [
+            video("/a.mp4", 30.0, 3000, (1280, 720), 200),
+            video("/b.mp4", 10.0, 1000, (3840, 2160), 300),
+            video("/c.mp4", 20.0, 2000, (640, 480), 100),
+        ];
+
+        assert_eq!(
+            sorted_names(&videos, SortKey::Size),
+            ["/b.mp4", "/c.mp4", "/a.mp4"]
+        );
+        assert_eq!(
+            sorted_names(&videos, SortKey::Resolution),
+            ["/c.mp4", "/a.mp4", "/b.mp4"]
+        );
+        assert_eq!(
+            sorted_names(&videos, SortKey::CreatedDate),
+            ["/c.mp4", "/a.mp4", "/b.mp4"]
+        );
+        assert_eq!(
+            sorted_names(&videos, SortKey::ModifiedDate),
+            ["/b.mp4", "/a.mp4", "/c.mp4"]
+        );
+    }
+
+    #[test]
+    fn test_natural_name_sort() {
+        let videos = [
+            "/Show ep10.mp4",
+            "/show ep2.mp4",
+            "/show ep02b.mp4",
+            "/show ep1.mp4",
+        ]
+        .map(|name| video(name, 0.0, 0, (0, 0), 0));
+        assert_eq!(
+            sorted_names(&videos, SortKey::NaturalName),
+            [
+                "/show ep1.mp4",
+                "/show ep2.mp4",
+                "/show ep02b.mp4",
+                "/Show ep10.mp4"
+            ]
+        );
     }
 
     #[test]
@@ -1,15 +1,43 @@
 use crate::config::types::{Config, FileTypeTable, UserSettings};
+use crate::tools::expand_home;
 use anyhow::{Context, Result};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// 編譯時嵌入的檔案類型設定（不需要外部檔案）
 const FILE_TYPE_TABLE_JSON: &str = include_str!("../data/file_type_table.json");
 
+/// 設定檔名稱
+pub const SETTINGS_FILE: &str = "settings.json";
+
+/// 設定檔所在的資料夾
+///
+/// 工作目錄已有設定檔時沿用（舊版的位置），否則使用
+/// `$XDG_CONFIG_HOME/auto_video_organize`，未設定時使用 `~/.config`
+#[must_use]
+pub fn config_directory() -> PathBuf {
+    if Path::new(SETTINGS_FILE).is_file()
+        && let Ok(cwd) = std::env::current_dir()
+    {
+        return cwd;
+    }
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map_or_else(|| expand_home("~/.config"), PathBuf::from)
+        .join("auto_video_organize")
+}
+
+/// 設定檔路徑
+#[must_use]
+pub fn settings_file_path() -> PathBuf {
+    config_directory().join(SETTINGS_FILE)
+}
+
 impl Config {
     pub fn new() -> Result<Self> {
-        let file_type_table = Self::load_embedded_file_type_table()?;
+        let mut file_type_table = Self::load_embedded_file_type_table()?;
         let settings = Self::load_settings().unwrap_or_default();
+        file_type_table.merge_extra_extensions(&settings.extra_extensions);
 
         Ok(Self {
             file_type_table,
@@ -17,17 +45,31 @@ impl Config {
         })
     }
 
+    /// 重新讀取設定檔，讓各功能執行期間儲存的設定（例如最近使用的路徑）反映到選單
+    pub fn reload_settings(&mut self) -> Result<()> {
+        let settings = Self::load_settings()?;
+        let mut file_type_table = Self::load_embedded_file_type_table()?;
+        file_type_table.merge_extra_extensions(&settings.extra_extensions);
+        self.file_type_table = file_type_table;
+        self.settings = settings;
+        Ok(())
+    }
+
     fn load_settings() -> Result<UserSettings> {
-        let path = Path::new("settings.json");
+        let path = settings_file_path();
         if !path.exists() {
             return Ok(UserSettings::default());
         }
 
-        let content = fs::read_to_string(path)
+        let content = fs::read_to_string(&path)
             .with_context(|| format!("Failed to read settings from {}", path.display()))?;
 
-        serde_json::from_str(&content)
-            .with_context(|| format!("Failed to parse settings from {}", path.display()))
+        let mut settings: UserSettings = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse settings from {}", path.display()))?;
+        settings.video_encoder.sanitize();
+        settings.duplication.perceptual.sanitize();
+        settings.sanitize_category_folder_overrides();
+        Ok(settings)
     }
 
     /// 從編譯時嵌入的 JSON 載入檔案類型表
@@ -3,6 +3,12 @@ pub mod save;
 pub mod types;
 
 pub use types::{
-    Config, ContactSheetOutputMode, ContactSheetSettings, FileCategory, FileTypeTable, Language,
-    MAX_RECENT_PATHS, PostEncodeAction, UserSettings, VideoEncoderSettings,
+    AudioMode, Config, ContactSheetFormat, ContactSheetOutputMode, ContactSheetSettings,
+    DEFAULT_AAC_BITRATE, DEFAULT_CRF, DEFAULT_FILENAME_TEMPLATE, DEFAULT_MAX_GPU_PARALLEL,
+    DEFAULT_PRESET, DateGranularity, DateOrganizeSettings, DeletionMode, DuplicateAction,
+    DuplicationSettings, EncodeBackend, EncoderCodec, FileCategory, FileTypeTable, KeepPolicy,
+    Language, LogLevel, LogSettings, MAX_AV1_CRF, MAX_CRF, MAX_PERCEPTUAL_THRESHOLD,
+    MAX_RECENT_PATHS, MatchMode, OrganizeBy, OrphanPairingMode, OrphanSettings, PathFeature,
+    PerceptualSettings, PostEncodeAction, RecentPathList, RenamerSettings, StemNormalization,
+    UserSettings, VideoEncoderSettings, is_valid_folder_name,
 };
@@ -1,28 +1,26 @@
-use crate::config::types::{MAX_RECENT_PATHS, UserSettings};
+use crate::config::load::settings_file_path;
+use crate::config::types::{PathFeature, UserSettings};
 use anyhow::{Context, Result};
 use std::fs;
-use std::path::Path;
 
 pub fn save_settings(settings: &UserSettings) -> Result<()> {
-    // Save to settings.json in the current working directory
-    let path = Path::new("settings.json");
+    // Save to settings.json in the config directory
+    let path = settings_file_path();
     let content = serde_json::to_string_pretty(settings).context("Failed to serialize settings")?;
 
-    fs::write(path, content)
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(&path, content)
         .with_context(|| format!("Failed to write settings to {}", path.display()))?;
 
     Ok(())
 }
 
-/// 更新最近使用的路徑
-/// 將新路徑加入最前面，去重並限制數量
-pub fn add_recent_path(settings: &mut UserSettings, path: &str) {
-    // 移除已存在的相同路徑
-    settings.recent_paths.retain(|p| p != path);
-
-    // 加入到最前面
-    settings.recent_paths.insert(0, path.to_string());
-
-    // 限制數量
-    settings.recent_paths.truncate(MAX_RECENT_PATHS);
+/// 更新功能的最近使用路徑
+/// 將新路徑加入最前面，去重並限制數量（釘選的路徑不受影響）
+pub fn add_recent_path(settings: &mut UserSettings, feature: PathFeature, path: &str) {
+    let limit = settings.max_recent_paths();
+    settings.recent_path_list_mut(feature).record(path, limit);
 }
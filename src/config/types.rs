@@ -1,7 +1,9 @@
+use log::warn;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
-use std::path::Path;
+use std::ops::RangeInclusive;
+use std::path::{Component, Path, PathBuf};
 
 /// 支援的語言
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
@@ -52,6 +54,17 @@ pub enum PostEncodeAction {
     /// 移動新影片（轉檔後檔案）到 finish 資料夾
     #[serde(rename = "move_new_to_finish")]
     MoveNewToFinish,
+    /// 新影片放到原始檔案旁，原始檔案依刪除方式移到 finish 資料夾或資源回收筒
+    #[serde(rename = "replace_in_place")]
+    ReplaceInPlace,
+}
+
+impl PostEncodeAction {
+    /// 是否需要 finish 資料夾
+    #[must_use]
+    pub const fn uses_finish_directory(self) -> bool {
+        matches!(self, Self::MoveOldToFinish | Self::MoveNewToFinish)
+    }
 }
 
 impl fmt::Display for PostEncodeAction {
@@ -60,6 +73,575 @@ impl fmt::Display for PostEncodeAction {
             Self::None => write!(f, "不移動"),
             Self::MoveOldToFinish => write!(f, "移動舊影片到 finish"),
             Self::MoveNewToFinish => write!(f, "移動新影片到 finish"),
+            Self::ReplaceInPlace => write!(f, "取代原始檔案"),
+        }
+    }
+}
+
+/// 重複檔案的處理方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum DuplicateAction {
+    /// 移動到 duplication_file 資料夾（預設）
+    #[default]
+    #[serde(rename = "move")]
+    Move,
+    /// 直接刪除
+    #[serde(rename = "delete")]
+    Delete,
+    /// 刪除後以指向原始檔案的硬連結取代
+    #[serde(rename = "hardlink")]
+    Hardlink,
+}
+
+impl DuplicateAction {
+    /// 所有處理方式
+    pub const ALL: [Self; 3] = [Self::Move, Self::Delete, Self::Hardlink];
+}
+
+impl fmt::Display for DuplicateAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Move => write!(f, "移動到 duplication_file"),
+            Self::Delete => write!(f, "直接刪除"),
+            Self::Hardlink => write!(f, "以硬連結取代"),
+        }
+    }
+}
+
+/// 不要的檔案（重複檔案、失敗的輸出）的去向
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum DeletionMode {
+    /// 移到 duplication_file 等資料夾（預設）
+    #[default]
+    #[serde(rename = "move_to_folder")]
+    MoveToFolder,
+    /// 送到系統資源回收筒，系統不支援時改移到資料夾
+    #[serde(rename = "system_trash")]
+    SystemTrash,
+}
+
+impl DeletionMode {
+    /// 所有丟棄方式
+    pub const ALL: [Self; 2] = [Self::MoveToFolder, Self::SystemTrash];
+}
+
+impl fmt::Display for DeletionMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MoveToFolder => write!(f, "移到資料夾"),
+            Self::SystemTrash => write!(f, "送到資源回收筒"),
+        }
+    }
+}
+
+/// 資料夾模式下每組重複檔案保留哪一個
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum KeepPolicy {
+    /// 修改時間最早的檔案（預設）
+    #[default]
+    #[serde(rename = "oldest")]
+    Oldest,
+    /// 修改時間最新的檔案
+    #[serde(rename = "newest")]
+    Newest,
+    /// 路徑最短的檔案
+    #[serde(rename = "shortest_path")]
+    ShortestPath,
+}
+
+impl KeepPolicy {
+    /// 所有保留方式
+    pub const ALL: [Self; 3] = [Self::Oldest, Self::Newest, Self::ShortestPath];
+}
+
+impl fmt::Display for KeepPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Oldest => write!(f, "保留最舊的檔案"),
+            Self::Newest => write!(f, "保留最新的檔案"),
+            Self::ShortestPath => write!(f, "保留路徑最短的檔案"),
+        }
+    }
+}
+
+/// 紀錄模式下重複檔案的比對方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum MatchMode {
+    /// 內容完全相同（BLAKE3，預設）
+    #[default]
+    #[serde(rename = "exact")]
+    Exact,
+    /// 另比對影片畫面，找出重新編碼或縮放過的影片
+    #[serde(rename = "perceptual")]
+    Perceptual,
+}
+
+impl MatchMode {
+    /// 所有比對方式
+    pub const ALL: [Self; 2] = [Self::Exact, Self::Perceptual];
+}
+
+impl fmt::Display for MatchMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exact => write!(f, "內容完全相同"),
+            Self::Perceptual => write!(f, "畫面相似（含重新編碼的影片）"),
+        }
+    }
+}
+
+/// 畫面相似比對設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerceptualSettings {
+    /// 每部影片取樣的影格數
+    #[serde(default = "PerceptualSettings::default_frames")]
+    pub frames: usize,
+    /// 視為相似的平均每影格 Hamming 距離上限（0–64，越小越嚴格）
+    #[serde(default = "PerceptualSettings::default_threshold")]
+    pub threshold: u32,
+}
+
+impl PerceptualSettings {
+    const fn default_frames() -> usize {
+        8
+    }
+    const fn default_threshold() -> u32 {
+        10
+    }
+
+    /// 修正超出範圍的設定值（載入設定檔後呼叫）
+    pub fn sanitize(&mut self) {
+        if self.frames == 0 {
+            warn!("取樣影格數不可為 0，改用 1");
+            self.frames = 1;
+        }
+        if self.threshold > MAX_PERCEPTUAL_THRESHOLD {
+            warn!(
+                "相似度門檻 {} 超出範圍，改用 {MAX_PERCEPTUAL_THRESHOLD}",
+                self.threshold
+            );
+            self.threshold = MAX_PERCEPTUAL_THRESHOLD;
+        }
+    }
+}
+
+/// 相似度門檻上限（dHash 的位元數）
+pub const MAX_PERCEPTUAL_THRESHOLD: u32 = 64;
+
+impl Default for PerceptualSettings {
+    fn default() -> Self {
+        Self {
+            frames: Self::default_frames(),
+            threshold: Self::default_threshold(),
+        }
+    }
+}
+
+/// 去重設定
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DuplicationSettings {
+    /// 重複檔案的處理方式
+    #[serde(default)]
+    pub action: DuplicateAction,
+    /// 資料夾模式下每組重複檔案保留的檔案
+    #[serde(default)]
+    pub keep_policy: KeepPolicy,
+    /// 紀錄模式下與紀錄中的原始檔案重複時保留的檔案，未設定時保留先登記的原始檔案
+    ///
+    /// 只有位於本次掃描資料夾中的原始檔案會被取代
+    #[serde(default)]
+    pub record_keep_policy: Option<KeepPolicy>,
+    /// 同時計算 hash 的執行緒數，0 代表依 CPU 核心數自動決定
+    ///
+    /// 傳統硬碟同時讀取多個檔案反而較慢，可設為 1 依序計算
+    #[serde(default)]
+    pub hash_threads: usize,
+    /// 紀錄模式的比對方式
+    #[serde(default)]
+    pub match_mode: MatchMode,
+    /// 畫面相似比對設定
+    #[serde(default)]
+    pub perceptual: PerceptualSettings,
+}
+
+/// 孤立檔案的判斷方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum OrphanPairingMode {
+    /// 有任何同名檔案即算配對（預設）
+    #[default]
+    #[serde(rename = "any")]
+    AnyPairing,
+    /// 群組含有錨點副檔名（例如影片）才算配對，單獨的錨點檔案保留
+    #[serde(rename = "anchor")]
+    Anchor,
+}
+
+impl fmt::Display for OrphanPairingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AnyPairing => write!(f, "有任何同名檔案即算配對"),
+            Self::Anchor => write!(f, "必須含有錨點檔案（例如影片）"),
+        }
+    }
+}
+
+/// 孤立檔案移動設定
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OrphanSettings {
+    /// 預設的判斷方式
+    #[serde(default)]
+    pub pairing_mode: OrphanPairingMode,
+    /// 錨點副檔名（例如 `mp4`），空白時使用檔案類型表中的影片副檔名
+    #[serde(default)]
+    pub anchor_extensions: Vec<String>,
+    /// 分組前的檔名正規化規則
+    #[serde(default)]
+    pub stem_normalization: StemNormalization,
+    /// 不處理的副檔名（例如 `part`、`json`、`log`），優先於強制孤立清單
+    #[serde(default)]
+    pub ignore_extensions: Vec<String>,
+    /// 一律視為孤立檔案的副檔名（例如 `tmp`），即使有同名檔案也會移動
+    #[serde(default)]
+    pub always_orphan_extensions: Vec<String>,
+}
+
+/// 分組前的檔名正規化規則（`Movie.Name-poster.jpg`、`Movie.Name.en.srt` 視為同一組）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StemNormalization {
+    /// 預設是否套用正規化
+    #[serde(default)]
+    pub enabled: bool,
+    /// 要移除的檔名結尾（不分大小寫）
+    #[serde(default = "StemNormalization::default_strip_suffixes")]
+    pub strip_suffixes: Vec<String>,
+    /// 移除副檔名前的語言代碼（`.en`、`.zh-TW`）
+    #[serde(default = "StemNormalization::default_true")]
+    pub strip_language_codes: bool,
+    /// 不分大小寫
+    #[serde(default = "StemNormalization::default_true")]
+    pub ignore_case: bool,
+    /// 移除前後空白
+    #[serde(default = "StemNormalization::default_true")]
+    pub trim_whitespace: bool,
+}
+
+impl StemNormalization {
+    fn default_strip_suffixes() -> Vec<String> {
+        ["-poster", "-fanart", "-thumb"]
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+    const fn default_true() -> bool {
+        true
+    }
+}
+
+impl Default for StemNormalization {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            strip_suffixes: Self::default_strip_suffixes(),
+            strip_language_codes: Self::default_true(),
+            ignore_case: Self::default_true(),
+            trim_whitespace: Self::default_true(),
+        }
+    }
+}
+
+/// 預設的重新命名檔名樣板
+pub const DEFAULT_FILENAME_TEMPLATE: &str = "[{index}] {name}_{uuid}{convert}.{ext}";
+
+/// 影片重新命名設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenamerSettings {
+    /// 新檔名樣板，可用 `{index}`（或 `{index:03}` 補零）、`{name}`、`{uuid}`、
+    /// `{duration}`、`{convert}`、`{ext}`
+    #[serde(default = "RenamerSettings::default_filename_template")]
+    pub filename_template: String,
+    /// 一併重新命名同名的字幕、預覽圖等附屬檔案
+    #[serde(default = "RenamerSettings::default_rename_companions")]
+    pub rename_companions: bool,
+    /// 新檔名加入 UUID；關閉時保留原檔名
+    #[serde(default = "RenamerSettings::default_include_uuid")]
+    pub include_uuid: bool,
+    /// 子資料夾中的影片依所在資料夾分組，各自從起始編號開始編號
+    #[serde(default)]
+    pub number_per_directory: bool,
+    /// 略過檔名已含 UUID（先前已重新命名）的影片，重複執行時不會再次改名
+    #[serde(default)]
+    pub skip_renamed: bool,
+}
+
+impl RenamerSettings {
+    fn default_filename_template() -> String {
+        DEFAULT_FILENAME_TEMPLATE.to_string()
+    }
+    const fn default_rename_companions() -> bool {
+        true
+    }
+    const fn default_include_uuid() -> bool {
+        true
+    }
+}
+
+impl Default for RenamerSettings {
+    fn default() -> Self {
+        Self {
+            filename_template: Self::default_filename_template(),
+            rename_companions: Self::default_rename_companions(),
+            include_uuid: Self::default_include_uuid(),
+            number_per_directory: false,
+            skip_renamed: false,
+        }
+    }
+}
+
+/// 依日期整理設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DateOrganizeSettings {
+    /// 日期資料夾格式，可用 `{year}`、`{month}`、`{day}`
+    #[serde(default = "DateOrganizeSettings::default_pattern")]
+    pub pattern: String,
+    /// 影片優先使用 ffprobe 讀到的建立時間，沒有時使用修改時間
+    #[serde(default = "DateOrganizeSettings::default_use_media_time")]
+    pub use_media_time: bool,
+}
+
+impl DateOrganizeSettings {
+    fn default_pattern() -> String {
+        "{year}/{month}".to_string()
+    }
+    const fn default_use_media_time() -> bool {
+        true
+    }
+}
+
+impl Default for DateOrganizeSettings {
+    fn default() -> Self {
+        Self {
+            pattern: Self::default_pattern(),
+            use_media_time: Self::default_use_media_time(),
+        }
+    }
+}
+
+/// 日期子資料夾的層級
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum DateGranularity {
+    /// 只分年份（`image/2023/`）
+    #[serde(rename = "year")]
+    Year,
+    /// 分年份與月份（`image/2023/06/`，預設）
+    #[default]
+    #[serde(rename = "month")]
+    Month,
+}
+
+impl DateGranularity {
+    /// 對應的日期資料夾格式
+    #[must_use]
+    pub const fn pattern(self) -> &'static str {
+        match self {
+            Self::Year => "{year}",
+            Self::Month => "{year}/{month}",
+        }
+    }
+}
+
+/// 依類型整理時的資料夾結構
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum OrganizeBy {
+    /// 只分類型資料夾（預設）
+    #[default]
+    #[serde(rename = "category")]
+    Category,
+    /// 類型資料夾下再依修改時間分日期資料夾
+    #[serde(rename = "category_then_date")]
+    CategoryThenDate { granularity: DateGranularity },
+}
+
+/// 影片編碼器
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum EncoderCodec {
+    /// H.265 / HEVC 10-bit（預設）
+    #[default]
+    #[serde(rename = "x265")]
+    X265,
+    /// H.264 / AVC 8-bit（相容性最佳）
+    #[serde(rename = "x264")]
+    X264,
+    /// AV1 10-bit（SVT-AV1，檔案最小）
+    #[serde(rename = "av1")]
+    SvtAv1,
+}
+
+impl EncoderCodec {
+    /// 對應的 ffmpeg 編碼器名稱
+    #[must_use]
+    pub const fn ffmpeg_encoder(self) -> &'static str {
+        match self {
+            Self::X265 => "libx265",
+            Self::X264 => "libx264",
+            Self::SvtAv1 => "libsvtav1",
+        }
+    }
+
+    /// 編碼器接受的 CRF 範圍（x264/x265 為 0-51，SVT-AV1 為 0-63）
+    #[must_use]
+    pub const fn crf_range(self) -> RangeInclusive<u8> {
+        match self {
+            Self::X265 | Self::X264 => 0..=MAX_CRF,
+            Self::SvtAv1 => 0..=MAX_AV1_CRF,
+        }
+    }
+
+    /// 編碼器的預設 CRF
+    #[must_use]
+    pub const fn default_crf(self) -> u8 {
+        match self {
+            Self::X265 | Self::X264 => DEFAULT_CRF,
+            Self::SvtAv1 => DEFAULT_AV1_CRF,
+        }
+    }
+}
+
+impl fmt::Display for EncoderCodec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::X265 => write!(f, "H.265 (x265)"),
+            Self::X264 => write!(f, "H.264 (x264)"),
+            Self::SvtAv1 => write!(f, "AV1 (SVT-AV1)"),
+        }
+    }
+}
+
+/// 音訊處理方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum AudioMode {
+    /// 重新編碼為 FLAC（無損，預設）
+    #[default]
+    #[serde(rename = "flac")]
+    Flac,
+    /// 直接複製原始音訊，不重新編碼
+    #[serde(rename = "copy")]
+    Copy,
+    /// 重新編碼為 AAC（位元率單位 kbps）
+    #[serde(rename = "aac")]
+    Aac { bitrate: u32 },
+}
+
+impl AudioMode {
+    /// 對應的 ffmpeg 音訊編碼器名稱（直接複製時為 `None`）
+    #[must_use]
+    pub const fn ffmpeg_encoder(self) -> Option<&'static str> {
+        match self {
+            Self::Flac => Some("flac"),
+            Self::Copy => None,
+            Self::Aac { .. } => Some("aac"),
+        }
+    }
+}
+
+impl fmt::Display for AudioMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Flac => write!(f, "FLAC（無損）"),
+            Self::Copy => write!(f, "直接複製（不重新編碼）"),
+            Self::Aac { bitrate } => write!(f, "AAC {bitrate} kbps"),
+        }
+    }
+}
+
+/// 影片編碼後端
+///
+/// GPU 後端固定輸出 HEVC 10-bit，`codec` 設定只作用於軟體編碼
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum EncodeBackend {
+    /// CPU 軟體編碼（預設）
+    #[default]
+    #[serde(rename = "software")]
+    Software,
+    /// NVIDIA NVENC
+    #[serde(rename = "nvenc")]
+    Nvenc,
+    /// Intel Quick Sync Video
+    #[serde(rename = "qsv")]
+    Qsv,
+    /// Apple VideoToolbox
+    #[serde(rename = "videotoolbox")]
+    VideoToolbox,
+}
+
+impl EncodeBackend {
+    /// 所有支援的後端
+    pub const ALL: [Self; 4] = [Self::Software, Self::Nvenc, Self::Qsv, Self::VideoToolbox];
+
+    /// 是否使用 GPU 硬體編碼
+    #[must_use]
+    pub const fn is_gpu(self) -> bool {
+        !matches!(self, Self::Software)
+    }
+
+    /// GPU 後端對應的 HEVC 編碼器名稱（軟體編碼回傳 `None`）
+    #[must_use]
+    pub const fn hevc_encoder(self) -> Option<&'static str> {
+        match self {
+            Self::Software => None,
+            Self::Nvenc => Some("hevc_nvenc"),
+            Self::Qsv => Some("hevc_qsv"),
+            Self::VideoToolbox => Some("hevc_videotoolbox"),
+        }
+    }
+}
+
+impl fmt::Display for EncodeBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Software => write!(f, "軟體 (CPU)"),
+            Self::Nvenc => write!(f, "NVIDIA NVENC"),
+            Self::Qsv => write!(f, "Intel QSV"),
+            Self::VideoToolbox => write!(f, "Apple VideoToolbox"),
+        }
+    }
+}
+
+/// 預覽圖輸出格式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum ContactSheetFormat {
+    /// JPEG（預設，檔案小）
+    #[default]
+    #[serde(rename = "jpeg")]
+    Jpeg,
+    /// PNG（無損，文字最清晰）
+    #[serde(rename = "png")]
+    Png,
+    /// WebP（檔案小且較少壓縮痕跡）
+    #[serde(rename = "webp")]
+    Webp,
+}
+
+impl ContactSheetFormat {
+    /// 所有支援的格式
+    pub const ALL: [Self; 3] = [Self::Jpeg, Self::Png, Self::Webp];
+
+    /// 輸出副檔名（不含點）
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpg",
+            Self::Png => "png",
+            Self::Webp => "webp",
+        }
+    }
+}
+
+impl fmt::Display for ContactSheetFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Jpeg => write!(f, "JPEG"),
+            Self::Png => write!(f, "PNG"),
+            Self::Webp => write!(f, "WebP"),
         }
     }
 }
@@ -76,47 +658,354 @@ pub enum ContactSheetOutputMode {
     SameDirectory,
 }
 
-impl fmt::Display for ContactSheetOutputMode {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::SubDirectory => write!(f, "子目錄"),
-            Self::SameDirectory => write!(f, "同目錄"),
+impl fmt::Display for ContactSheetOutputMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SubDirectory => write!(f, "子目錄"),
+            Self::SameDirectory => write!(f, "同目錄"),
+        }
+    }
+}
+
+/// 縮圖產生設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContactSheetSettings {
+    /// 輸出模式
+    pub output_mode: ContactSheetOutputMode,
+    /// 精準模式是否使用批次擷取（每批一個 ffmpeg 程序）
+    #[serde(default)]
+    pub use_batch_extraction: bool,
+    /// 網格欄數
+    #[serde(default = "ContactSheetSettings::default_grid_cols")]
+    pub grid_cols: usize,
+    /// 網格列數
+    #[serde(default = "ContactSheetSettings::default_grid_rows")]
+    pub grid_rows: usize,
+    /// 在縮圖上標示時間戳記
+    #[serde(default)]
+    pub burn_timestamps: bool,
+    /// 在預覽圖上方加入檔案資訊標頭
+    #[serde(default)]
+    pub show_header: bool,
+    /// 輸出格式
+    #[serde(default)]
+    pub output_format: ContactSheetFormat,
+    /// 輸出品質（1-100，PNG 為無損不適用）
+    #[serde(default = "ContactSheetSettings::default_quality")]
+    pub quality: u8,
+    /// 預設使用快速模式（跳過場景偵測）
+    #[serde(default = "ContactSheetSettings::default_fast_mode")]
+    pub fast_mode: bool,
+    /// 精準模式重用先前的場景偵測結果
+    #[serde(default = "ContactSheetSettings::default_use_scene_cache")]
+    pub use_scene_cache: bool,
+}
+
+impl ContactSheetSettings {
+    const fn default_grid_cols() -> usize {
+        9
+    }
+    const fn default_grid_rows() -> usize {
+        6
+    }
+    const fn default_quality() -> u8 {
+        90
+    }
+    const fn default_fast_mode() -> bool {
+        true
+    }
+    const fn default_use_scene_cache() -> bool {
+        true
+    }
+}
+
+impl Default for ContactSheetSettings {
+    fn default() -> Self {
+        Self {
+            output_mode: ContactSheetOutputMode::default(),
+            use_batch_extraction: false,
+            grid_cols: Self::default_grid_cols(),
+            grid_rows: Self::default_grid_rows(),
+            burn_timestamps: false,
+            show_header: false,
+            output_format: ContactSheetFormat::default(),
+            quality: Self::default_quality(),
+            fast_mode: Self::default_fast_mode(),
+            use_scene_cache: Self::default_use_scene_cache(),
+        }
+    }
+}
+
+/// 影片轉檔設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VideoEncoderSettings {
+    /// 轉檔後處理動作
+    pub post_encode_action: PostEncodeAction,
+    /// 初始最大轉檔數（None = CPU 1/4）
+    #[serde(default = "VideoEncoderSettings::default_initial_limit")]
+    pub initial_max_parallel: Option<usize>,
+    /// 最大同時轉檔數（None = 無上限，僅依 CPU 使用率調整）
+    #[serde(
+        default = "VideoEncoderSettings::default_max_parallel",
+        alias = "max_concurrent_tasks"
+    )]
+    pub max_parallel: Option<usize>,
+    /// 寫入中檔案偵測的間隔秒數（0 = 不檢查）
+    #[serde(default = "VideoEncoderSettings::default_stability_check_secs")]
+    pub stability_check_secs: u64,
+    /// 是否重新編碼已是 HEVC 的影片
+    #[serde(default)]
+    pub reencode_hevc: bool,
+    /// 影片編碼器
+    #[serde(default)]
+    pub codec: EncoderCodec,
+    /// CRF 品質（數字越小品質越高，範圍依編碼器而定；None = 編碼器的預設值）
+    #[serde(default)]
+    pub crf: Option<u8>,
+    /// 編碼速度 preset（例如 fast、medium、slow）
+    #[serde(default = "VideoEncoderSettings::default_preset")]
+    pub preset: String,
+    /// 編碼後端（軟體或 GPU）
+    #[serde(default)]
+    pub backend: EncodeBackend,
+    /// GPU 編碼最大同時數（消費級 GPU 通常只有 1-2 個編碼工作階段）
+    #[serde(default = "VideoEncoderSettings::default_max_gpu_parallel")]
+    pub max_gpu_parallel: usize,
+    /// 可用記憶體低於此值（MB）時不再新增任務（0 = 不檢查）
+    #[serde(default = "VideoEncoderSettings::default_min_free_memory_mb")]
+    pub min_free_memory_mb: u64,
+    /// 可用記憶體低於此百分比時不再新增任務（0 = 不檢查）
+    #[serde(default = "VideoEncoderSettings::default_min_free_memory_percent")]
+    pub min_free_memory_percent: u8,
+    /// 輸出位置可用空間低於此值（MB）時不再新增任務（0 = 不檢查）
+    #[serde(default = "VideoEncoderSettings::default_min_free_space_mb")]
+    pub min_free_space_mb: u64,
+    /// 轉檔後的檔案沿用原始檔案的修改時間
+    #[serde(default = "VideoEncoderSettings::default_preserve_mtime")]
+    pub preserve_mtime: bool,
+    /// 編碼後檔案比原始檔案大時，刪除輸出並保留原檔
+    #[serde(default)]
+    pub keep_smaller: bool,
+    /// 輸出比原始檔案大多少百分比以內仍保留輸出（搭配 `keep_smaller`）
+    #[serde(default)]
+    pub keep_smaller_tolerance_percent: u8,
+    /// 編碼失敗後的重試次數（用盡後才移到 fail 資料夾）
+    #[serde(default = "VideoEncoderSettings::default_max_retries")]
+    pub max_retries: u32,
+    /// 中斷時等待 ffmpeg 正常結束的秒數，逾時才強制終止
+    #[serde(default = "VideoEncoderSettings::default_shutdown_grace_secs")]
+    pub shutdown_grace_secs: u64,
+    /// 編碼成功後保留 ffmpeg 記錄檔（失敗的記錄檔一律保留）
+    #[serde(default)]
+    pub keep_success_logs: bool,
+    /// 音訊處理方式
+    #[serde(default)]
+    pub audio_mode: AudioMode,
+    /// 保留所有音軌（預設只保留第一條）
+    #[serde(default)]
+    pub keep_all_audio: bool,
+    /// 保留內嵌字幕（直接複製到 Matroska）
+    #[serde(default)]
+    pub keep_subtitles: bool,
+    /// 轉檔後檔案的輸出資料夾（None = 與原始檔案相同資料夾）
+    #[serde(default)]
+    pub output_dir: Option<PathBuf>,
+    /// 取代原始檔案時，輸出改用原始檔名（去掉 `.convert`）
+    #[serde(default)]
+    pub replace_use_original_name: bool,
+}
+
+impl VideoEncoderSettings {
+    const fn default_initial_limit() -> Option<usize> {
+        None
+    }
+    const fn default_max_parallel() -> Option<usize> {
+        None
+    }
+    const fn default_stability_check_secs() -> u64 {
+        5
+    }
+    fn default_preset() -> String {
+        DEFAULT_PRESET.to_string()
+    }
+    const fn default_max_gpu_parallel() -> usize {
+        DEFAULT_MAX_GPU_PARALLEL
+    }
+    const fn default_min_free_memory_mb() -> u64 {
+        2048
+    }
+    const fn default_min_free_memory_percent() -> u8 {
+        10
+    }
+    const fn default_min_free_space_mb() -> u64 {
+        1024
+    }
+    const fn default_preserve_mtime() -> bool {
+        true
+    }
+    const fn default_max_retries() -> u32 {
+        1
+    }
+    const fn default_shutdown_grace_secs() -> u64 {
+        10
+    }
+
+    /// 實際使用的 ffmpeg 視訊編碼器名稱
+    #[must_use]
+    pub fn ffmpeg_encoder(&self) -> &'static str {
+        self.backend
+            .hevc_encoder()
+            .unwrap_or_else(|| self.codec.ffmpeg_encoder())
+    }
+
+    /// 決定 CRF 範圍與預設值的編碼器（GPU 編碼一律輸出 HEVC）
+    #[must_use]
+    pub const fn quality_codec(codec: EncoderCodec, backend: EncodeBackend) -> EncoderCodec {
+        if backend.is_gpu() {
+            EncoderCodec::X265
+        } else {
+            codec
+        }
+    }
+
+    /// 目前編碼器與後端接受的 CRF 範圍
+    #[must_use]
+    pub const fn crf_range(&self) -> RangeInclusive<u8> {
+        Self::quality_codec(self.codec, self.backend).crf_range()
+    }
+
+    /// 實際使用的 CRF（未設定時為編碼器的預設值）
+    #[must_use]
+    pub const fn effective_crf(&self) -> u8 {
+        match self.crf {
+            Some(crf) => crf,
+            None => Self::quality_codec(self.codec, self.backend).default_crf(),
+        }
+    }
+
+    /// 檢查 CRF 是否在目前編碼器的範圍內
+    pub fn validate_crf(&self, crf: u8) -> Result<(), String> {
+        let range = self.crf_range();
+        if range.contains(&crf) {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} 的 CRF 必須介於 {} 到 {}",
+                Self::quality_codec(self.codec, self.backend),
+                range.start(),
+                range.end()
+            ))
         }
     }
-}
 
-/// 縮圖產生設定
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct ContactSheetSettings {
-    /// 輸出模式
-    pub output_mode: ContactSheetOutputMode,
-}
+    /// 輸出是否為 HEVC 10-bit
+    #[must_use]
+    pub fn outputs_hevc(&self) -> bool {
+        self.backend.is_gpu() || self.codec == EncoderCodec::X265
+    }
 
-/// 影片轉檔設定
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct VideoEncoderSettings {
-    /// 轉檔後處理動作
-    pub post_encode_action: PostEncodeAction,
-    /// 初始最大轉檔數（None = CPU 1/4）
-    #[serde(default = "VideoEncoderSettings::default_initial_limit")]
-    pub initial_max_parallel: Option<usize>,
-    /// 最大同時轉檔數（None = 無上限）
-    #[serde(default = "VideoEncoderSettings::default_max_parallel")]
-    pub max_parallel: Option<usize>,
-}
+    /// 實際的最大同時轉檔數
+    ///
+    /// GPU 編碼時另受 `max_gpu_parallel` 限制，取兩者較小值
+    #[must_use]
+    pub fn effective_max_parallel(&self) -> Option<usize> {
+        if !self.backend.is_gpu() {
+            return self.max_parallel;
+        }
+        let gpu_limit = self.max_gpu_parallel.max(1);
+        Some(
+            self.max_parallel
+                .map_or(gpu_limit, |maxp| maxp.min(gpu_limit)),
+        )
+    }
 
-impl VideoEncoderSettings {
-    const fn default_initial_limit() -> Option<usize> {
-        None
+    /// 修正超出範圍的設定值（載入設定檔後呼叫）
+    pub fn sanitize(&mut self) {
+        let max_crf = *self.crf_range().end();
+        if let Some(crf) = self.crf
+            && crf > max_crf
+        {
+            warn!(
+                "CRF {crf} 超出 {} 的範圍，改用 {max_crf}",
+                Self::quality_codec(self.codec, self.backend)
+            );
+            self.crf = Some(max_crf);
+        }
+        let preset = self.preset.trim();
+        if preset.is_empty() {
+            self.preset = Self::default_preset();
+        } else if preset.len() != self.preset.len() {
+            self.preset = preset.to_string();
+        }
+        if self.max_gpu_parallel == 0 {
+            warn!("GPU 最大同時數不可為 0，改用 1");
+            self.max_gpu_parallel = 1;
+        }
+        if self.min_free_memory_percent > 100 {
+            warn!(
+                "可用記憶體門檻 {}% 超出範圍，改用 100%",
+                self.min_free_memory_percent
+            );
+            self.min_free_memory_percent = 100;
+        }
     }
-    const fn default_max_parallel() -> Option<usize> {
-        None
+}
+
+impl Default for VideoEncoderSettings {
+    fn default() -> Self {
+        Self {
+            post_encode_action: PostEncodeAction::default(),
+            initial_max_parallel: Self::default_initial_limit(),
+            max_parallel: Self::default_max_parallel(),
+            stability_check_secs: Self::default_stability_check_secs(),
+            reencode_hevc: false,
+            codec: EncoderCodec::default(),
+            crf: None,
+            preset: Self::default_preset(),
+            backend: EncodeBackend::default(),
+            max_gpu_parallel: Self::default_max_gpu_parallel(),
+            min_free_memory_mb: Self::default_min_free_memory_mb(),
+            min_free_memory_percent: Self::default_min_free_memory_percent(),
+            min_free_space_mb: Self::default_min_free_space_mb(),
+            preserve_mtime: Self::default_preserve_mtime(),
+            keep_smaller: false,
+            keep_smaller_tolerance_percent: 0,
+            max_retries: Self::default_max_retries(),
+            shutdown_grace_secs: Self::default_shutdown_grace_secs(),
+            keep_success_logs: false,
+            audio_mode: AudioMode::default(),
+            keep_all_audio: false,
+            keep_subtitles: false,
+            output_dir: None,
+            replace_use_original_name: false,
+        }
     }
 }
 
 /// 最近使用路徑的最大數量
 pub const MAX_RECENT_PATHS: usize = 10;
 
+/// x264/x265 的預設 CRF 品質
+pub const DEFAULT_CRF: u8 = 16;
+
+/// SVT-AV1 的預設 CRF 品質
+pub const DEFAULT_AV1_CRF: u8 = 30;
+
+/// AAC 預設位元率（kbps）
+pub const DEFAULT_AAC_BITRATE: u32 = 192;
+
+/// GPU 編碼預設最大同時數
+pub const DEFAULT_MAX_GPU_PARALLEL: usize = 2;
+
+/// CRF 上限（x264/x265 與 GPU 編碼的有效範圍為 0-51）
+pub const MAX_CRF: u8 = 51;
+
+/// SVT-AV1 的 CRF 上限（有效範圍為 0-63），也是所有編碼器中最大的 CRF
+pub const MAX_AV1_CRF: u8 = 63;
+
+/// 預設編碼速度 preset
+pub const DEFAULT_PRESET: &str = "fast";
+
 /// 使用者設定
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct UserSettings {
@@ -128,29 +1017,366 @@ pub struct UserSettings {
     /// 縮圖產生設定
     #[serde(default)]
     pub contact_sheet: ContactSheetSettings,
-    /// 最近使用的路徑（最多 10 個）
+    /// 去重設定
+    #[serde(default)]
+    pub duplication: DuplicationSettings,
+    /// 孤立檔案移動設定
+    #[serde(default)]
+    pub orphan: OrphanSettings,
+    /// 影片重新命名設定
+    #[serde(default)]
+    pub renamer: RenamerSettings,
+    /// 依日期整理設定
+    #[serde(default)]
+    pub date_organize: DateOrganizeSettings,
+    /// 依類型整理時的資料夾結構
+    #[serde(default)]
+    pub organize_by: OrganizeBy,
+    /// 舊版各功能共用的最近使用路徑，功能還沒有自己的清單時沿用
     #[serde(default)]
     pub recent_paths: Vec<String>,
+    /// 各功能的最近使用路徑與釘選路徑
+    #[serde(default)]
+    pub path_history: HashMap<PathFeature, RecentPathList>,
+    /// 每個功能保留的最近使用路徑數量（不含釘選），未設定時為 [`MAX_RECENT_PATHS`]
+    #[serde(default)]
+    pub recent_path_limit: Option<usize>,
+    /// ffmpeg 執行檔或所在資料夾（未設定時從 PATH 尋找）
+    #[serde(default)]
+    pub ffmpeg_path: Option<String>,
+    /// 自訂分類資料夾名稱（未設定的分類使用預設名稱）
+    #[serde(default)]
+    pub category_folder_overrides: HashMap<FileCategory, String>,
+    /// 額外的副檔名分類（例如 `".m4s": "video"`），優先於內建的檔案類型表
+    ///
+    /// 也接受 `[{"ext": "heic", "category": "Image"}]` 清單格式
+    #[serde(default, deserialize_with = "deserialize_extra_extensions")]
+    pub extra_extensions: HashMap<String, FileCategory>,
+    /// 重複檔案與失敗輸出的去向
+    #[serde(default)]
+    pub deletion_mode: DeletionMode,
+    /// 掃描時略過的 glob 規則（例如 `sample-*.mkv`、`**/@eaDir/**`），套用於所有元件
+    #[serde(default)]
+    pub exclude_globs: Vec<String>,
+    /// 依類型整理時額外略過的資料夾名稱（任何層級，不分大小寫），
+    /// 內建已略過 `.git`、`node_modules`、`@eaDir`
+    #[serde(default)]
+    pub excluded_folders: Vec<String>,
+    /// 掃描時的檔案大小下限（例如 `"10MB"`，未設定時不限制）
+    #[serde(default)]
+    pub min_file_size: Option<String>,
+    /// 掃描時的檔案大小上限（例如 `"50GB"`，未設定時不限制）
+    #[serde(default)]
+    pub max_file_size: Option<String>,
+    /// 去重紀錄（hash table）檔案位置，可用 `~` 表示家目錄
+    ///
+    /// 未設定時使用設定檔旁的 `hash_table.json`
+    #[serde(default)]
+    pub hash_table_path: Option<String>,
+    /// 具名的去重紀錄（例如 `movies`、`photos`），各自存放在預設紀錄旁的獨立檔案
+    #[serde(default)]
+    pub hash_tables: Vec<String>,
+    /// 命令列執行時的結果輸出：`"-"` 以 JSON 寫到 stdout，其他值為寫入 JSON 的檔案路徑
+    ///
+    /// 未設定時顯示彩色摘要；命令列的 `--json`、`--json-file` 優先
+    #[serde(default)]
+    pub json_output: Option<String>,
+    /// 日誌檔設定
+    #[serde(default)]
+    pub logging: LogSettings,
+}
+
+/// 分類資料夾名稱必須是單一層資料夾：不可為空、`.`、`..`、絕對路徑或包含路徑分隔字元
+#[must_use]
+pub fn is_valid_folder_name(name: &str) -> bool {
+    if name.contains(['/', '\\']) {
+        return false;
+    }
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    )
+}
+
+impl UserSettings {
+    /// 移除無效的分類資料夾名稱（避免整理時寫到輸入資料夾之外）
+    pub fn sanitize_category_folder_overrides(&mut self) {
+        self.category_folder_overrides.retain(|category, name| {
+            let name = name.trim();
+            let valid = name.is_empty() || is_valid_folder_name(name);
+            if !valid {
+                warn!(
+                    "{} 的分類資料夾名稱無效，改用預設名稱: {name}",
+                    category.display_name()
+                );
+            }
+            valid
+        });
+    }
+
+    /// 每個功能保留的最近使用路徑數量（至少 1 個）
+    #[must_use]
+    pub fn max_recent_paths(&self) -> usize {
+        self.recent_path_limit
+            .filter(|&limit| limit > 0)
+            .unwrap_or(MAX_RECENT_PATHS)
+    }
+
+    /// 功能的路徑清單，還沒有自己的清單時沿用舊版共用的最近使用路徑
+    #[must_use]
+    pub fn recent_path_list(&self, feature: PathFeature) -> RecentPathList {
+        self.path_history
+            .get(&feature)
+            .cloned()
+            .unwrap_or_else(|| self.legacy_path_list())
+    }
+
+    /// 可修改的功能路徑清單
+    pub fn recent_path_list_mut(&mut self, feature: PathFeature) -> &mut RecentPathList {
+        let legacy = self.legacy_path_list();
+        self.path_history.entry(feature).or_insert(legacy)
+    }
+
+    fn legacy_path_list(&self) -> RecentPathList {
+        let mut recent = self.recent_paths.clone();
+        recent.truncate(self.max_recent_paths());
+        RecentPathList {
+            pinned: Vec::new(),
+            recent,
+        }
+    }
+}
+
+/// 各自保留最近使用路徑的功能
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PathFeature {
+    VideoEncoder,
+    Duplication,
+    ContactSheet,
+    AutoMove,
+    OrphanFiles,
+    Renamer,
+}
+
+/// 單一功能的路徑清單
+///
+/// 釘選的路徑永遠保留並列在最前面；其餘路徑依最近使用排序，超過上限時移除最舊的
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecentPathList {
+    #[serde(default)]
+    pub pinned: Vec<String>,
+    /// 最近使用的路徑（不含釘選），新的在前
+    #[serde(default)]
+    pub recent: Vec<String>,
+}
+
+impl RecentPathList {
+    /// 依顯示順序列出所有路徑（釘選在前）
+    pub fn entries(&self) -> impl Iterator<Item = &str> {
+        self.pinned.iter().chain(&self.recent).map(String::as_str)
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pinned.is_empty() && self.recent.is_empty()
+    }
+
+    #[must_use]
+    pub fn is_pinned(&self, path: &str) -> bool {
+        self.pinned.iter().any(|p| p == path)
+    }
+
+    /// 記錄使用的路徑：移到最近使用的最前面，釘選的路徑維持原位
+    pub fn record(&mut self, path: &str, limit: usize) {
+        if self.is_pinned(path) {
+            return;
+        }
+        self.recent.retain(|p| p != path);
+        self.recent.insert(0, path.to_string());
+        self.recent.truncate(limit);
+    }
+
+    /// 釘選路徑，不再因數量上限被移除
+    pub fn pin(&mut self, path: &str) {
+        self.recent.retain(|p| p != path);
+        if !self.is_pinned(path) {
+            self.pinned.push(path.to_string());
+        }
+    }
+
+    /// 取消釘選，改列為最近使用的第一個
+    pub fn unpin(&mut self, path: &str, limit: usize) {
+        if self.is_pinned(path) {
+            self.pinned.retain(|p| p != path);
+            self.record(path, limit);
+        }
+    }
+
+    /// 從清單中移除路徑（包含釘選）
+    pub fn remove(&mut self, path: &str) {
+        self.pinned.retain(|p| p != path);
+        self.recent.retain(|p| p != path);
+    }
+}
+
+/// 寫入日誌檔的最低等級
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum LogLevel {
+    /// 不寫入日誌檔
+    #[serde(rename = "off")]
+    Off,
+    #[serde(rename = "error")]
+    Error,
+    #[serde(rename = "warn")]
+    Warn,
+    /// 包含每個任務的開始與完成（預設）
+    #[default]
+    #[serde(rename = "info")]
+    Info,
+    #[serde(rename = "debug")]
+    Debug,
+    #[serde(rename = "trace")]
+    Trace,
+}
+
+impl LogLevel {
+    /// 所有等級
+    pub const ALL: [Self; 6] = [
+        Self::Off,
+        Self::Error,
+        Self::Warn,
+        Self::Info,
+        Self::Debug,
+        Self::Trace,
+    ];
+
+    #[must_use]
+    pub const fn to_level_filter(self) -> log::LevelFilter {
+        match self {
+            Self::Off => log::LevelFilter::Off,
+            Self::Error => log::LevelFilter::Error,
+            Self::Warn => log::LevelFilter::Warn,
+            Self::Info => log::LevelFilter::Info,
+            Self::Debug => log::LevelFilter::Debug,
+            Self::Trace => log::LevelFilter::Trace,
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Off => write!(f, "不寫入日誌檔"),
+            Self::Error => write!(f, "只記錄錯誤"),
+            Self::Warn => write!(f, "警告與錯誤"),
+            Self::Info => write!(f, "一般資訊（含每個任務的開始與完成）"),
+            Self::Debug => write!(f, "除錯資訊"),
+            Self::Trace => write!(f, "所有細節"),
+        }
+    }
+}
+
+/// 日誌檔設定
+///
+/// 日誌檔依設定的等級記錄；警告與錯誤另會顯示在終端機（stderr）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogSettings {
+    #[serde(default)]
+    pub level: LogLevel,
+    /// 日誌資料夾（可用 `~`），未設定時使用 `~/.local/share/auto_video_organize/logs`
+    #[serde(default)]
+    pub directory: Option<String>,
+    /// 單一日誌檔的大小上限（MB），超過時輪替
+    #[serde(default = "LogSettings::default_max_file_size_mb")]
+    pub max_file_size_mb: u64,
+    /// 保留的舊日誌檔數量（`app.log.1` 為最新）
+    #[serde(default = "LogSettings::default_max_files")]
+    pub max_files: usize,
+}
+
+impl LogSettings {
+    const fn default_max_file_size_mb() -> u64 {
+        10
+    }
+    const fn default_max_files() -> usize {
+        5
+    }
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            level: LogLevel::default(),
+            directory: None,
+            max_file_size_mb: Self::default_max_file_size_mb(),
+            max_files: Self::default_max_files(),
+        }
+    }
+}
+
+/// 副檔名分類的清單格式項目
+#[derive(Deserialize)]
+struct ExtensionMapping {
+    ext: String,
+    category: FileCategory,
+}
+
+/// 讀取副檔名分類，接受物件或清單格式
+fn deserialize_extra_extensions<'de, D>(
+    deserializer: D,
+) -> Result<HashMap<String, FileCategory>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ExtraExtensions {
+        Map(HashMap<String, FileCategory>),
+        List(Vec<ExtensionMapping>),
+    }
+
+    Ok(match ExtraExtensions::deserialize(deserializer)? {
+        ExtraExtensions::Map(map) => map,
+        ExtraExtensions::List(list) => list.into_iter().map(|m| (m.ext, m.category)).collect(),
+    })
 }
 
-/// 檔案類型分類
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// 檔案類型分類（序列化名稱與預設資料夾名稱相同，讀取時也接受變體名稱如 `"Image"`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum FileCategory {
+    #[serde(rename = "video", alias = "Video")]
     Video,
+    #[serde(rename = "audio", alias = "Audio")]
     Audio,
+    #[serde(rename = "image", alias = "Image")]
     Image,
+    #[serde(rename = "archive", alias = "Archive")]
     Archive,
+    #[serde(rename = "document", alias = "Document")]
     Document,
+    #[serde(rename = "spreadsheet", alias = "Spreadsheet")]
     Spreadsheet,
+    #[serde(rename = "presentation", alias = "Presentation")]
     Presentation,
+    #[serde(rename = "ebook", alias = "Ebook")]
     Ebook,
+    #[serde(rename = "code", alias = "Code")]
     Code,
+    #[serde(rename = "markup", alias = "Markup")]
     Markup,
+    #[serde(rename = "database", alias = "Database")]
     Database,
+    #[serde(rename = "executable", alias = "Executable")]
     Executable,
+    #[serde(rename = "font", alias = "Font")]
     Font,
+    #[serde(rename = "cad_3d", alias = "Cad3D")]
     Cad3D,
+    #[serde(rename = "system", alias = "System")]
     System,
+    #[serde(rename = "other", alias = "Other")]
     Other,
 }
 
@@ -178,6 +1404,16 @@ impl FileCategory {
         }
     }
 
+    /// 取得分類的資料夾名稱，使用者有自訂且名稱有效時優先使用自訂名稱
+    #[must_use]
+    pub fn folder_name_with<'a>(&self, overrides: &'a HashMap<Self, String>) -> &'a str {
+        overrides
+            .get(self)
+            .map(|name| name.trim())
+            .filter(|name| is_valid_folder_name(name))
+            .unwrap_or(self.folder_name())
+    }
+
     /// 取得分類的顯示名稱
     #[must_use]
     pub const fn display_name(&self) -> &'static str {
@@ -259,6 +1495,51 @@ pub struct FileTypeTable {
 }
 
 impl FileTypeTable {
+    fn extensions_mut(&mut self, category: FileCategory) -> Option<&mut Vec<String>> {
+        let extensions = match category {
+            FileCategory::Video => &mut self.video_file,
+            FileCategory::Audio => &mut self.audio_file,
+            FileCategory::Image => &mut self.image_file,
+            FileCategory::Archive => &mut self.archive_file,
+            FileCategory::Document => &mut self.document_file,
+            FileCategory::Spreadsheet => &mut self.spreadsheet_file,
+            FileCategory::Presentation => &mut self.presentation_file,
+            FileCategory::Ebook => &mut self.ebook_file,
+            FileCategory::Code => &mut self.code_file,
+            FileCategory::Markup => &mut self.markup_language_file,
+            FileCategory::Database => &mut self.database_file,
+            FileCategory::Executable => &mut self.executable_file,
+            FileCategory::Font => &mut self.font_file,
+            FileCategory::Cad3D => &mut self.cad_3d_file,
+            FileCategory::System => &mut self.system_file,
+            FileCategory::Other => return None,
+        };
+        Some(extensions)
+    }
+
+    /// 合併使用者設定的副檔名分類
+    ///
+    /// 副檔名會先從所有內建分類移除再加入指定分類，因此使用者設定優先；
+    /// 指定為 `other` 即可讓內建分類不再認得該副檔名
+    pub fn merge_extra_extensions(&mut self, extra: &HashMap<String, FileCategory>) {
+        for (extension, &category) in extra {
+            let extension = extension.trim().trim_start_matches('.').to_lowercase();
+            if extension.is_empty() {
+                continue;
+            }
+            let extension = format!(".{extension}");
+
+            for &known in FileCategory::all_categories() {
+                if let Some(list) = self.extensions_mut(known) {
+                    list.retain(|ext| ext.to_lowercase() != extension);
+                }
+            }
+            if let Some(list) = self.extensions_mut(category) {
+                list.push(extension);
+            }
+        }
+    }
+
     /// 取得指定分類的副檔名集合
     #[must_use]
     pub fn extensions_for_category(&self, category: FileCategory) -> HashSet<String> {
@@ -387,4 +1668,289 @@ mod tests {
         assert_eq!(FileCategory::Image.folder_name(), "image");
         assert_eq!(FileCategory::Other.folder_name(), "other");
     }
+
+    #[test]
+    fn test_video_encoder_settings_sanitize() {
+        let mut settings: VideoEncoderSettings =
+            serde_json::from_str(r#"{"post_encode_action":"none","crf":80,"preset":"  "}"#)
+                .unwrap();
+        settings.sanitize();
+        assert_eq!(settings.crf, Some(MAX_CRF));
+        assert_eq!(settings.preset, DEFAULT_PRESET);
+
+        let mut defaults: VideoEncoderSettings =
+            serde_json::from_str(r#"{"post_encode_action":"none"}"#).unwrap();
+        defaults.sanitize();
+        assert_eq!(defaults.crf, None);
+        assert_eq!(defaults.effective_crf(), DEFAULT_CRF);
+        assert_eq!(defaults.preset, DEFAULT_PRESET);
+    }
+
+    #[test]
+    fn test_crf_depends_on_codec() {
+        let mut av1: VideoEncoderSettings =
+            serde_json::from_str(r#"{"post_encode_action":"none","codec":"av1","crf":60}"#)
+                .unwrap();
+        av1.sanitize();
+        assert_eq!(av1.crf, Some(60));
+        assert!(av1.validate_crf(63).is_ok());
+        assert!(av1.validate_crf(64).is_err());
+
+        av1.crf = None;
+        assert_eq!(av1.effective_crf(), DEFAULT_AV1_CRF);
+        // GPU 編碼一律輸出 HEVC，沿用 x265 的範圍
+        av1.backend = EncodeBackend::Nvenc;
+        assert_eq!(av1.effective_crf(), DEFAULT_CRF);
+        assert!(av1.validate_crf(52).is_err());
+
+        let mut x265: VideoEncoderSettings =
+            serde_json::from_str(r#"{"post_encode_action":"none","crf":60}"#).unwrap();
+        x265.sanitize();
+        assert_eq!(x265.crf, Some(MAX_CRF));
+    }
+
+    #[test]
+    fn test_perceptual_settings_sanitize() {
+        let mut settings: DuplicationSettings =
+            serde_json::from_str(r#"{"perceptual":{"frames":0,"threshold":99}}"#).unwrap();
+        assert_eq!(settings.match_mode, MatchMode::Exact);
+        settings.perceptual.sanitize();
+        assert_eq!(settings.perceptual.frames, 1);
+        assert_eq!(settings.perceptual.threshold, MAX_PERCEPTUAL_THRESHOLD);
+
+        let defaults: DuplicationSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(defaults.perceptual.frames, 8);
+        assert_eq!(defaults.perceptual.threshold, 10);
+    }
+
+    #[test]
+    fn test_max_recent_paths() {
+        let mut settings = UserSettings::default();
+        assert_eq!(settings.max_recent_paths(), MAX_RECENT_PATHS);
+        settings.recent_path_limit = Some(0);
+        assert_eq!(settings.max_recent_paths(), MAX_RECENT_PATHS);
+        settings.recent_path_limit = Some(3);
+        assert_eq!(settings.max_recent_paths(), 3);
+
+        for path in ["/a", "/b", "/c", "/d", "/b"] {
+            crate::config::save::add_recent_path(&mut settings, PathFeature::VideoEncoder, path);
+        }
+        assert_eq!(
+            settings.recent_path_list(PathFeature::VideoEncoder).recent,
+            ["/b", "/d", "/c"]
+        );
+        // 其他功能不受影響
+        assert!(settings.recent_path_list(PathFeature::Renamer).is_empty());
+    }
+
+    #[test]
+    fn test_recent_path_list_pin_survives_rotation() {
+        let mut list = RecentPathList::default();
+        list.record("/library", 2);
+        list.pin("/library");
+        for path in ["/tmp/a", "/tmp/b", "/tmp/c", "/library"] {
+            list.record(path, 2);
+        }
+        assert_eq!(list.pinned, ["/library"]);
+        assert_eq!(list.recent, ["/tmp/c", "/tmp/b"]);
+        assert_eq!(
+            list.entries().collect::<Vec<_>>(),
+            ["/library", "/tmp/c", "/tmp/b"]
+        );
+
+        list.unpin("/library", 2);
+        assert!(list.pinned.is_empty());
+        assert_eq!(list.recent, ["/library", "/tmp/c"]);
+
+        list.pin("/tmp/c");
+        list.remove("/tmp/c");
+        list.remove("/library");
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn test_recent_path_list_seeded_from_legacy_paths() {
+        let mut settings: UserSettings =
+            serde_json::from_str(r#"{"language":"zh-TW","recent_paths":["/old"]}"#).unwrap();
+        assert_eq!(
+            settings.recent_path_list(PathFeature::Duplication).recent,
+            ["/old"]
+        );
+
+        settings
+            .recent_path_list_mut(PathFeature::Duplication)
+            .pin("/old");
+        let json = serde_json::to_value(&settings).unwrap();
+        assert_eq!(json["path_history"]["duplication"]["pinned"][0], "/old");
+        assert_eq!(
+            settings.recent_path_list(PathFeature::AutoMove).recent,
+            ["/old"]
+        );
+    }
+
+    #[test]
+    fn test_effective_max_parallel_caps_gpu() {
+        let mut settings = VideoEncoderSettings {
+            max_parallel: Some(6),
+            ..VideoEncoderSettings::default()
+        };
+        assert_eq!(settings.effective_max_parallel(), Some(6));
+        assert_eq!(settings.ffmpeg_encoder(), "libx265");
+
+        settings.backend = EncodeBackend::Nvenc;
+        assert_eq!(
+            settings.effective_max_parallel(),
+            Some(DEFAULT_MAX_GPU_PARALLEL)
+        );
+        assert_eq!(settings.ffmpeg_encoder(), "hevc_nvenc");
+
+        settings.max_parallel = None;
+        settings.max_gpu_parallel = 1;
+        assert_eq!(settings.effective_max_parallel(), Some(1));
+    }
+
+    #[test]
+    fn test_encode_backend_serde() {
+        let settings: VideoEncoderSettings = serde_json::from_str(
+            r#"{"post_encode_action":"none","backend":"qsv","max_gpu_parallel":0}"#,
+        )
+        .unwrap();
+        assert_eq!(settings.backend, EncodeBackend::Qsv);
+
+        let mut settings = settings;
+        settings.sanitize();
+        assert_eq!(settings.max_gpu_parallel, 1);
+
+        let defaults: VideoEncoderSettings =
+            serde_json::from_str(r#"{"post_encode_action":"none"}"#).unwrap();
+        assert_eq!(defaults.backend, EncodeBackend::Software);
+        assert_eq!(defaults.max_gpu_parallel, DEFAULT_MAX_GPU_PARALLEL);
+    }
+
+    #[test]
+    fn test_max_parallel_accepts_max_concurrent_tasks_alias() {
+        let settings: VideoEncoderSettings =
+            serde_json::from_str(r#"{"post_encode_action":"none","max_concurrent_tasks":3}"#)
+                .unwrap();
+        assert_eq!(settings.max_parallel, Some(3));
+
+        let defaults: VideoEncoderSettings =
+            serde_json::from_str(r#"{"post_encode_action":"none"}"#).unwrap();
+        assert_eq!(defaults.max_parallel, None);
+    }
+
+    #[test]
+    fn test_audio_mode_serde() {
+        let copy: VideoEncoderSettings =
+            serde_json::from_str(r#"{"post_encode_action":"none","audio_mode":"copy"}"#).unwrap();
+        assert_eq!(copy.audio_mode, AudioMode::Copy);
+        assert_eq!(copy.audio_mode.ffmpeg_encoder(), None);
+
+        let aac: VideoEncoderSettings = serde_json::from_str(
+            r#"{"post_encode_action":"none","audio_mode":{"aac":{"bitrate":256}}}"#,
+        )
+        .unwrap();
+        assert_eq!(aac.audio_mode, AudioMode::Aac { bitrate: 256 });
+        assert_eq!(aac.audio_mode.to_string(), "AAC 256 kbps");
+
+        let defaults: VideoEncoderSettings =
+            serde_json::from_str(r#"{"post_encode_action":"none"}"#).unwrap();
+        assert_eq!(defaults.audio_mode, AudioMode::Flac);
+    }
+
+    #[test]
+    fn test_category_folder_overrides() {
+        let settings: UserSettings = serde_json::from_str(
+            r#"{"language":"zh-TW","category_folder_overrides":{"video":"Movies","cad_3d":"  "}}"#,
+        )
+        .unwrap();
+        let overrides = &settings.category_folder_overrides;
+        assert_eq!(FileCategory::Video.folder_name_with(overrides), "Movies");
+        assert_eq!(FileCategory::Cad3D.folder_name_with(overrides), "cad_3d");
+        assert_eq!(
+            FileCategory::Document.folder_name_with(overrides),
+            "document"
+        );
+
+        let json = serde_json::to_string(&settings).unwrap();
+        assert!(json.contains(r#""video":"Movies""#));
+    }
+
+    #[test]
+    fn test_category_folder_overrides_reject_paths() {
+        for name in ["..", ".", "/tmp", "a/b", r"a\b", "movies/", r"C:\Movies"] {
+            assert!(!is_valid_folder_name(name), "{name}");
+        }
+        assert!(is_valid_folder_name("Movies"));
+        assert!(is_valid_folder_name("影片 2024"));
+
+        let mut settings: UserSettings = serde_json::from_str(
+            r#"{"language":"zh-TW","category_folder_overrides":{"video":"..","audio":"/tmp/music","image":"a/b","font":"Fonts"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            FileCategory::Video.folder_name_with(&settings.category_folder_overrides),
+            "video"
+        );
+        settings.sanitize_category_folder_overrides();
+        assert_eq!(settings.category_folder_overrides.len(), 1);
+        assert_eq!(
+            FileCategory::Font.folder_name_with(&settings.category_folder_overrides),
+            "Fonts"
+        );
+    }
+
+    #[test]
+    fn test_merge_extra_extensions() {
+        let mut table = create_test_file_type_table();
+        table.code_file.push(".ts".to_string());
+
+        let settings: UserSettings = serde_json::from_str(
+            r#"{"language":"zh-TW","extra_extensions":{".ts":"video","M4S":"video","heic":"image","TXT":"other"}}"#,
+        )
+        .unwrap();
+        table.merge_extra_extensions(&settings.extra_extensions);
+
+        // 使用者設定優先於內建分類
+        assert_eq!(
+            table.categorize_file(Path::new("a.ts")),
+            FileCategory::Video
+        );
+        assert!(!table.code_file.contains(&".ts".to_string()));
+        assert!(table.is_video_file(Path::new("a.M4S")));
+        assert!(table.video_extensions_set().contains(".m4s"));
+        assert_eq!(
+            table.categorize_file(Path::new("a.heic")),
+            FileCategory::Image
+        );
+        assert_eq!(
+            table.categorize_file(Path::new("a.txt")),
+            FileCategory::Other
+        );
+        assert_eq!(
+            table.categorize_file(Path::new("a.mp4")),
+            FileCategory::Video
+        );
+
+        let json = serde_json::to_string(&settings).unwrap();
+        let reloaded: UserSettings = serde_json::from_str(&json).unwrap();
+        assert_eq!(reloaded.extra_extensions, settings.extra_extensions);
+    }
+
+    #[test]
+    fn test_extra_extensions_list_format() {
+        let settings: UserSettings = serde_json::from_str(
+            r#"{"language":"zh-TW","extra_extensions":[{"ext":"heic","category":"Image"},{"ext":".webm","category":"video"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(settings.extra_extensions["heic"], FileCategory::Image);
+
+        let mut table = create_test_file_type_table();
+        table.merge_extra_extensions(&settings.extra_extensions);
+        assert_eq!(
+            table.categorize_file(Path::new("photo.HEIC")),
+            FileCategory::Image
+        );
+        assert!(table.is_video_file(Path::new("clip.webm")));
+    }
 }
@@ -1,3 +1,40 @@
-pub fn init() {
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+use crate::config::UserSettings;
+use crate::logging;
+use crate::tools::{configure_ffmpeg_path, recheck_dependencies};
+use console::style;
+use log::{error, info, warn};
+use rust_i18n::t;
+
+pub fn init(settings: &UserSettings) {
+    logging::init(&settings.logging);
+    info!("auto_video_organize {} 啟動", env!("CARGO_PKG_VERSION"));
+    check_binaries(settings);
+}
+
+/// 依設定的 ffmpeg 路徑確認 ffmpeg / ffprobe 可用（啟動時與更換路徑後執行）
+///
+/// 找不到時只顯示錯誤，不中止程式（檔案整理等功能不需要 ffmpeg），
+/// 缺少相依的功能會在主選單中停用
+pub fn check_binaries(settings: &UserSettings) {
+    configure_ffmpeg_path(settings.ffmpeg_path.as_deref());
+
+    let report = recheck_dependencies();
+    for binary in report.ffmpeg.iter().chain(&report.ffprobe) {
+        info!("{} 版本 {}", binary.path.display(), binary.version);
+    }
+
+    for message in report.errors() {
+        error!("{message}");
+        eprintln!(
+            "{} {message}",
+            style(t!("main_menu.error_prefix")).red().bold()
+        );
+    }
+    for message in report.warnings(settings.contact_sheet.burn_timestamps) {
+        warn!("{message}");
+        eprintln!(
+            "{} {message}",
+            style(t!("dependency.warning_prefix")).yellow().bold()
+        );
+    }
 }
@@ -0,0 +1,494 @@
+//! 批次工作
+//!
+//! 以 TOML 工作檔描述要依序執行的功能（每個步驟一個資料夾與選項），
+//! 每個步驟以子命令相同的非互動方式執行，結束後彙整各步驟的摘要為一份報告
+
+use crate::cli::{self, Command, CommonArgs, RenameSort};
+use crate::component::{OutputFormat, PreflightFailed, ResultSink, RunStatus, report};
+use crate::config::{Config, MAX_AV1_CRF};
+use crate::tools::expand_home;
+use anyhow::{Context, Result, anyhow, bail};
+use console::style;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+/// 步驟失敗時的處理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnError {
+    /// 停止，後續步驟不執行
+    #[default]
+    Stop,
+    /// 繼續執行後續步驟
+    Continue,
+}
+
+/// 工作檔內容
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JobFile {
+    /// 步驟失敗時的預設處理方式
+    #[serde(default)]
+    pub on_error: OnError,
+    /// 報告的寫入位置（預設為工作檔旁的 `<檔名>.report.json`）
+    #[serde(default)]
+    pub report: Option<String>,
+    /// 依序執行的步驟
+    pub steps: Vec<JobStep>,
+}
+
+/// 單一步驟
+#[derive(Debug, Deserialize)]
+pub struct JobStep {
+    /// 要處理的資料夾（相對路徑以工作檔所在資料夾為基準）
+    pub input: String,
+    /// 覆寫此步驟失敗時的處理方式
+    #[serde(default)]
+    pub on_error: Option<OnError>,
+    #[serde(flatten)]
+    pub action: JobAction,
+}
+
+/// 步驟要執行的功能與選項，欄位與同名子命令相同
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub enum JobAction {
+    Encode {
+        #[serde(default)]
+        crf: Option<u8>,
+    },
+    Dedup {
+        #[serde(default)]
+        dry_run: bool,
+        #[serde(default)]
+        perceptual: bool,
+    },
+    ContactSheet {
+        #[serde(default)]
+        cols: Option<usize>,
+        #[serde(default)]
+        rows: Option<usize>,
+    },
+    AutoMove {
+        #[serde(default)]
+        copy: bool,
+        #[serde(default)]
+        dry_run: bool,
+    },
+    Orphan {
+        #[serde(default)]
+        report: bool,
+    },
+    Rename {
+        #[serde(default)]
+        sort: RenameSort,
+        #[serde(default = "JobAction::default_start_index")]
+        start_index: usize,
+        #[serde(default)]
+        preview: bool,
+        #[serde(default)]
+        per_directory: bool,
+        #[serde(default)]
+        skip_renamed: bool,
+    },
+}
+
+impl JobAction {
+    const fn default_start_index() -> usize {
+        1
+    }
+
+    /// 與子命令相同的名稱
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Encode { .. } => "encode",
+            Self::Dedup { .. } => "dedup",
+            Self::ContactSheet { .. } => "contact-sheet",
+            Self::AutoMove { .. } => "auto-move",
+            Self::Orphan { .. } => "orphan",
+            Self::Rename { .. } => "rename",
+        }
+    }
+
+    /// 轉為對應的子命令，確認提示一律視為同意
+    fn into_command(self, input: PathBuf) -> Command {
+        let common = CommonArgs { input, yes: true };
+        match self {
+            Self::Encode { crf } => Command::Encode { common, crf },
+            Self::Dedup {
+                dry_run,
+                perceptual,
+            } => Command::Dedup {
+                common,
+                dry_run,
+                perceptual,
+            },
+            Self::ContactSheet { cols, rows } => Command::ContactSheet { common, cols, rows },
+            Self::AutoMove { copy, dry_run } => Command::AutoMove {
+                common,
+                copy,
+                dry_run,
+            },
+            Self::Orphan { report } => Command::Orphan { common, report },
+            Self::Rename {
+                sort,
+                start_index,
+                preview,
+                per_directory,
+                skip_renamed,
+            } => Command::Rename {
+                common,
+                sort,
+                start_index,
+                preview,
+                per_directory,
+                skip_renamed,
+            },
+        }
+    }
+}
+
+impl JobFile {
+    /// 讀取並驗證工作檔
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("無法讀取工作檔: {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("工作檔格式錯誤: {}", path.display()))
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        let jobs: Self = toml::from_str(content)?;
+        jobs.validate().map_err(|e| anyhow!(e))?;
+        Ok(jobs)
+    }
+
+    /// 驗證步驟內容
+    ///
+    /// CRF 只檢查所有編碼器的共同上限，是否符合設定的編碼器在執行步驟時檢查
+    pub fn validate(&self) -> Result<(), String> {
+        if self.steps.is_empty() {
+            return Err("工作檔沒有任何步驟".to_string());
+        }
+        for (index, step) in self.steps.iter().enumerate() {
+            if let JobAction::Encode { crf: Some(crf) } = step.action
+                && crf > MAX_AV1_CRF
+            {
+                return Err(format!(
+                    "第 {} 個步驟的 crf 必須介於 0 到 {MAX_AV1_CRF}",
+                    index + 1
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 步驟的執行結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Succeeded,
+    /// 執行完畢，但有部分檔案處理失敗
+    CompletedWithFailures,
+    Failed,
+    /// 因中斷訊號而中途停止或未執行
+    Aborted,
+    /// 因先前步驟失敗而未執行
+    Skipped,
+}
+
+/// 單一步驟的報告
+#[derive(Debug, Serialize)]
+pub struct StepReport {
+    /// 步驟編號（從 1 開始）
+    pub step: usize,
+    pub action: &'static str,
+    pub input: PathBuf,
+    pub status: StepStatus,
+    pub error: Option<String>,
+    pub elapsed_seconds: f64,
+    /// 功能本身的結果摘要（與該子命令 `--json` 的輸出相同）
+    pub summary: Option<Value>,
+}
+
+/// 整份工作的報告
+#[derive(Debug, Serialize)]
+pub struct JobsReport {
+    pub job_file: PathBuf,
+    pub steps: Vec<StepReport>,
+}
+
+impl JobsReport {
+    fn count(&self, status: StepStatus) -> usize {
+        self.steps.iter().filter(|s| s.status == status).count()
+    }
+
+    /// 沒有步驟失敗時的整體狀態
+    fn status(&self) -> RunStatus {
+        self.steps
+            .iter()
+            .map(|step| match step.status {
+                StepStatus::Succeeded => RunStatus::Completed,
+                StepStatus::CompletedWithFailures => RunStatus::CompletedWithFailures,
+                StepStatus::Aborted | StepStatus::Failed | StepStatus::Skipped => {
+                    RunStatus::Aborted
+                }
+            })
+            .fold(RunStatus::Completed, RunStatus::worst)
+    }
+}
+
+/// 依序執行工作檔中的步驟，彙整報告後印出並寫入 JSON
+pub fn run_jobs(
+    job_file: &Path,
+    output: &OutputFormat,
+    config: &Config,
+    shutdown_signal: &Arc<AtomicBool>,
+) -> Result<RunStatus> {
+    let jobs = JobFile::load(job_file).context(PreflightFailed)?;
+    let base = job_file.parent().unwrap_or_else(|| Path::new(""));
+    let total = jobs.steps.len();
+    let mut halted: Option<StepStatus> = None;
+    let mut steps = Vec::with_capacity(total);
+
+    for (index, step) in jobs.steps.into_iter().enumerate() {
+        let input = resolve_path(base, &step.input);
+        let action = step.action.name();
+        if halted.is_none() && shutdown_signal.load(Ordering::SeqCst) {
+            warn!("收到中斷訊號，停止執行剩餘步驟");
+            halted = Some(StepStatus::Aborted);
+        }
+        if let Some(status) = halted {
+            steps.push(StepReport {
+                step: index + 1,
+                action,
+                input,
+                status,
+                error: None,
+                elapsed_seconds: 0.0,
+                summary: None,
+            });
+            continue;
+        }
+
+        report!(output);
+        report!(
+            output,
+            "{}",
+            style(format!(
+                "[{}/{total}] {action} {}",
+                index + 1,
+                input.display()
+            ))
+            .cyan()
+            .bold()
+        );
+        info!(
+            "批次工作步驟 {}/{total}: {action} {}",
+            index + 1,
+            input.display()
+        );
+
+        let sink = ResultSink::default();
+        let step_output = OutputFormat::Collect {
+            sink: sink.clone(),
+            quiet: !output.is_human(),
+        };
+        let started = Instant::now();
+        let result = cli::run(
+            step.action.into_command(input.clone()),
+            step_output,
+            config,
+            shutdown_signal,
+        );
+        let (status, error) = match result {
+            Ok(RunStatus::Completed) => (StepStatus::Succeeded, None),
+            Ok(RunStatus::CompletedWithFailures) => (StepStatus::CompletedWithFailures, None),
+            Ok(RunStatus::Aborted) => (StepStatus::Aborted, None),
+            Err(e) => {
+                warn!("批次工作步驟 {} 失敗: {e:#}", index + 1);
+                if step.on_error.unwrap_or(jobs.on_error) == OnError::Stop {
+                    halted = Some(StepStatus::Skipped);
+                }
+                (StepStatus::Failed, Some(format!("{e:#}")))
+            }
+        };
+        steps.push(StepReport {
+            step: index + 1,
+            action,
+            input,
+            status,
+            error,
+            elapsed_seconds: started.elapsed().as_secs_f64(),
+            summary: sink.take(),
+        });
+    }
+
+    let report = JobsReport {
+        job_file: job_file.to_path_buf(),
+        steps,
+    };
+    let report_path = jobs.report.map_or_else(
+        || job_file.with_extension("report.json"),
+        |path| resolve_path(base, &path),
+    );
+    write_report(&report_path, &report);
+    if !output.emit_json(&report) {
+        print_report(output, &report, &report_path);
+    }
+
+    let failed = report.count(StepStatus::Failed);
+    if failed > 0 {
+        bail!("批次工作有 {failed} 個步驟失敗");
+    }
+    Ok(report.status())
+}
+
+/// 展開 `~`，相對路徑以工作檔所在資料夾為基準
+fn resolve_path(base: &Path, path: &str) -> PathBuf {
+    let path = expand_home(path);
+    if path.is_relative() {
+        base.join(path)
+    } else {
+        path
+    }
+}
+
+fn write_report(path: &Path, report: &JobsReport) {
+    let written = serde_json::to_string_pretty(report)
+        .map_err(anyhow::Error::from)
+        .and_then(|json| fs::write(path, json).map_err(anyhow::Error::from));
+    match written {
+        Ok(()) => info!("批次工作報告: {}", path.display()),
+        Err(e) => warn!("無法寫入批次工作報告 {}: {e}", path.display()),
+    }
+}
+
+fn print_report(output: &OutputFormat, report: &JobsReport, report_path: &Path) {
+    report!(output);
+    report!(output, "{}", style("批次工作結果").green().bold());
+    for step in &report.steps {
+        let status = match step.status {
+            StepStatus::Succeeded => style("成功").green(),
+            StepStatus::CompletedWithFailures => style("部分失敗").yellow(),
+            StepStatus::Failed => style("失敗").red(),
+            StepStatus::Aborted => style("已中斷").yellow(),
+            StepStatus::Skipped => style("未執行").dim(),
+        };
+        report!(
+            output,
+            "  {}. {status} {} {} ({:.1} 秒)",
+            step.step,
+            step.action,
+            step.input.display(),
+            step.elapsed_seconds
+        );
+        if let Some(error) = &step.error {
+            report!(output, "     {}", style(error).red());
+        }
+    }
+    report!(
+        output,
+        "成功 {}、部分失敗 {}、失敗 {}、未執行 {}",
+        report.count(StepStatus::Succeeded),
+        report.count(StepStatus::CompletedWithFailures),
+        report.count(StepStatus::Failed),
+        report.count(StepStatus::Aborted) + report.count(StepStatus::Skipped)
+    );
+    report!(
+        output,
+        "{}",
+        style(format!("報告: {}", report_path.display())).dim()
+    );
+    output.report_log_file();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+on_error = "continue"
+
+[[steps]]
+action = "dedup"
+input = "/videos"
+
+[[steps]]
+action = "auto-move"
+input = "incoming"
+dry_run = true
+on_error = "stop"
+
+[[steps]]
+action = "encode"
+input = "/videos"
+crf = 20
+
+[[steps]]
+action = "rename"
+input = "/videos"
+sort = "name"
+"#;
+
+    #[test]
+    fn test_parse_job_file() {
+        let jobs = JobFile::parse(SAMPLE).unwrap();
+        assert_eq!(jobs.on_error, OnError::Continue);
+        assert_eq!(jobs.steps.len(), 4);
+        assert!(matches!(
+            jobs.steps[0].action,
+            JobAction::Dedup {
+                dry_run: false,
+                perceptual: false
+            }
+        ));
+        assert_eq!(jobs.steps[1].on_error, Some(OnError::Stop));
+        assert!(matches!(
+            jobs.steps[1].action,
+            JobAction::AutoMove {
+                copy: false,
+                dry_run: true
+            }
+        ));
+        assert!(matches!(
+            jobs.steps[2].action,
+            JobAction::Encode { crf: Some(20) }
+        ));
+        assert!(matches!(
+            jobs.steps[3].action,
+            JobAction::Rename {
+                sort: RenameSort::Name,
+                start_index: 1,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_parse_job_file_defaults_and_errors() {
+        let jobs = JobFile::parse("[[steps]]\naction = \"orphan\"\ninput = \"/v\"\n").unwrap();
+        assert_eq!(jobs.on_error, OnError::Stop);
+        assert!(jobs.report.is_none());
+
+        assert!(JobFile::parse("steps = []").is_err());
+        assert!(JobFile::parse("[[steps]]\naction = \"unknown\"\ninput = \"/v\"\n").is_err());
+        assert!(
+            JobFile::parse("[[steps]]\naction = \"encode\"\ninput = \"/v\"\ncrf = 64\n").is_err()
+        );
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        let base = Path::new("/jobs");
+        assert_eq!(resolve_path(base, "videos"), PathBuf::from("/jobs/videos"));
+        assert_eq!(resolve_path(base, "/videos"), PathBuf::from("/videos"));
+    }
+}
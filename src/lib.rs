@@ -3,9 +3,12 @@ extern crate rust_i18n;
 
 i18n!("locales", fallback = "en-US");
 
+pub mod cli;
 pub mod component;
 pub mod config;
 pub mod init;
+pub mod jobs;
+pub mod logging;
 pub mod menu;
 pub mod signal;
 pub mod tools;
@@ -0,0 +1,266 @@
+//! 日誌
+//!
+//! 日誌檔依設定的等級寫入（預設 `~/.local/share/auto_video_organize/logs/app.log`），
+//! 超過大小上限時輪替；終端機只顯示警告與錯誤（可用 `RUST_LOG` 調整），避免干擾互動畫面。
+//! 日誌資料夾在第一次寫入時才建立，無法開啟時只提示一次，不影響程式執行
+
+use crate::config::LogSettings;
+use crate::tools::expand_home;
+use console::style;
+use env_logger::{Builder, Env, Logger, Target, WriteStyle};
+use log::{LevelFilter, Log, Metadata, Record};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 日誌檔名稱
+pub const LOG_FILE_NAME: &str = "app.log";
+
+/// 設定的日誌檔
+static LOG_FILE: OnceLock<PathBuf> = OnceLock::new();
+/// 日誌檔無法寫入，之後不再嘗試
+static LOG_FILE_FAILED: AtomicBool = AtomicBool::new(false);
+
+/// 預設的日誌資料夾：`$XDG_DATA_HOME/auto_video_organize/logs`，未設定時使用 `~/.local/share`
+#[must_use]
+pub fn default_log_directory() -> PathBuf {
+    std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map_or_else(|| expand_home("~/.local/share"), PathBuf::from)
+        .join("auto_video_organize")
+        .join("logs")
+}
+
+/// 設定對應的日誌檔路徑
+#[must_use]
+pub fn log_file_path(settings: &LogSettings) -> PathBuf {
+    settings
+        .directory
+        .as_deref()
+        .map(str::trim)
+        .filter(|dir| !dir.is_empty())
+        .map_or_else(default_log_directory, expand_home)
+        .join(LOG_FILE_NAME)
+}
+
+/// 目前寫入的日誌檔（未啟用或無法寫入時為 `None`）
+#[must_use]
+pub fn active_log_file() -> Option<&'static Path> {
+    if LOG_FILE_FAILED.load(Ordering::Relaxed) {
+        return None;
+    }
+    LOG_FILE.get().map(PathBuf::as_path)
+}
+
+/// 安裝 logger：終端機顯示警告與錯誤，日誌檔依設定的等級記錄
+///
+/// 只有第一次呼叫有效
+pub fn init(settings: &LogSettings) {
+    let stderr = Builder::from_env(Env::default().default_filter_or("warn")).build();
+    let level = settings.level.to_level_filter();
+    let file = (level != LevelFilter::Off).then(|| {
+        let path = log_file_path(settings);
+        let writer = RotatingFile::new(
+            path.clone(),
+            settings.max_file_size_mb.saturating_mul(1024 * 1024),
+            settings.max_files,
+        );
+        let _ = LOG_FILE.set(path);
+        Builder::new()
+            .filter_level(level)
+            .write_style(WriteStyle::Never)
+            .target(Target::Pipe(Box::new(writer)))
+            .build()
+    });
+
+    let max_level = file
+        .as_ref()
+        .map_or(stderr.filter(), |file| file.filter().max(stderr.filter()));
+    if log::set_boxed_logger(Box::new(TeeLogger { stderr, file })).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// 同時寫到終端機與日誌檔，各自套用自己的等級
+struct TeeLogger {
+    stderr: Logger,
+    file: Option<Logger>,
+}
+
+impl Log for TeeLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.stderr.enabled(metadata) || self.file.as_ref().is_some_and(|f| f.enabled(metadata))
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        self.stderr.log(record);
+        if let Some(file) = &self.file {
+            file.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.stderr.flush();
+        if let Some(file) = &self.file {
+            file.flush();
+        }
+    }
+}
+
+/// 依大小輪替的日誌檔（`app.log` → `app.log.1` → … → `app.log.N`，最舊的刪除）
+///
+/// 第一次寫入時才建立資料夾與檔案；寫入失敗時提示一次並停用，不回報錯誤給 logger
+struct RotatingFile {
+    path: PathBuf,
+    /// 單一檔案的大小上限，0 代表不輪替
+    max_bytes: u64,
+    /// 保留的舊檔案數量
+    max_files: usize,
+    file: Option<File>,
+    size: u64,
+    failed: bool,
+}
+
+impl RotatingFile {
+    const fn new(path: PathBuf, max_bytes: u64, max_files: usize) -> Self {
+        Self {
+            path,
+            max_bytes,
+            max_files,
+            file: None,
+            size: 0,
+            failed: false,
+        }
+    }
+
+    fn write_record(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.open()?;
+        let len = buf.len() as u64;
+        if self.max_bytes > 0 && self.size > 0 && self.size + len > self.max_bytes {
+            self.rotate()?;
+            self.open()?;
+        }
+        if let Some(file) = &mut self.file {
+            file.write_all(buf)?;
+        }
+        self.size += len;
+        Ok(())
+    }
+
+    fn open(&mut self) -> io::Result<()> {
+        if self.file.is_some() {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = file.metadata()?.len();
+        self.file = Some(file);
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file = None;
+        if self.max_files == 0 {
+            return fs::remove_file(&self.path);
+        }
+        let oldest = self.numbered(self.max_files);
+        if oldest.exists() {
+            fs::remove_file(oldest)?;
+        }
+        for index in (1..self.max_files).rev() {
+            let from = self.numbered(index);
+            if from.exists() {
+                fs::rename(from, self.numbered(index + 1))?;
+            }
+        }
+        fs::rename(&self.path, self.numbered(1))
+    }
+
+    fn numbered(&self, index: usize) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".{index}"));
+        self.path.with_file_name(name)
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.failed
+            && let Err(e) = self.write_record(buf)
+        {
+            self.failed = true;
+            self.file = None;
+            LOG_FILE_FAILED.store(true, Ordering::Relaxed);
+            eprintln!(
+                "{} 無法寫入日誌檔 {}，之後只在終端機顯示警告與錯誤: {e}",
+                style("警告:").yellow().bold(),
+                self.path.display()
+            );
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(file) = &mut self.file {
+            let _ = file.flush();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_log_file_path() {
+        let settings = LogSettings {
+            directory: Some("/var/log/avo".to_string()),
+            ..LogSettings::default()
+        };
+        assert_eq!(
+            log_file_path(&settings),
+            PathBuf::from("/var/log/avo/app.log")
+        );
+        assert_eq!(
+            log_file_path(&LogSettings::default()),
+            default_log_directory().join(LOG_FILE_NAME)
+        );
+    }
+
+    #[test]
+    fn test_rotating_file_creates_directory_and_rotates() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("logs").join(LOG_FILE_NAME);
+        let mut writer = RotatingFile::new(path.clone(), 10, 2);
+        assert!(!path.exists());
+
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            writer.write_all(line.as_bytes()).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth\n");
+        assert_eq!(fs::read_to_string(writer.numbered(1)).unwrap(), "third\n");
+        assert_eq!(fs::read_to_string(writer.numbered(2)).unwrap(), "second\n");
+        assert!(!writer.numbered(3).exists());
+    }
+
+    #[test]
+    fn test_rotating_file_failure_is_swallowed() {
+        let dir = TempDir::new().unwrap();
+        let blocker = dir.path().join("not_a_dir");
+        fs::write(&blocker, b"").unwrap();
+        let mut writer = RotatingFile::new(blocker.join(LOG_FILE_NAME), 0, 0);
+
+        assert_eq!(writer.write(b"line\n").unwrap(), 5);
+        assert!(writer.failed);
+        assert_eq!(writer.write(b"again\n").unwrap(), 6);
+    }
+}
@@ -1,8 +1,10 @@
 use anyhow::Result;
+use auto_video_organize::cli::{self, Cli};
 use auto_video_organize::config::types::Config;
 use auto_video_organize::init;
 use auto_video_organize::menu::show_main_menu;
 use auto_video_organize::signal::setup_shutdown_signal;
+use clap::Parser;
 use console::{Term, style};
 use log::{info, warn};
 use rust_i18n::t;
@@ -13,14 +15,28 @@ extern crate rust_i18n;
 i18n!("locales", fallback = "en-US");
 
 fn main() -> Result<()> {
-    init::init();
-    let term = Term::stdout();
-    let shutdown_signal = setup_shutdown_signal();
+    let args = Cli::parse();
 
     // Load config and set locale
     let mut config = Config::new()?;
+    let output = args.output_format(&config.settings);
     rust_i18n::set_locale(config.settings.language.as_str());
 
+    init::init(&config.settings);
+    let term = Term::stdout();
+    let shutdown_signal = setup_shutdown_signal();
+
+    // Run a single feature non-interactively when a subcommand is given,
+    // and report the outcome through the exit code
+    if let Some(command) = args.command {
+        let result = cli::run(command, output, &config, &shutdown_signal);
+        if let Err(e) = &result {
+            warn!("Command failed: {e:#}");
+            eprintln!("{} {e:#}", style(t!("main_menu.error_prefix")).red().bold());
+        }
+        std::process::exit(cli::exit_code(&result));
+    }
+
     loop {
         // We pass the config to show_main_menu so it can update settings
         match show_main_menu(&term, &shutdown_signal, &mut config) {
@@ -1,16 +1,31 @@
+use crate::component::duplication_checker::{
+    DEFAULT_HASH_TABLE_FILE, is_valid_table_name, resolve_hash_table_path,
+};
+use crate::component::{OutputFormat, prompt_output_directory};
 use crate::config::save::save_settings;
 use crate::config::types::{
-    Config, ContactSheetOutputMode, Language, PostEncodeAction, VideoEncoderSettings,
+    AudioMode, Config, ContactSheetFormat, ContactSheetOutputMode, DeletionMode, DuplicateAction,
+    EncodeBackend, EncoderCodec, FileCategory, Language, LogLevel, MatchMode, PostEncodeAction,
+    VideoEncoderSettings,
+};
+use crate::config::{
+    DEFAULT_AAC_BITRATE, MAX_PERCEPTUAL_THRESHOLD, MAX_RECENT_PATHS, is_valid_folder_name,
 };
+use crate::init::check_binaries;
+use crate::logging::log_file_path;
 use crate::menu::handlers::{
     run_auto_move_by_type, run_contact_sheet_generator, run_duplication_checker,
     run_orphan_file_mover, run_video_encoder, run_video_renamer,
 };
+use crate::pause;
+use crate::tools::{DependencyReport, SizeFilter, check_dependencies, validate_glob};
 use anyhow::Result;
 use console::{Term, style};
 use dialoguer::theme::ColorfulTheme;
-use dialoguer::{Input, Select};
+use dialoguer::{Confirm, Input, Select};
+use log::warn;
 use rust_i18n::t;
+use std::path::Path;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 use std::thread;
@@ -26,15 +41,16 @@ pub fn show_main_menu(
     println!("{}", style(t!("main_menu.title")).cyan().bold());
     println!("{}", style(t!("common.esc_hint")).dim());
 
+    let deps = check_dependencies();
     let options = vec![
-        t!("main_menu.opt_encoder"),
-        t!("main_menu.opt_dedup"),
-        t!("main_menu.opt_contact"),
-        t!("main_menu.opt_auto_move"),
-        t!("main_menu.opt_orphan"),
-        t!("main_menu.opt_renamer"),
-        t!("main_menu.opt_settings"),
-        t!("main_menu.exit"),
+        feature_label(&t!("main_menu.opt_encoder"), deps.video_tools_ready()),
+        t!("main_menu.opt_dedup").to_string(),
+        feature_label(&t!("main_menu.opt_contact"), deps.contact_sheet_ready()),
+        t!("main_menu.opt_auto_move").to_string(),
+        t!("main_menu.opt_orphan").to_string(),
+        t!("main_menu.opt_renamer").to_string(),
+        t!("main_menu.opt_settings").to_string(),
+        t!("main_menu.exit").to_string(),
     ];
 
     let selection = Select::with_theme(&ColorfulTheme::default())
@@ -45,37 +61,59 @@ pub fn show_main_menu(
 
     match selection {
         Some(0) => {
-            run_video_encoder(term, shutdown_signal, config)?;
-            Ok(true)
-        }
-        Some(1) => {
-            run_duplication_checker(term, shutdown_signal, config)?;
-            Ok(true)
+            if ensure_available(term, deps.video_tools_ready(), deps)? {
+                run_video_encoder(term, shutdown_signal, config)?;
+            }
         }
+        Some(1) => run_duplication_checker(term, shutdown_signal, config)?,
         Some(2) => {
-            run_contact_sheet_generator(term, shutdown_signal, config)?;
-            Ok(true)
-        }
-        Some(3) => {
-            run_auto_move_by_type(term, shutdown_signal, config)?;
-            Ok(true)
-        }
-        Some(4) => {
-            run_orphan_file_mover(term, shutdown_signal, config)?;
-            Ok(true)
-        }
-        Some(5) => {
-            run_video_renamer(term, shutdown_signal, config)?;
-            Ok(true)
+            if ensure_available(term, deps.contact_sheet_ready(), deps)? {
+                run_contact_sheet_generator(term, shutdown_signal, config)?;
+            }
         }
+        Some(3) => run_auto_move_by_type(term, shutdown_signal, config)?,
+        Some(4) => run_orphan_file_mover(term, shutdown_signal, config)?,
+        Some(5) => run_video_renamer(term, shutdown_signal, config)?,
         Some(6) => {
             show_settings_menu(term, config)?;
-            Ok(true)
+            return Ok(true);
         }
-        Some(7) => Ok(false),
-        None => Ok(false), // ESC pressed - exit
+        Some(7) | None => return Ok(false), // ESC pressed - exit
         _ => unreachable!(),
     }
+
+    // 各功能以設定的副本執行，重新讀取它們儲存的設定，避免之後在設定選單中覆寫
+    if let Err(e) = config.reload_settings() {
+        warn!("無法重新讀取設定檔，沿用目前的設定: {e:#}");
+    }
+    Ok(true)
+}
+
+/// 缺少相依的功能在選項後標示無法使用
+fn feature_label(label: &str, available: bool) -> String {
+    if available {
+        label.to_string()
+    } else {
+        format!("{label} {}", t!("main_menu.unavailable"))
+    }
+}
+
+/// 功能缺少相依時顯示原因並回到主選單
+fn ensure_available(term: &Term, available: bool, deps: &DependencyReport) -> Result<bool> {
+    if available {
+        return Ok(true);
+    }
+
+    eprintln!(
+        "{} {}",
+        style(t!("main_menu.error_prefix")).red().bold(),
+        t!("main_menu.dependency_missing")
+    );
+    for message in deps.errors() {
+        eprintln!("  - {message}");
+    }
+    pause(term)?;
+    Ok(false)
 }
 
 /// 設定選單
@@ -89,6 +127,10 @@ fn show_settings_menu(term: &Term, config: &mut Config) -> Result<()> {
         let options = vec![
             t!("settings.opt_encoder"),
             t!("settings.opt_contact_sheet"),
+            t!("settings.opt_duplication"),
+            t!("settings.opt_category_folders"),
+            t!("settings.opt_scan_filters"),
+            t!("settings.opt_general"),
             t!("settings.opt_language"),
             t!("settings.back"),
         ];
@@ -102,8 +144,12 @@ fn show_settings_menu(term: &Term, config: &mut Config) -> Result<()> {
         match selection {
             Some(0) => show_encoder_settings_menu(term, config)?,
             Some(1) => show_contact_sheet_settings_menu(term, config)?,
-            Some(2) => show_language_menu(term, config)?,
-            Some(3) | None => break, // ESC or back
+            Some(2) => show_duplication_settings_menu(term, config)?,
+            Some(3) => show_category_folder_menu(term, config)?,
+            Some(4) => show_scan_filter_menu(term, config)?,
+            Some(5) => show_general_settings_menu(term, config)?,
+            Some(6) => show_language_menu(term, config)?,
+            Some(7) | None => break, // ESC or back
             _ => unreachable!(),
         }
     }
@@ -122,7 +168,12 @@ fn show_encoder_settings_menu(term: &Term, config: &mut Config) -> Result<()> {
         println!();
 
         let back = t!("settings.back").to_string();
-        let options = vec!["檔案處理設定".to_string(), "轉檔數量設定".to_string(), back];
+        let options = vec![
+            "編碼品質設定".to_string(),
+            "檔案處理設定".to_string(),
+            "轉檔數量設定".to_string(),
+            back,
+        ];
 
         let selection = Select::with_theme(&ColorfulTheme::default())
             .with_prompt("選擇設定分類")
@@ -131,9 +182,10 @@ fn show_encoder_settings_menu(term: &Term, config: &mut Config) -> Result<()> {
             .interact_on_opt(term)?;
 
         match selection {
-            Some(0) => show_encoder_file_settings(term, config)?,
-            Some(1) => show_encoder_parallel_settings(term, config)?,
-            Some(2) | None => break,
+            Some(0) => show_encoder_quality_settings(term, config)?,
+            Some(1) => show_encoder_file_settings(term, config)?,
+            Some(2) => show_encoder_parallel_settings(term, config)?,
+            Some(3) | None => break,
             _ => unreachable!(),
         }
     }
@@ -155,12 +207,27 @@ fn format_max_limit(settings: &VideoEncoderSettings) -> String {
     }
 }
 
+fn format_post_encode_action(settings: &VideoEncoderSettings) -> String {
+    if settings.post_encode_action == PostEncodeAction::ReplaceInPlace
+        && settings.replace_use_original_name
+    {
+        format!("{}（使用原始檔名）", settings.post_encode_action)
+    } else {
+        settings.post_encode_action.to_string()
+    }
+}
+
 fn render_encoder_overview(config: &Config) {
     let enc = &config.settings.video_encoder;
+    println!("{:<18} {}", style("編碼器").dim(), enc.codec);
+    println!("{:<18} {}", style("編碼後端").dim(), enc.backend);
+    println!("{:<18} {}", style("CRF").dim(), enc.effective_crf());
+    println!("{:<18} {}", style("Preset").dim(), enc.preset);
+    println!("{:<18} {}", style("音訊").dim(), enc.audio_mode);
     println!(
         "{:<18} {}",
         style("轉檔後處理").dim(),
-        enc.post_encode_action
+        format_post_encode_action(enc)
     );
     println!(
         "{:<18} {}",
@@ -172,6 +239,266 @@ fn render_encoder_overview(config: &Config) {
         style("最大同時數").dim(),
         format_max_limit(enc)
     );
+    println!(
+        "{:<18} {}",
+        style("GPU 最大同時數").dim(),
+        enc.max_gpu_parallel
+    );
+    println!(
+        "{:<18} {}",
+        style("最低可用記憶體").dim(),
+        format_memory_threshold(enc)
+    );
+    println!(
+        "{:<18} {}",
+        style("最低可用磁碟空間").dim(),
+        match enc.min_free_space_mb {
+            0 => "不檢查".to_string(),
+            mb => format!("{mb} MB"),
+        }
+    );
+    println!(
+        "{:<18} {}",
+        style("寫入檢查間隔").dim(),
+        format_stability_check(enc)
+    );
+    println!(
+        "{:<18} {}",
+        style("重新編碼 HEVC").dim(),
+        if enc.reencode_hevc {
+            "是"
+        } else {
+            "否（跳過）"
+        }
+    );
+    println!(
+        "{:<18} {}",
+        style("保留修改時間").dim(),
+        if enc.preserve_mtime { "是" } else { "否" }
+    );
+    println!(
+        "{:<18} {}",
+        style("較大時保留原檔").dim(),
+        format_keep_smaller(enc)
+    );
+    println!("{:<18} {}", style("失敗重試次數").dim(), enc.max_retries);
+    println!(
+        "{:<18} {}",
+        style("保留所有音軌").dim(),
+        if enc.keep_all_audio {
+            "是"
+        } else {
+            "否（只保留第一條）"
+        }
+    );
+    println!(
+        "{:<18} {}",
+        style("保留字幕").dim(),
+        if enc.keep_subtitles { "是" } else { "否" }
+    );
+    println!(
+        "{:<18} {}",
+        style("保留成功記錄檔").dim(),
+        if enc.keep_success_logs { "是" } else { "否" }
+    );
+    println!(
+        "{:<18} {}",
+        style("輸出資料夾").dim(),
+        enc.output_dir.as_ref().map_or_else(
+            || "與原始檔案相同".to_string(),
+            |dir| dir.display().to_string()
+        )
+    );
+}
+
+fn format_keep_smaller(settings: &VideoEncoderSettings) -> String {
+    match (
+        settings.keep_smaller,
+        settings.keep_smaller_tolerance_percent,
+    ) {
+        (false, _) => "否".to_string(),
+        (true, 0) => "是".to_string(),
+        (true, tolerance) => format!("是（容許 +{tolerance}%）"),
+    }
+}
+
+fn format_memory_threshold(settings: &VideoEncoderSettings) -> String {
+    match (
+        settings.min_free_memory_mb,
+        settings.min_free_memory_percent,
+    ) {
+        (0, 0) => "不檢查".to_string(),
+        (mb, 0) => format!("{mb} MB"),
+        (0, percent) => format!("{percent}%"),
+        (mb, percent) => format!("{mb} MB 且 {percent}%"),
+    }
+}
+
+fn format_stability_check(settings: &VideoEncoderSettings) -> String {
+    match settings.stability_check_secs {
+        0 => "不檢查".to_string(),
+        v => format!("{v} 秒"),
+    }
+}
+
+fn show_encoder_quality_settings(term: &Term, config: &mut Config) -> Result<()> {
+    term.clear_screen()?;
+    println!("{}", style("編碼品質設定").cyan().bold());
+    println!("{}", style(t!("common.esc_hint")).dim());
+    println!();
+    render_encoder_overview(config);
+    println!();
+
+    let codecs = [EncoderCodec::X265, EncoderCodec::X264, EncoderCodec::SvtAv1];
+    let items: Vec<String> = vec![
+        "H.265 (x265) — 10-bit，畫質與大小平衡".to_string(),
+        "H.264 (x264) — 8-bit，相容性最佳".to_string(),
+        "AV1 (SVT-AV1) — 10-bit，檔案最小".to_string(),
+    ];
+
+    let default_index = codecs
+        .iter()
+        .position(|&c| c == config.settings.video_encoder.codec)
+        .unwrap_or(0);
+
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("選擇編碼器")
+        .items(&items)
+        .default(default_index)
+        .interact_on_opt(term)?;
+
+    let Some(idx) = selection else {
+        return Ok(());
+    };
+
+    let backend_items: Vec<String> = EncodeBackend::ALL
+        .iter()
+        .map(|backend| match backend {
+            EncodeBackend::Software => format!("{backend} — 使用上方選擇的編碼器"),
+            _ => format!("{backend} — HEVC 10-bit，速度快但檔案較大"),
+        })
+        .collect();
+    let backend_default = EncodeBackend::ALL
+        .iter()
+        .position(|&b| b == config.settings.video_encoder.backend)
+        .unwrap_or(0);
+    let Some(backend_idx) = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("選擇編碼後端")
+        .items(&backend_items)
+        .default(backend_default)
+        .interact_on_opt(term)?
+    else {
+        return Ok(());
+    };
+    let backend = EncodeBackend::ALL[backend_idx];
+
+    // CRF 範圍與預設值依選擇的編碼器而定，換了編碼器時改以新編碼器的預設值為預設
+    let current = &config.settings.video_encoder;
+    let quality_codec = VideoEncoderSettings::quality_codec(codecs[idx], backend);
+    let crf_default =
+        if quality_codec == VideoEncoderSettings::quality_codec(current.codec, current.backend) {
+            current.effective_crf()
+        } else {
+            quality_codec.default_crf()
+        };
+    let crf_range = quality_codec.crf_range();
+    let crf: u8 = Input::new()
+        .with_prompt(format!(
+            "CRF（{}-{}，數字越小品質越高）",
+            crf_range.start(),
+            crf_range.end()
+        ))
+        .default(crf_default)
+        .validate_with(|v: &u8| {
+            if crf_range.contains(v) {
+                Ok(())
+            } else {
+                Err(format!(
+                    "{quality_codec} 的 CRF 必須介於 {} 到 {}",
+                    crf_range.start(),
+                    crf_range.end()
+                ))
+            }
+        })
+        .interact_text()?;
+    // 與編碼器預設值相同時不固定，之後換編碼器會跟著使用新的預設值
+    let crf = (crf != quality_codec.default_crf()).then_some(crf);
+    let preset: String = Input::new()
+        .with_prompt("Preset（例如 fast、medium、slow）")
+        .default(config.settings.video_encoder.preset.clone())
+        .interact_text()?;
+    let preset = preset.trim().to_string();
+
+    let Some(audio_mode) = prompt_audio_mode(term, config.settings.video_encoder.audio_mode)?
+    else {
+        return Ok(());
+    };
+
+    let encoder = &mut config.settings.video_encoder;
+    if codecs[idx] != encoder.codec
+        || backend != encoder.backend
+        || crf != encoder.crf
+        || preset != encoder.preset
+        || audio_mode != encoder.audio_mode
+    {
+        encoder.codec = codecs[idx];
+        encoder.backend = backend;
+        encoder.crf = crf;
+        encoder.preset = preset;
+        encoder.audio_mode = audio_mode;
+        encoder.sanitize();
+        save_settings(&config.settings)?;
+        println!("\n{}", style(t!("settings.saved")).green());
+        thread::sleep(Duration::from_secs(1));
+    }
+
+    Ok(())
+}
+
+/// 選擇音訊處理方式，AAC 另外詢問位元率（按 ESC 回傳 `None`）
+fn prompt_audio_mode(term: &Term, current: AudioMode) -> Result<Option<AudioMode>> {
+    let items = [
+        "FLAC — 無損重新編碼（預設）",
+        "直接複製 — 不重新編碼，保留原始音訊且最快",
+        "AAC — 有損壓縮，檔案最小",
+    ];
+    let default_index = match current {
+        AudioMode::Flac => 0,
+        AudioMode::Copy => 1,
+        AudioMode::Aac { .. } => 2,
+    };
+    let Some(idx) = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("選擇音訊處理方式")
+        .items(items)
+        .default(default_index)
+        .interact_on_opt(term)?
+    else {
+        return Ok(None);
+    };
+
+    let mode = match idx {
+        0 => AudioMode::Flac,
+        1 => AudioMode::Copy,
+        _ => {
+            let current_bitrate = match current {
+                AudioMode::Aac { bitrate } => bitrate,
+                _ => DEFAULT_AAC_BITRATE,
+            };
+            let bitrate: u32 = Input::new()
+                .with_prompt("AAC 位元率（kbps）")
+                .default(current_bitrate)
+                .validate_with(|v: &u32| {
+                    if (32..=512).contains(v) {
+                        Ok(())
+                    } else {
+                        Err("位元率必須介於 32 到 512 kbps".to_string())
+                    }
+                })
+                .interact_text()?;
+            AudioMode::Aac { bitrate }
+        }
+    };
+    Ok(Some(mode))
 }
 
 fn show_encoder_file_settings(term: &Term, config: &mut Config) -> Result<()> {
@@ -186,12 +513,14 @@ fn show_encoder_file_settings(term: &Term, config: &mut Config) -> Result<()> {
         PostEncodeAction::None,
         PostEncodeAction::MoveOldToFinish,
         PostEncodeAction::MoveNewToFinish,
+        PostEncodeAction::ReplaceInPlace,
     ];
 
     let items: Vec<String> = vec![
         t!("settings.encoder.action_none").to_string(),
         t!("settings.encoder.action_move_old").to_string(),
         t!("settings.encoder.action_move_new").to_string(),
+        t!("settings.encoder.action_replace").to_string(),
     ];
 
     let default_index = actions
@@ -205,14 +534,106 @@ fn show_encoder_file_settings(term: &Term, config: &mut Config) -> Result<()> {
         .default(default_index)
         .interact_on_opt(term)?;
 
-    if let Some(idx) = selection {
-        let selected_action = actions[idx];
-        if selected_action != config.settings.video_encoder.post_encode_action {
-            config.settings.video_encoder.post_encode_action = selected_action;
-            save_settings(&config.settings)?;
-            println!("\n{}", style(t!("settings.saved")).green());
-            thread::sleep(Duration::from_secs(1));
-        }
+    let Some(idx) = selection else {
+        return Ok(());
+    };
+
+    let selected_action = actions[idx];
+    let replace_use_original_name = if selected_action == PostEncodeAction::ReplaceInPlace {
+        Confirm::new()
+            .with_prompt("取代時是否改用原始檔名？（去掉 .convert，副檔名為 .mkv）")
+            .default(config.settings.video_encoder.replace_use_original_name)
+            .interact()?
+    } else {
+        config.settings.video_encoder.replace_use_original_name
+    };
+    let stability_check_secs: u64 = Input::new()
+        .with_prompt("寫入中檔案檢查間隔秒數（0 = 不檢查）")
+        .default(config.settings.video_encoder.stability_check_secs)
+        .interact_text()?;
+    let reencode_hevc = Confirm::new()
+        .with_prompt("是否重新編碼已是 HEVC 的影片？")
+        .default(config.settings.video_encoder.reencode_hevc)
+        .interact()?;
+    let preserve_mtime = Confirm::new()
+        .with_prompt("轉檔後的檔案是否沿用原始檔案的修改時間？")
+        .default(config.settings.video_encoder.preserve_mtime)
+        .interact()?;
+    let keep_smaller = Confirm::new()
+        .with_prompt("編碼後檔案比原始檔案大時，是否刪除輸出並保留原檔？")
+        .default(config.settings.video_encoder.keep_smaller)
+        .interact()?;
+    let keep_smaller_tolerance_percent: u8 = if keep_smaller {
+        Input::new()
+            .with_prompt("輸出比原始檔案大多少百分比以內仍保留輸出（0 = 只要較大就保留原檔）")
+            .default(config.settings.video_encoder.keep_smaller_tolerance_percent)
+            .interact_text()?
+    } else {
+        config.settings.video_encoder.keep_smaller_tolerance_percent
+    };
+    let keep_all_audio = Confirm::new()
+        .with_prompt("是否保留所有音軌？（否 = 只保留第一條）")
+        .default(config.settings.video_encoder.keep_all_audio)
+        .interact()?;
+    let keep_subtitles = Confirm::new()
+        .with_prompt("是否保留內嵌字幕？")
+        .default(config.settings.video_encoder.keep_subtitles)
+        .interact()?;
+    let keep_success_logs = Confirm::new()
+        .with_prompt("編碼成功後是否保留 ffmpeg 記錄檔？（失敗的記錄檔一律保留）")
+        .default(config.settings.video_encoder.keep_success_logs)
+        .interact()?;
+    let max_retries: u32 = Input::new()
+        .with_prompt("編碼失敗後的重試次數（0 = 不重試）")
+        .default(config.settings.video_encoder.max_retries)
+        .interact_text()?;
+    let shutdown_grace_secs: u64 = Input::new()
+        .with_prompt("中斷時等待 ffmpeg 正常結束的秒數（逾時強制終止）")
+        .default(config.settings.video_encoder.shutdown_grace_secs)
+        .interact_text()?;
+    let output_dir = prompt_output_directory(
+        "轉檔輸出資料夾（留空 = 與原始檔案相同資料夾）",
+        &config
+            .settings
+            .video_encoder
+            .output_dir
+            .as_ref()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default(),
+        &OutputFormat::Human,
+    )?;
+
+    let encoder = &mut config.settings.video_encoder;
+    if selected_action != encoder.post_encode_action
+        || replace_use_original_name != encoder.replace_use_original_name
+        || stability_check_secs != encoder.stability_check_secs
+        || reencode_hevc != encoder.reencode_hevc
+        || preserve_mtime != encoder.preserve_mtime
+        || keep_smaller != encoder.keep_smaller
+        || keep_smaller_tolerance_percent != encoder.keep_smaller_tolerance_percent
+        || keep_all_audio != encoder.keep_all_audio
+        || keep_subtitles != encoder.keep_subtitles
+        || keep_success_logs != encoder.keep_success_logs
+        || max_retries != encoder.max_retries
+        || shutdown_grace_secs != encoder.shutdown_grace_secs
+        || output_dir != encoder.output_dir
+    {
+        encoder.post_encode_action = selected_action;
+        encoder.replace_use_original_name = replace_use_original_name;
+        encoder.stability_check_secs = stability_check_secs;
+        encoder.reencode_hevc = reencode_hevc;
+        encoder.preserve_mtime = preserve_mtime;
+        encoder.keep_smaller = keep_smaller;
+        encoder.keep_smaller_tolerance_percent = keep_smaller_tolerance_percent;
+        encoder.keep_all_audio = keep_all_audio;
+        encoder.keep_subtitles = keep_subtitles;
+        encoder.keep_success_logs = keep_success_logs;
+        encoder.max_retries = max_retries;
+        encoder.shutdown_grace_secs = shutdown_grace_secs;
+        encoder.output_dir = output_dir;
+        save_settings(&config.settings)?;
+        println!("\n{}", style(t!("settings.saved")).green());
+        thread::sleep(Duration::from_secs(1));
     }
 
     Ok(())
@@ -251,7 +672,7 @@ fn show_encoder_parallel_settings(term: &Term, config: &mut Config) -> Result<()
         .map(|v| v as i64)
         .unwrap_or(-1);
     let max_limit: i64 = Input::new()
-        .with_prompt("最大同時轉檔數（-1 = 無限制）")
+        .with_prompt("最大同時轉檔數（-1 = 無限制，1 = 逐一轉檔）")
         .default(current_max)
         .interact_text()?;
     config.settings.video_encoder.max_parallel = if max_limit <= 0 {
@@ -260,6 +681,46 @@ fn show_encoder_parallel_settings(term: &Term, config: &mut Config) -> Result<()
         Some(max_limit as usize)
     };
 
+    // GPU 編碼最大同時數
+    let max_gpu_parallel: usize = Input::new()
+        .with_prompt("GPU 編碼最大同時數（消費級 GPU 通常為 1-2）")
+        .default(config.settings.video_encoder.max_gpu_parallel)
+        .validate_with(|v: &usize| {
+            if *v >= 1 {
+                Ok(())
+            } else {
+                Err("至少為 1".to_string())
+            }
+        })
+        .interact_text()?;
+    config.settings.video_encoder.max_gpu_parallel = max_gpu_parallel;
+
+    // 可用記憶體門檻
+    let min_free_memory_mb: u64 = Input::new()
+        .with_prompt("可用記憶體低於多少 MB 時暫停新增任務（0 = 不檢查）")
+        .default(config.settings.video_encoder.min_free_memory_mb)
+        .interact_text()?;
+    let min_free_memory_percent: u8 = Input::new()
+        .with_prompt("可用記憶體低於多少 % 時暫停新增任務（0 = 不檢查）")
+        .default(config.settings.video_encoder.min_free_memory_percent)
+        .validate_with(|v: &u8| {
+            if *v <= 100 {
+                Ok(())
+            } else {
+                Err("必須介於 0 到 100".to_string())
+            }
+        })
+        .interact_text()?;
+    config.settings.video_encoder.min_free_memory_mb = min_free_memory_mb;
+    config.settings.video_encoder.min_free_memory_percent = min_free_memory_percent;
+
+    // 輸出位置最低可用空間
+    let min_free_space_mb: u64 = Input::new()
+        .with_prompt("輸出位置可用空間低於多少 MB 時停止新增任務（0 = 不檢查）")
+        .default(config.settings.video_encoder.min_free_space_mb)
+        .interact_text()?;
+    config.settings.video_encoder.min_free_space_mb = min_free_space_mb;
+
     save_settings(&config.settings)?;
     println!("\n{}", style(t!("settings.saved")).green());
     thread::sleep(Duration::from_secs(1));
@@ -283,6 +744,18 @@ fn show_contact_sheet_settings_menu(term: &Term, config: &mut Config) -> Result<
         style(t!("settings.contact_sheet.current")).dim(),
         config.settings.contact_sheet.output_mode
     );
+    println!(
+        "{} {}（品質 {}）",
+        style("輸出格式:").dim(),
+        config.settings.contact_sheet.output_format,
+        config.settings.contact_sheet.quality
+    );
+    println!(
+        "{} {}x{}",
+        style("網格大小:").dim(),
+        config.settings.contact_sheet.grid_cols,
+        config.settings.contact_sheet.grid_rows
+    );
     println!();
 
     let modes = [
@@ -312,9 +785,68 @@ fn show_contact_sheet_settings_menu(term: &Term, config: &mut Config) -> Result<
     };
 
     let selected_mode = modes[selection];
+    let use_batch_extraction = Confirm::new()
+        .with_prompt("精準模式是否使用批次擷取（減少 ffmpeg 程序數）？")
+        .default(config.settings.contact_sheet.use_batch_extraction)
+        .interact()?;
+    let use_scene_cache = Confirm::new()
+        .with_prompt("精準模式是否重用先前的場景偵測結果？")
+        .default(config.settings.contact_sheet.use_scene_cache)
+        .interact()?;
+    let burn_timestamps = Confirm::new()
+        .with_prompt("是否在縮圖上標示時間戳記？")
+        .default(config.settings.contact_sheet.burn_timestamps)
+        .interact()?;
+    let show_header = Confirm::new()
+        .with_prompt("是否在預覽圖上方加入檔案資訊標頭？")
+        .default(config.settings.contact_sheet.show_header)
+        .interact()?;
 
-    if selected_mode != config.settings.contact_sheet.output_mode {
-        config.settings.contact_sheet.output_mode = selected_mode;
+    let formats = ContactSheetFormat::ALL;
+    let format_items: Vec<String> = formats.iter().map(ToString::to_string).collect();
+    let format_index = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("選擇輸出格式")
+        .items(&format_items)
+        .default(
+            formats
+                .iter()
+                .position(|&f| f == config.settings.contact_sheet.output_format)
+                .unwrap_or(0),
+        )
+        .interact_on(term)?;
+    let output_format = formats[format_index];
+    let quality = if output_format == ContactSheetFormat::Png {
+        config.settings.contact_sheet.quality
+    } else {
+        Input::<u8>::new()
+            .with_prompt("輸出品質（1-100）")
+            .default(config.settings.contact_sheet.quality)
+            .validate_with(|v: &u8| {
+                if (1..=100).contains(v) {
+                    Ok(())
+                } else {
+                    Err("品質必須介於 1 到 100")
+                }
+            })
+            .interact_text()?
+    };
+
+    let contact_sheet = &mut config.settings.contact_sheet;
+    if selected_mode != contact_sheet.output_mode
+        || use_batch_extraction != contact_sheet.use_batch_extraction
+        || use_scene_cache != contact_sheet.use_scene_cache
+        || burn_timestamps != contact_sheet.burn_timestamps
+        || show_header != contact_sheet.show_header
+        || output_format != contact_sheet.output_format
+        || quality != contact_sheet.quality
+    {
+        contact_sheet.output_mode = selected_mode;
+        contact_sheet.use_batch_extraction = use_batch_extraction;
+        contact_sheet.use_scene_cache = use_scene_cache;
+        contact_sheet.burn_timestamps = burn_timestamps;
+        contact_sheet.show_header = show_header;
+        contact_sheet.output_format = output_format;
+        contact_sheet.quality = quality;
         save_settings(&config.settings)?;
         println!(
             "\n{} {}",
@@ -327,6 +859,555 @@ fn show_contact_sheet_settings_menu(term: &Term, config: &mut Config) -> Result<
     Ok(())
 }
 
+/// 去重設定選單
+fn show_duplication_settings_menu(term: &Term, config: &mut Config) -> Result<()> {
+    term.clear_screen()?;
+    println!("{}", style("=== 去重設定 ===").cyan().bold());
+    println!("{}", style(t!("common.esc_hint")).dim());
+    println!(
+        "\n{} {}",
+        style("重複檔案處理方式:").dim(),
+        config.settings.duplication.action
+    );
+    println!(
+        "{} {}",
+        style("重複檔案與失敗輸出的去向:").dim(),
+        config.settings.deletion_mode
+    );
+    println!();
+
+    let items = [
+        "移動到 duplication_file 資料夾（可事後確認）",
+        "直接刪除（釋放空間，無法復原）",
+        "以硬連結取代（保留路徑且不佔空間，需在同一磁碟）",
+    ];
+    let default_index = DuplicateAction::ALL
+        .iter()
+        .position(|&a| a == config.settings.duplication.action)
+        .unwrap_or(0);
+
+    let Some(idx) = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("選擇重複檔案處理方式")
+        .items(items)
+        .default(default_index)
+        .interact_on_opt(term)?
+    else {
+        return Ok(());
+    };
+
+    let action = DuplicateAction::ALL[idx];
+    if action != config.settings.duplication.action {
+        config.settings.duplication.action = action;
+        save_settings(&config.settings)?;
+        println!("\n{} {action}", style(t!("settings.saved")).green());
+        thread::sleep(Duration::from_secs(1));
+    }
+
+    let items = [
+        "移到資料夾（duplication_file、fail 等）",
+        "送到系統資源回收筒（不支援時改移到資料夾）",
+    ];
+    let default_index = DeletionMode::ALL
+        .iter()
+        .position(|&m| m == config.settings.deletion_mode)
+        .unwrap_or(0);
+    let Some(idx) = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("重複檔案與失敗輸出的去向")
+        .items(items)
+        .default(default_index)
+        .interact_on_opt(term)?
+    else {
+        return Ok(());
+    };
+
+    let deletion_mode = DeletionMode::ALL[idx];
+    if deletion_mode != config.settings.deletion_mode {
+        config.settings.deletion_mode = deletion_mode;
+        save_settings(&config.settings)?;
+        println!("\n{} {deletion_mode}", style(t!("settings.saved")).green());
+        thread::sleep(Duration::from_secs(1));
+    }
+
+    let hash_threads: usize = Input::new()
+        .with_prompt("同時計算 hash 的執行緒數（0 = 自動，傳統硬碟建議 1）")
+        .default(config.settings.duplication.hash_threads)
+        .interact_text()?;
+    if hash_threads != config.settings.duplication.hash_threads {
+        config.settings.duplication.hash_threads = hash_threads;
+        save_settings(&config.settings)?;
+        println!("\n{} {hash_threads}", style(t!("settings.saved")).green());
+        thread::sleep(Duration::from_secs(1));
+    }
+
+    let items: Vec<String> = MatchMode::ALL.iter().map(ToString::to_string).collect();
+    let default_index = MatchMode::ALL
+        .iter()
+        .position(|&m| m == config.settings.duplication.match_mode)
+        .unwrap_or(0);
+    let Some(idx) = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("紀錄模式的比對方式")
+        .items(&items)
+        .default(default_index)
+        .interact_on_opt(term)?
+    else {
+        return Ok(());
+    };
+
+    let match_mode = MatchMode::ALL[idx];
+    if match_mode != config.settings.duplication.match_mode {
+        config.settings.duplication.match_mode = match_mode;
+        save_settings(&config.settings)?;
+        println!("\n{} {match_mode}", style(t!("settings.saved")).green());
+        thread::sleep(Duration::from_secs(1));
+    }
+    if match_mode == MatchMode::Perceptual {
+        let perceptual = config.settings.duplication.perceptual.clone();
+        let frames: usize = Input::new()
+            .with_prompt("每部影片取樣的影格數（越多越準確，但越慢）")
+            .default(perceptual.frames)
+            .validate_with(|value: &usize| {
+                if *value == 0 {
+                    Err("至少需要 1 個影格")
+                } else {
+                    Ok(())
+                }
+            })
+            .interact_text()?;
+        let threshold: u32 = Input::new()
+            .with_prompt(format!(
+                "相似度門檻（平均每影格不同的位元數，0–{MAX_PERCEPTUAL_THRESHOLD}，越小越嚴格）"
+            ))
+            .default(perceptual.threshold)
+            .validate_with(|value: &u32| {
+                if *value > MAX_PERCEPTUAL_THRESHOLD {
+                    Err(format!("門檻不可超過 {MAX_PERCEPTUAL_THRESHOLD}"))
+                } else {
+                    Ok(())
+                }
+            })
+            .interact_text()?;
+        if frames != perceptual.frames || threshold != perceptual.threshold {
+            config.settings.duplication.perceptual.frames = frames;
+            config.settings.duplication.perceptual.threshold = threshold;
+            save_settings(&config.settings)?;
+            println!(
+                "\n{} {frames} 個影格，門檻 {threshold}",
+                style(t!("settings.saved")).green()
+            );
+            thread::sleep(Duration::from_secs(1));
+        }
+    }
+
+    let hash_table_input: String = Input::new()
+        .with_prompt(format!(
+            "紀錄檔位置（可用 ~ 表示家目錄，留空則使用設定檔旁的 {DEFAULT_HASH_TABLE_FILE}）"
+        ))
+        .with_initial_text(config.settings.hash_table_path.clone().unwrap_or_default())
+        .allow_empty(true)
+        .interact_text()?;
+    let hash_table_path = Some(hash_table_input.trim())
+        .filter(|path| !path.is_empty())
+        .map(str::to_string);
+    if hash_table_path != config.settings.hash_table_path {
+        config.settings.hash_table_path = hash_table_path;
+        save_settings(&config.settings)?;
+        println!(
+            "\n{} {}",
+            style(t!("settings.saved")).green(),
+            resolve_hash_table_path(config.settings.hash_table_path.as_deref(), None).display()
+        );
+        thread::sleep(Duration::from_secs(1));
+    }
+
+    let names_input: String = Input::new()
+        .with_prompt("具名紀錄（以逗號分隔，例如 movies,photos；留空則只使用預設紀錄）")
+        .with_initial_text(config.settings.hash_tables.join(","))
+        .allow_empty(true)
+        .validate_with(|input: &String| {
+            input
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .find(|name| !is_valid_table_name(name))
+                .map_or(Ok(()), |name| {
+                    Err(format!("名稱只能包含英數字、- 與 _: {name}"))
+                })
+        })
+        .interact_text()?;
+    let mut hash_tables: Vec<String> = Vec::new();
+    for name in names_input.split(',').map(str::trim) {
+        if !name.is_empty() && !hash_tables.iter().any(|n| n == name) {
+            hash_tables.push(name.to_string());
+        }
+    }
+    if hash_tables != config.settings.hash_tables {
+        config.settings.hash_tables = hash_tables;
+        save_settings(&config.settings)?;
+        println!(
+            "\n{} {}",
+            style(t!("settings.saved")).green(),
+            config.settings.hash_tables.join(", ")
+        );
+        thread::sleep(Duration::from_secs(1));
+    }
+
+    Ok(())
+}
+
+/// 分類資料夾名稱設定選單
+fn show_category_folder_menu(term: &Term, config: &mut Config) -> Result<()> {
+    let categories: Vec<FileCategory> = FileCategory::all_categories()
+        .iter()
+        .copied()
+        .chain([FileCategory::Other])
+        .collect();
+
+    loop {
+        term.clear_screen()?;
+        println!("{}", style("=== 分類資料夾名稱 ===").cyan().bold());
+        println!("{}", style(t!("common.esc_hint")).dim());
+        println!();
+
+        let overrides = &config.settings.category_folder_overrides;
+        let mut options: Vec<String> = categories
+            .iter()
+            .map(|category| {
+                let folder = category.folder_name_with(overrides);
+                if folder == category.folder_name() {
+                    format!("{:<10} {folder}", category.display_name())
+                } else {
+                    format!(
+                        "{:<10} {folder} {}",
+                        category.display_name(),
+                        style(format!("（預設: {}）", category.folder_name())).dim()
+                    )
+                }
+            })
+            .collect();
+        options.push(t!("settings.back").to_string());
+
+        let Some(idx) = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("選擇要修改的分類")
+            .items(&options)
+            .default(0)
+            .interact_on_opt(term)?
+        else {
+            break;
+        };
+        let Some(&category) = categories.get(idx) else {
+            break;
+        };
+
+        let current = category
+            .folder_name_with(&config.settings.category_folder_overrides)
+            .to_string();
+        let input: String = Input::new()
+            .with_prompt(format!(
+                "{} 的資料夾名稱（留空 = 預設 {}）",
+                category.display_name(),
+                category.folder_name()
+            ))
+            .with_initial_text(current)
+            .allow_empty(true)
+            .validate_with(|input: &String| {
+                let name = input.trim();
+                if name.is_empty() || is_valid_folder_name(name) {
+                    Ok(())
+                } else {
+                    Err("資料夾名稱只能是單一層資料夾，不可包含路徑分隔字元、. 或 ..")
+                }
+            })
+            .interact_text()?;
+        let name = input.trim();
+
+        let overrides = &mut config.settings.category_folder_overrides;
+        let changed = if name.is_empty() || name == category.folder_name() {
+            overrides.remove(&category).is_some()
+        } else {
+            overrides.insert(category, name.to_string()).as_deref() != Some(name)
+        };
+        if changed {
+            save_settings(&config.settings)?;
+            println!("\n{}", style(t!("settings.saved")).green());
+            thread::sleep(Duration::from_secs(1));
+        }
+    }
+
+    Ok(())
+}
+
+/// 掃描篩選設定選單（排除規則與檔案大小範圍）
+fn show_scan_filter_menu(term: &Term, config: &mut Config) -> Result<()> {
+    loop {
+        term.clear_screen()?;
+        println!("{}", style("=== 掃描篩選 ===").cyan().bold());
+        println!("{}", style(t!("common.esc_hint")).dim());
+        println!(
+            "{}",
+            style("規則以掃描資料夾的相對路徑或檔名比對，例如 sample-*.mkv、**/@eaDir/**").dim()
+        );
+        println!();
+
+        let globs = &config.settings.exclude_globs;
+        let size_label = |value: &Option<String>| value.clone().unwrap_or_else(|| "不限制".into());
+        let mut options: Vec<String> = globs.iter().map(|glob| format!("移除 {glob}")).collect();
+        options.push("新增規則...".to_string());
+        options.push(format!(
+            "檔案大小下限: {}",
+            size_label(&config.settings.min_file_size)
+        ));
+        options.push(format!(
+            "檔案大小上限: {}",
+            size_label(&config.settings.max_file_size)
+        ));
+        options.push(t!("settings.back").to_string());
+
+        let Some(idx) = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("選擇要修改的項目")
+            .items(&options)
+            .default(globs.len())
+            .interact_on_opt(term)?
+        else {
+            break;
+        };
+
+        let rule_count = globs.len();
+        if idx < rule_count {
+            let removed = config.settings.exclude_globs.remove(idx);
+            save_settings(&config.settings)?;
+            println!("\n{} {removed}", style(t!("settings.saved")).green());
+        } else if idx == rule_count {
+            let input: String = Input::new()
+                .with_prompt("排除規則（glob）")
+                .validate_with(|input: &String| validate_glob(input.trim()))
+                .interact_text()?;
+            let glob = input.trim().to_string();
+            if config.settings.exclude_globs.contains(&glob) {
+                continue;
+            }
+            config.settings.exclude_globs.push(glob.clone());
+            save_settings(&config.settings)?;
+            println!("\n{} {glob}", style(t!("settings.saved")).green());
+        } else if idx <= rule_count + 2 {
+            let is_min = idx == rule_count + 1;
+            let (min, max) = (
+                config.settings.min_file_size.clone(),
+                config.settings.max_file_size.clone(),
+            );
+            let current = if is_min { &min } else { &max };
+            let input: String = Input::new()
+                .with_prompt(if is_min {
+                    "檔案大小下限（例如 10MB，留空 = 不限制）"
+                } else {
+                    "檔案大小上限（例如 50GB，留空 = 不限制）"
+                })
+                .with_initial_text(current.clone().unwrap_or_default())
+                .allow_empty(true)
+                .validate_with(|input: &String| {
+                    let (min, max) = if is_min {
+                        (Some(input.as_str()), max.as_deref())
+                    } else {
+                        (min.as_deref(), Some(input.as_str()))
+                    };
+                    SizeFilter::parse(min, max)
+                        .map(|_| ())
+                        .map_err(|e| format!("{e:#}"))
+                })
+                .interact_text()?;
+            let value = Some(input.trim().to_string()).filter(|v| !v.is_empty());
+            if is_min {
+                config.settings.min_file_size = value;
+            } else {
+                config.settings.max_file_size = value;
+            }
+            save_settings(&config.settings)?;
+            println!("\n{}", style(t!("settings.saved")).green());
+        } else {
+            break;
+        }
+        thread::sleep(Duration::from_secs(1));
+    }
+
+    Ok(())
+}
+
+/// 一般設定選單（最近使用的路徑、ffmpeg、結果輸出、日誌）
+fn show_general_settings_menu(term: &Term, config: &mut Config) -> Result<()> {
+    loop {
+        term.clear_screen()?;
+        println!("{}", style("=== 一般設定 ===").cyan().bold());
+        println!("{}", style(t!("common.esc_hint")).dim());
+        println!();
+
+        let settings = &config.settings;
+        let restart = style("（重新啟動後生效）").dim();
+        let options = vec![
+            format!(
+                "{:<18} {}",
+                "每個功能的最近路徑數",
+                settings.max_recent_paths()
+            ),
+            format!(
+                "{:<18} {}",
+                "ffmpeg 位置",
+                settings.ffmpeg_path.as_deref().unwrap_or("從 PATH 尋找")
+            ),
+            format!(
+                "{:<18} {}",
+                "命令列結果輸出",
+                format_json_output(settings.json_output.as_deref())
+            ),
+            format!("{:<18} {} {restart}", "日誌等級", settings.logging.level),
+            format!(
+                "{:<18} {} {restart}",
+                "日誌資料夾",
+                log_file_path(&settings.logging)
+                    .parent()
+                    .unwrap_or_else(|| Path::new(""))
+                    .display()
+            ),
+            format!(
+                "{:<18} {} MB，保留 {} 份 {restart}",
+                "日誌檔輪替", settings.logging.max_file_size_mb, settings.logging.max_files
+            ),
+            t!("settings.back").to_string(),
+        ];
+
+        let Some(idx) = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("選擇要修改的設定")
+            .items(&options)
+            .default(0)
+            .interact_on_opt(term)?
+        else {
+            break;
+        };
+
+        let changed = match idx {
+            0 => prompt_recent_path_limit(config),
+            1 => prompt_optional_text(
+                "ffmpeg 執行檔或所在資料夾（留空 = 從 PATH 尋找）",
+                &mut config.settings.ffmpeg_path,
+            ),
+            2 => prompt_optional_text(
+                "命令列結果輸出（- = JSON 輸出到 stdout，其他為 JSON 檔案路徑，留空 = 彩色摘要）",
+                &mut config.settings.json_output,
+            ),
+            3 => prompt_log_level(term, config),
+            4 => prompt_optional_text(
+                "日誌資料夾（可用 ~ 表示家目錄，留空 = 預設位置）",
+                &mut config.settings.logging.directory,
+            ),
+            5 => prompt_log_rotation(config),
+            _ => break,
+        }?;
+        if changed {
+            save_settings(&config.settings)?;
+            println!("\n{}", style(t!("settings.saved")).green());
+            // 更換 ffmpeg 位置後立即重新尋找並檢查，主選單依新的結果啟用功能
+            if idx == 1 {
+                check_binaries(&config.settings);
+                if !check_dependencies().errors().is_empty() {
+                    pause(term)?;
+                    continue;
+                }
+            }
+            thread::sleep(Duration::from_secs(1));
+        }
+    }
+
+    Ok(())
+}
+
+fn format_json_output(json_output: Option<&str>) -> String {
+    match json_output {
+        None => "彩色摘要".to_string(),
+        Some("-") => "JSON（stdout）".to_string(),
+        Some(path) => format!("JSON 檔案 {path}"),
+    }
+}
+
+/// 修改最近路徑保留數量，超出的舊路徑立即移除
+fn prompt_recent_path_limit(config: &mut Config) -> Result<bool> {
+    let current = config.settings.max_recent_paths();
+    let limit: usize = Input::new()
+        .with_prompt("每個功能保留的最近使用路徑數量（不含釘選）")
+        .default(current)
+        .validate_with(|value: &usize| {
+            if *value == 0 {
+                Err("至少需要保留 1 個路徑")
+            } else {
+                Ok(())
+            }
+        })
+        .interact_text()?;
+    if limit == current {
+        return Ok(false);
+    }
+    config.settings.recent_path_limit = (limit != MAX_RECENT_PATHS).then_some(limit);
+    config.settings.recent_paths.truncate(limit);
+    for list in config.settings.path_history.values_mut() {
+        list.recent.truncate(limit);
+    }
+    Ok(true)
+}
+
+/// 修改可留空的文字設定，回傳是否有變更
+fn prompt_optional_text(prompt: &str, value: &mut Option<String>) -> Result<bool> {
+    let input: String = Input::new()
+        .with_prompt(prompt)
+        .with_initial_text(value.clone().unwrap_or_default())
+        .allow_empty(true)
+        .interact_text()?;
+    let input = Some(input.trim())
+        .filter(|input| !input.is_empty())
+        .map(str::to_string);
+    if input == *value {
+        return Ok(false);
+    }
+    *value = input;
+    Ok(true)
+}
+
+fn prompt_log_level(term: &Term, config: &mut Config) -> Result<bool> {
+    let items: Vec<String> = LogLevel::ALL.iter().map(ToString::to_string).collect();
+    let default_index = LogLevel::ALL
+        .iter()
+        .position(|&level| level == config.settings.logging.level)
+        .unwrap_or(0);
+    let Some(idx) = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("寫入日誌檔的等級")
+        .items(&items)
+        .default(default_index)
+        .interact_on_opt(term)?
+    else {
+        return Ok(false);
+    };
+    let level = LogLevel::ALL[idx];
+    if level == config.settings.logging.level {
+        return Ok(false);
+    }
+    config.settings.logging.level = level;
+    Ok(true)
+}
+
+fn prompt_log_rotation(config: &mut Config) -> Result<bool> {
+    let logging = &config.settings.logging;
+    let max_file_size_mb: u64 = Input::new()
+        .with_prompt("單一日誌檔的大小上限（MB，0 = 不輪替）")
+        .default(logging.max_file_size_mb)
+        .interact_text()?;
+    let max_files: usize = Input::new()
+        .with_prompt("保留的舊日誌檔數量")
+        .default(logging.max_files)
+        .interact_text()?;
+    if max_file_size_mb == logging.max_file_size_mb && max_files == logging.max_files {
+        return Ok(false);
+    }
+    config.settings.logging.max_file_size_mb = max_file_size_mb;
+    config.settings.logging.max_files = max_files;
+    Ok(true)
+}
+
 /// 語言設定選單
 fn show_language_menu(term: &Term, config: &mut Config) -> Result<()> {
     term.clear_screen()?;
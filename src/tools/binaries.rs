@@ -0,0 +1,214 @@
+//! ffmpeg / ffprobe 執行檔定位
+//!
+//! 依序檢查設定中的 `ffmpeg_path`、PATH 環境變數、常見安裝位置，
+//! 找到後快取結果，之後所有呼叫直接重用；重新設定路徑時清除快取。
+
+use anyhow::{Result, anyhow};
+use log::{debug, info};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::{PoisonError, RwLock};
+
+/// 常見的安裝位置（PATH 找不到時依序嘗試）
+const COMMON_LOCATIONS: &[&str] = &[
+    "/usr/bin",
+    "/usr/local/bin",
+    "/opt/homebrew/bin",
+    "/opt/local/bin",
+    "/snap/bin",
+    "C:/ffmpeg/bin",
+    "C:/Program Files/ffmpeg/bin",
+    "C:/ProgramData/chocolatey/bin",
+];
+
+/// 設定中的 ffmpeg 路徑（檔案或所在資料夾）
+static CONFIGURED_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// 尋找結果的快取，外層 `None` 代表尚未尋找
+type BinaryCache = RwLock<Option<Option<&'static Path>>>;
+static FFMPEG: BinaryCache = RwLock::new(None);
+static FFPROBE: BinaryCache = RwLock::new(None);
+
+/// 設定使用者指定的 ffmpeg 路徑
+///
+/// 清除已找到的執行檔與 ffmpeg 功能快取，下次使用時依新的設定重新尋找
+pub fn configure_ffmpeg_path(path: Option<&str>) {
+    let path = path
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from);
+    *CONFIGURED_PATH
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = path;
+    for cache in [&FFMPEG, &FFPROBE] {
+        *cache.write().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+    super::ffmpeg_caps::clear_capabilities();
+}
+
+/// 取得 ffmpeg 執行檔路徑
+pub fn resolve_ffmpeg() -> Result<&'static Path> {
+    resolve_cached(&FFMPEG, "ffmpeg")
+}
+
+/// 取得 ffprobe 執行檔路徑
+pub fn resolve_ffprobe() -> Result<&'static Path> {
+    resolve_cached(&FFPROBE, "ffprobe")
+}
+
+/// 取得快取的執行檔路徑，尚未尋找時依目前設定尋找
+///
+/// 找到的路徑會常駐記憶體以回傳 `'static` 參照，只有重新設定路徑時才會再配置
+fn resolve_cached(cache: &'static BinaryCache, name: &str) -> Result<&'static Path> {
+    let cached = *cache.read().unwrap_or_else(PoisonError::into_inner);
+    let found = cached.unwrap_or_else(|| {
+        let configured = CONFIGURED_PATH
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        let found: Option<&'static Path> = find_binary(name, configured.as_deref(), &search_dirs())
+            .map(|path| {
+                info!("使用 {name}: {}", path.display());
+                &*Box::leak(path.into_boxed_path())
+            });
+        *cache.write().unwrap_or_else(PoisonError::into_inner) = Some(found);
+        found
+    });
+    found.ok_or_else(|| {
+        anyhow!("找不到 {name}，請安裝 ffmpeg 並加入 PATH，或在 settings.json 設定 ffmpeg_path")
+    })
+}
+
+/// 搜尋資料夾：PATH、程式所在資料夾、常見安裝位置
+fn search_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).collect())
+        .unwrap_or_default();
+
+    // 可攜式安裝：ffmpeg 與本程式放在同一資料夾
+    if let Some(exe_dir) = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+    {
+        dirs.push(exe_dir);
+    }
+
+    dirs.extend(COMMON_LOCATIONS.iter().map(PathBuf::from));
+    dirs
+}
+
+/// 在設定路徑與搜尋資料夾中尋找執行檔
+///
+/// 設定路徑可以是資料夾，或是 ffmpeg 執行檔本身（此時在同一資料夾尋找 ffprobe）
+fn find_binary(name: &str, configured: Option<&Path>, search_dirs: &[PathBuf]) -> Option<PathBuf> {
+    let file_name = format!("{name}{}", env::consts::EXE_SUFFIX);
+
+    if let Some(configured) = configured {
+        let candidate = if configured.is_dir() {
+            Some(configured.join(&file_name))
+        } else if configured.file_stem().is_some_and(|stem| stem == name) {
+            Some(configured.to_path_buf())
+        } else {
+            configured.parent().map(|dir| dir.join(&file_name))
+        };
+
+        if let Some(candidate) = candidate.filter(|p| p.is_file()) {
+            return Some(candidate);
+        }
+        debug!(
+            "設定的 ffmpeg_path 中找不到 {name}: {}",
+            configured.display()
+        );
+    }
+
+    search_dirs
+        .iter()
+        .map(|dir| dir.join(&file_name))
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn touch(dir: &Path, name: &str) -> PathBuf {
+        let path = dir.join(format!("{name}{}", env::consts::EXE_SUFFIX));
+        fs::write(&path, b"").unwrap();
+        path
+    }
+
+    #[test]
+    fn test_find_binary_prefers_configured_dir() {
+        let configured = TempDir::new().unwrap();
+        let on_path = TempDir::new().unwrap();
+        let expected = touch(configured.path(), "ffmpeg");
+        touch(on_path.path(), "ffmpeg");
+
+        let found = find_binary(
+            "ffmpeg",
+            Some(configured.path()),
+            &[on_path.path().to_path_buf()],
+        );
+        assert_eq!(found, Some(expected));
+    }
+
+    #[test]
+    fn test_find_binary_configured_file_locates_sibling() {
+        let dir = TempDir::new().unwrap();
+        let ffmpeg = touch(dir.path(), "ffmpeg");
+        let ffprobe = touch(dir.path(), "ffprobe");
+
+        assert_eq!(
+            find_binary("ffmpeg", Some(&ffmpeg), &[]),
+            Some(ffmpeg.clone())
+        );
+        assert_eq!(find_binary("ffprobe", Some(&ffmpeg), &[]), Some(ffprobe));
+    }
+
+    #[test]
+    fn test_find_binary_falls_back_to_search_dirs() {
+        let empty = TempDir::new().unwrap();
+        let first = TempDir::new().unwrap();
+        let second = TempDir::new().unwrap();
+        touch(second.path(), "ffprobe");
+
+        let found = find_binary(
+            "ffprobe",
+            Some(empty.path()),
+            &[first.path().to_path_buf(), second.path().to_path_buf()],
+        );
+        assert_eq!(
+            found,
+            Some(
+                second
+                    .path()
+                    .join(format!("ffprobe{}", env::consts::EXE_SUFFIX))
+            )
+        );
+    }
+
+    #[test]
+    fn test_find_binary_not_found() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(
+            find_binary("ffmpeg", None, &[dir.path().to_path_buf()]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_reconfigure_clears_cache() {
+        let first = TempDir::new().unwrap();
+        let second = TempDir::new().unwrap();
+        let first_probe = touch(first.path(), "ffprobe");
+        let second_probe = touch(second.path(), "ffprobe");
+
+        configure_ffmpeg_path(first.path().to_str());
+        assert_eq!(resolve_ffprobe().unwrap(), first_probe);
+        configure_ffmpeg_path(second.path().to_str());
+        assert_eq!(resolve_ffprobe().unwrap(), second_probe);
+        configure_ffmpeg_path(None);
+    }
+}
@@ -0,0 +1,54 @@
+//! CSV 工具
+
+/// 含逗號、引號或換行的欄位以引號包住
+#[must_use]
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 拆解 [`csv_field`] 寫出的單行 CSV，引號內的逗號與 `""` 會還原
+#[must_use]
+pub fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("/media/a.mp4"), "/media/a.mp4");
+        assert_eq!(csv_field("/media/a, b.mp4"), "\"/media/a, b.mp4\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn test_parse_csv_line_round_trip() {
+        let values = ["/media/a, b.mp4", "say \"hi\"", "", "plain"];
+        let line = values.map(csv_field).join(",");
+        assert_eq!(parse_csv_line(&line), values);
+        assert_eq!(parse_csv_line("a,,b"), ["a", "", "b"]);
+    }
+}
@@ -0,0 +1,240 @@
+//! 啟動時的外部相依檢查
+//!
+//! 執行一次 `ffmpeg -version` 與 `ffprobe -version` 取得版本，
+//! 並確認 ffmpeg 具備預覽圖需要的濾鏡。結果會快取（更換 ffmpeg 路徑後可重新檢查），
+//! 主選單據此停用缺少相依的功能，而不是讓任務逐一失敗。
+
+use super::binaries::{resolve_ffmpeg, resolve_ffprobe};
+use super::ffmpeg_caps::probe_capabilities;
+use anyhow::{Context, Result, bail};
+use log::{debug, warn};
+use rust_i18n::t;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{PoisonError, RwLock};
+
+/// 檢查的 ffmpeg 濾鏡
+const CHECKED_FILTERS: &[&str] = &["scdet", "xstack", "drawtext"];
+
+/// 已找到的執行檔與版本
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinaryVersion {
+    pub path: PathBuf,
+    pub version: String,
+}
+
+/// 相依檢查結果
+#[derive(Debug, Clone, Default)]
+pub struct DependencyReport {
+    pub ffmpeg: Option<BinaryVersion>,
+    pub ffprobe: Option<BinaryVersion>,
+    /// ffmpeg 缺少的濾鏡
+    pub missing_filters: Vec<&'static str>,
+}
+
+impl DependencyReport {
+    /// ffprobe 是否可用（依時長重新命名需要）
+    #[must_use]
+    pub const fn ffprobe_ready(&self) -> bool {
+        self.ffprobe.is_some()
+    }
+
+    /// ffmpeg 與 ffprobe 是否皆可用（影片重新編碼需要）
+    #[must_use]
+    pub const fn video_tools_ready(&self) -> bool {
+        self.ffmpeg.is_some() && self.ffprobe.is_some()
+    }
+
+    /// 預覽圖生成是否可用（另需 xstack 濾鏡）
+    #[must_use]
+    pub fn contact_sheet_ready(&self) -> bool {
+        self.video_tools_ready() && !self.is_missing("xstack")
+    }
+
+    #[must_use]
+    pub fn is_missing(&self, filter: &str) -> bool {
+        self.missing_filters.contains(&filter)
+    }
+
+    /// 會停用功能的問題（依目前語言）
+    #[must_use]
+    pub fn errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.ffmpeg.is_none() {
+            errors.push(t!("dependency.ffmpeg_missing").to_string());
+        }
+        if self.ffprobe.is_none() {
+            errors.push(t!("dependency.ffprobe_missing").to_string());
+        }
+        if self.ffmpeg.is_some() && self.is_missing("xstack") {
+            errors.push(t!("dependency.xstack_missing").to_string());
+        }
+        errors
+    }
+
+    /// 只影響部分選項的問題（依目前語言）
+    #[must_use]
+    pub fn warnings(&self, burn_timestamps: bool) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.ffmpeg.is_none() {
+            return warnings;
+        }
+        if self.is_missing("scdet") {
+            warnings.push(t!("dependency.scdet_missing").to_string());
+        }
+        if burn_timestamps && self.is_missing("drawtext") {
+            warnings.push(t!("dependency.drawtext_missing").to_string());
+        }
+        warnings
+    }
+}
+
+/// 相依檢查結果的快取
+static REPORT: RwLock<Option<&'static DependencyReport>> = RwLock::new(None);
+
+/// 取得相依檢查結果（第一次呼叫時執行檢查，之後重用快取）
+pub fn check_dependencies() -> &'static DependencyReport {
+    let cached = *REPORT.read().unwrap_or_else(PoisonError::into_inner);
+    cached.unwrap_or_else(recheck_dependencies)
+}
+
+/// 重新執行相依檢查並更新快取（更換 ffmpeg 路徑後使用）
+pub fn recheck_dependencies() -> &'static DependencyReport {
+    let ffmpeg = detect_version(resolve_ffmpeg(), "ffmpeg");
+    let ffprobe = detect_version(resolve_ffprobe(), "ffprobe");
+
+    let missing_filters = if ffmpeg.is_some() {
+        match probe_capabilities() {
+            Ok(caps) => CHECKED_FILTERS
+                .iter()
+                .copied()
+                .filter(|name| !caps.has_filter(name))
+                .collect(),
+            Err(e) => {
+                warn!("無法取得 ffmpeg 濾鏡清單: {e}");
+                Vec::new()
+            }
+        }
+    } else {
+        Vec::new()
+    };
+
+    let report: &'static DependencyReport = Box::leak(Box::new(DependencyReport {
+        ffmpeg,
+        ffprobe,
+        missing_filters,
+    }));
+    *REPORT.write().unwrap_or_else(PoisonError::into_inner) = Some(report);
+    report
+}
+
+fn detect_version(path: Result<&'static Path>, name: &str) -> Option<BinaryVersion> {
+    let path = path.ok()?;
+    match read_version(path, name) {
+        Ok(version) => {
+            debug!("{name} 版本: {version}");
+            Some(BinaryVersion {
+                path: path.to_path_buf(),
+                version,
+            })
+        }
+        Err(e) => {
+            warn!("{e:#}");
+            None
+        }
+    }
+}
+
+fn read_version(path: &Path, name: &str) -> Result<String> {
+    let output = Command::new(path)
+        .arg("-version")
+        .output()
+        .with_context(|| format!("無法執行 {}", path.display()))?;
+
+    if !output.status.success() {
+        bail!("{name} -version 執行失敗: {}", path.display());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    parse_version(&stdout, name)
+        .with_context(|| format!("無法解析 {name} 版本: {}", path.display()))
+}
+
+/// 解析 `-version` 輸出第一列的版本字串
+///
+/// 例如 `ffmpeg version 6.1.1-3ubuntu5 Copyright (c) ...` 回傳 `6.1.1-3ubuntu5`
+fn parse_version(output: &str, name: &str) -> Option<String> {
+    let mut tokens = output.lines().next()?.split_whitespace();
+    (tokens.next()? == name && tokens.next()? == "version")
+        .then(|| tokens.next().map(str::to_string))
+        .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(name: &str) -> Option<BinaryVersion> {
+        Some(BinaryVersion {
+            path: PathBuf::from(format!("/usr/bin/{name}")),
+            version: "6.1.1".to_string(),
+        })
+    }
+
+    #[test]
+    fn test_parse_version() {
+        let output = "ffmpeg version 6.1.1-3ubuntu5 Copyright (c) 2000-2023 the FFmpeg developers\n\
+                      built with gcc 13 (Ubuntu 13.2.0-23ubuntu3)\n";
+        assert_eq!(
+            parse_version(output, "ffmpeg").as_deref(),
+            Some("6.1.1-3ubuntu5")
+        );
+        assert_eq!(
+            parse_version("ffprobe version n7.0 Copyright", "ffprobe").as_deref(),
+            Some("n7.0")
+        );
+        assert_eq!(parse_version(output, "ffprobe"), None);
+        assert_eq!(parse_version("", "ffmpeg"), None);
+    }
+
+    #[test]
+    fn test_report_disables_features() {
+        let report = DependencyReport {
+            ffmpeg: found("ffmpeg"),
+            ffprobe: found("ffprobe"),
+            missing_filters: vec!["xstack", "drawtext"],
+        };
+        assert!(report.video_tools_ready());
+        assert!(!report.contact_sheet_ready());
+        assert_eq!(report.errors().len(), 1);
+        assert_eq!(report.warnings(true).len(), 1);
+        assert!(report.warnings(false).is_empty());
+
+        let missing_ffprobe = DependencyReport {
+            ffmpeg: found("ffmpeg"),
+            ..DependencyReport::default()
+        };
+        assert!(!missing_ffprobe.ffprobe_ready());
+        assert!(!missing_ffprobe.video_tools_ready());
+        assert!(!missing_ffprobe.contact_sheet_ready());
+        assert_eq!(missing_ffprobe.errors().len(), 1);
+    }
+
+    #[test]
+    fn test_messages_are_localized() {
+        let keys = [
+            "dependency.ffmpeg_missing",
+            "dependency.ffprobe_missing",
+            "dependency.xstack_missing",
+            "dependency.scdet_missing",
+            "dependency.drawtext_missing",
+        ];
+        for key in keys {
+            let english = t!(key, locale = "en-US");
+            assert_ne!(english, key);
+            for locale in ["zh-TW", "zh-CN", "ja-JP"] {
+                assert_ne!(t!(key, locale = locale), english, "{locale} 缺少 {key}");
+            }
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! 磁碟可用空間檢查
+//!
+//! 以 sysinfo 列出掛載點，取路徑所在檔案系統（最長相符的掛載點）的可用空間，
+//! 讓長時間的批次工作在開始前就能發現空間不足。
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use sysinfo::Disks;
+
+/// 預估需求與實際可用空間（位元組）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpaceCheck {
+    pub required: u64,
+    pub available: u64,
+}
+
+impl SpaceCheck {
+    #[must_use]
+    pub const fn is_sufficient(&self) -> bool {
+        self.available >= self.required
+    }
+
+    /// 不足的空間（足夠時為 0）
+    #[must_use]
+    pub const fn shortfall(&self) -> u64 {
+        self.required.saturating_sub(self.available)
+    }
+}
+
+/// 取得路徑所在檔案系統的可用空間（位元組）
+///
+/// 路徑尚未建立時以最近的既有上層目錄判斷
+pub fn available_space(path: &Path) -> Result<u64> {
+    let existing = nearest_existing_ancestor(path)
+        .with_context(|| format!("找不到路徑的既有上層目錄: {}", path.display()))?;
+    let canonical = existing
+        .canonicalize()
+        .with_context(|| format!("無法解析路徑: {}", existing.display()))?;
+
+    let disks = Disks::new_with_refreshed_list();
+    available_on_mount(
+        &canonical,
+        disks
+            .list()
+            .iter()
+            .map(|disk| (disk.mount_point(), disk.available_space())),
+    )
+    .with_context(|| format!("找不到路徑所在的檔案系統: {}", canonical.display()))
+}
+
+/// 檢查路徑所在檔案系統是否有足夠空間
+pub fn check_space(path: &Path, required: u64) -> Result<SpaceCheck> {
+    Ok(SpaceCheck {
+        required,
+        available: available_space(path)?,
+    })
+}
+
+fn nearest_existing_ancestor(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|ancestor| !ancestor.as_os_str().is_empty() && ancestor.exists())
+        .map(Path::to_path_buf)
+        .or_else(|| path.is_relative().then(|| PathBuf::from(".")))
+}
+
+/// 從掛載點清單中找出包含路徑的最長掛載點，回傳其可用空間
+fn available_on_mount<'a>(
+    path: &Path,
+    mounts: impl IntoIterator<Item = (&'a Path, u64)>,
+) -> Option<u64> {
+    mounts
+        .into_iter()
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.components().count())
+        .map(|(_, available)| available)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_available_on_mount_picks_longest_prefix() {
+        let mounts = [
+            (Path::new("/"), 100),
+            (Path::new("/mnt/media"), 5),
+            (Path::new("/mnt/media2"), 7),
+        ];
+        assert_eq!(
+            available_on_mount(Path::new("/mnt/media/videos/a.mp4"), mounts),
+            Some(5)
+        );
+        assert_eq!(
+            available_on_mount(Path::new("/home/user"), mounts),
+            Some(100)
+        );
+        assert_eq!(
+            available_on_mount(Path::new("/mnt/media2"), mounts),
+            Some(7)
+        );
+        assert_eq!(available_on_mount(Path::new("/data"), []), None);
+    }
+
+    #[test]
+    fn test_space_check() {
+        let check = SpaceCheck {
+            required: 300,
+            available: 100,
+        };
+        assert!(!check.is_sufficient());
+        assert_eq!(check.shortfall(), 200);
+
+        let check = SpaceCheck {
+            required: 100,
+            available: 100,
+        };
+        assert!(check.is_sufficient());
+        assert_eq!(check.shortfall(), 0);
+    }
+
+    #[test]
+    fn test_nearest_existing_ancestor() {
+        let dir = TempDir::new().unwrap();
+        let missing = dir.path().join("not").join("created");
+        assert_eq!(
+            nearest_existing_ancestor(&missing).as_deref(),
+            Some(dir.path())
+        );
+    }
+}
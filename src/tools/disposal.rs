@@ -0,0 +1,119 @@
+//! 檔案丟棄方式
+//!
+//! 依設定將不要的檔案移到指定資料夾，或送到系統資源回收筒。
+//! 系統沒有資源回收筒時（例如沒有桌面環境的 NAS）記錄警告後改用資料夾。
+
+use super::{ensure_directory_exists, move_file, unique_destination};
+use crate::config::DeletionMode;
+use anyhow::{Context, Result, anyhow};
+use log::warn;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 檔案實際的去向
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Disposal {
+    /// 已送到系統資源回收筒
+    Trashed,
+    /// 已移到資料夾中的此路徑
+    MovedTo(PathBuf),
+}
+
+/// 將檔案移到 `folder`（同名時加上編號），或依設定送到資源回收筒
+pub fn dispose_file(path: &Path, folder: &Path, mode: DeletionMode) -> Result<Disposal> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("無法取得檔案名稱"))?;
+    dispose_file_as(path, file_name, folder, mode)
+}
+
+/// 同 [`dispose_file`]，但在資料夾中改用 `file_name` 命名（檔案目前是暫存名稱時使用）
+pub fn dispose_file_as(
+    path: &Path,
+    file_name: &OsStr,
+    folder: &Path,
+    mode: DeletionMode,
+) -> Result<Disposal> {
+    if mode == DeletionMode::SystemTrash {
+        match trash::delete(path) {
+            Ok(()) => return Ok(Disposal::Trashed),
+            Err(e) => warn!(
+                "無法送到資源回收筒，改移到 {}: {} ({e})",
+                folder.display(),
+                path.display()
+            ),
+        }
+    }
+
+    ensure_directory_exists(folder)?;
+    let target = unique_destination(folder, file_name);
+    move_file(path, &target)
+        .with_context(|| format!("無法移動檔案: {} -> {}", path.display(), target.display()))?;
+    Ok(Disposal::MovedTo(target))
+}
+
+/// 刪除檔案；資源回收筒模式下改送到資源回收筒，回傳是否已送到資源回收筒
+///
+/// 無法使用資源回收筒時記錄警告後直接刪除
+pub fn remove_or_trash(path: &Path, mode: DeletionMode) -> Result<bool> {
+    if mode == DeletionMode::SystemTrash {
+        match trash::delete(path) {
+            Ok(()) => return Ok(true),
+            Err(e) => warn!("無法送到資源回收筒，改為直接刪除: {} ({e})", path.display()),
+        }
+    }
+    fs::remove_file(path).with_context(|| format!("無法刪除檔案: {}", path.display()))?;
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_dispose_to_folder() {
+        let dir = TempDir::new().unwrap();
+        let folder = dir.path().join("duplication_file");
+        for content in ["first", "second"] {
+            let path = dir.path().join("a.txt");
+            fs::write(&path, content).unwrap();
+            dispose_file(&path, &folder, DeletionMode::MoveToFolder).unwrap();
+            assert!(!path.exists());
+        }
+        assert_eq!(fs::read_to_string(folder.join("a.txt")).unwrap(), "first");
+        assert_eq!(
+            fs::read_to_string(folder.join("a_1.txt")).unwrap(),
+            "second"
+        );
+    }
+
+    #[test]
+    fn test_dispose_as_restores_name() {
+        let dir = TempDir::new().unwrap();
+        let folder = dir.path().join("finish");
+        let path = dir.path().join("a.mkv.replacing");
+        fs::write(&path, b"source").unwrap();
+        let disposal = dispose_file_as(
+            &path,
+            OsStr::new("a.mkv"),
+            &folder,
+            DeletionMode::MoveToFolder,
+        )
+        .unwrap();
+        assert_eq!(disposal, Disposal::MovedTo(folder.join("a.mkv")));
+        assert!(!path.exists());
+        assert_eq!(fs::read(folder.join("a.mkv")).unwrap(), b"source");
+    }
+
+    #[test]
+    fn test_remove_or_trash_without_trash() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("output.mkv");
+        fs::write(&path, b"partial").unwrap();
+        assert!(!remove_or_trash(&path, DeletionMode::MoveToFolder).unwrap());
+        assert!(!path.exists());
+        assert!(remove_or_trash(&path, DeletionMode::MoveToFolder).is_err());
+    }
+}
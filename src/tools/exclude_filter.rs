@@ -0,0 +1,106 @@
+//! 掃描排除規則
+//!
+//! 以 glob 比對相對於掃描目錄的路徑與檔名，符合的檔案或資料夾在掃描時直接略過。
+//! 例如 `sample-*.mkv` 比對任何層級的檔名，`**/@eaDir/**` 比對整個資料夾。
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use log::warn;
+use std::path::Path;
+
+/// 編譯後的排除規則
+#[derive(Debug, Clone, Default)]
+pub struct ExcludeFilter {
+    set: GlobSet,
+}
+
+impl ExcludeFilter {
+    /// 編譯排除規則，無效的規則記錄警告後略過
+    #[must_use]
+    pub fn new(patterns: &[String]) -> Self {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            match Glob::new(pattern) {
+                Ok(glob) => {
+                    builder.add(glob);
+                }
+                Err(e) => warn!("略過無效的排除規則 {pattern}: {e}"),
+            }
+        }
+        let set = builder.build().unwrap_or_else(|e| {
+            warn!("無法編譯排除規則: {e}");
+            GlobSet::empty()
+        });
+        Self { set }
+    }
+
+    /// 是否沒有任何排除規則
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    /// 路徑是否被排除（`root` 為掃描目錄，規則以相對路徑比對）
+    #[must_use]
+    pub fn is_excluded(&self, path: &Path, root: &Path) -> bool {
+        if self.set.is_empty() {
+            return false;
+        }
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        if relative.as_os_str().is_empty() {
+            return false;
+        }
+        self.set.is_match(relative) || path.file_name().is_some_and(|name| self.set.is_match(name))
+    }
+}
+
+/// 檢查排除規則格式
+pub fn validate_glob(pattern: &str) -> Result<(), String> {
+    if pattern.trim().is_empty() {
+        return Err("規則不可為空".to_string());
+    }
+    Glob::new(pattern).map(|_| ()).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(patterns: &[&str]) -> ExcludeFilter {
+        ExcludeFilter::new(&patterns.iter().map(ToString::to_string).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_file_name_patterns() {
+        let root = Path::new("/data");
+        let filter = filter(&["sample-*.mkv", "*.partial~"]);
+
+        assert!(filter.is_excluded(Path::new("/data/sample-1.mkv"), root));
+        assert!(filter.is_excluded(Path::new("/data/a/b/sample-2.mkv"), root));
+        assert!(filter.is_excluded(Path::new("/data/movie.mp4.partial~"), root));
+        assert!(!filter.is_excluded(Path::new("/data/movie.mkv"), root));
+        assert!(!filter.is_excluded(Path::new("/data/sample.mkv"), root));
+    }
+
+    #[test]
+    fn test_directory_patterns() {
+        let root = Path::new("/data");
+        let filter = filter(&["**/@eaDir/**", "tmp"]);
+
+        assert!(filter.is_excluded(Path::new("/data/@eaDir/thumb.jpg"), root));
+        assert!(filter.is_excluded(Path::new("/data/a/@eaDir/b/thumb.jpg"), root));
+        assert!(filter.is_excluded(Path::new("/data/a/tmp"), root));
+        assert!(!filter.is_excluded(Path::new("/data/a/eaDir/thumb.jpg"), root));
+        // 掃描目錄本身不受規則影響
+        assert!(!filter.is_excluded(Path::new("/data/tmp"), Path::new("/data/tmp")));
+    }
+
+    #[test]
+    fn test_invalid_patterns_are_skipped() {
+        let filter = filter(&["[", "*.tmp"]);
+        assert!(filter.is_excluded(Path::new("/data/a.tmp"), Path::new("/data")));
+        assert!(ExcludeFilter::default().is_empty());
+        assert!(validate_glob("[").is_err());
+        assert!(validate_glob(" ").is_err());
+        assert!(validate_glob("**/@eaDir/**").is_ok());
+    }
+}
@@ -0,0 +1,178 @@
+//! ffmpeg 功能預檢
+//!
+//! 部分 ffmpeg 版本缺少 `scdet`、`xstack` 或 `libx265`，
+//! 若等到處理途中才失敗，只會得到難以理解的 stderr。
+//! 因此在開始前執行一次 `ffmpeg -filters` 與 `ffmpeg -encoders` 確認可用功能。
+
+use super::binaries::resolve_ffmpeg;
+use anyhow::{Context, Result, bail};
+use log::debug;
+use std::collections::HashSet;
+use std::process::Command;
+use std::sync::{PoisonError, RwLock};
+
+/// ffmpeg 可用的濾鏡與編碼器
+#[derive(Debug, Clone, Default)]
+pub struct FfmpegCapabilities {
+    filters: HashSet<String>,
+    encoders: HashSet<String>,
+}
+
+impl FfmpegCapabilities {
+    #[must_use]
+    pub fn has_filter(&self, name: &str) -> bool {
+        self.filters.contains(name)
+    }
+
+    #[must_use]
+    pub fn has_encoder(&self, name: &str) -> bool {
+        self.encoders.contains(name)
+    }
+
+    /// 確認所需的濾鏡與編碼器皆存在，缺少時回傳可操作的錯誤訊息
+    pub fn ensure(&self, filters: &[&str], encoders: &[&str]) -> Result<()> {
+        let missing: Vec<&str> = filters
+            .iter()
+            .filter(|name| !self.has_filter(name))
+            .chain(encoders.iter().filter(|name| !self.has_encoder(name)))
+            .copied()
+            .collect();
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        bail!(
+            "你的 ffmpeg 缺少 {}，請安裝完整版本的 ffmpeg（full build）後再試",
+            missing.join("、")
+        )
+    }
+}
+
+/// ffmpeg 可用功能的快取（更換 ffmpeg 路徑時清除）
+static CAPABILITIES: RwLock<Option<&'static FfmpegCapabilities>> = RwLock::new(None);
+
+/// 清除 ffmpeg 功能快取，下次使用時重新檢查
+pub(super) fn clear_capabilities() {
+    *CAPABILITIES.write().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// 取得 ffmpeg 可用功能（同一個 ffmpeg 只執行一次，結果會快取）
+pub fn probe_capabilities() -> Result<&'static FfmpegCapabilities> {
+    if let Some(capabilities) = *CAPABILITIES.read().unwrap_or_else(PoisonError::into_inner) {
+        return Ok(capabilities);
+    }
+
+    let capabilities = FfmpegCapabilities {
+        filters: parse_capability_list(&run_ffmpeg_list("-filters")?),
+        encoders: parse_capability_list(&run_ffmpeg_list("-encoders")?),
+    };
+    debug!(
+        "ffmpeg 功能: {} 個濾鏡, {} 個編碼器",
+        capabilities.filters.len(),
+        capabilities.encoders.len()
+    );
+
+    let capabilities: &'static FfmpegCapabilities = Box::leak(Box::new(capabilities));
+    *CAPABILITIES.write().unwrap_or_else(PoisonError::into_inner) = Some(capabilities);
+    Ok(capabilities)
+}
+
+fn run_ffmpeg_list(flag: &str) -> Result<String> {
+    let output = Command::new(resolve_ffmpeg()?)
+        .args(["-hide_banner", flag])
+        .output()
+        .with_context(|| format!("無法執行 ffmpeg {flag}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("ffmpeg {flag} 執行失敗: {}", stderr.trim());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// 解析 `ffmpeg -filters` / `ffmpeg -encoders` 的輸出
+///
+/// 每一列格式為「旗標 名稱 說明」，
+/// 說明列（`T.. = Timeline support`）與分隔線會被略過
+fn parse_capability_list(output: &str) -> HashSet<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut tokens = line.split_whitespace();
+            let flags = tokens.next()?;
+            let name = tokens.next()?;
+            tokens.next()?;
+
+            let is_flags = flags
+                .chars()
+                .all(|c| c == '.' || c == '|' || c.is_ascii_uppercase());
+            (is_flags && name != "=").then(|| name.to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILTERS_OUTPUT: &str = "Filters:
+  T.. = Timeline support
+  .S. = Slice threading
+  ..C = Command support
+  A = Audio input/output
+  V = Video input/output
+  N = Dynamic number and/or type of input/output
+  | = Source or sink filter
+ ... abench            A->A       Benchmark part of an audio graph.
+ TSC scale             V->V       Scale the input video size and/or convert the image format.
+ ... scdet             V->V       Detect video scene change
+ ... xstack            N->V       Stack video inputs into custom layout.
+";
+
+    const ENCODERS_OUTPUT: &str = "Encoders:
+ V..... = Video
+ A..... = Audio
+ .F.... = Frame-level multithreading
+ ------
+ V....D libx264              libx264 H.264 / AVC / MPEG-4 AVC (codec h264)
+ A....D flac                 FLAC (Free Lossless Audio Codec)
+";
+
+    #[test]
+    fn test_parse_filters() {
+        let filters = parse_capability_list(FILTERS_OUTPUT);
+        assert_eq!(filters.len(), 4);
+        assert!(filters.contains("scdet"));
+        assert!(filters.contains("xstack"));
+        assert!(!filters.contains("="));
+        assert!(!filters.contains("Filters:"));
+    }
+
+    #[test]
+    fn test_parse_encoders() {
+        let encoders = parse_capability_list(ENCODERS_OUTPUT);
+        assert_eq!(encoders.len(), 2);
+        assert!(encoders.contains("libx264"));
+        assert!(encoders.contains("flac"));
+        assert!(!encoders.contains("------"));
+    }
+
+    #[test]
+    fn test_ensure_reports_missing() {
+        let capabilities = FfmpegCapabilities {
+            filters: parse_capability_list(FILTERS_OUTPUT),
+            encoders: parse_capability_list(ENCODERS_OUTPUT),
+        };
+
+        assert!(capabilities.ensure(&["scdet", "xstack"], &["flac"]).is_ok());
+
+        let err = capabilities
+            .ensure(&["xstack"], &["libx265"])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("libx265"));
+        assert!(!err.contains("xstack"));
+    }
+}
@@ -1,3 +1,4 @@
+use super::binaries::resolve_ffprobe;
 use anyhow::{Context, Result, bail};
 use serde::Deserialize;
 use std::path::Path;
@@ -10,6 +11,30 @@ pub struct VideoInfo {
     pub height: u32,
     #[allow(dead_code)]
     pub frame_rate: f64,
+    /// 視訊編碼名稱（例如 "hevc"、"h264"）
+    pub codec_name: Option<String>,
+    /// 視訊編碼 profile（例如 "Main 10"）
+    pub profile: Option<String>,
+    /// 像素格式（例如 "yuv420p10le"）
+    pub pixel_format: Option<String>,
+    /// 整體位元率（bps，優先取自 format，其次取自視訊串流）
+    pub bit_rate: Option<u64>,
+    /// 第一個音訊串流的編碼名稱（例如 "aac"），沒有音訊時為 `None`
+    pub audio_codec: Option<String>,
+    /// 第一個音訊串流的聲道數
+    pub audio_channels: Option<u32>,
+    /// 各字幕串流的編碼名稱（依串流順序，例如 "subrip"、"mov_text"）
+    pub subtitle_codecs: Vec<String>,
+}
+
+impl VideoInfo {
+    /// 是否已是 HEVC 編碼（不論 profile 與位元深度）
+    #[must_use]
+    pub fn is_hevc(&self) -> bool {
+        self.codec_name
+            .as_deref()
+            .is_some_and(|c| matches!(c.to_lowercase().as_str(), "hevc" | "h265"))
+    }
 }
 
 #[derive(Deserialize)]
@@ -21,20 +46,26 @@ struct FfprobeOutput {
 #[derive(Deserialize)]
 struct FormatInfo {
     duration: Option<String>,
+    bit_rate: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct StreamInfo {
     codec_type: Option<String>,
+    codec_name: Option<String>,
+    profile: Option<String>,
+    pix_fmt: Option<String>,
     width: Option<u32>,
     height: Option<u32>,
     r_frame_rate: Option<String>,
     duration: Option<String>,
+    bit_rate: Option<String>,
+    channels: Option<u32>,
 }
 
 /// 使用 ffprobe 取得影片資訊
 pub fn get_video_info(path: &Path) -> Result<VideoInfo> {
-    let output = Command::new("ffprobe")
+    let output = Command::new(resolve_ffprobe()?)
         .args([
             "-v",
             "quiet",
@@ -53,19 +84,23 @@ pub fn get_video_info(path: &Path) -> Result<VideoInfo> {
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
+    parse_video_info(&stdout).with_context(|| format!("無法取得影片資訊: {}", path.display()))
+}
+
+/// 解析 ffprobe 的 JSON 輸出（`-show_format -show_streams`）
+fn parse_video_info(json: &str) -> Result<VideoInfo> {
     let probe: FfprobeOutput =
-        serde_json::from_str(&stdout).with_context(|| "無法解析 ffprobe 輸出")?;
+        serde_json::from_str(json).with_context(|| "無法解析 ffprobe 輸出")?;
+    let streams = probe.streams.as_deref().unwrap_or_default();
+    let find_stream = |codec_type: &str| {
+        streams
+            .iter()
+            .find(|s| s.codec_type.as_deref() == Some(codec_type))
+    };
 
     // 找到視訊串流
-    let video_stream = probe
-        .streams
-        .as_ref()
-        .and_then(|streams| {
-            streams
-                .iter()
-                .find(|s| s.codec_type.as_deref() == Some("video"))
-        })
-        .ok_or_else(|| anyhow::anyhow!("找不到視訊串流: {}", path.display()))?;
+    let video_stream = find_stream("video").ok_or_else(|| anyhow::anyhow!("找不到視訊串流"))?;
+    let audio_stream = find_stream("audio");
 
     // 取得寬度和高度
     let width = video_stream
@@ -91,14 +126,60 @@ pub fn get_video_info(path: &Path) -> Result<VideoInfo> {
         .and_then(|r| parse_frame_rate(r))
         .unwrap_or(30.0);
 
+    let bit_rate = probe
+        .format
+        .as_ref()
+        .and_then(|f| f.bit_rate.as_ref())
+        .or(video_stream.bit_rate.as_ref())
+        .and_then(|b| b.parse::<u64>().ok());
+
     Ok(VideoInfo {
         duration_seconds,
         width,
         height,
         frame_rate,
+        codec_name: video_stream.codec_name.clone(),
+        profile: video_stream.profile.clone(),
+        pixel_format: video_stream.pix_fmt.clone(),
+        bit_rate,
+        audio_codec: audio_stream.and_then(|s| s.codec_name.clone()),
+        audio_channels: audio_stream.and_then(|s| s.channels),
+        subtitle_codecs: streams
+            .iter()
+            .filter(|s| s.codec_type.as_deref() == Some("subtitle"))
+            .map(|s| s.codec_name.clone().unwrap_or_default())
+            .collect(),
     })
 }
 
+/// 使用 ffprobe 取得媒體的建立時間標籤（`creation_time`），沒有標籤時回傳 `None`
+pub fn get_creation_time(path: &Path) -> Result<Option<String>> {
+    let output = Command::new(resolve_ffprobe()?)
+        .args([
+            "-v",
+            "quiet",
+            "-show_entries",
+            "format_tags=creation_time",
+            "-of",
+            "default=noprint_wrappers=1:nokey=1",
+        ])
+        .arg(path)
+        .output()
+        .with_context(|| format!("無法執行 ffprobe: {}", path.display()))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("ffprobe 執行失敗: {stderr}");
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string))
+}
+
 /// 解析幀率字串（例如 "30/1" 或 "30000/1001"）
 fn parse_frame_rate(rate: &str) -> Option<f64> {
     if let Some((num_str, den_str)) = rate.split_once('/') {
@@ -128,6 +209,73 @@ mod tests {
         assert!((parse_frame_rate("60").unwrap() - 60.0).abs() < 0.01);
     }
 
+    fn video_info_with_codec(codec: &str, profile: &str, pix_fmt: &str) -> VideoInfo {
+        VideoInfo {
+            duration_seconds: 60.0,
+            width: 1920,
+            height: 1080,
+            frame_rate: 30.0,
+            codec_name: Some(codec.to_string()),
+            profile: Some(profile.to_string()),
+            pixel_format: Some(pix_fmt.to_string()),
+            bit_rate: None,
+            audio_codec: None,
+            audio_channels: None,
+            subtitle_codecs: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_is_hevc() {
+        assert!(video_info_with_codec("hevc", "Main 10", "yuv420p10le").is_hevc());
+        assert!(video_info_with_codec("h265", "Main", "yuv420p10le").is_hevc());
+        // 8-bit HEVC 也視為已編碼，避免重複轉檔
+        assert!(video_info_with_codec("hevc", "Main", "yuv420p").is_hevc());
+        assert!(!video_info_with_codec("h264", "High 10", "yuv420p10le").is_hevc());
+    }
+
+    #[test]
+    fn test_parse_video_info() {
+        let json = r#"{
+            "streams": [
+                {"codec_type": "audio", "codec_name": "aac", "channels": 2, "bit_rate": "128000"},
+                {"codec_type": "video", "codec_name": "h264", "profile": "High",
+                 "pix_fmt": "yuv420p", "width": 1920, "height": 1080,
+                 "r_frame_rate": "30000/1001", "bit_rate": "4872000", "duration": "59.9"},
+                {"codec_type": "subtitle", "codec_name": "mov_text"},
+                {"codec_type": "subtitle", "codec_name": "subrip"}
+            ],
+            "format": {"duration": "60.010000", "bit_rate": "5000000"}
+        }"#;
+        let info = parse_video_info(json).unwrap();
+        assert!((info.duration_seconds - 60.01).abs() < 0.001);
+        assert_eq!((info.width, info.height), (1920, 1080));
+        assert!((info.frame_rate - 29.97).abs() < 0.01);
+        assert_eq!(info.codec_name.as_deref(), Some("h264"));
+        assert_eq!(info.bit_rate, Some(5_000_000));
+        assert_eq!(info.audio_codec.as_deref(), Some("aac"));
+        assert_eq!(info.audio_channels, Some(2));
+        assert_eq!(info.subtitle_codecs, ["mov_text", "subrip"]);
+    }
+
+    #[test]
+    fn test_parse_video_info_without_audio() {
+        let json = r#"{
+            "streams": [
+                {"codec_type": "video", "codec_name": "hevc", "width": 640, "height": 360,
+                 "bit_rate": "800000", "duration": "12.5"}
+            ],
+            "format": {}
+        }"#;
+        let info = parse_video_info(json).unwrap();
+        assert!((info.duration_seconds - 12.5).abs() < 0.001);
+        assert_eq!(info.bit_rate, Some(800_000));
+        assert!(info.audio_codec.is_none());
+        assert!(info.audio_channels.is_none());
+
+        assert!(parse_video_info(r#"{"streams": [{"codec_type": "audio"}]}"#).is_err());
+    }
+
     #[test]
     fn test_parse_frame_rate_invalid() {
         assert!(parse_frame_rate("invalid").is_none());
@@ -1,17 +1,44 @@
 use anyhow::{Context, Result};
+use std::fmt;
 use std::fs::File;
-use std::io::{BufReader, Read};
+use std::io::{BufReader, Read, Seek, SeekFrom};
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 const BUFFER_SIZE: usize = 4 * 1024 * 1024; // 4MB buffer
 
+/// 計算 hash 途中收到中斷訊號
+///
+/// 呼叫端以 `error.is::<HashAborted>()` 區分中斷與讀取失敗
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashAborted;
+
+impl fmt::Display for HashAborted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "收到中斷訊號，停止計算 hash")
+    }
+}
+
+impl std::error::Error for HashAborted {}
+
 pub fn calculate_file_hash(path: &Path) -> Result<String> {
+    calculate_file_hash_cancellable(path, &AtomicBool::new(false))
+}
+
+/// 計算整個檔案的 hash，每讀取一個區塊前檢查中斷訊號，收到時回傳 [`HashAborted`]
+pub fn calculate_file_hash_cancellable(
+    path: &Path,
+    shutdown_signal: &AtomicBool,
+) -> Result<String> {
     let file = File::open(path).with_context(|| format!("無法開啟檔案: {}", path.display()))?;
     let mut reader = BufReader::with_capacity(BUFFER_SIZE, file);
     let mut hasher = blake3::Hasher::new();
     let mut buffer = vec![0u8; BUFFER_SIZE];
 
     loop {
+        if shutdown_signal.load(Ordering::SeqCst) {
+            return Err(HashAborted.into());
+        }
         let bytes_read = reader
             .read(&mut buffer)
             .with_context(|| format!("讀取檔案失敗: {}", path.display()))?;
@@ -24,6 +51,33 @@ pub fn calculate_file_hash(path: &Path) -> Result<String> {
     Ok(hasher.finalize().to_hex().to_string())
 }
 
+/// 只計算檔案開頭與結尾各 `bytes` 位元組的 hash，用來快速排除大小相同但內容不同的檔案
+///
+/// 檔案不超過 `2 * bytes` 時改為計算整個檔案，結果與 [`calculate_file_hash`] 相同
+pub fn calculate_partial_hash(path: &Path, bytes: u64) -> Result<String> {
+    let mut file = File::open(path).with_context(|| format!("無法開啟檔案: {}", path.display()))?;
+    let len = file
+        .metadata()
+        .with_context(|| format!("無法讀取檔案資訊: {}", path.display()))?
+        .len();
+    if len <= bytes.saturating_mul(2) {
+        return calculate_file_hash(path);
+    }
+
+    let mut hasher = blake3::Hasher::new();
+    let mut buffer = vec![0u8; usize::try_from(bytes)?];
+    let read_err = || format!("讀取檔案失敗: {}", path.display());
+
+    file.read_exact(&mut buffer).with_context(read_err)?;
+    hasher.update(&buffer);
+    file.seek(SeekFrom::Start(len - bytes))
+        .with_context(read_err)?;
+    file.read_exact(&mut buffer).with_context(read_err)?;
+    hasher.update(&buffer);
+
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -67,4 +121,49 @@ mod tests {
 
         assert_ne!(hash1, hash2);
     }
+
+    #[test]
+    fn test_partial_hash_ignores_middle() {
+        let mut content = vec![1u8; 64];
+        let mut temp_file1 = NamedTempFile::new().unwrap();
+        temp_file1.write_all(&content).unwrap();
+        content[32] = 2;
+        let mut temp_file2 = NamedTempFile::new().unwrap();
+        temp_file2.write_all(&content).unwrap();
+
+        let partial1 = calculate_partial_hash(temp_file1.path(), 16).unwrap();
+        let partial2 = calculate_partial_hash(temp_file2.path(), 16).unwrap();
+        assert_eq!(partial1, partial2);
+        assert_ne!(
+            calculate_file_hash(temp_file1.path()).unwrap(),
+            calculate_file_hash(temp_file2.path()).unwrap()
+        );
+
+        // 檔案不超過兩段長度時等同完整 hash
+        assert_eq!(
+            calculate_partial_hash(temp_file1.path(), 32).unwrap(),
+            calculate_file_hash(temp_file1.path()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_calculate_file_hash_cancellable() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(b"test content").unwrap();
+
+        let hash =
+            calculate_file_hash_cancellable(temp_file.path(), &AtomicBool::new(false)).unwrap();
+        assert_eq!(hash, calculate_file_hash(temp_file.path()).unwrap());
+
+        let err =
+            calculate_file_hash_cancellable(temp_file.path(), &AtomicBool::new(true)).unwrap_err();
+        assert!(err.is::<HashAborted>());
+
+        let missing = calculate_file_hash_cancellable(
+            &temp_file.path().with_extension("missing"),
+            &AtomicBool::new(true),
+        )
+        .unwrap_err();
+        assert!(!missing.is::<HashAborted>());
+    }
 }
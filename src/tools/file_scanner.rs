@@ -1,25 +1,86 @@
-use anyhow::Result;
+use super::{ExcludeFilter, SizeFilter};
+use anyhow::{Context, Result};
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use walkdir::WalkDir;
 
 #[derive(Debug, Clone)]
 pub struct FileInfo {
     pub path: PathBuf,
     pub size: u64,
+    /// 修改時間（檔案系統不支援時為 `None`）
+    pub modified: Option<SystemTime>,
 }
 
 /// 掃描目錄下所有檔案，不過濾檔案類型，按大小排序（由小到大）
-pub fn scan_all_files(directory: &Path) -> Result<Vec<FileInfo>> {
+///
+/// 符合排除規則的檔案與資料夾（連同其內容）會被略過；
+/// 指定大小篩選時，範圍外的檔案會被略過並計入篩選的篩除數量
+pub fn scan_all_files(
+    directory: &Path,
+    exclude: &ExcludeFilter,
+    size_filter: Option<&SizeFilter>,
+) -> Result<Vec<FileInfo>> {
+    scan_all_files_with(directory, exclude, size_filter, |_| false)
+}
+
+/// 同 [`scan_all_files`]，`skip_dir` 判斷為 `true` 的子資料夾不會進入（連同其內容略過）
+pub fn scan_all_files_with(
+    directory: &Path,
+    exclude: &ExcludeFilter,
+    size_filter: Option<&SizeFilter>,
+    skip_dir: impl Fn(&Path) -> bool,
+) -> Result<Vec<FileInfo>> {
     let mut files: Vec<FileInfo> = WalkDir::new(directory)
         .follow_links(false)
         .into_iter()
+        .filter_entry(|entry| {
+            let skipped_dir = entry.file_type().is_dir() && skip_dir(entry.path());
+            !skipped_dir && !exclude.is_excluded(entry.path(), directory)
+        })
         .filter_map(std::result::Result::ok)
         .filter(|entry| entry.file_type().is_file())
         .filter_map(|entry| {
             let metadata = entry.metadata().ok()?;
+            if size_filter.is_some_and(|filter| !filter.accepts(metadata.len())) {
+                return None;
+            }
             Some(FileInfo {
                 path: entry.into_path(),
                 size: metadata.len(),
+                modified: metadata.modified().ok(),
+            })
+        })
+        .collect();
+
+    files.sort_by_key(|file| file.size);
+    Ok(files)
+}
+
+/// 只掃描目錄本身的檔案（不進入子資料夾），按大小排序（由小到大）
+///
+/// 排除規則與大小篩選的處理方式與 [`scan_all_files`] 相同
+pub fn scan_top_level_files(
+    directory: &Path,
+    exclude: &ExcludeFilter,
+    size_filter: Option<&SizeFilter>,
+) -> Result<Vec<FileInfo>> {
+    let entries = fs::read_dir(directory)
+        .with_context(|| format!("無法讀取目錄: {}", directory.display()))?;
+    let mut files: Vec<FileInfo> = entries
+        .filter_map(std::result::Result::ok)
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .filter(|entry| !exclude.is_excluded(&entry.path(), directory))
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            if size_filter.is_some_and(|filter| !filter.accepts(metadata.len())) {
+                return None;
+            }
+            Some(FileInfo {
+                path: entry.path(),
+                size: metadata.len(),
+                modified: metadata.modified().ok(),
             })
         })
         .collect();
@@ -52,7 +113,7 @@ mod tests {
             file2.write_all(b"this is a larger file content").unwrap();
         }
 
-        let files = scan_all_files(temp_dir.path()).unwrap();
+        let files = scan_all_files(temp_dir.path(), &ExcludeFilter::default(), None).unwrap();
 
         assert_eq!(files.len(), 2);
         // 應該按大小排序，小的在前
@@ -62,7 +123,53 @@ mod tests {
     #[test]
     fn test_scan_empty_directory() {
         let temp_dir = TempDir::new().unwrap();
-        let files = scan_all_files(temp_dir.path()).unwrap();
+        let files = scan_all_files(temp_dir.path(), &ExcludeFilter::default(), None).unwrap();
         assert!(files.is_empty());
     }
+
+    #[test]
+    fn test_scan_all_files_with_exclude() {
+        let temp_dir = TempDir::new().unwrap();
+        let nested = temp_dir.path().join("a").join("@eaDir");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(temp_dir.path().join("movie.mkv"), b"movie").unwrap();
+        std::fs::write(temp_dir.path().join("a").join("sample-1.mkv"), b"sample").unwrap();
+        std::fs::write(nested.join("thumb.jpg"), b"thumb").unwrap();
+
+        let exclude = ExcludeFilter::new(&["sample-*.mkv".to_string(), "**/@eaDir/**".to_string()]);
+        let files = scan_all_files(temp_dir.path(), &exclude, None).unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, temp_dir.path().join("movie.mkv"));
+    }
+
+    #[test]
+    fn test_scan_top_level_files() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join("sub")).unwrap();
+        std::fs::write(temp_dir.path().join("movie.mkv"), b"movie").unwrap();
+        std::fs::write(temp_dir.path().join("skip.tmp"), b"tmp").unwrap();
+        std::fs::write(temp_dir.path().join("sub").join("nested.mkv"), b"nested").unwrap();
+
+        let exclude = ExcludeFilter::new(&["*.tmp".to_string()]);
+        let files = scan_top_level_files(temp_dir.path(), &exclude, None).unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, temp_dir.path().join("movie.mkv"));
+    }
+
+    #[test]
+    fn test_scan_all_files_with_size_filter() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("stub.mkv"), [0u8; 10]).unwrap();
+        std::fs::write(temp_dir.path().join("movie.mkv"), [0u8; 2048]).unwrap();
+
+        let filter = SizeFilter::new(Some(1024), None);
+        let files =
+            scan_all_files(temp_dir.path(), &ExcludeFilter::default(), Some(&filter)).unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].size, 2048);
+        assert_eq!(filter.rejected(), 1);
+    }
 }
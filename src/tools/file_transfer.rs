@@ -0,0 +1,153 @@
+//! 檔案移動與複製
+//!
+//! `fs::rename` 無法跨檔案系統，移動失敗時改為複製後刪除原檔。
+
+use anyhow::{Context, Result};
+use log::debug;
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 檔案傳送方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TransferMode {
+    /// 移動（來源不再保留）
+    #[default]
+    Move,
+    /// 複製（保留來源）
+    Copy,
+}
+
+impl fmt::Display for TransferMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Move => write!(f, "移動"),
+            Self::Copy => write!(f, "複製"),
+        }
+    }
+}
+
+/// 依傳送方式移動或複製檔案
+pub fn transfer_file(source: &Path, target: &Path, mode: TransferMode) -> Result<()> {
+    match mode {
+        TransferMode::Move => move_file(source, target),
+        TransferMode::Copy => copy_file(source, target),
+    }
+}
+
+/// 移動檔案，`rename` 失敗時（例如跨檔案系統）改為複製後刪除原檔
+pub fn move_file(source: &Path, target: &Path) -> Result<()> {
+    let Err(rename_err) = fs::rename(source, target) else {
+        return Ok(());
+    };
+    debug!(
+        "無法直接移動 {}（{rename_err}），改為複製後刪除",
+        source.display()
+    );
+
+    copy_file(source, target).with_context(|| format!("無法移動檔案（原始錯誤: {rename_err}）"))?;
+    fs::remove_file(source).with_context(|| format!("刪除原檔案失敗: {}", source.display()))
+}
+
+/// 複製檔案，失敗時刪除不完整的目標檔
+pub fn copy_file(source: &Path, target: &Path) -> Result<()> {
+    if let Err(e) = fs::copy(source, target) {
+        let _ = fs::remove_file(target);
+        return Err(e).with_context(|| {
+            format!("複製檔案失敗: {} -> {}", source.display(), target.display())
+        });
+    }
+    Ok(())
+}
+
+/// 在檔名主幹後加上編號（`name_1.ext`）
+#[must_use]
+pub fn numbered_path(path: &Path, counter: u32) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map_or_else(|| "file".into(), |s| s.to_string_lossy());
+    let name = match path.extension() {
+        Some(ext) => format!("{stem}_{counter}.{}", ext.to_string_lossy()),
+        None => format!("{stem}_{counter}"),
+    };
+    path.with_file_name(name)
+}
+
+/// 目標資料夾中不會覆蓋既有檔案的路徑，同名時依序加上編號（`name_1.ext`、`name_2.ext`）
+#[must_use]
+pub fn unique_destination(dir: &Path, file_name: &OsStr) -> PathBuf {
+    unique_destination_with(dir, file_name, |_| false)
+}
+
+/// 同 [`unique_destination`]，`taken` 另外判斷路徑是否已被使用（例如平行處理時已分配的路徑）
+pub fn unique_destination_with(
+    dir: &Path,
+    file_name: &OsStr,
+    taken: impl Fn(&Path) -> bool,
+) -> PathBuf {
+    let target = dir.join(file_name);
+    let mut candidate = target.clone();
+    let mut counter = 1;
+    while taken(&candidate) || candidate.exists() {
+        candidate = numbered_path(&target, counter);
+        counter += 1;
+    }
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_transfer_file_modes() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("a.txt");
+        fs::write(&source, b"content").unwrap();
+
+        let copied = dir.path().join("copied.txt");
+        transfer_file(&source, &copied, TransferMode::Copy).unwrap();
+        assert!(source.exists());
+        assert_eq!(fs::read(&copied).unwrap(), b"content");
+
+        let moved = dir.path().join("moved.txt");
+        transfer_file(&source, &moved, TransferMode::Move).unwrap();
+        assert!(!source.exists());
+        assert_eq!(fs::read(&moved).unwrap(), b"content");
+
+        assert!(copy_file(&source, &dir.path().join("missing.txt")).is_err());
+        assert!(!dir.path().join("missing.txt").exists());
+    }
+
+    #[test]
+    fn test_unique_destination() {
+        let dir = TempDir::new().unwrap();
+        let name = OsStr::new("movie.mp4");
+        assert_eq!(unique_destination(dir.path(), name), dir.path().join(name));
+
+        fs::write(dir.path().join("movie.mp4"), b"a").unwrap();
+        fs::write(dir.path().join("movie_1.mp4"), b"b").unwrap();
+        assert_eq!(
+            unique_destination(dir.path(), name),
+            dir.path().join("movie_2.mp4")
+        );
+        assert_eq!(
+            unique_destination_with(dir.path(), name, |p| p.ends_with("movie_2.mp4")),
+            dir.path().join("movie_3.mp4")
+        );
+    }
+
+    #[test]
+    fn test_numbered_path() {
+        assert_eq!(
+            numbered_path(Path::new("/data/movie.mp4"), 2),
+            PathBuf::from("/data/movie_2.mp4")
+        );
+        assert_eq!(
+            numbered_path(Path::new("/data/README"), 1),
+            PathBuf::from("/data/README_1")
+        );
+    }
+}
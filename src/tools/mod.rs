@@ -2,14 +2,43 @@
 //!
 //! 這些工具被多個 component 使用
 
+mod binaries;
+mod csv;
+mod dependency_check;
+mod disk_space;
+mod disposal;
+mod exclude_filter;
+mod ffmpeg_caps;
 mod ffprobe_info;
 mod file_hasher;
 mod file_scanner;
+mod file_transfer;
 mod path_validator;
+mod perceptual_hash;
+mod size_filter;
 mod video_scanner;
 
-pub use ffprobe_info::{VideoInfo, get_video_info};
-pub use file_hasher::calculate_file_hash;
-pub use file_scanner::{FileInfo, scan_all_files};
-pub use path_validator::{ensure_directory_exists, validate_directory_exists};
+pub use binaries::{configure_ffmpeg_path, resolve_ffmpeg, resolve_ffprobe};
+pub use csv::{csv_field, parse_csv_line};
+pub use dependency_check::{
+    BinaryVersion, DependencyReport, check_dependencies, recheck_dependencies,
+};
+pub use disk_space::{SpaceCheck, available_space, check_space};
+pub use disposal::{Disposal, dispose_file, dispose_file_as, remove_or_trash};
+pub use exclude_filter::{ExcludeFilter, validate_glob};
+pub use ffmpeg_caps::{FfmpegCapabilities, probe_capabilities};
+pub use ffprobe_info::{VideoInfo, get_creation_time, get_video_info};
+pub use file_hasher::{
+    HashAborted, calculate_file_hash, calculate_file_hash_cancellable, calculate_partial_hash,
+};
+pub use file_scanner::{FileInfo, scan_all_files, scan_all_files_with, scan_top_level_files};
+pub use file_transfer::{
+    TransferMode, copy_file, move_file, numbered_path, transfer_file, unique_destination,
+    unique_destination_with,
+};
+pub use path_validator::{
+    ensure_directory_exists, expand_home, expand_path, validate_directory_exists,
+};
+pub use perceptual_hash::{VideoSignature, dhash, hamming_distance, image_dhash};
+pub use size_filter::{SizeFilter, format_size, parse_size};
 pub use video_scanner::{VideoFileInfo, scan_video_files};
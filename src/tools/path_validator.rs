@@ -1,5 +1,5 @@
 use anyhow::{Result, bail};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub fn validate_directory_exists(path: &Path) -> Result<()> {
     if !path.exists() {
@@ -17,3 +17,94 @@ pub fn ensure_directory_exists(path: &Path) -> Result<()> {
     }
     Ok(())
 }
+
+/// 將開頭的 `~` 展開為使用者的家目錄（取不到家目錄時保持原樣）
+#[must_use]
+pub fn expand_home(path: &str) -> PathBuf {
+    let rest = match path.strip_prefix('~') {
+        Some("") => "",
+        Some(rest) if rest.starts_with(['/', '\\']) => &rest[1..],
+        _ => return PathBuf::from(path),
+    };
+    std::env::home_dir().map_or_else(|| PathBuf::from(path), |home| home.join(rest))
+}
+
+/// 展開使用者輸入的路徑：`$VAR`、`${VAR}` 環境變數與開頭的 `~`
+///
+/// 未設定的環境變數保持原樣，方便錯誤訊息顯示使用者實際輸入的內容
+#[must_use]
+pub fn expand_path(path: &str) -> PathBuf {
+    expand_home(&expand_env_vars(path))
+}
+
+fn expand_env_vars(path: &str) -> String {
+    let mut result = String::with_capacity(path.len());
+    let mut rest = path;
+    while let Some(start) = rest.find('$') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, consumed) = if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            }
+        } else {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            (&after[..end], end)
+        };
+        match std::env::var(name) {
+            Ok(value) if !name.is_empty() => result.push_str(&value),
+            _ => result.push_str(&rest[start..=start + consumed]),
+        }
+        rest = &after[consumed..];
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_home() {
+        let home = std::env::home_dir().unwrap();
+        assert_eq!(expand_home("~"), home);
+        assert_eq!(expand_home("~/tables/a.json"), home.join("tables/a.json"));
+        assert_eq!(expand_home("~user/a.json"), PathBuf::from("~user/a.json"));
+        assert_eq!(expand_home("/data/a.json"), PathBuf::from("/data/a.json"));
+    }
+
+    #[test]
+    fn test_expand_path_tilde() {
+        let home = std::env::home_dir().unwrap();
+        assert_eq!(expand_path("~/videos"), home.join("videos"));
+        assert_eq!(expand_path("~"), home);
+        assert_eq!(expand_path("/data/~/a"), PathBuf::from("/data/~/a"));
+    }
+
+    #[test]
+    fn test_expand_path_env_vars() {
+        let home = std::env::var("HOME").unwrap();
+        assert_eq!(
+            expand_path("$HOME/videos"),
+            PathBuf::from(format!("{home}/videos"))
+        );
+        assert_eq!(
+            expand_path("${HOME}_backup/a"),
+            PathBuf::from(format!("{home}_backup/a"))
+        );
+        assert_eq!(
+            expand_path("$AVO_SURELY_UNSET_VAR/a"),
+            PathBuf::from("$AVO_SURELY_UNSET_VAR/a")
+        );
+        assert_eq!(
+            expand_path("${AVO_SURELY_UNSET_VAR}/a"),
+            PathBuf::from("${AVO_SURELY_UNSET_VAR}/a")
+        );
+        assert_eq!(expand_path("/data/$/a"), PathBuf::from("/data/$/a"));
+        assert_eq!(expand_path("/data/${HOME"), PathBuf::from("/data/${HOME"));
+    }
+}
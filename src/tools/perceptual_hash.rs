@@ -0,0 +1,167 @@
+//! 感知 hash（dHash）
+//!
+//! 以畫面亮度的相對變化產生 64 bits 指紋，重新編碼、縮放或調整畫質後的畫面
+//! 指紋仍相近，以 Hamming 距離衡量兩張畫面的差異
+
+use super::binaries::resolve_ffmpeg;
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+
+/// dHash 取樣寬度：每列比較相鄰的 9 個像素得到 8 bits
+const DHASH_WIDTH: usize = 9;
+/// dHash 取樣高度
+const DHASH_HEIGHT: usize = 8;
+
+/// 時長差距在此秒數內視為同一部影片
+const DURATION_TOLERANCE_SECONDS: f64 = 1.0;
+/// 時長差距在此比例內視為同一部影片（較長的影片以比例為準）
+const DURATION_TOLERANCE_RATIO: f64 = 0.01;
+
+/// 由 9x8 灰階像素（逐列排列）計算 dHash
+///
+/// 每個 bit 代表同一列中左側像素是否比右側亮
+#[must_use]
+pub fn dhash(pixels: &[u8; DHASH_WIDTH * DHASH_HEIGHT]) -> u64 {
+    pixels
+        .chunks_exact(DHASH_WIDTH)
+        .flat_map(|row| row.windows(2).map(|pair| pair[0] > pair[1]))
+        .fold(0, |hash, brighter| (hash << 1) | u64::from(brighter))
+}
+
+/// 兩個 hash 不同的 bit 數（0–64）
+#[must_use]
+pub const fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// 以 ffmpeg 將圖片縮成 9x8 灰階後計算 dHash
+pub fn image_dhash(image: &Path) -> Result<u64> {
+    let output = Command::new(resolve_ffmpeg()?)
+        .args(["-hide_banner", "-loglevel", "error", "-i"])
+        .arg(image)
+        .args([
+            "-vf",
+            &format!("scale={DHASH_WIDTH}:{DHASH_HEIGHT}:flags=area,format=gray"),
+            "-frames:v",
+            "1",
+            "-f",
+            "rawvideo",
+            "-",
+        ])
+        .output()
+        .with_context(|| format!("無法執行 ffmpeg 讀取圖片: {}", image.display()))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("ffmpeg 讀取圖片失敗: {}", stderr.trim());
+    }
+
+    let pixels: &[u8; DHASH_WIDTH * DHASH_HEIGHT] = output
+        .stdout
+        .as_slice()
+        .try_into()
+        .with_context(|| format!("圖片像素數量不符: {} bytes", output.stdout.len()))?;
+    Ok(dhash(pixels))
+}
+
+/// 影片指紋：時長與均勻取樣影格的 dHash
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VideoSignature {
+    pub duration_seconds: f64,
+    pub frames: Vec<u64>,
+}
+
+impl VideoSignature {
+    /// 平均每個影格的 Hamming 距離
+    ///
+    /// 影格數不同或時長差距過大時無法比較，回傳 `None`
+    #[must_use]
+    pub fn distance(&self, other: &Self) -> Option<u32> {
+        if self.frames.is_empty() || self.frames.len() != other.frames.len() {
+            return None;
+        }
+        let longest = self.duration_seconds.max(other.duration_seconds);
+        let tolerance = DURATION_TOLERANCE_SECONDS.max(longest * DURATION_TOLERANCE_RATIO);
+        if (self.duration_seconds - other.duration_seconds).abs() > tolerance {
+            return None;
+        }
+
+        let total: u32 = self
+            .frames
+            .iter()
+            .zip(&other.frames)
+            .map(|(&a, &b)| hamming_distance(a, b))
+            .sum();
+        Some(total.div_ceil(self.frames.len() as u32))
+    }
+
+    /// 平均距離不超過 `threshold` 時視為同一部影片
+    #[must_use]
+    pub fn is_similar(&self, other: &Self, threshold: u32) -> bool {
+        self.distance(other)
+            .is_some_and(|distance| distance <= threshold)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient(reverse: bool) -> [u8; 72] {
+        let mut pixels = [0u8; 72];
+        for (i, pixel) in pixels.iter_mut().enumerate() {
+            let x = (i % DHASH_WIDTH) as u8;
+            *pixel = if reverse { x * 20 } else { 200 - x * 20 };
+        }
+        pixels
+    }
+
+    #[test]
+    fn test_dhash() {
+        // 每列由亮到暗：所有 bit 皆為 1
+        assert_eq!(dhash(&gradient(false)), u64::MAX);
+        assert_eq!(dhash(&gradient(true)), 0);
+        assert_eq!(dhash(&[128; 72]), 0);
+
+        // 亮度整體偏移不影響指紋
+        let brighter = gradient(false).map(|p| p.saturating_add(30));
+        assert_eq!(dhash(&brighter), dhash(&gradient(false)));
+    }
+
+    #[test]
+    fn test_hamming_distance() {
+        assert_eq!(hamming_distance(0, 0), 0);
+        assert_eq!(hamming_distance(0b1011, 0b0001), 2);
+        assert_eq!(hamming_distance(0, u64::MAX), 64);
+    }
+
+    #[test]
+    fn test_signature_distance() {
+        let signature = VideoSignature {
+            duration_seconds: 600.0,
+            frames: vec![0, u64::MAX, 0xFF00],
+        };
+        let reencoded = VideoSignature {
+            duration_seconds: 600.4,
+            frames: vec![0b111, u64::MAX, 0xFF01],
+        };
+        // (3 + 0 + 1) / 3 無條件進位
+        assert_eq!(signature.distance(&reencoded), Some(2));
+        assert!(signature.is_similar(&reencoded, 2));
+        assert!(!signature.is_similar(&reencoded, 1));
+
+        let longer = VideoSignature {
+            duration_seconds: 700.0,
+            ..signature.clone()
+        };
+        assert_eq!(signature.distance(&longer), None);
+
+        let fewer_frames = VideoSignature {
+            frames: vec![0],
+            ..signature.clone()
+        };
+        assert_eq!(signature.distance(&fewer_frames), None);
+    }
+}
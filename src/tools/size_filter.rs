@@ -0,0 +1,196 @@
+//! 掃描檔案大小篩選
+//!
+//! 大小以人類可讀的字串設定（例如 `500K`、`10MB`、`1.5GB`），單位以 1024 進位，
+//! 與介面顯示的 MB/GB 一致。
+
+use anyhow::{Context, Result, bail};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const KIB: f64 = 1024.0;
+
+/// 解析人類可讀的檔案大小（不區分大小寫，`K`/`KB`/`KiB` 視為相同）
+pub fn parse_size(input: &str) -> Result<u64> {
+    let trimmed = input.trim();
+    let split = trimmed
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    let value: f64 = number
+        .parse()
+        .with_context(|| format!("無法解析檔案大小: {input}"))?;
+
+    let multiplier = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1.0,
+        "K" | "KB" | "KIB" => KIB,
+        "M" | "MB" | "MIB" => KIB.powi(2),
+        "G" | "GB" | "GIB" => KIB.powi(3),
+        "T" | "TB" | "TIB" => KIB.powi(4),
+        other => bail!("無法辨識的大小單位: {other}（可使用 B、K、M、G、T）"),
+    };
+
+    let bytes = (value * multiplier).round();
+    if !bytes.is_finite() || bytes > u64::MAX as f64 {
+        bail!("檔案大小超出範圍: {input}");
+    }
+    Ok(bytes as u64)
+}
+
+/// 以最合適的單位顯示檔案大小
+#[must_use]
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= KIB && unit < UNITS.len() - 1 {
+        value /= KIB;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// 檔案大小範圍，並記錄被篩除的檔案數
+#[derive(Debug, Default)]
+pub struct SizeFilter {
+    min: Option<u64>,
+    max: Option<u64>,
+    rejected: AtomicUsize,
+}
+
+impl SizeFilter {
+    #[must_use]
+    pub const fn new(min: Option<u64>, max: Option<u64>) -> Self {
+        Self {
+            min,
+            max,
+            rejected: AtomicUsize::new(0),
+        }
+    }
+
+    /// 相同範圍、篩除數量從 0 開始的篩選，用來分開統計不同的掃描
+    #[must_use]
+    pub const fn same_range(&self) -> Self {
+        Self::new(self.min, self.max)
+    }
+
+    /// 由設定字串建立（空字串視為不限制）
+    pub fn parse(min: Option<&str>, max: Option<&str>) -> Result<Self> {
+        let parse = |value: Option<&str>| {
+            value
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(parse_size)
+                .transpose()
+        };
+        let (min, max) = (parse(min)?, parse(max)?);
+        if let (Some(min), Some(max)) = (min, max)
+            && min > max
+        {
+            bail!(
+                "檔案大小下限 {} 大於上限 {}",
+                format_size(min),
+                format_size(max)
+            );
+        }
+        Ok(Self::new(min, max))
+    }
+
+    #[must_use]
+    pub const fn min(&self) -> Option<u64> {
+        self.min
+    }
+
+    #[must_use]
+    pub const fn max(&self) -> Option<u64> {
+        self.max
+    }
+
+    /// 是否沒有任何限制
+    #[must_use]
+    pub const fn is_unbounded(&self) -> bool {
+        self.min.is_none() && self.max.is_none()
+    }
+
+    /// 檔案大小是否在範圍內，不在範圍內時計入篩除數量
+    pub fn accepts(&self, size: u64) -> bool {
+        let accepted =
+            self.min.is_none_or(|min| size >= min) && self.max.is_none_or(|max| size <= max);
+        if !accepted {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+        }
+        accepted
+    }
+
+    /// 被篩除的檔案數
+    #[must_use]
+    pub fn rejected(&self) -> usize {
+        self.rejected.load(Ordering::Relaxed)
+    }
+
+    /// 篩選範圍說明（例如 `10.0 MB 以上、2.0 GB 以下`）
+    #[must_use]
+    pub fn describe(&self) -> String {
+        match (self.min, self.max) {
+            (None, None) => "不限制".to_string(),
+            (Some(min), None) => format!("{} 以上", format_size(min)),
+            (None, Some(max)) => format!("{} 以下", format_size(max)),
+            (Some(min), Some(max)) => {
+                format!("{} 以上、{} 以下", format_size(min), format_size(max))
+            }
+        }
+    }
+}
+
+impl Clone for SizeFilter {
+    /// 複製範圍設定，篩除數量重新計算
+    fn clone(&self) -> Self {
+        Self::new(self.min, self.max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024").unwrap(), 1024);
+        assert_eq!(parse_size("500K").unwrap(), 500 * 1024);
+        assert_eq!(parse_size("10MB").unwrap(), 10 * 1024 * 1024);
+        assert_eq!(parse_size("1.5GB").unwrap(), 1536 * 1024 * 1024);
+        assert_eq!(parse_size(" 2 kib ").unwrap(), 2048);
+        assert_eq!(parse_size("1t").unwrap(), 1024u64.pow(4));
+        assert!(parse_size("").is_err());
+        assert!(parse_size("MB").is_err());
+        assert!(parse_size("10XB").is_err());
+        assert!(parse_size("-1K").is_err());
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(10 * 1024 * 1024), "10.0 MB");
+    }
+
+    #[test]
+    fn test_size_filter() {
+        let filter = SizeFilter::parse(Some("2K"), Some("1MB")).unwrap();
+        assert!(!filter.accepts(100));
+        assert!(filter.accepts(2048));
+        assert!(filter.accepts(1024 * 1024));
+        assert!(!filter.accepts(1024 * 1024 + 1));
+        assert_eq!(filter.rejected(), 2);
+        assert_eq!(filter.clone().rejected(), 0);
+        assert_eq!(filter.describe(), "2.0 KB 以上、1.0 MB 以下");
+
+        let unbounded = SizeFilter::parse(Some(" "), None).unwrap();
+        assert!(unbounded.is_unbounded());
+        assert!(unbounded.accepts(0));
+
+        assert!(SizeFilter::parse(Some("2MB"), Some("1MB")).is_err());
+    }
+}
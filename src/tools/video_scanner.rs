@@ -1,7 +1,10 @@
 use crate::config::FileTypeTable;
-use crate::tools::get_video_info;
+use crate::tools::{ExcludeFilter, SizeFilter, get_video_info};
 use anyhow::Result;
+use log::info;
+use rayon::prelude::*;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use walkdir::WalkDir;
 
 #[derive(Debug, Clone)]
@@ -11,30 +14,58 @@ pub struct VideoFileInfo {
     pub duration_ms: Option<u64>,
 }
 
+/// 掃描目錄下所有影片檔案，按大小排序（由小到大）
+///
+/// 符合排除規則的檔案與資料夾（連同其內容）會被略過；
+/// 指定大小篩選時，範圍外的影片在讀取時長前就會被略過並計入篩選的篩除數量。
+/// `probe_duration` 為 `true` 時以 ffprobe 平行讀取時長，否則 `duration_ms` 皆為 `None`；
+/// 收到中斷訊號後不再讀取，尚未讀取的影片不列入結果
 pub fn scan_video_files(
     directory: &Path,
     file_type_table: &FileTypeTable,
+    exclude: &ExcludeFilter,
+    size_filter: Option<&SizeFilter>,
+    probe_duration: bool,
+    shutdown_signal: &AtomicBool,
 ) -> Result<Vec<VideoFileInfo>> {
-    let mut video_files: Vec<VideoFileInfo> = WalkDir::new(directory)
+    let candidates: Vec<(PathBuf, u64)> = WalkDir::new(directory)
         .follow_links(false)
         .into_iter()
+        .filter_entry(|entry| !exclude.is_excluded(entry.path(), directory))
         .filter_map(std::result::Result::ok)
         .filter(|entry| entry.file_type().is_file())
         .filter(|entry| file_type_table.is_video_file(entry.path()))
         .filter_map(|entry| {
-            let metadata = entry.metadata().ok()?;
-            let duration_ms = get_video_info(entry.path())
-                .ok()
-                .map(|info| (info.duration_seconds * 1000.0).round() as u64);
+            let size = entry.metadata().ok()?.len();
+            if size_filter.is_some_and(|filter| !filter.accepts(size)) {
+                return None;
+            }
+            Some((entry.into_path(), size))
+        })
+        .collect();
 
+    let mut video_files: Vec<VideoFileInfo> = candidates
+        .into_par_iter()
+        .filter_map(|(path, size)| {
+            if shutdown_signal.load(Ordering::SeqCst) {
+                return None;
+            }
+            let duration_ms = probe_duration
+                .then(|| get_video_info(&path).ok())
+                .flatten()
+                .map(|info| (info.duration_seconds * 1000.0).round() as u64);
             Some(VideoFileInfo {
-                path: entry.into_path(),
-                size: metadata.len(),
+                path,
+                size,
                 duration_ms,
             })
         })
         .collect();
 
+    if shutdown_signal.load(Ordering::SeqCst) {
+        info!("收到中斷訊號，停止掃描影片");
+    }
+
     video_files.sort_by_key(|file| file.size);
     Ok(video_files)
 }
@@ -42,6 +73,46 @@ pub fn scan_video_files(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::Config;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn scan(directory: &Path, shutdown_signal: &AtomicBool) -> Vec<VideoFileInfo> {
+        let config = Config::new().unwrap();
+        scan_video_files(
+            directory,
+            &config.file_type_table,
+            &ExcludeFilter::default(),
+            None,
+            false,
+            shutdown_signal,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_scan_sorts_by_size() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("large.mp4"), vec![0u8; 300]).unwrap();
+        fs::write(temp_dir.path().join("small.mkv"), vec![0u8; 100]).unwrap();
+        fs::write(temp_dir.path().join("notes.txt"), vec![0u8; 50]).unwrap();
+
+        let files = scan(temp_dir.path(), &AtomicBool::new(false));
+        let names: Vec<_> = files
+            .iter()
+            .map(|f| f.path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["small.mkv", "large.mp4"]);
+        assert!(files.iter().all(|f| f.duration_ms.is_none()));
+    }
+
+    #[test]
+    fn test_scan_stops_on_shutdown() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.mp4"), vec![0u8; 100]).unwrap();
+
+        assert!(scan(temp_dir.path(), &AtomicBool::new(true)).is_empty());
+    }
 
     #[test]
     fn test_video_file_info_sorting() {
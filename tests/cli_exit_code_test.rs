@@ -0,0 +1,178 @@
+//! 子命令結束代碼測試
+//!
+//! 以暫存資料夾作為工作目錄、設定與日誌資料夾執行程式，避免讀寫使用者的設定檔與日誌
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use tempfile::TempDir;
+
+fn run(cwd: &Path, args: &[&str]) -> i32 {
+    let status = Command::new(env!("CARGO_BIN_EXE_auto_video_organize"))
+        .args(args)
+        .current_dir(cwd)
+        .env("XDG_DATA_HOME", cwd)
+        .env("XDG_CONFIG_HOME", cwd)
+        .output()
+        .expect("無法執行程式")
+        .status;
+    status.code().expect("程式被訊號終止")
+}
+
+#[test]
+fn test_exit_code_completed() {
+    let cwd = TempDir::new().unwrap();
+    let input = cwd.path().join("videos");
+    fs::create_dir(&input).unwrap();
+    fs::write(input.join("note.txt"), b"hello").unwrap();
+
+    let code = run(
+        cwd.path(),
+        &["orphan", "-i", input.to_str().unwrap(), "-y", "--json"],
+    );
+    assert_eq!(code, 0);
+
+    let log = fs::read_to_string(cwd.path().join("auto_video_organize/logs/app.log")).unwrap();
+    assert!(log.contains("啟動"));
+}
+
+#[test]
+fn test_exit_code_preflight_failed() {
+    let cwd = TempDir::new().unwrap();
+    let missing = cwd.path().join("missing");
+
+    let code = run(
+        cwd.path(),
+        &["orphan", "-i", missing.to_str().unwrap(), "-y", "--json"],
+    );
+    assert_eq!(code, 4);
+
+    let code = run(cwd.path(), &["run-jobs", "missing.toml"]);
+    assert_eq!(code, 4);
+}
+
+/// 來源資料夾設為唯讀時無法移走其中的檔案，整理完成但有檔案失敗
+#[cfg(unix)]
+#[test]
+fn test_exit_code_completed_with_failures() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let cwd = TempDir::new().unwrap();
+    let input = cwd.path().join("videos");
+    let locked = input.join("locked");
+    fs::create_dir_all(&locked).unwrap();
+    fs::write(locked.join("note.txt"), b"hello").unwrap();
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o555)).unwrap();
+
+    // 以 root 執行時仍可寫入唯讀資料夾，無法造成失敗
+    let probe = locked.join("probe");
+    if fs::write(&probe, b"").is_ok() {
+        fs::remove_file(&probe).unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        eprintln!("略過：目前的使用者可寫入唯讀資料夾");
+        return;
+    }
+
+    let code = run(
+        cwd.path(),
+        &["auto-move", "-i", input.to_str().unwrap(), "-y", "--json"],
+    );
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+    assert_eq!(code, 2);
+    assert!(locked.join("note.txt").exists());
+}
+
+/// 處理中收到 SIGINT 時停止並回傳中斷的結束代碼
+///
+/// 以每次讀取都要等待一秒的假 ffprobe 依時長排序少量影片，送出訊號時必定還在處理中
+#[cfg(unix)]
+#[test]
+fn test_exit_code_aborted_by_sigint() {
+    use nix::sys::signal::{Signal, kill};
+    use nix::unistd::Pid;
+    use std::os::unix::fs::PermissionsExt;
+    use std::process::Child;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    /// 等到條件成立，逾時則終止程式並讓測試失敗
+    fn wait_for(
+        child: &mut Child,
+        timeout: Duration,
+        message: &str,
+        mut done: impl FnMut(&mut Child) -> bool,
+    ) {
+        let deadline = Instant::now() + timeout;
+        while !done(child) {
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                panic!("{message}");
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    let cwd = TempDir::new().unwrap();
+    let input = cwd.path().join("videos");
+    fs::create_dir(&input).unwrap();
+    for i in 0..64 {
+        fs::write(input.join(format!("{i:02}.mp4")), b"video").unwrap();
+    }
+
+    // 假 ffprobe：回報版本；讀取影片時留下標記後等待
+    let bin = cwd.path().join("bin");
+    fs::create_dir(&bin).unwrap();
+    let probing = cwd.path().join("probing");
+    let ffprobe = bin.join("ffprobe");
+    fs::write(
+        &ffprobe,
+        format!(
+            "#!/bin/sh\n\
+             if [ \"$1\" = \"-version\" ]; then echo \"ffprobe version 6.0\"; exit 0; fi\n\
+             touch \"{}\"\n\
+             sleep 1\n",
+            probing.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&ffprobe, fs::Permissions::from_mode(0o755)).unwrap();
+    let path = std::env::join_paths(std::iter::once(bin).chain(std::env::split_paths(
+        &std::env::var_os("PATH").unwrap_or_default(),
+    )))
+    .unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_auto_video_organize"))
+        .args(["rename", "-i", input.to_str().unwrap(), "-y", "--json"])
+        .current_dir(cwd.path())
+        .env("XDG_DATA_HOME", cwd.path())
+        .env("XDG_CONFIG_HOME", cwd.path())
+        .env("PATH", path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("無法執行程式");
+
+    // 等到開始讀取影片資訊（中斷處理器已設定）後再送出訊號
+    wait_for(
+        &mut child,
+        Duration::from_secs(30),
+        "程式未開始處理檔案",
+        |_| probing.exists(),
+    );
+    let pid = Pid::from_raw(i32::try_from(child.id()).unwrap());
+    kill(pid, Signal::SIGINT).unwrap();
+
+    let mut status = None;
+    wait_for(
+        &mut child,
+        Duration::from_secs(30),
+        "程式收到訊號後未結束",
+        |child| {
+            status = child.try_wait().expect("無法等待程式結束");
+            status.is_some()
+        },
+    );
+    assert_eq!(status.and_then(|status| status.code()), Some(3));
+}
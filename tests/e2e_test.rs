@@ -6,18 +6,23 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
+use std::time::{Duration, UNIX_EPOCH};
 
-use auto_video_organize::component::auto_move_by_type::FileCategorizer;
+use auto_video_organize::component::auto_move_by_type::{
+    DateOrganizer, FileCategorizer, undo_last_move,
+};
 use auto_video_organize::component::contact_sheet_generator::{
-    DEFAULT_GRID_COLS, DEFAULT_GRID_ROWS, DEFAULT_THUMBNAIL_COUNT, create_contact_sheet,
-    create_thumbnail_tasks, detect_scenes, extract_thumbnails_parallel, select_timestamps,
+    DEFAULT_GRID_COLS, DEFAULT_GRID_ROWS, DEFAULT_THUMBNAIL_COUNT, SheetEncoding,
+    create_contact_sheet, create_thumbnail_tasks, detect_scenes, extract_thumbnails_parallel,
+    select_timestamps,
 };
 use auto_video_organize::component::duplication_checker::DuplicationDetector;
 use auto_video_organize::component::orphan_file_mover::FileGrouper;
 use auto_video_organize::component::video_renamer::{FilenameCleaner, VideoSorter};
-use auto_video_organize::config::{Config, FileCategory};
+use auto_video_organize::config::{Config, DuplicateAction, FileCategory};
 use auto_video_organize::tools::{
-    ensure_directory_exists, get_video_info, scan_all_files, scan_video_files,
+    ExcludeFilter, TransferMode, ensure_directory_exists, get_video_info, scan_all_files,
+    scan_video_files,
 };
 
 /// 測試 Duplication Checker 功能
@@ -44,8 +49,14 @@ fn test_duplication_checker_e2e() {
 
     // 執行第一次掃描 - 註冊所有檔案
     let shutdown_signal = Arc::new(AtomicBool::new(false));
-    let mut detector =
-        DuplicationDetector::new(&hash_table_path, test_dir, Arc::clone(&shutdown_signal)).unwrap();
+    let mut detector = DuplicationDetector::new(
+        &hash_table_path,
+        test_dir,
+        DuplicateAction::Move,
+        false,
+        Arc::clone(&shutdown_signal),
+    )
+    .unwrap();
 
     let result1 = detector.detect_and_move_duplicates(test_dir).unwrap();
 
@@ -58,6 +69,9 @@ fn test_duplication_checker_e2e() {
     assert_eq!(result1.duplicates_found, 0, "第一次掃描不應該有重複");
     assert_eq!(result1.new_files_registered, 3, "應該註冊 3 個新檔案");
 
+    // 釋放 hash table 鎖定，讓下一次掃描可以使用
+    drop(detector);
+
     // 現在新增重複檔案
     let file1_dup = test_dir.join("file1_dup.txt");
     let file2_dup = test_dir.join("file2_dup.txt");
@@ -68,8 +82,14 @@ fn test_duplication_checker_e2e() {
     // 注意：第二次掃描會掃描到 5 個檔案（3 原始 + 2 新增）
     // 其中原始 3 個已在 hash table 中，會被識別為重複
     // 新增的 2 個因為內容相同也會被識別為重複
-    let mut detector2 =
-        DuplicationDetector::new(&hash_table_path, test_dir, shutdown_signal).unwrap();
+    let mut detector2 = DuplicationDetector::new(
+        &hash_table_path,
+        test_dir,
+        DuplicateAction::Move,
+        false,
+        shutdown_signal,
+    )
+    .unwrap();
     let result2 = detector2.detect_and_move_duplicates(test_dir).unwrap();
 
     println!("\n第二次掃描結果:");
@@ -175,6 +195,7 @@ fn test_contact_sheet_stages_e2e() {
         &output_path,
         DEFAULT_GRID_COLS,
         DEFAULT_GRID_ROWS,
+        SheetEncoding::default(),
     )
     .unwrap();
 
@@ -201,7 +222,7 @@ fn test_scan_all_files_e2e() {
         return;
     }
 
-    let files = scan_all_files(input_dir).unwrap();
+    let files = scan_all_files(input_dir, &ExcludeFilter::default(), None).unwrap();
 
     println!("掃描到 {} 個檔案", files.len());
 
@@ -305,7 +326,7 @@ fn test_auto_move_by_type_e2e() {
     // 移動檔案
     println!("\n=== 移動檔案到分類資料夾 ===");
     let result = categorizer
-        .move_files_to_categories(&files, test_dir)
+        .move_files_to_categories(&files, test_dir, TransferMode::Move)
         .unwrap();
 
     println!("  移動: {} 個", result.files_moved);
@@ -356,6 +377,81 @@ fn test_auto_move_by_type_e2e() {
     println!("\n✓ 自動依類型整理檔案 E2E 測試通過");
 }
 
+/// 測試依日期整理功能
+#[test]
+fn test_auto_move_by_date_e2e() {
+    let test_dir = Path::new("/tmp/e2e_test/auto_move_date_test");
+
+    if test_dir.exists() {
+        fs::remove_dir_all(test_dir).unwrap();
+    }
+    fs::create_dir_all(test_dir.join("sub")).unwrap();
+
+    // 建立不同修改時間的測試檔案（UNIX 秒，UTC）
+    let set_mtime = |path: &Path, secs: u64| {
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(UNIX_EPOCH + Duration::from_secs(secs))
+            .unwrap();
+    };
+    let july_2023 = 1_689_400_000; // 2023-07-15
+    let january_2024 = 1_705_300_000; // 2024-01-15
+
+    fs::write(test_dir.join("a.txt"), "july").unwrap();
+    set_mtime(&test_dir.join("a.txt"), july_2023);
+    fs::write(test_dir.join("b.jpg"), "january").unwrap();
+    set_mtime(&test_dir.join("b.jpg"), january_2024);
+    // 不同資料夾的同名檔案，內容大小不同，應加上編號
+    fs::write(test_dir.join("sub/a.txt"), "another july").unwrap();
+    set_mtime(&test_dir.join("sub/a.txt"), july_2023);
+
+    let config = Config::new().expect("無法載入設定");
+    let shutdown_signal = Arc::new(AtomicBool::new(false));
+    let organizer = DateOrganizer::new(config.file_type_table, shutdown_signal)
+        .with_pattern("{year}/{month}")
+        .with_media_time(false);
+
+    let files = organizer.scan_and_date(test_dir).unwrap();
+    assert_eq!(files.len(), 3, "應該有 3 個檔案");
+
+    let result = organizer
+        .move_files_to_date_folders(&files, test_dir, TransferMode::Move)
+        .unwrap();
+    assert_eq!(result.files_moved, 3, "應該移動 3 個檔案");
+    assert_eq!(result.errors, 0, "不應該有錯誤");
+    assert_eq!(result.folder_counts[Path::new("2023/07")], 2);
+    assert_eq!(result.folder_counts[Path::new("2024/01")], 1);
+
+    assert!(test_dir.join("2024/01/b.jpg").exists());
+    assert!(test_dir.join("2023/07/a.txt").exists());
+    assert!(test_dir.join("2023/07/a_1.txt").exists());
+    let mut contents = [
+        fs::read_to_string(test_dir.join("2023/07/a.txt")).unwrap(),
+        fs::read_to_string(test_dir.join("2023/07/a_1.txt")).unwrap(),
+    ];
+    contents.sort();
+    assert_eq!(contents, ["another july", "july"]);
+
+    // 再次整理時已在日期資料夾中的檔案不列入
+    let files = organizer.scan_and_date(test_dir).unwrap();
+    assert!(files.is_empty(), "已整理的檔案不應再列入");
+
+    // 復原後檔案回到原位置，日期資料夾一併移除
+    assert!(result.undo_log_written, "應該寫入復原紀錄");
+    let undo = undo_last_move(test_dir).unwrap();
+    assert_eq!(undo.restored, 3, "應該移回 3 個檔案");
+    assert_eq!(undo.errors, 0, "復原不應該有錯誤");
+    assert_eq!(fs::read_to_string(test_dir.join("a.txt")).unwrap(), "july");
+    assert_eq!(
+        fs::read_to_string(test_dir.join("sub/a.txt")).unwrap(),
+        "another july"
+    );
+    assert!(test_dir.join("b.jpg").exists());
+    assert!(!test_dir.join("2023").exists());
+    assert!(!test_dir.join("2024").exists());
+
+    println!("\n✓ 依日期整理檔案 E2E 測試通過");
+}
+
 /// 測試孤立檔案移動功能
 #[test]
 fn test_orphan_file_mover_e2e() {
@@ -397,12 +493,12 @@ fn test_orphan_file_mover_e2e() {
     );
 
     // 取得孤立檔案列表
-    let orphan_files = FileGrouper::get_orphan_files(&groups);
+    let orphan_files = grouper.get_orphan_files(&groups);
     println!("孤立檔案: {} 個", orphan_files.len());
     assert_eq!(orphan_files.len(), 3, "應該有 3 個孤立檔案");
 
     // 取得有對應的群組
-    let paired_groups = FileGrouper::get_paired_groups(&groups);
+    let paired_groups = grouper.get_paired_groups(&groups);
     println!("有對應的群組: {} 個", paired_groups.len());
     assert_eq!(paired_groups.len(), 2, "應該有 2 個有對應的群組");
 
@@ -525,7 +621,15 @@ fn test_video_renamer_e2e() {
 
     // 掃描影片
     println!("\n掃描影片檔案...");
-    let videos = scan_video_files(test_dir, &config.file_type_table).expect("掃描失敗");
+    let videos = scan_video_files(
+        test_dir,
+        &config.file_type_table,
+        &ExcludeFilter::default(),
+        None,
+        false,
+        &shutdown_signal,
+    )
+    .expect("掃描失敗");
     assert_eq!(videos.len(), 5, "應該有 5 個影片");
     println!("  找到 {} 個影片", videos.len());
 
@@ -544,18 +648,14 @@ fn test_video_renamer_e2e() {
     let expected_order = [3.0, 5.0, 8.0, 10.0, 15.0];
     for (i, video) in sorted_videos.iter().enumerate() {
         let filename = video.path.file_name().unwrap().to_string_lossy();
-        println!(
-            "  {}. [{:.0}秒] {}",
-            i + 1,
-            video.duration_seconds,
-            filename
-        );
+        let duration_seconds = video.duration_seconds.expect("應該有影片時長");
+        println!("  {}. [{:.0}秒] {}", i + 1, duration_seconds, filename);
         assert!(
-            (video.duration_seconds - expected_order[i]).abs() < 1.0,
+            (duration_seconds - expected_order[i]).abs() < 1.0,
             "第 {} 個影片時長應該約為 {} 秒，實際為 {} 秒",
             i + 1,
             expected_order[i],
-            video.duration_seconds
+            duration_seconds
         );
     }
 
@@ -613,7 +713,7 @@ fn test_video_renamer_e2e() {
         let filename = video.path.file_name().unwrap().to_string_lossy();
         let cleaned = cleaner.clean(&filename);
         let new_uuid = Uuid::new_v4().to_string();
-        let new_name = cleaner.format_new_filename(i + 1, &cleaned, &new_uuid);
+        let new_name = cleaner.format_new_filename(i + 1, 0, &cleaned, Some(&new_uuid));
 
         println!("原始: {}", filename);
         println!("新名: {}", new_name);
@@ -633,7 +733,7 @@ fn test_video_renamer_e2e() {
         let filename = video.path.file_name().unwrap().to_string_lossy();
         let cleaned = cleaner.clean(&filename);
         let new_uuid = Uuid::new_v4().to_string();
-        let new_name = cleaner.format_new_filename(i + 1, &cleaned, &new_uuid);
+        let new_name = cleaner.format_new_filename(i + 1, 0, &cleaned, Some(&new_uuid));
         let new_path = video.path.parent().unwrap().join(&new_name);
 
         fs::rename(&video.path, &new_path).expect("重新命名失敗");
@@ -13,8 +13,10 @@ use auto_video_organize::component::contact_sheet_generator::{
 };
 use auto_video_organize::component::duplication_checker::DuplicationDetector;
 use auto_video_organize::component::orphan_file_mover::FileGrouper;
-use auto_video_organize::config::{Config, FileCategory};
-use auto_video_organize::tools::{ensure_directory_exists, get_video_info, scan_all_files};
+use auto_video_organize::config::{Config, DuplicateAction, FileCategory};
+use auto_video_organize::tools::{
+    ExcludeFilter, ensure_directory_exists, get_video_info, scan_all_files,
+};
 
 const TEST_INPUT_DIR: &str = "/tmp/video_organize_test/input";
 const TEST_OUTPUT_DIR: &str = "/tmp/video_organize_test/output";
@@ -28,7 +30,7 @@ fn test_file_scanning() {
         return;
     }
 
-    let files = scan_all_files(input_dir).unwrap();
+    let files = scan_all_files(input_dir, &ExcludeFilter::default(), None).unwrap();
     println!("掃描到 {} 個檔案", files.len());
 
     // 應該至少有我們建立的測試檔案
@@ -245,8 +247,8 @@ fn test_file_grouping() {
 
     let groups = grouper.scan_and_group(input_dir).unwrap();
 
-    let orphan_files = FileGrouper::get_orphan_files(&groups);
-    let paired_groups = FileGrouper::get_paired_groups(&groups);
+    let orphan_files = grouper.get_orphan_files(&groups);
+    let paired_groups = grouper.get_paired_groups(&groups);
 
     println!("分組結果:");
     println!("  總群組數: {}", groups.len());
@@ -257,7 +259,7 @@ fn test_file_grouping() {
     let paired_video_group = groups.iter().find(|g| g.stem == "paired_video");
     assert!(paired_video_group.is_some(), "應該找到 paired_video 群組");
     assert!(
-        !paired_video_group.unwrap().is_orphan(),
+        !paired_video_group.unwrap().is_orphan(2),
         "paired_video 群組不應該是孤立的"
     );
 
@@ -265,7 +267,7 @@ fn test_file_grouping() {
     let lonely_group = groups.iter().find(|g| g.stem == "lonely_file");
     assert!(lonely_group.is_some(), "應該找到 lonely_file 群組");
     assert!(
-        lonely_group.unwrap().is_orphan(),
+        lonely_group.unwrap().is_orphan(2),
         "lonely_file 應該是孤立的"
     );
 
@@ -292,8 +294,14 @@ fn test_duplication_detection() {
     let shutdown_signal = Arc::new(AtomicBool::new(false));
 
     // 第一次掃描 - 註冊檔案
-    let mut detector =
-        DuplicationDetector::new(&hash_table_path, test_dir, Arc::clone(&shutdown_signal)).unwrap();
+    let mut detector = DuplicationDetector::new(
+        &hash_table_path,
+        test_dir,
+        DuplicateAction::Move,
+        false,
+        Arc::clone(&shutdown_signal),
+    )
+    .unwrap();
     let result1 = detector.detect_and_move_duplicates(test_dir).unwrap();
 
     println!("第一次掃描:");
@@ -303,6 +311,9 @@ fn test_duplication_detection() {
     assert_eq!(result1.new_files_registered, 2, "應該註冊 2 個新檔案");
     assert_eq!(result1.duplicates_found, 0, "第一次不應該有重複");
 
+    // 釋放 hash table 鎖定，讓下一次掃描可以使用
+    drop(detector);
+
     // 新增重複檔案
     fs::write(
         test_dir.join("original_copy.txt"),
@@ -311,8 +322,14 @@ fn test_duplication_detection() {
     .unwrap();
 
     // 第二次掃描 - 應該偵測到重複
-    let mut detector2 =
-        DuplicationDetector::new(&hash_table_path, test_dir, shutdown_signal).unwrap();
+    let mut detector2 = DuplicationDetector::new(
+        &hash_table_path,
+        test_dir,
+        DuplicateAction::Move,
+        false,
+        shutdown_signal,
+    )
+    .unwrap();
     let result2 = detector2.detect_and_move_duplicates(test_dir).unwrap();
 
     println!("第二次掃描:");